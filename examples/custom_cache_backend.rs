@@ -0,0 +1,87 @@
+//! Attaching a custom `CacheBackend`
+//!
+//! Implements a tiny HashMap-backed cache that logs every access, then plugs it
+//! into `MarketClient::with_cache`. The same approach works for any store
+//! (disk, shared memory, a remote KV service, ...).
+//!
+//! Run with: `cargo run --example custom_cache_backend`
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tradergrader::{CacheBackend, CacheKey, CacheStats, MarketClient, Result};
+
+/// A logging, unbounded cache suitable for experiments
+#[derive(Debug, Default)]
+struct LoggingCache {
+    entries: Mutex<HashMap<String, Vec<u8>>>,
+    stats: Mutex<CacheStats>,
+}
+
+#[async_trait]
+impl CacheBackend for LoggingCache {
+    async fn get_bytes(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let value = self.entries.lock().unwrap().get(key).cloned();
+        let mut stats = self.stats.lock().unwrap();
+        if value.is_some() {
+            stats.hits += 1;
+            println!("[cache] hit  {key}");
+        } else {
+            stats.misses += 1;
+            println!("[cache] miss {key}");
+        }
+        Ok(value)
+    }
+
+    async fn set_bytes(&self, key: &str, data: Vec<u8>, ttl: Duration) -> Result<()> {
+        println!("[cache] set  {key} ({} bytes, ttl {:?})", data.len(), ttl);
+        self.entries.lock().unwrap().insert(key.to_string(), data);
+        Ok(())
+    }
+
+    async fn remove(&self, key: &CacheKey) -> Result<()> {
+        self.entries.lock().unwrap().remove(&key.to_string());
+        Ok(())
+    }
+
+    async fn clear(&self) -> Result<()> {
+        self.entries.lock().unwrap().clear();
+        Ok(())
+    }
+
+    async fn stats(&self) -> Result<CacheStats> {
+        let mut stats = self.stats.lock().unwrap().clone();
+        stats.item_count = self.entries.lock().unwrap().len() as u64;
+        let total = stats.hits + stats.misses;
+        if total > 0 {
+            stats.hit_ratio = stats.hits as f64 / total as f64;
+        }
+        stats.backend_info = "logging-hashmap".to_string();
+        Ok(stats)
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let cache = Arc::new(LoggingCache::default());
+    let client = MarketClient::with_cache(cache.clone());
+
+    // The second call is served from our custom cache
+    for _ in 0..2 {
+        let summary = client.get_market_summary(10000002, 34).await?;
+        println!("{summary}\n");
+    }
+
+    let stats = cache.stats().await?;
+    println!(
+        "hits: {}, misses: {}, items: {}, hit ratio: {:.2}",
+        stats.hits, stats.misses, stats.item_count, stats.hit_ratio
+    );
+
+    Ok(())
+}
@@ -0,0 +1,58 @@
+//! Running a custom scan with the analysis engine
+//!
+//! Fetches history for a handful of items and ranks them by weekly change using
+//! `MarketClient::analyze_history`, the same calculations the MCP tools use.
+//!
+//! Run with: `cargo run --example custom_scan`
+
+use tradergrader::{MarketClient, PriceAnalysis};
+
+/// Minerals worth keeping an eye on in The Forge
+const WATCHED_TYPES: [(i32, &str); 5] = [
+    (34, "Tritanium"),
+    (35, "Pyerite"),
+    (36, "Mexallon"),
+    (37, "Isogen"),
+    (38, "Nocxium"),
+];
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let client = MarketClient::new();
+    let region_id = 10000002;
+
+    let mut results: Vec<(&str, PriceAnalysis)> = Vec::new();
+    for (type_id, name) in WATCHED_TYPES {
+        let history = match client.fetch_market_history(region_id, type_id).await {
+            Ok(history) => history,
+            Err(e) => {
+                eprintln!("Skipping {name}: {e}");
+                continue;
+            }
+        };
+
+        // Custom filtering before analysis: ignore thin days entirely
+        let history: Vec<_> = history.into_iter().filter(|day| day.volume > 0).collect();
+
+        match MarketClient::analyze_history(history) {
+            Ok(analysis) => results.push((name, analysis)),
+            Err(e) => eprintln!("Skipping {name}: {e}"),
+        }
+    }
+
+    results.sort_by(|a, b| {
+        b.1.week_change_percent
+            .partial_cmp(&a.1.week_change_percent)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    println!("{:<12} {:>12} {:>10} {:>16}", "Item", "Price", "Week %", "Trend");
+    for (name, analysis) in results {
+        println!(
+            "{:<12} {:>12.2} {:>+10.2} {:>16}",
+            name, analysis.current_price, analysis.week_change_percent, analysis.trend
+        );
+    }
+
+    Ok(())
+}
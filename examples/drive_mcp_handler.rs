@@ -0,0 +1,56 @@
+//! Driving `McpHandler` programmatically
+//!
+//! Sends JSON-RPC messages straight to the handler without stdio, which is how
+//! you would host TraderGrader inside another transport (HTTP, websockets, a
+//! test harness) or script an agent scenario.
+//!
+//! Run with: `cargo run --example drive_mcp_handler`
+
+use serde_json::json;
+use std::time::Duration;
+use tradergrader::{CacheConfig, MarketClient, McpHandler};
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let client = MarketClient::with_cache_config(CacheConfig::in_memory(
+        500,
+        Duration::from_secs(600),
+    ))?;
+    let handler = McpHandler::with_market_client(
+        "TraderGrader".to_string(),
+        "0.1.0".to_string(),
+        client,
+    );
+
+    // A typical agent session: handshake, discover tools, then call one
+    let session = [
+        json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {}}),
+        json!({"jsonrpc": "2.0", "method": "notifications/initialized"}),
+        json!({"jsonrpc": "2.0", "id": 2, "method": "tools/list"}),
+        json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "method": "tools/call",
+            "params": {
+                "name": "get_price_analysis",
+                "arguments": {"region_id": 10000002, "type_id": 44992}
+            }
+        }),
+    ];
+
+    for message in session {
+        let response = handler.handle_message(message).await;
+        if response.is_null() {
+            continue; // notifications have no response
+        }
+
+        if let Some(tools) = response["result"]["tools"].as_array() {
+            let names: Vec<&str> = tools.iter().filter_map(|t| t["name"].as_str()).collect();
+            println!("Available tools: {}", names.join(", "));
+        } else {
+            println!("{}", serde_json::to_string_pretty(&response)?);
+        }
+    }
+
+    Ok(())
+}
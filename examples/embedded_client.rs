@@ -0,0 +1,54 @@
+//! Embedding `MarketClient` in another service
+//!
+//! Shows how a host application can own a single shared `MarketClient` and
+//! use it from several concurrent tasks, e.g. behind a web handler or a bot.
+//!
+//! Run with: `cargo run --example embedded_client`
+
+use std::sync::Arc;
+use std::time::Duration;
+use tradergrader::{CacheConfig, MarketClient, RateLimitConfig};
+
+/// A minimal "service" that answers price questions for its own users
+struct PriceService {
+    market: Arc<MarketClient>,
+    region_id: i32,
+}
+
+impl PriceService {
+    fn new(market: Arc<MarketClient>, region_id: i32) -> Self {
+        Self { market, region_id }
+    }
+
+    /// Answer a quote request with the current best buy/sell for an item
+    async fn quote(&self, type_id: i32) -> tradergrader::Result<String> {
+        self.market.get_market_summary(self.region_id, type_id).await
+    }
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    // Conservative settings are a good default for a long-running host process
+    let market = Arc::new(MarketClient::with_configs(
+        CacheConfig::in_memory(5_000, Duration::from_secs(900)),
+        RateLimitConfig::conservative(),
+    )?);
+
+    let service = Arc::new(PriceService::new(market, 10000002)); // The Forge
+
+    // Several callers share the same client, cache and rate limiter
+    let mut tasks = Vec::new();
+    for type_id in [34, 35, 36] {
+        let service = Arc::clone(&service);
+        tasks.push(tokio::spawn(async move { (type_id, service.quote(type_id).await) }));
+    }
+
+    for task in tasks {
+        match task.await? {
+            (_, Ok(summary)) => println!("{summary}\n"),
+            (type_id, Err(e)) => eprintln!("Quote for type {type_id} failed: {e}"),
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,350 @@
+//! Price alerts for TraderGrader
+//!
+//! Traders want to hear when PLEX drops under 3.2M in The Forge without asking
+//! every few minutes. A [`PriceAlert`] is a threshold rule on the best sell or
+//! buy price of an item in a region, and an [`AlertMonitor`] polls the order
+//! books behind its rules in the background, through the client's cache and
+//! rate limiter. When a rule's condition becomes true it queues an MCP
+//! `notifications/message` at the `alert` level; the rule then stays quiet
+//! until the price moves back across the threshold, so a price sitting below
+//! it doesn't repeat the notification on every poll.
+
+use crate::error::{Result, TraderGraderError};
+use crate::logging::{LogLevel, McpLogger};
+use crate::market::MarketClient;
+use crate::orderbook::BookSide;
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// Most alerts one server keeps
+pub const MAX_PRICE_ALERTS: usize = 100;
+
+/// Default time between polls; order data is cached by ESI for five minutes
+pub const DEFAULT_ALERT_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Which way the price must cross the threshold
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertCondition {
+    /// Triggers when the price drops under the threshold
+    Below,
+    /// Triggers when the price rises over the threshold
+    Above,
+}
+
+impl AlertCondition {
+    fn holds(self, price: f64, threshold: f64) -> bool {
+        match self {
+            Self::Below => price < threshold,
+            Self::Above => price > threshold,
+        }
+    }
+}
+
+impl fmt::Display for AlertCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Below => "below",
+            Self::Above => "above",
+        })
+    }
+}
+
+impl FromStr for AlertCondition {
+    type Err = TraderGraderError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "below" | "<" => Ok(Self::Below),
+            "above" | ">" => Ok(Self::Above),
+            other => Err(TraderGraderError::InvalidArgument {
+                field: "condition".to_string(),
+                reason: format!("expected 'below' or 'above', got '{other}'"),
+            }),
+        }
+    }
+}
+
+/// A threshold rule on an item's best price in a region
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriceAlert {
+    pub id: u64,
+    pub region_id: i32,
+    pub type_id: i32,
+    /// `Ask` watches the lowest sell order, `Bid` the highest buy order
+    pub side: BookSide,
+    pub condition: AlertCondition,
+    pub threshold: f64,
+    pub created_at: DateTime<Utc>,
+    /// Best price seen on the last poll
+    pub last_price: Option<f64>,
+    /// When the rule last fired
+    pub last_triggered: Option<DateTime<Utc>>,
+    /// Whether the condition held on the last poll; the rule fires again only after it stops holding
+    triggered: bool,
+}
+
+impl PriceAlert {
+    /// Describes the rule, e.g. `sell price of type 44992 in region 10000002 below 3200000.00 ISK`
+    pub fn describe(&self) -> String {
+        format!(
+            "{} price of type {} in region {} {} {:.2} ISK",
+            side_name(self.side),
+            self.type_id,
+            self.region_id,
+            self.condition,
+            self.threshold
+        )
+    }
+}
+
+fn side_name(side: BookSide) -> &'static str {
+    match side {
+        BookSide::Ask => "sell",
+        BookSide::Bid => "buy",
+    }
+}
+
+/// Parses `sell` or `buy` into the order book side an alert watches
+pub fn parse_alert_side(side: &str) -> Result<BookSide> {
+    match side.trim().to_ascii_lowercase().as_str() {
+        "sell" => Ok(BookSide::Ask),
+        "buy" => Ok(BookSide::Bid),
+        other => Err(TraderGraderError::InvalidArgument {
+            field: "side".to_string(),
+            reason: format!("expected 'sell' or 'buy', got '{other}'"),
+        }),
+    }
+}
+
+#[derive(Debug, Default)]
+struct AlertBook {
+    alerts: Vec<PriceAlert>,
+    next_id: u64,
+}
+
+/// Holds price alerts and the background task that checks them
+///
+/// The poller starts with the first alert and is stopped when the monitor is dropped.
+#[derive(Debug)]
+pub struct AlertMonitor {
+    book: Arc<Mutex<AlertBook>>,
+    poll_interval: Duration,
+    poller: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl Default for AlertMonitor {
+    fn default() -> Self {
+        Self::new(DEFAULT_ALERT_POLL_INTERVAL)
+    }
+}
+
+impl AlertMonitor {
+    /// Create an empty monitor that polls every `poll_interval`
+    pub fn new(poll_interval: Duration) -> Self {
+        Self {
+            book: Arc::default(),
+            poll_interval,
+            poller: Mutex::new(None),
+        }
+    }
+
+    /// Adds a rule, returning it with its new ID
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::alerts::{AlertCondition, AlertMonitor};
+    /// use tradergrader::orderbook::BookSide;
+    ///
+    /// let monitor = AlertMonitor::default();
+    /// let alert = monitor.add(10000002, 44992, BookSide::Ask, AlertCondition::Below, 3_200_000.0)?;
+    /// assert_eq!(monitor.list(), vec![alert.clone()]);
+    /// assert!(monitor.remove(alert.id));
+    /// # Ok::<(), tradergrader::TraderGraderError>(())
+    /// ```
+    pub fn add(
+        &self,
+        region_id: i32,
+        type_id: i32,
+        side: BookSide,
+        condition: AlertCondition,
+        threshold: f64,
+    ) -> Result<PriceAlert> {
+        if !threshold.is_finite() || threshold <= 0.0 {
+            return Err(TraderGraderError::InvalidArgument {
+                field: "threshold".to_string(),
+                reason: "must be a positive ISK price".to_string(),
+            });
+        }
+        let mut book = self.book.lock().unwrap_or_else(|e| e.into_inner());
+        if book.alerts.len() >= MAX_PRICE_ALERTS {
+            return Err(TraderGraderError::InvalidArgument {
+                field: "alerts".to_string(),
+                reason: format!("at most {MAX_PRICE_ALERTS} alerts can be set; remove one first"),
+            });
+        }
+        book.next_id += 1;
+        let alert = PriceAlert {
+            id: book.next_id,
+            region_id,
+            type_id,
+            side,
+            condition,
+            threshold,
+            created_at: Utc::now(),
+            last_price: None,
+            last_triggered: None,
+            triggered: false,
+        };
+        book.alerts.push(alert.clone());
+        Ok(alert)
+    }
+
+    /// Every alert, oldest first
+    pub fn list(&self) -> Vec<PriceAlert> {
+        self.book.lock().unwrap_or_else(|e| e.into_inner()).alerts.clone()
+    }
+
+    /// Removes an alert, returning whether it existed
+    pub fn remove(&self, id: u64) -> bool {
+        let mut book = self.book.lock().unwrap_or_else(|e| e.into_inner());
+        let before = book.alerts.len();
+        book.alerts.retain(|alert| alert.id != id);
+        book.alerts.len() < before
+    }
+
+    /// Starts the background poller if it isn't running; must be called from within a Tokio runtime
+    pub fn ensure_polling(&self, client: Arc<MarketClient>, logger: McpLogger) {
+        let mut poller = self.poller.lock().unwrap_or_else(|e| e.into_inner());
+        if poller.as_ref().is_some_and(|task| !task.is_finished()) {
+            return;
+        }
+        let book = Arc::clone(&self.book);
+        let interval = self.poll_interval;
+        *poller = Some(tokio::spawn(async move {
+            loop {
+                check_alerts(&client, &book, &logger).await;
+                tokio::time::sleep(interval).await;
+            }
+        }));
+    }
+}
+
+impl Drop for AlertMonitor {
+    fn drop(&mut self) {
+        if let Some(task) = self.poller.get_mut().unwrap_or_else(|e| e.into_inner()).take() {
+            task.abort();
+        }
+    }
+}
+
+/// Polls each watched order book once and notifies for every rule that newly holds
+async fn check_alerts(client: &MarketClient, book: &Mutex<AlertBook>, logger: &McpLogger) {
+    let markets: BTreeSet<(i32, i32)> = book
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .alerts
+        .iter()
+        .map(|alert| (alert.region_id, alert.type_id))
+        .collect();
+
+    for (region_id, type_id) in markets {
+        let order_book = match client.fetch_order_book(region_id, Some(type_id)).await {
+            Ok(order_book) => order_book,
+            Err(e) => {
+                tracing::debug!("price alert check for type {type_id} in region {region_id} failed: {e}");
+                continue;
+            }
+        };
+        let prices = |side| match side {
+            BookSide::Ask => order_book.best_ask(),
+            BookSide::Bid => order_book.best_bid(),
+        };
+
+        let mut book = book.lock().unwrap_or_else(|e| e.into_inner());
+        let watching = book
+            .alerts
+            .iter_mut()
+            .filter(|alert| alert.region_id == region_id && alert.type_id == type_id);
+        for alert in watching {
+            let price = prices(alert.side);
+            if let Some(message) = evaluate(alert, price, Utc::now()) {
+                logger.log(
+                    LogLevel::Alert,
+                    "price_alert",
+                    json!({
+                        "event": "price_alert_triggered",
+                        "alert_id": alert.id,
+                        "region_id": alert.region_id,
+                        "type_id": alert.type_id,
+                        "side": side_name(alert.side),
+                        "condition": alert.condition.to_string(),
+                        "threshold": alert.threshold,
+                        "price": price,
+                        "message": message,
+                    }),
+                );
+            }
+        }
+    }
+}
+
+/// Records `price` on the alert, returning a message when the rule has just started to hold
+fn evaluate(alert: &mut PriceAlert, price: Option<f64>, now: DateTime<Utc>) -> Option<String> {
+    alert.last_price = price;
+    // No orders on the watched side: nothing to compare, keep the current state
+    let price = price?;
+    let holds = alert.condition.holds(price, alert.threshold);
+    let fire = holds && !alert.triggered;
+    alert.triggered = holds;
+    if !fire {
+        return None;
+    }
+    alert.last_triggered = Some(now);
+    Some(format!("Alert #{}: {} (now {price:.2} ISK)", alert.id, alert.describe()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alerts_fire_once_per_crossing() {
+        let monitor = AlertMonitor::default();
+        let mut alert = monitor.add(10000002, 44992, BookSide::Ask, AlertCondition::Below, 3_200_000.0).unwrap();
+        let now = Utc::now();
+
+        assert_eq!(evaluate(&mut alert, Some(3_300_000.0), now), None);
+        let message = evaluate(&mut alert, Some(3_150_000.0), now).unwrap();
+        assert!(message.contains("sell price of type 44992 in region 10000002 below 3200000.00 ISK"));
+        assert_eq!(alert.last_triggered, Some(now));
+
+        // Staying below is quiet, an empty book changes nothing, and recovering re-arms the rule
+        assert_eq!(evaluate(&mut alert, Some(3_100_000.0), now), None);
+        assert_eq!(evaluate(&mut alert, None, now), None);
+        assert_eq!(evaluate(&mut alert, Some(3_250_000.0), now), None);
+        assert!(evaluate(&mut alert, Some(3_199_999.0), now).is_some());
+    }
+
+    #[test]
+    fn test_add_and_remove() {
+        let monitor = AlertMonitor::default();
+        assert!(monitor.add(10000002, 34, BookSide::Bid, AlertCondition::Above, -1.0).is_err());
+        let first = monitor.add(10000002, 34, BookSide::Bid, AlertCondition::Above, 5.0).unwrap();
+        let second = monitor.add(10000043, 34, BookSide::Ask, AlertCondition::Below, 4.0).unwrap();
+        assert_ne!(first.id, second.id);
+
+        assert!(monitor.remove(first.id));
+        assert!(!monitor.remove(first.id));
+        assert_eq!(monitor.list(), vec![second]);
+
+        assert_eq!("<".parse::<AlertCondition>().unwrap(), AlertCondition::Below);
+        assert!(parse_alert_side("ask").is_err());
+    }
+}
@@ -0,0 +1,385 @@
+//! Price forecasting for TraderGrader
+//!
+//! Traders planning a restock or a haul want a rough idea of where a price is
+//! heading. This module fits two simple models to recent daily prices and
+//! extrapolates them: a least-squares line, which captures a steady drift, and
+//! additive Holt-Winters smoothing with a weekly season, which also follows the
+//! weekend rhythm of many EVE markets. Each forecast carries 80% and 95%
+//! prediction bands derived from the model's in-sample errors.
+//!
+//! These are statistical extrapolations of past prices, not predictions: they
+//! know nothing about patches, events or market manipulation, and the reports
+//! say so.
+
+use crate::error::{Result, TraderGraderError};
+use crate::market::MarketClient;
+use crate::returns;
+use crate::types::{ForecastModel, ForecastPoint, MarketHistory, ModelForecast, PriceForecast};
+use chrono::{Duration, NaiveDate};
+
+/// Longest forecast horizon, in days
+pub const MAX_FORECAST_DAYS: usize = 30;
+
+/// Days of recent history the models are fitted to
+pub const FORECAST_WINDOW_DAYS: usize = 90;
+
+/// Fewest days of history a forecast is made from
+pub const MIN_FORECAST_HISTORY: usize = 10;
+
+/// Length of the Holt-Winters season: EVE trading follows a weekly rhythm
+pub const SEASON_LENGTH: usize = 7;
+
+/// Standard normal quantiles for the 80% and 95% bands
+const Z_80: f64 = 1.2816;
+const Z_95: f64 = 1.96;
+
+/// Holt-Winters smoothing parameters tried when fitting, as (alpha, beta, gamma)
+const ALPHAS: [f64; 5] = [0.1, 0.3, 0.5, 0.7, 0.9];
+const BETAS: [f64; 4] = [0.01, 0.05, 0.1, 0.2];
+const GAMMAS: [f64; 3] = [0.05, 0.15, 0.3];
+
+impl PriceForecast {
+    /// Forecasts `horizon` days past the newest day of history in any order
+    ///
+    /// Fits to the last [`FORECAST_WINDOW_DAYS`] days. Holt-Winters needs two
+    /// full weeks and is left out with less.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::{MarketHistory, PriceForecast};
+    ///
+    /// // Rising 1 ISK a day
+    /// let history: Vec<MarketHistory> = (1..=30)
+    ///     .map(|day| MarketHistory {
+    ///         average: 100.0 + day as f64,
+    ///         date: format!("2025-06-{day:02}"),
+    ///         highest: 0.0,
+    ///         lowest: 0.0,
+    ///         order_count: 10,
+    ///         volume: 1_000,
+    ///         extra: Default::default(),
+    ///     })
+    ///     .collect();
+    ///
+    /// let forecast = PriceForecast::from_history(&history, 7)?;
+    /// let week_out = &forecast.linear.points[6];
+    /// assert_eq!(week_out.date, "2025-07-07");
+    /// assert!((week_out.expected - 137.0).abs() < 1e-6);
+    /// # Ok::<(), tradergrader::TraderGraderError>(())
+    /// ```
+    pub fn from_history(history: &[MarketHistory], horizon: usize) -> Result<Self> {
+        if horizon == 0 || horizon > MAX_FORECAST_DAYS {
+            return Err(TraderGraderError::InvalidArgument {
+                field: "horizon_days".to_string(),
+                reason: format!("must be between 1 and {MAX_FORECAST_DAYS}"),
+            });
+        }
+        let mut days: Vec<(NaiveDate, f64)> = history
+            .iter()
+            .filter_map(|h| NaiveDate::parse_from_str(&h.date, "%Y-%m-%d").ok().map(|date| (date, h.average)))
+            .filter(|(_, price)| *price > 0.0)
+            .collect();
+        days.sort_by_key(|(date, _)| *date);
+        days.drain(..days.len().saturating_sub(FORECAST_WINDOW_DAYS));
+        if days.len() < MIN_FORECAST_HISTORY {
+            return Err(format!("Need at least {MIN_FORECAST_HISTORY} days of history to forecast").into());
+        }
+
+        let prices: Vec<f64> = days.iter().map(|(_, price)| *price).collect();
+        let (last_date, last_price) = days[days.len() - 1];
+        let point = |days_ahead: usize, expected: f64, std_error: f64| ForecastPoint {
+            days_ahead,
+            date: (last_date + Duration::days(days_ahead as i64)).format("%Y-%m-%d").to_string(),
+            expected,
+            lower_80: (expected - Z_80 * std_error).max(0.0),
+            upper_80: expected + Z_80 * std_error,
+            lower_95: (expected - Z_95 * std_error).max(0.0),
+            upper_95: expected + Z_95 * std_error,
+        };
+
+        let line = LinearFit::new(&prices);
+        let linear = ModelForecast {
+            model: ForecastModel::LinearRegression,
+            points: (1..=horizon).map(|h| point(h, line.predict(h), line.prediction_error(h))).collect(),
+            residual_std_dev: line.residual_std_dev,
+        };
+
+        let holt_winters = HoltWinters::fit(&prices, SEASON_LENGTH).map(|fit| ModelForecast {
+            model: ForecastModel::HoltWinters,
+            points: (1..=horizon).map(|h| point(h, fit.predict(h), fit.prediction_error(h))).collect(),
+            residual_std_dev: fit.residual_std_dev,
+        });
+
+        Ok(Self {
+            last_date: last_date.format("%Y-%m-%d").to_string(),
+            last_price,
+            fitted_days: prices.len(),
+            linear,
+            holt_winters,
+        })
+    }
+}
+
+/// Least-squares line through prices at x = 0, 1, 2, ...
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinearFit {
+    pub intercept: f64,
+    /// ISK per day
+    pub slope: f64,
+    /// Standard error of the residuals
+    pub residual_std_dev: f64,
+    len: usize,
+    x_mean: f64,
+    x_variance_sum: f64,
+}
+
+impl LinearFit {
+    /// Fits a line to at least two prices
+    pub fn new(prices: &[f64]) -> Self {
+        let n = prices.len() as f64;
+        let x_mean = (n - 1.0) / 2.0;
+        let y_mean = returns::mean(prices).unwrap_or(0.0);
+        let (covariance, x_variance_sum) = prices.iter().enumerate().fold((0.0, 0.0), |(cov, var), (x, y)| {
+            let dx = x as f64 - x_mean;
+            (cov + dx * (y - y_mean), var + dx * dx)
+        });
+        let slope = if x_variance_sum > 0.0 { covariance / x_variance_sum } else { 0.0 };
+        let intercept = y_mean - slope * x_mean;
+        let squared_errors: f64 =
+            prices.iter().enumerate().map(|(x, y)| (y - (intercept + slope * x as f64)).powi(2)).sum();
+        let residual_std_dev = if prices.len() > 2 { (squared_errors / (n - 2.0)).sqrt() } else { 0.0 };
+        Self {
+            intercept,
+            slope,
+            residual_std_dev,
+            len: prices.len(),
+            x_mean,
+            x_variance_sum,
+        }
+    }
+
+    /// Expected price `days_ahead` days after the last price
+    pub fn predict(&self, days_ahead: usize) -> f64 {
+        self.intercept + self.slope * self.future_x(days_ahead)
+    }
+
+    /// Standard error of a new observation `days_ahead` days out, widening away from the data
+    fn prediction_error(&self, days_ahead: usize) -> f64 {
+        let n = self.len as f64;
+        let distance = (self.future_x(days_ahead) - self.x_mean).powi(2);
+        let leverage = if self.x_variance_sum > 0.0 { distance / self.x_variance_sum } else { 0.0 };
+        self.residual_std_dev * (1.0 + 1.0 / n + leverage).sqrt()
+    }
+
+    fn future_x(&self, days_ahead: usize) -> f64 {
+        (self.len - 1 + days_ahead) as f64
+    }
+}
+
+/// Additive Holt-Winters model with the smoothing parameters that fitted best
+#[derive(Debug, Clone, PartialEq)]
+pub struct HoltWinters {
+    pub alpha: f64,
+    pub beta: f64,
+    pub gamma: f64,
+    /// Standard deviation of the one-step-ahead errors
+    pub residual_std_dev: f64,
+    level: f64,
+    trend: f64,
+    /// Seasonal offsets, indexed by position in the season
+    seasonal: Vec<f64>,
+    /// Position in the season of the first forecast day
+    next_season_index: usize,
+}
+
+impl HoltWinters {
+    /// Fits to prices by grid search over the smoothing parameters
+    ///
+    /// Returns `None` with fewer than two full seasons of prices.
+    pub fn fit(prices: &[f64], season: usize) -> Option<Self> {
+        if season < 2 || prices.len() < 2 * season {
+            return None;
+        }
+        let mut best: Option<Self> = None;
+        for alpha in ALPHAS {
+            for beta in BETAS {
+                for gamma in GAMMAS {
+                    let fit = Self::run(prices, season, alpha, beta, gamma);
+                    if best.as_ref().is_none_or(|b| fit.residual_std_dev < b.residual_std_dev) {
+                        best = Some(fit);
+                    }
+                }
+            }
+        }
+        best
+    }
+
+    /// Smooths the whole series with fixed parameters
+    fn run(prices: &[f64], season: usize, alpha: f64, beta: f64, gamma: f64) -> Self {
+        let first = returns::mean(&prices[..season]).unwrap_or(0.0);
+        let second = returns::mean(&prices[season..2 * season]).unwrap_or(first);
+        let mut level = first;
+        let mut trend = (second - first) / season as f64;
+        let mut seasonal: Vec<f64> = prices[..season].iter().map(|p| p - first).collect();
+
+        let mut squared_errors = 0.0;
+        for (t, &price) in prices.iter().enumerate().skip(season) {
+            let index = t % season;
+            let error = price - (level + trend + seasonal[index]);
+            squared_errors += error * error;
+
+            let previous_level = level;
+            level = alpha * (price - seasonal[index]) + (1.0 - alpha) * (level + trend);
+            trend = beta * (level - previous_level) + (1.0 - beta) * trend;
+            seasonal[index] = gamma * (price - level) + (1.0 - gamma) * seasonal[index];
+        }
+        let steps = (prices.len() - season) as f64;
+
+        Self {
+            alpha,
+            beta,
+            gamma,
+            residual_std_dev: (squared_errors / steps).sqrt(),
+            level,
+            trend,
+            seasonal,
+            next_season_index: prices.len() % season,
+        }
+    }
+
+    /// Expected price `days_ahead` days after the last price
+    pub fn predict(&self, days_ahead: usize) -> f64 {
+        let index = (self.next_season_index + days_ahead - 1) % self.seasonal.len();
+        self.level + days_ahead as f64 * self.trend + self.seasonal[index]
+    }
+
+    /// Approximate standard error `days_ahead` days out, from the additive model's error propagation
+    fn prediction_error(&self, days_ahead: usize) -> f64 {
+        let season = self.seasonal.len();
+        let spread: f64 = (1..days_ahead)
+            .map(|j| {
+                let seasonal = if j % season == 0 { self.gamma } else { 0.0 };
+                (self.alpha * (1.0 + j as f64 * self.beta) + seasonal).powi(2)
+            })
+            .sum();
+        self.residual_std_dev * (1.0 + spread).sqrt()
+    }
+}
+
+impl MarketClient {
+    /// Forecasts an item's price `horizon` days ahead from its market history
+    pub async fn forecast_price(&self, region_id: i32, type_id: i32, horizon: usize) -> Result<PriceForecast> {
+        let history = self.fetch_market_history(region_id, type_id).await?;
+        PriceForecast::from_history(&history, horizon)
+    }
+
+    /// Generates a formatted price forecast report
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// println!("{}", client.get_price_forecast_summary(10000002, 44992, 30).await?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_price_forecast_summary(&self, region_id: i32, type_id: i32, horizon: usize) -> Result<String> {
+        let forecast = self.forecast_price(region_id, type_id, horizon).await?;
+        Ok(format_price_forecast(
+            &format!("Price Forecast for {} in Region {region_id}", self.type_label(type_id).await),
+            &forecast,
+        ))
+    }
+}
+
+/// Formats a forecast as a text report, showing every day of a week-long horizon or weekly steps of a longer one
+fn format_price_forecast(title: &str, forecast: &PriceForecast) -> String {
+    let horizon = forecast.linear.points.len();
+    let mut report = format!(
+        "{title} (next {horizon} days):\n\
+         ⚠️ Statistical extrapolation of past prices, not a prediction: it can't anticipate patches, events or manipulation.\n\
+         Fitted to {} days ending {} (last average {:.2} ISK)\n",
+        forecast.fitted_days, forecast.last_date, forecast.last_price
+    );
+    for model in [Some(&forecast.linear), forecast.holt_winters.as_ref()].into_iter().flatten() {
+        report.push_str(&format!("\n{} (residual σ {:.2} ISK):\n", model.model, model.residual_std_dev));
+        let shown = model
+            .points
+            .iter()
+            .filter(|p| horizon <= 7 || p.days_ahead == 1 || p.days_ahead % 7 == 0 || p.days_ahead == horizon);
+        for p in shown {
+            report.push_str(&format!(
+                "+{}d {}: {:.2} ISK (80%: {:.2} to {:.2}, 95%: {:.2} to {:.2})\n",
+                p.days_ahead, p.date, p.expected, p.lower_80, p.upper_80, p.lower_95, p.upper_95
+            ));
+        }
+    }
+    if forecast.holt_winters.is_none() {
+        report.push_str(&format!("\nHolt-Winters needs at least {} days of history.\n", 2 * SEASON_LENGTH));
+    }
+    report.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::daily_history;
+
+    fn history(prices: impl IntoIterator<Item = f64>) -> Vec<MarketHistory> {
+        daily_history("2025-03-03", prices.into_iter().map(|average| (average, 1)))
+    }
+
+    #[test]
+    fn test_linear_fit_recovers_trend() {
+        let fit = LinearFit::new(&[10.0, 12.0, 14.0, 16.0]);
+        assert!((fit.slope - 2.0).abs() < 1e-9);
+        assert!((fit.intercept - 10.0).abs() < 1e-9);
+        assert!((fit.predict(1) - 18.0).abs() < 1e-9);
+        assert_eq!(fit.residual_std_dev, 0.0);
+    }
+
+    #[test]
+    fn test_holt_winters_follows_weekly_season() {
+        // Weekends 10% dearer on a slow upward drift, over ten weeks
+        let seasonal = |day: usize| 100.0 + day as f64 * 0.2 + if day % 7 >= 5 { 10.0 } else { 0.0 };
+        let prices: Vec<f64> = (0..70).map(seasonal).collect();
+        let forecast = PriceForecast::from_history(&history(prices), 14).unwrap();
+
+        let holt_winters = forecast.holt_winters.unwrap();
+        for point in &holt_winters.points {
+            let actual = seasonal(69 + point.days_ahead);
+            assert!((point.expected - actual).abs() < 1.5, "day {}: {} vs {actual}", point.days_ahead, point.expected);
+        }
+        // The straight line can't follow the weekend bump
+        assert!(holt_winters.residual_std_dev < forecast.linear.residual_std_dev);
+    }
+
+    #[test]
+    fn test_bands_widen_and_stay_positive() {
+        let noisy: Vec<f64> = (0..40).map(|day| 10.0 + if day % 2 == 0 { 2.0 } else { -2.0 } - day as f64 * 0.1).collect();
+        let forecast = PriceForecast::from_history(&history(noisy), 30).unwrap();
+        for model in [&forecast.linear, forecast.holt_winters.as_ref().unwrap()] {
+            let (first, last) = (&model.points[0], &model.points[29]);
+            assert!(last.upper_95 - last.expected > first.upper_95 - first.expected);
+            assert!(model.points.iter().all(|p| p.lower_95 >= 0.0 && p.lower_95 <= p.lower_80));
+        }
+
+        let report = format_price_forecast("Forecast", &forecast);
+        assert!(report.contains("not a prediction"));
+        assert!(report.contains("+7d 2025-04-18"));
+        assert!(!report.contains("+2d"));
+    }
+
+    #[test]
+    fn test_short_history_and_bad_horizon() {
+        assert!(PriceForecast::from_history(&history((0..5).map(|d| d as f64 + 1.0)), 7).is_err());
+        assert!(PriceForecast::from_history(&history((0..30).map(|d| d as f64 + 1.0)), 31).is_err());
+
+        let forecast = PriceForecast::from_history(&history((0..12).map(|d| d as f64 + 1.0)), 7).unwrap();
+        assert!(forecast.holt_winters.is_none());
+        assert!(format_price_forecast("Forecast", &forecast).contains("Holt-Winters needs at least 14 days"));
+    }
+}
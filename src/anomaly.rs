@@ -0,0 +1,255 @@
+//! Price and volume anomaly detection for TraderGrader
+//!
+//! Manipulation, speculation around a rumoured change and patch-driven shifts
+//! all show up in daily history as days far outside the recent range. This
+//! compares each day's average price and traded volume against the mean and
+//! standard deviation of the days just before it, and flags the days more than
+//! a chosen number of standard deviations away.
+
+use crate::error::{Result, TraderGraderError};
+use crate::market::MarketClient;
+use crate::returns;
+use crate::types::{AnomalyMetric, AnomalyReport, MarketAnomaly, MarketHistory};
+
+/// Default number of days in the rolling baseline
+pub const DEFAULT_ANOMALY_WINDOW: usize = 30;
+
+/// Default standard deviations from the baseline that count as an anomaly
+pub const DEFAULT_ANOMALY_THRESHOLD: f64 = 3.0;
+
+/// Fewest baseline days a standard deviation is trusted from
+pub const MIN_ANOMALY_WINDOW: usize = 7;
+
+/// Most anomalies listed in a report, newest first
+const MAX_REPORTED_ANOMALIES: usize = 20;
+
+impl AnomalyReport {
+    /// Flags days in history (any order) whose price or volume is more than
+    /// `threshold` standard deviations from the previous `window` days
+    ///
+    /// Days whose baseline never moved are skipped, since any change from a
+    /// flat line would otherwise count as infinitely unusual.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::{AnomalyMetric, AnomalyReport, MarketHistory};
+    ///
+    /// // Quiet trading with a one-day volume spike at the end
+    /// let history: Vec<MarketHistory> = (1..=31)
+    ///     .map(|day| MarketHistory {
+    ///         average: if day % 2 == 0 { 100.0 } else { 101.0 },
+    ///         date: format!("2025-05-{day:02}"),
+    ///         highest: 0.0,
+    ///         lowest: 0.0,
+    ///         order_count: 10,
+    ///         volume: if day == 31 { 50_000 } else if day % 2 == 0 { 1_000 } else { 1_100 },
+    ///         extra: Default::default(),
+    ///     })
+    ///     .collect();
+    ///
+    /// let report = AnomalyReport::from_history(&history, 30, 3.0)?;
+    /// assert_eq!(report.anomalies.len(), 1);
+    /// assert_eq!(report.anomalies[0].metric, AnomalyMetric::Volume);
+    /// assert_eq!(report.anomalies[0].date, "2025-05-31");
+    /// # Ok::<(), tradergrader::TraderGraderError>(())
+    /// ```
+    pub fn from_history(history: &[MarketHistory], window: usize, threshold: f64) -> Result<Self> {
+        if window < MIN_ANOMALY_WINDOW {
+            return Err(TraderGraderError::InvalidArgument {
+                field: "window_days".to_string(),
+                reason: format!("must be at least {MIN_ANOMALY_WINDOW}"),
+            });
+        }
+        if threshold.is_nan() || threshold <= 0.0 {
+            return Err(TraderGraderError::InvalidArgument {
+                field: "std_devs".to_string(),
+                reason: "must be positive".to_string(),
+            });
+        }
+        let mut days: Vec<&MarketHistory> = history.iter().collect();
+        days.sort_by(|a, b| a.date.cmp(&b.date));
+        if days.len() <= window {
+            return Err(format!("Need more than {window} days of history to detect anomalies").into());
+        }
+
+        let prices: Vec<f64> = days.iter().map(|h| h.average).collect();
+        let volumes: Vec<f64> = days.iter().map(|h| h.volume as f64).collect();
+        let mut anomalies = Vec::new();
+        for t in window..days.len() {
+            for (metric, series) in [(AnomalyMetric::Price, &prices), (AnomalyMetric::Volume, &volumes)] {
+                let baseline = &series[t - window..t];
+                let (Some(mean), Some(std_dev)) = (returns::mean(baseline), returns::std_dev(baseline)) else {
+                    continue;
+                };
+                if std_dev <= 0.0 {
+                    continue;
+                }
+                let z_score = (series[t] - mean) / std_dev;
+                if z_score.abs() > threshold {
+                    anomalies.push(MarketAnomaly {
+                        date: days[t].date.clone(),
+                        metric,
+                        value: series[t],
+                        baseline_mean: mean,
+                        baseline_std_dev: std_dev,
+                        z_score,
+                    });
+                }
+            }
+        }
+
+        Ok(Self {
+            window_days: window,
+            threshold,
+            days_checked: days.len() - window,
+            anomalies,
+        })
+    }
+}
+
+impl MarketClient {
+    /// Finds days where an item's price or volume broke out of its recent range
+    pub async fn detect_anomalies(
+        &self,
+        region_id: i32,
+        type_id: i32,
+        window: usize,
+        threshold: f64,
+    ) -> Result<AnomalyReport> {
+        let history = self.fetch_market_history(region_id, type_id).await?;
+        AnomalyReport::from_history(&history, window, threshold)
+    }
+
+    /// Generates a formatted anomaly report
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// println!("{}", client.get_anomaly_summary(10000002, 44992, 30, 3.0).await?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_anomaly_summary(
+        &self,
+        region_id: i32,
+        type_id: i32,
+        window: usize,
+        threshold: f64,
+    ) -> Result<String> {
+        let report = self.detect_anomalies(region_id, type_id, window, threshold).await?;
+        Ok(format_anomaly_report(
+            &format!("Anomalies for {} in Region {region_id}", self.type_label(type_id).await),
+            &report,
+        ))
+    }
+}
+
+/// Formats anomalies newest first, noting days where price and volume broke out together
+fn format_anomaly_report(title: &str, report: &AnomalyReport) -> String {
+    let mut text = format!(
+        "{title}:\nChecked {} days against the previous {} days; flagging moves beyond {:.1}σ\n",
+        report.days_checked, report.window_days, report.threshold
+    );
+    if report.anomalies.is_empty() {
+        text.push_str("\nNo anomalies found.");
+        return text;
+    }
+
+    text.push_str(&format!("\n{} anomalies", report.anomalies.len()));
+    if report.anomalies.len() > MAX_REPORTED_ANOMALIES {
+        text.push_str(&format!(" (newest {MAX_REPORTED_ANOMALIES} shown)"));
+    }
+    text.push_str(":\n");
+    for anomaly in report.anomalies.iter().rev().take(MAX_REPORTED_ANOMALIES) {
+        let value = match anomaly.metric {
+            AnomalyMetric::Price => format!("{:.2} ISK vs {:.2} usual", anomaly.value, anomaly.baseline_mean),
+            AnomalyMetric::Volume => format!("{:.0} units vs {:.0} usual", anomaly.value, anomaly.baseline_mean),
+        };
+        let together = report
+            .anomalies
+            .iter()
+            .any(|other| other.date == anomaly.date && other.metric != anomaly.metric);
+        text.push_str(&format!(
+            "{} {} {} {:+.1}σ: {value}{}\n",
+            anomaly.date,
+            anomaly.metric,
+            if anomaly.z_score > 0.0 { "spike" } else { "drop" },
+            anomaly.z_score,
+            if together { " (price and volume together)" } else { "" }
+        ));
+    }
+    text.push_str(
+        "\nPrice moves on normal volume can mean a thin market being pushed; price and volume breaking out together \
+         usually means speculation or a real shift in demand, such as a patch.",
+    );
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::daily_history;
+
+    fn history(days: impl IntoIterator<Item = (f64, i64)>) -> Vec<MarketHistory> {
+        daily_history("2025-01-01", days)
+    }
+
+    /// Alternating quiet days, so the baseline has a small but non-zero spread
+    fn quiet(day: usize) -> (f64, i64) {
+        if day.is_multiple_of(2) { (100.0, 1_000) } else { (102.0, 1_200) }
+    }
+
+    #[test]
+    fn test_flags_price_and_volume_breakouts() {
+        let mut days: Vec<(f64, i64)> = (0..20).map(quiet).collect();
+        days.push((130.0, 9_000));
+        days.push((60.0, 1_100));
+        let report = AnomalyReport::from_history(&history(days), 10, 3.0).unwrap();
+
+        assert_eq!(report.days_checked, 12);
+        let found: Vec<(&str, AnomalyMetric, bool)> = report
+            .anomalies
+            .iter()
+            .map(|a| (a.date.as_str(), a.metric, a.z_score > 0.0))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("2025-01-21", AnomalyMetric::Price, true),
+                ("2025-01-21", AnomalyMetric::Volume, true),
+                ("2025-01-22", AnomalyMetric::Price, false),
+            ]
+        );
+
+        let text = format_anomaly_report("Anomalies", &report);
+        assert!(text.contains("2025-01-21 Volume spike"));
+        assert!(text.contains("(price and volume together)"));
+        assert!(text.contains("2025-01-22 Price drop"));
+    }
+
+    #[test]
+    fn test_threshold_and_flat_baselines() {
+        let mut days: Vec<(f64, i64)> = (0..10).map(quiet).collect();
+        days.push((104.0, 1_300));
+        // A modest move only counts at a low threshold
+        assert!(AnomalyReport::from_history(&history(days.clone()), 10, 3.0).unwrap().anomalies.is_empty());
+        assert_eq!(AnomalyReport::from_history(&history(days), 10, 1.5).unwrap().anomalies.len(), 2);
+
+        // Nothing to measure against when the baseline never moved
+        let mut flat: Vec<(f64, i64)> = vec![(50.0, 10); 10];
+        flat.push((500.0, 1_000));
+        assert!(AnomalyReport::from_history(&history(flat), 10, 3.0).unwrap().anomalies.is_empty());
+    }
+
+    #[test]
+    fn test_rejects_bad_arguments() {
+        let days = history((0..20).map(quiet));
+        assert!(AnomalyReport::from_history(&days, 5, 3.0).is_err());
+        assert!(AnomalyReport::from_history(&days, 10, 0.0).is_err());
+        assert!(AnomalyReport::from_history(&days, 20, 3.0).is_err());
+    }
+}
@@ -0,0 +1,481 @@
+//! Persistent market history archive for TraderGrader
+//!
+//! ESI only publishes about 13 months of daily history, so anything older is
+//! gone unless someone kept it. A [`HistoryArchive`] is a SQLite file that
+//! every history fetch appends to, and a [`HistoryArchiver`] fetches the
+//! watchlist's items in the background so their archive grows day by day even
+//! when nobody asks about them. Once the archive reaches back further than
+//! ESI, price analysis runs over the longer series and year-over-year
+//! comparisons can look several years back.
+//!
+//! The archive needs the opt-in `history-archive` feature, which compiles a
+//! bundled SQLite: build with `cargo build --release --features history-archive`.
+//! Without it, opening an archive fails and history stays within ESI's window.
+
+use crate::error::{Result, TraderGraderError};
+use crate::market::MarketClient;
+use crate::types::{MarketHistory, YearOverYear, YearWindow};
+use crate::watchlist::WatchlistStore;
+use chrono::{Duration as ChronoDuration, Months, NaiveDate};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// Default days compared in each year of a year-over-year comparison
+pub const DEFAULT_YOY_WINDOW: usize = 30;
+
+/// Shortest window a year-over-year comparison accepts
+pub const MIN_YOY_WINDOW: usize = 7;
+
+/// Longest window a year-over-year comparison accepts
+pub const MAX_YOY_WINDOW: usize = 90;
+
+/// How often the archiver fetches the watchlist's history; ESI publishes a new day once a day
+pub const DEFAULT_ARCHIVE_INTERVAL: Duration = Duration::from_secs(6 * 3600);
+
+/// A SQLite file of daily market history, one row per region, item and day
+#[derive(Debug)]
+pub struct HistoryArchive {
+    path: PathBuf,
+    #[cfg(feature = "history-archive")]
+    conn: std::sync::Mutex<rusqlite::Connection>,
+}
+
+#[cfg(feature = "history-archive")]
+fn sql_error(e: rusqlite::Error) -> TraderGraderError {
+    TraderGraderError::InternalError(format!("History archive error: {e}"))
+}
+
+impl HistoryArchive {
+    /// Opens the archive at `path`, creating the file and its table if needed
+    #[cfg(feature = "history-archive")]
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let conn = rusqlite::Connection::open(path).map_err(sql_error)?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS market_history (
+                region_id INTEGER NOT NULL,
+                type_id INTEGER NOT NULL,
+                date TEXT NOT NULL,
+                average REAL NOT NULL,
+                highest REAL NOT NULL,
+                lowest REAL NOT NULL,
+                order_count INTEGER NOT NULL,
+                volume INTEGER NOT NULL,
+                PRIMARY KEY (region_id, type_id, date)
+            ) WITHOUT ROWID;",
+        )
+        .map_err(sql_error)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            conn: std::sync::Mutex::new(conn),
+        })
+    }
+
+    /// Opens the archive at `path` (unavailable without the `history-archive` feature)
+    #[cfg(not(feature = "history-archive"))]
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Err(TraderGraderError::InternalError(format!(
+            "Cannot open {}: the history archive requires the history-archive feature",
+            path.as_ref().display()
+        )))
+    }
+
+    /// File the archive is kept in
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Stores days of history for an item, returning how many days were new
+    ///
+    /// Days already archived are overwritten, since ESI revises the latest day
+    /// until it is over.
+    #[cfg(feature = "history-archive")]
+    pub fn record(&self, region_id: i32, type_id: i32, history: &[MarketHistory]) -> Result<usize> {
+        let mut conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        let tx = conn.transaction().map_err(sql_error)?;
+        let mut added = 0;
+        {
+            let mut exists = tx
+                .prepare("SELECT 1 FROM market_history WHERE region_id = ?1 AND type_id = ?2 AND date = ?3")
+                .map_err(sql_error)?;
+            let mut insert = tx
+                .prepare(
+                    "INSERT OR REPLACE INTO market_history
+                     (region_id, type_id, date, average, highest, lowest, order_count, volume)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                )
+                .map_err(sql_error)?;
+            for day in history {
+                if !exists.exists(rusqlite::params![region_id, type_id, day.date]).map_err(sql_error)? {
+                    added += 1;
+                }
+                insert
+                    .execute(rusqlite::params![
+                        region_id,
+                        type_id,
+                        day.date,
+                        day.average,
+                        day.highest,
+                        day.lowest,
+                        day.order_count,
+                        day.volume
+                    ])
+                    .map_err(sql_error)?;
+            }
+        }
+        tx.commit().map_err(sql_error)?;
+        Ok(added)
+    }
+
+    /// Stores days of history for an item (unavailable without the `history-archive` feature)
+    #[cfg(not(feature = "history-archive"))]
+    pub fn record(&self, _region_id: i32, _type_id: i32, _history: &[MarketHistory]) -> Result<usize> {
+        Err("The history archive requires the history-archive feature".into())
+    }
+
+    /// Every archived day of an item, oldest first
+    #[cfg(feature = "history-archive")]
+    pub fn load(&self, region_id: i32, type_id: i32) -> Result<Vec<MarketHistory>> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        let mut stmt = conn
+            .prepare(
+                "SELECT date, average, highest, lowest, order_count, volume FROM market_history
+                 WHERE region_id = ?1 AND type_id = ?2 ORDER BY date",
+            )
+            .map_err(sql_error)?;
+        let rows = stmt
+            .query_map([region_id, type_id], |row| {
+                Ok(MarketHistory {
+                    date: row.get(0)?,
+                    average: row.get(1)?,
+                    highest: row.get(2)?,
+                    lowest: row.get(3)?,
+                    order_count: row.get(4)?,
+                    volume: row.get(5)?,
+                    extra: Default::default(),
+                })
+            })
+            .map_err(sql_error)?;
+        rows.collect::<std::result::Result<_, _>>().map_err(sql_error)
+    }
+
+    /// Every archived day of an item (unavailable without the `history-archive` feature)
+    #[cfg(not(feature = "history-archive"))]
+    pub fn load(&self, _region_id: i32, _type_id: i32) -> Result<Vec<MarketHistory>> {
+        Err("The history archive requires the history-archive feature".into())
+    }
+}
+
+/// Combines archived days with freshly fetched ones, oldest first
+///
+/// Fetched days win over archived days of the same date.
+///
+/// # Examples
+///
+/// ```
+/// use tradergrader::archive::merge_history;
+/// use tradergrader::MarketHistory;
+///
+/// let day = |date: &str, average: f64| MarketHistory {
+///     average,
+///     date: date.to_string(),
+///     highest: average,
+///     lowest: average,
+///     order_count: 10,
+///     volume: 1_000,
+///     extra: Default::default(),
+/// };
+/// let archived = vec![day("2023-05-01", 4.0), day("2024-06-01", 5.0)];
+/// let fetched = vec![day("2024-06-01", 5.5), day("2024-06-02", 6.0)];
+///
+/// let merged = merge_history(archived, fetched);
+/// let dates: Vec<&str> = merged.iter().map(|d| d.date.as_str()).collect();
+/// assert_eq!(dates, ["2023-05-01", "2024-06-01", "2024-06-02"]);
+/// assert_eq!(merged[1].average, 5.5);
+/// ```
+pub fn merge_history(archived: Vec<MarketHistory>, fetched: Vec<MarketHistory>) -> Vec<MarketHistory> {
+    let mut days: BTreeMap<String, MarketHistory> = archived.into_iter().map(|day| (day.date.clone(), day)).collect();
+    days.extend(fetched.into_iter().map(|day| (day.date.clone(), day)));
+    days.into_values().collect()
+}
+
+impl YearOverYear {
+    /// Compares the latest `window_days` days with the same days in each earlier year the history covers
+    ///
+    /// Years are counted back from the most recent date in the history, and the
+    /// comparison stops at the first year without any trades in its window.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::{MarketHistory, YearOverYear};
+    ///
+    /// let day = |date: &str, average: f64| MarketHistory {
+    ///     average,
+    ///     date: date.to_string(),
+    ///     highest: average,
+    ///     lowest: average,
+    ///     order_count: 10,
+    ///     volume: 1_000,
+    ///     extra: Default::default(),
+    /// };
+    /// let history = vec![day("2023-06-10", 4.0), day("2024-06-10", 5.0), day("2025-06-10", 6.0)];
+    ///
+    /// let comparison = YearOverYear::from_history(10000002, 34, &history, 30)?;
+    /// assert_eq!(comparison.years.len(), 3);
+    /// assert_eq!(comparison.years[2].price_change_percent, Some(50.0));
+    /// # Ok::<(), tradergrader::TraderGraderError>(())
+    /// ```
+    pub fn from_history(region_id: i32, type_id: i32, history: &[MarketHistory], window_days: usize) -> Result<Self> {
+        if !(MIN_YOY_WINDOW..=MAX_YOY_WINDOW).contains(&window_days) {
+            return Err(TraderGraderError::InvalidArgument {
+                field: "window_days".to_string(),
+                reason: format!("must be between {MIN_YOY_WINDOW} and {MAX_YOY_WINDOW}"),
+            });
+        }
+        let dated: Vec<(NaiveDate, &MarketHistory)> = history
+            .iter()
+            .filter_map(|day| NaiveDate::parse_from_str(&day.date, "%Y-%m-%d").ok().map(|date| (date, day)))
+            .collect();
+        let (Some(latest), Some(first)) =
+            (dated.iter().map(|(date, _)| *date).max(), dated.iter().map(|(date, _)| *date).min())
+        else {
+            return Err("No historical data available".into());
+        };
+
+        let mut years: Vec<YearWindow> = Vec::new();
+        for years_ago in 0u32.. {
+            let Some(end) = latest.checked_sub_months(Months::new(12 * years_ago)) else {
+                break;
+            };
+            let start = end - ChronoDuration::days(window_days as i64 - 1);
+            let days: Vec<&MarketHistory> =
+                dated.iter().filter(|(date, _)| (start..=end).contains(date)).map(|(_, day)| *day).collect();
+            if days.is_empty() {
+                break;
+            }
+            let average_price = days.iter().map(|day| day.average).sum::<f64>() / days.len() as f64;
+            let average_daily_volume = days.iter().map(|day| day.volume as f64).sum::<f64>() / window_days as f64;
+            let change = |now: f64, then: f64| (years_ago > 0 && then > 0.0).then(|| (now - then) / then * 100.0);
+            let current = years.first();
+            years.push(YearWindow {
+                years_ago,
+                start_date: start.format("%Y-%m-%d").to_string(),
+                end_date: end.format("%Y-%m-%d").to_string(),
+                days_traded: days.len(),
+                average_price,
+                average_daily_volume,
+                price_change_percent: current.and_then(|c| change(c.average_price, average_price)),
+                volume_change_percent: current.and_then(|c| change(c.average_daily_volume, average_daily_volume)),
+            });
+        }
+
+        Ok(Self {
+            region_id,
+            type_id,
+            window_days,
+            first_date: first.format("%Y-%m-%d").to_string(),
+            years,
+        })
+    }
+}
+
+impl MarketClient {
+    /// Fetches an item's history from ESI and appends it to the archive, returning how many days were new
+    ///
+    /// Fails when no archive is configured.
+    pub async fn archive_history(&self, region_id: i32, type_id: i32) -> Result<usize> {
+        let Some(archive) = self.history_archive() else {
+            return Err("No history archive is configured".into());
+        };
+        let history = self.fetch_market_history(region_id, type_id).await?;
+        archive.record(region_id, type_id, &history)
+    }
+
+    /// Fetches an item's history with everything archived before ESI's window
+    ///
+    /// Without an archive this is the same as
+    /// [`fetch_market_history`](MarketClient::fetch_market_history). Archive
+    /// errors are logged and the ESI history returned on its own.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # use tradergrader::archive::HistoryArchive;
+    /// # use std::sync::Arc;
+    /// # async fn example() -> Result<()> {
+    /// let archive = HistoryArchive::open("/srv/tradergrader/history.sqlite")?;
+    /// let client = MarketClient::new().with_history_archive(Arc::new(archive));
+    /// let history = client.fetch_full_history(10000002, 44992).await?;
+    /// println!("{} days since {}", history.len(), history[0].date);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn fetch_full_history(&self, region_id: i32, type_id: i32) -> Result<Vec<MarketHistory>> {
+        let history = self.fetch_market_history(region_id, type_id).await?;
+        let Some(archive) = self.history_archive() else {
+            return Ok(history);
+        };
+        let archived = archive
+            .record(region_id, type_id, &history)
+            .and_then(|_| archive.load(region_id, type_id));
+        match archived {
+            Ok(archived) => Ok(merge_history(archived, history)),
+            Err(e) => {
+                tracing::warn!("History archive unavailable for {type_id} in {region_id}: {e}");
+                Ok(history)
+            }
+        }
+    }
+
+    /// Compares an item's recent price and volume with the same days in earlier years
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # use tradergrader::archive::DEFAULT_YOY_WINDOW;
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// let comparison = client.year_over_year(10000002, 44992, DEFAULT_YOY_WINDOW).await?;
+    /// for year in &comparison.years {
+    ///     println!("{} years ago: {:.0} ISK", year.years_ago, year.average_price);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn year_over_year(&self, region_id: i32, type_id: i32, window_days: usize) -> Result<YearOverYear> {
+        let history = self.fetch_full_history(region_id, type_id).await?;
+        YearOverYear::from_history(region_id, type_id, &history, window_days)
+    }
+}
+
+/// A running background archive of the watchlist's history, stopped when dropped
+#[derive(Debug)]
+pub struct HistoryArchiver {
+    task: JoinHandle<()>,
+}
+
+impl HistoryArchiver {
+    /// Starts archiving every watched item's history every `interval`
+    ///
+    /// Fails when the client has no archive. Must be called from within a Tokio runtime.
+    pub fn start(client: Arc<MarketClient>, watchlist: Arc<WatchlistStore>, interval: Duration) -> Result<Self> {
+        if client.history_archive().is_none() {
+            return Err("No history archive is configured".into());
+        }
+        let task = tokio::spawn(async move {
+            loop {
+                let mut added = 0;
+                for (region_id, type_id) in watchlist.entries() {
+                    match client.archive_history(region_id, type_id).await {
+                        Ok(days) => added += days,
+                        Err(e) => tracing::warn!("Archiving history of {type_id} in {region_id} failed: {e}"),
+                    }
+                }
+                tracing::debug!("Archived {added} new day(s) of watchlist history");
+                tokio::time::sleep(interval).await;
+            }
+        });
+        Ok(Self { task })
+    }
+}
+
+impl Drop for HistoryArchiver {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Formats a year-over-year comparison, one line per year
+pub(crate) fn format_year_over_year(comparison: &YearOverYear, type_label: &str) -> String {
+    let mut text = format!(
+        "Year-over-Year for {type_label} in region {} ({}-day windows, history since {}):\n",
+        comparison.region_id, comparison.window_days, comparison.first_date
+    );
+    let percent = |p: Option<f64>| p.map(|p| format!("{p:+.1}%")).unwrap_or_else(|| "-".to_string());
+    for year in &comparison.years {
+        let label = match year.years_ago {
+            0 => "This year".to_string(),
+            1 => "1 year ago".to_string(),
+            n => format!("{n} years ago"),
+        };
+        text.push_str(&format!(
+            "\n{label} ({} to {}, {} days traded): avg price {:.2} ISK, avg daily volume {:.0}",
+            year.start_date, year.end_date, year.days_traded, year.average_price, year.average_daily_volume
+        ));
+        if year.years_ago > 0 {
+            text.push_str(&format!(
+                " | now {} in price, {} in volume",
+                percent(year.price_change_percent),
+                percent(year.volume_change_percent)
+            ));
+        }
+    }
+    if comparison.years.len() < 2 {
+        text.push_str(
+            "\n\nNo earlier year has trades in this window yet. ESI keeps about 13 months of history; \
+             set a history archive path in a build with the history-archive feature to keep more.",
+        );
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::history_day;
+
+    #[test]
+    fn test_year_over_year_windows() {
+        let history = vec![
+            history_day("2023-05-20", 8.0, 3_000),
+            history_day("2024-05-25", 4.0, 2_000),
+            history_day("2024-06-01", 6.0, 2_000),
+            history_day("2025-06-01", 10.0, 1_000),
+            // Outside every window
+            history_day("2025-03-01", 100.0, 1),
+        ];
+        let comparison = YearOverYear::from_history(10000002, 34, &history, 14).unwrap();
+        assert_eq!(comparison.first_date, "2023-05-20");
+        // 2023's window (2023-05-19 to 2023-06-01) has a trade; nothing earlier does
+        assert_eq!(comparison.years.len(), 3);
+
+        let last_year = &comparison.years[1];
+        assert_eq!((last_year.start_date.as_str(), last_year.end_date.as_str()), ("2024-05-19", "2024-06-01"));
+        assert_eq!(last_year.average_price, 5.0);
+        assert_eq!(last_year.price_change_percent, Some(100.0));
+        assert_eq!(last_year.volume_change_percent, Some(-75.0));
+
+        let text = format_year_over_year(&comparison, "Tritanium (34)");
+        assert!(text.contains("1 year ago (2024-05-19 to 2024-06-01, 2 days traded)"));
+        assert!(text.contains("now +100.0% in price, -75.0% in volume"));
+        assert!(YearOverYear::from_history(10000002, 34, &history, 3).is_err());
+    }
+
+    #[cfg(feature = "history-archive")]
+    #[test]
+    fn test_archive_keeps_days_across_opens() {
+        let path = std::env::temp_dir().join(format!("tradergrader-archive-{}.sqlite", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let archive = HistoryArchive::open(&path).unwrap();
+        let days = [history_day("2024-01-01", 4.0, 10), history_day("2024-01-02", 4.1, 10)];
+        assert_eq!(archive.record(10000002, 34, &days).unwrap(), 2);
+        // The revised latest day replaces the archived one
+        let revised = [history_day("2024-01-02", 4.2, 20), history_day("2024-01-03", 4.3, 10)];
+        assert_eq!(archive.record(10000002, 34, &revised).unwrap(), 1);
+        drop(archive);
+
+        let archive = HistoryArchive::open(&path).unwrap();
+        let history = archive.load(10000002, 34).unwrap();
+        assert_eq!(history.len(), 3);
+        assert_eq!((history[1].average, history[1].volume), (4.2, 20));
+        assert!(archive.load(10000043, 34).unwrap().is_empty());
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,666 @@
+//! EVE SSO (OAuth2) authentication for TraderGrader
+//!
+//! Implements the EVE Single Sign-On authorization code flow with PKCE so that
+//! authenticated ESI endpoints (character orders, structure markets, wallet)
+//! become usable. Tokens are refreshed automatically shortly before expiry and
+//! can optionally be persisted to a JSON token store between runs.
+//!
+//! The flow is split in two steps so it works through an MCP client:
+//! 1. [`EveSso::start_login`] returns a login URL the user opens in a browser.
+//! 2. [`EveSso::complete_login`] exchanges the `code` from the callback for tokens.
+
+use crate::error::{Result, TraderGraderError};
+use crate::esi::EsiConfig;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use rand::RngCore;
+use reqwest::{Client, Url};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// EVE SSO authorization endpoint
+pub const SSO_AUTHORIZE_URL: &str = "https://login.eveonline.com/v2/oauth/authorize";
+
+/// EVE SSO token endpoint
+pub const SSO_TOKEN_URL: &str = "https://login.eveonline.com/v2/oauth/token";
+
+/// How long a started login stays valid before the user must restart it
+const PENDING_LOGIN_TTL_MINUTES: i64 = 15;
+
+/// Refresh tokens this long before they actually expire
+const REFRESH_MARGIN_SECONDS: i64 = 60;
+
+/// ESI scopes used by TraderGrader's authenticated features
+pub mod scopes {
+    /// Read a character's open market orders
+    pub const READ_CHARACTER_ORDERS: &str = "esi-markets.read_character_orders.v1";
+    /// Read market orders in player-owned structures
+    pub const STRUCTURE_MARKETS: &str = "esi-markets.structure_markets.v1";
+    /// Resolve names and details of player-owned structures
+    pub const READ_STRUCTURES: &str = "esi-universe.read_structures.v1";
+    /// Read a character's wallet transactions
+    pub const READ_WALLET: &str = "esi-wallet.read_character_wallet.v1";
+
+    /// Scopes requested when no explicit scope list is configured
+    pub fn default_scopes() -> Vec<String> {
+        vec![
+            READ_CHARACTER_ORDERS.to_string(),
+            STRUCTURE_MARKETS.to_string(),
+            READ_STRUCTURES.to_string(),
+            READ_WALLET.to_string(),
+        ]
+    }
+}
+
+/// Configuration for the EVE SSO application
+#[derive(Debug, Clone)]
+pub struct SsoConfig {
+    /// Client ID of the application registered at developers.eveonline.com
+    pub client_id: String,
+    /// Callback URL registered for the application
+    pub callback_url: String,
+    /// ESI scopes to request during login
+    pub scopes: Vec<String>,
+    /// Optional JSON file used to persist tokens between runs
+    pub token_store_path: Option<PathBuf>,
+    /// User-Agent sent to the SSO servers, carrying the same contact as ESI requests
+    pub user_agent: String,
+}
+
+impl SsoConfig {
+    /// Create an SSO configuration with default callback URL and scopes
+    pub fn new(client_id: String) -> Self {
+        Self {
+            client_id,
+            callback_url: "http://localhost:8080/callback".to_string(),
+            scopes: scopes::default_scopes(),
+            token_store_path: None,
+            user_agent: EsiConfig::default().user_agent_header(),
+        }
+    }
+
+    /// Load SSO configuration from environment variables
+    ///
+    /// Returns `None` when `TRADERGRADER_SSO_CLIENT_ID` is not set. Optional
+    /// variables: `TRADERGRADER_SSO_CALLBACK_URL`, `TRADERGRADER_SSO_SCOPES`
+    /// (space separated) and `TRADERGRADER_TOKEN_STORE` (file path).
+    pub fn from_env() -> Option<Self> {
+        let client_id = std::env::var("TRADERGRADER_SSO_CLIENT_ID").ok()?;
+        if client_id.trim().is_empty() {
+            return None;
+        }
+
+        let mut config = Self::new(client_id);
+        if let Ok(callback_url) = std::env::var("TRADERGRADER_SSO_CALLBACK_URL") {
+            config.callback_url = callback_url;
+        }
+        if let Ok(scopes) = std::env::var("TRADERGRADER_SSO_SCOPES") {
+            config.scopes = scopes.split_whitespace().map(str::to_string).collect();
+        }
+        if let Ok(path) = std::env::var("TRADERGRADER_TOKEN_STORE") {
+            config.token_store_path = Some(PathBuf::from(path));
+        }
+        Some(config)
+    }
+
+    /// Persist tokens to the given JSON file
+    pub fn with_token_store(mut self, path: PathBuf) -> Self {
+        self.token_store_path = Some(path);
+        self
+    }
+
+    /// Request a specific set of scopes
+    pub fn with_scopes(mut self, scopes: Vec<String>) -> Self {
+        self.scopes = scopes;
+        self
+    }
+
+    /// Identify SSO requests with the User-Agent, and so the contact, of an ESI configuration
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::{EsiConfig, SsoConfig};
+    ///
+    /// let esi = EsiConfig::default().with_contact("trader@example.com");
+    /// let config = SsoConfig::new("my-client".to_string()).with_esi_config(&esi);
+    /// assert!(config.user_agent.contains("trader@example.com"));
+    /// ```
+    pub fn with_esi_config(mut self, esi: &EsiConfig) -> Self {
+        self.user_agent = esi.user_agent_header();
+        self
+    }
+}
+
+/// A login started with [`EveSso::start_login`]
+#[derive(Debug, Clone)]
+pub struct LoginRequest {
+    /// URL the user must open to log in with EVE SSO
+    pub url: String,
+    /// Opaque state value that must be passed back to `complete_login`
+    pub state: String,
+}
+
+/// Tokens and identity for an authenticated character
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CharacterToken {
+    /// EVE character ID
+    pub character_id: i64,
+    /// Character name
+    pub character_name: String,
+    /// Current access token (JWT)
+    pub access_token: String,
+    /// Refresh token used to obtain new access tokens
+    pub refresh_token: String,
+    /// When the access token expires
+    pub expires_at: DateTime<Utc>,
+    /// Scopes granted to this token
+    pub scopes: Vec<String>,
+}
+
+impl CharacterToken {
+    /// Check whether the access token needs to be refreshed
+    pub fn needs_refresh(&self) -> bool {
+        Utc::now() + ChronoDuration::seconds(REFRESH_MARGIN_SECONDS) >= self.expires_at
+    }
+
+    /// Check whether this token was granted a scope
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.iter().any(|s| s == scope)
+    }
+}
+
+/// PKCE verifier and start time for a login in progress
+#[derive(Debug, Clone)]
+struct PendingLogin {
+    code_verifier: String,
+    started_at: DateTime<Utc>,
+}
+
+/// Raw token response from the SSO token endpoint
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: i64,
+    refresh_token: String,
+}
+
+/// EVE SSO client managing logins and character tokens
+#[derive(Debug)]
+pub struct EveSso {
+    config: SsoConfig,
+    http_client: Client,
+    pending: Mutex<HashMap<String, PendingLogin>>,
+    tokens: Mutex<HashMap<i64, CharacterToken>>,
+}
+
+impl EveSso {
+    /// Create a new SSO client, loading persisted tokens if a token store is configured
+    pub fn new(config: SsoConfig) -> Result<Self> {
+        let http_client = Client::builder()
+            .user_agent(config.user_agent.as_str())
+            .build()?;
+
+        let tokens = match &config.token_store_path {
+            Some(path) if path.exists() => {
+                let contents = std::fs::read_to_string(path).map_err(|e| {
+                    TraderGraderError::AuthenticationError(format!("Failed to read token store: {e}"))
+                })?;
+                let stored: Vec<CharacterToken> = serde_json::from_str(&contents)?;
+                stored.into_iter().map(|t| (t.character_id, t)).collect()
+            }
+            _ => HashMap::new(),
+        };
+
+        Ok(Self {
+            config,
+            http_client,
+            pending: Mutex::new(HashMap::new()),
+            tokens: Mutex::new(tokens),
+        })
+    }
+
+    /// Get the SSO configuration
+    pub fn config(&self) -> &SsoConfig {
+        &self.config
+    }
+
+    /// Start a PKCE login and return the URL the user must open
+    pub fn start_login(&self) -> Result<LoginRequest> {
+        let state = random_url_safe(16);
+        let code_verifier = random_url_safe(32);
+        let code_challenge = pkce_challenge(&code_verifier);
+
+        let url = Url::parse_with_params(
+            SSO_AUTHORIZE_URL,
+            &[
+                ("response_type", "code"),
+                ("redirect_uri", self.config.callback_url.as_str()),
+                ("client_id", self.config.client_id.as_str()),
+                ("scope", self.config.scopes.join(" ").as_str()),
+                ("code_challenge", code_challenge.as_str()),
+                ("code_challenge_method", "S256"),
+                ("state", state.as_str()),
+            ],
+        )
+        .map_err(|e| TraderGraderError::AuthenticationError(format!("Invalid SSO URL: {e}")))?;
+
+        let mut pending = self.lock_pending()?;
+        let cutoff = Utc::now() - ChronoDuration::minutes(PENDING_LOGIN_TTL_MINUTES);
+        pending.retain(|_, login| login.started_at > cutoff);
+        pending.insert(
+            state.clone(),
+            PendingLogin {
+                code_verifier,
+                started_at: Utc::now(),
+            },
+        );
+
+        Ok(LoginRequest {
+            url: url.to_string(),
+            state,
+        })
+    }
+
+    /// Complete a login by exchanging the authorization code for tokens
+    pub async fn complete_login(&self, code: &str, state: &str) -> Result<CharacterToken> {
+        let pending = self.lock_pending()?.remove(state).ok_or_else(|| {
+            TraderGraderError::AuthenticationError(
+                "Unknown or expired login state; start the login again".to_string(),
+            )
+        })?;
+
+        if pending.started_at < Utc::now() - ChronoDuration::minutes(PENDING_LOGIN_TTL_MINUTES) {
+            return Err(TraderGraderError::AuthenticationError(
+                "Login expired; start the login again".to_string(),
+            ));
+        }
+
+        let response = self
+            .request_token(&[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("client_id", self.config.client_id.as_str()),
+                ("code_verifier", pending.code_verifier.as_str()),
+            ])
+            .await?;
+
+        let token = token_from_response(response)?;
+        self.store_token(token.clone())?;
+        Ok(token)
+    }
+
+    /// Refresh the access token of a character
+    pub async fn refresh(&self, character_id: i64) -> Result<CharacterToken> {
+        let refresh_token = self.token(character_id)?.refresh_token;
+
+        let response = self
+            .request_token(&[
+                ("grant_type", "refresh_token"),
+                ("refresh_token", refresh_token.as_str()),
+                ("client_id", self.config.client_id.as_str()),
+            ])
+            .await?;
+
+        let token = token_from_response(response)?;
+        self.store_token(token.clone())?;
+        Ok(token)
+    }
+
+    /// Get a valid access token for a character, refreshing it if needed
+    pub async fn access_token(&self, character_id: i64) -> Result<String> {
+        let token = self.token(character_id)?;
+        if token.needs_refresh() {
+            Ok(self.refresh(character_id).await?.access_token)
+        } else {
+            Ok(token.access_token)
+        }
+    }
+
+    /// Get a valid access token for a character that must hold `scope`
+    pub async fn access_token_with_scope(&self, character_id: i64, scope: &str) -> Result<String> {
+        let token = self.token(character_id)?;
+        if !token.has_scope(scope) {
+            return Err(TraderGraderError::AuthenticationError(format!(
+                "Character {} has not granted scope {scope}; log in again with it enabled",
+                token.character_name
+            )));
+        }
+        self.access_token(character_id).await
+    }
+
+    /// Get the stored token for a character
+    pub fn token(&self, character_id: i64) -> Result<CharacterToken> {
+        self.lock_tokens()?.get(&character_id).cloned().ok_or_else(|| {
+            TraderGraderError::AuthenticationError(format!(
+                "Character {character_id} is not authenticated; use authenticate_character first"
+            ))
+        })
+    }
+
+    /// List all authenticated characters
+    pub fn characters(&self) -> Result<Vec<CharacterToken>> {
+        let mut characters: Vec<CharacterToken> = self.lock_tokens()?.values().cloned().collect();
+        characters.sort_by_key(|t| t.character_id);
+        Ok(characters)
+    }
+
+    /// Resolve which character to act as: the given ID, or the only logged-in character
+    pub fn resolve_character(&self, character_id: Option<i64>) -> Result<i64> {
+        if let Some(id) = character_id {
+            return Ok(id);
+        }
+
+        let characters = self.characters()?;
+        match characters.as_slice() {
+            [only] => Ok(only.character_id),
+            [] => Err(TraderGraderError::AuthenticationError(
+                "No authenticated characters; use authenticate_character first".to_string(),
+            )),
+            _ => Err(TraderGraderError::AuthenticationError(
+                "Multiple characters are authenticated; specify character_id".to_string(),
+            )),
+        }
+    }
+
+    /// Find an authenticated character that has granted `scope`
+    pub fn character_with_scope(&self, scope: &str) -> Result<i64> {
+        self.characters()?
+            .into_iter()
+            .find(|t| t.has_scope(scope))
+            .map(|t| t.character_id)
+            .ok_or_else(|| {
+                TraderGraderError::AuthenticationError(format!(
+                    "No authenticated character has granted scope {scope}; use authenticate_character"
+                ))
+            })
+    }
+
+    /// Remove a character's tokens
+    pub fn logout(&self, character_id: i64) -> Result<()> {
+        self.lock_tokens()?.remove(&character_id);
+        self.persist()
+    }
+
+    /// POST a form to the token endpoint
+    async fn request_token(&self, form: &[(&str, &str)]) -> Result<TokenResponse> {
+        let response = self
+            .http_client
+            .post(SSO_TOKEN_URL)
+            .header("Host", "login.eveonline.com")
+            .form(form)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(TraderGraderError::AuthenticationError(format!(
+                "SSO token request failed with status {status}: {body}"
+            )));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    fn store_token(&self, token: CharacterToken) -> Result<()> {
+        self.lock_tokens()?.insert(token.character_id, token);
+        self.persist()
+    }
+
+    /// Write all tokens to the token store, if one is configured
+    ///
+    /// Refresh tokens grant account access, so on unix the store is readable
+    /// by its owner only. It's written to a temporary file and renamed over
+    /// the old one, so a crash mid-write can't lose every login.
+    fn persist(&self) -> Result<()> {
+        let Some(path) = &self.config.token_store_path else {
+            return Ok(());
+        };
+
+        let tokens = self.characters()?;
+        let contents = serde_json::to_string_pretty(&tokens)?;
+        let write_error =
+            |e: std::io::Error| TraderGraderError::AuthenticationError(format!("Failed to write token store: {e}"));
+        let mut temp = path.clone().into_os_string();
+        temp.push(".tmp");
+        let temp = PathBuf::from(temp);
+
+        // A leftover temporary file could have looser permissions; start afresh
+        match std::fs::remove_file(&temp) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(write_error(e)),
+            _ => {}
+        }
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(&temp).map_err(write_error)?;
+        file.write_all(contents.as_bytes()).map_err(write_error)?;
+        file.sync_all().map_err(write_error)?;
+        drop(file);
+        std::fs::rename(&temp, path).map_err(write_error)
+    }
+
+    fn lock_pending(&self) -> Result<std::sync::MutexGuard<'_, HashMap<String, PendingLogin>>> {
+        self.pending
+            .lock()
+            .map_err(|_| TraderGraderError::InternalError("SSO login state poisoned".to_string()))
+    }
+
+    fn lock_tokens(&self) -> Result<std::sync::MutexGuard<'_, HashMap<i64, CharacterToken>>> {
+        self.tokens
+            .lock()
+            .map_err(|_| TraderGraderError::InternalError("SSO token state poisoned".to_string()))
+    }
+}
+
+/// Build a `CharacterToken` from a token response by reading the JWT claims
+fn token_from_response(response: TokenResponse) -> Result<CharacterToken> {
+    let claims = decode_jwt_claims(&response.access_token)?;
+
+    let character_id = claims
+        .get("sub")
+        .and_then(|v| v.as_str())
+        .and_then(|sub| sub.strip_prefix("CHARACTER:EVE:"))
+        .and_then(|id| id.parse::<i64>().ok())
+        .ok_or_else(|| {
+            TraderGraderError::AuthenticationError("Access token has no character subject".to_string())
+        })?;
+
+    let character_name = claims
+        .get("name")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string();
+
+    // `scp` is a string for a single scope and an array otherwise
+    let scopes = match claims.get("scp") {
+        Some(Value::String(scope)) => vec![scope.clone()],
+        Some(Value::Array(scopes)) => scopes
+            .iter()
+            .filter_map(|s| s.as_str().map(str::to_string))
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    Ok(CharacterToken {
+        character_id,
+        character_name,
+        access_token: response.access_token,
+        refresh_token: response.refresh_token,
+        expires_at: Utc::now() + ChronoDuration::seconds(response.expires_in),
+        scopes,
+    })
+}
+
+/// Decode the claims of a JWT without verifying its signature
+///
+/// Tokens are received directly from the SSO over TLS, so the claims are only
+/// used to identify the character and scopes, never as proof of identity.
+fn decode_jwt_claims(token: &str) -> Result<Value> {
+    let payload = token.split('.').nth(1).ok_or_else(|| {
+        TraderGraderError::AuthenticationError("Access token is not a JWT".to_string())
+    })?;
+
+    let bytes = URL_SAFE_NO_PAD
+        .decode(payload.trim_end_matches('='))
+        .map_err(|e| TraderGraderError::AuthenticationError(format!("Invalid JWT payload: {e}")))?;
+
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+/// Compute the S256 PKCE challenge for a verifier
+fn pkce_challenge(code_verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()))
+}
+
+/// Generate a random URL-safe string from `len` random bytes
+fn random_url_safe(len: usize) -> String {
+    let mut bytes = vec![0u8; len];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn fake_jwt(claims: Value) -> String {
+        let header = URL_SAFE_NO_PAD.encode(br#"{"alg":"RS256","typ":"JWT"}"#);
+        let payload = URL_SAFE_NO_PAD.encode(claims.to_string());
+        format!("{header}.{payload}.signature")
+    }
+
+    #[test]
+    fn test_pkce_challenge() {
+        // Test vector from RFC 7636 appendix B
+        let challenge = pkce_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk");
+        assert_eq!(challenge, "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM");
+    }
+
+    #[test]
+    fn test_start_login_url() {
+        let sso = EveSso::new(SsoConfig::new("my-client".to_string())).expect("Should create SSO");
+        let login = sso.start_login().expect("Should start login");
+
+        assert!(login.url.starts_with(SSO_AUTHORIZE_URL));
+        assert!(login.url.contains("client_id=my-client"));
+        assert!(login.url.contains("code_challenge_method=S256"));
+        assert!(login.url.contains(&format!("state={}", login.state)));
+        assert!(login.url.contains("esi-markets.read_character_orders.v1"));
+    }
+
+    #[test]
+    fn test_sso_user_agent_carries_esi_contact() {
+        let esi = EsiConfig::default().with_contact("trader@example.com");
+        let sso = EveSso::new(SsoConfig::new("my-client".to_string()).with_esi_config(&esi)).expect("Should create SSO");
+        assert_eq!(sso.config().user_agent, esi.user_agent_header());
+    }
+
+    #[tokio::test]
+    async fn test_complete_login_rejects_unknown_state() {
+        let sso = EveSso::new(SsoConfig::new("my-client".to_string())).expect("Should create SSO");
+        let result = sso.complete_login("code", "not-a-state").await;
+        assert!(matches!(result, Err(TraderGraderError::AuthenticationError(_))));
+    }
+
+    #[test]
+    fn test_token_from_response() {
+        let access_token = fake_jwt(json!({
+            "sub": "CHARACTER:EVE:2112625428",
+            "name": "Test Pilot",
+            "scp": ["esi-markets.read_character_orders.v1", "esi-markets.structure_markets.v1"]
+        }));
+
+        let token = token_from_response(TokenResponse {
+            access_token,
+            expires_in: 1199,
+            refresh_token: "refresh".to_string(),
+        })
+        .expect("Should parse token");
+
+        assert_eq!(token.character_id, 2112625428);
+        assert_eq!(token.character_name, "Test Pilot");
+        assert!(token.has_scope(scopes::READ_CHARACTER_ORDERS));
+        assert!(!token.has_scope(scopes::READ_WALLET));
+        assert!(!token.needs_refresh());
+    }
+
+    #[test]
+    fn test_single_scope_claim() {
+        let access_token = fake_jwt(json!({
+            "sub": "CHARACTER:EVE:1",
+            "name": "Solo",
+            "scp": "esi-markets.structure_markets.v1"
+        }));
+
+        let token = token_from_response(TokenResponse {
+            access_token,
+            expires_in: 30,
+            refresh_token: "refresh".to_string(),
+        })
+        .expect("Should parse token");
+
+        assert_eq!(token.scopes, vec![scopes::STRUCTURE_MARKETS.to_string()]);
+        assert!(token.needs_refresh()); // Within the refresh margin
+    }
+
+    #[test]
+    fn test_resolve_character() {
+        let sso = EveSso::new(SsoConfig::new("my-client".to_string())).expect("Should create SSO");
+        assert!(sso.resolve_character(None).is_err());
+        assert_eq!(sso.resolve_character(Some(42)).unwrap(), 42);
+
+        sso.store_token(CharacterToken {
+            character_id: 7,
+            character_name: "Only".to_string(),
+            access_token: String::new(),
+            refresh_token: String::new(),
+            expires_at: Utc::now(),
+            scopes: Vec::new(),
+        })
+        .expect("Should store token");
+        assert_eq!(sso.resolve_character(None).unwrap(), 7);
+        assert!(sso.character_with_scope(scopes::STRUCTURE_MARKETS).is_err());
+    }
+
+    #[test]
+    fn test_token_store_is_private_and_reloads() {
+        let path = std::env::temp_dir().join(format!("tradergrader-tokens-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let config = SsoConfig::new("my-client".to_string()).with_token_store(path.clone());
+
+        let sso = EveSso::new(config.clone()).expect("Should create SSO");
+        sso.store_token(CharacterToken {
+            character_id: 7,
+            character_name: "Saved".to_string(),
+            access_token: String::new(),
+            refresh_token: "secret".to_string(),
+            expires_at: Utc::now(),
+            scopes: Vec::new(),
+        })
+        .expect("Should store token");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        let mut temp = path.clone().into_os_string();
+        temp.push(".tmp");
+        assert!(!PathBuf::from(temp).exists());
+
+        let reloaded = EveSso::new(config).expect("Should reload SSO");
+        assert_eq!(reloaded.resolve_character(None).unwrap(), 7);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
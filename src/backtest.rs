@@ -0,0 +1,432 @@
+//! Backtesting simple trading rules for TraderGrader
+//!
+//! Replays an item's daily history against a rule set: buy when the price
+//! dips a chosen distance below its moving average, sell at a profit target,
+//! a stop loss or after a maximum holding time. Every trade fills at the day's
+//! average price with a fixed amount of ISK, pays broker fees on both orders
+//! and sales tax on the sale under the fee schedule in effect at the time, and
+//! the result reports profit, drawdown and how the rules compare with simply
+//! holding the item.
+//!
+//! Daily averages hide the spread and the queue, so the figures are an upper
+//! bound on what the rules could have made, not a forecast.
+
+use crate::error::{Result, TraderGraderError};
+use crate::fees::{FeeSchedules, TradingProfile};
+use crate::market::MarketClient;
+use crate::types::{BacktestExit, BacktestResult, BacktestTrade, MarketHistory};
+use chrono::NaiveDate;
+
+/// Most trades listed in a report, newest first
+const MAX_REPORTED_TRADES: usize = 15;
+
+/// Entry and exit rules replayed over daily history
+///
+/// # Examples
+///
+/// ```
+/// use tradergrader::backtest::BacktestRules;
+///
+/// // Buy 10% under the 30-day average, take 15%, cut losses at 10%
+/// let rules = BacktestRules {
+///     entry_discount_percent: 10.0,
+///     take_profit_percent: 15.0,
+///     stop_loss_percent: Some(10.0),
+///     ..BacktestRules::default()
+/// };
+/// assert!(rules.validate().is_ok());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BacktestRules {
+    /// Days in the moving average entries are measured against
+    pub sma_days: usize,
+    /// Buy when the day's average is this far below the moving average, in percent
+    pub entry_discount_percent: f64,
+    /// Sell once the price is this far above the entry, in percent
+    pub take_profit_percent: f64,
+    /// Sell once the price is this far below the entry, in percent
+    pub stop_loss_percent: Option<f64>,
+    /// Sell after this many days whatever the price
+    pub max_holding_days: Option<i64>,
+    /// ISK committed to each trade
+    pub position_isk: f64,
+}
+
+impl Default for BacktestRules {
+    fn default() -> Self {
+        Self {
+            sma_days: 30,
+            entry_discount_percent: 5.0,
+            take_profit_percent: 10.0,
+            stop_loss_percent: None,
+            max_holding_days: None,
+            position_isk: 100_000_000.0,
+        }
+    }
+}
+
+impl BacktestRules {
+    /// Checks the moving average has at least two days and every threshold is positive
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |field: &str, reason: &str| {
+            Err(TraderGraderError::InvalidArgument {
+                field: field.to_string(),
+                reason: reason.to_string(),
+            })
+        };
+        let positive = |value: f64| value > 0.0 && value.is_finite();
+        if self.sma_days < 2 {
+            return invalid("sma_days", "must be at least 2");
+        }
+        if !(self.entry_discount_percent >= 0.0 && self.entry_discount_percent < 100.0) {
+            return invalid("entry_discount_percent", "must be from 0 up to 100");
+        }
+        if !positive(self.take_profit_percent) {
+            return invalid("take_profit_percent", "must be positive");
+        }
+        if self.stop_loss_percent.is_some_and(|stop| !(positive(stop) && stop < 100.0)) {
+            return invalid("stop_loss_percent", "must be between 0 and 100");
+        }
+        if self.max_holding_days.is_some_and(|days| days < 1) {
+            return invalid("max_holding_days", "must be at least 1");
+        }
+        if !positive(self.position_isk) {
+            return invalid("position_isk", "must be positive");
+        }
+        Ok(())
+    }
+
+    /// Why a position bought at `entry_price` on `entry_date` closes at `price` on `date`, if it does
+    fn exit(&self, entry_price: f64, entry_date: NaiveDate, price: f64, date: NaiveDate) -> Option<BacktestExit> {
+        let change_percent = (price / entry_price - 1.0) * 100.0;
+        if change_percent >= self.take_profit_percent {
+            Some(BacktestExit::TakeProfit)
+        } else if self.stop_loss_percent.is_some_and(|stop| change_percent <= -stop) {
+            Some(BacktestExit::StopLoss)
+        } else if self.max_holding_days.is_some_and(|days| (date - entry_date).num_days() >= days) {
+            Some(BacktestExit::MaxHolding)
+        } else {
+            None
+        }
+    }
+}
+
+/// A position bought and not yet sold
+struct OpenPosition {
+    date: NaiveDate,
+    price: f64,
+    units: f64,
+    entry_fee: f64,
+}
+
+impl BacktestResult {
+    /// Replays `rules` over history in any order, pricing fees for `trading`
+    ///
+    /// Rows with an unparseable date are skipped. A position still open when
+    /// the history ends is closed at the last price.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::backtest::BacktestRules;
+    /// use tradergrader::fees::{TradingProfile, TradingSkills};
+    /// use tradergrader::{BacktestExit, BacktestResult, MarketHistory};
+    ///
+    /// // 100 ISK for a month, a dip to 90, then a recovery to 102
+    /// let history: Vec<MarketHistory> = (1..=40)
+    ///     .map(|day| MarketHistory {
+    ///         average: match day {
+    ///             31 => 90.0,
+    ///             32.. => 102.0,
+    ///             _ => 100.0,
+    ///         },
+    ///         date: format!("2025-{:02}-{:02}", 5 + day / 32, (day - 1) % 31 + 1),
+    ///         highest: 0.0,
+    ///         lowest: 0.0,
+    ///         order_count: 10,
+    ///         volume: 1_000,
+    ///         extra: Default::default(),
+    ///     })
+    ///     .collect();
+    ///
+    /// let trading = TradingProfile {
+    ///     skills: TradingSkills::max_skills(),
+    ///     ..TradingProfile::default()
+    /// };
+    /// let result = BacktestResult::from_history(&history, &BacktestRules::default(), &trading)?;
+    /// assert_eq!(result.trades.len(), 1);
+    /// assert_eq!(result.trades[0].exit, BacktestExit::TakeProfit);
+    /// assert!(result.total_profit > 0.0);
+    /// # Ok::<(), tradergrader::TraderGraderError>(())
+    /// ```
+    pub fn from_history(history: &[MarketHistory], rules: &BacktestRules, trading: &TradingProfile) -> Result<Self> {
+        rules.validate()?;
+        let mut days: Vec<(NaiveDate, f64)> = history
+            .iter()
+            .filter(|h| h.average > 0.0)
+            .filter_map(|h| NaiveDate::parse_from_str(&h.date, "%Y-%m-%d").ok().map(|date| (date, h.average)))
+            .collect();
+        days.sort_by_key(|(date, _)| *date);
+        if days.len() <= rules.sma_days {
+            return Err(format!("Need more than {} days of history to backtest", rules.sma_days).into());
+        }
+
+        let schedules = FeeSchedules::default();
+        let fees_on = |date: NaiveDate| trading.fee_schedule(schedules.for_date(date));
+        let mut trades = Vec::new();
+        let mut open: Option<OpenPosition> = None;
+        let (mut realized, mut peak, mut max_drawdown) = (0.0, 0.0, 0.0f64);
+
+        for (t, &(date, price)) in days.iter().enumerate() {
+            let closing = open.take_if(|position| {
+                rules.exit(position.price, position.date, price, date).is_some() || t == days.len() - 1
+            });
+            if let Some(position) = closing {
+                let exit = rules
+                    .exit(position.price, position.date, price, date)
+                    .unwrap_or(BacktestExit::EndOfData);
+                let value = position.units * price;
+                let fees = fees_on(date);
+                let exit_fees = fees.broker_fee(value, &trading.skills) + fees.sales_tax(value, &trading.skills);
+                let profit = value - rules.position_isk - position.entry_fee - exit_fees;
+                realized += profit;
+                trades.push(BacktestTrade {
+                    entry_date: position.date.to_string(),
+                    entry_price: position.price,
+                    exit_date: date.to_string(),
+                    exit_price: price,
+                    exit,
+                    holding_days: (date - position.date).num_days(),
+                    fees: position.entry_fee + exit_fees,
+                    profit,
+                    return_percent: profit / rules.position_isk * 100.0,
+                });
+            } else if open.is_none() && t + 1 >= rules.sma_days && t + 1 < days.len() {
+                let window = &days[t + 1 - rules.sma_days..=t];
+                let sma = window.iter().map(|(_, p)| p).sum::<f64>() / rules.sma_days as f64;
+                if price <= sma * (1.0 - rules.entry_discount_percent / 100.0) {
+                    open = Some(OpenPosition {
+                        date,
+                        price,
+                        units: rules.position_isk / price,
+                        entry_fee: fees_on(date).broker_fee(rules.position_isk, &trading.skills),
+                    });
+                }
+            }
+
+            let unrealized = open
+                .as_ref()
+                .map_or(0.0, |p| p.units * price - rules.position_isk - p.entry_fee);
+            let equity = realized + unrealized;
+            peak = f64::max(peak, equity);
+            max_drawdown = max_drawdown.max(peak - equity);
+        }
+
+        let (first, last) = (days[0], days[days.len() - 1]);
+        let wins = trades.iter().filter(|t| t.profit > 0.0).count();
+        Ok(Self {
+            from_date: first.0.to_string(),
+            to_date: last.0.to_string(),
+            days_replayed: days.len(),
+            total_profit: realized,
+            total_fees: trades.iter().map(|t| t.fees).sum(),
+            win_rate_percent: if trades.is_empty() { 0.0 } else { wins as f64 / trades.len() as f64 * 100.0 },
+            trades,
+            max_drawdown,
+            max_drawdown_percent: max_drawdown / rules.position_isk * 100.0,
+            buy_and_hold_percent: (last.1 / first.1 - 1.0) * 100.0,
+        })
+    }
+}
+
+impl MarketClient {
+    /// Replays trading rules over an item's market history
+    pub async fn backtest_strategy(
+        &self,
+        region_id: i32,
+        type_id: i32,
+        rules: &BacktestRules,
+        trading: &TradingProfile,
+    ) -> Result<BacktestResult> {
+        rules.validate()?;
+        let history = self.fetch_market_history(region_id, type_id).await?;
+        BacktestResult::from_history(&history, rules, trading)
+    }
+
+    /// Generates a formatted backtest report
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// use tradergrader::backtest::BacktestRules;
+    /// use tradergrader::fees::TradingProfile;
+    ///
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// let rules = BacktestRules::default();
+    /// println!("{}", client.get_backtest_summary(10000002, 44992, &rules, &TradingProfile::default()).await?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_backtest_summary(
+        &self,
+        region_id: i32,
+        type_id: i32,
+        rules: &BacktestRules,
+        trading: &TradingProfile,
+    ) -> Result<String> {
+        let result = self.backtest_strategy(region_id, type_id, rules, trading).await?;
+        Ok(format_backtest(
+            &format!("Backtest for {} in Region {region_id}", self.type_label(type_id).await),
+            rules,
+            &result,
+        ))
+    }
+}
+
+/// Formats the rules, the totals and the most recent trades
+fn format_backtest(title: &str, rules: &BacktestRules, result: &BacktestResult) -> String {
+    let mut exits = format!("take profit at +{:.1}%", rules.take_profit_percent);
+    if let Some(stop) = rules.stop_loss_percent {
+        exits.push_str(&format!(", stop loss at -{stop:.1}%"));
+    }
+    if let Some(days) = rules.max_holding_days {
+        exits.push_str(&format!(", sell after {days} days"));
+    }
+    let mut text = format!(
+        "{title} ({} to {}, {} days):\n\
+         Rules: buy {:.1}% under the {}-day average with {:.0} ISK; {exits}\n\
+         \n\
+         Trades: {} ({:.0}% profitable)\n\
+         Profit after fees: {:.2} ISK ({:+.2}% of the position size), fees {:.2} ISK\n\
+         Max drawdown: {:.2} ISK ({:.2}% of the position size)\n\
+         Buy and hold over the same days: {:+.2}%\n",
+        result.from_date,
+        result.to_date,
+        result.days_replayed,
+        rules.entry_discount_percent,
+        rules.sma_days,
+        rules.position_isk,
+        result.trades.len(),
+        result.win_rate_percent,
+        result.total_profit,
+        result.total_profit / rules.position_isk * 100.0,
+        result.total_fees,
+        result.max_drawdown,
+        result.max_drawdown_percent,
+        result.buy_and_hold_percent
+    );
+
+    if !result.trades.is_empty() {
+        text.push_str("\nTrades");
+        if result.trades.len() > MAX_REPORTED_TRADES {
+            text.push_str(&format!(" (newest {MAX_REPORTED_TRADES} shown)"));
+        }
+        text.push_str(":\n");
+        for trade in result.trades.iter().rev().take(MAX_REPORTED_TRADES) {
+            text.push_str(&format!(
+                "{} {:.2} -> {} {:.2} ISK, {} days, {}: {:+.2} ISK ({:+.2}%)\n",
+                trade.entry_date,
+                trade.entry_price,
+                trade.exit_date,
+                trade.exit_price,
+                trade.holding_days,
+                trade.exit,
+                trade.profit,
+                trade.return_percent
+            ));
+        }
+    }
+    text.push_str(
+        "\nTrades fill at each day's average price, so spreads, queues and slippage are ignored; treat the \
+         result as a best case.",
+    );
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fees::{TradingSkills, TradingVenue};
+    use crate::test_support::daily_history;
+
+    fn history(prices: &[f64]) -> Vec<MarketHistory> {
+        daily_history("2025-03-01", prices.iter().map(|&average| (average, 100)))
+    }
+
+    /// Fee-free trading, so profits are easy to check by hand
+    fn free_trading() -> TradingProfile {
+        TradingProfile {
+            skills: TradingSkills::max_skills(),
+            venue: TradingVenue::Structure { broker_fee_percent: 0.0 },
+        }
+    }
+
+    #[test]
+    fn test_rules_open_and_close_trades() {
+        let rules = BacktestRules {
+            sma_days: 5,
+            entry_discount_percent: 5.0,
+            take_profit_percent: 10.0,
+            stop_loss_percent: Some(10.0),
+            max_holding_days: None,
+            position_isk: 1_000_000.0,
+        };
+        // Dip to 90 and recover to 100, dip to 90 again and slide to 80
+        let prices = [100.0, 100.0, 100.0, 100.0, 90.0, 100.0, 100.0, 100.0, 90.0, 85.0, 80.0, 80.0];
+        let result = BacktestResult::from_history(&history(&prices), &rules, &free_trading()).unwrap();
+
+        let exits: Vec<(BacktestExit, &str)> = result.trades.iter().map(|t| (t.exit, t.entry_date.as_str())).collect();
+        assert_eq!(exits, vec![(BacktestExit::TakeProfit, "2025-03-05"), (BacktestExit::StopLoss, "2025-03-09")]);
+        let win = &result.trades[0];
+        assert_eq!(win.holding_days, 1);
+        // Sales tax is still charged at a fee-free structure
+        assert!(win.profit > 0.0 && win.profit < 1_000_000.0 / 9.0);
+        assert_eq!(result.win_rate_percent, 50.0);
+        assert!(result.max_drawdown > 100_000.0);
+        assert!((result.buy_and_hold_percent + 20.0).abs() < 1e-9);
+
+        let text = format_backtest("Backtest", &rules, &result);
+        assert!(text.contains("Trades: 2 (50% profitable)"));
+        assert!(text.contains("2025-03-09 90.00 -> 2025-03-11 80.00 ISK, 2 days, stop loss"));
+    }
+
+    #[test]
+    fn test_open_position_and_holding_limit() {
+        let prices = [100.0, 100.0, 100.0, 90.0, 92.0, 93.0, 94.0];
+        let base = BacktestRules {
+            sma_days: 3,
+            position_isk: 1_000_000.0,
+            ..BacktestRules::default()
+        };
+        let result = BacktestResult::from_history(&history(&prices), &base, &free_trading()).unwrap();
+        assert_eq!(result.trades.len(), 1);
+        assert_eq!(result.trades[0].exit, BacktestExit::EndOfData);
+
+        let limited = BacktestRules {
+            max_holding_days: Some(2),
+            ..base
+        };
+        let result = BacktestResult::from_history(&history(&prices), &limited, &free_trading()).unwrap();
+        assert_eq!(result.trades[0].exit, BacktestExit::MaxHolding);
+        assert_eq!(result.trades[0].exit_date, "2025-03-06");
+    }
+
+    #[test]
+    fn test_rejects_bad_rules_and_short_history() {
+        let profile = TradingProfile::default();
+        let prices = history(&[100.0; 10]);
+        let no_target = BacktestRules {
+            take_profit_percent: 0.0,
+            ..BacktestRules::default()
+        };
+        assert!(BacktestResult::from_history(&prices, &no_target, &profile).is_err());
+        assert!(BacktestResult::from_history(&prices, &BacktestRules::default(), &profile).is_err());
+        let short_average = BacktestRules {
+            sma_days: 1,
+            ..BacktestRules::default()
+        };
+        assert!(short_average.validate().is_err());
+    }
+}
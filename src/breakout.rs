@@ -0,0 +1,252 @@
+//! Price channel breakout detection for TraderGrader
+//!
+//! A Donchian channel is the highest and lowest price over the last N days.
+//! A day that closes above the channel high (or below the low) has broken out
+//! of its recent range, the simple momentum signal speculators watch for when
+//! a patch announcement starts moving an item. Daily averages are used for
+//! both the channel and the price: ESI's daily highs and lows include single
+//! fat-fingered or scam trades that would set the channel edges on their own.
+
+use crate::error::{Result, TraderGraderError};
+use crate::market::MarketClient;
+use crate::types::{Breakout, BreakoutDirection, BreakoutReport, MarketHistory};
+
+/// Default days in the channel, the classic 20-day Donchian channel
+pub const DEFAULT_BREAKOUT_WINDOW: usize = 20;
+
+/// Fewest days a channel is drawn from
+pub const MIN_BREAKOUT_WINDOW: usize = 5;
+
+/// Most past breakouts listed in a report, newest first
+const MAX_REPORTED_BREAKOUTS: usize = 10;
+
+/// Volume against the channel average that counts as confirming a breakout
+const CONFIRMING_VOLUME_RATIO: f64 = 1.5;
+
+impl BreakoutReport {
+    /// Flags days in history (any order) whose average price is above the
+    /// highest or below the lowest average of the previous `window` days
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::{BreakoutDirection, BreakoutReport, MarketHistory};
+    ///
+    /// // A month ranging between 100 and 104, then a jump to 110
+    /// let history: Vec<MarketHistory> = (1..=31)
+    ///     .map(|day| MarketHistory {
+    ///         average: if day == 31 { 110.0 } else { 100.0 + (day % 5) as f64 },
+    ///         date: format!("2025-05-{day:02}"),
+    ///         highest: 0.0,
+    ///         lowest: 0.0,
+    ///         order_count: 10,
+    ///         volume: 1_000,
+    ///         extra: Default::default(),
+    ///     })
+    ///     .collect();
+    ///
+    /// let report = BreakoutReport::from_history(&history, 20)?;
+    /// assert_eq!(report.channel_high, 104.0);
+    /// let current = report.current.unwrap();
+    /// assert_eq!(current.direction, BreakoutDirection::Upside);
+    /// assert_eq!(current.date, "2025-05-31");
+    /// # Ok::<(), tradergrader::TraderGraderError>(())
+    /// ```
+    pub fn from_history(history: &[MarketHistory], window: usize) -> Result<Self> {
+        if window < MIN_BREAKOUT_WINDOW {
+            return Err(TraderGraderError::InvalidArgument {
+                field: "window_days".to_string(),
+                reason: format!("must be at least {MIN_BREAKOUT_WINDOW}"),
+            });
+        }
+        let mut days: Vec<&MarketHistory> = history.iter().collect();
+        days.sort_by(|a, b| a.date.cmp(&b.date));
+        if days.len() <= window {
+            return Err(format!("Need more than {window} days of history to detect breakouts").into());
+        }
+
+        let breakouts: Vec<Breakout> = (window..days.len())
+            .filter_map(|t| breakout(days[t], &days[t - window..t]))
+            .collect();
+        let newest = days[days.len() - 1];
+        let (channel_high, channel_low) = channel(&days[days.len() - 1 - window..days.len() - 1]);
+        let current = breakouts.last().filter(|b| b.date == newest.date).cloned();
+
+        Ok(Self {
+            window_days: window,
+            current_price: newest.average,
+            channel_high,
+            channel_low,
+            current,
+            breakouts,
+        })
+    }
+}
+
+/// Highest and lowest daily average of the channel days
+fn channel(days: &[&MarketHistory]) -> (f64, f64) {
+    days.iter().fold((f64::NEG_INFINITY, f64::INFINITY), |(high, low), h| {
+        (high.max(h.average), low.min(h.average))
+    })
+}
+
+/// The day's breakout from the channel before it, if it broke out
+fn breakout(day: &MarketHistory, before: &[&MarketHistory]) -> Option<Breakout> {
+    let (channel_high, channel_low) = channel(before);
+    let (direction, edge) = if day.average > channel_high {
+        (BreakoutDirection::Upside, channel_high)
+    } else if day.average < channel_low {
+        (BreakoutDirection::Downside, channel_low)
+    } else {
+        return None;
+    };
+    let average_volume = before.iter().map(|h| h.volume as f64).sum::<f64>() / before.len() as f64;
+
+    Some(Breakout {
+        date: day.date.clone(),
+        direction,
+        price: day.average,
+        channel_high,
+        channel_low,
+        beyond_percent: if edge > 0.0 { ((day.average - edge) / edge * 100.0).abs() } else { 0.0 },
+        volume_ratio: if average_volume > 0.0 { day.volume as f64 / average_volume } else { 0.0 },
+    })
+}
+
+impl MarketClient {
+    /// Finds days where an item's price broke out of its `window`-day channel
+    pub async fn detect_breakouts(&self, region_id: i32, type_id: i32, window: usize) -> Result<BreakoutReport> {
+        let history = self.fetch_market_history(region_id, type_id).await?;
+        BreakoutReport::from_history(&history, window)
+    }
+
+    /// Generates a formatted breakout report
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// println!("{}", client.get_breakout_summary(10000002, 44992, 20).await?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_breakout_summary(&self, region_id: i32, type_id: i32, window: usize) -> Result<String> {
+        let report = self.detect_breakouts(region_id, type_id, window).await?;
+        Ok(format_breakout_report(
+            &format!("Breakouts for {} in Region {region_id}", self.type_label(type_id).await),
+            &report,
+        ))
+    }
+}
+
+/// Formats the current channel, today's breakout if any, and past breakouts newest first
+fn format_breakout_report(title: &str, report: &BreakoutReport) -> String {
+    let mut text = format!(
+        "{title}:\n{}-day channel: {:.2} - {:.2} ISK, current price {:.2} ISK\n",
+        report.window_days, report.channel_low, report.channel_high, report.current_price
+    );
+    match &report.current {
+        Some(current) => text.push_str(&format!(
+            "Breaking out: {} {:.1}% past the channel{}\n",
+            current.direction,
+            current.beyond_percent,
+            volume_note(current)
+        )),
+        None => text.push_str("No breakout today: the price is inside its channel\n"),
+    }
+
+    if report.breakouts.is_empty() {
+        text.push_str("\nNo breakouts in the history.");
+        return text;
+    }
+    text.push_str(&format!("\n{} breakout days", report.breakouts.len()));
+    if report.breakouts.len() > MAX_REPORTED_BREAKOUTS {
+        text.push_str(&format!(" (newest {MAX_REPORTED_BREAKOUTS} shown)"));
+    }
+    text.push_str(":\n");
+    for breakout in report.breakouts.iter().rev().take(MAX_REPORTED_BREAKOUTS) {
+        text.push_str(&format!(
+            "{} {} {:.2} ISK ({:.1}% past {:.2} - {:.2}){}\n",
+            breakout.date,
+            breakout.direction,
+            breakout.price,
+            breakout.beyond_percent,
+            breakout.channel_low,
+            breakout.channel_high,
+            volume_note(breakout)
+        ));
+    }
+    text.push_str(
+        "\nBreakouts on heavy volume are more likely to run; one on thin volume in a quiet market is often a single \
+         order being filled.",
+    );
+    text
+}
+
+/// Notes when a breakout came on heavy volume
+fn volume_note(breakout: &Breakout) -> String {
+    if breakout.volume_ratio >= CONFIRMING_VOLUME_RATIO {
+        format!(" on {:.1}x usual volume", breakout.volume_ratio)
+    } else {
+        String::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::daily_history;
+
+    fn history(days: impl IntoIterator<Item = (f64, i64)>) -> Vec<MarketHistory> {
+        daily_history("2025-01-01", days)
+            .into_iter()
+            .map(|day| {
+                let average = day.average;
+                day.with_range(average * 0.5, average * 1.5)
+            })
+            .collect()
+    }
+
+    /// Alternating days between 100 and 102
+    fn ranging(day: usize) -> (f64, i64) {
+        if day.is_multiple_of(2) { (100.0, 1_000) } else { (102.0, 1_000) }
+    }
+
+    #[test]
+    fn test_flags_breakouts_both_ways() {
+        let mut days: Vec<(f64, i64)> = (0..10).map(ranging).collect();
+        days.push((105.0, 3_000));
+        days.push((103.0, 1_000));
+        days.push((95.0, 1_000));
+        let report = BreakoutReport::from_history(&history(days), 5).unwrap();
+
+        let found: Vec<(&str, BreakoutDirection)> =
+            report.breakouts.iter().map(|b| (b.date.as_str(), b.direction)).collect();
+        assert_eq!(
+            found,
+            vec![("2025-01-11", BreakoutDirection::Upside), ("2025-01-13", BreakoutDirection::Downside)]
+        );
+        // The 12th is below the 105 set the day before, so inside the channel
+        assert_eq!(report.breakouts[0].volume_ratio, 3.0);
+        assert_eq!((report.channel_high, report.channel_low), (105.0, 100.0));
+        assert_eq!(report.current.as_ref().map(|b| b.beyond_percent), Some(5.0));
+
+        let text = format_breakout_report("Breakouts", &report);
+        assert!(text.contains("Breaking out: Downside 5.0% past the channel"));
+        assert!(text.contains("2025-01-11 Upside 105.00 ISK (2.9% past 100.00 - 102.00) on 3.0x usual volume"));
+    }
+
+    #[test]
+    fn test_quiet_market_and_bad_arguments() {
+        let days = history((0..30).map(ranging));
+        let report = BreakoutReport::from_history(&days, 20).unwrap();
+        assert!(report.breakouts.is_empty());
+        assert!(report.current.is_none());
+        assert!(format_breakout_report("Breakouts", &report).contains("No breakouts in the history."));
+
+        assert!(BreakoutReport::from_history(&days, 4).is_err());
+        assert!(BreakoutReport::from_history(&days, 30).is_err());
+    }
+}
@@ -3,13 +3,20 @@
 //! Provides zero-cost in-memory caching with ESI header respect and optional Redis backend.
 //! The caching system is designed to reduce ESI API calls while respecting EVE Online's
 //! caching guidelines.
+//!
+//! With [`CacheConfig::with_serve_stale`], expired entries are kept for a
+//! while longer so that, when ESI fails, the last known data can be served
+//! instead of an error. Stale reads made during a tool call are collected by
+//! [`track_stale_reads`] so the response can say how old the data is.
 
 use crate::error::Result;
 use async_trait::async_trait;
 use reqwest::header::{HeaderMap, CACHE_CONTROL};
 use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::future::Future;
 use std::sync::Arc;
-use std::fmt::Debug;
+use std::fmt::{self, Debug};
 use std::time::Duration;
 
 /// Cache key for organizing different types of cached data
@@ -66,20 +73,172 @@ impl CacheKey {
         }
     }
 
-    /// Convert cache key to string representation
-    pub fn to_string(&self) -> String {
+    /// Create a new cache key for all orders in a player-owned structure
+    pub fn structure_orders(structure_id: i64) -> Self {
+        Self {
+            data_type: "structure_orders".to_string(),
+            region_id: 0,
+            type_id: None,
+            params: Some(structure_id.to_string()),
+        }
+    }
+
+    /// Create a new cache key for all public contracts in a region
+    pub fn public_contracts(region_id: i32) -> Self {
+        Self {
+            data_type: "public_contracts".to_string(),
+            region_id,
+            type_id: None,
+            params: None,
+        }
+    }
+
+    /// Create a new cache key for the items of one public contract
+    pub fn public_contract_items(contract_id: i64) -> Self {
+        Self {
+            data_type: "public_contract_items".to_string(),
+            region_id: 0,
+            type_id: None,
+            params: Some(contract_id.to_string()),
+        }
+    }
+
+    /// Create a new cache key for static universe data (regions, constellations, ...)
+    pub fn universe(resource: &str, id: i64) -> Self {
+        Self {
+            data_type: "universe".to_string(),
+            region_id: 0,
+            type_id: None,
+            params: Some(format!("{resource}:{id}")),
+        }
+    }
+
+    /// Create a new cache key for an item type's static information
+    pub fn type_info(type_id: i32) -> Self {
+        Self {
+            data_type: "types".to_string(),
+            region_id: 0,
+            type_id: Some(type_id),
+            params: None,
+        }
+    }
+
+    /// Create a new cache key for a stargate route between two systems
+    pub fn route(origin: i32, destination: i32, flag: &str) -> Self {
+        Self {
+            data_type: "route".to_string(),
+            region_id: 0,
+            type_id: None,
+            params: Some(format!("{origin}:{destination}:{flag}")),
+        }
+    }
+
+    /// Create a new cache key for galaxy-wide activity statistics (jumps, kills)
+    pub fn activity(statistic: &str) -> Self {
+        Self {
+            data_type: "activity".to_string(),
+            region_id: 0,
+            type_id: None,
+            params: Some(statistic.to_string()),
+        }
+    }
+
+    /// Create a new cache key for galaxy-wide industry data (cost indices, adjusted prices)
+    pub fn industry(resource: &str) -> Self {
+        Self {
+            data_type: "industry".to_string(),
+            region_id: 0,
+            type_id: None,
+            params: Some(resource.to_string()),
+        }
+    }
+
+    /// Create a new cache key for a corporation's loyalty point store offers
+    pub fn loyalty_store(corporation_id: i32) -> Self {
+        Self {
+            data_type: "loyalty".to_string(),
+            region_id: 0,
+            type_id: None,
+            params: Some(corporation_id.to_string()),
+        }
+    }
+
+    /// Create a new cache key for the insurance levels of every ship
+    pub fn insurance_prices() -> Self {
+        Self {
+            data_type: "insurance".to_string(),
+            region_id: 0,
+            type_id: None,
+            params: None,
+        }
+    }
+
+    /// Create a new cache key for the holder of every solar system
+    pub fn sovereignty_map() -> Self {
+        Self {
+            data_type: "sovereignty".to_string(),
+            region_id: 0,
+            type_id: None,
+            params: None,
+        }
+    }
+
+    /// Create a new cache key for the game server status
+    pub fn server_status() -> Self {
+        Self {
+            data_type: "status".to_string(),
+            region_id: 0,
+            type_id: None,
+            params: None,
+        }
+    }
+
+    /// Create a new cache key for a character's wallet transactions
+    pub fn wallet_transactions(character_id: i64) -> Self {
+        Self {
+            data_type: "wallet_transactions".to_string(),
+            region_id: 0,
+            type_id: None,
+            params: Some(character_id.to_string()),
+        }
+    }
+
+    /// Create a new cache key for a character's own market orders
+    pub fn character_orders(character_id: i64) -> Self {
+        Self {
+            data_type: "character_orders".to_string(),
+            region_id: 0,
+            type_id: None,
+            params: Some(character_id.to_string()),
+        }
+    }
+
+    /// Create a new cache key for a raw ESI route fetched through `esi_get`
+    pub fn esi_get(route: &str) -> Self {
+        Self {
+            data_type: "esi_get".to_string(),
+            region_id: 0,
+            type_id: None,
+            params: Some(route.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for CacheKey {
+    /// Formats the cache key as its string representation used by backends
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match (self.type_id, &self.params) {
             (Some(type_id), Some(params)) => {
-                format!("tradergrader:{}:{}:{}:{}", self.data_type, self.region_id, type_id, params)
+                write!(f, "tradergrader:{}:{}:{}:{}", self.data_type, self.region_id, type_id, params)
             }
             (Some(type_id), None) => {
-                format!("tradergrader:{}:{}:{}", self.data_type, self.region_id, type_id)
+                write!(f, "tradergrader:{}:{}:{}", self.data_type, self.region_id, type_id)
             }
             (None, Some(params)) => {
-                format!("tradergrader:{}:{}:{}", self.data_type, self.region_id, params)
+                write!(f, "tradergrader:{}:{}:{}", self.data_type, self.region_id, params)
             }
             (None, None) => {
-                format!("tradergrader:{}:{}", self.data_type, self.region_id)
+                write!(f, "tradergrader:{}:{}", self.data_type, self.region_id)
             }
         }
     }
@@ -113,6 +272,16 @@ impl<T> CacheItem<T> {
         now < expires_at
     }
 
+    /// How long ago this item was cached
+    pub fn age(&self) -> Duration {
+        (chrono::Utc::now() - self.cached_at).to_std().unwrap_or_default()
+    }
+
+    /// How long this item has been expired, or `None` while it is still valid
+    pub fn expired_for(&self) -> Option<Duration> {
+        self.age().checked_sub(self.ttl).filter(|_| !self.is_valid())
+    }
+
     /// Get remaining TTL for this item
     pub fn remaining_ttl(&self) -> Option<Duration> {
         if self.is_valid() {
@@ -161,14 +330,9 @@ pub trait CacheBackendExt: CacheBackend {
         if let Some(cached_bytes) = self.get_bytes(&key_str).await? {
             match bincode::deserialize::<CacheItem<T>>(&cached_bytes) {
                 Ok(item) => {
-                    // Check if item is still valid
-                    if item.is_valid() {
-                        Ok(Some(item))
-                    } else {
-                        // Item expired, remove it
-                        self.remove(key).await?;
-                        Ok(None)
-                    }
+                    // Expired items stay in the backend until their retention
+                    // ends, for get_allow_stale
+                    Ok(item.is_valid().then_some(item))
                 }
                 Err(_) => {
                     // Deserialization error, remove corrupted item
@@ -181,8 +345,37 @@ pub trait CacheBackendExt: CacheBackend {
         }
     }
 
+    /// Get an item from the cache even if it has expired
+    ///
+    /// Only finds items stored with a grace period by
+    /// [`set_with_grace`](Self::set_with_grace) that hasn't run out yet; check
+    /// [`CacheItem::expired_for`] to tell stale items from fresh ones.
+    async fn get_allow_stale<T>(&self, key: &CacheKey) -> Result<Option<CacheItem<T>>>
+    where
+        T: for<'de> Deserialize<'de> + Send,
+    {
+        match self.get_bytes(&key.to_string()).await? {
+            Some(cached_bytes) => match bincode::deserialize::<CacheItem<T>>(&cached_bytes) {
+                Ok(item) => Ok(Some(item)),
+                Err(_) => {
+                    self.remove(key).await?;
+                    Ok(None)
+                }
+            },
+            None => Ok(None),
+        }
+    }
+
     /// Set an item in the cache with serialization
     async fn set<T>(&self, key: &CacheKey, item: CacheItem<T>) -> Result<()>
+    where
+        T: Serialize + Send,
+    {
+        self.set_with_grace(key, item, Duration::ZERO).await
+    }
+
+    /// Set an item that the backend keeps for `grace` past its TTL, for serving stale
+    async fn set_with_grace<T>(&self, key: &CacheKey, item: CacheItem<T>, grace: Duration) -> Result<()>
     where
         T: Serialize + Send,
     {
@@ -190,7 +383,7 @@ pub trait CacheBackendExt: CacheBackend {
         
         match bincode::serialize(&item) {
             Ok(serialized_bytes) => {
-                self.set_bytes(&key_str, serialized_bytes, item.ttl).await
+                self.set_bytes(&key_str, serialized_bytes, item.ttl.saturating_add(grace)).await
             }
             Err(e) => Err(crate::error::TraderGraderError::CacheError {
                 message: format!("Failed to serialize cache item: {}", e)
@@ -202,6 +395,52 @@ pub trait CacheBackendExt: CacheBackend {
 // Implement the extension trait for all cache backends
 impl<T: CacheBackend + ?Sized> CacheBackendExt for T {}
 
+/// Expired data served because ESI failed
+#[derive(Debug, Clone, PartialEq)]
+pub struct StaleRead {
+    /// Cache key of the data
+    pub key: String,
+    /// How long ago the data was fetched from ESI
+    pub age: Duration,
+    /// The ESI failure that made the cached copy necessary
+    pub reason: String,
+}
+
+tokio::task_local! {
+    static STALE_READS: RefCell<Vec<StaleRead>>;
+}
+
+/// Runs `future`, collecting the stale cache reads made while it runs
+///
+/// # Examples
+///
+/// ```no_run
+/// # use tradergrader::{MarketClient, Result};
+/// use tradergrader::cache::track_stale_reads;
+///
+/// # async fn example() -> Result<()> {
+/// let client = MarketClient::new();
+/// let (orders, stale) = track_stale_reads(client.fetch_market_orders(10000002, Some(34))).await;
+/// for read in stale {
+///     println!("{} is {}s old: {}", read.key, read.age.as_secs(), read.reason);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub async fn track_stale_reads<F: Future>(future: F) -> (F::Output, Vec<StaleRead>) {
+    STALE_READS
+        .scope(RefCell::new(Vec::new()), async {
+            let output = future.await;
+            (output, STALE_READS.with(|reads| reads.take()))
+        })
+        .await
+}
+
+/// Records a stale read for the surrounding [`track_stale_reads`], if any
+pub(crate) fn note_stale_read(read: StaleRead) {
+    let _ = STALE_READS.try_with(|reads| reads.borrow_mut().push(read));
+}
+
 /// Cache statistics for monitoring and debugging
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CacheStats {
@@ -288,6 +527,18 @@ impl EsiHeaderParser {
             "history" => Duration::from_secs(3600),  // 1 hour (daily updates)
             "summary" => Duration::from_secs(180),   // 3 minutes (derived from orders)
             "analysis" => Duration::from_secs(1800), // 30 minutes (expensive calculations)
+            "character_orders" => Duration::from_secs(1200), // 20 minutes (ESI cache timer)
+            "wallet_transactions" => Duration::from_secs(3600), // 1 hour (ESI cache timer)
+            "contracts" => Duration::from_secs(1800), // 30 minutes (ESI cache timer)
+            "activity" => Duration::from_secs(3600),  // 1 hour (ESI cache timer)
+            "industry" => Duration::from_secs(3600),  // 1 hour (ESI cache timer)
+            "loyalty" => Duration::from_secs(3600),   // 1 hour (ESI cache timer)
+            "insurance" => Duration::from_secs(3600), // 1 hour (ESI cache timer)
+            "sovereignty" => Duration::from_secs(3600), // 1 hour (ESI cache timer)
+            "status" => Duration::from_secs(30),      // 30 seconds (ESI cache timer)
+            "universe" => Duration::from_secs(86400), // 1 day (static data)
+            "types" => Duration::from_secs(604800),   // 1 week (changes only with game patches)
+            "locations" => Duration::from_secs(604800), // 1 week (station and structure names rarely change)
             _ => Duration::from_secs(300),           // 5 minutes default
         }
     }
@@ -344,6 +595,8 @@ pub struct CacheConfig {
     pub default_ttl: Duration,
     /// Cache backend type
     pub backend_type: CacheBackendType,
+    /// How long past expiry items may be served when ESI fails (`None` never serves stale data)
+    pub serve_stale: Option<Duration>,
 }
 
 /// Types of cache backends available
@@ -363,6 +616,7 @@ impl Default for CacheConfig {
             max_capacity: 1000,
             default_ttl: Duration::from_secs(3600), // 1 hour
             backend_type: CacheBackendType::InMemory,
+            serve_stale: None,
         }
     }
 }
@@ -388,6 +642,7 @@ impl CacheConfig {
             max_capacity,
             default_ttl,
             backend_type: CacheBackendType::InMemory,
+            serve_stale: None,
         }
     }
 
@@ -399,9 +654,19 @@ impl CacheConfig {
             max_capacity,
             default_ttl,
             backend_type: CacheBackendType::Redis { connection_string },
+            serve_stale: None,
         }
     }
 
+    /// Serve items up to `max_stale` past their expiry when ESI fails
+    ///
+    /// Items are kept in the backend for that much longer, so memory use grows
+    /// with the window.
+    pub fn with_serve_stale(mut self, max_stale: Duration) -> Self {
+        self.serve_stale = Some(max_stale);
+        self
+    }
+
     /// Create a cache backend from this configuration
     pub fn create_backend(&self) -> Result<Option<Arc<dyn CacheBackend>>> {
         if !self.enabled {
@@ -410,7 +675,8 @@ impl CacheConfig {
 
         match &self.backend_type {
             CacheBackendType::InMemory => {
-                let backend = InMemoryCacheBackend::new(self.max_capacity, Some(self.default_ttl));
+                let max_ttl = self.default_ttl.saturating_add(self.serve_stale.unwrap_or_default());
+                let backend = InMemoryCacheBackend::new(self.max_capacity, Some(max_ttl));
                 Ok(Some(Arc::new(backend)))
             }
             #[cfg(feature = "redis-cache")]
@@ -424,25 +690,76 @@ impl CacheConfig {
     }
 }
 
+/// Serialized cache entry stored in moka along with its own TTL
+#[derive(Debug, Clone)]
+struct InMemoryEntry {
+    data: Vec<u8>,
+    ttl: Duration,
+}
+
+/// Moka expiry policy that expires each entry at its own ESI-derived TTL
+///
+/// An optional `max_ttl` caps every entry so the backend-wide TTL from
+/// `CacheConfig` still acts as an upper bound.
+#[derive(Debug, Clone)]
+struct PerEntryExpiry {
+    max_ttl: Option<Duration>,
+}
+
+impl PerEntryExpiry {
+    fn ttl_for(&self, entry: &InMemoryEntry) -> Duration {
+        match self.max_ttl {
+            Some(max_ttl) => entry.ttl.min(max_ttl),
+            None => entry.ttl,
+        }
+    }
+}
+
+impl moka::Expiry<String, InMemoryEntry> for PerEntryExpiry {
+    fn expire_after_create(
+        &self,
+        _key: &String,
+        value: &InMemoryEntry,
+        _created_at: std::time::Instant,
+    ) -> Option<Duration> {
+        Some(self.ttl_for(value))
+    }
+
+    fn expire_after_update(
+        &self,
+        _key: &String,
+        value: &InMemoryEntry,
+        _updated_at: std::time::Instant,
+        _duration_until_expiry: Option<Duration>,
+    ) -> Option<Duration> {
+        // A replaced value restarts the clock with its own TTL
+        Some(self.ttl_for(value))
+    }
+}
+
 /// In-memory cache backend using moka
+///
+/// Every entry expires exactly at the TTL it was stored with (normally derived
+/// from ESI `Cache-Control` headers), so memory is reclaimed eagerly instead of
+/// waiting for a cache-wide TTL.
 #[derive(Debug)]
 pub struct InMemoryCacheBackend {
-    cache: moka::future::Cache<String, Vec<u8>>,
+    cache: moka::future::Cache<String, InMemoryEntry>,
     stats: std::sync::Arc<std::sync::Mutex<CacheStats>>,
 }
 
 impl InMemoryCacheBackend {
     /// Create a new in-memory cache backend
+    ///
+    /// `ttl` is an optional upper bound applied on top of each entry's own TTL.
     pub fn new(max_capacity: u64, ttl: Option<Duration>) -> Self {
-        let mut builder = moka::future::Cache::builder()
-            .max_capacity(max_capacity);
-            
-        if let Some(ttl) = ttl {
-            builder = builder.time_to_live(ttl);
-        }
+        let cache = moka::future::Cache::builder()
+            .max_capacity(max_capacity)
+            .expire_after(PerEntryExpiry { max_ttl: ttl })
+            .build();
         
         Self {
-            cache: builder.build(),
+            cache,
             stats: std::sync::Arc::new(std::sync::Mutex::new(CacheStats {
                 hits: 0,
                 misses: 0,
@@ -453,14 +770,6 @@ impl InMemoryCacheBackend {
         }
     }
     
-    /// Create a default in-memory cache with reasonable settings
-    pub fn default() -> Self {
-        Self::new(
-            1000,                           // Max 1000 items
-            Some(Duration::from_secs(3600)) // 1 hour default TTL
-        )
-    }
-    
     /// Update cache statistics
     fn update_stats(&self, hit: bool) {
         if let Ok(mut stats) = self.stats.lock() {
@@ -480,20 +789,30 @@ impl InMemoryCacheBackend {
     }
 }
 
+impl Default for InMemoryCacheBackend {
+    /// Create a default in-memory cache with reasonable settings
+    fn default() -> Self {
+        Self::new(
+            1000,                           // Max 1000 items
+            Some(Duration::from_secs(3600)) // 1 hour default TTL
+        )
+    }
+}
+
 #[async_trait]
 impl CacheBackend for InMemoryCacheBackend {
     async fn get_bytes(&self, key: &str) -> Result<Option<Vec<u8>>> {
-        if let Some(cached_bytes) = self.cache.get(key).await {
+        if let Some(entry) = self.cache.get(key).await {
             self.update_stats(true);
-            Ok(Some(cached_bytes))
+            Ok(Some(entry.data))
         } else {
             self.update_stats(false);
             Ok(None)
         }
     }
 
-    async fn set_bytes(&self, key: &str, data: Vec<u8>, _ttl: Duration) -> Result<()> {
-        self.cache.insert(key.to_string(), data).await;
+    async fn set_bytes(&self, key: &str, data: Vec<u8>, ttl: Duration) -> Result<()> {
+        self.cache.insert(key.to_string(), InMemoryEntry { data, ttl }).await;
         Ok(())
     }
 
@@ -639,6 +958,10 @@ mod tests {
             EsiHeaderParser::recommended_ttl_for_data_type("history"),
             Duration::from_secs(3600)
         );
+        assert_eq!(
+            EsiHeaderParser::recommended_ttl_for_data_type("types"),
+            Duration::from_secs(604800)
+        );
         assert_eq!(
             EsiHeaderParser::recommended_ttl_for_data_type("unknown"),
             Duration::from_secs(300)
@@ -761,6 +1084,60 @@ mod tests {
         assert!(retrieved.is_none());
     }
 
+    #[tokio::test]
+    async fn test_stale_items_are_retained_for_their_grace() {
+        let cache = InMemoryCacheBackend::new(100, None);
+        let key = CacheKey::market_orders(10000002, Some(34));
+        let item = CacheItem {
+            data: "last known".to_string(),
+            cached_at: chrono::Utc::now() - chrono::Duration::seconds(90),
+            ttl: Duration::from_secs(60),
+        };
+        cache.set_with_grace(&key, item, Duration::from_secs(3600)).await.expect("Should set item");
+
+        assert!(cache.get::<String>(&key).await.expect("Should read").is_none());
+        let stale = cache.get_allow_stale::<String>(&key).await.expect("Should read").expect("Should be retained");
+        assert_eq!(stale.data, "last known");
+        let expired_for = stale.expired_for().expect("Should be expired");
+        assert!(expired_for >= Duration::from_secs(29) && expired_for <= Duration::from_secs(31));
+        assert!(stale.age() >= Duration::from_secs(89));
+
+        let (_, reads) = track_stale_reads(async {
+            note_stale_read(StaleRead {
+                key: key.to_string(),
+                age: stale.age(),
+                reason: "timeout".to_string(),
+            })
+        })
+        .await;
+        assert_eq!(reads.len(), 1);
+        assert_eq!(reads[0].reason, "timeout");
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_cache_per_entry_ttl() {
+        let cache = InMemoryCacheBackend::new(100, Some(Duration::from_secs(60)));
+
+        cache.set_bytes("short", vec![1], Duration::from_millis(100)).await.expect("Should set short entry");
+        cache.set_bytes("long", vec![2], Duration::from_secs(30)).await.expect("Should set long entry");
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+
+        // The short-lived entry is gone from the backend itself, not just filtered on read
+        assert!(cache.get_bytes("short").await.expect("Should read").is_none());
+        assert_eq!(cache.get_bytes("long").await.expect("Should read"), Some(vec![2]));
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_cache_max_ttl_caps_entries() {
+        let cache = InMemoryCacheBackend::new(100, Some(Duration::from_millis(100)));
+
+        cache.set_bytes("capped", vec![1], Duration::from_secs(3600)).await.expect("Should set entry");
+        tokio::time::sleep(Duration::from_millis(250)).await;
+
+        assert!(cache.get_bytes("capped").await.expect("Should read").is_none());
+    }
+
     #[tokio::test]
     async fn test_in_memory_cache_health_check() {
         let cache = InMemoryCacheBackend::new(100, Some(Duration::from_secs(60)));
@@ -0,0 +1,768 @@
+//! Configuration loading for TraderGrader
+//!
+//! Settings come from three layers, each overriding the one before: built-in
+//! defaults, a TOML file, and `TRADERGRADER_*` environment variables. The file
+//! is `tradergrader.toml` in the working directory unless `TRADERGRADER_CONFIG`
+//! (or `--config`) names another; a missing default file is not an error.
+//!
+//! ```toml
+//! [cache]
+//! enabled = true
+//! backend = "memory"        # or "redis" with redis_url
+//! max_capacity = 5000
+//! default_ttl_secs = 3600
+//! serve_stale_secs = 3600   # serve data up to an hour past expiry when ESI fails
+//!
+//! [rate_limit]
+//! requests_per_second = 50
+//! max_retries = 5
+//! max_concurrent_requests = 8   # pages of a multi-page pull fetched at once
+//!
+//! [rate_limit.endpoints]    # requests per second per endpoint class (default: the global limit)
+//! history = 20
+//! universe = 50
+//!
+//! [esi]
+//! user_agent = "MyCorpTools/1.0"
+//! contact = "ops@example.com"
+//!
+//! [limits]
+//! tool_budget_bytes = 33554432
+//!
+//! [trend]                  # when price analysis calls a trend, and a strong one
+//! change_percent = 2.0
+//! strong_change_percent = 5.0
+//! volume_confirmation_percent = 10.0
+//!
+//! [courier]                # reward guidance of price_courier_contract
+//! isk_per_jump = 250000
+//! isk_per_m3_per_jump = 1.0
+//! collateral_percent = 1.0
+//! low_sec_multiplier = 2.0
+//! null_sec_multiplier = 3.0
+//! minimum_reward = 1000000
+//!
+//! [prefetch]               # keep these region_id:type_id pairs warm from startup
+//! targets = ["10000002:34", "10000002:35"]
+//! lead_time_secs = 10
+//!
+//! [snapshot]               # rebuild a basket-by-hub market snapshot in the background
+//! enabled = true
+//! type_ids = [34, 35, 36, 37, 44992]
+//! hubs = ["Jita", "Amarr", "Dodixie"]
+//! refresh_secs = 900
+//!
+//! [server]
+//! log_level = "info"
+//! sde_path = "/srv/sde/sqlite-latest.sqlite"
+//! esi_allowlist = ["/markets/", "/universe/"]
+//! watchlist_path = "/srv/tradergrader/watchlist.json"
+//! portfolio_path = "/srv/tradergrader/portfolio.json"
+//! journal_path = "/srv/tradergrader/journal.json"
+//! history_archive_path = "/srv/tradergrader/history.sqlite"
+//!
+//! [locations.home]         # alias usable wherever a region, system or station ID is
+//! region_id = 10000002
+//! system_id = 30000144
+//! station_id = 1028858195912
+//! ```
+//!
+//! | Variable | Setting |
+//! |---|---|
+//! | `TRADERGRADER_CACHE_ENABLED` | `cache.enabled` |
+//! | `TRADERGRADER_CACHE_BACKEND` | `cache.backend` |
+//! | `TRADERGRADER_REDIS_URL` | `cache.redis_url` |
+//! | `TRADERGRADER_CACHE_MAX_CAPACITY` | `cache.max_capacity` |
+//! | `TRADERGRADER_CACHE_TTL_SECS` | `cache.default_ttl_secs` |
+//! | `TRADERGRADER_SERVE_STALE_SECS` | `cache.serve_stale_secs` |
+//! | `TRADERGRADER_RATE_LIMIT_RPS` | `rate_limit.requests_per_second` |
+//! | `TRADERGRADER_MAX_RETRIES` | `rate_limit.max_retries` |
+//! | `TRADERGRADER_ERROR_BUDGET_THRESHOLD` | `rate_limit.error_budget_threshold` |
+//! | `TRADERGRADER_MAX_CONCURRENT_REQUESTS` | `rate_limit.max_concurrent_requests` |
+//! | `TRADERGRADER_ENDPOINT_LIMITS` | `rate_limit.endpoints` (e.g. `history=20,universe=50`) |
+//! | `TRADERGRADER_USER_AGENT` | `esi.user_agent` |
+//! | `TRADERGRADER_CONTACT` | `esi.contact` |
+//! | `TRADERGRADER_ESI_VERSION` | `esi.version` |
+//! | `TRADERGRADER_ESI_COMPATIBILITY_DATE` | `esi.compatibility_date` |
+//! | `TRADERGRADER_MAX_RESPONSE_BYTES` | `limits.max_response_bytes` |
+//! | `TRADERGRADER_MAX_CACHED_ITEM_BYTES` | `limits.max_cached_item_bytes` |
+//! | `TRADERGRADER_TOOL_BUDGET_BYTES` | `limits.tool_budget_bytes` |
+//! | `TRADERGRADER_TREND_CHANGE_PERCENT` | `trend.change_percent` |
+//! | `TRADERGRADER_TREND_STRONG_CHANGE_PERCENT` | `trend.strong_change_percent` |
+//! | `TRADERGRADER_TREND_VOLUME_CONFIRMATION_PERCENT` | `trend.volume_confirmation_percent` |
+//! | `TRADERGRADER_COURIER_ISK_PER_JUMP` | `courier.isk_per_jump` |
+//! | `TRADERGRADER_COURIER_ISK_PER_M3_PER_JUMP` | `courier.isk_per_m3_per_jump` |
+//! | `TRADERGRADER_COURIER_COLLATERAL_PERCENT` | `courier.collateral_percent` |
+//! | `TRADERGRADER_PREFETCH` | `prefetch.targets` (e.g. `10000002:34,10000002:35`) |
+//! | `TRADERGRADER_PREFETCH_LEAD_TIME_SECS` | `prefetch.lead_time_secs` |
+//! | `TRADERGRADER_SNAPSHOT_ENABLED` | `snapshot.enabled` |
+//! | `TRADERGRADER_SNAPSHOT_REFRESH_SECS` | `snapshot.refresh_secs` |
+//! | `TRADERGRADER_SERVER_NAME` | `server.name` |
+//! | `TRADERGRADER_LOG_LEVEL` | `server.log_level` |
+//! | `TRADERGRADER_SDE_PATH` | `server.sde_path` |
+//! | `TRADERGRADER_ESI_ALLOWLIST` | `server.esi_allowlist` (comma-separated) |
+//! | `TRADERGRADER_WATCHLIST_PATH` | `server.watchlist_path` |
+//! | `TRADERGRADER_PORTFOLIO_PATH` | `server.portfolio_path` |
+//! | `TRADERGRADER_JOURNAL_PATH` | `server.journal_path` |
+//! | `TRADERGRADER_HISTORY_ARCHIVE_PATH` | `server.history_archive_path` |
+//! | `TRADERGRADER_LOCATIONS` | `locations` (e.g. `home=10000002:30000144,staging=10000060`) |
+
+use crate::cache::{CacheBackendType, CacheConfig};
+use crate::courier::CourierRates;
+use crate::error::{Result, TraderGraderError};
+use crate::esi::EsiConfig;
+use crate::limits::ResponseLimits;
+use crate::locations::{LocationPreset, LocationRegistry};
+use crate::logging::LogLevel;
+use crate::passthrough::EsiAllowlist;
+use crate::prefetch::{PrefetchConfig, PrefetchTarget};
+use crate::rate_limit::RateLimitConfig;
+use crate::snapshot::SnapshotConfig;
+use crate::trend::TrendConfig;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Config file read when no other is named
+pub const DEFAULT_CONFIG_FILE: &str = "tradergrader.toml";
+
+/// Everything needed to build the MCP server
+#[derive(Debug, Clone, Default)]
+pub struct TraderGraderConfig {
+    pub cache: CacheConfig,
+    pub rate_limit: RateLimitConfig,
+    pub esi: EsiConfig,
+    pub limits: ResponseLimits,
+    /// Rules behind the trend label of price analysis
+    pub trend: TrendConfig,
+    /// Rates courier contracts are priced at unless a quote overrides them
+    pub courier: CourierRates,
+    /// Targets kept warm from startup and by `start_prefetch` without arguments
+    pub prefetch: PrefetchConfig,
+    /// Basket and hubs of the market snapshot, and whether it is rebuilt from startup
+    pub snapshot: SnapshotConfig,
+    pub server: ServerOptions,
+    /// Built-in location aliases plus any configured ones
+    pub locations: LocationRegistry,
+}
+
+/// Settings of the MCP server itself
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerOptions {
+    /// Name reported to MCP clients on initialize
+    pub name: String,
+    /// Version reported to MCP clients on initialize
+    pub version: String,
+    /// Initial MCP log notification level
+    pub log_level: LogLevel,
+    /// Local SDE (CSV directory or SQLite file) for static lookups
+    pub sde_path: Option<PathBuf>,
+    /// Route prefixes reachable through `esi_get`
+    pub esi_allowlist: EsiAllowlist,
+    /// File the watchlist served as MCP resources is saved to; kept in memory when unset
+    pub watchlist_path: Option<PathBuf>,
+    /// File portfolio positions are saved to; kept in memory when unset
+    pub portfolio_path: Option<PathBuf>,
+    /// File the trade journal is saved to; kept in memory when unset
+    pub journal_path: Option<PathBuf>,
+    /// SQLite file daily history is archived to beyond ESI's 13 months; not archived when unset
+    ///
+    /// Needs a build with the `history-archive` feature.
+    pub history_archive_path: Option<PathBuf>,
+}
+
+impl Default for ServerOptions {
+    fn default() -> Self {
+        Self {
+            name: "TraderGrader".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            log_level: LogLevel::default(),
+            sde_path: None,
+            esi_allowlist: EsiAllowlist::default(),
+            watchlist_path: None,
+            portfolio_path: None,
+            journal_path: None,
+            history_archive_path: None,
+        }
+    }
+}
+
+impl TraderGraderConfig {
+    /// Loads defaults, then the config file, then environment variables
+    ///
+    /// `path` overrides `TRADERGRADER_CONFIG`. An explicitly named file must
+    /// exist; the default `tradergrader.toml` is skipped when absent.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tradergrader::config::TraderGraderConfig;
+    ///
+    /// let config = TraderGraderConfig::load(None)?;
+    /// println!("{} requests/s", config.rate_limit.requests_per_second);
+    /// # Ok::<(), tradergrader::TraderGraderError>(())
+    /// ```
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let named = path
+            .map(Path::to_path_buf)
+            .or_else(|| std::env::var("TRADERGRADER_CONFIG").ok().filter(|p| !p.trim().is_empty()).map(PathBuf::from));
+        let mut config = Self::default();
+        match named {
+            Some(path) => config.apply_file(&path)?,
+            None if Path::new(DEFAULT_CONFIG_FILE).is_file() => config.apply_file(Path::new(DEFAULT_CONFIG_FILE))?,
+            None => {}
+        }
+        config.apply_env(|name| std::env::var(name).ok())?;
+        Ok(config)
+    }
+
+    /// Parses a TOML document over the defaults
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::config::TraderGraderConfig;
+    ///
+    /// let config = TraderGraderConfig::from_toml_str("[rate_limit]\nrequests_per_second = 20\n")?;
+    /// assert_eq!(config.rate_limit.requests_per_second, 20);
+    /// assert!(TraderGraderConfig::from_toml_str("[rate_limit]\nrequests_per_secnod = 20\n").is_err());
+    /// # Ok::<(), tradergrader::TraderGraderError>(())
+    /// ```
+    pub fn from_toml_str(text: &str) -> Result<Self> {
+        let mut config = Self::default();
+        config.apply_toml(text, "config")?;
+        Ok(config)
+    }
+
+    fn apply_file(&mut self, path: &Path) -> Result<()> {
+        let text = std::fs::read_to_string(path).map_err(|e| config_error(path.display(), e))?;
+        self.apply_toml(&text, &path.display().to_string())
+    }
+
+    fn apply_toml(&mut self, text: &str, source: &str) -> Result<()> {
+        let file: ConfigFile = toml::from_str(text).map_err(|e| config_error(source, e))?;
+        self.apply(file)
+    }
+
+    /// Applies `TRADERGRADER_*` variables looked up through `var`
+    pub fn apply_env(&mut self, var: impl Fn(&str) -> Option<String>) -> Result<()> {
+        let var = |name: &str| var(name).filter(|value| !value.trim().is_empty());
+        let parsed = |name: &str| -> Result<Option<u64>> {
+            var(name)
+                .map(|value| value.trim().parse::<u64>().map_err(|e| config_error(name, e)))
+                .transpose()
+        };
+        let parsed_float = |name: &str| -> Result<Option<f64>> {
+            var(name)
+                .map(|value| value.trim().parse::<f64>().map_err(|e| config_error(name, e)))
+                .transpose()
+        };
+        let overrides = ConfigFile {
+            cache: CacheSection {
+                enabled: var("TRADERGRADER_CACHE_ENABLED").map(|v| matches!(v.trim(), "1" | "true" | "yes")),
+                backend: var("TRADERGRADER_CACHE_BACKEND"),
+                redis_url: var("TRADERGRADER_REDIS_URL"),
+                max_capacity: parsed("TRADERGRADER_CACHE_MAX_CAPACITY")?,
+                default_ttl_secs: parsed("TRADERGRADER_CACHE_TTL_SECS")?,
+                serve_stale_secs: parsed("TRADERGRADER_SERVE_STALE_SECS")?,
+            },
+            rate_limit: RateLimitSection {
+                requests_per_second: parsed("TRADERGRADER_RATE_LIMIT_RPS")?.map(|v| v as u32),
+                max_retries: parsed("TRADERGRADER_MAX_RETRIES")?.map(|v| v as u32),
+                error_budget_threshold: parsed("TRADERGRADER_ERROR_BUDGET_THRESHOLD")?.map(|v| v as u32),
+                max_concurrent_requests: parsed("TRADERGRADER_MAX_CONCURRENT_REQUESTS")?.map(|v| v as usize),
+                endpoints: var("TRADERGRADER_ENDPOINT_LIMITS").map(|list| parse_endpoint_limits(&list)).transpose()?,
+                ..RateLimitSection::default()
+            },
+            esi: EsiSection {
+                user_agent: var("TRADERGRADER_USER_AGENT"),
+                contact: var("TRADERGRADER_CONTACT"),
+                version: var("TRADERGRADER_ESI_VERSION"),
+                compatibility_date: var("TRADERGRADER_ESI_COMPATIBILITY_DATE"),
+                ..EsiSection::default()
+            },
+            limits: LimitsSection {
+                max_response_bytes: parsed("TRADERGRADER_MAX_RESPONSE_BYTES")?.map(|v| v as usize),
+                max_cached_item_bytes: parsed("TRADERGRADER_MAX_CACHED_ITEM_BYTES")?.map(|v| v as usize),
+                tool_budget_bytes: parsed("TRADERGRADER_TOOL_BUDGET_BYTES")?.map(|v| v as usize),
+            },
+            trend: TrendSection {
+                change_percent: parsed_float("TRADERGRADER_TREND_CHANGE_PERCENT")?,
+                strong_change_percent: parsed_float("TRADERGRADER_TREND_STRONG_CHANGE_PERCENT")?,
+                volume_confirmation_percent: parsed_float("TRADERGRADER_TREND_VOLUME_CONFIRMATION_PERCENT")?,
+                ..TrendSection::default()
+            },
+            courier: CourierSection {
+                isk_per_jump: parsed_float("TRADERGRADER_COURIER_ISK_PER_JUMP")?,
+                isk_per_m3_per_jump: parsed_float("TRADERGRADER_COURIER_ISK_PER_M3_PER_JUMP")?,
+                collateral_percent: parsed_float("TRADERGRADER_COURIER_COLLATERAL_PERCENT")?,
+                ..CourierSection::default()
+            },
+            prefetch: PrefetchSection {
+                targets: var("TRADERGRADER_PREFETCH")
+                    .map(|list| list.split(',').filter(|t| !t.trim().is_empty()).map(str::to_string).collect()),
+                lead_time_secs: parsed("TRADERGRADER_PREFETCH_LEAD_TIME_SECS")?,
+                ..PrefetchSection::default()
+            },
+            snapshot: SnapshotSection {
+                enabled: var("TRADERGRADER_SNAPSHOT_ENABLED").map(|v| matches!(v.trim(), "1" | "true" | "yes")),
+                refresh_secs: parsed("TRADERGRADER_SNAPSHOT_REFRESH_SECS")?,
+                ..SnapshotSection::default()
+            },
+            server: ServerSection {
+                name: var("TRADERGRADER_SERVER_NAME"),
+                log_level: var("TRADERGRADER_LOG_LEVEL"),
+                sde_path: var("TRADERGRADER_SDE_PATH").map(|p| PathBuf::from(p.trim())),
+                esi_allowlist: var("TRADERGRADER_ESI_ALLOWLIST")
+                    .map(|list| list.split(',').filter(|p| !p.trim().is_empty()).map(str::to_string).collect()),
+                watchlist_path: var("TRADERGRADER_WATCHLIST_PATH").map(|p| PathBuf::from(p.trim())),
+                portfolio_path: var("TRADERGRADER_PORTFOLIO_PATH").map(|p| PathBuf::from(p.trim())),
+                journal_path: var("TRADERGRADER_JOURNAL_PATH").map(|p| PathBuf::from(p.trim())),
+                history_archive_path: var("TRADERGRADER_HISTORY_ARCHIVE_PATH").map(|p| PathBuf::from(p.trim())),
+            },
+            locations: var("TRADERGRADER_LOCATIONS").map(|list| parse_locations(&list)).transpose()?.unwrap_or_default(),
+        };
+        self.apply(overrides)
+    }
+
+    /// Overwrites every setting the layer sets
+    fn apply(&mut self, layer: ConfigFile) -> Result<()> {
+        let ConfigFile {
+            cache,
+            rate_limit,
+            esi,
+            limits,
+            trend,
+            courier,
+            prefetch,
+            snapshot,
+            server,
+            locations,
+        } = layer;
+
+        if let Some(enabled) = cache.enabled {
+            self.cache.enabled = enabled;
+        }
+        if let Some(max_capacity) = cache.max_capacity {
+            self.cache.max_capacity = max_capacity;
+        }
+        if let Some(secs) = cache.default_ttl_secs {
+            self.cache.default_ttl = Duration::from_secs(secs);
+        }
+        if let Some(secs) = cache.serve_stale_secs {
+            self.cache.serve_stale = (secs > 0).then(|| Duration::from_secs(secs));
+        }
+        if let Some(backend) = cache.backend {
+            self.cache.backend_type = cache_backend(&backend, cache.redis_url)?;
+        } else if let Some(url) = cache.redis_url {
+            self.cache.backend_type = cache_backend("redis", Some(url))?;
+        }
+
+        set(&mut self.rate_limit.requests_per_second, rate_limit.requests_per_second);
+        set(&mut self.rate_limit.max_retries, rate_limit.max_retries);
+        set(&mut self.rate_limit.base_delay_ms, rate_limit.base_delay_ms);
+        set(&mut self.rate_limit.max_delay_seconds, rate_limit.max_delay_seconds);
+        set(&mut self.rate_limit.error_budget_threshold, rate_limit.error_budget_threshold);
+        set(&mut self.rate_limit.jitter, rate_limit.jitter);
+        set(&mut self.rate_limit.max_concurrent_requests, rate_limit.max_concurrent_requests);
+        for (class, requests_per_second) in rate_limit.endpoints.unwrap_or_default() {
+            let class = class.parse().map_err(|e| config_error("rate_limit.endpoints", e))?;
+            self.rate_limit.endpoint_limits.insert(class, requests_per_second);
+        }
+
+        set(&mut self.esi.user_agent, esi.user_agent);
+        set(&mut self.esi.esi_version, esi.version);
+        if esi.contact.is_some() {
+            self.esi.contact = esi.contact;
+        }
+        if esi.compatibility_date.is_some() {
+            self.esi.compatibility_date = esi.compatibility_date;
+        }
+        set(&mut self.esi.connect_timeout, esi.connect_timeout_secs.map(Duration::from_secs));
+        set(&mut self.esi.request_timeout, esi.request_timeout_secs.map(Duration::from_secs));
+        set(&mut self.esi.prewarm_connections, esi.prewarm_connections);
+
+        set(&mut self.limits.max_response_bytes, limits.max_response_bytes);
+        set(&mut self.limits.max_cached_item_bytes, limits.max_cached_item_bytes);
+        set(&mut self.limits.tool_budget_bytes, limits.tool_budget_bytes);
+
+        set(&mut self.trend.window_days, trend.window_days);
+        set(&mut self.trend.change_percent, trend.change_percent);
+        set(&mut self.trend.strong_change_percent, trend.strong_change_percent);
+        set(&mut self.trend.volume_confirmation_percent, trend.volume_confirmation_percent);
+        set(&mut self.trend.require_volume_confirmation, trend.require_volume_confirmation);
+        self.trend.validate().map_err(|e| config_error("trend", e))?;
+
+        set(&mut self.courier.isk_per_jump, courier.isk_per_jump);
+        set(&mut self.courier.isk_per_m3_per_jump, courier.isk_per_m3_per_jump);
+        set(&mut self.courier.collateral_percent, courier.collateral_percent);
+        set(&mut self.courier.low_sec_multiplier, courier.low_sec_multiplier);
+        set(&mut self.courier.null_sec_multiplier, courier.null_sec_multiplier);
+        set(&mut self.courier.minimum_reward, courier.minimum_reward);
+        self.courier.validate().map_err(|e| config_error("courier", e))?;
+
+        if let Some(targets) = prefetch.targets {
+            self.prefetch.targets = targets
+                .iter()
+                .map(|target| target.parse::<PrefetchTarget>())
+                .collect::<Result<_>>()
+                .map_err(|e| config_error("prefetch.targets", e))?;
+        }
+        set(&mut self.prefetch.include_history, prefetch.include_history);
+        set(&mut self.prefetch.lead_time, prefetch.lead_time_secs.map(Duration::from_secs));
+        set(&mut self.prefetch.max_interval, prefetch.max_interval_secs.map(Duration::from_secs));
+
+        set(&mut self.snapshot.enabled, snapshot.enabled);
+        set(&mut self.snapshot.type_ids, snapshot.type_ids);
+        if let Some(hubs) = snapshot.hubs {
+            self.snapshot.hubs = hubs
+                .iter()
+                .map(|hub| hub.parse())
+                .collect::<std::result::Result<_, _>>()
+                .map_err(|e| config_error("snapshot.hubs", e))?;
+        }
+        set(&mut self.snapshot.refresh_interval, snapshot.refresh_secs.map(Duration::from_secs));
+        self.snapshot.validate().map_err(|e| config_error("snapshot", e))?;
+
+        set(&mut self.server.name, server.name);
+        if let Some(level) = server.log_level {
+            self.server.log_level = level.parse().map_err(|e| config_error("server.log_level", e))?;
+        }
+        if server.sde_path.is_some() {
+            self.server.sde_path = server.sde_path;
+        }
+        if server.watchlist_path.is_some() {
+            self.server.watchlist_path = server.watchlist_path;
+        }
+        if server.portfolio_path.is_some() {
+            self.server.portfolio_path = server.portfolio_path;
+        }
+        if server.journal_path.is_some() {
+            self.server.journal_path = server.journal_path;
+        }
+        if server.history_archive_path.is_some() {
+            self.server.history_archive_path = server.history_archive_path;
+        }
+        if let Some(prefixes) = server.esi_allowlist {
+            self.server.esi_allowlist = EsiAllowlist::new(prefixes);
+        }
+
+        for (alias, preset) in locations {
+            self.locations.insert(&alias, preset);
+        }
+        Ok(())
+    }
+}
+
+fn set<T>(slot: &mut T, value: Option<T>) {
+    if let Some(value) = value {
+        *slot = value;
+    }
+}
+
+/// Parses `class=rps` pairs separated by commas
+fn parse_endpoint_limits(list: &str) -> Result<HashMap<String, u32>> {
+    list.split(',')
+        .filter(|pair| !pair.trim().is_empty())
+        .map(|pair| {
+            let (class, rate) = pair.split_once('=').ok_or_else(|| {
+                config_error("TRADERGRADER_ENDPOINT_LIMITS", format!("expected class=requests_per_second, got '{pair}'"))
+            })?;
+            let rate = rate.trim().parse().map_err(|e| config_error("TRADERGRADER_ENDPOINT_LIMITS", e))?;
+            Ok((class.trim().to_string(), rate))
+        })
+        .collect()
+}
+
+/// Parses `alias=region_id[:system_id[:station_id]]` pairs separated by commas
+fn parse_locations(list: &str) -> Result<HashMap<String, LocationPreset>> {
+    list.split(',')
+        .filter(|pair| !pair.trim().is_empty())
+        .map(|pair| {
+            let (alias, preset) = pair.split_once('=').ok_or_else(|| {
+                config_error("TRADERGRADER_LOCATIONS", format!("expected alias=region_id[:system_id[:station_id]], got '{pair}'"))
+            })?;
+            let preset = preset.parse().map_err(|e| config_error("TRADERGRADER_LOCATIONS", e))?;
+            Ok((alias.trim().to_string(), preset))
+        })
+        .collect()
+}
+
+fn cache_backend(name: &str, redis_url: Option<String>) -> Result<CacheBackendType> {
+    match name.trim().to_ascii_lowercase().as_str() {
+        "memory" | "in_memory" => Ok(CacheBackendType::InMemory),
+        #[cfg(feature = "redis-cache")]
+        "redis" => match redis_url {
+            Some(connection_string) => Ok(CacheBackendType::Redis { connection_string }),
+            None => Err(config_error("cache.backend", "the redis backend needs redis_url")),
+        },
+        other => {
+            let _ = redis_url;
+            Err(config_error("cache.backend", format!("unknown or disabled cache backend '{other}'")))
+        }
+    }
+}
+
+fn config_error(source: impl std::fmt::Display, error: impl std::fmt::Display) -> TraderGraderError {
+    TraderGraderError::InternalError(format!("Invalid configuration in {source}: {error}"))
+}
+
+/// One configuration layer, as written in the TOML file
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    cache: CacheSection,
+    rate_limit: RateLimitSection,
+    esi: EsiSection,
+    limits: LimitsSection,
+    trend: TrendSection,
+    courier: CourierSection,
+    prefetch: PrefetchSection,
+    snapshot: SnapshotSection,
+    server: ServerSection,
+    locations: HashMap<String, LocationPreset>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct CacheSection {
+    enabled: Option<bool>,
+    backend: Option<String>,
+    redis_url: Option<String>,
+    max_capacity: Option<u64>,
+    default_ttl_secs: Option<u64>,
+    serve_stale_secs: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RateLimitSection {
+    requests_per_second: Option<u32>,
+    max_retries: Option<u32>,
+    base_delay_ms: Option<u64>,
+    max_delay_seconds: Option<u64>,
+    error_budget_threshold: Option<u32>,
+    jitter: Option<f64>,
+    max_concurrent_requests: Option<usize>,
+    endpoints: Option<HashMap<String, u32>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct EsiSection {
+    user_agent: Option<String>,
+    contact: Option<String>,
+    version: Option<String>,
+    compatibility_date: Option<String>,
+    connect_timeout_secs: Option<u64>,
+    request_timeout_secs: Option<u64>,
+    prewarm_connections: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct LimitsSection {
+    max_response_bytes: Option<usize>,
+    max_cached_item_bytes: Option<usize>,
+    tool_budget_bytes: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct TrendSection {
+    window_days: Option<u32>,
+    change_percent: Option<f64>,
+    strong_change_percent: Option<f64>,
+    volume_confirmation_percent: Option<f64>,
+    require_volume_confirmation: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct CourierSection {
+    isk_per_jump: Option<f64>,
+    isk_per_m3_per_jump: Option<f64>,
+    collateral_percent: Option<f64>,
+    low_sec_multiplier: Option<f64>,
+    null_sec_multiplier: Option<f64>,
+    minimum_reward: Option<f64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct PrefetchSection {
+    targets: Option<Vec<String>>,
+    include_history: Option<bool>,
+    lead_time_secs: Option<u64>,
+    max_interval_secs: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct SnapshotSection {
+    enabled: Option<bool>,
+    type_ids: Option<Vec<i32>>,
+    hubs: Option<Vec<String>>,
+    refresh_secs: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ServerSection {
+    name: Option<String>,
+    log_level: Option<String>,
+    sde_path: Option<PathBuf>,
+    esi_allowlist: Option<Vec<String>>,
+    watchlist_path: Option<PathBuf>,
+    portfolio_path: Option<PathBuf>,
+    journal_path: Option<PathBuf>,
+    history_archive_path: Option<PathBuf>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hubs::TradeHub;
+    use crate::rate_limit::EndpointClass;
+
+    const SAMPLE: &str = r#"
+        [cache]
+        max_capacity = 5000
+        default_ttl_secs = 600
+        serve_stale_secs = 1800
+
+        [rate_limit]
+        requests_per_second = 50
+        max_retries = 5
+        max_concurrent_requests = 4
+
+        [rate_limit.endpoints]
+        universe = 30
+
+        [esi]
+        user_agent = "CorpTools/2.0"
+        request_timeout_secs = 20
+
+        [limits]
+        tool_budget_bytes = 1024
+
+        [trend]
+        strong_change_percent = 8.0
+        require_volume_confirmation = false
+
+        [courier]
+        isk_per_jump = 400000
+        null_sec_multiplier = 5.0
+
+        [prefetch]
+        targets = ["10000002:34"]
+        include_history = false
+
+        [snapshot]
+        type_ids = [34, 44992]
+        hubs = ["jita", "Amarr"]
+
+        [server]
+        name = "Corp Market Desk"
+        log_level = "info"
+        esi_allowlist = ["/markets/"]
+
+        [locations.home]
+        region_id = 10000016
+        system_id = 30001363
+    "#;
+
+    #[test]
+    fn test_file_overrides_defaults() {
+        let config = TraderGraderConfig::from_toml_str(SAMPLE).unwrap();
+        assert_eq!(config.cache.max_capacity, 5000);
+        assert_eq!(config.cache.default_ttl, Duration::from_secs(600));
+        assert_eq!(config.cache.serve_stale, Some(Duration::from_secs(1800)));
+        assert!(config.cache.enabled);
+        assert_eq!(config.rate_limit.requests_per_second, 50);
+        assert_eq!(config.rate_limit.base_delay_ms, RateLimitConfig::default().base_delay_ms);
+        assert_eq!(config.rate_limit.max_concurrent_requests, 4);
+        assert_eq!(config.rate_limit.endpoint_limits[&EndpointClass::Universe], 30);
+        assert_eq!(config.rate_limit.endpoint_limits[&EndpointClass::History], 20);
+        assert_eq!(config.esi.user_agent, "CorpTools/2.0");
+        assert_eq!(config.esi.request_timeout, Duration::from_secs(20));
+        assert_eq!(config.limits.tool_budget_bytes, 1024);
+        assert_eq!(config.trend.strong_change_percent, 8.0);
+        assert_eq!(config.trend.change_percent, TrendConfig::default().change_percent);
+        assert!(!config.trend.require_volume_confirmation);
+        assert_eq!(config.courier.isk_per_jump, 400_000.0);
+        assert_eq!(config.courier.null_sec_multiplier, 5.0);
+        assert_eq!(config.courier.low_sec_multiplier, CourierRates::default().low_sec_multiplier);
+        assert_eq!(config.prefetch.targets, vec![PrefetchTarget::new(10000002, 34)]);
+        assert!(!config.prefetch.include_history);
+        assert_eq!(config.snapshot.type_ids, vec![34, 44992]);
+        assert_eq!(config.snapshot.hubs, vec![TradeHub::Jita, TradeHub::Amarr]);
+        assert!(!config.snapshot.enabled);
+        assert_eq!(config.server.name, "Corp Market Desk");
+        assert_eq!(config.server.log_level, LogLevel::Info);
+        assert!(!config.server.esi_allowlist.allows("/universe/types/"));
+        assert_eq!(config.locations.get("home").unwrap().system_id, Some(30001363));
+        assert!(config.locations.get("jita").is_some());
+    }
+
+    #[test]
+    fn test_env_overrides_file() {
+        let mut config = TraderGraderConfig::from_toml_str(SAMPLE).unwrap();
+        let env: HashMap<&str, &str> = HashMap::from([
+            ("TRADERGRADER_RATE_LIMIT_RPS", "10"),
+            ("TRADERGRADER_CACHE_ENABLED", "false"),
+            ("TRADERGRADER_SERVER_NAME", " "),
+            ("TRADERGRADER_ESI_ALLOWLIST", "/status/,/route/"),
+            ("TRADERGRADER_ENDPOINT_LIMITS", "history=5, orders=40"),
+            ("TRADERGRADER_MAX_CONCURRENT_REQUESTS", "16"),
+            ("TRADERGRADER_TREND_CHANGE_PERCENT", "1.5"),
+            ("TRADERGRADER_COURIER_COLLATERAL_PERCENT", "2.5"),
+            ("TRADERGRADER_PREFETCH", "10000002:34,10000043:35"),
+            ("TRADERGRADER_SNAPSHOT_ENABLED", "true"),
+            ("TRADERGRADER_HISTORY_ARCHIVE_PATH", "/srv/history.sqlite"),
+            ("TRADERGRADER_LOCATIONS", "staging=10000060, home=10000002:30000144"),
+        ]);
+        config.apply_env(|name| env.get(name).map(|v| v.to_string())).unwrap();
+
+        assert_eq!(config.rate_limit.requests_per_second, 10);
+        assert_eq!(config.rate_limit.max_retries, 5);
+        assert_eq!(config.rate_limit.max_concurrent_requests, 16);
+        assert_eq!(config.trend.change_percent, 1.5);
+        assert_eq!(config.trend.strong_change_percent, 8.0);
+        assert_eq!(config.courier.collateral_percent, 2.5);
+        assert_eq!(config.courier.isk_per_jump, 400_000.0);
+        assert_eq!(config.rate_limit.endpoint_limits[&EndpointClass::History], 5);
+        assert_eq!(config.rate_limit.endpoint_limits[&EndpointClass::Orders], 40);
+        assert!(!config.cache.enabled);
+        assert_eq!(config.prefetch.targets.len(), 2);
+        assert!(config.snapshot.enabled);
+        assert_eq!(config.server.history_archive_path, Some(PathBuf::from("/srv/history.sqlite")));
+        assert_eq!(config.snapshot.type_ids, vec![34, 44992]);
+        // Blank variables are ignored
+        assert_eq!(config.server.name, "Corp Market Desk");
+        assert!(config.server.esi_allowlist.allows("/route/"));
+        assert_eq!(config.locations.get("staging"), Some(&LocationPreset::region(10000060)));
+        assert_eq!(config.locations.get("home").unwrap().region_id, 10000002);
+
+        let error = config
+            .apply_env(|name| (name == "TRADERGRADER_CACHE_MAX_CAPACITY").then(|| "lots".to_string()))
+            .unwrap_err();
+        assert!(error.to_string().contains("TRADERGRADER_CACHE_MAX_CAPACITY"));
+    }
+
+    #[test]
+    fn test_invalid_files_are_rejected() {
+        assert!(TraderGraderConfig::from_toml_str("[cache]\nmax_capacty = 1\n").is_err());
+        assert!(TraderGraderConfig::from_toml_str("[cache]\nbackend = \"memcached\"\n").is_err());
+        assert!(TraderGraderConfig::from_toml_str("[server]\nlog_level = \"chatty\"\n").is_err());
+        assert!(TraderGraderConfig::from_toml_str("[rate_limit.endpoints]\nkillmails = 5\n").is_err());
+        assert!(TraderGraderConfig::from_toml_str("[trend]\nstrong_change_percent = 1.0\n").is_err());
+        assert!(TraderGraderConfig::from_toml_str("[trend]\nchange_percent = 0.0\n").is_err());
+        assert!(TraderGraderConfig::from_toml_str("[courier]\nlow_sec_multiplier = 0.5\n").is_err());
+        assert!(TraderGraderConfig::from_toml_str("[snapshot]\nhubs = [\"Perimeter\"]\n").is_err());
+        assert!(TraderGraderConfig::from_toml_str("[snapshot]\nrefresh_secs = 10\n").is_err());
+        assert!(TraderGraderConfig::from_toml_str("[locations.home]\nsystem_id = 30000144\n").is_err());
+        assert!(TraderGraderConfig::load(Some(Path::new("/nonexistent/tradergrader.toml"))).is_err());
+    }
+}
@@ -0,0 +1,424 @@
+//! Public contract scanning for TraderGrader
+//!
+//! Item exchange contracts are priced by their issuers, not by an order book,
+//! so some sit well under what their items fetch on the market: a fitted ship
+//! sold for its hull price, a hauler dumping loot in bulk. Each outstanding
+//! contract in a region is a candidate; the newest that pass the filters have
+//! their items fetched and priced at a trade hub station, and are ranked by
+//! how far their price is under the items' market value. Items the acceptor
+//! has to supply count against the contract.
+
+use crate::error::{Result, TraderGraderError};
+use crate::hubs::TradeHub;
+use crate::market::MarketClient;
+use crate::scan::SCAN_CONCURRENCY;
+use crate::types::{ContractItem, ContractScan, ContractValuation, PublicContract};
+use futures::stream::{self, StreamExt};
+use std::collections::{BTreeSet, HashMap};
+
+/// Contracts listed unless the caller asks for more
+pub const DEFAULT_CONTRACT_LIMIT: usize = 20;
+
+/// Most contracts one scan lists
+pub const MAX_CONTRACT_LIMIT: usize = 100;
+
+/// Matching contracts whose items are fetched, newest first; each takes an ESI request
+pub const VALUED_CONTRACTS: usize = 200;
+
+/// Item types named in a contract's summary before the rest are counted
+const SUMMARY_ITEMS: usize = 3;
+
+/// Which public contracts a scan considers
+///
+/// Courier contracts carry no items to value and are always left out.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContractFilter {
+    /// Leave out auctions
+    pub item_exchange_only: bool,
+    /// Leave out contracts asking more than this
+    pub max_price: Option<f64>,
+}
+
+impl Default for ContractFilter {
+    fn default() -> Self {
+        Self {
+            item_exchange_only: true,
+            max_price: None,
+        }
+    }
+}
+
+impl ContractFilter {
+    /// Whether a contract passes the filter
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::contracts::ContractFilter;
+    /// use tradergrader::types::PublicContract;
+    ///
+    /// let contract: PublicContract = serde_json::from_str(
+    ///     r#"{"contract_id": 1, "type": "auction", "date_issued": "2026-10-01T10:00:00Z",
+    ///         "date_expired": "2026-10-15T10:00:00Z", "issuer_id": 2, "issuer_corporation_id": 3,
+    ///         "price": 1000000.0, "buyout": 5000000.0}"#,
+    /// )
+    /// .unwrap();
+    ///
+    /// assert!(!ContractFilter::default().matches(&contract));
+    /// let auctions = ContractFilter { item_exchange_only: false, max_price: Some(4_000_000.0) };
+    /// // An auction asks its buyout
+    /// assert!(!auctions.matches(&contract));
+    /// ```
+    pub fn matches(&self, contract: &PublicContract) -> bool {
+        let kind_matches = match contract.contract_type.as_str() {
+            "item_exchange" => true,
+            "auction" => !self.item_exchange_only,
+            _ => false,
+        };
+        kind_matches && self.max_price.is_none_or(|max| asking_price(contract) <= max)
+    }
+}
+
+/// What accepting a contract costs: an auction's buyout when it has one, else the price
+fn asking_price(contract: &PublicContract) -> f64 {
+    match contract.contract_type.as_str() {
+        "auction" => contract.buyout.or(contract.price),
+        _ => contract.price,
+    }
+    .unwrap_or(0.0)
+}
+
+/// Names a contract's included items, largest stacks first, e.g. "3x Rifter (587), 1x Tritanium (34)"
+fn item_summary(items: &[ContractItem], labels: &HashMap<i32, String>) -> String {
+    let mut stacks: Vec<(i32, i64)> = Vec::new();
+    for item in items.iter().filter(|item| item.is_included) {
+        match stacks.iter_mut().find(|(type_id, _)| *type_id == item.type_id) {
+            Some((_, quantity)) => *quantity += item.quantity,
+            None => stacks.push((item.type_id, item.quantity)),
+        }
+    }
+    stacks.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    let mut named: Vec<String> = stacks
+        .iter()
+        .take(SUMMARY_ITEMS)
+        .map(|(type_id, quantity)| match labels.get(type_id) {
+            Some(label) => format!("{quantity}x {label}"),
+            None => format!("{quantity}x Type {type_id}"),
+        })
+        .collect();
+    if stacks.len() > SUMMARY_ITEMS {
+        named.push(format!("{} more", stacks.len() - SUMMARY_ITEMS));
+    }
+    named.join(", ")
+}
+
+impl ContractValuation {
+    /// Values a contract's items from hub prices and compares it with its asking price
+    ///
+    /// Included items are valued at the lowest sell order (`market_value`)
+    /// and the highest buy order (`liquidation_value`); items the acceptor
+    /// supplies are subtracted at the lowest sell order either way. Blueprint
+    /// copies and items without orders leave both values `None`.
+    pub fn new(
+        contract: &PublicContract,
+        items: &[ContractItem],
+        item_summary: String,
+        buy_prices: &HashMap<i32, f64>,
+        sell_prices: &HashMap<i32, f64>,
+    ) -> Self {
+        let unpriced_items: Vec<i32> = items
+            .iter()
+            .filter(|item| item.is_blueprint_copy || !sell_prices.contains_key(&item.type_id))
+            .map(|item| item.type_id)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let supplied: f64 = items
+            .iter()
+            .filter(|item| !item.is_included)
+            .filter_map(|item| sell_prices.get(&item.type_id).map(|price| price * item.quantity as f64))
+            .sum();
+        let included_value = |prices: &HashMap<i32, f64>| -> Option<f64> {
+            items
+                .iter()
+                .filter(|item| item.is_included)
+                .map(|item| prices.get(&item.type_id).map(|price| price * item.quantity as f64))
+                .sum()
+        };
+        let priced = unpriced_items.is_empty();
+        let market_value = included_value(sell_prices).filter(|_| priced).map(|value| value - supplied);
+        let liquidation_value = included_value(buy_prices).filter(|_| priced).map(|value| value - supplied);
+
+        let asking_price = asking_price(contract);
+        Self {
+            contract_id: contract.contract_id,
+            contract_type: contract.contract_type.clone(),
+            title: contract.title.clone().filter(|title| !title.trim().is_empty()),
+            start_location_id: contract.start_location_id,
+            date_expired: contract.date_expired.clone(),
+            asking_price,
+            item_summary,
+            item_count: items.iter().filter(|item| item.is_included).count(),
+            market_value,
+            liquidation_value,
+            discount_percent: market_value
+                .filter(|&value| value > 0.0)
+                .map(|value| (value - asking_price) / value * 100.0),
+            instant_profit: liquidation_value.map(|value| value - asking_price),
+            unpriced_items,
+        }
+    }
+}
+
+impl MarketClient {
+    /// Finds public contracts in a region priced under their items' market value
+    ///
+    /// Contracts are fetched with every page and cached. Of those passing
+    /// `filter`, the [`VALUED_CONTRACTS`] newest have their items priced at
+    /// `hub`'s trade station; the `limit` with the biggest discount are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # use tradergrader::contracts::ContractFilter;
+    /// # use tradergrader::hubs::TradeHub;
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// let filter = ContractFilter { max_price: Some(100_000_000.0), ..Default::default() };
+    /// let scan = client.scan_public_contracts(10000002, TradeHub::Jita, &filter, 10).await?;
+    /// for contract in &scan.contracts {
+    ///     println!("{}: {:?}% under market", contract.item_summary, contract.discount_percent);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn scan_public_contracts(
+        &self,
+        region_id: i32,
+        hub: TradeHub,
+        filter: &ContractFilter,
+        limit: usize,
+    ) -> Result<ContractScan> {
+        if filter.max_price.is_some_and(|max| max <= 0.0) {
+            return Err(TraderGraderError::InvalidArgument {
+                field: "max_price".to_string(),
+                reason: "must be positive".to_string(),
+            });
+        }
+        let contracts = self.fetch_public_contracts(region_id).await?;
+        let mut matching: Vec<&PublicContract> = contracts.iter().filter(|c| filter.matches(c)).collect();
+        let matched = matching.len();
+        // Fresh contracts are the ones nobody has snapped up yet
+        matching.sort_by(|a, b| b.date_issued.cmp(&a.date_issued));
+        matching.truncate(VALUED_CONTRACTS);
+
+        let with_items: Vec<(&PublicContract, Vec<ContractItem>)> = stream::iter(matching)
+            .map(|contract| async move {
+                match self.esi().public_contract_items(contract.contract_id).await {
+                    Ok(items) => (contract, items),
+                    Err(e) => {
+                        tracing::debug!("No items for contract {}: {e}", contract.contract_id);
+                        (contract, Vec::new())
+                    }
+                }
+            })
+            .buffered(SCAN_CONCURRENCY)
+            .collect()
+            .await;
+        // Contracts accepted or expired since the list was cached come back empty
+        let with_items: Vec<(&PublicContract, Vec<ContractItem>)> =
+            with_items.into_iter().filter(|(_, items)| !items.is_empty()).collect();
+
+        let type_ids: BTreeSet<i32> = with_items
+            .iter()
+            .flat_map(|(_, items)| items.iter().map(|item| item.type_id))
+            .collect();
+        let ids: Vec<i64> = type_ids.iter().map(|&type_id| type_id as i64).collect();
+        let (buy_prices, sell_prices) = self.station_prices(hub, type_ids).await;
+        let labels: HashMap<i32, String> = match self.resolve_names(&ids).await {
+            Ok(names) => names
+                .into_values()
+                .map(|name| (name.id as i32, format!("{} ({})", name.name, name.id)))
+                .collect(),
+            Err(e) => {
+                tracing::debug!("Couldn't resolve contract item names: {e}");
+                HashMap::new()
+            }
+        };
+
+        let mut valuations: Vec<ContractValuation> = with_items
+            .iter()
+            .map(|(contract, items)| {
+                ContractValuation::new(contract, items, item_summary(items, &labels), &buy_prices, &sell_prices)
+            })
+            .collect();
+        let valued = valuations.len();
+        valuations.sort_by(|a, b| {
+            let discount = |v: &ContractValuation| v.discount_percent.unwrap_or(f64::NEG_INFINITY);
+            discount(b).total_cmp(&discount(a))
+        });
+        valuations.truncate(limit.clamp(1, MAX_CONTRACT_LIMIT));
+
+        Ok(ContractScan {
+            region_id,
+            hub: hub.to_string(),
+            total_contracts: contracts.len(),
+            matched,
+            valued,
+            contracts: valuations,
+        })
+    }
+}
+
+/// Formats a contract scan as a table, biggest discount first
+pub(crate) fn format_contract_scan(scan: &ContractScan) -> String {
+    let mut report = format!(
+        "Public Contracts in Region {} valued at {}: {} of {} outstanding contracts matched, {} newest valued\n",
+        scan.region_id, scan.hub, scan.matched, scan.total_contracts, scan.valued
+    );
+    if scan.contracts.is_empty() {
+        report.push_str("\nNo matching contracts with items to value.");
+        return report;
+    }
+
+    let isk = |value: Option<f64>| value.map_or_else(|| "-".to_string(), |v| format!("{v:.2}"));
+    report.push_str(
+        "\n| Contract | Type | Items | Asking | Market value | Discount | Instant profit | Expires |\n\
+         |---|---|---|---|---|---|---|---|\n",
+    );
+    for c in &scan.contracts {
+        let items = match &c.title {
+            Some(title) => format!("{} \"{title}\"", c.item_summary),
+            None => c.item_summary.clone(),
+        };
+        report.push_str(&format!(
+            "| {} | {} | {} | {:.2} | {} | {} | {} | {} |\n",
+            c.contract_id,
+            c.contract_type,
+            items,
+            c.asking_price,
+            isk(c.market_value),
+            c.discount_percent.map_or_else(|| "-".to_string(), |d| format!("{d:+.1}%")),
+            isk(c.instant_profit),
+            c.date_expired
+        ));
+    }
+    report.push_str(
+        "\nMarket value counts included items at the lowest sell order and instant profit at the highest buy \
+         order, both less any items the acceptor supplies. Contracts with blueprint copies or items without \
+         orders at the hub can't be valued.\n",
+    );
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contract(contract_type: &str, price: f64, buyout: Option<f64>) -> PublicContract {
+        PublicContract {
+            contract_id: 42,
+            contract_type: contract_type.to_string(),
+            date_issued: "2026-10-01T10:00:00Z".to_string(),
+            date_expired: "2026-10-15T10:00:00Z".to_string(),
+            issuer_id: 2,
+            issuer_corporation_id: 3,
+            for_corporation: false,
+            title: Some(" ".to_string()),
+            start_location_id: Some(60003760),
+            end_location_id: None,
+            price: Some(price),
+            buyout,
+            reward: None,
+            collateral: None,
+            volume: None,
+            days_to_complete: None,
+        }
+    }
+
+    fn item(type_id: i32, quantity: i64, is_included: bool) -> ContractItem {
+        ContractItem {
+            record_id: type_id as i64,
+            type_id,
+            quantity,
+            is_included,
+            is_blueprint_copy: false,
+        }
+    }
+
+    #[test]
+    fn test_filter() {
+        let filter = ContractFilter {
+            item_exchange_only: true,
+            max_price: Some(10_000_000.0),
+        };
+        assert!(filter.matches(&contract("item_exchange", 10_000_000.0, None)));
+        assert!(!filter.matches(&contract("item_exchange", 10_000_001.0, None)));
+        assert!(!filter.matches(&contract("courier", 0.0, None)));
+        assert!(!filter.matches(&contract("auction", 1.0, None)));
+
+        let auctions = ContractFilter {
+            item_exchange_only: false,
+            ..filter
+        };
+        assert!(auctions.matches(&contract("auction", 1.0, None)));
+        assert!(!auctions.matches(&contract("auction", 1.0, Some(20_000_000.0))));
+    }
+
+    #[test]
+    fn test_valuation() {
+        let buy = HashMap::from([(587, 400_000.0), (34, 4.0)]);
+        let sell = HashMap::from([(587, 500_000.0), (34, 5.0)]);
+        let items = [item(587, 2, true), item(34, 1_000, true), item(34, 200, false)];
+        let valuation = ContractValuation::new(
+            &contract("item_exchange", 700_000.0, None),
+            &items,
+            String::new(),
+            &buy,
+            &sell,
+        );
+        assert_eq!(valuation.market_value, Some(1_005_000.0 - 1_000.0));
+        assert_eq!(valuation.liquidation_value, Some(804_000.0 - 1_000.0));
+        assert_eq!(valuation.instant_profit, Some(103_000.0));
+        assert!((valuation.discount_percent.unwrap() - 304_000.0 / 1_004_000.0 * 100.0).abs() < 1e-9);
+        assert_eq!(valuation.item_count, 2);
+        assert_eq!(valuation.title, None);
+
+        let mut copy = item(688, 1, true);
+        copy.is_blueprint_copy = true;
+        let unpriced =
+            ContractValuation::new(&contract("item_exchange", 1.0, None), &[copy], String::new(), &buy, &sell);
+        assert_eq!(unpriced.unpriced_items, vec![688]);
+        assert_eq!(unpriced.market_value, None);
+        assert_eq!(unpriced.discount_percent, None);
+
+        let labels = HashMap::from([(587, "Rifter (587)".to_string())]);
+        assert_eq!(item_summary(&items, &labels), "1000x Type 34, 2x Rifter (587)");
+    }
+
+    #[test]
+    fn test_format() {
+        let valuation = ContractValuation::new(
+            &contract("item_exchange", 700_000.0, None),
+            &[item(587, 2, true)],
+            "2x Rifter (587)".to_string(),
+            &HashMap::from([(587, 400_000.0)]),
+            &HashMap::from([(587, 500_000.0)]),
+        );
+        let scan = ContractScan {
+            region_id: 10000002,
+            hub: "Jita".to_string(),
+            total_contracts: 5000,
+            matched: 1200,
+            valued: 200,
+            contracts: vec![valuation],
+        };
+        let report = format_contract_scan(&scan);
+        assert!(report.starts_with("Public Contracts in Region 10000002 valued at Jita: 1200 of 5000"));
+        assert!(report.contains(
+            "| 42 | item_exchange | 2x Rifter (587) | 700000.00 | 1000000.00 | +30.0% | 100000.00 | 2026-10-15T10:00:00Z |"
+        ));
+    }
+}
@@ -0,0 +1,294 @@
+//! Correlation analysis between items for TraderGrader
+//!
+//! Related items often move together: PLEX and Skill Injectors, minerals and
+//! the hulls built from them. This lines up the daily history of several items
+//! in one region by date, then reports how closely their day-over-day returns
+//! track each other (Pearson correlation) and how each performed over the
+//! shared period.
+
+use crate::error::{Result, TraderGraderError};
+use crate::market::MarketClient;
+use crate::returns;
+use crate::scan::SCAN_CONCURRENCY;
+use crate::types::{ItemComparison, ItemCorrelation, ItemPerformance, MarketHistory};
+use futures::stream::{self, StreamExt};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Most items compared in one call
+pub const MAX_COMPARED_ITEMS: usize = 10;
+
+/// Default days of history compared
+pub const DEFAULT_COMPARISON_DAYS: usize = 90;
+
+/// Fewest shared trading days a correlation is reported from
+pub const MIN_COMMON_DAYS: usize = 10;
+
+/// Pearson correlation of two equally long series
+///
+/// Returns `None` for fewer than two values, mismatched lengths, or a series
+/// that never changes.
+///
+/// # Examples
+///
+/// ```
+/// use tradergrader::correlation::pearson;
+///
+/// assert!((pearson(&[1.0, 2.0, 3.0], &[2.0, 4.0, 6.0]).unwrap() - 1.0).abs() < 1e-12);
+/// assert!((pearson(&[1.0, 2.0, 3.0], &[3.0, 2.0, 1.0]).unwrap() + 1.0).abs() < 1e-12);
+/// assert_eq!(pearson(&[1.0, 2.0, 3.0], &[5.0, 5.0, 5.0]), None);
+/// ```
+pub fn pearson(a: &[f64], b: &[f64]) -> Option<f64> {
+    if a.len() != b.len() || a.len() < 2 {
+        return None;
+    }
+    let (mean_a, mean_b) = (returns::mean(a)?, returns::mean(b)?);
+    let (covariance, variance_a, variance_b) =
+        a.iter().zip(b).fold((0.0, 0.0, 0.0), |(cov, var_a, var_b), (x, y)| {
+            let (dx, dy) = (x - mean_a, y - mean_b);
+            (cov + dx * dy, var_a + dx * dx, var_b + dy * dy)
+        });
+    if variance_a <= 0.0 || variance_b <= 0.0 {
+        return None;
+    }
+    Some((covariance / (variance_a * variance_b).sqrt()).clamp(-1.0, 1.0))
+}
+
+impl ItemComparison {
+    /// Compares items from their histories, given as `(type_id, label, history)`
+    ///
+    /// Only the last `days` dates every item traded on are used, and returns
+    /// are taken between consecutive shared dates.
+    pub fn from_histories(region_id: i32, items: &[(i32, String, Vec<MarketHistory>)], days: usize) -> Result<Self> {
+        if items.len() < 2 {
+            return Err(TraderGraderError::InvalidParams("Comparing needs at least two items".to_string()));
+        }
+        let by_date: Vec<BTreeMap<&str, f64>> = items
+            .iter()
+            .map(|(_, _, history)| {
+                history
+                    .iter()
+                    .filter(|h| h.average > 0.0)
+                    .map(|h| (h.date.as_str(), h.average))
+                    .collect()
+            })
+            .collect();
+        let mut shared: BTreeSet<&str> = by_date[0].keys().copied().collect();
+        for prices in &by_date[1..] {
+            shared.retain(|date| prices.contains_key(date));
+        }
+        let dates: Vec<&str> = shared.iter().copied().skip(shared.len().saturating_sub(days)).collect();
+        if dates.len() < MIN_COMMON_DAYS {
+            return Err(format!(
+                "The items share only {} trading days; need at least {MIN_COMMON_DAYS} to compare",
+                dates.len()
+            )
+            .into());
+        }
+
+        let series: Vec<Vec<f64>> = by_date
+            .iter()
+            .map(|prices| dates.iter().map(|date| prices[date]).collect())
+            .collect();
+        let log_returns: Vec<Vec<f64>> = series.iter().map(|prices| returns::log_returns(prices)).collect();
+
+        let performance: Vec<ItemPerformance> = items
+            .iter()
+            .zip(&series)
+            .zip(&log_returns)
+            .map(|(((type_id, type_label, _), prices), logs)| {
+                let (first_price, last_price) = (prices[0], prices[prices.len() - 1]);
+                ItemPerformance {
+                    type_id: *type_id,
+                    type_label: type_label.clone(),
+                    first_price,
+                    last_price,
+                    return_percent: (last_price / first_price - 1.0) * 100.0,
+                    daily_volatility_percent: returns::std_dev(logs).unwrap_or(0.0) * 100.0,
+                }
+            })
+            .collect();
+
+        let mut correlations = Vec::new();
+        for i in 0..items.len() {
+            for j in i + 1..items.len() {
+                let growth = |item: &ItemPerformance| item.last_price / item.first_price;
+                correlations.push(ItemCorrelation {
+                    first_type_id: items[i].0,
+                    second_type_id: items[j].0,
+                    correlation: pearson(&log_returns[i], &log_returns[j]),
+                    relative_return_percent: (growth(&performance[i]) / growth(&performance[j]) - 1.0) * 100.0,
+                });
+            }
+        }
+
+        Ok(Self {
+            region_id,
+            start_date: dates[0].to_string(),
+            end_date: dates[dates.len() - 1].to_string(),
+            common_days: dates.len(),
+            items: performance,
+            correlations,
+        })
+    }
+}
+
+impl MarketClient {
+    /// Compares how several items' prices moved together in a region
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// // PLEX against Large Skill Injectors in The Forge
+    /// let comparison = client.compare_items(10000002, &[44992, 40520], 90).await?;
+    /// println!("{:?}", comparison.correlations[0].correlation);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn compare_items(&self, region_id: i32, type_ids: &[i32], days: usize) -> Result<ItemComparison> {
+        if type_ids.len() > MAX_COMPARED_ITEMS {
+            return Err(TraderGraderError::InvalidParams(format!(
+                "At most {MAX_COMPARED_ITEMS} items can be compared at once"
+            )));
+        }
+        let items: Vec<(i32, String, Vec<MarketHistory>)> = stream::iter(type_ids.iter().copied())
+            .map(|type_id| async move {
+                let history = self.fetch_market_history(region_id, type_id).await?;
+                Ok::<_, TraderGraderError>((type_id, self.type_label(type_id).await, history))
+            })
+            .buffered(SCAN_CONCURRENCY)
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<_>>()?;
+        ItemComparison::from_histories(region_id, &items, days)
+    }
+
+    /// Generates a formatted item comparison report
+    pub async fn get_item_comparison_summary(&self, region_id: i32, type_ids: &[i32], days: usize) -> Result<String> {
+        let comparison = self.compare_items(region_id, type_ids, days).await?;
+        Ok(format_item_comparison(&comparison))
+    }
+}
+
+/// Plain-language strength of a correlation
+fn describe_correlation(correlation: f64) -> &'static str {
+    match (correlation >= 0.0, correlation.abs()) {
+        (_, s) if s < 0.2 => "no meaningful relationship",
+        (true, s) if s < 0.5 => "weak positive",
+        (true, s) if s < 0.8 => "moderate positive",
+        (true, _) => "strong positive",
+        (false, s) if s < 0.5 => "weak negative",
+        (false, s) if s < 0.8 => "moderate negative",
+        (false, _) => "strong negative",
+    }
+}
+
+/// Formats a comparison as a performance table followed by pairwise correlations
+fn format_item_comparison(comparison: &ItemComparison) -> String {
+    let mut report = format!(
+        "Item Comparison in Region {} ({} shared trading days, {} to {}):\n\n",
+        comparison.region_id, comparison.common_days, comparison.start_date, comparison.end_date
+    );
+    report.push_str("| Item | First | Last | Return | Daily volatility |\n|---|---|---|---|---|\n");
+    for item in &comparison.items {
+        report.push_str(&format!(
+            "| {} | {:.2} | {:.2} | {:+.2}% | {:.2}% |\n",
+            item.type_label, item.first_price, item.last_price, item.return_percent, item.daily_volatility_percent
+        ));
+    }
+
+    let label = |type_id: i32| {
+        comparison
+            .items
+            .iter()
+            .find(|item| item.type_id == type_id)
+            .map_or_else(|| format!("Type {type_id}"), |item| item.type_label.clone())
+    };
+    report.push_str("\nCorrelation of daily returns:\n");
+    for pair in &comparison.correlations {
+        let correlation = match pair.correlation {
+            Some(r) => format!("{r:+.2} ({})", describe_correlation(r)),
+            None => "n/a (price never moved)".to_string(),
+        };
+        report.push_str(&format!(
+            "{} vs {}: {correlation}; first {} the second by {:.2}%\n",
+            label(pair.first_type_id),
+            label(pair.second_type_id),
+            if pair.relative_return_percent >= 0.0 { "outperformed" } else { "underperformed" },
+            pair.relative_return_percent.abs()
+        ));
+    }
+    report.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::history_day;
+
+    fn history(prices: &[(u32, f64)]) -> Vec<MarketHistory> {
+        prices.iter().map(|&(day, average)| history_day(&format!("2025-02-{day:02}"), average, 1)).collect()
+    }
+
+    fn zigzag(day: u32, scale: f64) -> f64 {
+        100.0 * (1.0 + day as f64 * 0.01) * if day.is_multiple_of(2) { 1.0 + scale } else { 1.0 - scale }
+    }
+
+    #[test]
+    fn test_aligns_dates_and_correlates_returns() {
+        let together: Vec<(u32, f64)> = (1..=20).map(|d| (d, zigzag(d, 0.02))).collect();
+        // Same moves at twice the size, with a gap on day 5 and extra days before
+        let doubled: Vec<(u32, f64)> = (1..=25).filter(|&d| d != 5).map(|d| (d, zigzag(d, 0.04) * 2.0)).collect();
+        // Moves against the first item
+        let opposite: Vec<(u32, f64)> = (1..=20).map(|d| (d, zigzag(d + 1, 0.02))).collect();
+
+        let items = vec![
+            (1, "A".to_string(), history(&together)),
+            (2, "B".to_string(), history(&doubled)),
+            (3, "C".to_string(), history(&opposite)),
+        ];
+        let comparison = ItemComparison::from_histories(10000002, &items, 90).unwrap();
+        assert_eq!(comparison.common_days, 19);
+        assert_eq!(comparison.start_date, "2025-02-01");
+        assert_eq!(comparison.end_date, "2025-02-20");
+
+        let pairs: Vec<(i32, i32)> =
+            comparison.correlations.iter().map(|c| (c.first_type_id, c.second_type_id)).collect();
+        assert_eq!(pairs, vec![(1, 2), (1, 3), (2, 3)]);
+        assert!(comparison.correlations[0].correlation.unwrap() > 0.9);
+        assert!(comparison.correlations[1].correlation.unwrap() < -0.9);
+
+        let text = format_item_comparison(&comparison);
+        assert!(text.contains("A vs B: +"));
+        assert!(text.contains("strong negative"));
+    }
+
+    #[test]
+    fn test_relative_performance_and_window() {
+        let rising: Vec<(u32, f64)> = (1..=20).map(|d| (d, zigzag(d, 0.01) * 1.5f64.powf(d as f64 / 19.0))).collect();
+        let flat: Vec<(u32, f64)> = (1..=20).map(|d| (d, zigzag(d, 0.01))).collect();
+        let items = vec![(1, "Up".to_string(), history(&rising)), (2, "Flat".to_string(), history(&flat))];
+
+        let comparison = ItemComparison::from_histories(10000002, &items, 90).unwrap();
+        // Both zigzag the same way, but the first gains 50% on top from day 1 to day 20
+        assert!((comparison.correlations[0].relative_return_percent - 50.0).abs() < 1e-6);
+        assert!(comparison.items[0].return_percent > comparison.items[1].return_percent);
+
+        let recent = ItemComparison::from_histories(10000002, &items, 12).unwrap();
+        assert_eq!(recent.common_days, 12);
+        assert_eq!(recent.start_date, "2025-02-09");
+    }
+
+    #[test]
+    fn test_needs_two_items_with_enough_shared_days() {
+        let a = history(&(1..=20).map(|d| (d, zigzag(d, 0.02))).collect::<Vec<_>>());
+        let b = history(&(15..=28).map(|d| (d, zigzag(d, 0.02))).collect::<Vec<_>>());
+        assert!(ItemComparison::from_histories(1, &[(1, "A".to_string(), a.clone())], 90).is_err());
+        assert!(ItemComparison::from_histories(1, &[(1, "A".to_string(), a), (2, "B".to_string(), b)], 90).is_err());
+        assert_eq!(pearson(&[1.0], &[1.0]), None);
+        assert_eq!(pearson(&[1.0, 2.0], &[1.0]), None);
+    }
+}
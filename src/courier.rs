@@ -0,0 +1,294 @@
+//! Courier contract pricing for TraderGrader
+//!
+//! Haulers price courier contracts by distance, cargo volume and risk. This
+//! turns a gate route from ESI `/route/` into reward guidance: a rate per jump
+//! and per m³ carried per jump, with jumps into low- and null-sec weighted up
+//! for the gate camps there, plus a premium on the collateral the hauler
+//! puts up. The rates come from configuration and can be overridden per
+//! quote, since they vary with the market and the corporation doing the work.
+
+use crate::error::{Result, TraderGraderError};
+use crate::hauling::{RouteFlag, DEFAULT_CARGO_CAPACITY_M3};
+use crate::market::MarketClient;
+use crate::route::format_route_segments;
+use crate::types::{CourierQuote, RoutePlan, SecurityClass};
+
+/// Rates a courier reward is built from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CourierRates {
+    /// ISK per high-sec jump, regardless of cargo
+    pub isk_per_jump: f64,
+    /// ISK per m³ of cargo per high-sec jump
+    pub isk_per_m3_per_jump: f64,
+    /// Share of the collateral added to the reward, in percent
+    pub collateral_percent: f64,
+    /// How many high-sec jumps a jump into low-sec counts as
+    pub low_sec_multiplier: f64,
+    /// How many high-sec jumps a jump into null-sec counts as
+    pub null_sec_multiplier: f64,
+    /// Smallest reward worth a hauler's undock
+    pub minimum_reward: f64,
+}
+
+impl Default for CourierRates {
+    fn default() -> Self {
+        Self {
+            isk_per_jump: 250_000.0,
+            isk_per_m3_per_jump: 1.0,
+            collateral_percent: 1.0,
+            low_sec_multiplier: 2.0,
+            null_sec_multiplier: 3.0,
+            minimum_reward: 1_000_000.0,
+        }
+    }
+}
+
+impl CourierRates {
+    /// Checks that rates are non-negative and risky jumps cost at least as much as safe ones
+    pub fn validate(&self) -> Result<()> {
+        let rates = [
+            ("isk_per_jump", self.isk_per_jump),
+            ("isk_per_m3_per_jump", self.isk_per_m3_per_jump),
+            ("collateral_percent", self.collateral_percent),
+            ("minimum_reward", self.minimum_reward),
+        ];
+        for (field, value) in rates {
+            if !value.is_finite() || value < 0.0 {
+                return Err(TraderGraderError::InvalidArgument {
+                    field: field.to_string(),
+                    reason: "must be zero or more".to_string(),
+                });
+            }
+        }
+        for (field, value) in [
+            ("low_sec_multiplier", self.low_sec_multiplier),
+            ("null_sec_multiplier", self.null_sec_multiplier),
+        ] {
+            if !value.is_finite() || value < 1.0 {
+                return Err(TraderGraderError::InvalidArgument {
+                    field: field.to_string(),
+                    reason: "must be at least 1".to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// High-sec jumps the route's jumps are worth after risk weighting
+    fn weighted_jumps(&self, high_sec: usize, low_sec: usize, null_sec: usize) -> f64 {
+        high_sec as f64 + low_sec as f64 * self.low_sec_multiplier + null_sec as f64 * self.null_sec_multiplier
+    }
+}
+
+impl CourierQuote {
+    /// Prices a courier contract along a planned route
+    ///
+    /// Each jump is weighted by the security of the system it enters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::courier::CourierRates;
+    /// use tradergrader::hauling::RouteFlag;
+    /// use tradergrader::{CourierQuote, Position, RoutePlan, SystemInfo};
+    ///
+    /// let system = |system_id: i32, name: &str, security_status: f64| SystemInfo {
+    ///     system_id,
+    ///     name: name.to_string(),
+    ///     constellation_id: 1,
+    ///     security_status,
+    ///     position: Position { x: 0.0, y: 0.0, z: 0.0 },
+    ///     stations: Vec::new(),
+    /// };
+    /// // Two high-sec jumps and one into low-sec
+    /// let systems = [system(1, "Start", 0.9), system(2, "Mid", 0.5), system(3, "Edge", 0.5), system(4, "Camp", 0.3)];
+    /// let route = RoutePlan::from_systems(&systems, RouteFlag::Shortest)?;
+    ///
+    /// let quote = CourierQuote::from_route(&route, 10_000.0, 500_000_000.0, &CourierRates::default())?;
+    /// assert_eq!((quote.jumps, quote.low_sec_jumps), (3, 1));
+    /// // 4 weighted jumps × (250,000 + 10,000 m³ × 1 ISK) + 1% of collateral
+    /// assert_eq!(quote.reward, 4.0 * 260_000.0 + 5_000_000.0);
+    /// # Ok::<(), tradergrader::TraderGraderError>(())
+    /// ```
+    pub fn from_route(route: &RoutePlan, volume_m3: f64, collateral: f64, rates: &CourierRates) -> Result<Self> {
+        rates.validate()?;
+        if !volume_m3.is_finite() || volume_m3 < 0.0 {
+            return Err(TraderGraderError::InvalidArgument {
+                field: "volume_m3".to_string(),
+                reason: "must be zero or more".to_string(),
+            });
+        }
+        if !collateral.is_finite() || collateral < 0.0 {
+            return Err(TraderGraderError::InvalidArgument {
+                field: "collateral".to_string(),
+                reason: "must be zero or more".to_string(),
+            });
+        }
+        let (Some(origin), Some(destination), Some(lowest)) =
+            (route.systems.first(), route.systems.last(), route.lowest_security_system())
+        else {
+            return Err("A courier route needs at least one system".into());
+        };
+
+        let (high_sec_jumps, low_sec_jumps, null_sec_jumps) = (
+            route.jumps_into(SecurityClass::HighSec),
+            route.jumps_into(SecurityClass::LowSec),
+            route.jumps_into(SecurityClass::NullSec),
+        );
+
+        let weighted_jumps = rates.weighted_jumps(high_sec_jumps, low_sec_jumps, null_sec_jumps);
+        let jump_reward = weighted_jumps * rates.isk_per_jump;
+        let volume_reward = weighted_jumps * volume_m3 * rates.isk_per_m3_per_jump;
+        let collateral_reward = collateral * rates.collateral_percent / 100.0;
+
+        Ok(Self {
+            origin_system_id: origin.system_id,
+            origin_name: origin.name.clone(),
+            destination_system_id: destination.system_id,
+            destination_name: destination.name.clone(),
+            jumps: route.jumps,
+            high_sec_jumps,
+            low_sec_jumps,
+            null_sec_jumps,
+            lowest_security_status: lowest.security_status,
+            lowest_security_system: lowest.name.clone(),
+            segments: route.segments.clone(),
+            volume_m3,
+            collateral,
+            jump_reward,
+            volume_reward,
+            collateral_reward,
+            reward: (jump_reward + volume_reward + collateral_reward).max(rates.minimum_reward),
+        })
+    }
+}
+
+impl MarketClient {
+    /// Prices a courier contract between two systems along the route `flag` picks
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # use tradergrader::courier::CourierRates;
+    /// # use tradergrader::hauling::RouteFlag;
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// // Jita to Amarr, 60,000 m³ with 2 billion ISK collateral
+    /// let quote = client
+    ///     .price_courier_contract(30000142, 30002187, 60_000.0, 2e9, RouteFlag::Secure, &CourierRates::default())
+    ///     .await?;
+    /// println!("{} jumps, reward {:.0} ISK", quote.jumps, quote.reward);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn price_courier_contract(
+        &self,
+        origin_system_id: i32,
+        destination_system_id: i32,
+        volume_m3: f64,
+        collateral: f64,
+        flag: RouteFlag,
+        rates: &CourierRates,
+    ) -> Result<CourierQuote> {
+        let route = self.plan_route(origin_system_id, destination_system_id, flag).await?;
+        CourierQuote::from_route(&route, volume_m3, collateral, rates)
+    }
+}
+
+/// Formats the suggested reward, how it breaks down and the route's risk
+pub(crate) fn format_courier_quote(quote: &CourierQuote, flag: RouteFlag) -> String {
+    let mut text = format!(
+        "Courier Contract from {} to {}:\n{} jumps on the {} route ({} high-sec, {} low-sec, {} null-sec)\n",
+        quote.origin_name,
+        quote.destination_name,
+        quote.jumps,
+        flag.as_str(),
+        quote.high_sec_jumps,
+        quote.low_sec_jumps,
+        quote.null_sec_jumps
+    );
+    text.push_str(&format!(
+        "Cargo: {:.0} m³, collateral {:.0} ISK\n\nSuggested reward: {:.0} ISK\n",
+        quote.volume_m3, quote.collateral, quote.reward
+    ));
+    text.push_str(&format!(
+        "- Distance: {:.0} ISK\n- Volume: {:.0} ISK\n- Collateral: {:.0} ISK\n",
+        quote.jump_reward, quote.volume_reward, quote.collateral_reward
+    ));
+    if quote.reward > quote.jump_reward + quote.volume_reward + quote.collateral_reward {
+        text.push_str("Raised to the minimum reward.\n");
+    }
+
+    if !quote.segments.is_empty() {
+        let lowest = SecurityClass::from_security_status(quote.lowest_security_status);
+        text.push_str(&format!(
+            "\n{}\nThe route passes through {lowest} space (lowest {} at {:.1}); most high-sec haulers will not take \
+             it, so expect it to sit longer or be picked up by a blockade runner or jump freighter.\n",
+            format_route_segments(&quote.segments),
+            quote.lowest_security_system,
+            quote.lowest_security_status
+        ));
+    }
+    if quote.volume_m3 > DEFAULT_CARGO_CAPACITY_M3 {
+        text.push_str("\nThe cargo is more than one freighter load; split it into several contracts.\n");
+    }
+    text.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Position, SystemInfo};
+
+    fn route(security: &[f64]) -> RoutePlan {
+        let systems: Vec<SystemInfo> = security.iter().enumerate().map(|(i, &s)| system(i as i32 + 1, s)).collect();
+        RoutePlan::from_systems(&systems, RouteFlag::Shortest).unwrap()
+    }
+
+    fn system(system_id: i32, security_status: f64) -> SystemInfo {
+        SystemInfo {
+            system_id,
+            name: format!("System {system_id}"),
+            constellation_id: 1,
+            security_status,
+            position: Position { x: 0.0, y: 0.0, z: 0.0 },
+            stations: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_weights_jumps_by_security() {
+        let route = route(&[1.0, 0.46, 0.44, 0.0, -0.5]);
+        let rates = CourierRates {
+            minimum_reward: 0.0,
+            ..CourierRates::default()
+        };
+        let quote = CourierQuote::from_route(&route, 1_000.0, 0.0, &rates).unwrap();
+
+        assert_eq!((quote.high_sec_jumps, quote.low_sec_jumps, quote.null_sec_jumps), (1, 1, 2));
+        // 1 + 2 + 2 × 3 weighted jumps
+        assert_eq!(quote.jump_reward, 9.0 * 250_000.0);
+        assert_eq!(quote.volume_reward, 9.0 * 1_000.0);
+        assert_eq!(quote.lowest_security_system, "System 5");
+
+        let text = format_courier_quote(&quote, RouteFlag::Shortest);
+        assert!(text.contains("4 jumps on the shortest route (1 high-sec, 1 low-sec, 2 null-sec)"));
+        assert!(text.contains("null-sec space (lowest System 5 at -0.5)"));
+    }
+
+    #[test]
+    fn test_minimum_reward_and_bad_inputs() {
+        let route = route(&[0.9, 0.8]);
+        let quote = CourierQuote::from_route(&route, 100.0, 0.0, &CourierRates::default()).unwrap();
+        assert_eq!(quote.reward, 1_000_000.0);
+        assert!(format_courier_quote(&quote, RouteFlag::Secure).contains("Raised to the minimum reward."));
+
+        assert!(CourierQuote::from_route(&route, -1.0, 0.0, &CourierRates::default()).is_err());
+        let cheap_risk = CourierRates {
+            low_sec_multiplier: 0.5,
+            ..CourierRates::default()
+        };
+        assert!(CourierQuote::from_route(&route, 1.0, 0.0, &cheap_risk).is_err());
+    }
+}
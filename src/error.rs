@@ -1,5 +1,6 @@
 //! Error types for TraderGrader MCP server
 
+use serde_json::{json, Value};
 use thiserror::Error;
 
 /// TraderGrader specific errors
@@ -8,6 +9,13 @@ pub enum TraderGraderError {
     #[error("EVE ESI API error: {message}")]
     EsiApiError { message: String },
     
+    #[error("EVE is unavailable: {message}")]
+    EsiUnavailable {
+        message: String,
+        /// Seconds until the service is expected back, when known
+        retry_after_secs: Option<u64>,
+    },
+    
     #[error("Invalid region ID: {region_id}")]
     InvalidRegionId { region_id: i32 },
     
@@ -23,12 +31,28 @@ pub enum TraderGraderError {
     #[error("Cache error: {message}")]
     CacheError { message: String },
     
-    #[error("Rate limit exceeded: {0}")]
-    RateLimitError(String),
+    #[error("Rate limit exceeded: {message}")]
+    RateLimitError {
+        message: String,
+        /// Seconds ESI asked us to wait before retrying, when it said
+        retry_after_secs: Option<u64>,
+    },
     
     #[error("Authentication error: {0}")]
     AuthenticationError(String),
     
+    #[error("Invalid parameters: {0}")]
+    InvalidParams(String),
+    
+    #[error("{field} {reason}")]
+    InvalidArgument { field: String, reason: String },
+    
+    #[error("Not enough market data: {0}")]
+    InsufficientData(String),
+    
+    #[error("Result too large, narrow your query: {what} is over the {limit_bytes} byte limit")]
+    ResultTooLarge { what: String, limit_bytes: usize },
+    
     #[error("Internal server error: {0}")]
     InternalError(String),
 }
@@ -53,16 +77,79 @@ impl TraderGraderError {
     pub fn to_rpc_code(&self) -> i32 {
         match self {
             Self::EsiApiError { .. } => -32603, // Internal error
+            Self::EsiUnavailable { .. } => -32003, // Server error (custom)
             Self::InvalidRegionId { .. } => -32602, // Invalid params
             Self::InvalidTypeId { .. } => -32602, // Invalid params
             Self::NetworkError(_) => -32603, // Internal error
             Self::JsonError(_) => -32700, // Parse error
             Self::CacheError { .. } => -32603, // Internal error
-            Self::RateLimitError(_) => -32000, // Server error (custom)
+            Self::RateLimitError { .. } => -32000, // Server error (custom)
             Self::AuthenticationError(_) => -32001, // Server error (custom)
+            Self::InvalidParams(_) => -32602, // Invalid params
+            Self::InvalidArgument { .. } => -32602, // Invalid params
+            Self::InsufficientData(_) => -32004, // Server error (custom)
+            Self::ResultTooLarge { .. } => -32002, // Server error (custom)
             Self::InternalError(_) => -32603, // Internal error
         }
     }
+
+    /// Whether the error means the request itself was malformed
+    ///
+    /// MCP reports these as protocol errors; anything else that goes wrong
+    /// while running a tool is returned as a tool result with `isError` set.
+    pub fn is_invalid_request(&self) -> bool {
+        matches!(
+            self,
+            Self::InvalidRegionId { .. }
+                | Self::InvalidTypeId { .. }
+                | Self::InvalidParams(_)
+                | Self::InvalidArgument { .. }
+        )
+    }
+
+    /// Structured details for the JSON-RPC error `data` field
+    ///
+    /// Lets clients tell which ID was rejected, or how long to back off,
+    /// without parsing the message.
+    pub fn to_rpc_data(&self) -> Option<Value> {
+        match self {
+            Self::InvalidRegionId { region_id } => Some(json!({"kind": "invalid_region_id", "region_id": region_id})),
+            Self::InvalidTypeId { type_id } => Some(json!({"kind": "invalid_type_id", "type_id": type_id})),
+            Self::InvalidArgument { field, .. } => Some(json!({"kind": "invalid_argument", "field": field})),
+            Self::InsufficientData(_) => Some(json!({"kind": "insufficient_data"})),
+            Self::ResultTooLarge { limit_bytes, .. } => Some(json!({"kind": "result_too_large", "limit_bytes": limit_bytes})),
+            Self::RateLimitError { retry_after_secs, .. } => {
+                Some(json!({"kind": "rate_limited", "retry_after_secs": retry_after_secs}))
+            }
+            Self::EsiUnavailable { retry_after_secs, .. } => {
+                Some(json!({"kind": "esi_unavailable", "retry_after_secs": retry_after_secs}))
+            }
+            _ => None,
+        }
+    }
+
+    /// Convert to a JSON-RPC error object with code, message and optional data
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::TraderGraderError;
+    ///
+    /// let error = TraderGraderError::InvalidTypeId { type_id: -1 }.to_rpc_error("Failed to get market summary");
+    /// assert_eq!(error["code"], -32602);
+    /// assert_eq!(error["message"], "Failed to get market summary: Invalid type ID: -1");
+    /// assert_eq!(error["data"]["type_id"], -1);
+    /// ```
+    pub fn to_rpc_error(&self, context: &str) -> Value {
+        let mut error = json!({
+            "code": self.to_rpc_code(),
+            "message": format!("{context}: {self}"),
+        });
+        if let Some(data) = self.to_rpc_data() {
+            error["data"] = data;
+        }
+        error
+    }
 }
 
 #[cfg(test)]
@@ -99,8 +186,63 @@ mod tests {
 
     #[test] 
     fn test_rate_limit_error() {
-        let error = TraderGraderError::RateLimitError("Too many requests".to_string());
+        let error = TraderGraderError::RateLimitError {
+            message: "Too many requests".to_string(),
+            retry_after_secs: Some(30),
+        };
         assert_eq!(error.to_rpc_code(), -32000);
         assert!(error.to_string().contains("Rate limit exceeded"));
+
+        let rpc = error.to_rpc_error("Failed to fetch market orders");
+        assert_eq!(rpc["code"], -32000);
+        assert_eq!(rpc["data"], json!({"kind": "rate_limited", "retry_after_secs": 30}));
+    }
+
+    #[test]
+    fn test_invalid_request_errors() {
+        assert!(TraderGraderError::InvalidTypeId { type_id: 0 }.is_invalid_request());
+        assert!(TraderGraderError::InvalidParams("Missing type_id".to_string()).is_invalid_request());
+        assert!(!TraderGraderError::from("boom").is_invalid_request());
+        assert!(!TraderGraderError::AuthenticationError("no token".to_string()).is_invalid_request());
+        // Valid arguments against a thin market aren't a malformed request
+        assert!(!TraderGraderError::InsufficientData("No sell orders".to_string()).is_invalid_request());
+    }
+
+    #[test]
+    fn test_rpc_error_data() {
+        let rpc = TraderGraderError::InvalidRegionId { region_id: 5 }.to_rpc_error("Failed");
+        assert_eq!(rpc["code"], -32602);
+        assert_eq!(rpc["data"], json!({"kind": "invalid_region_id", "region_id": 5}));
+
+        let rpc = TraderGraderError::from("boom").to_rpc_error("Failed");
+        assert_eq!(rpc["code"], -32603);
+        assert_eq!(rpc["message"], "Failed: Internal server error: boom");
+        assert!(rpc.get("data").is_none());
+    }
+
+    #[test]
+    fn test_result_too_large() {
+        let error = TraderGraderError::ResultTooLarge {
+            what: "ESI response from /latest/markets/10000002/orders/".to_string(),
+            limit_bytes: 1024,
+        };
+        assert!(!error.is_invalid_request());
+        let rpc = error.to_rpc_error("Failed to fetch market orders");
+        assert_eq!(rpc["code"], -32002);
+        assert_eq!(rpc["data"], json!({"kind": "result_too_large", "limit_bytes": 1024}));
+        assert!(rpc["message"].as_str().unwrap().contains("narrow your query"));
+    }
+
+    #[test]
+    fn test_esi_unavailable() {
+        let error = TraderGraderError::EsiUnavailable {
+            message: "EVE is in daily downtime (11:00–11:15 UTC)".to_string(),
+            retry_after_secs: Some(240),
+        };
+        assert!(!error.is_invalid_request());
+        assert_eq!(error.to_string(), "EVE is unavailable: EVE is in daily downtime (11:00–11:15 UTC)");
+        let rpc = error.to_rpc_error("Failed to fetch market orders");
+        assert_eq!(rpc["code"], -32003);
+        assert_eq!(rpc["data"], json!({"kind": "esi_unavailable", "retry_after_secs": 240}));
     }
 }
\ No newline at end of file
@@ -0,0 +1,951 @@
+//! Typed ESI client for TraderGrader
+//!
+//! [`EsiClient`] owns everything needed to talk to ESI: the HTTP client, the
+//! (possibly shared) rate limiter, the cache, EVE SSO and the MCP logger. Every
+//! endpoint takes the same path: a cache lookup, a rate-limited request with
+//! retries, all pages of paginated routes, caching by the response's `Expires`
+//! header and, when ESI fails, the expired cached copy if serving stale data is
+//! enabled. Adding an endpoint takes a route and a cache key.
+//!
+//! [`MarketClient`](crate::MarketClient) is the analysis layer on top: it asks
+//! this client for typed data and never builds a request itself, so analysis
+//! can be tested against a pre-filled cache without a network.
+
+use super::{prewarm, CachingResolver, DeprecationTracker, EsiConfig, EsiDiagnostics};
+use crate::auth::{scopes, EveSso};
+use crate::cache::{note_stale_read, CacheBackend, CacheBackendExt, CacheItem, CacheKey, EsiHeaderParser, StaleRead};
+use crate::error::{Result, TraderGraderError};
+use crate::limits::{self, ResponseLimits};
+use crate::logging::{LogLevel, McpLogger};
+use crate::rate_limit::{EndpointClass, EsiRateLimiter};
+use crate::sde::{META_LEVEL_ATTRIBUTE_ID, TECH_LEVEL_ATTRIBUTE_ID};
+use crate::singleflight::SingleFlight;
+use crate::types::{
+    CharacterOrder, ContractItem, EsiObject, KeepsExtraFields, MarketHistory, MarketOrder, PublicContract,
+    ServerStatus, StructureInfo, TypeInfo, WalletTransaction,
+};
+use futures::stream::{self, StreamExt, TryStreamExt};
+use reqwest::header::HeaderMap;
+use reqwest::{Client, Response};
+use serde_json::{json, Value};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+/// Typed, cached and rate-limited access to ESI
+///
+/// # Examples
+///
+/// ```
+/// use tradergrader::{EsiClient, EsiConfig, EsiRateLimiter, MarketClient, RateLimitConfig};
+///
+/// let limiter = EsiRateLimiter::shared(RateLimitConfig::default())?;
+/// let esi = EsiClient::new(None, limiter, EsiConfig::default())?.with_max_concurrent_requests(4);
+/// let client = MarketClient::from_esi(esi);
+/// assert_eq!(client.esi().max_concurrent_requests(), 4);
+/// # Ok::<(), tradergrader::TraderGraderError>(())
+/// ```
+#[derive(Debug)]
+pub struct EsiClient {
+    http_client: Client,
+    config: EsiConfig,
+    resolver: Option<Arc<CachingResolver>>,
+    deprecations: DeprecationTracker,
+    cache: Option<Arc<dyn CacheBackend>>,
+    rate_limiter: Arc<EsiRateLimiter>,
+    auth: Option<Arc<EveSso>>,
+    /// Receiver of MCP log events, when running behind an MCP handler
+    logger: Option<McpLogger>,
+    /// Caps on response bodies and cached items
+    limits: ResponseLimits,
+    /// How long past expiry cached data may be served when ESI fails
+    serve_stale: Option<Duration>,
+    /// Order fetches in progress, shared by concurrent callers
+    orders_in_flight: SingleFlight<CacheKey, Vec<MarketOrder>>,
+    /// History fetches in progress, shared by concurrent callers
+    history_in_flight: SingleFlight<CacheKey, Vec<MarketHistory>>,
+    /// Most ESI requests open at once, taken after the rate limiter admits a request
+    max_concurrent_requests: usize,
+    request_slots: Arc<Semaphore>,
+}
+
+impl EsiClient {
+    /// Creates a client from an optional cache, a (possibly shared) rate limiter
+    /// and the HTTP client configuration
+    pub fn new(
+        cache: Option<Arc<dyn CacheBackend>>,
+        rate_limiter: Arc<EsiRateLimiter>,
+        config: EsiConfig,
+    ) -> Result<Self> {
+        let resolver = config.create_resolver();
+        let max_concurrent_requests = rate_limiter.config().max_concurrent_requests.max(1);
+        Ok(Self {
+            http_client: config.build_http_client_with_resolver(resolver.clone())?,
+            config,
+            resolver,
+            deprecations: DeprecationTracker::new(),
+            cache,
+            rate_limiter,
+            auth: None,
+            logger: None,
+            limits: ResponseLimits::default(),
+            serve_stale: None,
+            orders_in_flight: SingleFlight::new(),
+            history_in_flight: SingleFlight::new(),
+            max_concurrent_requests,
+            request_slots: Arc::new(Semaphore::new(max_concurrent_requests)),
+        })
+    }
+
+    /// Attaches an EVE SSO client, enabling authenticated endpoints
+    pub fn with_authenticator(mut self, auth: Arc<EveSso>) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    /// Reports requests, cache lookups and retries to an MCP logger
+    pub fn with_logger(mut self, logger: McpLogger) -> Self {
+        self.logger = Some(logger);
+        self
+    }
+
+    /// Replace the response size limits
+    pub fn with_response_limits(mut self, limits: ResponseLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Replace how many requests may be open at once; values below 1 are raised to 1
+    pub fn with_max_concurrent_requests(mut self, max_concurrent_requests: usize) -> Self {
+        self.max_concurrent_requests = max_concurrent_requests.max(1);
+        self.request_slots = Arc::new(Semaphore::new(self.max_concurrent_requests));
+        self
+    }
+
+    /// Serve cached data up to `max_stale` past its expiry when ESI fails
+    pub fn with_serve_stale(mut self, max_stale: Option<Duration>) -> Self {
+        self.serve_stale = max_stale;
+        self
+    }
+
+    /// Get the HTTP client configuration
+    pub fn config(&self) -> &EsiConfig {
+        &self.config
+    }
+
+    /// Reports the connection settings in effect (user agent, DNS, pooling)
+    pub fn diagnostics(&self) -> EsiDiagnostics {
+        EsiDiagnostics::collect(&self.config, self.resolver.as_deref()).with_deprecations(&self.deprecations.notices())
+    }
+
+    /// Opens connections to ESI in the background so the first request is fast
+    ///
+    /// Returns `None` when pre-warming is disabled (`prewarm_connections == 0`),
+    /// otherwise a handle resolving to the number of connections established.
+    /// Must be called from within a Tokio runtime.
+    pub fn spawn_prewarm(&self) -> Option<tokio::task::JoinHandle<usize>> {
+        let connections = self.config.prewarm_connections;
+        if connections == 0 {
+            return None;
+        }
+        Some(tokio::spawn(prewarm(self.http_client.clone(), self.config.url("/status/"), connections)))
+    }
+
+    /// Get the rate limiter; clone the `Arc` to share it with other clients
+    pub fn rate_limiter(&self) -> &Arc<EsiRateLimiter> {
+        &self.rate_limiter
+    }
+
+    /// Get the EVE SSO client, if authentication is configured
+    pub fn authenticator(&self) -> Option<&Arc<EveSso>> {
+        self.auth.as_ref()
+    }
+
+    /// Get the MCP logger, if one is attached
+    pub fn logger(&self) -> Option<&McpLogger> {
+        self.logger.as_ref()
+    }
+
+    /// Get the response size limits
+    pub fn response_limits(&self) -> &ResponseLimits {
+        &self.limits
+    }
+
+    /// Get how many requests may be open at once
+    pub fn max_concurrent_requests(&self) -> usize {
+        self.max_concurrent_requests
+    }
+
+    /// Get how long past expiry cached data may be served when ESI fails
+    pub fn serve_stale(&self) -> Option<Duration> {
+        self.serve_stale
+    }
+
+    /// Check if caching is enabled
+    pub fn has_cache(&self) -> bool {
+        self.cache.is_some()
+    }
+
+    /// The cache backend, when caching is enabled
+    pub(crate) fn cache_backend(&self) -> Option<&Arc<dyn CacheBackend>> {
+        self.cache.as_ref()
+    }
+
+    /// Market orders in a region, optionally for one item type (every page)
+    pub async fn market_orders(&self, region_id: i32, type_id: Option<i32>) -> Result<Vec<MarketOrder>> {
+        match self.cached(&CacheKey::market_orders(region_id, type_id)).await? {
+            Some(orders) => Ok(orders),
+            None => self.refresh_market_orders(region_id, type_id).await,
+        }
+    }
+
+    /// Fetches market orders whether or not they are cached, and caches them
+    pub(crate) async fn refresh_market_orders(&self, region_id: i32, type_id: Option<i32>) -> Result<Vec<MarketOrder>> {
+        let cache_key = CacheKey::market_orders(region_id, type_id);
+        let mut url = self.config.url(&format!("/markets/{region_id}/orders/"));
+        if let Some(tid) = type_id {
+            url = format!("{url}?type_id={tid}");
+        }
+
+        // Concurrent callers asking for the same orders share one ESI request
+        self.orders_in_flight
+            .run(cache_key.clone(), || {
+                self.fetch_and_store(&cache_key, "orders", async {
+                    let (orders, headers) = self.get_all_pages::<Value>(&url).await?;
+                    Ok((self.decode_objects(&url, orders), headers))
+                })
+            })
+            .await
+    }
+
+    /// Daily market history of one item type in a region
+    pub async fn market_history(&self, region_id: i32, type_id: i32) -> Result<Vec<MarketHistory>> {
+        match self.cached(&CacheKey::market_history(region_id, type_id)).await? {
+            Some(history) => Ok(history),
+            None => self.refresh_market_history(region_id, type_id).await,
+        }
+    }
+
+    /// Fetches market history whether or not it is cached, and caches it
+    pub(crate) async fn refresh_market_history(&self, region_id: i32, type_id: i32) -> Result<Vec<MarketHistory>> {
+        let cache_key = CacheKey::market_history(region_id, type_id);
+        let url = self.config.url(&format!("/markets/{region_id}/history/?type_id={type_id}"));
+
+        // Concurrent callers asking for the same history share one ESI request
+        self.history_in_flight
+            .run(cache_key.clone(), || {
+                self.fetch_and_store(&cache_key, "history", async {
+                    let (days, headers) = self.get_page::<Vec<Value>>(&url).await?;
+                    Ok((self.decode_objects(&url, days), headers))
+                })
+            })
+            .await
+    }
+
+    /// Market orders in a player-owned structure (every page)
+    ///
+    /// Made as an authenticated character holding the
+    /// `esi-markets.structure_markets.v1` scope who has docking access.
+    pub async fn structure_orders(&self, structure_id: i64) -> Result<Vec<MarketOrder>> {
+        let url = self.config.url(&format!("/markets/structures/{structure_id}/"));
+        self.cached_or_fetch(&CacheKey::structure_orders(structure_id), "orders", async {
+            let character_id = self.sso()?.character_with_scope(scopes::STRUCTURE_MARKETS)?;
+            let (orders, headers) = self
+                .authenticated_get_all_pages::<Value>(&url, character_id, scopes::STRUCTURE_MARKETS)
+                .await?;
+            Ok((self.decode_objects(&url, orders), headers))
+        })
+        .await
+    }
+
+    /// Outstanding public contracts in a region (every page)
+    pub async fn public_contracts(&self, region_id: i32) -> Result<Vec<PublicContract>> {
+        let url = self.config.url(&format!("/contracts/public/{region_id}/"));
+        self.cached_or_fetch(&CacheKey::public_contracts(region_id), "contracts", self.get_all_pages(&url))
+            .await
+    }
+
+    /// Items in a public contract (every page)
+    pub async fn public_contract_items(&self, contract_id: i64) -> Result<Vec<ContractItem>> {
+        let url = self.config.url(&format!("/contracts/public/items/{contract_id}/"));
+        self.cached_or_fetch(&CacheKey::public_contract_items(contract_id), "contracts", self.get_all_pages(&url))
+            .await
+    }
+
+    /// Open market orders of an authenticated character
+    pub async fn character_orders(&self, character_id: i64) -> Result<Vec<CharacterOrder>> {
+        let url = self.config.url(&format!("/characters/{character_id}/orders/"));
+        self.cached_or_fetch(
+            &CacheKey::character_orders(character_id),
+            "character_orders",
+            self.authenticated_get_json(&url, character_id, scopes::READ_CHARACTER_ORDERS),
+        )
+        .await
+    }
+
+    /// Recent market transactions of an authenticated character
+    pub async fn wallet_transactions(&self, character_id: i64) -> Result<Vec<WalletTransaction>> {
+        let url = self.config.url(&format!("/characters/{character_id}/wallet/transactions/"));
+        self.cached_or_fetch(
+            &CacheKey::wallet_transactions(character_id),
+            "wallet_transactions",
+            self.authenticated_get_json(&url, character_id, scopes::READ_WALLET),
+        )
+        .await
+    }
+
+    /// An item type's name, group, volume and levels, cached for the static data TTL
+    pub async fn type_info(&self, type_id: i32) -> Result<TypeInfo> {
+        let mut info: TypeInfo = self
+            .get_static(&format!("/universe/types/{type_id}/"), &CacheKey::type_info(type_id), "types")
+            .await?;
+        // ESI lists meta and tech level among the dogma attributes
+        info.meta_level = info.dogma_attribute(META_LEVEL_ATTRIBUTE_ID).map(|level| level as i32);
+        info.tech_level = info.dogma_attribute(TECH_LEVEL_ATTRIBUTE_ID).map(|level| level as i32);
+        Ok(info)
+    }
+
+    /// A player structure's name and solar system, cached for a week
+    ///
+    /// Needs an authenticated character holding the
+    /// `esi-universe.read_structures.v1` scope with docking access.
+    pub async fn structure(&self, structure_id: i64) -> Result<StructureInfo> {
+        let cache_key = CacheKey::universe("structure", structure_id);
+        if let Some(structure) = self.cached::<StructureInfo>(&cache_key).await? {
+            return Ok(structure);
+        }
+
+        let character_id = self.sso()?.character_with_scope(scopes::READ_STRUCTURES)?;
+        let url = self.config.url(&format!("/universe/structures/{structure_id}/"));
+        let (structure, _) = self
+            .authenticated_get_json::<StructureInfo>(&url, character_id, scopes::READ_STRUCTURES)
+            .await?;
+
+        self.store_cached(&cache_key, structure.clone(), "locations").await;
+        Ok(structure)
+    }
+
+    /// Tranquility's status, cached for 30 seconds as ESI does
+    pub async fn server_status(&self) -> Result<ServerStatus> {
+        self.get_cached("/status/", &CacheKey::server_status(), "status").await
+    }
+
+    /// Fetches a public ESI route, serving it from the cache when possible
+    ///
+    /// `path` is relative to the configured ESI version (e.g. `/universe/regions/10000002/`).
+    /// The response is cached using its `Expires` header, falling back to the
+    /// recommended TTL for `data_type`.
+    pub(crate) async fn get_cached<T>(&self, path: &str, cache_key: &CacheKey, data_type: &str) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned + serde::Serialize + Clone + Send,
+    {
+        self.cached_or_fetch(cache_key, data_type, self.get_public(path)).await
+    }
+
+    /// Fetches static ESI data, caching it for the full recommended TTL of `data_type`
+    ///
+    /// Unlike [`get_cached`](Self::get_cached) this ignores the response's
+    /// `Expires` header, which ESI sets to an hour even for data that only
+    /// changes with game patches.
+    pub(crate) async fn get_static<T>(&self, path: &str, cache_key: &CacheKey, data_type: &str) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned + serde::Serialize + Clone + Send,
+    {
+        if let Some(data) = self.cached::<T>(cache_key).await? {
+            return Ok(data);
+        }
+
+        let data = match self.get_public::<T>(path).await {
+            Ok((data, _)) => data,
+            Err(e) => return self.serve_stale_on_error(cache_key, e).await,
+        };
+        self.store_cached(cache_key, data.clone(), data_type).await;
+        Ok(data)
+    }
+
+    /// Serves `cache_key` from the cache, or awaits `fetch` and caches its result
+    async fn cached_or_fetch<T, F>(&self, cache_key: &CacheKey, data_type: &str, fetch: F) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned + serde::Serialize + Clone + Send,
+        F: Future<Output = Result<(T, HeaderMap)>>,
+    {
+        match self.cached::<T>(cache_key).await? {
+            Some(data) => Ok(data),
+            None => self.fetch_and_store(cache_key, data_type, fetch).await,
+        }
+    }
+
+    /// Awaits `fetch` and caches its result by the response headers
+    ///
+    /// When `fetch` fails, the expired cached copy is served if allowed.
+    async fn fetch_and_store<T, F>(&self, cache_key: &CacheKey, data_type: &str, fetch: F) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned + serde::Serialize + Clone + Send,
+        F: Future<Output = Result<(T, HeaderMap)>>,
+    {
+        let (data, headers) = match fetch.await {
+            Ok(fetched) => fetched,
+            Err(e) => return self.serve_stale_on_error(cache_key, e).await,
+        };
+
+        if let Some(cache) = &self.cache {
+            let cache_item = EsiHeaderParser::create_cache_item_from_response(data.clone(), &headers, data_type);
+            if self.fits_cache(cache_key, &cache_item) {
+                let _ = cache.set_with_grace(cache_key, cache_item, self.stale_grace()).await; // Ignore cache errors
+            }
+        }
+
+        Ok(data)
+    }
+
+    /// Sends a public GET request to ESI and decodes the JSON response
+    async fn get_public<T>(&self, path: &str) -> Result<(T, HeaderMap)>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        self.get_page(&self.config.url(path)).await
+    }
+
+    /// Sends a single GET to a public ESI route with a short timeout and no retries
+    ///
+    /// Meant for probes that must answer quickly. The request still waits for
+    /// the rate limiter, and the response's error-limit and deprecation headers
+    /// are recorded; its status is left for the caller to judge.
+    pub(crate) async fn probe_public(&self, path: &str, timeout: Duration) -> Result<Response> {
+        let url = self.config.url(path);
+        self.rate_limiter.acquire_for(EndpointClass::from_url(&url)).await?;
+        let response = self.http_client.get(&url).timeout(timeout).send().await?;
+        self.rate_limiter.record_error_limit(response.headers());
+        self.deprecations.observe(&url, response.headers());
+        Ok(response)
+    }
+
+    /// Sends a public POST request with a JSON body to ESI
+    ///
+    /// The response is returned whatever its status so callers can treat
+    /// specific failures (such as a 404 for unknown IDs) themselves.
+    pub(crate) async fn post_public<B>(&self, path: &str, body: &B) -> Result<Response>
+    where
+        B: serde::Serialize + Sync,
+    {
+        let url = self.config.url(path);
+        self.send(&url, || self.http_client.post(&url).json(body)).await
+    }
+
+    /// The EVE SSO client, or an error saying how to configure one
+    fn sso(&self) -> Result<&Arc<EveSso>> {
+        self.auth.as_ref().ok_or_else(|| {
+            TraderGraderError::AuthenticationError(
+                "EVE SSO is not configured; set TRADERGRADER_SSO_CLIENT_ID".to_string(),
+            )
+        })
+    }
+
+    /// Sends an authenticated GET request to ESI on behalf of a character
+    ///
+    /// Requires an attached EVE SSO client and a token for the character that
+    /// holds `scope`; expired access tokens are refreshed transparently.
+    pub(crate) async fn authenticated_get(&self, url: &str, character_id: i64, scope: &str) -> Result<Response> {
+        let access_token = self.sso()?.access_token_with_scope(character_id, scope).await?;
+
+        let response = self.send(url, || self.http_client.get(url).bearer_auth(&access_token)).await?;
+
+        if !response.status().is_success() {
+            return Err(self.rate_limiter.error_for_status(&response));
+        }
+
+        Ok(response)
+    }
+
+    /// Sends an authenticated GET request and decodes the JSON response with its headers
+    async fn authenticated_get_json<T>(&self, url: &str, character_id: i64, scope: &str) -> Result<(T, HeaderMap)>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let response = self.authenticated_get(url, character_id, scope).await?;
+        let headers = response.headers().clone();
+        Ok((self.read_json(response).await?, headers))
+    }
+
+    /// Fetches every page of a paginated, public ESI endpoint
+    ///
+    /// The first page gives the page count in its `X-Pages` header; the rest
+    /// are fetched concurrently, up to the concurrent request limit, and
+    /// combined in page order. Returns the combined items and the headers of
+    /// the first page. A `204 No Content` response (ESI's answer for an empty
+    /// result) yields no items.
+    async fn get_all_pages<T>(&self, url: &str) -> Result<(Vec<T>, HeaderMap)>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let (mut items, headers) = self.get_page::<Vec<T>>(&page_url(url, 1)).await?;
+        let remaining: Vec<Vec<T>> = stream::iter(2..=page_count(&headers))
+            .map(|page| async move {
+                let (items, _) = self.get_page::<Vec<T>>(&page_url(url, page)).await?;
+                Ok::<_, TraderGraderError>(items)
+            })
+            .buffered(self.max_concurrent_requests)
+            .try_collect()
+            .await?;
+        items.extend(remaining.into_iter().flatten());
+
+        Ok((items, headers))
+    }
+
+    /// Sends a public GET request to a full ESI URL and decodes the JSON response with its headers
+    ///
+    /// A `204 No Content` response (ESI's answer for an empty result) decodes as an empty list.
+    async fn get_page<T>(&self, url: &str) -> Result<(T, HeaderMap)>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let response = self.send(url, || self.http_client.get(url)).await?;
+
+        if !response.status().is_success() {
+            return Err(self.rate_limiter.error_for_status(&response));
+        }
+
+        let headers = response.headers().clone();
+        if response.status() == reqwest::StatusCode::NO_CONTENT {
+            return Ok((serde_json::from_str("[]")?, headers));
+        }
+        Ok((self.read_json(response).await?, headers))
+    }
+
+    /// Fetches every page of a paginated, authenticated ESI endpoint
+    ///
+    /// Pages after the first are fetched concurrently, like
+    /// [`get_all_pages`](Self::get_all_pages). Returns the combined items and
+    /// the headers of the first page.
+    async fn authenticated_get_all_pages<T>(
+        &self,
+        url: &str,
+        character_id: i64,
+        scope: &str,
+    ) -> Result<(Vec<T>, HeaderMap)>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let (mut items, headers) = self
+            .authenticated_get_json::<Vec<T>>(&page_url(url, 1), character_id, scope)
+            .await?;
+
+        let remaining: Vec<Vec<T>> = stream::iter(2..=page_count(&headers))
+            .map(|page| async move {
+                let (items, _) = self
+                    .authenticated_get_json::<Vec<T>>(&page_url(url, page), character_id, scope)
+                    .await?;
+                Ok::<_, TraderGraderError>(items)
+            })
+            .buffered(self.max_concurrent_requests)
+            .try_collect()
+            .await?;
+        items.extend(remaining.into_iter().flatten());
+
+        Ok((items, headers))
+    }
+
+    /// Decodes ESI objects one at a time, keeping the fields `T` doesn't declare
+    ///
+    /// An object that doesn't fit the schema, such as one missing a required
+    /// field, is skipped and reported instead of failing the whole response.
+    fn decode_objects<T>(&self, url: &str, values: Vec<Value>) -> Vec<T>
+    where
+        T: serde::de::DeserializeOwned + KeepsExtraFields,
+    {
+        let received = values.len();
+        let mut first_error = None;
+        let objects: Vec<T> = values
+            .into_iter()
+            .filter_map(|value| match serde_json::from_value::<EsiObject<T>>(value) {
+                Ok(EsiObject { mut known, extra }) => {
+                    known.set_extra(extra);
+                    Some(known)
+                }
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                    None
+                }
+            })
+            .collect();
+
+        if let Some(error) = first_error {
+            let skipped = received - objects.len();
+            tracing::warn!(url, skipped, %error, "skipped ESI objects that don't match the schema");
+            self.log(LogLevel::Warning, "esi", || {
+                json!({"event": "schema_mismatch", "url": url, "skipped": skipped, "error": error.to_string()})
+            });
+        }
+        objects
+    }
+
+    /// Sends a request to ESI through the rate limiter
+    ///
+    /// Records deprecation headers and reports the request, its status and
+    /// any retries to the MCP logger. Unsuccessful responses are returned for
+    /// the caller to handle.
+    #[tracing::instrument(level = "debug", skip_all, fields(url = %url, status, elapsed_ms))]
+    async fn send<F>(&self, url: &str, request: F) -> Result<Response>
+    where
+        F: Fn() -> reqwest::RequestBuilder,
+    {
+        let started = std::time::Instant::now();
+        let result = self
+            .rate_limiter
+            .execute_classified(
+                EndpointClass::from_url(url),
+                || async {
+                    let _slot = self.request_slots.acquire().await.map_err(|_| {
+                        TraderGraderError::InternalError("ESI request slots closed".to_string())
+                    })?;
+                    Ok(request().send().await?)
+                },
+                |status, delay, attempt| {
+                    self.log(LogLevel::Warning, "rate_limit", || {
+                        json!({
+                            "event": "retry",
+                            "url": url,
+                            "status": status.as_u16(),
+                            "delay_ms": delay.as_millis() as u64,
+                            "attempt": attempt
+                        })
+                    })
+                },
+            )
+            .await;
+        let elapsed_ms = started.elapsed().as_millis() as u64;
+        tracing::Span::current().record("elapsed_ms", elapsed_ms);
+
+        match result {
+            Ok(response) => {
+                self.deprecations.observe(url, response.headers());
+                let status = response.status();
+                tracing::Span::current().record("status", status.as_u16());
+                if status.is_success() {
+                    tracing::debug!("ESI request completed");
+                } else {
+                    tracing::warn!(url, %status, elapsed_ms, "ESI request unsuccessful");
+                }
+                let level = if status.is_success() { LogLevel::Info } else { LogLevel::Warning };
+                self.log(level, "esi", || {
+                    json!({"event": "esi_request", "url": url, "status": status.as_u16(), "elapsed_ms": elapsed_ms})
+                });
+                Ok(response)
+            }
+            Err(e) => {
+                tracing::warn!(url, elapsed_ms, error = %e, "ESI request failed");
+                self.log(LogLevel::Error, "esi", || {
+                    json!({"event": "esi_request", "url": url, "error": e.to_string(), "elapsed_ms": elapsed_ms})
+                });
+                Err(e)
+            }
+        }
+    }
+
+    /// Queues an MCP log event when a logger is attached and `level` is enabled
+    fn log(&self, level: LogLevel, logger: &str, data: impl FnOnce() -> serde_json::Value) {
+        if let Some(mcp_logger) = self.logger.as_ref().filter(|l| l.enabled(level)) {
+            mcp_logger.log(level, logger, data());
+        }
+    }
+
+    /// Decodes a JSON response body, enforcing the response size limit and tool budget
+    pub(crate) async fn read_json<T>(&self, response: Response) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let body = limits::read_body(response, self.limits.max_response_bytes).await?;
+        Ok(serde_json::from_slice(&body)?)
+    }
+
+    /// Whether an item is small enough to cache; oversized items are served but not kept
+    fn fits_cache<T: serde::Serialize>(&self, cache_key: &CacheKey, item: &CacheItem<T>) -> bool {
+        let size = bincode::serialized_size(item).unwrap_or(u64::MAX);
+        let fits = size <= self.limits.max_cached_item_bytes as u64;
+        if !fits {
+            tracing::debug!(key = %cache_key, bytes = size, "result too large to cache");
+            self.log(LogLevel::Notice, "cache", || {
+                json!({"event": "cache_skip", "key": cache_key.to_string(), "bytes": size})
+            });
+        }
+        fits
+    }
+
+    /// Reads an item from the cache, if caching is enabled and the item is fresh
+    #[tracing::instrument(level = "trace", skip_all, fields(key = %cache_key))]
+    pub(crate) async fn cached<T>(&self, cache_key: &CacheKey) -> Result<Option<T>>
+    where
+        T: serde::de::DeserializeOwned + Send,
+    {
+        let Some(cache) = &self.cache else {
+            return Ok(None);
+        };
+        let data = cache.get::<T>(cache_key).await?.map(|item| item.data);
+        let hit = data.is_some();
+        tracing::debug!(key = %cache_key, hit, "cache lookup");
+        self.log(LogLevel::Debug, "cache", || {
+            json!({"event": if hit { "cache_hit" } else { "cache_miss" }, "key": cache_key.to_string()})
+        });
+        Ok(data)
+    }
+
+    /// Time left before a cached item expires, or `None` when it isn't cached or has expired
+    pub(crate) async fn cached_ttl<T>(&self, cache_key: &CacheKey) -> Option<Duration>
+    where
+        T: serde::de::DeserializeOwned + Send,
+    {
+        let cache = self.cache.as_ref()?;
+        cache.get::<T>(cache_key).await.ok()??.remaining_ttl()
+    }
+
+    /// Stores an item in the cache for the recommended TTL of `data_type`
+    #[tracing::instrument(level = "trace", skip_all, fields(key = %cache_key, data_type))]
+    pub(crate) async fn store_cached<T>(&self, cache_key: &CacheKey, data: T, data_type: &str)
+    where
+        T: serde::Serialize + Send,
+    {
+        if let Some(cache) = &self.cache {
+            let ttl = EsiHeaderParser::recommended_ttl_for_data_type(data_type);
+            let item = CacheItem::new(data, ttl);
+            if self.fits_cache(cache_key, &item) {
+                let _ = cache.set_with_grace(cache_key, item, self.stale_grace()).await; // Ignore cache errors
+            }
+        }
+    }
+
+    /// Extra time cached items are kept past their TTL for serving stale
+    fn stale_grace(&self) -> Duration {
+        self.serve_stale.unwrap_or_default()
+    }
+
+    /// Falls back to the expired cached copy of `cache_key` after ESI failed with `error`
+    ///
+    /// Only ESI and network failures are covered, and only when serving stale
+    /// data is enabled and the copy expired no more than the allowed time ago;
+    /// otherwise `error` is returned. Served copies are reported to the
+    /// surrounding [`track_stale_reads`](crate::cache::track_stale_reads).
+    async fn serve_stale_on_error<T>(&self, cache_key: &CacheKey, error: TraderGraderError) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned + Send,
+    {
+        let (Some(cache), Some(max_stale)) = (&self.cache, self.serve_stale) else {
+            return Err(error);
+        };
+        if !matches!(
+            error,
+            TraderGraderError::EsiApiError { .. }
+                | TraderGraderError::EsiUnavailable { .. }
+                | TraderGraderError::NetworkError(_)
+                | TraderGraderError::RateLimitError { .. }
+                | TraderGraderError::JsonError(_)
+        ) {
+            return Err(error);
+        }
+        let item = match cache.get_allow_stale::<T>(cache_key).await {
+            Ok(Some(item)) if item.expired_for().is_none_or(|expired| expired <= max_stale) => item,
+            _ => return Err(error),
+        };
+
+        let age = item.age();
+        tracing::warn!(key = %cache_key, age_secs = age.as_secs(), %error, "ESI failed, serving stale cached data");
+        self.log(LogLevel::Warning, "cache", || {
+            json!({
+                "event": "cache_stale",
+                "key": cache_key.to_string(),
+                "age_secs": age.as_secs(),
+                "error": error.to_string()
+            })
+        });
+        note_stale_read(StaleRead {
+            key: cache_key.to_string(),
+            age,
+            reason: error.to_string(),
+        });
+        Ok(item.data)
+    }
+}
+
+/// Adds the `page` query parameter to an ESI URL that may already have a query
+fn page_url(url: &str, page: u32) -> String {
+    let separator = if url.contains('?') { '&' } else { '?' };
+    format!("{url}{separator}page={page}")
+}
+
+/// Reads the ESI `X-Pages` header, defaulting to a single page
+fn page_count(headers: &HeaderMap) -> u32 {
+    headers
+        .get("x-pages")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|s| s.parse::<u32>().ok())
+        .unwrap_or(1)
+        .max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::{track_stale_reads, InMemoryCacheBackend};
+    use crate::rate_limit::RateLimitConfig;
+
+    fn client(cache: Option<Arc<dyn CacheBackend>>) -> EsiClient {
+        EsiClient::new(cache, EsiRateLimiter::shared(RateLimitConfig::testing()).unwrap(), EsiConfig::default())
+            .unwrap()
+    }
+
+    /// Serve `pages` pages of `[page]` from a local socket, earlier pages slowest, tracking peak concurrency
+    async fn serve_slow_pages(pages: usize, peak: Arc<std::sync::atomic::AtomicUsize>) -> std::net::SocketAddr {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let (in_flight, peak) = (in_flight.clone(), peak.clone());
+                tokio::spawn(async move {
+                    let mut request = Vec::new();
+                    let mut buf = [0u8; 1024];
+                    while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                        let n = socket.read(&mut buf).await.unwrap();
+                        if n == 0 {
+                            return;
+                        }
+                        request.extend_from_slice(&buf[..n]);
+                    }
+                    let request_line = String::from_utf8_lossy(&request).lines().next().unwrap_or("").to_string();
+                    let page: usize = request_line
+                        .split("page=")
+                        .nth(1)
+                        .and_then(|rest| rest.split(|c: char| !c.is_ascii_digit()).next())
+                        .and_then(|n| n.parse().ok())
+                        .unwrap_or(1);
+
+                    let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(((pages + 1 - page) * 30) as u64)).await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+
+                    let body = format!("[{page}]");
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nx-pages: {pages}\r\n\
+                         Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    );
+                    let _ = socket.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_get_all_pages_bounded_and_ordered() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let peak = Arc::new(AtomicUsize::new(0));
+        let addr = serve_slow_pages(6, peak.clone()).await;
+        let limiter = EsiRateLimiter::shared(RateLimitConfig::testing()).unwrap();
+        let esi = EsiClient::new(None, limiter, EsiConfig::default().without_proxy())
+            .unwrap()
+            .with_max_concurrent_requests(2);
+
+        let (items, _) = esi.get_all_pages::<i64>(&format!("http://{addr}/markets/10000002/orders/")).await.unwrap();
+        // Later pages finish first, but the result still follows page order
+        assert_eq!(items, vec![1, 2, 3, 4, 5, 6]);
+        // Pages overlap, yet never beyond the request slots
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_page_count() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(page_count(&headers), 1);
+
+        headers.insert("x-pages", "7".parse().unwrap());
+        assert_eq!(page_count(&headers), 7);
+
+        headers.insert("x-pages", "0".parse().unwrap());
+        assert_eq!(page_count(&headers), 1);
+
+        assert_eq!(
+            page_url("https://esi.evetech.net/markets/10000002/orders/", 2),
+            "https://esi.evetech.net/markets/10000002/orders/?page=2"
+        );
+        assert_eq!(
+            page_url("https://esi.evetech.net/markets/10000002/orders/?type_id=34", 3),
+            "https://esi.evetech.net/markets/10000002/orders/?type_id=34&page=3"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cached_endpoints_need_no_network() {
+        let cache = Arc::new(InMemoryCacheBackend::new(100, None));
+        let status = ServerStatus {
+            players: 23_000,
+            server_version: "2541839".to_string(),
+            start_time: "2026-10-17T11:00:00Z".to_string(),
+            vip: None,
+        };
+        let key = CacheKey::server_status();
+        cache.set(&key, CacheItem::new(status.clone(), Duration::from_secs(60))).await.unwrap();
+
+        let esi = client(Some(cache));
+        assert_eq!(esi.server_status().await.unwrap(), status);
+        assert_eq!(esi.cached_ttl::<ServerStatus>(&key).await.map(|ttl| ttl <= Duration::from_secs(60)), Some(true));
+    }
+
+    #[test]
+    fn test_decode_objects_skips_malformed() {
+        let days = vec![
+            json!({"average": 5.0, "date": "2026-10-16", "highest": 5.5, "lowest": 4.5, "volume": 1000}),
+            json!({"average": "n/a", "date": "2026-10-17"}),
+        ];
+        let history: Vec<MarketHistory> = client(None).decode_objects("/markets/10000002/history/", days);
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].order_count, 0);
+        assert!(history[0].extra.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_authenticated_endpoints_require_sso() {
+        let esi = client(None);
+        let result = esi.structure_orders(1028858195912).await;
+        assert!(matches!(result, Err(TraderGraderError::AuthenticationError(_))));
+        let result = esi.character_orders(1).await;
+        assert!(matches!(result, Err(TraderGraderError::AuthenticationError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_serve_stale_on_error() {
+        let cache = Arc::new(InMemoryCacheBackend::new(100, None));
+        let key = CacheKey::market_history(10000002, 34);
+        let item = CacheItem {
+            data: vec![34_i32],
+            cached_at: chrono::Utc::now() - chrono::Duration::minutes(10),
+            ttl: Duration::from_secs(60),
+        };
+        cache.set_with_grace(&key, item, Duration::from_secs(3600)).await.unwrap();
+        let esi_down = || TraderGraderError::EsiApiError {
+            message: "request failed with status: 503 Service Unavailable".to_string(),
+        };
+
+        // Off by default
+        let esi = client(Some(cache));
+        assert!(esi.serve_stale_on_error::<Vec<i32>>(&key, esi_down()).await.is_err());
+
+        let esi = esi.with_serve_stale(Some(Duration::from_secs(3600)));
+        let (data, reads) = track_stale_reads(esi.serve_stale_on_error::<Vec<i32>>(&key, esi_down())).await;
+        assert_eq!(data.unwrap(), vec![34]);
+        assert_eq!(reads.len(), 1);
+        assert!(reads[0].age >= Duration::from_secs(599));
+        assert!(reads[0].reason.contains("503"));
+
+        // Bad requests aren't papered over, and copies past the window aren't served
+        let invalid = TraderGraderError::InvalidParams("bad".to_string());
+        assert!(esi.serve_stale_on_error::<Vec<i32>>(&key, invalid).await.is_err());
+        let esi = esi.with_serve_stale(Some(Duration::from_secs(60)));
+        assert!(esi.serve_stale_on_error::<Vec<i32>>(&key, esi_down()).await.is_err());
+    }
+}
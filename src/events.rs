@@ -0,0 +1,91 @@
+//! Game events that move EVE Online markets
+//!
+//! Expansions rebalance ships, add items and change how resources are
+//! gathered, and speculators buy ahead of them. This bundles the dates of
+//! major releases so tools can refer to an event by name instead of a date;
+//! any other date can still be given directly.
+
+use crate::error::{Result, TraderGraderError};
+use crate::history::parse_history_date;
+use chrono::NaiveDate;
+
+/// Major releases as `(name, YYYY-MM-DD)`, oldest first
+const KNOWN_EVENTS: &[(&str, &str)] = &[
+    ("Lifeblood", "2017-10-24"),
+    ("Into the Abyss", "2018-05-29"),
+    ("Onslaught", "2018-11-13"),
+    ("Invasion", "2019-05-28"),
+    ("Uprising", "2022-11-08"),
+    ("Revenant", "2023-11-14"),
+    ("Equinox", "2024-06-11"),
+    ("Havoc", "2024-11-19"),
+];
+
+/// A dated game event, such as an expansion release
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameEvent {
+    pub name: String,
+    pub date: NaiveDate,
+}
+
+impl GameEvent {
+    /// An event on `date` with a free-form name
+    pub fn new(name: impl Into<String>, date: NaiveDate) -> Self {
+        Self {
+            name: name.into(),
+            date,
+        }
+    }
+
+    /// The bundled major releases, oldest first
+    pub fn known() -> Vec<Self> {
+        KNOWN_EVENTS
+            .iter()
+            .map(|(name, date)| Self::new(*name, NaiveDate::parse_from_str(date, "%Y-%m-%d").expect("valid event date")))
+            .collect()
+    }
+
+    /// Looks up a bundled event by name (case-insensitive), or reads a `YYYY-MM-DD` date
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::events::GameEvent;
+    ///
+    /// assert_eq!(GameEvent::parse("equinox")?.date.to_string(), "2024-06-11");
+    /// assert_eq!(GameEvent::parse("2025-03-04")?.name, "2025-03-04");
+    /// assert!(GameEvent::parse("next tuesday").is_err());
+    /// # Ok::<(), tradergrader::TraderGraderError>(())
+    /// ```
+    pub fn parse(value: &str) -> Result<Self> {
+        let value = value.trim();
+        if let Some(event) = Self::known().into_iter().find(|e| e.name.eq_ignore_ascii_case(value)) {
+            return Ok(event);
+        }
+        parse_history_date("event", value)
+            .map(|date| Self::new(value, date))
+            .map_err(|_| TraderGraderError::InvalidArgument {
+                field: "event".to_string(),
+                reason: format!("expected a YYYY-MM-DD date or one of {}, got '{value}'", known_names()),
+            })
+    }
+}
+
+/// Names of the bundled events, comma-separated
+pub fn known_names() -> String {
+    KNOWN_EVENTS.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_events_are_ordered() {
+        let events = GameEvent::known();
+        assert_eq!(events.len(), KNOWN_EVENTS.len());
+        assert!(events.windows(2).all(|pair| pair[0].date < pair[1].date));
+        assert_eq!(GameEvent::parse(" Into the Abyss ").unwrap().date.to_string(), "2018-05-29");
+        assert!(GameEvent::parse("Abyss").unwrap_err().to_string().contains("Equinox"));
+    }
+}
@@ -0,0 +1,362 @@
+//! CSV export of market history and orders
+//!
+//! Most traders keep their numbers in spreadsheets. These helpers turn ESI
+//! history and order data into CSV with a fixed header row, for the
+//! `export_market_history` tool and the command line's `--format csv`:
+//!
+//! ```text
+//! tradergrader history 10000002 34 --days 30 --format csv > tritanium.csv
+//! tradergrader orders 10000002 34 --format csv
+//! ```
+
+use crate::error::{Result, TraderGraderError};
+use crate::market::MarketClient;
+use crate::types::{MarketHistory, MarketOrder};
+use serde::Serialize;
+use std::str::FromStr;
+
+/// Columns of exported history, oldest day first
+pub const HISTORY_COLUMNS: [&str; 6] = ["date", "average", "highest", "lowest", "order_count", "volume"];
+
+/// Columns of exported orders, sells cheapest first then buys dearest first
+pub const ORDER_COLUMNS: [&str; 12] = [
+    "order_id",
+    "type_id",
+    "side",
+    "price",
+    "volume_remain",
+    "volume_total",
+    "min_volume",
+    "location_id",
+    "system_id",
+    "range",
+    "issued",
+    "duration",
+];
+
+/// Output format of command line exports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    /// Human-readable lines
+    #[default]
+    Text,
+    /// Comma-separated values with a header row
+    Csv,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "csv" => Ok(Self::Csv),
+            other => Err(format!("Unknown format '{other}', expected text or csv")),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct OrderRow<'a> {
+    order_id: i64,
+    type_id: i32,
+    side: &'static str,
+    price: f64,
+    volume_remain: i32,
+    volume_total: i32,
+    min_volume: i32,
+    location_id: i64,
+    system_id: i32,
+    range: &'a str,
+    issued: &'a str,
+    duration: i32,
+}
+
+/// Days of history, oldest first, keeping only the most recent `days` when given
+pub fn recent_history(history: &[MarketHistory], days: Option<usize>) -> Vec<MarketHistory> {
+    let mut rows = history.to_vec();
+    rows.sort_by(|a, b| a.date.cmp(&b.date));
+    if let Some(days) = days {
+        rows.drain(..rows.len().saturating_sub(days));
+    }
+    rows
+}
+
+/// Writes history rows as CSV, in the order given
+///
+/// # Examples
+///
+/// ```
+/// use tradergrader::export::history_csv;
+/// use tradergrader::MarketHistory;
+///
+/// let day = MarketHistory {
+///     date: "2024-05-01".to_string(),
+///     average: 5.1,
+///     highest: 5.3,
+///     lowest: 4.9,
+///     order_count: 1200,
+///     volume: 9_000_000,
+///     extra: Default::default(),
+/// };
+/// let csv = history_csv(&[day])?;
+/// assert_eq!(csv, "date,average,highest,lowest,order_count,volume\n2024-05-01,5.1,5.3,4.9,1200,9000000\n");
+/// # Ok::<(), tradergrader::TraderGraderError>(())
+/// ```
+pub fn history_csv(history: &[MarketHistory]) -> Result<String> {
+    let rows = history
+        .iter()
+        .map(|day| (&day.date, day.average, day.highest, day.lowest, day.order_count, day.volume));
+    write_csv(&HISTORY_COLUMNS, rows)
+}
+
+/// Writes orders as CSV, sells cheapest first then buys dearest first
+pub fn orders_csv(orders: &[MarketOrder]) -> Result<String> {
+    let rows = sorted_orders(orders).into_iter().map(|order| OrderRow {
+        order_id: order.order_id,
+        type_id: order.type_id,
+        side: if order.is_buy_order { "buy" } else { "sell" },
+        price: order.price,
+        volume_remain: order.volume_remain,
+        volume_total: order.volume_total,
+        min_volume: order.min_volume,
+        location_id: order.location_id,
+        system_id: order.system_id,
+        range: &order.range,
+        issued: &order.issued,
+        duration: order.duration,
+    });
+    write_csv(&ORDER_COLUMNS, rows)
+}
+
+fn sorted_orders(orders: &[MarketOrder]) -> Vec<&MarketOrder> {
+    let mut sorted: Vec<&MarketOrder> = orders.iter().collect();
+    sorted.sort_by(|a, b| match (a.is_buy_order, b.is_buy_order) {
+        (false, false) => a.price.total_cmp(&b.price),
+        (true, true) => b.price.total_cmp(&a.price),
+        (buy, _) => buy.cmp(&!buy),
+    });
+    sorted
+}
+
+fn write_csv<T: Serialize>(columns: &[&str], rows: impl IntoIterator<Item = T>) -> Result<String> {
+    let csv_error = |e: csv::Error| TraderGraderError::InternalError(format!("Failed to write CSV: {e}"));
+    let mut writer = csv::WriterBuilder::new().has_headers(false).from_writer(Vec::new());
+    writer.write_record(columns).map_err(csv_error)?;
+    for row in rows {
+        writer.serialize(row).map_err(csv_error)?;
+    }
+    let bytes = writer
+        .into_inner()
+        .map_err(|e| TraderGraderError::InternalError(format!("Failed to write CSV: {e}")))?;
+    String::from_utf8(bytes).map_err(|e| TraderGraderError::InternalError(format!("Failed to write CSV: {e}")))
+}
+
+/// A one-shot export requested on the command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExportCommand {
+    /// `history <region_id> <type_id> [--days N]`
+    History {
+        region_id: i32,
+        type_id: i32,
+        days: Option<usize>,
+        format: ExportFormat,
+    },
+    /// `orders <region_id> <type_id>`
+    Orders {
+        region_id: i32,
+        type_id: i32,
+        format: ExportFormat,
+    },
+}
+
+impl ExportCommand {
+    /// Parses command line arguments (without the program name)
+    ///
+    /// Returns `None` when the first argument isn't an export command, so the
+    /// caller can start the MCP server instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::export::{ExportCommand, ExportFormat};
+    ///
+    /// let args = ["history", "10000002", "34", "--format", "csv"].map(String::from);
+    /// let command = ExportCommand::parse(&args)?;
+    /// assert!(matches!(command, Some(ExportCommand::History { format: ExportFormat::Csv, .. })));
+    /// assert_eq!(ExportCommand::parse(&["--health".to_string()])?, None);
+    /// # Ok::<(), tradergrader::TraderGraderError>(())
+    /// ```
+    pub fn parse(args: &[String]) -> Result<Option<Self>> {
+        let Some(command) = args.first() else {
+            return Ok(None);
+        };
+        if command != "history" && command != "orders" {
+            return Ok(None);
+        }
+
+        let mut positional = Vec::new();
+        let mut format = ExportFormat::default();
+        let mut days = None;
+        let mut rest = args[1..].iter();
+        while let Some(arg) = rest.next() {
+            match arg.as_str() {
+                "--format" | "--days" => {
+                    let value = rest
+                        .next()
+                        .ok_or_else(|| TraderGraderError::InvalidParams(format!("{arg} needs a value")))?;
+                    if arg == "--format" {
+                        format = value.parse().map_err(TraderGraderError::InvalidParams)?;
+                    } else {
+                        days = Some(value.parse().map_err(|_| TraderGraderError::InvalidArgument {
+                            field: "days".to_string(),
+                            reason: format!("'{value}' is not a number of days"),
+                        })?);
+                    }
+                }
+                _ => positional.push(arg),
+            }
+        }
+
+        let [region_id, type_id] = positional[..] else {
+            return Err(TraderGraderError::InvalidParams(format!(
+                "Usage: tradergrader {command} <region_id> <type_id> [--format text|csv]"
+            )));
+        };
+        let id = |field: &str, value: &str| {
+            value.parse().map_err(|_| TraderGraderError::InvalidArgument {
+                field: field.to_string(),
+                reason: format!("'{value}' is not an ID"),
+            })
+        };
+        let region_id = id("region_id", region_id)?;
+        let type_id = id("type_id", type_id)?;
+
+        Ok(Some(if command == "history" {
+            Self::History {
+                region_id,
+                type_id,
+                days,
+                format,
+            }
+        } else {
+            Self::Orders {
+                region_id,
+                type_id,
+                format,
+            }
+        }))
+    }
+
+    /// Fetches the data and renders it in the requested format
+    pub async fn run(&self, client: &MarketClient) -> Result<String> {
+        match *self {
+            Self::History {
+                region_id,
+                type_id,
+                days,
+                format,
+            } => {
+                let history = recent_history(&client.fetch_market_history(region_id, type_id).await?, days);
+                match format {
+                    ExportFormat::Csv => history_csv(&history),
+                    ExportFormat::Text => Ok(history
+                        .iter()
+                        .map(|day| {
+                            format!(
+                                "{}: Avg: {:.2} ISK, High: {:.2} ISK, Low: {:.2} ISK, Volume: {}\n",
+                                day.date, day.average, day.highest, day.lowest, day.volume
+                            )
+                        })
+                        .collect()),
+                }
+            }
+            Self::Orders {
+                region_id,
+                type_id,
+                format,
+            } => {
+                let orders = client.fetch_market_orders(region_id, Some(type_id)).await?;
+                match format {
+                    ExportFormat::Csv => orders_csv(&orders),
+                    ExportFormat::Text => Ok(sorted_orders(&orders)
+                        .iter()
+                        .map(|order| {
+                            format!(
+                                "{} {:.2} ISK x {} at {}\n",
+                                if order.is_buy_order { "BUY " } else { "SELL" },
+                                order.price,
+                                order.volume_remain,
+                                order.location_id
+                            )
+                        })
+                        .collect()),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{history_day, market_order};
+
+    fn order(order_id: i64, is_buy_order: bool, price: f64) -> MarketOrder {
+        MarketOrder {
+            volume_remain: 50,
+            ..market_order(order_id, is_buy_order, price).with_issued("2024-05-01T12:00:00Z")
+        }
+    }
+
+    #[test]
+    fn test_recent_history() {
+        let history = vec![
+            history_day("2024-05-03", 3.0, 100),
+            history_day("2024-05-01", 1.0, 100),
+            history_day("2024-05-02", 2.0, 100),
+        ];
+        let dates: Vec<String> = recent_history(&history, Some(2)).into_iter().map(|d| d.date).collect();
+        assert_eq!(dates, ["2024-05-02", "2024-05-03"]);
+        assert_eq!(recent_history(&history, None).len(), 3);
+    }
+
+    #[test]
+    fn test_orders_csv() {
+        let orders = vec![order(1, true, 4.0), order(2, false, 6.0), order(3, false, 5.0), order(4, true, 4.5)];
+        let csv = orders_csv(&orders).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], ORDER_COLUMNS.join(","));
+        let ids: Vec<&str> = lines[1..].iter().map(|line| line.split(',').next().unwrap()).collect();
+        assert_eq!(ids, ["3", "2", "4", "1"]);
+        assert_eq!(lines[1], "3,34,sell,5.0,50,100,1,60003760,30000142,region,2024-05-01T12:00:00Z,90");
+
+        // The header is written even with nothing to export
+        assert_eq!(history_csv(&[]).unwrap(), "date,average,highest,lowest,order_count,volume\n");
+    }
+
+    #[test]
+    fn test_parse_command() {
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            ExportCommand::parse(&args(&["orders", "10000002", "34"])).unwrap(),
+            Some(ExportCommand::Orders {
+                region_id: 10000002,
+                type_id: 34,
+                format: ExportFormat::Text
+            })
+        );
+        assert_eq!(
+            ExportCommand::parse(&args(&["history", "--days", "30", "10000002", "34", "--format", "CSV"])).unwrap(),
+            Some(ExportCommand::History {
+                region_id: 10000002,
+                type_id: 34,
+                days: Some(30),
+                format: ExportFormat::Csv
+            })
+        );
+        assert!(ExportCommand::parse(&args(&["history", "10000002"])).is_err());
+        assert!(ExportCommand::parse(&args(&["history", "10000002", "34", "--format", "xlsx"])).is_err());
+        assert_eq!(ExportCommand::parse(&[]).unwrap(), None);
+    }
+}
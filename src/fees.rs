@@ -0,0 +1,373 @@
+//! Market fee schedules for TraderGrader
+//!
+//! CCP changes the sales tax and broker fee formulas from time to time. Each
+//! [`FeeSchedule`] records one version of the formulas together with the date
+//! it took effect, so profit reconstructions for past trades can use the fees
+//! that applied at the time while current analysis uses the latest schedule.
+//!
+//! The built-in schedules cover NPC station trading. Player-owned structures
+//! charge a broker fee set by their owner instead, which skills and standings
+//! don't reduce; a [`TradingProfile`] records which kind of venue a character
+//! trades at, along with their skills and standings.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Skills and standings that reduce market fees
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize, Serialize)]
+pub struct TradingSkills {
+    /// Accounting level (reduces sales tax)
+    pub accounting: u8,
+    /// Broker Relations level (reduces broker fees)
+    pub broker_relations: u8,
+    /// Standing towards the station owner's faction
+    pub faction_standing: f64,
+    /// Standing towards the station owner's corporation
+    pub corporation_standing: f64,
+}
+
+impl TradingSkills {
+    /// A character with Accounting and Broker Relations at level V and no standings
+    pub fn max_skills() -> Self {
+        Self {
+            accounting: 5,
+            broker_relations: 5,
+            ..Self::default()
+        }
+    }
+}
+
+/// Where a character places their orders
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum TradingVenue {
+    /// An NPC station, charging the fee schedule's broker fee
+    #[default]
+    NpcStation,
+    /// A player-owned structure charging its owner's broker fee
+    Structure { broker_fee_percent: f64 },
+}
+
+impl fmt::Display for TradingVenue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NpcStation => f.write_str("NPC station"),
+            Self::Structure { broker_fee_percent } => write!(f, "structure ({broker_fee_percent:.2}% broker fee)"),
+        }
+    }
+}
+
+/// A character's fee-relevant skills and standings and where they trade
+///
+/// Profitability tools price fees from the session's profile.
+///
+/// # Examples
+///
+/// ```
+/// use tradergrader::fees::{FeeSchedules, TradingProfile, TradingSkills, TradingVenue};
+///
+/// let profile = TradingProfile {
+///     skills: TradingSkills::max_skills(),
+///     venue: TradingVenue::Structure { broker_fee_percent: 0.5 },
+/// };
+/// let fees = profile.fee_schedule(FeeSchedules::default().latest());
+/// // Broker Relations doesn't reduce a structure's fee, but Accounting still reduces sales tax
+/// assert_eq!(fees.broker_fee_percent(&profile.skills), 0.5);
+/// assert!((fees.sales_tax_percent(&profile.skills) - 3.375).abs() < 1e-9);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize, Serialize)]
+pub struct TradingProfile {
+    pub skills: TradingSkills,
+    pub venue: TradingVenue,
+}
+
+impl TradingProfile {
+    /// `schedule` as it applies to orders placed at this profile's venue
+    pub fn fee_schedule(&self, schedule: &FeeSchedule) -> FeeSchedule {
+        match self.venue {
+            TradingVenue::NpcStation => schedule.clone(),
+            TradingVenue::Structure { broker_fee_percent } => FeeSchedule {
+                name: format!("{} at a structure", schedule.name),
+                base_broker_fee_percent: broker_fee_percent,
+                broker_relations_reduction: 0.0,
+                faction_standing_reduction: 0.0,
+                corporation_standing_reduction: 0.0,
+                ..schedule.clone()
+            },
+        }
+    }
+
+    /// The latest built-in schedule as it applies to this profile
+    pub fn current_fees(&self) -> FeeSchedule {
+        self.fee_schedule(FeeSchedules::default().latest())
+    }
+
+    /// Formats the profile with the sales tax and broker fee it pays today
+    pub fn to_text(&self) -> String {
+        let fees = self.current_fees();
+        format!(
+            "Trading Profile:\n\
+             Accounting: {}\n\
+             Broker Relations: {}\n\
+             Faction standing: {:.2}\n\
+             Corporation standing: {:.2}\n\
+             Venue: {}\n\
+             \n\
+             Fees ({} schedule): {:.2}% sales tax, {:.2}% broker fee",
+            self.skills.accounting,
+            self.skills.broker_relations,
+            self.skills.faction_standing,
+            self.skills.corporation_standing,
+            self.venue,
+            fees.name,
+            fees.sales_tax_percent(&self.skills),
+            fees.broker_fee_percent(&self.skills)
+        )
+    }
+}
+
+/// One version of the sales tax and broker fee formulas
+///
+/// Sales tax is `base_sales_tax_percent × (1 - accounting_reduction × level)`.
+/// The broker fee is `base_broker_fee_percent` minus fixed percentage points per
+/// Broker Relations level and per point of positive standing, charged at
+/// `min_broker_fee_isk` or more per order.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct FeeSchedule {
+    pub name: String,
+    /// First day the schedule applies
+    pub effective_from: NaiveDate,
+    pub base_sales_tax_percent: f64,
+    /// Fraction of the sales tax removed per Accounting level
+    pub accounting_reduction: f64,
+    pub base_broker_fee_percent: f64,
+    /// Percentage points removed per Broker Relations level
+    pub broker_relations_reduction: f64,
+    /// Percentage points removed per point of faction standing
+    pub faction_standing_reduction: f64,
+    /// Percentage points removed per point of corporation standing
+    pub corporation_standing_reduction: f64,
+    pub min_broker_fee_isk: f64,
+}
+
+impl FeeSchedule {
+    /// Sales tax rate in percent for a character
+    pub fn sales_tax_percent(&self, skills: &TradingSkills) -> f64 {
+        let reduction = self.accounting_reduction * skills.accounting.min(5) as f64;
+        (self.base_sales_tax_percent * (1.0 - reduction)).max(0.0)
+    }
+
+    /// Broker fee rate in percent for a character
+    pub fn broker_fee_percent(&self, skills: &TradingSkills) -> f64 {
+        let reduction = self.broker_relations_reduction * skills.broker_relations.min(5) as f64
+            + self.faction_standing_reduction * skills.faction_standing.clamp(0.0, 10.0)
+            + self.corporation_standing_reduction * skills.corporation_standing.clamp(0.0, 10.0);
+        (self.base_broker_fee_percent - reduction).max(0.0)
+    }
+
+    /// Sales tax in ISK on a sale worth `value`
+    pub fn sales_tax(&self, value: f64, skills: &TradingSkills) -> f64 {
+        value * self.sales_tax_percent(skills) / 100.0
+    }
+
+    /// Broker fee in ISK for placing an order worth `value`
+    pub fn broker_fee(&self, value: f64, skills: &TradingSkills) -> f64 {
+        (value * self.broker_fee_percent(skills) / 100.0).max(self.min_broker_fee_isk)
+    }
+}
+
+/// Fee schedules ordered by the date they took effect
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use tradergrader::fees::{FeeSchedules, TradingSkills};
+///
+/// let schedules = FeeSchedules::default();
+/// let skills = TradingSkills::max_skills();
+///
+/// let then = schedules.for_date(NaiveDate::from_ymd_opt(2019, 6, 1).unwrap());
+/// let now = schedules.latest();
+/// assert!(now.sales_tax_percent(&skills) > then.sales_tax_percent(&skills));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeeSchedules {
+    schedules: Vec<FeeSchedule>,
+}
+
+impl Default for FeeSchedules {
+    /// The NPC station schedules TraderGrader ships with
+    fn default() -> Self {
+        Self::new(vec![
+            FeeSchedule {
+                name: "Pre-2020".to_string(),
+                effective_from: NaiveDate::from_ymd_opt(2003, 5, 6).expect("valid date"),
+                base_sales_tax_percent: 2.0,
+                accounting_reduction: 0.10,
+                base_broker_fee_percent: 3.0,
+                broker_relations_reduction: 0.1,
+                faction_standing_reduction: 0.03,
+                corporation_standing_reduction: 0.02,
+                min_broker_fee_isk: 100.0,
+            },
+            FeeSchedule {
+                name: "December 2020".to_string(),
+                effective_from: NaiveDate::from_ymd_opt(2020, 12, 8).expect("valid date"),
+                base_sales_tax_percent: 5.0,
+                accounting_reduction: 0.11,
+                base_broker_fee_percent: 3.0,
+                broker_relations_reduction: 0.3,
+                faction_standing_reduction: 0.03,
+                corporation_standing_reduction: 0.02,
+                min_broker_fee_isk: 100.0,
+            },
+            FeeSchedule {
+                name: "December 2021".to_string(),
+                effective_from: NaiveDate::from_ymd_opt(2021, 12, 14).expect("valid date"),
+                base_sales_tax_percent: 8.0,
+                accounting_reduction: 0.11,
+                base_broker_fee_percent: 3.0,
+                broker_relations_reduction: 0.3,
+                faction_standing_reduction: 0.03,
+                corporation_standing_reduction: 0.02,
+                min_broker_fee_isk: 100.0,
+            },
+            FeeSchedule {
+                name: "2024".to_string(),
+                effective_from: NaiveDate::from_ymd_opt(2024, 6, 11).expect("valid date"),
+                base_sales_tax_percent: 7.5,
+                accounting_reduction: 0.11,
+                base_broker_fee_percent: 3.0,
+                broker_relations_reduction: 0.3,
+                faction_standing_reduction: 0.03,
+                corporation_standing_reduction: 0.02,
+                min_broker_fee_isk: 100.0,
+            },
+        ])
+    }
+}
+
+impl FeeSchedules {
+    /// Creates a set of schedules; at least one is required
+    ///
+    /// # Panics
+    ///
+    /// Panics if `schedules` is empty.
+    pub fn new(mut schedules: Vec<FeeSchedule>) -> Self {
+        assert!(!schedules.is_empty(), "at least one fee schedule is required");
+        schedules.sort_by_key(|s| s.effective_from);
+        Self { schedules }
+    }
+
+    /// Adds a schedule, replacing any existing one with the same effective date
+    pub fn with_schedule(mut self, schedule: FeeSchedule) -> Self {
+        self.schedules.retain(|s| s.effective_from != schedule.effective_from);
+        self.schedules.push(schedule);
+        self.schedules.sort_by_key(|s| s.effective_from);
+        self
+    }
+
+    /// The schedule in effect on `date`
+    ///
+    /// Dates before the first schedule use the first schedule.
+    pub fn for_date(&self, date: NaiveDate) -> &FeeSchedule {
+        self.schedules
+            .iter()
+            .rev()
+            .find(|s| s.effective_from <= date)
+            .unwrap_or(&self.schedules[0])
+    }
+
+    /// The most recent schedule, used for current analysis
+    pub fn latest(&self) -> &FeeSchedule {
+        self.schedules.last().expect("fee schedules are never empty")
+    }
+
+    /// All schedules, oldest first
+    pub fn schedules(&self) -> &[FeeSchedule] {
+        &self.schedules
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_schedule_lookup_by_date() {
+        let schedules = FeeSchedules::default();
+        assert_eq!(schedules.for_date(date(2019, 1, 1)).name, "Pre-2020");
+        assert_eq!(schedules.for_date(date(2020, 12, 8)).name, "December 2020");
+        assert_eq!(schedules.for_date(date(2023, 7, 1)).name, "December 2021");
+        assert_eq!(schedules.for_date(date(2001, 1, 1)).name, "Pre-2020");
+        assert_eq!(schedules.latest(), schedules.for_date(date(2030, 1, 1)));
+    }
+
+    #[test]
+    fn test_rates_with_skills() {
+        let schedules = FeeSchedules::default();
+        let latest = schedules.latest();
+        let untrained = TradingSkills::default();
+        let maxed = TradingSkills::max_skills();
+
+        assert_eq!(latest.sales_tax_percent(&untrained), 7.5);
+        assert!((latest.sales_tax_percent(&maxed) - 3.375).abs() < 1e-9);
+        assert!((latest.broker_fee_percent(&maxed) - 1.5).abs() < 1e-9);
+
+        let with_standings = TradingSkills {
+            faction_standing: 5.0,
+            corporation_standing: 5.0,
+            ..maxed
+        };
+        assert!((latest.broker_fee_percent(&with_standings) - 1.25).abs() < 1e-9);
+
+        let old = schedules.for_date(date(2019, 1, 1));
+        assert!((old.sales_tax_percent(&maxed) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_fee_amounts() {
+        let schedules = FeeSchedules::default();
+        let latest = schedules.latest();
+        let skills = TradingSkills::max_skills();
+        assert!((latest.sales_tax(1_000_000.0, &skills) - 33_750.0).abs() < 1e-6);
+        assert!((latest.broker_fee(1_000_000.0, &skills) - 15_000.0).abs() < 1e-6);
+        assert_eq!(latest.broker_fee(100.0, &skills), 100.0);
+    }
+
+    #[test]
+    fn test_trading_profile_venues() {
+        let station = TradingProfile {
+            skills: TradingSkills { faction_standing: 5.0, ..TradingSkills::max_skills() },
+            venue: TradingVenue::NpcStation,
+        };
+        assert_eq!(station.current_fees(), *FeeSchedules::default().latest());
+        assert!((station.current_fees().broker_fee_percent(&station.skills) - 1.35).abs() < 1e-9);
+
+        let structure = TradingProfile { venue: TradingVenue::Structure { broker_fee_percent: 1.0 }, ..station };
+        let fees = structure.current_fees();
+        assert_eq!(fees.name, "2024 at a structure");
+        assert_eq!(fees.broker_fee_percent(&structure.skills), 1.0);
+        assert_eq!(fees.broker_fee(1_000.0, &structure.skills), 100.0);
+
+        let text = structure.to_text();
+        assert!(text.contains("Venue: structure (1.00% broker fee)"));
+        assert!(text.contains("% sales tax, 1.00% broker fee"));
+    }
+
+    #[test]
+    fn test_with_schedule_replaces_same_date() {
+        let mut custom = FeeSchedules::default().latest().clone();
+        custom.name = "Custom".to_string();
+        custom.base_sales_tax_percent = 4.0;
+        let schedules = FeeSchedules::default().with_schedule(custom);
+        assert_eq!(schedules.schedules().len(), 4);
+        assert_eq!(schedules.latest().name, "Custom");
+    }
+}
@@ -0,0 +1,485 @@
+//! Time-to-fill estimates for TraderGrader
+//!
+//! Before buying a large stack to flip, the question is how long it takes to
+//! sell again. A sell order at price P fills from trades where buyers paid P
+//! or more, after every cheaper (or equally priced, older) sell order has
+//! filled. ESI history only gives each day's lowest, average and highest
+//! price, so each day's volume is assumed spread evenly between the low and
+//! the average and between the average and the high, half on each side. The
+//! share of volume at or above P is averaged over the last 30 calendar days,
+//! counting days without trades as zero, and the quantity plus the units
+//! queued ahead are divided by it.
+//!
+//! Buy orders are the mirror image: a buy order at P fills from trades at P or
+//! less, after every higher (or equally priced, older) buy order. History
+//! doesn't say which trades were sellers hitting buy orders and which were
+//! buyers taking sell orders, so the volume is scaled by buy orders' share of
+//! the orders in the book.
+
+use crate::error::{Result, TraderGraderError};
+use crate::market::MarketClient;
+use crate::orderbook::MarketOrderBook;
+use crate::types::{FillEstimate, MarketHistory, TradeSide};
+use chrono::{Duration, NaiveDate};
+
+/// Calendar days of history the daily volume is averaged over
+pub const FILL_WINDOW_DAYS: usize = 30;
+
+impl FillEstimate {
+    /// Estimates how long a sell order of `quantity` units at `price` takes to fill
+    ///
+    /// Sell orders in `book` at `price` or cheaper are counted as filling
+    /// first. The returned estimate's `type_label` is a placeholder;
+    /// [`MarketClient::estimate_time_to_sell`] fills in the item name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::orderbook::MarketOrderBook;
+    /// use tradergrader::{FillEstimate, MarketHistory};
+    ///
+    /// // 3,000 units a day, half of them at 5.00 ISK or more
+    /// let history: Vec<MarketHistory> = (1..=30)
+    ///     .map(|day| MarketHistory {
+    ///         average: 5.0,
+    ///         date: format!("2025-06-{day:02}"),
+    ///         highest: 6.0,
+    ///         lowest: 4.0,
+    ///         order_count: 100,
+    ///         volume: 3_000,
+    ///         extra: Default::default(),
+    ///     })
+    ///     .collect();
+    /// let estimate = FillEstimate::sell(10000002, 34, 6_000, 5.0, &history, &MarketOrderBook::new(vec![]))?;
+    /// assert_eq!(estimate.daily_volume_at_price, 1_500.0);
+    /// assert_eq!(estimate.estimated_days, Some(4.0));
+    /// # Ok::<(), tradergrader::TraderGraderError>(())
+    /// ```
+    pub fn sell(
+        region_id: i32,
+        type_id: i32,
+        quantity: i64,
+        price: f64,
+        history: &[MarketHistory],
+        book: &MarketOrderBook,
+    ) -> Result<Self> {
+        Self::estimate(TradeSide::Sell, region_id, type_id, quantity, price, history, book)
+    }
+
+    /// Estimates how long a buy order for `quantity` units at `price` takes to fill
+    ///
+    /// Buy orders in `book` at `price` or higher are counted as filling first,
+    /// and volume at or below `price` is scaled by the book's buy order ratio.
+    /// The returned estimate's `type_label` is a placeholder;
+    /// [`MarketClient::estimate_buy_fill_time`] fills in the item name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::orderbook::MarketOrderBook;
+    /// use tradergrader::{FillEstimate, MarketHistory};
+    ///
+    /// // 3,000 units a day, half of them at 5.00 ISK or less
+    /// let history: Vec<MarketHistory> = (1..=30)
+    ///     .map(|day| MarketHistory {
+    ///         average: 5.0,
+    ///         date: format!("2025-06-{day:02}"),
+    ///         highest: 6.0,
+    ///         lowest: 4.0,
+    ///         order_count: 100,
+    ///         volume: 3_000,
+    ///         extra: Default::default(),
+    ///     })
+    ///     .collect();
+    /// // An empty book splits volume evenly between buy and sell orders
+    /// let estimate = FillEstimate::buy(10000002, 34, 3_000, 5.0, &history, &MarketOrderBook::new(vec![]))?;
+    /// assert_eq!(estimate.buy_order_ratio, Some(0.5));
+    /// assert_eq!(estimate.daily_volume_at_price, 750.0);
+    /// assert_eq!(estimate.estimated_days, Some(4.0));
+    /// # Ok::<(), tradergrader::TraderGraderError>(())
+    /// ```
+    pub fn buy(
+        region_id: i32,
+        type_id: i32,
+        quantity: i64,
+        price: f64,
+        history: &[MarketHistory],
+        book: &MarketOrderBook,
+    ) -> Result<Self> {
+        Self::estimate(TradeSide::Buy, region_id, type_id, quantity, price, history, book)
+    }
+
+    fn estimate(
+        side: TradeSide,
+        region_id: i32,
+        type_id: i32,
+        quantity: i64,
+        price: f64,
+        history: &[MarketHistory],
+        book: &MarketOrderBook,
+    ) -> Result<Self> {
+        if quantity <= 0 {
+            return Err(TraderGraderError::InvalidArgument {
+                field: "quantity".to_string(),
+                reason: "must be at least 1".to_string(),
+            });
+        }
+        if price.is_nan() || price <= 0.0 {
+            return Err(TraderGraderError::InvalidArgument {
+                field: "price".to_string(),
+                reason: "must be greater than 0".to_string(),
+            });
+        }
+
+        let (total_volume, volume_at_price, buy_order_ratio, units_ahead) = match side {
+            TradeSide::Sell => {
+                let (total, at_price) = window_volumes(history, |day| share_at_or_above(day, price));
+                let ahead = book.sells().filter(|o| o.price <= price).map(|o| o.volume_remain as i64).sum();
+                (total, at_price, None, ahead)
+            }
+            TradeSide::Buy => {
+                let (total, at_price) = window_volumes(history, |day| share_at_or_below(day, price));
+                let ahead = book.buys().filter(|o| o.price >= price).map(|o| o.volume_remain as i64).sum();
+                (total, at_price, Some(buy_order_ratio(book)), ahead)
+            }
+        };
+        let avg_daily_volume = total_volume / FILL_WINDOW_DAYS as f64;
+        let daily_volume_at_price = volume_at_price * buy_order_ratio.unwrap_or(1.0) / FILL_WINDOW_DAYS as f64;
+        Ok(Self {
+            region_id,
+            type_id,
+            type_label: format!("Type {type_id}"),
+            side,
+            quantity,
+            price,
+            window_days: FILL_WINDOW_DAYS,
+            avg_daily_volume,
+            volume_share: if total_volume > 0.0 { volume_at_price / total_volume } else { 0.0 },
+            buy_order_ratio,
+            daily_volume_at_price,
+            units_ahead,
+            estimated_days: (daily_volume_at_price > 0.0)
+                .then(|| (units_ahead + quantity) as f64 / daily_volume_at_price),
+        })
+    }
+}
+
+/// Total volume and volume at the order's price over the last [`FILL_WINDOW_DAYS`] calendar days
+///
+/// The window ends at the latest day in `history`; `share` gives the part of
+/// a day's volume, from 0 to 1, the order would have filled from.
+fn window_volumes(history: &[MarketHistory], share: impl Fn(&MarketHistory) -> f64) -> (f64, f64) {
+    let days: Vec<(NaiveDate, &MarketHistory)> = history
+        .iter()
+        .filter_map(|h| NaiveDate::parse_from_str(&h.date, "%Y-%m-%d").ok().map(|date| (date, h)))
+        .collect();
+    let Some(latest) = days.iter().map(|(date, _)| *date).max() else {
+        return (0.0, 0.0);
+    };
+    let start = latest - Duration::days(FILL_WINDOW_DAYS as i64 - 1);
+    days.iter()
+        .filter(|(date, _)| *date >= start)
+        .fold((0.0, 0.0), |(total, at_price), (_, day)| {
+            let volume = day.volume as f64;
+            (total + volume, at_price + volume * share(day))
+        })
+}
+
+/// Share of a day's volume, from 0 to 1, estimated to have traded at `price` or higher
+fn share_at_or_above(day: &MarketHistory, price: f64) -> f64 {
+    let share = if price <= day.lowest {
+        1.0
+    } else if price > day.highest {
+        0.0
+    } else if price <= day.average {
+        1.0 - 0.5 * (price - day.lowest) / (day.average - day.lowest)
+    } else {
+        0.5 * (day.highest - price) / (day.highest - day.average)
+    };
+    share.clamp(0.0, 1.0)
+}
+
+/// Share of a day's volume, from 0 to 1, estimated to have traded at `price` or lower
+fn share_at_or_below(day: &MarketHistory, price: f64) -> f64 {
+    if price >= day.highest {
+        1.0
+    } else if price < day.lowest {
+        0.0
+    } else {
+        1.0 - share_at_or_above(day, price)
+    }
+}
+
+/// Buy orders' share of the orders in the book; half when the book is empty
+fn buy_order_ratio(book: &MarketOrderBook) -> f64 {
+    let buys = book.buys().count();
+    let total = buys + book.sells().count();
+    if total == 0 {
+        0.5
+    } else {
+        buys as f64 / total as f64
+    }
+}
+
+impl MarketClient {
+    /// Estimates how long selling `quantity` units at `price` would take
+    ///
+    /// Without a price, the estimate is for undercutting the best sell order
+    /// by 0.01 ISK.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// let estimate = client.estimate_time_to_sell(10000002, 34, 1_000_000, Some(4.5)).await?;
+    /// if let Some(days) = estimate.estimated_days {
+    ///     println!("About {days:.1} days to sell");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn estimate_time_to_sell(
+        &self,
+        region_id: i32,
+        type_id: i32,
+        quantity: i64,
+        price: Option<f64>,
+    ) -> Result<FillEstimate> {
+        let history = self.fetch_market_history(region_id, type_id).await?;
+        let book = self.fetch_order_book(region_id, Some(type_id)).await?;
+        let Some(price) = price.or_else(|| book.best_ask().map(|p| p - 0.01)) else {
+            return Err(TraderGraderError::InsufficientData("No sell orders to price against; pass price".to_string()));
+        };
+        let mut estimate = FillEstimate::sell(region_id, type_id, quantity, price, &history, &book)?;
+        estimate.type_label = self.type_label(type_id).await;
+        Ok(estimate)
+    }
+
+    /// Generates a formatted time-to-sell estimate
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// println!("{}", client.get_time_to_sell_summary(10000002, 34, 1_000_000, None).await?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_time_to_sell_summary(
+        &self,
+        region_id: i32,
+        type_id: i32,
+        quantity: i64,
+        price: Option<f64>,
+    ) -> Result<String> {
+        let estimate = self.estimate_time_to_sell(region_id, type_id, quantity, price).await?;
+        Ok(format_fill_estimate(&estimate))
+    }
+
+    /// Estimates how long a buy order for `quantity` units at `price` would take to fill
+    ///
+    /// Without a price, the estimate is for outbidding the best buy order by
+    /// 0.01 ISK.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// let estimate = client.estimate_buy_fill_time(10000002, 34, 1_000_000, Some(3.9)).await?;
+    /// if let Some(days) = estimate.estimated_days {
+    ///     println!("About {days:.1} days to fill");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn estimate_buy_fill_time(
+        &self,
+        region_id: i32,
+        type_id: i32,
+        quantity: i64,
+        price: Option<f64>,
+    ) -> Result<FillEstimate> {
+        let history = self.fetch_market_history(region_id, type_id).await?;
+        let book = self.fetch_order_book(region_id, Some(type_id)).await?;
+        let Some(price) = price.or_else(|| book.best_bid().map(|p| p + 0.01)) else {
+            return Err(TraderGraderError::InsufficientData("No buy orders to price against; pass price".to_string()));
+        };
+        let mut estimate = FillEstimate::buy(region_id, type_id, quantity, price, &history, &book)?;
+        estimate.type_label = self.type_label(type_id).await;
+        Ok(estimate)
+    }
+
+    /// Generates a formatted buy order fill time estimate
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// println!("{}", client.get_buy_fill_time_summary(10000002, 34, 1_000_000, None).await?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_buy_fill_time_summary(
+        &self,
+        region_id: i32,
+        type_id: i32,
+        quantity: i64,
+        price: Option<f64>,
+    ) -> Result<String> {
+        let estimate = self.estimate_buy_fill_time(region_id, type_id, quantity, price).await?;
+        Ok(format_fill_estimate(&estimate))
+    }
+}
+
+/// Formats a fill estimate with the volume and queue behind it
+fn format_fill_estimate(estimate: &FillEstimate) -> String {
+    let (title, verb, filled_at, queued) = match estimate.side {
+        TradeSide::Sell => ("Time to Sell", "Selling", "at or above", "Sell orders at or below"),
+        TradeSide::Buy => ("Time to Buy", "Buying", "at or below", "Buy orders at or above"),
+    };
+    let mut report = format!(
+        "{title} {} in Region {}\n\
+        \n\
+        {verb} {} units at {:.2} ISK\n\
+        Volume (last {} days): {:.0} units/day, {:.0}% of it {filled_at} {:.2} ISK\n",
+        estimate.type_label,
+        estimate.region_id,
+        estimate.quantity,
+        estimate.price,
+        estimate.window_days,
+        estimate.avg_daily_volume,
+        estimate.volume_share * 100.0,
+        estimate.price,
+    );
+    if let Some(ratio) = estimate.buy_order_ratio {
+        report.push_str(&format!(
+            "Buy orders are {:.0}% of the orders in the book, so about {:.0} units/day sell into buy orders at that price\n",
+            ratio * 100.0,
+            estimate.daily_volume_at_price
+        ));
+    } else {
+        report.push_str(&format!("Expected to fill: {:.0} units/day\n", estimate.daily_volume_at_price));
+    }
+    report.push_str(&format!("{queued} {:.2} ISK ahead of yours: {} units\n", estimate.price, estimate.units_ahead));
+    let label = match estimate.side {
+        TradeSide::Sell => "Estimated time to sell",
+        TradeSide::Buy => "Estimated time to fill",
+    };
+    match estimate.estimated_days {
+        Some(days) => {
+            report.push_str(&format!("{label}: {}\n", format_days(days)));
+            if days > estimate.window_days as f64 {
+                report.push_str("This is longer than the history it is based on, so treat it as a rough guide.\n");
+            }
+        }
+        None => report.push_str(&format!(
+            "{label}: unknown, nothing traded {filled_at} {:.2} ISK in the last {} days\n",
+            estimate.price, estimate.window_days
+        )),
+    }
+    report.trim_end().to_string()
+}
+
+/// Formats a duration in days, in hours when under a day
+fn format_days(days: f64) -> String {
+    if days < 1.0 {
+        format!("{:.1} hours", days * 24.0)
+    } else {
+        format!("{days:.1} days")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{history_day, market_order};
+    use crate::types::MarketOrder;
+
+    fn order(is_buy_order: bool, price: f64, volume_remain: i32) -> MarketOrder {
+        market_order((price * 100.0) as i64, is_buy_order, price).with_volume(volume_remain)
+    }
+
+    #[test]
+    fn test_share_at_or_above() {
+        let d = history_day("2025-06-01", 5.0, 100).with_range(4.0, 8.0);
+        assert_eq!(share_at_or_above(&d, 3.0), 1.0);
+        assert_eq!(share_at_or_above(&d, 4.5), 0.75);
+        assert_eq!(share_at_or_above(&d, 5.0), 0.5);
+        assert_eq!(share_at_or_above(&d, 7.0), 0.5 / 3.0);
+        assert_eq!(share_at_or_above(&d, 9.0), 0.0);
+
+        let flat = history_day("2025-06-01", 5.0, 100).with_range(5.0, 5.0);
+        assert_eq!(share_at_or_above(&flat, 5.0), 1.0);
+        assert_eq!(share_at_or_above(&flat, 5.01), 0.0);
+    }
+
+    #[test]
+    fn test_sell_estimate_counts_queue_and_quiet_days() {
+        // Two trading days in the window; days before it and gaps count as no volume
+        let history = vec![
+            history_day("2025-04-01", 1.0, 1_000_000).with_range(1.0, 1.0),
+            history_day("2025-06-01", 5.0, 3_000).with_range(4.0, 6.0),
+            history_day("2025-06-30", 5.0, 3_000).with_range(4.0, 6.0),
+        ];
+        let book = MarketOrderBook::new(vec![order(false, 4.9, 200), order(false, 5.0, 100), order(false, 5.5, 1_000)]);
+        let estimate = FillEstimate::sell(10000002, 34, 700, 5.0, &history, &book).unwrap();
+        assert_eq!(estimate.avg_daily_volume, 200.0);
+        assert_eq!(estimate.volume_share, 0.5);
+        assert_eq!(estimate.daily_volume_at_price, 100.0);
+        assert_eq!(estimate.units_ahead, 300);
+        assert_eq!(estimate.estimated_days, Some(10.0));
+
+        let text = format_fill_estimate(&estimate);
+        assert!(text.contains("Selling 700 units at 5.00 ISK"));
+        assert!(text.contains("200 units/day, 50% of it at or above 5.00 ISK"));
+        assert!(text.contains("Expected to fill: 100 units/day"));
+        assert!(text.contains("Estimated time to sell: 10.0 days"));
+    }
+
+    #[test]
+    fn test_sell_estimate_without_volume_at_price() {
+        let history = vec![history_day("2025-06-01", 5.0, 3_000).with_range(4.0, 6.0)];
+        let book = MarketOrderBook::new(vec![]);
+        let estimate = FillEstimate::sell(10000002, 34, 10, 7.0, &history, &book).unwrap();
+        assert_eq!(estimate.estimated_days, None);
+        assert!(format_fill_estimate(&estimate).contains("unknown, nothing traded at or above 7.00 ISK"));
+
+        assert!(FillEstimate::sell(10000002, 34, 0, 5.0, &history, &book).is_err());
+        assert!(FillEstimate::sell(10000002, 34, 10, 0.0, &history, &book).is_err());
+        assert_eq!(format_days(0.25), "6.0 hours");
+    }
+
+    #[test]
+    fn test_buy_estimate_scales_by_buy_order_ratio() {
+        let history = vec![history_day("2025-06-30", 5.0, 6_000).with_range(4.0, 6.0)];
+        // One buy order in four: a quarter of the volume at or below the price sells into buy orders
+        let book = MarketOrderBook::new(vec![
+            order(true, 4.5, 50),
+            order(false, 5.5, 100),
+            order(false, 5.6, 100),
+            order(false, 5.7, 100),
+        ]);
+        let estimate = FillEstimate::buy(10000002, 34, 100, 4.5, &history, &book).unwrap();
+        assert_eq!(estimate.side, TradeSide::Buy);
+        assert_eq!(estimate.volume_share, 0.25);
+        assert_eq!(estimate.buy_order_ratio, Some(0.25));
+        assert_eq!(estimate.daily_volume_at_price, 12.5);
+        assert_eq!(estimate.units_ahead, 50);
+        assert_eq!(estimate.estimated_days, Some(12.0));
+
+        let text = format_fill_estimate(&estimate);
+        assert!(text.starts_with("Time to Buy Type 34 in Region 10000002"));
+        assert!(text.contains("Buy orders are 25% of the orders in the book"));
+        assert!(text.contains("Buy orders at or above 4.50 ISK ahead of yours: 50 units"));
+        assert!(text.contains("Estimated time to fill: 12.0 days"));
+        assert_eq!(FillEstimate::buy(10000002, 34, 100, 3.0, &history, &book).unwrap().estimated_days, None);
+    }
+}
@@ -0,0 +1,319 @@
+//! Regional market flow for TraderGrader
+//!
+//! Picking a secondary hub to seed means knowing how much ISK actually changes
+//! hands there. This rolls the recent daily history of a basket of staple
+//! items up into a region-wide picture: total ISK traded, which items carry
+//! it, which prices are moving most, and which items have stopped trading.
+
+use crate::error::{Result, TraderGraderError};
+use crate::market::MarketClient;
+use crate::scan::SCAN_CONCURRENCY;
+use crate::types::{ItemFlow, MarketHistory, RegionFlowReport};
+use chrono::{Duration, NaiveDate};
+use futures::stream::{self, StreamExt};
+
+/// Staple items sampled when no list is given: minerals, PLEX and skill
+/// injectors, fuel blocks, ice products and nanite paste
+pub const STAPLE_TYPE_IDS: [i32; 17] = [
+    34, 35, 36, 37, 38, 39, 40, 11399, // Tritanium to Megacyte, Morphite
+    44992, 40520, // PLEX, Large Skill Injector
+    4051, 4246, 4247, 4312, // Nitrogen, Hydrogen, Helium, Oxygen Fuel Blocks
+    16273, 16275, // Liquid Ozone, Strontium Clathrates
+    28668, // Nanite Repair Paste
+];
+
+/// Default days of history rolled up
+pub const DEFAULT_FLOW_DAYS: usize = 30;
+
+/// Most items sampled in one report
+pub const MAX_FLOW_ITEMS: usize = 50;
+
+/// Items with no trades in this many most recent days count as dead
+pub const DEAD_ITEM_DAYS: i64 = 7;
+
+/// Most items listed as top movers
+const TOP_MOVERS: usize = 5;
+
+impl RegionFlowReport {
+    /// Rolls up histories given as `(type_id, label, history)` over the last `days` days
+    ///
+    /// The window ends on the most recent date any item traded, so a report
+    /// built from a cache an hour old covers the same days as a fresh one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::{MarketHistory, RegionFlowReport};
+    ///
+    /// let day = |date: &str, average: f64, volume: i64| MarketHistory {
+    ///     average,
+    ///     date: date.to_string(),
+    ///     highest: average,
+    ///     lowest: average,
+    ///     order_count: 1,
+    ///     volume,
+    ///     extra: Default::default(),
+    /// };
+    /// let items = vec![
+    ///     (34, "Tritanium".to_string(), vec![day("2025-06-01", 4.0, 1_000), day("2025-06-30", 5.0, 1_000)]),
+    ///     (35, "Pyerite".to_string(), vec![day("2025-06-01", 10.0, 50)]),
+    /// ];
+    ///
+    /// let report = RegionFlowReport::from_histories(10000043, &items, 30)?;
+    /// assert_eq!(report.total_isk_volume, 9_500.0);
+    /// assert_eq!(report.dead_items, vec![35]);
+    /// # Ok::<(), tradergrader::TraderGraderError>(())
+    /// ```
+    pub fn from_histories(region_id: i32, items: &[(i32, String, Vec<MarketHistory>)], days: usize) -> Result<Self> {
+        if days == 0 {
+            return Err(TraderGraderError::InvalidArgument {
+                field: "days".to_string(),
+                reason: "must be at least 1".to_string(),
+            });
+        }
+        let parse = |date: &str| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok();
+        let end = items
+            .iter()
+            .flat_map(|(_, _, history)| history.iter().filter_map(|h| parse(&h.date)))
+            .max()
+            .ok_or_else(|| TraderGraderError::InvalidParams("None of the items has any history".to_string()))?;
+        let start = end - Duration::days(days as i64 - 1);
+        let dead_after = end - Duration::days(DEAD_ITEM_DAYS - 1);
+
+        let mut flows: Vec<ItemFlow> = items
+            .iter()
+            .map(|(type_id, label, history)| {
+                let mut window: Vec<(NaiveDate, &MarketHistory)> = history
+                    .iter()
+                    .filter_map(|h| Some((parse(&h.date)?, h)))
+                    .filter(|(date, _)| *date >= start && *date <= end)
+                    .collect();
+                window.sort_by_key(|(date, _)| *date);
+                let traded: Vec<&(NaiveDate, &MarketHistory)> = window.iter().filter(|(_, h)| h.volume > 0).collect();
+                let isk_volume: f64 = traded.iter().map(|(_, h)| h.average * h.volume as f64).sum();
+                let price_change_percent = match (traded.first(), traded.last()) {
+                    (Some((first_date, first)), Some((last_date, last))) if first_date < last_date && first.average > 0.0 => {
+                        Some((last.average / first.average - 1.0) * 100.0)
+                    }
+                    _ => None,
+                };
+                ItemFlow {
+                    type_id: *type_id,
+                    type_label: label.clone(),
+                    isk_volume,
+                    units_traded: traded.iter().map(|(_, h)| h.volume).sum(),
+                    trading_days: traded.len(),
+                    avg_daily_isk_volume: isk_volume / days as f64,
+                    price_change_percent,
+                    last_trade_date: traded.last().map(|(date, _)| date.to_string()),
+                    share_percent: 0.0,
+                }
+            })
+            .collect();
+
+        let total_isk_volume: f64 = flows.iter().map(|f| f.isk_volume).sum();
+        for flow in &mut flows {
+            if total_isk_volume > 0.0 {
+                flow.share_percent = flow.isk_volume / total_isk_volume * 100.0;
+            }
+        }
+        flows.sort_by(|a, b| b.isk_volume.total_cmp(&a.isk_volume).then(a.type_id.cmp(&b.type_id)));
+
+        let mut movers: Vec<&ItemFlow> = flows.iter().filter(|f| f.price_change_percent.is_some()).collect();
+        movers.sort_by(|a, b| {
+            let change = |f: &ItemFlow| f.price_change_percent.unwrap_or(0.0).abs();
+            change(b).total_cmp(&change(a))
+        });
+        let top_movers = movers.iter().take(TOP_MOVERS).map(|f| f.type_id).collect();
+        let dead_items = flows
+            .iter()
+            .filter(|f| {
+                f.last_trade_date
+                    .as_deref()
+                    .and_then(parse)
+                    .is_none_or(|last| last < dead_after)
+            })
+            .map(|f| f.type_id)
+            .collect();
+
+        Ok(Self {
+            region_id,
+            days,
+            start_date: start.to_string(),
+            end_date: end.to_string(),
+            total_isk_volume,
+            avg_daily_isk_volume: total_isk_volume / days as f64,
+            items: flows,
+            top_movers,
+            dead_items,
+            failures: Vec::new(),
+        })
+    }
+}
+
+impl MarketClient {
+    /// Rolls up a region's recent trading across a basket of items
+    ///
+    /// Items whose history can't be fetched are listed in
+    /// [`RegionFlowReport::failures`] instead of failing the report.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # use tradergrader::flow::{DEFAULT_FLOW_DAYS, STAPLE_TYPE_IDS};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// // Domain, home of Amarr
+    /// let report = client.region_flow(10000043, &STAPLE_TYPE_IDS, DEFAULT_FLOW_DAYS).await?;
+    /// println!("{:.0} ISK a day", report.avg_daily_isk_volume);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn region_flow(&self, region_id: i32, type_ids: &[i32], days: usize) -> Result<RegionFlowReport> {
+        if type_ids.len() > MAX_FLOW_ITEMS {
+            return Err(TraderGraderError::InvalidParams(format!(
+                "At most {MAX_FLOW_ITEMS} items can be sampled at once"
+            )));
+        }
+        let fetched: Vec<(i32, Result<Vec<MarketHistory>>)> = stream::iter(type_ids.iter().copied())
+            .map(|type_id| async move { (type_id, self.fetch_market_history(region_id, type_id).await) })
+            .buffered(SCAN_CONCURRENCY)
+            .collect()
+            .await;
+
+        let mut items = Vec::new();
+        let mut failures = Vec::new();
+        for (type_id, outcome) in fetched {
+            match outcome {
+                Ok(history) => items.push((type_id, self.type_label(type_id).await, history)),
+                Err(e) => failures.push((type_id, e.to_string())),
+            }
+        }
+        let mut report = RegionFlowReport::from_histories(region_id, &items, days)?;
+        report.failures = failures;
+        Ok(report)
+    }
+
+    /// Generates a formatted regional flow report
+    pub async fn get_region_flow_summary(&self, region_id: i32, type_ids: &[i32], days: usize) -> Result<String> {
+        let report = self.region_flow(region_id, type_ids, days).await?;
+        Ok(format_region_flow(&report))
+    }
+}
+
+/// Formats a flow report as totals, a per-item table, movers and dead items
+fn format_region_flow(report: &RegionFlowReport) -> String {
+    let label = |type_id: i32| {
+        report
+            .items
+            .iter()
+            .find(|f| f.type_id == type_id)
+            .map_or_else(|| format!("Type {type_id}"), |f| f.type_label.clone())
+    };
+    let mut text = format!(
+        "Market Flow in Region {} ({} days, {} to {}):\n\
+         Total traded: {:.0} ISK ({:.0} ISK/day) across {} items\n\n\
+         | Item | ISK traded | ISK/day | Share | Units | Days traded | Price change |\n\
+         |---|---|---|---|---|---|---|\n",
+        report.region_id,
+        report.days,
+        report.start_date,
+        report.end_date,
+        report.total_isk_volume,
+        report.avg_daily_isk_volume,
+        report.items.len()
+    );
+    for f in &report.items {
+        text.push_str(&format!(
+            "| {} | {:.0} | {:.0} | {:.1}% | {} | {}/{} | {} |\n",
+            f.type_label,
+            f.isk_volume,
+            f.avg_daily_isk_volume,
+            f.share_percent,
+            f.units_traded,
+            f.trading_days,
+            report.days,
+            f.price_change_percent.map_or_else(|| "-".to_string(), |c| format!("{c:+.1}%"))
+        ));
+    }
+
+    if !report.top_movers.is_empty() {
+        text.push_str("\nTop movers: ");
+        let movers: Vec<String> = report
+            .top_movers
+            .iter()
+            .filter_map(|id| report.items.iter().find(|f| f.type_id == *id))
+            .map(|f| format!("{} {:+.1}%", f.type_label, f.price_change_percent.unwrap_or(0.0)))
+            .collect();
+        text.push_str(&movers.join(", "));
+        text.push('\n');
+    }
+    if !report.dead_items.is_empty() {
+        let dead: Vec<String> = report.dead_items.iter().map(|&id| label(id)).collect();
+        text.push_str(&format!(
+            "\nNo trades in the last {DEAD_ITEM_DAYS} days: {}\n",
+            dead.join(", ")
+        ));
+    }
+    for (type_id, error) in &report.failures {
+        text.push_str(&format!("\nCould not fetch type {type_id}: {error}"));
+    }
+    text.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::history_day;
+
+    fn history(days: &[(u32, f64, i64)]) -> Vec<MarketHistory> {
+        days.iter().map(|&(day, average, volume)| history_day(&format!("2025-03-{day:02}"), average, volume)).collect()
+    }
+
+    #[test]
+    fn test_rolls_up_isk_volume_within_the_window() {
+        let items = vec![
+            // Day 1 falls outside a 20-day window ending on the 30th
+            (34, "Tritanium".to_string(), history(&[(1, 4.0, 1_000_000), (11, 5.0, 1_000), (30, 6.0, 1_000)])),
+            (44992, "PLEX".to_string(), history(&[(12, 4_000_000.0, 10), (29, 5_000_000.0, 10)])),
+        ];
+        let report = RegionFlowReport::from_histories(10000032, &items, 20).unwrap();
+
+        assert_eq!(report.start_date, "2025-03-11");
+        assert_eq!(report.end_date, "2025-03-30");
+        assert_eq!(report.total_isk_volume, 11_000.0 + 90_000_000.0);
+        assert_eq!(report.avg_daily_isk_volume, report.total_isk_volume / 20.0);
+        assert_eq!(report.items[0].type_id, 44992);
+        assert_eq!(report.items[1].units_traded, 2_000);
+        assert_eq!(report.items[1].trading_days, 2);
+        assert!((report.items[1].price_change_percent.unwrap() - 20.0).abs() < 1e-9);
+        assert!((report.items.iter().map(|f| f.share_percent).sum::<f64>() - 100.0).abs() < 1e-9);
+        assert_eq!(report.top_movers, vec![44992, 34]);
+        assert!(report.dead_items.is_empty());
+    }
+
+    #[test]
+    fn test_dead_items_and_formatting() {
+        let items = vec![
+            (34, "Tritanium".to_string(), history(&[(20, 5.0, 100), (30, 5.5, 100)])),
+            // Last traded 8 days before the end, and a zero-volume day since
+            (11399, "Morphite".to_string(), history(&[(22, 9_000.0, 5), (29, 9_500.0, 0)])),
+            (16275, "Strontium Clathrates".to_string(), Vec::new()),
+        ];
+        let report = RegionFlowReport::from_histories(10000030, &items, 30).unwrap();
+        assert_eq!(report.dead_items, vec![11399, 16275]);
+        assert_eq!(report.items[0].price_change_percent, None);
+
+        let text = format_region_flow(&report);
+        assert!(text.contains("No trades in the last 7 days: Morphite, Strontium Clathrates"));
+        assert!(text.contains("Top movers: Tritanium +10.0%"));
+    }
+
+    #[test]
+    fn test_needs_some_history() {
+        let items = vec![(34, "Tritanium".to_string(), Vec::new())];
+        assert!(RegionFlowReport::from_histories(10000002, &items, 30).is_err());
+        assert!(RegionFlowReport::from_histories(10000002, &[], 0).is_err());
+    }
+}
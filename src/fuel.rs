@@ -0,0 +1,416 @@
+//! Fuel block and ice profitability for TraderGrader
+//!
+//! Every structure burns fuel blocks, built from ice products (heavy water,
+//! liquid ozone, strontium and a racial isotope) and five planetary
+//! commodities, 40 blocks a run. Whether building them beats buying them
+//! depends on hub prices that drift apart, so each block is priced at every
+//! hub from the station's sell orders, after the sales tax and broker fee of
+//! listing the blocks. Recipes come from the local SDE when one is loaded and
+//! from the bundled blueprint otherwise. Ice miners get the other side: what
+//! compressed ice sells for over raw ice at each hub, since compressing costs
+//! a structure's time but makes ice ten times cheaper to haul.
+
+use crate::error::{Result, TraderGraderError};
+use crate::fees::TradingProfile;
+use crate::hubs::TradeHub;
+use crate::industry::{material_quantity, station_price, MAX_MATERIAL_EFFICIENCY};
+use crate::market::MarketClient;
+use crate::scan::SCAN_CONCURRENCY;
+use crate::types::{CompressionQuote, FuelBlockAnalysis, FuelBlockMargin, IceCompression, PriceBasis};
+use futures::stream::{self, StreamExt};
+use std::collections::{BTreeSet, HashMap};
+
+/// Fuel blocks made by one run of a fuel block blueprint
+pub const BLOCKS_PER_RUN: i64 = 40;
+
+/// Fuel blocks and the isotope each is built from: Nitrogen, Hydrogen, Helium and Oxygen
+pub const FUEL_BLOCKS: [(i32, i32); 4] = [(4051, 17888), (4246, 17889), (4247, 16274), (4312, 17887)];
+
+/// Racial isotopes in one run of a fuel block blueprint
+pub const ISOTOPES_PER_RUN: i64 = 450;
+
+/// Materials every fuel block takes per run, besides its isotope, as (type ID, quantity):
+/// Enriched Uranium, Oxygen, Mechanical Parts, Coolant, Robotics, Heavy Water,
+/// Liquid Ozone and Strontium Clathrates
+pub const SHARED_FUEL_INPUTS: [(i32, i64); 8] = [
+    (44, 4),
+    (3683, 22),
+    (3689, 4),
+    (9832, 9),
+    (9848, 1),
+    (16272, 170),
+    (16273, 350),
+    (16275, 20),
+];
+
+/// Ice ores and their compressed forms, unit for unit
+pub const ICE_COMPRESSION: [(i32, i32); 8] = [
+    (16262, 28434), // Clear Icicle
+    (16263, 28438), // Glacial Mass
+    (16264, 28433), // Blue Ice
+    (16265, 28444), // White Glaze
+    (16266, 28439), // Glare Crust
+    (16267, 28435), // Dark Glitter
+    (16268, 28437), // Gelidus
+    (16269, 28440), // Krystallos
+];
+
+/// Runs a blueprint is priced for, so material efficiency rounds as it does on a real batch
+const PRICED_RUNS: i64 = 100;
+
+/// What one run of a fuel block blueprint takes and makes
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuelBlockRecipe {
+    pub block_type_id: i32,
+    /// Materials at ME 0, as (type ID, quantity)
+    pub materials: Vec<(i32, i64)>,
+    pub blocks_per_run: i64,
+}
+
+impl FuelBlockRecipe {
+    /// The bundled recipe of a fuel block, for when no SDE is loaded
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::fuel::{FuelBlockRecipe, ISOTOPES_PER_RUN};
+    ///
+    /// // Nitrogen Fuel Blocks take Nitrogen Isotopes
+    /// let recipe = FuelBlockRecipe::bundled(4051).unwrap();
+    /// assert!(recipe.materials.contains(&(17888, ISOTOPES_PER_RUN)));
+    /// assert_eq!(recipe.materials.len(), 9);
+    /// assert!(FuelBlockRecipe::bundled(34).is_none());
+    /// ```
+    pub fn bundled(block_type_id: i32) -> Option<Self> {
+        let (_, isotope) = FUEL_BLOCKS.iter().find(|(block, _)| *block == block_type_id)?;
+        let mut materials = SHARED_FUEL_INPUTS.to_vec();
+        materials.push((*isotope, ISOTOPES_PER_RUN));
+        Some(Self {
+            block_type_id,
+            materials,
+            blocks_per_run: BLOCKS_PER_RUN,
+        })
+    }
+}
+
+impl FuelBlockMargin {
+    /// Prices one run of a fuel block blueprint from unit prices at a hub
+    ///
+    /// Inputs are bought from sell orders and the blocks listed at the lowest
+    /// sell order, paying sales tax and the broker fee. Costs are `None` when
+    /// any input has no price.
+    pub fn new(
+        recipe: &FuelBlockRecipe,
+        block_label: String,
+        hub: TradeHub,
+        material_efficiency: i32,
+        prices: &HashMap<i32, f64>,
+        trading: &TradingProfile,
+    ) -> Self {
+        let unpriced_inputs: Vec<i32> = recipe
+            .materials
+            .iter()
+            .map(|(type_id, _)| *type_id)
+            .filter(|type_id| !prices.contains_key(type_id))
+            .collect();
+        let input_cost = unpriced_inputs.is_empty().then(|| {
+            recipe
+                .materials
+                .iter()
+                .map(|&(type_id, quantity)| {
+                    let batch = material_quantity(quantity, PRICED_RUNS, material_efficiency);
+                    prices[&type_id] * batch as f64 / PRICED_RUNS as f64
+                })
+                .sum::<f64>()
+        });
+
+        let output_value = prices
+            .get(&recipe.block_type_id)
+            .map(|price| price * recipe.blocks_per_run as f64);
+        let fees = trading.current_fees();
+        let selling_fees =
+            output_value.map(|value| fees.sales_tax(value, &trading.skills) + fees.broker_fee(value, &trading.skills));
+        let profit = output_value
+            .zip(selling_fees)
+            .zip(input_cost)
+            .map(|((value, fees), cost)| value - fees - cost);
+
+        Self {
+            block_type_id: recipe.block_type_id,
+            block_label,
+            hub: hub.to_string(),
+            input_cost,
+            output_value,
+            selling_fees,
+            profit,
+            margin_percent: profit
+                .zip(output_value)
+                .filter(|&(_, value)| value > 0.0)
+                .map(|(profit, value)| profit / value * 100.0),
+            unpriced_inputs,
+        }
+    }
+}
+
+impl CompressionQuote {
+    /// Compares compressed and raw ice prices at a hub
+    pub fn new(hub: TradeHub, raw_price: Option<f64>, compressed_price: Option<f64>) -> Self {
+        Self {
+            hub: hub.to_string(),
+            raw_price,
+            compressed_price,
+            premium_percent: compressed_price
+                .zip(raw_price)
+                .filter(|&(_, raw)| raw > 0.0)
+                .map(|(compressed, raw)| (compressed / raw - 1.0) * 100.0),
+        }
+    }
+}
+
+impl MarketClient {
+    /// Prices fuel block production and ice compression at trade hubs
+    ///
+    /// Each hub's prices only count orders at its trade station. Items whose
+    /// order book can't be fetched count as unpriced.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # use tradergrader::fees::TradingProfile;
+    /// # use tradergrader::hubs::TradeHub;
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// let analysis = client.fuel_block_analysis(&TradeHub::ALL, 10, &TradingProfile::default()).await?;
+    /// for margin in &analysis.margins {
+    ///     println!("{} at {}: {:?} ISK a run", margin.block_label, margin.hub, margin.profit);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn fuel_block_analysis(
+        &self,
+        hubs: &[TradeHub],
+        material_efficiency: i32,
+        trading: &TradingProfile,
+    ) -> Result<FuelBlockAnalysis> {
+        if hubs.is_empty() {
+            return Err("No trade hubs to price at".into());
+        }
+        if !(0..=MAX_MATERIAL_EFFICIENCY).contains(&material_efficiency) {
+            return Err(TraderGraderError::InvalidArgument {
+                field: "material_efficiency".to_string(),
+                reason: format!("must be between 0 and {MAX_MATERIAL_EFFICIENCY}"),
+            });
+        }
+
+        // The SDE's blueprints are current; the bundled recipe is the fallback
+        let sde_recipes: Option<Vec<FuelBlockRecipe>> = self.static_data().and_then(|sde| {
+            FUEL_BLOCKS
+                .iter()
+                .map(|(block, _)| {
+                    sde.blueprint_for_product(*block).map(|blueprint| FuelBlockRecipe {
+                        block_type_id: *block,
+                        materials: blueprint.materials.clone(),
+                        blocks_per_run: blueprint.product_quantity,
+                    })
+                })
+                .collect()
+        });
+        let recipe_from_sde = sde_recipes.is_some();
+        let recipes = sde_recipes.unwrap_or_else(|| {
+            FUEL_BLOCKS
+                .iter()
+                .filter_map(|(block, _)| FuelBlockRecipe::bundled(*block))
+                .collect()
+        });
+
+        let mut type_ids: BTreeSet<i32> = BTreeSet::new();
+        for recipe in &recipes {
+            type_ids.insert(recipe.block_type_id);
+            type_ids.extend(recipe.materials.iter().map(|(type_id, _)| *type_id));
+        }
+        for (raw, compressed) in ICE_COMPRESSION {
+            type_ids.extend([raw, compressed]);
+        }
+        let lookups = (0..hubs.len()).flat_map(|index| type_ids.iter().map(move |&type_id| (index, type_id)));
+        let quotes: Vec<(usize, i32, Option<f64>)> = stream::iter(lookups)
+            .map(|(index, type_id)| async move {
+                let hub = hubs[index];
+                let price = match self.fetch_order_book(hub.region_id(), Some(type_id)).await {
+                    Ok(book) => station_price(&book, hub.station_id(), PriceBasis::Sell),
+                    Err(e) => {
+                        tracing::debug!("No {hub} price for type {type_id}: {e}");
+                        None
+                    }
+                };
+                (index, type_id, price)
+            })
+            .buffered(SCAN_CONCURRENCY)
+            .collect()
+            .await;
+        // Prices at each hub, in the order the hubs were given
+        let mut prices: Vec<HashMap<i32, f64>> = vec![HashMap::new(); hubs.len()];
+        for (index, type_id, price) in quotes {
+            if let Some(price) = price {
+                prices[index].insert(type_id, price);
+            }
+        }
+
+        let mut margins = Vec::new();
+        for recipe in &recipes {
+            let label = self.type_label(recipe.block_type_id).await;
+            for (&hub, hub_prices) in hubs.iter().zip(&prices) {
+                margins.push(FuelBlockMargin::new(recipe, label.clone(), hub, material_efficiency, hub_prices, trading));
+            }
+        }
+        margins.sort_by(|a, b| {
+            let profit = |m: &FuelBlockMargin| m.profit.unwrap_or(f64::NEG_INFINITY);
+            profit(b).total_cmp(&profit(a))
+        });
+
+        let mut compression = Vec::new();
+        for (raw, compressed) in ICE_COMPRESSION {
+            compression.push(IceCompression {
+                ice_type_id: raw,
+                ice_label: self.type_label(raw).await,
+                compressed_type_id: compressed,
+                quotes: hubs
+                    .iter()
+                    .zip(&prices)
+                    .map(|(&hub, hub_prices)| {
+                        CompressionQuote::new(hub, hub_prices.get(&raw).copied(), hub_prices.get(&compressed).copied())
+                    })
+                    .collect(),
+            });
+        }
+
+        Ok(FuelBlockAnalysis {
+            hubs: hubs.iter().map(ToString::to_string).collect(),
+            material_efficiency,
+            recipe_from_sde,
+            margins,
+            compression,
+        })
+    }
+}
+
+/// Formats fuel block margins, most profitable first, and ice compression premiums by hub
+pub(crate) fn format_fuel_block_analysis(analysis: &FuelBlockAnalysis) -> String {
+    let isk = |value: Option<f64>| value.map_or_else(|| "-".to_string(), |v| format!("{v:.2}"));
+    let percent = |value: Option<f64>| value.map_or_else(|| "-".to_string(), |v| format!("{v:+.1}%"));
+    let mut report = format!(
+        "Fuel Block Production at ME {} ({} recipe), per run, inputs bought from and blocks listed at sell orders:\n\n\
+         | Fuel block | Hub | Inputs | Blocks | Selling fees | Profit | Margin |\n\
+         |---|---|---|---|---|---|---|\n",
+        analysis.material_efficiency,
+        if analysis.recipe_from_sde { "SDE" } else { "bundled" }
+    );
+    for m in &analysis.margins {
+        report.push_str(&format!(
+            "| {} | {} | {} | {} | {} | {} | {} |\n",
+            m.block_label,
+            m.hub,
+            isk(m.input_cost),
+            isk(m.output_value),
+            isk(m.selling_fees),
+            isk(m.profit),
+            percent(m.margin_percent)
+        ));
+    }
+    let unpriced: BTreeSet<String> = analysis
+        .margins
+        .iter()
+        .filter(|m| !m.unpriced_inputs.is_empty())
+        .map(|m| m.hub.clone())
+        .collect();
+    if !unpriced.is_empty() {
+        report.push_str(&format!(
+            "\nSome inputs have no sell orders at {}, so those rows can't be priced.\n",
+            unpriced.into_iter().collect::<Vec<_>>().join(", ")
+        ));
+    }
+    report.push_str("\nJob installation fees aren't included; manufacturing_profit prices them for one block type.\n");
+
+    report.push_str(&format!(
+        "\nIce compression premium (compressed over raw sell price):\n\n| Ice | {} |\n|---|{}\n",
+        analysis.hubs.join(" | "),
+        "---|".repeat(analysis.hubs.len())
+    ));
+    for ice in &analysis.compression {
+        let cells: Vec<String> = ice.quotes.iter().map(|q| percent(q.premium_percent)).collect();
+        report.push_str(&format!("| {} | {} |\n", ice.ice_label, cells.join(" | ")));
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nitrogen() -> FuelBlockRecipe {
+        FuelBlockRecipe::bundled(4051).unwrap()
+    }
+
+    fn prices() -> HashMap<i32, f64> {
+        let mut prices: HashMap<i32, f64> = nitrogen().materials.into_iter().map(|(id, _)| (id, 100.0)).collect();
+        prices.insert(4051, 30_000.0);
+        prices
+    }
+
+    #[test]
+    fn test_fuel_block_margin() {
+        let recipe = nitrogen();
+        let units: i64 = recipe.materials.iter().map(|(_, quantity)| quantity).sum();
+        let trading = TradingProfile::default();
+        let margin = FuelBlockMargin::new(&recipe, String::new(), TradeHub::Jita, 0, &prices(), &trading);
+
+        assert_eq!(margin.input_cost, Some(units as f64 * 100.0));
+        assert_eq!(margin.output_value, Some(1_200_000.0));
+        let fees = trading.current_fees();
+        let selling_fees = fees.sales_tax(1_200_000.0, &trading.skills) + fees.broker_fee(1_200_000.0, &trading.skills);
+        assert_eq!(margin.profit, Some(1_200_000.0 - selling_fees - units as f64 * 100.0));
+
+        // ME saves materials, a tenth at ME 10
+        let researched = FuelBlockMargin::new(&recipe, String::new(), TradeHub::Jita, 10, &prices(), &trading);
+        assert!(researched.input_cost.unwrap() < margin.input_cost.unwrap());
+
+        let mut missing = prices();
+        missing.remove(&17888);
+        let unpriced = FuelBlockMargin::new(&recipe, String::new(), TradeHub::Amarr, 0, &missing, &trading);
+        assert_eq!(unpriced.unpriced_inputs, vec![17888]);
+        assert_eq!(unpriced.profit, None);
+        assert_eq!(unpriced.output_value, Some(1_200_000.0));
+    }
+
+    #[test]
+    fn test_format() {
+        let trading = TradingProfile::default();
+        let analysis = FuelBlockAnalysis {
+            hubs: vec!["Jita".to_string(), "Amarr".to_string()],
+            material_efficiency: 0,
+            recipe_from_sde: false,
+            margins: vec![FuelBlockMargin::new(
+                &nitrogen(),
+                "Nitrogen Fuel Block (4051)".to_string(),
+                TradeHub::Jita,
+                0,
+                &prices(),
+                &trading,
+            )],
+            compression: vec![IceCompression {
+                ice_type_id: 16264,
+                ice_label: "Blue Ice (16264)".to_string(),
+                compressed_type_id: 28433,
+                quotes: vec![
+                    CompressionQuote::new(TradeHub::Jita, Some(200.0), Some(210.0)),
+                    CompressionQuote::new(TradeHub::Amarr, None, Some(210.0)),
+                ],
+            }],
+        };
+        let report = format_fuel_block_analysis(&analysis);
+        assert!(report.starts_with("Fuel Block Production at ME 0 (bundled recipe)"));
+        assert!(report.contains("| Nitrogen Fuel Block (4051) | Jita | 103000.00 | 1200000.00 |"));
+        assert!(report.contains("| Ice | Jita | Amarr |\n|---|---|---|\n| Blue Ice (16264) | +5.0% | - |"));
+    }
+}
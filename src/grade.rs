@@ -0,0 +1,551 @@
+//! Trade grading for TraderGrader
+//!
+//! Grades a proposed station trade (buy with a buy order, resell with a sell
+//! order in the same region) from A to F. Six components are scored from 0 to
+//! 100 and combined with [`GradeWeights`]:
+//!
+//! - **margin**: profit after broker fees on both orders and sales tax; 20% or
+//!   more scores 100
+//! - **liquidity**: the item's liquidity score
+//! - **competition**: orders within 1% of the best buy and sell prices, which
+//!   will be racing to outbid and undercut; 20 or more scores 0
+//! - **volatility**: standard deviation of daily returns over the last 30
+//!   days; 10% or more scores 0
+//! - **time_to_fill**: days to buy and resell the quantity, assuming the trade
+//!   captures 10% of daily volume on each side; a day or less scores 100, two
+//!   weeks or more scores 0
+//! - **risk**: how many standard deviations of price movement over the
+//!   holding time the margin covers; three or more scores 100
+//!
+//! Every component's score and the data behind it are reported, so a grade can
+//! be explained and the weights tuned to taste.
+
+use crate::error::{Result, TraderGraderError};
+use crate::fees::{FeeSchedule, TradingProfile, TradingSkills};
+use crate::market::MarketClient;
+use crate::orderbook::MarketOrderBook;
+use crate::returns::{history_log_returns, std_dev};
+use crate::scan::average_daily_volume;
+use crate::types::{GradeComponent, MarketHistory, TradeGrade};
+
+/// Net margin, in percent, that earns the full margin score
+const FULL_MARGIN_PERCENT: f64 = 20.0;
+
+/// Competing orders near the best prices at which the competition score reaches 0
+const MAX_COMPETITORS: f64 = 20.0;
+
+/// How close to the best price, in percent, an order has to be to compete
+const COMPETITION_BAND_PERCENT: f64 = 1.0;
+
+/// Daily volatility, in percent, at which the volatility score reaches 0
+const MAX_VOLATILITY_PERCENT: f64 = 10.0;
+
+/// Share of each side's daily volume a trader can expect to capture
+const FILL_SHARE: f64 = 0.1;
+
+/// Days to fill at which the time-to-fill score reaches 0
+const MAX_DAYS_TO_FILL: f64 = 14.0;
+
+/// Standard deviations of price movement the margin must cover for a full risk score
+const FULL_RISK_COVERAGE: f64 = 3.0;
+
+/// Days of history used for the volatility estimate
+const VOLATILITY_WINDOW_DAYS: usize = 30;
+
+/// How much each component counts towards a trade grade
+///
+/// Weights are relative; they are normalized to sum to 1 when grading.
+///
+/// # Examples
+///
+/// ```
+/// use tradergrader::grade::GradeWeights;
+///
+/// let weights: GradeWeights = "conservative".parse().unwrap();
+/// assert!(weights.risk > GradeWeights::default().risk);
+///
+/// let margin_only = GradeWeights { margin: 1.0, ..GradeWeights::zero() };
+/// assert!(margin_only.validate().is_ok());
+/// assert!(GradeWeights::zero().validate().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct GradeWeights {
+    pub margin: f64,
+    pub liquidity: f64,
+    pub competition: f64,
+    pub volatility: f64,
+    pub time_to_fill: f64,
+    pub risk: f64,
+}
+
+impl Default for GradeWeights {
+    /// Balanced weights
+    fn default() -> Self {
+        Self {
+            margin: 0.25,
+            liquidity: 0.20,
+            competition: 0.15,
+            volatility: 0.10,
+            time_to_fill: 0.15,
+            risk: 0.15,
+        }
+    }
+}
+
+impl GradeWeights {
+    /// Every weight zero, a starting point for custom weights
+    pub fn zero() -> Self {
+        Self {
+            margin: 0.0,
+            liquidity: 0.0,
+            competition: 0.0,
+            volatility: 0.0,
+            time_to_fill: 0.0,
+            risk: 0.0,
+        }
+    }
+
+    /// Favors liquid, stable items that turn over quickly
+    pub fn conservative() -> Self {
+        Self {
+            margin: 0.15,
+            liquidity: 0.25,
+            competition: 0.10,
+            volatility: 0.15,
+            time_to_fill: 0.15,
+            risk: 0.20,
+        }
+    }
+
+    /// Favors fat margins over safety
+    pub fn aggressive() -> Self {
+        Self {
+            margin: 0.40,
+            liquidity: 0.15,
+            competition: 0.15,
+            volatility: 0.05,
+            time_to_fill: 0.10,
+            risk: 0.15,
+        }
+    }
+
+    /// Replaces one weight by component name
+    pub fn set(&mut self, component: &str, weight: f64) -> Result<()> {
+        let slot = match component {
+            "margin" => &mut self.margin,
+            "liquidity" => &mut self.liquidity,
+            "competition" => &mut self.competition,
+            "volatility" => &mut self.volatility,
+            "time_to_fill" => &mut self.time_to_fill,
+            "risk" => &mut self.risk,
+            other => {
+                return Err(TraderGraderError::InvalidArgument {
+                    field: format!("weights.{other}"),
+                    reason: "is not a grade component".to_string(),
+                })
+            }
+        };
+        *slot = weight;
+        Ok(())
+    }
+
+    /// Checks no weight is negative and at least one is positive
+    pub fn validate(&self) -> Result<()> {
+        let weights = self.as_array();
+        if weights.iter().any(|w| !w.is_finite() || *w < 0.0) {
+            return Err(TraderGraderError::InvalidParams("Grade weights can't be negative".to_string()));
+        }
+        if weights.iter().sum::<f64>() <= 0.0 {
+            return Err(TraderGraderError::InvalidParams("At least one grade weight must be positive".to_string()));
+        }
+        Ok(())
+    }
+
+    fn as_array(&self) -> [f64; 6] {
+        [self.margin, self.liquidity, self.competition, self.volatility, self.time_to_fill, self.risk]
+    }
+}
+
+impl std::str::FromStr for GradeWeights {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "balanced" => Ok(Self::default()),
+            "conservative" => Ok(Self::conservative()),
+            "aggressive" => Ok(Self::aggressive()),
+            other => Err(format!("Unknown grading profile: {other}")),
+        }
+    }
+}
+
+/// A station trade to grade
+///
+/// Prices left unset are taken from the order book: one cent over the best buy
+/// order and one cent under the best sell order. The quantity defaults to 10%
+/// of the average daily volume.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ProposedTrade {
+    pub region_id: i32,
+    pub type_id: i32,
+    pub buy_price: Option<f64>,
+    pub sell_price: Option<f64>,
+    pub quantity: Option<i64>,
+}
+
+impl ProposedTrade {
+    /// A trade at the current best prices
+    pub fn new(region_id: i32, type_id: i32) -> Self {
+        Self {
+            region_id,
+            type_id,
+            ..Self::default()
+        }
+    }
+}
+
+impl MarketClient {
+    /// Grades a proposed station trade from A to F
+    ///
+    /// Fees follow the latest fee schedule as it applies to `profile`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # use tradergrader::fees::{TradingProfile, TradingSkills};
+    /// # use tradergrader::grade::{GradeWeights, ProposedTrade};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// let trade = ProposedTrade::new(10000002, 34);
+    /// let profile = TradingProfile { skills: TradingSkills::max_skills(), ..TradingProfile::default() };
+    /// let grade = client.grade_trade(&trade, &profile, &GradeWeights::default()).await?;
+    /// println!("{}: {} ({:.0}/100)", grade.type_label, grade.grade, grade.score);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn grade_trade(
+        &self,
+        trade: &ProposedTrade,
+        profile: &TradingProfile,
+        weights: &GradeWeights,
+    ) -> Result<TradeGrade> {
+        weights.validate()?;
+        let history = self.fetch_market_history(trade.region_id, trade.type_id).await?;
+        let book = self.fetch_order_book(trade.region_id, Some(trade.type_id)).await?;
+        let mut grade = grade_trade(trade, &history, &book, &profile.current_fees(), &profile.skills, weights)?;
+        grade.type_label = self.type_label(trade.type_id).await;
+        Ok(grade)
+    }
+}
+
+/// Grades a trade from already-fetched history and orders
+///
+/// The returned grade's `type_label` is a placeholder; [`MarketClient::grade_trade`]
+/// fills in the item name.
+pub fn grade_trade(
+    trade: &ProposedTrade,
+    history: &[MarketHistory],
+    book: &MarketOrderBook,
+    fees: &FeeSchedule,
+    skills: &TradingSkills,
+    weights: &GradeWeights,
+) -> Result<TradeGrade> {
+    weights.validate()?;
+    let buy_price = match trade.buy_price.or_else(|| book.best_bid().map(|p| p + 0.01)) {
+        Some(price) if price > 0.0 => price,
+        Some(_) => return Err(invalid_price("buy_price")),
+        None => {
+            return Err(TraderGraderError::InvalidParams("No buy orders to price against; pass buy_price".to_string()))
+        }
+    };
+    let sell_price = match trade.sell_price.or_else(|| book.best_ask().map(|p| p - 0.01)) {
+        Some(price) if price > 0.0 => price,
+        Some(_) => return Err(invalid_price("sell_price")),
+        None => {
+            return Err(TraderGraderError::InvalidParams("No sell orders to price against; pass sell_price".to_string()))
+        }
+    };
+
+    let avg_daily_volume = average_daily_volume(history);
+    let quantity = trade.quantity.unwrap_or((avg_daily_volume * FILL_SHARE).round() as i64).max(1);
+    let units = quantity as f64;
+
+    // Fees per unit: broker fee on the buy order, broker fee and sales tax on the sale
+    let cost = buy_price + fees.broker_fee(buy_price * units, skills) / units;
+    let revenue =
+        sell_price - (fees.broker_fee(sell_price * units, skills) + fees.sales_tax(sell_price * units, skills)) / units;
+    let profit_per_unit = revenue - cost;
+    let net_margin_percent = profit_per_unit / cost * 100.0;
+
+    let volatility_percent = daily_volatility_percent(history);
+    let estimated_days_to_fill =
+        (avg_daily_volume > 0.0).then(|| 2.0 * units / (avg_daily_volume * FILL_SHARE));
+    let competitors = competing_orders(book);
+    let liquidity = MarketClient::liquidity_score(history.to_vec(), book.orders()).ok();
+
+    let margin_score = (net_margin_percent / FULL_MARGIN_PERCENT * 100.0).clamp(0.0, 100.0);
+    let liquidity_score = liquidity.as_ref().map_or(0.0, |l| l.score);
+    let competition_score = ((1.0 - competitors as f64 / MAX_COMPETITORS) * 100.0).clamp(0.0, 100.0);
+    let volatility_score =
+        volatility_percent.map_or(0.0, |v| ((1.0 - v / MAX_VOLATILITY_PERCENT) * 100.0).clamp(0.0, 100.0));
+    let fill_score = estimated_days_to_fill.map_or(0.0, |days| {
+        ((MAX_DAYS_TO_FILL - days) / (MAX_DAYS_TO_FILL - 1.0) * 100.0).clamp(0.0, 100.0)
+    });
+    let coverage = match (volatility_percent, estimated_days_to_fill) {
+        (Some(v), Some(days)) if net_margin_percent > 0.0 => {
+            Some(net_margin_percent / (v.max(0.01) * days.max(1.0).sqrt()))
+        }
+        _ => None,
+    };
+    let risk_score = coverage.map_or(0.0, |c| (c / FULL_RISK_COVERAGE * 100.0).clamp(0.0, 100.0));
+
+    let total_weight: f64 = weights.as_array().iter().sum();
+    let component = |name: &str, score: f64, weight: f64, detail: String| GradeComponent {
+        name: name.to_string(),
+        score,
+        weight: weight / total_weight,
+        detail,
+    };
+    let components = vec![
+        component(
+            "margin",
+            margin_score,
+            weights.margin,
+            format!("{net_margin_percent:.1}% after fees ({profit_per_unit:.2} ISK/unit)"),
+        ),
+        component(
+            "liquidity",
+            liquidity_score,
+            weights.liquidity,
+            match &liquidity {
+                Some(l) => format!("{} liquidity, {:.0} ISK traded per day", l.rating, l.avg_daily_turnover),
+                None => "no trade history".to_string(),
+            },
+        ),
+        component(
+            "competition",
+            competition_score,
+            weights.competition,
+            format!("{competitors} orders within {COMPETITION_BAND_PERCENT}% of the best prices"),
+        ),
+        component(
+            "volatility",
+            volatility_score,
+            weights.volatility,
+            match volatility_percent {
+                Some(v) => format!("{v:.2}% daily price swings"),
+                None => "not enough history".to_string(),
+            },
+        ),
+        component(
+            "time_to_fill",
+            fill_score,
+            weights.time_to_fill,
+            match estimated_days_to_fill {
+                Some(days) => {
+                    format!("about {days:.1} days for {quantity} units at {avg_daily_volume:.0} traded per day")
+                }
+                None => "the item hasn't traded recently".to_string(),
+            },
+        ),
+        component(
+            "risk",
+            risk_score,
+            weights.risk,
+            match coverage {
+                Some(c) => format!("margin covers {c:.1} standard deviations of price movement while held"),
+                None if net_margin_percent <= 0.0 => "the trade loses money".to_string(),
+                None => "price movement can't be estimated".to_string(),
+            },
+        ),
+    ];
+
+    let score = components.iter().map(|c| c.score * c.weight).sum::<f64>();
+    let grade = match score {
+        _ if profit_per_unit <= 0.0 => 'F',
+        s if s >= 80.0 => 'A',
+        s if s >= 65.0 => 'B',
+        s if s >= 50.0 => 'C',
+        s if s >= 35.0 => 'D',
+        _ => 'F',
+    };
+
+    Ok(TradeGrade {
+        region_id: trade.region_id,
+        type_id: trade.type_id,
+        type_label: format!("Type {}", trade.type_id),
+        buy_price,
+        sell_price,
+        quantity,
+        profit_per_unit,
+        net_margin_percent,
+        estimated_days_to_fill,
+        components,
+        score,
+        grade,
+    })
+}
+
+fn invalid_price(field: &str) -> TraderGraderError {
+    TraderGraderError::InvalidArgument {
+        field: field.to_string(),
+        reason: "must be greater than 0".to_string(),
+    }
+}
+
+/// Standard deviation of daily log returns over the volatility window, in percent
+fn daily_volatility_percent(history: &[MarketHistory]) -> Option<f64> {
+    let returns = history_log_returns(history);
+    let recent = &returns[returns.len().saturating_sub(VOLATILITY_WINDOW_DAYS)..];
+    std_dev(recent).map(|sigma| sigma * 100.0)
+}
+
+/// Orders within the competition band of the best buy and best sell prices
+fn competing_orders(book: &MarketOrderBook) -> usize {
+    let band = COMPETITION_BAND_PERCENT / 100.0;
+    let buys = book.best_bid().map_or(0, |bid| book.buys().filter(|o| o.price >= bid * (1.0 - band)).count());
+    let sells = book.best_ask().map_or(0, |ask| book.sells().filter(|o| o.price <= ask * (1.0 + band)).count());
+    buys + sells
+}
+
+/// Formats a trade grade with its component breakdown
+pub(crate) fn format_trade_grade(grade: &TradeGrade) -> String {
+    let mut report = format!(
+        "Trade Grade for {} in Region {}: {} ({:.0}/100)\n\
+        \n\
+        Buy at {:.2} ISK, sell at {:.2} ISK, {} units\n\
+        Profit: {:.2} ISK/unit ({:.2} ISK total), {:.1}% margin after fees\n\
+        \n\
+        Components (score × weight):\n",
+        grade.type_label,
+        grade.region_id,
+        grade.grade,
+        grade.score,
+        grade.buy_price,
+        grade.sell_price,
+        grade.quantity,
+        grade.profit_per_unit,
+        grade.profit_per_unit * grade.quantity as f64,
+        grade.net_margin_percent,
+    );
+    for component in &grade.components {
+        report.push_str(&format!(
+            "- {}: {:.0}/100 × {:.0}% — {}\n",
+            component.name,
+            component.score,
+            component.weight * 100.0,
+            component.detail
+        ));
+    }
+    if grade.profit_per_unit <= 0.0 {
+        report.push_str("\nThe trade loses money after fees, so it grades F whatever the other components say.\n");
+    }
+    report.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fees::FeeSchedules;
+    use crate::test_support::{history_day, market_order};
+    use crate::types::MarketOrder;
+
+    fn order(is_buy_order: bool, price: f64) -> MarketOrder {
+        MarketOrder {
+            volume_total: 100_000,
+            ..market_order((price * 100.0) as i64, is_buy_order, price).with_volume(50_000)
+        }
+    }
+
+    fn history(days: u32, average: impl Fn(u32) -> f64) -> Vec<MarketHistory> {
+        (1..=days)
+            .map(|day| {
+                let average = average(day);
+                history_day(&format!("2024-01-{day:02}"), average, 10_000_000)
+                    .with_range(average * 0.98, average * 1.02)
+                    .with_order_count(2_000)
+            })
+            .collect()
+    }
+
+    fn skills() -> TradingSkills {
+        TradingSkills::max_skills()
+    }
+
+    #[test]
+    fn test_profitable_stable_trade_grades_well() {
+        let book = MarketOrderBook::new(vec![order(true, 80.0), order(false, 100.0)]);
+        let history = history(30, |day| 90.0 + (day % 2) as f64 * 0.5);
+        let trade = ProposedTrade::new(10000002, 34);
+        let schedules = FeeSchedules::default();
+        let grade = grade_trade(&trade, &history, &book, schedules.latest(), &skills(), &GradeWeights::default()).unwrap();
+
+        assert_eq!(grade.buy_price, 80.01);
+        assert_eq!(grade.sell_price, 99.99);
+        assert_eq!(grade.quantity, 1_000_000);
+        assert!(grade.net_margin_percent > 15.0 && grade.net_margin_percent < 25.0);
+        assert_eq!(grade.components.len(), 6);
+        assert!((grade.components.iter().map(|c| c.weight).sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!(matches!(grade.grade, 'A' | 'B'), "got {} ({:.1})", grade.grade, grade.score);
+
+        let report = format_trade_grade(&grade);
+        assert!(report.contains("- margin:"));
+        assert!(report.contains("- risk:"));
+    }
+
+    #[test]
+    fn test_losing_trade_is_an_f() {
+        let book = MarketOrderBook::new(vec![order(true, 99.0), order(false, 100.0)]);
+        let history = history(30, |_| 100.0);
+        let trade = ProposedTrade::new(10000002, 34);
+        let schedules = FeeSchedules::default();
+        let grade = grade_trade(&trade, &history, &book, schedules.latest(), &skills(), &GradeWeights::default()).unwrap();
+        assert!(grade.profit_per_unit < 0.0);
+        assert_eq!(grade.grade, 'F');
+        assert!(format_trade_grade(&grade).contains("loses money after fees"));
+    }
+
+    #[test]
+    fn test_weights_change_the_score() {
+        let book = MarketOrderBook::new(vec![order(true, 80.0), order(false, 100.0)]);
+        // Volatile history
+        let history = history(30, |day| if day % 2 == 0 { 70.0 } else { 110.0 });
+        let trade = ProposedTrade {
+            quantity: Some(10_000),
+            ..ProposedTrade::new(10000002, 34)
+        };
+        let schedules = FeeSchedules::default();
+        let fees = schedules.latest();
+        let margin_only = GradeWeights { margin: 1.0, ..GradeWeights::zero() };
+        let volatility_only = GradeWeights { volatility: 1.0, ..GradeWeights::zero() };
+
+        let by_margin = grade_trade(&trade, &history, &book, fees, &skills(), &margin_only).unwrap();
+        let by_volatility = grade_trade(&trade, &history, &book, fees, &skills(), &volatility_only).unwrap();
+        assert!(by_margin.score > by_volatility.score);
+        assert_eq!(by_volatility.score, 0.0);
+    }
+
+    #[test]
+    fn test_missing_prices_and_bad_weights() {
+        let book = MarketOrderBook::new(vec![order(false, 100.0)]);
+        let trade = ProposedTrade::new(10000002, 34);
+        let schedules = FeeSchedules::default();
+        let fees = schedules.latest();
+        let error = grade_trade(&trade, &[], &book, fees, &skills(), &GradeWeights::default()).unwrap_err();
+        assert!(error.to_string().contains("pass buy_price"));
+
+        let priced = ProposedTrade {
+            buy_price: Some(50.0),
+            ..trade
+        };
+        let grade = grade_trade(&priced, &[], &book, fees, &skills(), &GradeWeights::default()).unwrap();
+        assert_eq!(grade.estimated_days_to_fill, None);
+
+        let mut weights = GradeWeights::default();
+        assert!(weights.set("speed", 1.0).is_err());
+        weights.set("risk", -1.0).unwrap();
+        assert!(weights.validate().is_err());
+        assert_eq!("AGGRESSIVE".parse::<GradeWeights>(), Ok(GradeWeights::aggressive()));
+    }
+}
@@ -0,0 +1,791 @@
+//! Hauling economics for TraderGrader
+//!
+//! Gate hauling between two markets (route length and its low- and null-sec
+//! stretches from the route planner, profit per jump and per m³) and jump freighter fuel modeling: light-year distances
+//! from solar system positions, isotope consumption per jump with skill
+//! reductions, and fuel priced from the market so route profits are reported
+//! after fuel.
+
+use crate::cache::CacheKey;
+use crate::error::{Result, TraderGraderError};
+use crate::fees::{FeeSchedule, FeeSchedules, TradingSkills};
+use crate::market::MarketClient;
+use crate::orderbook::{BookSide, MarketOrderBook};
+use crate::route::{format_dangerous_systems, format_route_segments};
+use crate::scan::SCAN_CONCURRENCY;
+use crate::types::{HaulingAnalysis, HaulingOpportunity, JumpFreighterProfit, JumpLeg, MarketOrder, Position, TypeInfo};
+use futures::stream::{self, StreamExt, TryStreamExt};
+use std::fmt;
+
+/// Meters in one light year
+pub const METERS_PER_LIGHT_YEAR: f64 = 9_460_730_472_580_800.0;
+
+/// Region whose market prices jump fuel (The Forge, home of Jita)
+pub const FUEL_PRICE_REGION_ID: i32 = 10000002;
+
+/// Cargo hold of a typical freighter with cargo expanders fitted, in m³
+pub const DEFAULT_CARGO_CAPACITY_M3: f64 = 1_000_000.0;
+
+/// Route preference passed to ESI `/route/`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RouteFlag {
+    /// Fewest jumps regardless of security
+    #[default]
+    Shortest,
+    /// Stay in high-security space where possible
+    Secure,
+    /// Prefer low- and null-security space
+    Insecure,
+}
+
+impl RouteFlag {
+    /// Value of ESI's `flag` query parameter
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Shortest => "shortest",
+            Self::Secure => "secure",
+            Self::Insecure => "insecure",
+        }
+    }
+}
+
+impl std::str::FromStr for RouteFlag {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "shortest" => Ok(Self::Shortest),
+            "secure" | "safest" => Ok(Self::Secure),
+            "insecure" => Ok(Self::Insecure),
+            other => Err(format!("Unknown route flag: {other}")),
+        }
+    }
+}
+
+/// Racial jump freighter, which determines the isotope it burns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JumpFreighter {
+    /// Amarr, burns Helium Isotopes
+    Ark,
+    /// Gallente, burns Oxygen Isotopes
+    Anshar,
+    /// Minmatar, burns Hydrogen Isotopes
+    Nomad,
+    /// Caldari, burns Nitrogen Isotopes
+    Rhea,
+}
+
+impl JumpFreighter {
+    /// Type ID of the isotope this ship burns
+    pub fn fuel_type_id(&self) -> i32 {
+        match self {
+            Self::Ark => 16274,    // Helium Isotopes
+            Self::Anshar => 17887, // Oxygen Isotopes
+            Self::Nomad => 17889,  // Hydrogen Isotopes
+            Self::Rhea => 17888,   // Nitrogen Isotopes
+        }
+    }
+}
+
+impl fmt::Display for JumpFreighter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Ark => "Ark",
+            Self::Anshar => "Anshar",
+            Self::Nomad => "Nomad",
+            Self::Rhea => "Rhea",
+        };
+        f.write_str(name)
+    }
+}
+
+impl std::str::FromStr for JumpFreighter {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "ark" | "amarr" => Ok(Self::Ark),
+            "anshar" | "gallente" => Ok(Self::Anshar),
+            "nomad" | "minmatar" => Ok(Self::Nomad),
+            "rhea" | "caldari" => Ok(Self::Rhea),
+            other => Err(format!("Unknown jump freighter: {other}")),
+        }
+    }
+}
+
+/// Pilot skills and hull numbers that determine jump fuel and range
+#[derive(Debug, Clone, PartialEq)]
+pub struct JumpFuelConfig {
+    /// Isotopes per light year before skills (10,000 for all jump freighters)
+    pub base_fuel_per_ly: f64,
+    /// Jump Fuel Conservation level (10% less fuel per level)
+    pub jump_fuel_conservation: u8,
+    /// Jump Freighters level (10% less fuel per level)
+    pub jump_freighters: u8,
+    /// Base jump range in light years before Jump Drive Calibration
+    pub base_range_ly: f64,
+    /// Jump Drive Calibration level (20% more range per level)
+    pub jump_drive_calibration: u8,
+}
+
+impl Default for JumpFuelConfig {
+    fn default() -> Self {
+        Self {
+            base_fuel_per_ly: 10_000.0,
+            jump_fuel_conservation: 4,
+            jump_freighters: 4,
+            base_range_ly: 5.0,
+            jump_drive_calibration: 5,
+        }
+    }
+}
+
+impl JumpFuelConfig {
+    /// Configuration for a pilot with every relevant skill at level V
+    pub fn max_skills() -> Self {
+        Self {
+            jump_fuel_conservation: 5,
+            jump_freighters: 5,
+            ..Self::default()
+        }
+    }
+
+    /// Isotopes burned per light year after skill reductions
+    pub fn fuel_per_ly(&self) -> f64 {
+        let conservation = 1.0 - 0.1 * self.jump_fuel_conservation.min(5) as f64;
+        let freighters = 1.0 - 0.1 * self.jump_freighters.min(5) as f64;
+        self.base_fuel_per_ly * conservation * freighters
+    }
+
+    /// Maximum jump range in light years after Jump Drive Calibration
+    pub fn max_range_ly(&self) -> f64 {
+        self.base_range_ly * (1.0 + 0.2 * self.jump_drive_calibration.min(5) as f64)
+    }
+
+    /// Isotopes burned for a single jump of `distance_ly`
+    pub fn fuel_for_jump(&self, distance_ly: f64) -> i64 {
+        (distance_ly * self.fuel_per_ly()).ceil() as i64
+    }
+}
+
+/// An item shipment between two regional markets
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HaulCargo {
+    /// Region the cargo is bought in
+    pub source_region_id: i32,
+    /// Region the cargo is sold in
+    pub destination_region_id: i32,
+    pub type_id: i32,
+    pub quantity: i64,
+}
+
+/// Straight-line distance between two positions in light years
+pub fn light_years_between(a: &Position, b: &Position) -> f64 {
+    let (dx, dy, dz) = (a.x - b.x, a.y - b.y, a.z - b.z);
+    (dx * dx + dy * dy + dz * dz).sqrt() / METERS_PER_LIGHT_YEAR
+}
+
+impl MarketClient {
+    /// Fetches the stargate route between two systems, including both endpoints
+    pub async fn fetch_route(&self, origin: i32, destination: i32, flag: RouteFlag) -> Result<Vec<i32>> {
+        self.esi().get_cached(
+            &format!("/route/{origin}/{destination}/?flag={}", flag.as_str()),
+            &CacheKey::route(origin, destination, flag.as_str()),
+            "universe",
+        )
+        .await
+    }
+
+    /// Analyzes the profit of gate hauling items from one system's market to another's
+    ///
+    /// For each item, units are bought from sell orders in the origin system and
+    /// sold into buy orders in the destination system, cheapest purchase against
+    /// best sale first, while each unit is still profitable after sales tax and
+    /// fits in `cargo_capacity_m3`. Sales tax follows the latest fee schedule for
+    /// `skills`. Profit is then expressed per jump of the route and per m³ of
+    /// cargo. Items with no profitable units are left out.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # use tradergrader::fees::TradingSkills;
+    /// # use tradergrader::hauling::{RouteFlag, DEFAULT_CARGO_CAPACITY_M3};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// let skills = TradingSkills::max_skills();
+    /// // Jita to Amarr
+    /// let analysis = client
+    ///     .hauling_analysis(30000142, 30002187, &[34, 35, 36], DEFAULT_CARGO_CAPACITY_M3, RouteFlag::Secure, &skills)
+    ///     .await?;
+    /// println!("{} jumps, {} profitable items", analysis.jumps, analysis.opportunities.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn hauling_analysis(
+        &self,
+        origin_system_id: i32,
+        destination_system_id: i32,
+        type_ids: &[i32],
+        cargo_capacity_m3: f64,
+        flag: RouteFlag,
+        skills: &TradingSkills,
+    ) -> Result<HaulingAnalysis> {
+        if type_ids.is_empty() {
+            return Err("No item types to analyze".into());
+        }
+        if cargo_capacity_m3 <= 0.0 {
+            return Err("Cargo capacity must be positive".into());
+        }
+
+        let plan = self.plan_route(origin_system_id, destination_system_id, flag).await?;
+        let jumps = plan.jumps;
+        let route_risk = match self.assess_route_risk(&plan).await {
+            Ok(risk) => Some(risk),
+            Err(e) => {
+                tracing::warn!("Route risk unavailable for hauling analysis: {e}");
+                None
+            }
+        };
+        let source_region_id = self.fetch_system_region(origin_system_id).await?;
+        let destination_region_id = self.fetch_system_region(destination_system_id).await?;
+        let fee_schedules = FeeSchedules::default();
+        let fees = fee_schedules.latest();
+
+        let mut opportunities: Vec<HaulingOpportunity> = stream::iter(type_ids.iter().copied())
+            .map(|type_id| async move {
+                let info = self.fetch_type_info(type_id).await?;
+                let source_orders = self.fetch_market_orders(source_region_id, Some(type_id)).await?;
+                let destination_orders = self.fetch_market_orders(destination_region_id, Some(type_id)).await?;
+                Ok::<_, TraderGraderError>(hauling_opportunity(
+                    &info,
+                    orders_in_system(&source_orders, false, origin_system_id),
+                    orders_in_system(&destination_orders, true, destination_system_id),
+                    cargo_capacity_m3,
+                    jumps,
+                    fees,
+                    skills,
+                ))
+            })
+            .buffer_unordered(SCAN_CONCURRENCY)
+            .try_filter_map(|opportunity| async move { Ok(opportunity) })
+            .try_collect()
+            .await?;
+        opportunities.sort_by(|a, b| {
+            b.profit_per_jump
+                .partial_cmp(&a.profit_per_jump)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(a.type_id.cmp(&b.type_id))
+        });
+
+        Ok(HaulingAnalysis {
+            origin_system_id,
+            destination_system_id,
+            source_region_id,
+            destination_region_id,
+            jumps,
+            route: plan.systems.iter().map(|system| system.system_id).collect(),
+            cargo_capacity_m3,
+            route_segments: plan.segments,
+            route_risk,
+            opportunities,
+        })
+    }
+
+    /// Computes the legs of a jump freighter route through the given systems
+    ///
+    /// `waypoints` lists the origin, any midpoint cyno systems and the
+    /// destination. Fails when a leg is longer than the ship can jump, since
+    /// that route needs an additional midpoint.
+    pub async fn jump_route_legs(&self, waypoints: &[i32], fuel: &JumpFuelConfig) -> Result<Vec<JumpLeg>> {
+        if waypoints.len() < 2 {
+            return Err(TraderGraderError::InternalError(
+                "A jump route needs at least an origin and a destination system".to_string(),
+            ));
+        }
+
+        let mut systems = Vec::with_capacity(waypoints.len());
+        for &system_id in waypoints {
+            systems.push(self.fetch_system(system_id).await?);
+        }
+
+        let max_range = fuel.max_range_ly();
+        let mut legs = Vec::with_capacity(systems.len() - 1);
+        for pair in systems.windows(2) {
+            let (from, to) = (&pair[0], &pair[1]);
+            let distance_ly = light_years_between(&from.position, &to.position);
+            if distance_ly > max_range {
+                return Err(TraderGraderError::InternalError(format!(
+                    "{} to {} is {:.2} LY, beyond the {:.1} LY jump range; add a midpoint",
+                    from.name, to.name, distance_ly, max_range
+                )));
+            }
+            legs.push(JumpLeg {
+                from_system_id: from.system_id,
+                from_name: from.name.clone(),
+                to_system_id: to.system_id,
+                to_name: to.name.clone(),
+                distance_ly,
+                fuel_units: fuel.fuel_for_jump(distance_ly),
+            });
+        }
+        Ok(legs)
+    }
+
+    /// Calculates the profit of hauling cargo along a jump freighter route after fuel
+    ///
+    /// The cargo is bought from the source region's sell orders, cheapest first,
+    /// and either sold into the destination region's buy orders or listed at its
+    /// lowest sell price. Fuel is priced at The Forge's lowest isotope sell order.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # use tradergrader::hauling::{HaulCargo, JumpFreighter, JumpFuelConfig};
+    /// # async fn example(origin: i32, midpoint: i32, destination: i32) -> Result<()> {
+    /// let client = MarketClient::new();
+    /// let cargo = HaulCargo {
+    ///     source_region_id: 10000002,
+    ///     destination_region_id: 10000060,
+    ///     type_id: 34,
+    ///     quantity: 1000,
+    /// };
+    /// let profit = client
+    ///     .jf_route_profit(&[origin, midpoint, destination], JumpFreighter::Rhea, &JumpFuelConfig::max_skills(), &cargo)
+    ///     .await?;
+    /// println!("Fuel cost: {:.2} ISK", profit.fuel_cost);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn jf_route_profit(
+        &self,
+        waypoints: &[i32],
+        ship: JumpFreighter,
+        fuel: &JumpFuelConfig,
+        cargo: &HaulCargo,
+    ) -> Result<JumpFreighterProfit> {
+        let HaulCargo {
+            source_region_id,
+            destination_region_id,
+            type_id,
+            quantity,
+        } = *cargo;
+        let legs = self.jump_route_legs(waypoints, fuel).await?;
+
+        let fuel_type_id = ship.fuel_type_id();
+        let fuel_book = self.fetch_order_book(FUEL_PRICE_REGION_ID, Some(fuel_type_id)).await?;
+        let fuel_unit_price = fuel_book.best_ask().ok_or_else(|| {
+            TraderGraderError::InternalError(format!("No sell orders for fuel type {fuel_type_id}"))
+        })?;
+
+        let source_book = self.fetch_order_book(source_region_id, Some(type_id)).await?;
+        let purchase_cost = source_book.fill_cost(BookSide::Ask, quantity).ok_or_else(|| {
+            TraderGraderError::InternalError(format!(
+                "Not enough sell orders in region {source_region_id} to buy {quantity} of type {type_id}"
+            ))
+        })?;
+        let destination_book = self.fetch_order_book(destination_region_id, Some(type_id)).await?;
+
+        let mut profit = jump_freighter_profit(
+            ship,
+            legs,
+            fuel_unit_price,
+            type_id,
+            quantity,
+            purchase_cost,
+            &destination_book,
+        );
+        profit.fuel_label = self.type_label(fuel_type_id).await;
+        profit.type_label = self.type_label(type_id).await;
+        Ok(profit)
+    }
+}
+
+/// One side of the book restricted to orders placed in `system_id`
+fn orders_in_system(orders: &[MarketOrder], buy_side: bool, system_id: i32) -> Vec<&MarketOrder> {
+    orders
+        .iter()
+        .filter(|o| o.is_buy_order == buy_side && o.system_id == system_id)
+        .collect()
+}
+
+/// Profit of hauling one item, or `None` when no unit can be hauled at a profit
+///
+/// `sells` are the origin's sell orders and `buys` the destination's buy orders.
+fn hauling_opportunity(
+    info: &TypeInfo,
+    sells: Vec<&MarketOrder>,
+    buys: Vec<&MarketOrder>,
+    cargo_capacity_m3: f64,
+    jumps: usize,
+    fees: &FeeSchedule,
+    skills: &TradingSkills,
+) -> Option<HaulingOpportunity> {
+    let unit_volume = info.cargo_volume();
+    let max_units = if unit_volume > 0.0 {
+        (cargo_capacity_m3 / unit_volume).floor() as i64
+    } else {
+        i64::MAX
+    };
+
+    let tax_rate = fees.sales_tax_percent(skills) / 100.0;
+    let (units, purchase_cost, sales_revenue) = profitable_fill(sells, buys, max_units, tax_rate);
+    if units == 0 {
+        return None;
+    }
+
+    let sales_tax = fees.sales_tax(sales_revenue, skills);
+    let profit = sales_revenue - sales_tax - purchase_cost;
+    Some(HaulingOpportunity {
+        type_id: info.type_id,
+        name: info.name.clone(),
+        unit_volume,
+        units,
+        purchase_cost,
+        sales_revenue,
+        sales_tax,
+        profit,
+        margin_percent: profit / purchase_cost * 100.0,
+        isk_per_m3: if unit_volume > 0.0 { profit / (units as f64 * unit_volume) } else { 0.0 },
+        profit_per_jump: profit / jumps.max(1) as f64,
+    })
+}
+
+/// Matches cheapest sell orders against best buy orders while each unit is profitable
+///
+/// A unit is profitable when its sale price, less `tax_rate` (a fraction),
+/// exceeds its purchase price. Returns the units moved, what they cost and what
+/// they sold for before tax.
+fn profitable_fill(
+    mut sells: Vec<&MarketOrder>,
+    mut buys: Vec<&MarketOrder>,
+    max_units: i64,
+    tax_rate: f64,
+) -> (i64, f64, f64) {
+    sells.sort_by(|a, b| a.price.partial_cmp(&b.price).unwrap_or(std::cmp::Ordering::Equal));
+    buys.sort_by(|a, b| b.price.partial_cmp(&a.price).unwrap_or(std::cmp::Ordering::Equal));
+
+    let (mut units, mut cost, mut revenue) = (0i64, 0.0, 0.0);
+    let (mut sell_idx, mut buy_idx) = (0, 0);
+    let mut sell_left = sells.first().map_or(0, |o| o.volume_remain as i64);
+    let mut buy_left = buys.first().map_or(0, |o| o.volume_remain as i64);
+
+    while sell_idx < sells.len() && buy_idx < buys.len() && units < max_units {
+        let (sell, buy) = (sells[sell_idx], buys[buy_idx]);
+        if sell.price >= buy.price * (1.0 - tax_rate) {
+            break;
+        }
+        let filled = sell_left.min(buy_left).min(max_units - units);
+        units += filled;
+        cost += filled as f64 * sell.price;
+        revenue += filled as f64 * buy.price;
+        sell_left -= filled;
+        buy_left -= filled;
+        if sell_left == 0 {
+            sell_idx += 1;
+            sell_left = sells.get(sell_idx).map_or(0, |o| o.volume_remain as i64);
+        }
+        if buy_left == 0 {
+            buy_idx += 1;
+            buy_left = buys.get(buy_idx).map_or(0, |o| o.volume_remain as i64);
+        }
+    }
+    (units, cost, revenue)
+}
+
+/// Assembles the profit breakdown once prices and legs are known
+fn jump_freighter_profit(
+    ship: JumpFreighter,
+    legs: Vec<JumpLeg>,
+    fuel_unit_price: f64,
+    type_id: i32,
+    quantity: i64,
+    purchase_cost: f64,
+    destination_book: &MarketOrderBook,
+) -> JumpFreighterProfit {
+    let fuel_units: i64 = legs.iter().map(|l| l.fuel_units).sum();
+    let fuel_cost = fuel_units as f64 * fuel_unit_price;
+    let instant_sell_revenue = destination_book.fill_cost(BookSide::Bid, quantity);
+    let listed_sell_revenue = destination_book.best_ask().map(|p| p * quantity as f64);
+
+    JumpFreighterProfit {
+        ship: ship.to_string(),
+        total_distance_ly: legs.iter().map(|l| l.distance_ly).sum(),
+        legs,
+        fuel_type_id: ship.fuel_type_id(),
+        fuel_label: format!("Type {}", ship.fuel_type_id()),
+        fuel_units,
+        fuel_unit_price,
+        fuel_cost,
+        type_id,
+        type_label: format!("Type {type_id}"),
+        quantity,
+        purchase_cost,
+        instant_sell_revenue,
+        listed_sell_revenue,
+        instant_profit_after_fuel: instant_sell_revenue.map(|r| r - purchase_cost - fuel_cost),
+        listed_profit_after_fuel: listed_sell_revenue.map(|r| r - purchase_cost - fuel_cost),
+    }
+}
+
+/// Formats a gate hauling analysis as a text report
+pub(crate) fn format_hauling_analysis(analysis: &HaulingAnalysis, limit: usize) -> String {
+    let mut report = format!(
+        "Hauling Analysis: System {} (Region {}) -> System {} (Region {})\n\
+        Route: {} jumps | Cargo: {:.0} m³\n",
+        analysis.origin_system_id,
+        analysis.source_region_id,
+        analysis.destination_system_id,
+        analysis.destination_region_id,
+        analysis.jumps,
+        analysis.cargo_capacity_m3,
+    );
+    if !analysis.route_segments.is_empty() {
+        report.push_str(&format_route_segments(&analysis.route_segments));
+        report.push('\n');
+    }
+    if let Some(risk) = &analysis.route_risk {
+        report.push_str(&format!(
+            "Route risk: {:.0}/100 ({}) from the last hour of kills\n",
+            risk.risk_score, risk.rating
+        ));
+        if risk.dangerous_systems().next().is_some() {
+            report.push_str(&format_dangerous_systems(risk));
+            report.push('\n');
+        }
+    }
+    if analysis.opportunities.is_empty() {
+        report.push_str("\nNo profitable items on this route.");
+        return report;
+    }
+    for (rank, o) in analysis.opportunities.iter().take(limit).enumerate() {
+        report.push_str(&format!(
+            "\n{}. {} ({})\n\
+            {} units ({:.1} m³): buy {:.2} ISK, sell {:.2} ISK, sales tax {:.2} ISK\n\
+            Profit: {:.2} ISK ({:.1}%) | {:.2} ISK/jump | {:.2} ISK/m³\n",
+            rank + 1,
+            o.name,
+            o.type_id,
+            o.units,
+            o.units as f64 * o.unit_volume,
+            o.purchase_cost,
+            o.sales_revenue,
+            o.sales_tax,
+            o.profit,
+            o.margin_percent,
+            o.profit_per_jump,
+            o.isk_per_m3,
+        ));
+    }
+    report.trim_end().to_string()
+}
+
+/// Formats a jump freighter profit breakdown as a text report
+pub(crate) fn format_jump_freighter_profit(profit: &JumpFreighterProfit) -> String {
+    let isk = |v: Option<f64>| v.map(|v| format!("{v:.2} ISK")).unwrap_or_else(|| "n/a".to_string());
+
+    let mut report = format!(
+        "Jump Freighter Route ({}): {} jumps, {:.2} LY\n",
+        profit.ship,
+        profit.legs.len(),
+        profit.total_distance_ly
+    );
+    for leg in &profit.legs {
+        report.push_str(&format!(
+            "{} -> {}: {:.2} LY, {} isotopes\n",
+            leg.from_name, leg.to_name, leg.distance_ly, leg.fuel_units
+        ));
+    }
+    report.push_str(&format!(
+        "\nFuel: {} units of {} at {:.2} ISK = {:.2} ISK\n\
+        Cargo: {} units of {}, bought for {:.2} ISK\n\
+        \n\
+        Sell to buy orders: {} revenue, {} profit after fuel\n\
+        List at lowest sell: {} revenue, {} profit after fuel",
+        profit.fuel_units,
+        profit.fuel_label,
+        profit.fuel_unit_price,
+        profit.fuel_cost,
+        profit.quantity,
+        profit.type_label,
+        profit.purchase_cost,
+        isk(profit.instant_sell_revenue),
+        isk(profit.instant_profit_after_fuel),
+        isk(profit.listed_sell_revenue),
+        isk(profit.listed_profit_after_fuel),
+    ));
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::market_order;
+    use crate::types::{RouteSegment, SecurityClass};
+
+    fn order(is_buy_order: bool, price: f64, volume_remain: i32) -> MarketOrder {
+        market_order(1, is_buy_order, price).with_volume(volume_remain)
+    }
+
+    #[test]
+    fn test_fuel_config() {
+        let default = JumpFuelConfig::default();
+        assert!((default.fuel_per_ly() - 3_600.0).abs() < 1e-6);
+        assert!((default.max_range_ly() - 10.0).abs() < 1e-9);
+
+        let max = JumpFuelConfig::max_skills();
+        assert!((max.fuel_per_ly() - 2_500.0).abs() < 1e-6);
+        assert_eq!(max.fuel_for_jump(4.2), 10_500);
+        assert_eq!(max.fuel_for_jump(4.2001), 10_501);
+    }
+
+    #[test]
+    fn test_light_years_between() {
+        let origin = Position { x: 0.0, y: 0.0, z: 0.0 };
+        let three_ly = Position {
+            x: 3.0 * METERS_PER_LIGHT_YEAR,
+            y: 0.0,
+            z: 0.0,
+        };
+        assert!((light_years_between(&origin, &three_ly) - 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_jump_freighter_parsing() {
+        assert_eq!("Rhea".parse::<JumpFreighter>(), Ok(JumpFreighter::Rhea));
+        assert_eq!("minmatar".parse::<JumpFreighter>(), Ok(JumpFreighter::Nomad));
+        assert!("charon".parse::<JumpFreighter>().is_err());
+        assert_eq!(JumpFreighter::Ark.fuel_type_id(), 16274);
+    }
+
+    #[test]
+    fn test_fill_cost_walks_the_book() {
+        let book = MarketOrderBook::new(vec![order(false, 12.0, 100), order(false, 10.0, 50), order(true, 9.0, 10)]);
+        assert_eq!(book.best_ask(), Some(10.0));
+        assert_eq!(book.best_bid(), Some(9.0));
+        assert_eq!(book.fill_cost(BookSide::Ask, 100), Some(50.0 * 10.0 + 50.0 * 12.0));
+        assert_eq!(book.fill_cost(BookSide::Ask, 151), None);
+        assert_eq!(book.fill_cost(BookSide::Bid, 10), Some(90.0));
+    }
+
+    #[test]
+    fn test_jump_freighter_profit_after_fuel() {
+        let legs = vec![JumpLeg {
+            from_system_id: 1,
+            from_name: "A".to_string(),
+            to_system_id: 2,
+            to_name: "B".to_string(),
+            distance_ly: 4.0,
+            fuel_units: 10_000,
+        }];
+        let destination = MarketOrderBook::new(vec![order(true, 1_500.0, 1_000), order(false, 2_000.0, 10)]);
+
+        let profit = jump_freighter_profit(JumpFreighter::Rhea, legs, 500.0, 34, 1_000, 1_000_000.0, &destination);
+        assert_eq!(profit.fuel_cost, 5_000_000.0);
+        assert_eq!(profit.instant_sell_revenue, Some(1_500_000.0));
+        assert_eq!(profit.instant_profit_after_fuel, Some(-4_500_000.0));
+        assert_eq!(profit.listed_profit_after_fuel, Some(2_000_000.0 - 1_000_000.0 - 5_000_000.0));
+
+        let report = format_jump_freighter_profit(&profit);
+        assert!(report.contains("Jump Freighter Route (Rhea): 1 jumps, 4.00 LY"));
+        assert!(report.contains("A -> B: 4.00 LY, 10000 isotopes"));
+    }
+
+    fn type_info(type_id: i32, volume: f64) -> TypeInfo {
+        TypeInfo {
+            type_id,
+            name: "Tritanium".to_string(),
+            group_id: 18,
+            market_group_id: Some(1857),
+            volume: Some(volume),
+            packaged_volume: Some(volume),
+            published: true,
+            meta_level: None,
+            tech_level: None,
+            dogma_attributes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_route_flag_parsing() {
+        assert_eq!("secure".parse::<RouteFlag>(), Ok(RouteFlag::Secure));
+        assert_eq!(RouteFlag::default().as_str(), "shortest");
+        assert!("scenic".parse::<RouteFlag>().is_err());
+    }
+
+    #[test]
+    fn test_profitable_fill_stops_at_break_even() {
+        let sells = [order(false, 10.0, 100), order(false, 14.0, 100), order(false, 11.0, 50)];
+        let buys = [order(true, 15.0, 120), order(true, 12.0, 200)];
+        let (units, cost, revenue) = profitable_fill(sells.iter().collect(), buys.iter().collect(), i64::MAX, 0.0);
+
+        // 100 @ 10 -> 15, 20 @ 11 -> 15, 30 @ 11 -> 12, then 14 >= 12 stops
+        assert_eq!(units, 150);
+        assert_eq!(cost, 100.0 * 10.0 + 50.0 * 11.0);
+        assert_eq!(revenue, 120.0 * 15.0 + 30.0 * 12.0);
+
+        let (capped, _, _) = profitable_fill(sells.iter().collect(), buys.iter().collect(), 40, 0.0);
+        assert_eq!(capped, 40);
+
+        // A 10% tax makes the 11 -> 12 pair unprofitable
+        let (taxed, _, _) = profitable_fill(sells.iter().collect(), buys.iter().collect(), i64::MAX, 0.1);
+        assert_eq!(taxed, 120);
+    }
+
+    #[test]
+    fn test_hauling_opportunity() {
+        let source = vec![order(false, 10.0, 1_000)];
+        let mut elsewhere = order(true, 100.0, 1_000);
+        elsewhere.system_id = 30000144;
+        let destination = vec![order(true, 12.0, 1_000), elsewhere];
+
+        let fees = FeeSchedule {
+            base_sales_tax_percent: 5.0,
+            accounting_reduction: 0.0,
+            ..FeeSchedules::default().latest().clone()
+        };
+        let skills = TradingSkills::default();
+
+        // 0.5 m³ units in a 200 m³ hold: 400 units over 4 jumps, 5% tax on 4,800 ISK
+        let sells = orders_in_system(&source, false, 30000142);
+        let buys = orders_in_system(&destination, true, 30000142);
+        assert_eq!(buys.len(), 1);
+        let o = hauling_opportunity(&type_info(34, 0.5), sells.clone(), buys, 200.0, 4, &fees, &skills).unwrap();
+        assert_eq!(o.units, 400);
+        assert_eq!(o.sales_tax, 240.0);
+        assert_eq!(o.profit, 560.0);
+        assert_eq!(o.profit_per_jump, 140.0);
+        assert!((o.isk_per_m3 - 2.8).abs() < 1e-9);
+        assert!((o.margin_percent - 14.0).abs() < 1e-9);
+
+        let unprofitable = vec![order(true, 10.5, 1_000)];
+        let buys = orders_in_system(&unprofitable, true, 30000142);
+        assert!(hauling_opportunity(&type_info(34, 0.5), sells, buys, 200.0, 4, &fees, &skills).is_none());
+
+        let analysis = HaulingAnalysis {
+            origin_system_id: 30000142,
+            destination_system_id: 30002187,
+            source_region_id: 10000002,
+            destination_region_id: 10000043,
+            jumps: 4,
+            route: vec![30000142, 1, 2, 3, 30002187],
+            cargo_capacity_m3: 200.0,
+            route_risk: None,
+            route_segments: vec![RouteSegment {
+                security_class: SecurityClass::LowSec,
+                from_system: "Tama".to_string(),
+                to_system: "Tama".to_string(),
+                system_count: 1,
+                lowest_security_status: 0.3,
+            }],
+            opportunities: vec![o],
+        };
+        let report = format_hauling_analysis(&analysis, 10);
+        assert!(report.contains("Route: 4 jumps | Cargo: 200 m³"));
+        assert!(report.contains("- low-sec: Tama, lowest 0.3"));
+        assert!(report.contains("1. Tritanium (34)"));
+        assert!(report.contains("sales tax 240.00 ISK"));
+        assert!(report.contains("140.00 ISK/jump | 2.80 ISK/m³"));
+    }
+}
@@ -0,0 +1,262 @@
+//! Health checks for TraderGrader
+//!
+//! A health check that only reports the process is up says nothing about
+//! whether the next tool call will work. [`MarketClient::health_check`]
+//! exercises what tool calls depend on: the cache is written and read back,
+//! ESI's `/status/` route is requested once with a short timeout, and the
+//! rate limiter's share of the ESI error budget is read. Each component gets
+//! a pass, warn or fail level; the report as a whole is as bad as its worst
+//! component.
+
+use crate::market::MarketClient;
+use crate::types::ServerStatus;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Longest a single component check may take
+pub const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// ESI responses slower than this are reported as a warning
+const SLOW_ESI_RESPONSE: Duration = Duration::from_secs(2);
+
+/// How healthy a component is, from best to worst
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthLevel {
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl fmt::Display for HealthLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Pass => "pass",
+            Self::Warn => "warn",
+            Self::Fail => "fail",
+        })
+    }
+}
+
+/// The outcome of checking one dependency
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ComponentHealth {
+    /// Component name, e.g. "cache" or "esi"
+    pub name: String,
+    pub level: HealthLevel,
+    /// What was found, in a sentence
+    pub detail: String,
+    /// How long the check took
+    pub elapsed_ms: u64,
+}
+
+impl ComponentHealth {
+    fn new(name: &str, level: HealthLevel, detail: String, started: Instant) -> Self {
+        Self {
+            name: name.to_string(),
+            level,
+            detail,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+        }
+    }
+}
+
+/// Every component's health at one point in time
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HealthReport {
+    pub checked_at: DateTime<Utc>,
+    pub components: Vec<ComponentHealth>,
+}
+
+impl HealthReport {
+    /// The worst level of any component; an empty report passes
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::Utc;
+    /// use tradergrader::health::{ComponentHealth, HealthLevel, HealthReport};
+    ///
+    /// let component = |name: &str, level| ComponentHealth {
+    ///     name: name.to_string(),
+    ///     level,
+    ///     detail: String::new(),
+    ///     elapsed_ms: 0,
+    /// };
+    /// let report = HealthReport {
+    ///     checked_at: Utc::now(),
+    ///     components: vec![component("cache", HealthLevel::Pass), component("esi", HealthLevel::Warn)],
+    /// };
+    /// assert_eq!(report.level(), HealthLevel::Warn);
+    /// ```
+    pub fn level(&self) -> HealthLevel {
+        self.components.iter().map(|c| c.level).max().unwrap_or(HealthLevel::Pass)
+    }
+
+    /// Formats the report as a status line and a table of components
+    pub fn to_text(&self, server_name: &str, server_version: &str) -> String {
+        let icon = match self.level() {
+            HealthLevel::Pass => "✅",
+            HealthLevel::Warn => "⚠️",
+            HealthLevel::Fail => "❌",
+        };
+        let mut text = format!(
+            "{icon} {server_name} v{server_version}: {}\nTimestamp: {}\n\n\
+             | Component | Status | Detail | Time |\n\
+             |---|---|---|---|\n",
+            self.level(),
+            self.checked_at.to_rfc3339()
+        );
+        for c in &self.components {
+            text.push_str(&format!("| {} | {} | {} | {} ms |\n", c.name, c.level, c.detail, c.elapsed_ms));
+        }
+        text.trim_end().to_string()
+    }
+}
+
+impl MarketClient {
+    /// Checks the cache, ESI and the rate limiter's error budget
+    ///
+    /// Makes one uncached, unretried request to ESI `/status/`; every check
+    /// gives up after [`HEALTH_CHECK_TIMEOUT`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::MarketClient;
+    /// use tradergrader::health::HealthLevel;
+    ///
+    /// # async fn example() {
+    /// let client = MarketClient::new();
+    /// let report = client.health_check().await;
+    /// if report.level() == HealthLevel::Fail {
+    ///     eprintln!("{}", report.to_text("TraderGrader", "0.1.0"));
+    /// }
+    /// # }
+    /// ```
+    pub async fn health_check(&self) -> HealthReport {
+        let (cache, esi) = tokio::join!(self.check_cache(), self.check_esi());
+        HealthReport {
+            checked_at: Utc::now(),
+            components: vec![cache, esi, self.check_rate_limiter()],
+        }
+    }
+
+    /// Writes and reads back a test item, then reports hit statistics
+    async fn check_cache(&self) -> ComponentHealth {
+        let started = Instant::now();
+        let Some(cache) = self.esi().cache_backend() else {
+            return ComponentHealth::new(
+                "cache",
+                HealthLevel::Warn,
+                "disabled: every tool call goes to ESI".to_string(),
+                started,
+            );
+        };
+        let (level, detail) = match tokio::time::timeout(HEALTH_CHECK_TIMEOUT, cache.health_check()).await {
+            Err(_) => (HealthLevel::Fail, format!("no answer within {}s", HEALTH_CHECK_TIMEOUT.as_secs())),
+            Ok(Err(e)) => (HealthLevel::Fail, format!("read-back failed: {e}")),
+            Ok(Ok(())) => match cache.stats().await {
+                Ok(stats) => (
+                    HealthLevel::Pass,
+                    format!(
+                        "{}: {} items, {:.0}% hit ratio",
+                        stats.backend_info,
+                        stats.item_count,
+                        stats.hit_ratio * 100.0
+                    ),
+                ),
+                Err(e) => (HealthLevel::Warn, format!("reachable, but stats failed: {e}")),
+            },
+        };
+        ComponentHealth::new("cache", level, detail, started)
+    }
+
+    /// Requests `/status/` once and judges the answer
+    async fn check_esi(&self) -> ComponentHealth {
+        let started = Instant::now();
+        let (level, detail) = match self.esi().probe_public("/status/", HEALTH_CHECK_TIMEOUT).await {
+            Err(e) => (HealthLevel::Fail, format!("unreachable: {e}")),
+            Ok(response) if response.status().is_success() => {
+                let elapsed = started.elapsed();
+                let slow = elapsed > SLOW_ESI_RESPONSE;
+                match response.json::<ServerStatus>().await {
+                    Ok(status) => (
+                        if slow || status.vip == Some(true) { HealthLevel::Warn } else { HealthLevel::Pass },
+                        format!(
+                            "Tranquility up, {} players, version {}{}{}",
+                            status.players,
+                            status.server_version,
+                            if status.vip == Some(true) { ", VIP mode" } else { "" },
+                            if slow { ", slow response" } else { "" }
+                        ),
+                    ),
+                    Err(e) => (HealthLevel::Warn, format!("reachable, but the status was unreadable: {e}")),
+                }
+            }
+            // ESI answers 503/504 during daily downtime and outages
+            Ok(response) if response.status().is_server_error() => {
+                (HealthLevel::Warn, format!("degraded: /status/ returned {}", response.status()))
+            }
+            Ok(response) => (HealthLevel::Fail, format!("/status/ returned {}", response.status())),
+        };
+        ComponentHealth::new("esi", level, detail, started)
+    }
+
+    /// Reports the ESI error budget this client's rate limiter has seen
+    fn check_rate_limiter(&self) -> ComponentHealth {
+        let started = Instant::now();
+        let limiter = self.rate_limiter();
+        let budget = limiter.error_budget();
+        let rate = format!("{} requests/s", limiter.config().requests_per_second);
+        let (level, detail) = match (budget.remaining, budget.resets_in) {
+            (_, Some(resets_in)) if budget.paused => (
+                HealthLevel::Warn,
+                format!("{rate}; error budget exhausted, requests held for {}s", resets_in.as_secs()),
+            ),
+            (Some(remaining), Some(resets_in)) => (
+                HealthLevel::Pass,
+                format!("{rate}; {remaining} errors left, window resets in {}s", resets_in.as_secs()),
+            ),
+            _ => (HealthLevel::Pass, format!("{rate}; no ESI errors in the current window")),
+        };
+        ComponentHealth::new("rate_limiter", level, detail, started)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cache_and_rate_limiter_checks() {
+        let client = MarketClient::new();
+        let cache = client.check_cache().await;
+        assert_eq!(cache.level, HealthLevel::Pass);
+        assert!(cache.detail.contains("items"));
+
+        let limiter = client.check_rate_limiter();
+        assert_eq!(limiter.level, HealthLevel::Pass);
+        assert!(limiter.detail.contains("no ESI errors"));
+
+        let uncached = MarketClient::without_cache().check_cache().await;
+        assert_eq!(uncached.level, HealthLevel::Warn);
+    }
+
+    #[test]
+    fn test_report_text() {
+        let report = HealthReport {
+            checked_at: Utc::now(),
+            components: vec![
+                ComponentHealth::new("cache", HealthLevel::Pass, "in memory".to_string(), Instant::now()),
+                ComponentHealth::new("esi", HealthLevel::Fail, "unreachable".to_string(), Instant::now()),
+            ],
+        };
+        assert_eq!(report.level(), HealthLevel::Fail);
+        let text = report.to_text("TraderGrader", "1.0.0");
+        assert!(text.starts_with("❌ TraderGrader v1.0.0: fail"));
+        assert!(text.contains("| esi | fail | unreachable |"));
+    }
+}
@@ -0,0 +1,586 @@
+//! Market history aggregation for TraderGrader
+//!
+//! ESI returns over a year of daily rows per item, which is more than a chart
+//! or an LLM context needs. [`aggregate_history`] rolls them up into weekly or
+//! monthly OHLC-style candles with summed volume, ISK turnover and order
+//! counts, a [`HistoryRange`] narrows them to the days a question is about,
+//! [`HistoryStats`] sums up recent turnover and whether volume is rising, and
+//! [`DataFreshness`] reports how stale and gappy the rows are.
+
+use crate::error::{Result, TraderGraderError};
+use crate::market::MarketClient;
+use crate::paging::Page;
+use crate::types::{Candle, DataFreshness, HistoryStats, MarketHistory, Period, TrendDirection};
+use chrono::{Datelike, Duration, NaiveDate};
+
+/// Change in average daily volume, last 7 days against last 30, that counts as a volume trend
+const VOLUME_TREND_PERCENT: f64 = 10.0;
+
+/// A window of daily history: calendar bounds, the last N days, or both
+///
+/// With `days` the window ends at `to`, or at the newest day of data when
+/// `to` isn't set. Every bound is inclusive.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HistoryRange {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub days: Option<u32>,
+}
+
+impl HistoryRange {
+    /// The most recent `days` days of data
+    pub fn last_days(days: u32) -> Self {
+        Self {
+            days: Some(days),
+            ..Self::default()
+        }
+    }
+
+    /// Every day from `from` to `to`, inclusive
+    pub fn between(from: NaiveDate, to: NaiveDate) -> Self {
+        Self {
+            from: Some(from),
+            to: Some(to),
+            days: None,
+        }
+    }
+
+    /// Whether the range keeps every day
+    pub fn is_unbounded(&self) -> bool {
+        self.from.is_none() && self.to.is_none() && self.days.is_none()
+    }
+
+    /// Checks `from` isn't after `to` and `days` isn't zero
+    pub fn validate(&self) -> Result<()> {
+        if let (Some(from), Some(to)) = (self.from, self.to) {
+            if from > to {
+                return Err(TraderGraderError::InvalidArgument {
+                    field: "from".to_string(),
+                    reason: format!("{from} is after the end date {to}"),
+                });
+            }
+        }
+        if self.days == Some(0) {
+            return Err(TraderGraderError::InvalidArgument {
+                field: "days".to_string(),
+                reason: "must be at least 1".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// The rows inside the range, oldest first; rows with an unparseable date are skipped
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::history::HistoryRange;
+    /// use tradergrader::MarketHistory;
+    ///
+    /// let history: Vec<MarketHistory> = (1..=30)
+    ///     .map(|day| MarketHistory {
+    ///         average: 100.0,
+    ///         date: format!("2025-06-{day:02}"),
+    ///         highest: 101.0,
+    ///         lowest: 99.0,
+    ///         order_count: 10,
+    ///         volume: 1_000,
+    ///         extra: Default::default(),
+    ///     })
+    ///     .collect();
+    ///
+    /// let week = HistoryRange::last_days(7).select(&history);
+    /// assert_eq!(week.len(), 7);
+    /// assert_eq!(week[0].date, "2025-06-24");
+    /// ```
+    pub fn select(&self, history: &[MarketHistory]) -> Vec<MarketHistory> {
+        let mut days: Vec<(NaiveDate, &MarketHistory)> = history
+            .iter()
+            .filter_map(|h| NaiveDate::parse_from_str(&h.date, "%Y-%m-%d").ok().map(|date| (date, h)))
+            .filter(|(date, _)| self.from.is_none_or(|from| *date >= from) && self.to.is_none_or(|to| *date <= to))
+            .collect();
+        days.sort_by_key(|(date, _)| *date);
+
+        if let (Some(count), Some((newest, _))) = (self.days, days.last()) {
+            let end = self.to.unwrap_or(*newest);
+            let start = end - Duration::days(i64::from(count) - 1);
+            days.retain(|(date, _)| *date >= start);
+        }
+        days.into_iter().map(|(_, h)| h.clone()).collect()
+    }
+}
+
+/// Parses a `YYYY-MM-DD` date argument
+pub fn parse_history_date(field: &str, value: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").map_err(|_| TraderGraderError::InvalidArgument {
+        field: field.to_string(),
+        reason: format!("expected a YYYY-MM-DD date, got '{value}'"),
+    })
+}
+
+impl MarketClient {
+    /// Market history for an item inside `range`, aggregated to `granularity`, oldest first
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Period, Result};
+    /// use tradergrader::history::HistoryRange;
+    ///
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// let weeks = client
+    ///     .fetch_history_candles(10000002, 34, HistoryRange::last_days(90), Period::Weekly)
+    ///     .await?;
+    /// println!("{} weeks of Tritanium", weeks.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn fetch_history_candles(
+        &self,
+        region_id: i32,
+        type_id: i32,
+        range: HistoryRange,
+        granularity: Period,
+    ) -> Result<Vec<Candle>> {
+        range.validate()?;
+        let history = self.fetch_market_history(region_id, type_id).await?;
+        Ok(aggregate_history(&range.select(&history), granularity))
+    }
+}
+
+/// Groups daily history into candles, oldest first
+///
+/// Rows may arrive in any order; rows with an unparseable date are skipped.
+/// The candle average is weighted by volume, falling back to the plain mean of
+/// daily averages for periods without trades.
+///
+/// # Examples
+///
+/// ```
+/// use tradergrader::{MarketHistory, Period};
+/// use tradergrader::history::aggregate_history;
+///
+/// let history: Vec<MarketHistory> = (1..=14)
+///     .map(|day| MarketHistory {
+///         average: 100.0 + day as f64,
+///         date: format!("2025-06-{day:02}"),
+///         highest: 102.0 + day as f64,
+///         lowest: 98.0 + day as f64,
+///         order_count: 10,
+///         volume: 1_000,
+///         extra: Default::default(),
+///     })
+///     .collect();
+///
+/// // June 1st 2025 is a Sunday, so two weeks of days span three ISO weeks
+/// let weeks = aggregate_history(&history, Period::Weekly);
+/// assert_eq!(weeks.len(), 3);
+/// assert_eq!(weeks[1].period_start, "2025-06-02");
+/// assert_eq!(weeks[1].volume, 7_000);
+/// ```
+pub fn aggregate_history(history: &[MarketHistory], period: Period) -> Vec<Candle> {
+    let mut days: Vec<(NaiveDate, &MarketHistory)> = history
+        .iter()
+        .filter_map(|h| NaiveDate::parse_from_str(&h.date, "%Y-%m-%d").ok().map(|date| (date, h)))
+        .collect();
+    days.sort_by_key(|(date, _)| *date);
+
+    let mut candles: Vec<(NaiveDate, Vec<&MarketHistory>)> = Vec::new();
+    for (date, day) in days {
+        let start = period_start(date, period);
+        match candles.last_mut() {
+            Some((current, rows)) if *current == start => rows.push(day),
+            _ => candles.push((start, vec![day])),
+        }
+    }
+
+    candles.into_iter().map(|(start, rows)| candle(start, &rows)).collect()
+}
+
+/// First day of the period containing `date`
+pub(crate) fn period_start(date: NaiveDate, period: Period) -> NaiveDate {
+    match period {
+        Period::Daily => date,
+        Period::Weekly => date - Duration::days(date.weekday().num_days_from_monday() as i64),
+        Period::Monthly => date.with_day(1).expect("every month has a first day"),
+    }
+}
+
+/// Builds one candle from a period's rows, oldest first
+fn candle(start: NaiveDate, rows: &[&MarketHistory]) -> Candle {
+    let volume: i64 = rows.iter().map(|h| h.volume).sum();
+    let isk_turnover: f64 = rows.iter().map(|h| h.average * h.volume as f64).sum();
+    let average = if volume > 0 {
+        isk_turnover / volume as f64
+    } else {
+        rows.iter().map(|h| h.average).sum::<f64>() / rows.len() as f64
+    };
+
+    Candle {
+        period_start: start.format("%Y-%m-%d").to_string(),
+        open: rows[0].average,
+        high: rows.iter().map(|h| h.highest).fold(f64::NEG_INFINITY, f64::max),
+        low: rows.iter().map(|h| h.lowest).fold(f64::INFINITY, f64::min),
+        close: rows[rows.len() - 1].average,
+        average,
+        volume,
+        isk_turnover,
+        order_count: rows.iter().map(|h| h.order_count).sum(),
+        days: rows.len(),
+    }
+}
+
+impl HistoryStats {
+    /// Turnover and volume averages over the 7 and 30 days up to the newest row
+    ///
+    /// Averages are per calendar day: ESI leaves out days without trades, so
+    /// those count as zero. Returns `None` when no row has a valid date.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::{HistoryStats, MarketHistory, TrendDirection};
+    ///
+    /// // 1,000 units a day for a month, then 2,000 a day for the last week
+    /// let history: Vec<MarketHistory> = (1..=30)
+    ///     .map(|day| MarketHistory {
+    ///         average: 10.0,
+    ///         date: format!("2025-06-{day:02}"),
+    ///         highest: 11.0,
+    ///         lowest: 9.0,
+    ///         order_count: 10,
+    ///         volume: if day > 23 { 2_000 } else { 1_000 },
+    ///         extra: Default::default(),
+    ///     })
+    ///     .collect();
+    ///
+    /// let stats = HistoryStats::from_history(&history).unwrap();
+    /// assert_eq!(stats.avg_daily_isk_turnover_7d, 20_000.0);
+    /// assert_eq!(stats.volume_trend, TrendDirection::Up);
+    /// ```
+    pub fn from_history(history: &[MarketHistory]) -> Option<Self> {
+        let days: Vec<(NaiveDate, &MarketHistory)> = history
+            .iter()
+            .filter_map(|h| NaiveDate::parse_from_str(&h.date, "%Y-%m-%d").ok().map(|date| (date, h)))
+            .collect();
+        let end = days.iter().map(|(date, _)| *date).max()?;
+        let window = |length: i64| {
+            let start = end - Duration::days(length - 1);
+            days.iter()
+                .filter(|(date, _)| *date >= start)
+                .fold((0i64, 0.0), |(volume, isk), (_, h)| (volume + h.volume, isk + h.average * h.volume as f64))
+        };
+        let (volume_7d, isk_turnover_7d) = window(7);
+        let (volume_30d, isk_turnover_30d) = window(30);
+        let (avg_daily_volume_7d, avg_daily_volume_30d) = (volume_7d as f64 / 7.0, volume_30d as f64 / 30.0);
+
+        let volume_change_percent =
+            (avg_daily_volume_30d > 0.0).then(|| (avg_daily_volume_7d / avg_daily_volume_30d - 1.0) * 100.0);
+        let volume_trend = match volume_change_percent {
+            Some(change) if change > VOLUME_TREND_PERCENT => TrendDirection::Up,
+            Some(change) if change < -VOLUME_TREND_PERCENT => TrendDirection::Down,
+            _ => TrendDirection::Flat,
+        };
+
+        Some(Self {
+            end_date: end.to_string(),
+            isk_turnover_7d,
+            isk_turnover_30d,
+            avg_daily_volume_7d,
+            avg_daily_volume_30d,
+            avg_daily_isk_turnover_7d: isk_turnover_7d / 7.0,
+            avg_daily_isk_turnover_30d: isk_turnover_30d / 30.0,
+            volume_change_percent,
+            volume_trend,
+        })
+    }
+}
+
+impl DataFreshness {
+    /// Gaps in the last `window_days` calendar days up to the newest row
+    ///
+    /// The window never starts before the oldest row, so a recently seeded
+    /// item isn't reported as missing days it never traded. Returns `None`
+    /// when no row has a valid date.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::{DataFreshness, MarketHistory};
+    ///
+    /// // June 1st to 10th without the 4th, 5th and 8th
+    /// let history: Vec<MarketHistory> = [1, 2, 3, 6, 7, 9, 10]
+    ///     .into_iter()
+    ///     .map(|day| MarketHistory {
+    ///         average: 10.0,
+    ///         date: format!("2025-06-{day:02}"),
+    ///         highest: 11.0,
+    ///         lowest: 9.0,
+    ///         order_count: 10,
+    ///         volume: 1_000,
+    ///         extra: Default::default(),
+    ///     })
+    ///     .collect();
+    ///
+    /// let freshness = DataFreshness::from_history(&history, 30).unwrap();
+    /// assert_eq!(freshness.last_data_date, "2025-06-10");
+    /// assert_eq!(freshness.window_days, 10);
+    /// assert_eq!(freshness.days_missing, 3);
+    /// assert_eq!(freshness.longest_gap_days, 2);
+    /// ```
+    pub fn from_history(history: &[MarketHistory], window_days: u32) -> Option<Self> {
+        let mut dates: Vec<NaiveDate> = history
+            .iter()
+            .filter_map(|h| NaiveDate::parse_from_str(&h.date, "%Y-%m-%d").ok())
+            .collect();
+        dates.sort_unstable();
+        dates.dedup();
+        let (oldest, newest) = (*dates.first()?, *dates.last()?);
+
+        let start = (newest - Duration::days(i64::from(window_days.max(1)) - 1)).max(oldest);
+        let in_window: Vec<NaiveDate> = dates.into_iter().filter(|date| *date >= start).collect();
+        let longest_gap_days = in_window
+            .windows(2)
+            .map(|pair| (pair[1] - pair[0]).num_days() as u32 - 1)
+            .max()
+            .unwrap_or(0);
+        let span = (newest - start).num_days() as u32 + 1;
+
+        Some(Self {
+            last_data_date: newest.to_string(),
+            window_days: span,
+            days_missing: span - in_window.len() as u32,
+            longest_gap_days,
+        })
+    }
+
+    /// Whole days between the newest row and `today`, `None` if the date doesn't parse
+    ///
+    /// ESI publishes each day after downtime, so 1 is normal and more means
+    /// the item hasn't traded since, or the history hasn't refreshed.
+    pub fn days_behind(&self, today: NaiveDate) -> Option<i64> {
+        NaiveDate::parse_from_str(&self.last_data_date, "%Y-%m-%d")
+            .ok()
+            .map(|last| (today - last).num_days())
+    }
+}
+
+/// Formats the last data date and any gaps as one line
+pub(crate) fn format_freshness(freshness: &DataFreshness, today: NaiveDate) -> String {
+    if freshness.last_data_date.is_empty() {
+        return "Data: no dated history".to_string();
+    }
+    let behind = match freshness.days_behind(today) {
+        Some(days) if days > 1 => format!(" ({days} days behind)"),
+        _ => String::new(),
+    };
+    let gaps = match freshness.days_missing {
+        0 => format!("no gaps in {} days", freshness.window_days),
+        missing => format!(
+            "{missing} of {} days missing, longest gap {} days",
+            freshness.window_days, freshness.longest_gap_days
+        ),
+    };
+    format!("Data through {}{behind}, {gaps}", freshness.last_data_date)
+}
+
+/// Formats turnover and volume averages as a short block
+pub(crate) fn format_history_stats(stats: &HistoryStats) -> String {
+    let change = stats
+        .volume_change_percent
+        .map(|c| format!(" ({c:+.1}% 7d vs 30d)"))
+        .unwrap_or_default();
+    format!(
+        "Turnover to {}:\n\
+         7-day: {:.2} ISK ({:.2} ISK/day, {:.0} units/day)\n\
+         30-day: {:.2} ISK ({:.2} ISK/day, {:.0} units/day)\n\
+         Volume trend: {}{change}",
+        stats.end_date,
+        stats.isk_turnover_7d,
+        stats.avg_daily_isk_turnover_7d,
+        stats.avg_daily_volume_7d,
+        stats.isk_turnover_30d,
+        stats.avg_daily_isk_turnover_30d,
+        stats.avg_daily_volume_30d,
+        stats.volume_trend
+    )
+}
+
+/// Formats a page of candles as a text table, newest first
+pub(crate) fn format_candles(title: &str, candles: &[Candle], page: Page) -> String {
+    if candles.is_empty() {
+        return "No historical data available".to_string();
+    }
+    let newest_first: Vec<&Candle> = candles.iter().rev().collect();
+    let shown = page.slice(&newest_first);
+    let mut report = match page.offset {
+        0 => format!("{title} (latest {} of {}):\n", shown.len(), candles.len()),
+        skipped => format!("{title} ({} of {}, skipping the latest {skipped}):\n", shown.len(), candles.len()),
+    };
+    for c in shown {
+        report.push_str(&format!(
+            "{}: Open: {:.2} | High: {:.2} | Low: {:.2} | Close: {:.2} | VWAP: {:.2} | Volume: {} | Turnover: {:.2} ISK ({} days)\n",
+            c.period_start, c.open, c.high, c.low, c.close, c.average, c.volume, c.isk_turnover, c.days
+        ));
+    }
+    if let Some(marker) = page.marker(candles.len(), "periods") {
+        report.push_str(&format!("{marker}\n"));
+    }
+    report.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::history_day;
+
+    #[test]
+    fn test_weekly_candles() {
+        // Wednesday to the following Monday, out of order, each day spanning 1 ISK either side
+        let history: Vec<MarketHistory> =
+            [("2025-06-09", 14.0, 100), ("2025-06-04", 10.0, 100), ("2025-06-06", 12.0, 300), ("2025-06-05", 20.0, 0)]
+                .into_iter()
+                .map(|(date, average, volume)| {
+                    history_day(date, average, volume).with_range(average - 1.0, average + 1.0).with_order_count(5)
+                })
+                .collect();
+        let candles = aggregate_history(&history, Period::Weekly);
+
+        assert_eq!(candles.len(), 2);
+        let week = &candles[0];
+        assert_eq!(week.period_start, "2025-06-02");
+        assert_eq!((week.open, week.close), (10.0, 12.0));
+        assert_eq!((week.high, week.low), (21.0, 9.0));
+        assert_eq!(week.volume, 400);
+        assert_eq!(week.order_count, 15);
+        assert_eq!(week.days, 3);
+        assert_eq!(week.average, (10.0 * 100.0 + 12.0 * 300.0) / 400.0);
+        assert_eq!(week.isk_turnover, 10.0 * 100.0 + 12.0 * 300.0);
+        assert_eq!(candles[1].period_start, "2025-06-09");
+    }
+
+    #[test]
+    fn test_monthly_and_daily_candles() {
+        let history = vec![
+            history_day("2025-05-31", 10.0, 0),
+            history_day("2025-06-01", 12.0, 0),
+            history_day("2025-06-30", 14.0, 0),
+            history_day("not a date", 99.0, 10),
+        ];
+
+        let months = aggregate_history(&history, Period::Monthly);
+        assert_eq!(months.len(), 2);
+        assert_eq!(months[1].period_start, "2025-06-01");
+        // No trades: plain mean of the daily averages
+        assert_eq!(months[1].average, 13.0);
+
+        let days = aggregate_history(&history, Period::Daily);
+        assert_eq!(days.len(), 3);
+        assert!(aggregate_history(&[], Period::Weekly).is_empty());
+    }
+
+    #[test]
+    fn test_period_parsing_and_format() {
+        assert_eq!("Weekly".parse::<Period>(), Ok(Period::Weekly));
+        assert_eq!("month".parse::<Period>(), Ok(Period::Monthly));
+        assert!("hourly".parse::<Period>().is_err());
+
+        let history = vec![
+            history_day("2025-06-02", 10.0, 10),
+            history_day("2025-06-09", 11.0, 10),
+            history_day("2025-06-16", 12.0, 10),
+        ];
+        let report = format_candles("Weekly history", &aggregate_history(&history, Period::Weekly), Page::first(2));
+        assert!(report.starts_with("Weekly history (latest 2 of 3):"));
+        assert!(report.contains("2025-06-16"));
+        assert!(!report.contains("2025-06-02"));
+        assert!(report.ends_with("Showing 1–2 of 3 periods; pass offset=2 for more"));
+
+        let candles = aggregate_history(&history, Period::Weekly);
+        let report = format_candles("Weekly history", &candles, Page { offset: 2, limit: 2 });
+        assert!(report.starts_with("Weekly history (1 of 3, skipping the latest 2):"));
+        assert!(report.contains("2025-06-02"));
+    }
+
+    #[test]
+    fn test_history_stats() {
+        // Five days of 100 units inside the last week, nothing else in the month
+        let history = vec![
+            history_day("2025-06-30", 10.0, 100),
+            history_day("2025-06-26", 10.0, 100),
+            history_day("2025-06-27", 20.0, 100),
+            history_day("2025-06-28", 10.0, 100),
+            history_day("2025-06-29", 10.0, 100),
+            history_day("2025-05-01", 50.0, 1_000),
+        ];
+        let stats = HistoryStats::from_history(&history).unwrap();
+        assert_eq!(stats.end_date, "2025-06-30");
+        assert_eq!(stats.isk_turnover_7d, 6_000.0);
+        assert_eq!(stats.isk_turnover_30d, 6_000.0);
+        assert_eq!(stats.avg_daily_volume_30d, 500.0 / 30.0);
+        // Same volume in both windows, spread over fewer days
+        assert_eq!(stats.volume_trend, TrendDirection::Up);
+
+        let flat: Vec<MarketHistory> = (1..=30).map(|d| history_day(&format!("2025-06-{d:02}"), 10.0, 100)).collect();
+        let stats = HistoryStats::from_history(&flat).unwrap();
+        assert_eq!(stats.volume_change_percent, Some(0.0));
+        assert_eq!(stats.volume_trend, TrendDirection::Flat);
+        assert!(format_history_stats(&stats).contains("7-day: 7000.00 ISK (1000.00 ISK/day, 100 units/day)"));
+
+        let quiet = vec![history_day("2025-06-01", 10.0, 0)];
+        assert_eq!(HistoryStats::from_history(&quiet).unwrap().volume_change_percent, None);
+        assert!(HistoryStats::from_history(&[]).is_none());
+    }
+
+    #[test]
+    fn test_history_range() {
+        let history = vec![
+            history_day("2025-06-03", 13.0, 10),
+            history_day("2025-06-01", 11.0, 10),
+            history_day("2025-06-02", 12.0, 10),
+            history_day("2025-06-05", 15.0, 10),
+        ];
+        let date = |d: &str| parse_history_date("date", d).unwrap();
+        let dates = |range: HistoryRange| range.select(&history).into_iter().map(|h| h.date).collect::<Vec<_>>();
+
+        assert_eq!(dates(HistoryRange::default()).len(), 4);
+        assert_eq!(dates(HistoryRange::between(date("2025-06-02"), date("2025-06-03"))), ["2025-06-02", "2025-06-03"]);
+        // Days count calendar days back from the newest row, gaps included
+        assert_eq!(dates(HistoryRange::last_days(3)), ["2025-06-03", "2025-06-05"]);
+        let to = HistoryRange {
+            to: Some(date("2025-06-02")),
+            ..HistoryRange::last_days(7)
+        };
+        assert_eq!(dates(to), ["2025-06-01", "2025-06-02"]);
+
+        assert!(HistoryRange::between(date("2025-06-05"), date("2025-06-01")).validate().is_err());
+        assert!(HistoryRange::last_days(0).validate().is_err());
+        assert!(parse_history_date("from", "06/01/2025").is_err());
+    }
+
+    #[test]
+    fn test_data_freshness() {
+        let history: Vec<MarketHistory> = (1..=40)
+            .filter(|d| !(20..=24).contains(d) && *d != 38)
+            .map(|d| {
+                let date = NaiveDate::from_ymd_opt(2025, 5, 31).unwrap() + Duration::days(d);
+                history_day(&date.to_string(), 10.0, 100)
+            })
+            .collect();
+
+        let freshness = DataFreshness::from_history(&history, 30).unwrap();
+        assert_eq!(freshness.last_data_date, "2025-07-10");
+        assert_eq!(freshness.window_days, 30);
+        assert_eq!((freshness.days_missing, freshness.longest_gap_days), (6, 5));
+
+        let today = parse_history_date("today", "2025-07-14").unwrap();
+        assert_eq!(freshness.days_behind(today), Some(4));
+        let line = format_freshness(&freshness, today);
+        assert!(line.contains("4 days behind"));
+        assert!(line.contains("6 of 30 days missing, longest gap 5 days"));
+
+        assert!(DataFreshness::from_history(&[history_day("yesterday", 10.0, 1)], 30).is_none());
+        assert!(format_freshness(&DataFreshness::default(), today).contains("no dated history"));
+    }
+}
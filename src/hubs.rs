@@ -0,0 +1,289 @@
+//! Trade hub comparison for TraderGrader
+//!
+//! Most trading in New Eden happens at five NPC stations. Comparing an item
+//! across them answers the most common trader questions (where is it cheapest,
+//! where does it sell best, where does it move), so the hub books and
+//! histories are fetched concurrently and reported side by side.
+
+use crate::error::Result;
+use crate::market::MarketClient;
+use crate::orderbook::MarketOrderBook;
+use crate::scan::average_daily_volume;
+use crate::types::{HubComparison, HubQuote, MarketHistory};
+use futures::future;
+use std::fmt;
+
+/// One of the five major NPC trade hubs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TradeHub {
+    /// Jita IV - Moon 4 - Caldari Navy Assembly Plant
+    Jita,
+    /// Amarr VIII (Oris) - Emperor Family Academy
+    Amarr,
+    /// Dodixie IX - Moon 20 - Federation Navy Assembly Plant
+    Dodixie,
+    /// Rens VI - Moon 8 - Brutor Tribe Treasury
+    Rens,
+    /// Hek VIII - Moon 12 - Boundless Creation Factory
+    Hek,
+}
+
+impl TradeHub {
+    /// Every hub, busiest first
+    pub const ALL: [TradeHub; 5] = [Self::Jita, Self::Amarr, Self::Dodixie, Self::Rens, Self::Hek];
+
+    /// Region the hub is in
+    pub fn region_id(&self) -> i32 {
+        match self {
+            Self::Jita => 10000002,    // The Forge
+            Self::Amarr => 10000043,   // Domain
+            Self::Dodixie => 10000032, // Sinq Laison
+            Self::Rens => 10000030,    // Heimatar
+            Self::Hek => 10000042,     // Metropolis
+        }
+    }
+
+    /// Solar system the hub is in
+    pub fn system_id(&self) -> i32 {
+        match self {
+            Self::Jita => 30000142,
+            Self::Amarr => 30002187,
+            Self::Dodixie => 30002659,
+            Self::Rens => 30002510,
+            Self::Hek => 30002053,
+        }
+    }
+
+    /// The hub's trade station
+    pub fn station_id(&self) -> i64 {
+        match self {
+            Self::Jita => 60003760,
+            Self::Amarr => 60008494,
+            Self::Dodixie => 60011866,
+            Self::Rens => 60004588,
+            Self::Hek => 60005686,
+        }
+    }
+}
+
+impl fmt::Display for TradeHub {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Jita => "Jita",
+            Self::Amarr => "Amarr",
+            Self::Dodixie => "Dodixie",
+            Self::Rens => "Rens",
+            Self::Hek => "Hek",
+        };
+        f.write_str(name)
+    }
+}
+
+impl std::str::FromStr for TradeHub {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "jita" => Ok(Self::Jita),
+            "amarr" => Ok(Self::Amarr),
+            "dodixie" => Ok(Self::Dodixie),
+            "rens" => Ok(Self::Rens),
+            "hek" => Ok(Self::Hek),
+            other => Err(format!("Unknown trade hub: {other}")),
+        }
+    }
+}
+
+impl MarketClient {
+    /// Compares one item across trade hubs
+    ///
+    /// Each hub's regional order book and history are fetched concurrently.
+    /// Prices and listed volumes only count orders at the hub station itself;
+    /// traded volume comes from regional history, the finest ESI publishes.
+    /// Hubs that fail to fetch are listed in [`HubComparison::failures`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # use tradergrader::hubs::TradeHub;
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// let comparison = client.compare_trade_hubs(34, &TradeHub::ALL).await?;
+    /// for quote in &comparison.quotes {
+    ///     println!("{}: {:?}", quote.hub, quote.best_sell);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn compare_trade_hubs(&self, type_id: i32, hubs: &[TradeHub]) -> Result<HubComparison> {
+        if hubs.is_empty() {
+            return Err("No trade hubs to compare".into());
+        }
+
+        let outcomes = future::join_all(
+            hubs.iter()
+                .map(|&hub| async move { (hub, self.fetch_hub_quote(hub, type_id).await) }),
+        )
+        .await;
+
+        let mut quotes = Vec::new();
+        let mut failures = Vec::new();
+        for (hub, outcome) in outcomes {
+            match outcome {
+                Ok(quote) => quotes.push(quote),
+                Err(e) => failures.push((hub.to_string(), e.to_string())),
+            }
+        }
+
+        Ok(HubComparison {
+            type_id,
+            type_label: self.type_label(type_id).await,
+            quotes,
+            failures,
+        })
+    }
+
+    /// Fetches one hub's order book and history and summarizes them
+    async fn fetch_hub_quote(&self, hub: TradeHub, type_id: i32) -> Result<HubQuote> {
+        let book = self.fetch_order_book(hub.region_id(), Some(type_id)).await?;
+        let history = self.fetch_market_history(hub.region_id(), type_id).await?;
+        Ok(hub_quote(hub, &book, &history))
+    }
+}
+
+/// Summarizes one hub's station orders and regional history
+fn hub_quote(hub: TradeHub, book: &MarketOrderBook, history: &[MarketHistory]) -> HubQuote {
+    let station_id = hub.station_id();
+    let station = MarketOrderBook::new(book.at_location(station_id).cloned().collect());
+
+    let best_buy = station.best_bid();
+    let best_sell = station.best_ask();
+    let spread = station.spread();
+    let spread_percent = match (spread, best_sell) {
+        (Some(spread), Some(sell)) if sell > 0.0 => Some(spread / sell * 100.0),
+        _ => None,
+    };
+
+    HubQuote {
+        hub: hub.to_string(),
+        region_id: hub.region_id(),
+        station_id,
+        best_buy,
+        best_sell,
+        spread,
+        spread_percent,
+        sell_volume: station.sells().map(|o| o.volume_remain as i64).sum(),
+        buy_volume: station.buys().map(|o| o.volume_remain as i64).sum(),
+        avg_daily_volume: average_daily_volume(history),
+    }
+}
+
+/// Formats a hub comparison as a text table
+pub(crate) fn format_hub_comparison(comparison: &HubComparison) -> String {
+    let price = |p: Option<f64>| p.map(|p| format!("{p:.2}")).unwrap_or_else(|| "-".to_string());
+    let mut report = format!("Trade Hub Comparison for {}:\n", comparison.type_label);
+    for quote in &comparison.quotes {
+        report.push_str(&format!(
+            "\n{}: Buy: {} | Sell: {} | Spread: {}\n\
+            Listed: {} units for sale, {} units wanted | Avg Daily Volume: {:.0} units (region)\n",
+            quote.hub,
+            price(quote.best_buy),
+            price(quote.best_sell),
+            quote
+                .spread_percent
+                .map(|s| format!("{s:.2}%"))
+                .unwrap_or_else(|| "n/a".to_string()),
+            quote.sell_volume,
+            quote.buy_volume,
+            quote.avg_daily_volume,
+        ));
+    }
+
+    let cheapest = comparison
+        .quotes
+        .iter()
+        .filter_map(|q| q.best_sell.map(|p| (q, p)))
+        .min_by(|a, b| a.1.total_cmp(&b.1));
+    let best_bid = comparison
+        .quotes
+        .iter()
+        .filter_map(|q| q.best_buy.map(|p| (q, p)))
+        .max_by(|a, b| a.1.total_cmp(&b.1));
+    if let Some((quote, p)) = cheapest {
+        report.push_str(&format!("\nCheapest to buy: {} at {p:.2} ISK\n", quote.hub));
+    }
+    if let Some((quote, p)) = best_bid {
+        report.push_str(&format!("Best to sell instantly: {} at {p:.2} ISK\n", quote.hub));
+    }
+    if !comparison.failures.is_empty() {
+        let failed: Vec<String> = comparison.failures.iter().map(|(hub, e)| format!("{hub} ({e})")).collect();
+        report.push_str(&format!("\nFailed: {}\n", failed.join(", ")));
+    }
+    report.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::market_order;
+    use crate::types::MarketOrder;
+
+    fn order(is_buy_order: bool, price: f64, location_id: i64) -> MarketOrder {
+        MarketOrder {
+            volume_total: 1000,
+            ..market_order(price as i64, is_buy_order, price).with_location(location_id).with_volume(500)
+        }
+    }
+
+    #[test]
+    fn test_hub_parsing() {
+        assert_eq!("Dodixie".parse::<TradeHub>(), Ok(TradeHub::Dodixie));
+        assert!("Perimeter".parse::<TradeHub>().is_err());
+        assert_eq!(TradeHub::ALL.len(), 5);
+        assert_eq!(TradeHub::Hek.to_string(), "Hek");
+    }
+
+    #[test]
+    fn test_hub_quote_uses_station_orders() {
+        let jita = TradeHub::Jita.station_id();
+        let book = MarketOrderBook::new(vec![
+            order(true, 4.0, jita),
+            order(false, 5.0, jita),
+            order(false, 6.0, jita),
+            // Cheaper, but not at the hub
+            order(false, 3.0, 60000361),
+        ]);
+        let quote = hub_quote(TradeHub::Jita, &book, &[]);
+
+        assert_eq!(quote.best_sell, Some(5.0));
+        assert_eq!(quote.best_buy, Some(4.0));
+        assert_eq!(quote.spread_percent, Some(20.0));
+        assert_eq!((quote.sell_volume, quote.buy_volume), (1000, 500));
+        assert_eq!(quote.avg_daily_volume, 0.0);
+    }
+
+    #[test]
+    fn test_format_hub_comparison() {
+        let jita = hub_quote(
+            TradeHub::Jita,
+            &MarketOrderBook::new(vec![order(false, 5.0, 60003760), order(true, 4.0, 60003760)]),
+            &[],
+        );
+        let amarr = hub_quote(
+            TradeHub::Amarr,
+            &MarketOrderBook::new(vec![order(false, 4.5, 60008494), order(true, 4.2, 60008494)]),
+            &[],
+        );
+        let comparison = HubComparison {
+            type_id: 34,
+            type_label: "Tritanium (34)".to_string(),
+            quotes: vec![jita, amarr],
+            failures: vec![("Hek".to_string(), "timeout".to_string())],
+        };
+        let report = format_hub_comparison(&comparison);
+        assert!(report.contains("Cheapest to buy: Amarr at 4.50 ISK"));
+        assert!(report.contains("Best to sell instantly: Amarr at 4.20 ISK"));
+        assert!(report.contains("Failed: Hek (timeout)"));
+    }
+}
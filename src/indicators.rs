@@ -0,0 +1,290 @@
+//! Technical indicators for TraderGrader price analysis
+//!
+//! Pure calculations over daily average prices, oldest first: simple and
+//! exponential moving averages, Wilder's RSI, MACD, Bollinger bands, return
+//! volatility and average true range. Each indicator returns `None` when there
+//! isn't enough history to compute it.
+
+use crate::returns;
+use crate::types::{MarketHistory, TechnicalIndicators};
+
+/// Period of the relative strength index
+pub const RSI_PERIOD: usize = 14;
+
+/// Fast, slow and signal EMA periods for MACD
+pub const MACD_PERIODS: (usize, usize, usize) = (12, 26, 9);
+
+/// Period and standard deviation multiplier of the Bollinger bands
+pub const BOLLINGER_PERIOD: usize = 20;
+pub const BOLLINGER_STD_DEV: f64 = 2.0;
+
+/// Period of the average true range
+pub const ATR_PERIOD: usize = 14;
+
+/// EVE's market trades every day of the year
+pub const TRADING_DAYS_PER_YEAR: f64 = 365.0;
+
+impl TechnicalIndicators {
+    /// Calculates all indicators from daily history in any order
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::{MarketHistory, TechnicalIndicators};
+    ///
+    /// let history: Vec<MarketHistory> = (1..=28)
+    ///     .map(|day| MarketHistory {
+    ///         average: 100.0 + day as f64,
+    ///         date: format!("2025-05-{day:02}"),
+    ///         highest: 101.0 + day as f64,
+    ///         lowest: 99.0 + day as f64,
+    ///         order_count: 10,
+    ///         volume: 1_000,
+    ///         extra: Default::default(),
+    ///     })
+    ///     .collect();
+    /// let indicators = TechnicalIndicators::from_history(&history);
+    /// assert_eq!(indicators.sma_7, Some(125.0));
+    /// assert!(indicators.sma_30.is_none());
+    /// ```
+    pub fn from_history(history: &[MarketHistory]) -> Self {
+        let mut sorted: Vec<&MarketHistory> = history.iter().collect();
+        sorted.sort_by(|a, b| a.date.cmp(&b.date));
+        let prices: Vec<f64> = sorted.iter().map(|h| h.average).collect();
+        Self::from_prices(&prices)
+    }
+
+    /// Calculates all indicators from prices ordered oldest first
+    pub fn from_prices(prices: &[f64]) -> Self {
+        let (fast, slow, signal) = MACD_PERIODS;
+        let macd = macd(prices, fast, slow, signal);
+        let bands = bollinger_bands(prices, BOLLINGER_PERIOD, BOLLINGER_STD_DEV);
+
+        Self {
+            sma_7: sma(prices, 7),
+            sma_30: sma(prices, 30),
+            ema_7: ema(prices, 7),
+            ema_30: ema(prices, 30),
+            rsi_14: rsi(prices, RSI_PERIOD),
+            macd: macd.map(|m| m.0),
+            macd_signal: macd.map(|m| m.1),
+            macd_histogram: macd.map(|m| m.0 - m.1),
+            bollinger_upper: bands.map(|b| b.0),
+            bollinger_middle: bands.map(|b| b.1),
+            bollinger_lower: bands.map(|b| b.2),
+            bollinger_width: bands.and_then(|(upper, middle, lower)| {
+                (middle != 0.0).then(|| (upper - lower) / middle)
+            }),
+        }
+    }
+}
+
+/// Simple moving average of the last `period` prices
+pub fn sma(prices: &[f64], period: usize) -> Option<f64> {
+    if period == 0 || prices.len() < period {
+        return None;
+    }
+    let window = &prices[prices.len() - period..];
+    Some(window.iter().sum::<f64>() / period as f64)
+}
+
+/// Exponential moving average series, seeded with the SMA of the first `period` prices
+///
+/// The returned series starts at index `period - 1` of the input.
+pub fn ema_series(prices: &[f64], period: usize) -> Vec<f64> {
+    if period == 0 || prices.len() < period {
+        return Vec::new();
+    }
+
+    let alpha = 2.0 / (period as f64 + 1.0);
+    let seed = prices[..period].iter().sum::<f64>() / period as f64;
+    let mut series = Vec::with_capacity(prices.len() - period + 1);
+    series.push(seed);
+    for price in &prices[period..] {
+        let previous = *series.last().expect("series is seeded");
+        series.push(alpha * price + (1.0 - alpha) * previous);
+    }
+    series
+}
+
+/// Latest exponential moving average
+pub fn ema(prices: &[f64], period: usize) -> Option<f64> {
+    ema_series(prices, period).last().copied()
+}
+
+/// Relative strength index using Wilder's smoothing
+///
+/// Needs `period + 1` prices. Returns 100 when there were no losing days.
+pub fn rsi(prices: &[f64], period: usize) -> Option<f64> {
+    if period == 0 || prices.len() <= period {
+        return None;
+    }
+
+    let changes: Vec<f64> = prices.windows(2).map(|w| w[1] - w[0]).collect();
+    let mut avg_gain = changes[..period].iter().map(|c| c.max(0.0)).sum::<f64>() / period as f64;
+    let mut avg_loss = changes[..period].iter().map(|c| (-c).max(0.0)).sum::<f64>() / period as f64;
+
+    for change in &changes[period..] {
+        avg_gain = (avg_gain * (period - 1) as f64 + change.max(0.0)) / period as f64;
+        avg_loss = (avg_loss * (period - 1) as f64 + (-change).max(0.0)) / period as f64;
+    }
+
+    if avg_loss == 0.0 {
+        return Some(if avg_gain == 0.0 { 50.0 } else { 100.0 });
+    }
+    let relative_strength = avg_gain / avg_loss;
+    Some(100.0 - 100.0 / (1.0 + relative_strength))
+}
+
+/// MACD line and signal line
+///
+/// Needs `slow + signal - 1` prices so the signal EMA has a full seed window.
+pub fn macd(prices: &[f64], fast: usize, slow: usize, signal: usize) -> Option<(f64, f64)> {
+    let fast_series = ema_series(prices, fast);
+    let slow_series = ema_series(prices, slow);
+    if slow_series.is_empty() || fast_series.len() < slow_series.len() {
+        return None;
+    }
+
+    // Align the fast series with the slow one, which starts later
+    let offset = fast_series.len() - slow_series.len();
+    let macd_line: Vec<f64> = slow_series
+        .iter()
+        .zip(&fast_series[offset..])
+        .map(|(slow, fast)| fast - slow)
+        .collect();
+
+    let signal_line = ema(&macd_line, signal)?;
+    Some((*macd_line.last()?, signal_line))
+}
+
+/// Bollinger bands (upper, middle, lower) over the last `period` prices
+pub fn bollinger_bands(prices: &[f64], period: usize, std_devs: f64) -> Option<(f64, f64, f64)> {
+    let middle = sma(prices, period)?;
+    let window = &prices[prices.len() - period..];
+    let variance = window.iter().map(|p| (p - middle).powi(2)).sum::<f64>() / period as f64;
+    let spread = variance.sqrt() * std_devs;
+    Some((middle + spread, middle, middle - spread))
+}
+
+/// Annualized volatility of daily log returns, in percent
+///
+/// Needs at least three prices (two returns). Non-positive prices are skipped.
+pub fn annualized_volatility(prices: &[f64]) -> Option<f64> {
+    let deviation = returns::std_dev(&returns::log_returns(prices))?;
+    Some(deviation * TRADING_DAYS_PER_YEAR.sqrt() * 100.0)
+}
+
+/// Average true range over the last `period` days of history ordered oldest first
+///
+/// ESI history has no closing price, so the previous day's average stands in
+/// for the close. Uses as many days as are available up to `period`; needs two.
+pub fn average_true_range(history: &[MarketHistory], period: usize) -> Option<f64> {
+    if period == 0 || history.len() < 2 {
+        return None;
+    }
+
+    let true_ranges: Vec<f64> = history
+        .windows(2)
+        .map(|w| {
+            let (previous, day) = (&w[0], &w[1]);
+            let high_low = day.highest - day.lowest;
+            let high_close = (day.highest - previous.average).abs();
+            let low_close = (day.lowest - previous.average).abs();
+            high_low.max(high_close).max(low_close)
+        })
+        .collect();
+
+    let window = &true_ranges[true_ranges.len().saturating_sub(period)..];
+    Some(window.iter().sum::<f64>() / window.len() as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::history_day;
+
+    #[test]
+    fn test_moving_averages() {
+        let prices = [1.0, 2.0, 3.0, 4.0, 5.0];
+        assert_eq!(sma(&prices, 5), Some(3.0));
+        assert_eq!(sma(&prices, 2), Some(4.5));
+        assert_eq!(sma(&prices, 6), None);
+
+        // Seeded with SMA(3) = 2, then alpha = 0.5
+        assert_eq!(ema_series(&prices, 3), vec![2.0, 3.0, 4.0]);
+        assert_eq!(ema(&prices, 3), Some(4.0));
+        assert_eq!(ema(&[5.0; 10], 4), Some(5.0));
+    }
+
+    #[test]
+    fn test_rsi_extremes() {
+        let rising: Vec<f64> = (0..20).map(|i| i as f64).collect();
+        assert_eq!(rsi(&rising, 14), Some(100.0));
+
+        let falling: Vec<f64> = rising.iter().rev().copied().collect();
+        assert_eq!(rsi(&falling, 14), Some(0.0));
+
+        assert_eq!(rsi(&[1.0; 20], 14), Some(50.0));
+        assert_eq!(rsi(&rising[..14], 14), None);
+    }
+
+    #[test]
+    fn test_rsi_mixed() {
+        // Alternating +2/-1 moves: average gain 1.0, average loss 0.5 -> RS 2
+        let mut prices = vec![100.0];
+        for i in 0..14 {
+            let last = *prices.last().unwrap();
+            prices.push(if i % 2 == 0 { last + 2.0 } else { last - 1.0 });
+        }
+        let value = rsi(&prices, 14).unwrap();
+        assert!((value - 66.666).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_macd_and_bollinger() {
+        let flat = [10.0; 40];
+        assert_eq!(macd(&flat, 12, 26, 9), Some((0.0, 0.0)));
+        assert_eq!(bollinger_bands(&flat, 20, 2.0), Some((10.0, 10.0, 10.0)));
+
+        let rising: Vec<f64> = (0..40).map(|i| 100.0 + i as f64).collect();
+        let (line, _signal) = macd(&rising, 12, 26, 9).unwrap();
+        assert!(line > 0.0);
+        assert!(macd(&rising[..30], 12, 26, 9).is_none());
+
+        let (upper, middle, lower) = bollinger_bands(&[1.0, 3.0], 2, 2.0).unwrap();
+        assert_eq!((upper, middle, lower), (4.0, 2.0, 0.0));
+    }
+
+    #[test]
+    fn test_annualized_volatility() {
+        assert_eq!(annualized_volatility(&[10.0; 30]), Some(0.0));
+        assert_eq!(annualized_volatility(&[10.0, 11.0]), None);
+
+        // Alternating ±10% moves are far more volatile than ±1% moves
+        let wild: Vec<f64> = (0..30).map(|i| if i % 2 == 0 { 100.0 } else { 110.0 }).collect();
+        let calm: Vec<f64> = (0..30).map(|i| if i % 2 == 0 { 100.0 } else { 101.0 }).collect();
+        assert!(annualized_volatility(&wild).unwrap() > 5.0 * annualized_volatility(&calm).unwrap());
+    }
+
+    #[test]
+    fn test_average_true_range() {
+        let day = |average: f64, highest: f64, lowest: f64| history_day("", average, 1).with_range(lowest, highest);
+
+        // Gap up: the true range reaches back to the previous average
+        let history = vec![day(100.0, 101.0, 99.0), day(110.0, 112.0, 108.0), day(110.0, 111.0, 109.0)];
+        assert_eq!(average_true_range(&history, 14), Some((12.0 + 2.0) / 2.0));
+        assert_eq!(average_true_range(&history, 1), Some(2.0));
+        assert_eq!(average_true_range(&history[..1], 14), None);
+    }
+
+    #[test]
+    fn test_indicators_short_history() {
+        let indicators = TechnicalIndicators::from_prices(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0]);
+        assert_eq!(indicators.sma_7, Some(4.0));
+        assert!(indicators.sma_30.is_none());
+        assert!(indicators.rsi_14.is_none());
+        assert!(indicators.macd.is_none());
+        assert!(indicators.bollinger_width.is_none());
+    }
+}
@@ -0,0 +1,638 @@
+//! Manufacturing profitability for TraderGrader
+//!
+//! Whether to build an item or buy it comes down to what its materials cost
+//! at a hub, how much of them the blueprint's material efficiency (ME) saves,
+//! and the installation fee, which is charged on the materials' estimated
+//! value at the system's manufacturing cost index from ESI
+//! `/industry/systems/`. Blueprint materials only ship in the Static Data
+//! Export, so this needs a local SDE (`TRADERGRADER_SDE_PATH`). Profit is
+//! after the sales tax and broker fee of selling the product, priced from the
+//! job's [`TradingProfile`].
+
+use crate::cache::CacheKey;
+use crate::error::{Result, TraderGraderError};
+use crate::fees::TradingProfile;
+use crate::hubs::TradeHub;
+use crate::market::MarketClient;
+use crate::orderbook::MarketOrderBook;
+use crate::scan::SCAN_CONCURRENCY;
+use crate::sde::SdeBlueprint;
+use crate::types::{IndustrySystem, ManufacturingMaterial, ManufacturingProfit, MarketPrice, PriceBasis};
+use futures::stream::{self, StreamExt};
+use std::collections::HashMap;
+
+/// Highest material efficiency a blueprint can be researched to
+pub const MAX_MATERIAL_EFFICIENCY: i32 = 10;
+
+/// Highest time efficiency a blueprint can be researched to
+pub const MAX_TIME_EFFICIENCY: i32 = 20;
+
+/// Most runs priced in one job
+pub const MAX_JOB_RUNS: i64 = 100_000;
+
+/// SCC surcharge added to every job fee, as a percentage of the estimated item value
+pub const SCC_SURCHARGE_PERCENT: f64 = 4.0;
+
+/// Facility tax of NPC stations, as a percentage of the estimated item value
+pub const DEFAULT_FACILITY_TAX_PERCENT: f64 = 0.25;
+
+/// Cost index activity name ESI uses for manufacturing
+const MANUFACTURING_ACTIVITY: &str = "manufacturing";
+
+/// How a manufacturing job is run and priced
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ManufacturingJob {
+    pub runs: i64,
+    /// Blueprint material efficiency, 0 to 10
+    pub material_efficiency: i32,
+    /// Blueprint time efficiency, 0 to 20
+    pub time_efficiency: i32,
+    /// Hub materials are bought and the product is sold at
+    pub hub: TradeHub,
+    /// Solar system the job is installed in; the hub's system when `None`
+    pub system_id: Option<i32>,
+    /// Facility tax as a percentage of the estimated item value
+    pub facility_tax_percent: f64,
+    /// Side of the book materials are bought from
+    pub input_basis: PriceBasis,
+    /// Side of the book the product is sold into
+    pub product_basis: PriceBasis,
+    /// Skills, standings and venue the product's selling fees are priced for
+    pub trading: TradingProfile,
+}
+
+impl Default for ManufacturingJob {
+    /// One run of an unresearched blueprint in Jita, buying from and selling to sell orders
+    fn default() -> Self {
+        Self {
+            runs: 1,
+            material_efficiency: 0,
+            time_efficiency: 0,
+            hub: TradeHub::Jita,
+            system_id: None,
+            facility_tax_percent: DEFAULT_FACILITY_TAX_PERCENT,
+            input_basis: PriceBasis::Sell,
+            product_basis: PriceBasis::Sell,
+            trading: TradingProfile::default(),
+        }
+    }
+}
+
+impl ManufacturingJob {
+    /// Solar system the job is installed in
+    pub fn system_id(&self) -> i32 {
+        self.system_id.unwrap_or_else(|| self.hub.system_id())
+    }
+
+    /// Checks runs, efficiencies and tax are in range
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |field: &str, reason: String| TraderGraderError::InvalidArgument {
+            field: field.to_string(),
+            reason,
+        };
+        if !(1..=MAX_JOB_RUNS).contains(&self.runs) {
+            return Err(invalid("runs", format!("must be between 1 and {MAX_JOB_RUNS}")));
+        }
+        if !(0..=MAX_MATERIAL_EFFICIENCY).contains(&self.material_efficiency) {
+            return Err(invalid("material_efficiency", format!("must be between 0 and {MAX_MATERIAL_EFFICIENCY}")));
+        }
+        if !(0..=MAX_TIME_EFFICIENCY).contains(&self.time_efficiency) {
+            return Err(invalid("time_efficiency", format!("must be between 0 and {MAX_TIME_EFFICIENCY}")));
+        }
+        if !(0.0..100.0).contains(&self.facility_tax_percent) {
+            return Err(invalid("facility_tax_percent", "must be at least 0 and below 100".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Quantity of a material needed for `runs` runs at a material efficiency level
+///
+/// Each run needs at least one unit, whatever the efficiency.
+///
+/// # Examples
+///
+/// ```
+/// use tradergrader::industry::material_quantity;
+///
+/// assert_eq!(material_quantity(32_000, 1, 10), 28_800);
+/// assert_eq!(material_quantity(3, 10, 10), 27);
+/// // ME can't take a single unit per run below one
+/// assert_eq!(material_quantity(1, 10, 10), 10);
+/// ```
+pub fn material_quantity(base_quantity: i64, runs: i64, material_efficiency: i32) -> i64 {
+    let reduced = base_quantity as f64 * runs as f64 * (1.0 - material_efficiency as f64 / 100.0);
+    // Rounded to hundredths first, as the game does, so float error can't add a unit
+    let reduced = ((reduced * 100.0).round() / 100.0).ceil() as i64;
+    reduced.max(runs)
+}
+
+/// Duration of `runs` runs in seconds at a time efficiency level
+///
+/// # Examples
+///
+/// ```
+/// use tradergrader::industry::build_time;
+///
+/// assert_eq!(build_time(6_000, 10, 20), 48_000);
+/// ```
+pub fn build_time(base_time: i64, runs: i64, time_efficiency: i32) -> i64 {
+    (base_time as f64 * (1.0 - time_efficiency as f64 / 100.0)).ceil() as i64 * runs
+}
+
+/// Manufacturing cost index of a system, if ESI lists one
+fn manufacturing_cost_index(systems: &[IndustrySystem], system_id: i32) -> Option<f64> {
+    systems
+        .iter()
+        .find(|s| s.solar_system_id == system_id)?
+        .cost_indices
+        .iter()
+        .find(|c| c.activity == MANUFACTURING_ACTIVITY)
+        .map(|c| c.cost_index)
+}
+
+/// Best price at one station on one side of the book
+pub(crate) fn station_price(book: &MarketOrderBook, station_id: i64, basis: PriceBasis) -> Option<f64> {
+    let orders = book.at_location(station_id);
+    match basis {
+        PriceBasis::Buy => orders.filter(|o| o.is_buy_order).map(|o| o.price).reduce(f64::max),
+        PriceBasis::Sell => orders.filter(|o| !o.is_buy_order).map(|o| o.price).reduce(f64::min),
+    }
+}
+
+impl ManufacturingProfit {
+    /// Works out the build cost and margin of a job from priced materials
+    ///
+    /// The job fee is the estimated item value (ME 0 materials at CCP's
+    /// adjusted prices) times the system cost index plus the facility tax and
+    /// SCC surcharge. A system ESI lists no cost index for counts as zero.
+    /// Costs are `None` when a material has no price at the hub.
+    pub fn new(
+        blueprint: &SdeBlueprint,
+        product_label: String,
+        job: &ManufacturingJob,
+        materials: Vec<ManufacturingMaterial>,
+        system_cost_index: Option<f64>,
+        product_best_buy: Option<f64>,
+        product_best_sell: Option<f64>,
+    ) -> Self {
+        let units_produced = blueprint.product_quantity * job.runs;
+        let material_cost = materials
+            .iter()
+            .map(ManufacturingMaterial::total_cost)
+            .sum::<Option<f64>>();
+        let estimated_item_value: f64 = materials
+            .iter()
+            .map(|m| m.adjusted_price.unwrap_or(0.0) * (m.base_quantity * job.runs) as f64)
+            .sum();
+        let fee_rate = system_cost_index.unwrap_or(0.0) + (job.facility_tax_percent + SCC_SURCHARGE_PERCENT) / 100.0;
+        let job_cost = estimated_item_value * fee_rate;
+        let build_cost = material_cost.map(|cost| cost + job_cost);
+
+        let product_unit_price = match job.product_basis {
+            PriceBasis::Buy => product_best_buy,
+            PriceBasis::Sell => product_best_sell,
+        };
+        let sale_value = product_unit_price.map(|price| price * units_produced as f64);
+        // Selling to a buy order is instant; a sell order also pays the broker fee
+        let fees = job.trading.current_fees();
+        let skills = &job.trading.skills;
+        let selling_fees = sale_value.map(|value| match job.product_basis {
+            PriceBasis::Buy => fees.sales_tax(value, skills),
+            PriceBasis::Sell => fees.sales_tax(value, skills) + fees.broker_fee(value, skills),
+        });
+        let profit = build_cost
+            .zip(sale_value.zip(selling_fees))
+            .map(|(cost, (value, fees))| value - fees - cost);
+        let margin_percent = profit
+            .zip(sale_value)
+            .filter(|&(_, value)| value > 0.0)
+            .map(|(profit, value)| profit / value * 100.0);
+        let buy_cost = product_best_sell.map(|price| price * units_produced as f64);
+
+        Self {
+            blueprint_type_id: blueprint.blueprint_type_id,
+            product_type_id: blueprint.product_type_id,
+            product_label,
+            hub: job.hub.to_string(),
+            system_id: job.system_id(),
+            runs: job.runs,
+            material_efficiency: job.material_efficiency,
+            time_efficiency: job.time_efficiency,
+            units_produced,
+            build_time_secs: build_time(blueprint.time, job.runs, job.time_efficiency),
+            input_basis: job.input_basis,
+            product_basis: job.product_basis,
+            materials,
+            material_cost,
+            estimated_item_value,
+            system_cost_index,
+            facility_tax_percent: job.facility_tax_percent,
+            job_cost,
+            build_cost,
+            build_cost_per_unit: build_cost.map(|cost| cost / units_produced as f64),
+            product_unit_price,
+            sale_value,
+            selling_fees,
+            profit,
+            margin_percent,
+            buy_cost,
+            build_savings: buy_cost.zip(build_cost).map(|(buy, build)| buy - build),
+        }
+    }
+}
+
+impl MarketClient {
+    /// Fetches the cost indices of every system with industry activity
+    pub async fn fetch_industry_systems(&self) -> Result<Vec<IndustrySystem>> {
+        self.esi().get_cached("/industry/systems/", &CacheKey::industry("systems"), "industry")
+            .await
+    }
+
+    /// The highest buy and lowest sell order of each item at a hub's trade station
+    ///
+    /// Returns the buy and the sell prices; items without orders on a side, or
+    /// whose order book can't be fetched, are missing from that map.
+    pub(crate) async fn station_prices(
+        &self,
+        hub: TradeHub,
+        type_ids: impl IntoIterator<Item = i32>,
+    ) -> (HashMap<i32, f64>, HashMap<i32, f64>) {
+        let quotes: Vec<(i32, Option<f64>, Option<f64>)> = stream::iter(type_ids)
+            .map(|type_id| async move {
+                match self.fetch_order_book(hub.region_id(), Some(type_id)).await {
+                    Ok(book) => (
+                        type_id,
+                        station_price(&book, hub.station_id(), PriceBasis::Buy),
+                        station_price(&book, hub.station_id(), PriceBasis::Sell),
+                    ),
+                    Err(e) => {
+                        tracing::debug!("No {hub} price for type {type_id}: {e}");
+                        (type_id, None, None)
+                    }
+                }
+            })
+            .buffered(SCAN_CONCURRENCY)
+            .collect()
+            .await;
+        let mut buy_prices = HashMap::new();
+        let mut sell_prices = HashMap::new();
+        for (type_id, buy, sell) in quotes {
+            if let Some(price) = buy {
+                buy_prices.insert(type_id, price);
+            }
+            if let Some(price) = sell {
+                sell_prices.insert(type_id, price);
+            }
+        }
+        (buy_prices, sell_prices)
+    }
+
+    /// Fetches CCP's average and adjusted prices for every item
+    pub async fn fetch_market_prices(&self) -> Result<Vec<MarketPrice>> {
+        self.esi().get_cached("/markets/prices/", &CacheKey::industry("prices"), "industry")
+            .await
+    }
+
+    /// Prices a manufacturing job at a trade hub
+    ///
+    /// `type_id` is either the blueprint or the item it builds. Materials come
+    /// from the local SDE; their prices, and the product's, only count orders
+    /// at the hub station.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::sync::Arc;
+    /// # use tradergrader::{MarketClient, Result};
+    /// # use tradergrader::industry::ManufacturingJob;
+    /// # use tradergrader::sde::StaticData;
+    /// # async fn example() -> Result<()> {
+    /// let sde = StaticData::load("/data/sde")?;
+    /// let client = MarketClient::new().with_static_data(Arc::new(sde));
+    /// let job = ManufacturingJob { runs: 10, material_efficiency: 10, ..ManufacturingJob::default() };
+    /// let profit = client.manufacturing_profit(587, &job).await?;
+    /// println!("{:?} ISK profit", profit.profit);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn manufacturing_profit(&self, type_id: i32, job: &ManufacturingJob) -> Result<ManufacturingProfit> {
+        job.validate()?;
+        let sde = self.static_data().ok_or_else(|| {
+            TraderGraderError::InternalError(
+                "Blueprint materials come from the Static Data Export; set TRADERGRADER_SDE_PATH to a local SDE"
+                    .to_string(),
+            )
+        })?;
+        let blueprint = sde
+            .get_blueprint(type_id)
+            .or_else(|| sde.blueprint_for_product(type_id))
+            .cloned()
+            .ok_or_else(|| TraderGraderError::InvalidArgument {
+                field: "type_id".to_string(),
+                reason: format!("{type_id} is neither a blueprint nor an item with a manufacturing blueprint in the SDE"),
+            })?;
+
+        let system_cost_index = manufacturing_cost_index(&self.fetch_industry_systems().await?, job.system_id());
+        let adjusted_prices: HashMap<i32, f64> = self
+            .fetch_market_prices()
+            .await?
+            .into_iter()
+            .filter_map(|p| Some((p.type_id, p.adjusted_price?)))
+            .collect();
+        let adjusted_prices = &adjusted_prices;
+        let region_id = job.hub.region_id();
+        let station_id = job.hub.station_id();
+
+        let materials = stream::iter(blueprint.materials.iter().copied())
+            .map(|(material_type_id, base_quantity)| async move {
+                let book = self.fetch_order_book(region_id, Some(material_type_id)).await?;
+                Ok(ManufacturingMaterial {
+                    type_id: material_type_id,
+                    type_label: self.type_label(material_type_id).await,
+                    base_quantity,
+                    quantity: material_quantity(base_quantity, job.runs, job.material_efficiency),
+                    unit_price: station_price(&book, station_id, job.input_basis),
+                    adjusted_price: adjusted_prices.get(&material_type_id).copied(),
+                })
+            })
+            .buffered(SCAN_CONCURRENCY)
+            .collect::<Vec<Result<ManufacturingMaterial>>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>>>()?;
+
+        let product_book = self.fetch_order_book(region_id, Some(blueprint.product_type_id)).await?;
+        Ok(ManufacturingProfit::new(
+            &blueprint,
+            self.type_label(blueprint.product_type_id).await,
+            job,
+            materials,
+            system_cost_index,
+            station_price(&product_book, station_id, PriceBasis::Buy),
+            station_price(&product_book, station_id, PriceBasis::Sell),
+        ))
+    }
+
+    /// Generates a formatted build-vs-buy report for a manufacturing job
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # use tradergrader::industry::ManufacturingJob;
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// println!("{}", client.get_manufacturing_profit_summary(587, &ManufacturingJob::default()).await?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_manufacturing_profit_summary(&self, type_id: i32, job: &ManufacturingJob) -> Result<String> {
+        Ok(format_manufacturing_profit(&self.manufacturing_profit(type_id, job).await?))
+    }
+}
+
+/// Formats a duration in seconds as days, hours and minutes
+fn format_build_time(secs: i64) -> String {
+    let (days, hours, minutes) = (secs / 86_400, secs % 86_400 / 3_600, secs % 3_600 / 60);
+    match (days, hours) {
+        (0, 0) => format!("{minutes}m"),
+        (0, _) => format!("{hours}h {minutes}m"),
+        _ => format!("{days}d {hours}h {minutes}m"),
+    }
+}
+
+/// Formats a manufacturing job's costs, materials and margin
+fn format_manufacturing_profit(p: &ManufacturingProfit) -> String {
+    let isk = |value: Option<f64>| value.map_or_else(|| "-".to_string(), |v| format!("{v:.2}"));
+    let side = |basis: PriceBasis| match basis {
+        PriceBasis::Buy => "highest buy order",
+        PriceBasis::Sell => "lowest sell order",
+    };
+    let cost_index = match p.system_cost_index {
+        Some(index) => format!("cost index {:.2}%", index * 100.0),
+        None => "no published cost index".to_string(),
+    };
+    let mut report = format!(
+        "Manufacturing Profit for {} at {}:\n\
+         Blueprint {}: {} runs at ME {} / TE {} make {} units in {}\n\
+         Installed in system {}: {cost_index}, facility tax {:.2}%, SCC surcharge {SCC_SURCHARGE_PERCENT:.1}%\n\n\
+         Materials, priced at the hub's {}:\n\n\
+         | Material | Quantity | Unit price | Total |\n\
+         |---|---|---|---|\n",
+        p.product_label,
+        p.hub,
+        p.blueprint_type_id,
+        p.runs,
+        p.material_efficiency,
+        p.time_efficiency,
+        p.units_produced,
+        format_build_time(p.build_time_secs),
+        p.system_id,
+        p.facility_tax_percent,
+        side(p.input_basis)
+    );
+    for m in &p.materials {
+        report.push_str(&format!(
+            "| {} | {} | {} | {} |\n",
+            m.type_label,
+            m.quantity,
+            isk(m.unit_price),
+            isk(m.total_cost())
+        ));
+    }
+
+    report.push_str(&format!(
+        "\nMaterials: {} ISK\nJob fee: {:.2} ISK (on an estimated item value of {:.2} ISK)\n\
+         Build cost: {} ISK ({} ISK per unit)\n",
+        isk(p.material_cost),
+        p.job_cost,
+        p.estimated_item_value,
+        isk(p.build_cost),
+        isk(p.build_cost_per_unit)
+    ));
+    if p.material_cost.is_none() {
+        let unpriced: Vec<&str> = p
+            .materials
+            .iter()
+            .filter(|m| m.unit_price.is_none())
+            .map(|m| m.type_label.as_str())
+            .collect();
+        report.push_str(&format!("No hub price for: {}\n", unpriced.join(", ")));
+    }
+
+    report.push_str(&format!(
+        "\nSale value at the {}: {} ISK\nSelling fees: {} ISK ({})\n",
+        side(p.product_basis),
+        isk(p.sale_value),
+        isk(p.selling_fees),
+        match p.product_basis {
+            PriceBasis::Buy => "sales tax",
+            PriceBasis::Sell => "sales tax and broker fee",
+        }
+    ));
+    if let Some(profit) = p.profit {
+        let margin = p.margin_percent.map(|m| format!(" ({m:+.1}% margin)")).unwrap_or_default();
+        report.push_str(&format!("Profit: {profit:+.2} ISK{margin}, after selling fees\n"));
+    }
+    if let (Some(buy_cost), Some(savings)) = (p.buy_cost, p.build_savings) {
+        report.push_str(&format!(
+            "Buying the {} units at the lowest sell instead: {buy_cost:.2} ISK, so building {} {:.2} ISK\n",
+            p.units_produced,
+            if savings >= 0.0 { "saves" } else { "costs an extra" },
+            savings.abs()
+        ));
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::market_order;
+    use crate::types::IndustryCostIndex;
+
+    fn rifter_blueprint() -> SdeBlueprint {
+        SdeBlueprint {
+            blueprint_type_id: 691,
+            product_type_id: 587,
+            product_quantity: 1,
+            time: 6000,
+            materials: vec![(34, 1000), (35, 100)],
+        }
+    }
+
+    fn material(type_id: i32, base_quantity: i64, job: &ManufacturingJob, unit_price: Option<f64>) -> ManufacturingMaterial {
+        ManufacturingMaterial {
+            type_id,
+            type_label: format!("type {type_id}"),
+            base_quantity,
+            quantity: material_quantity(base_quantity, job.runs, job.material_efficiency),
+            unit_price,
+            adjusted_price: unit_price,
+        }
+    }
+
+    #[test]
+    fn test_material_and_time_efficiency() {
+        assert_eq!(material_quantity(1000, 1, 0), 1000);
+        assert_eq!(material_quantity(1000, 3, 10), 2700);
+        // 0.9 × 5 runs of 7 is 31.5, rounded up
+        assert_eq!(material_quantity(7, 5, 10), 32);
+        assert_eq!(material_quantity(1, 5, 10), 5);
+        assert_eq!(build_time(6000, 2, 0), 12_000);
+        assert_eq!(build_time(6001, 1, 20), 4801);
+        assert_eq!(format_build_time(48_000), "13h 20m");
+        assert_eq!(format_build_time(90_060), "1d 1h 1m");
+    }
+
+    #[test]
+    fn test_build_cost_and_margin() {
+        let job = ManufacturingJob {
+            runs: 2,
+            material_efficiency: 10,
+            ..ManufacturingJob::default()
+        };
+        let materials = vec![material(34, 1000, &job, Some(5.0)), material(35, 100, &job, Some(20.0))];
+        let profit = ManufacturingProfit::new(
+            &rifter_blueprint(),
+            "Rifter (587)".to_string(),
+            &job,
+            materials,
+            Some(0.05),
+            Some(14_000.0),
+            Some(16_000.0),
+        );
+
+        // 1800 × 5 + 180 × 20
+        assert_eq!(profit.material_cost, Some(12_600.0));
+        // ME 0 quantities for two runs at adjusted prices: 2000 × 5 + 200 × 20
+        assert_eq!(profit.estimated_item_value, 14_000.0);
+        let job_cost = 14_000.0 * (0.05 + 0.0425);
+        assert!((profit.job_cost - job_cost).abs() < 1e-6);
+        let build_cost = 12_600.0 + job_cost;
+        assert!((profit.build_cost.unwrap() - build_cost).abs() < 1e-6);
+        assert!((profit.build_cost_per_unit.unwrap() - build_cost / 2.0).abs() < 1e-6);
+        assert_eq!(profit.sale_value, Some(32_000.0));
+        // Untrained: 7.5% sales tax and a 3% broker fee on the sell order
+        let selling_fees = 32_000.0 * 0.075 + 32_000.0 * 0.03;
+        assert!((profit.selling_fees.unwrap() - selling_fees).abs() < 1e-6);
+        assert!((profit.profit.unwrap() - (32_000.0 - selling_fees - build_cost)).abs() < 1e-6);
+        assert!((profit.build_savings.unwrap() - (32_000.0 - build_cost)).abs() < 1e-6);
+        assert_eq!(profit.build_time_secs, 12_000);
+        assert_eq!(profit.system_id, 30000142);
+
+        let text = format_manufacturing_profit(&profit);
+        assert!(text.contains("2 runs at ME 10 / TE 0 make 2 units in 3h 20m"));
+        assert!(text.contains("| type 34 | 1800 | 5.00 | 9000.00 |"));
+        assert!(text.contains("Selling fees: 3360.00 ISK (sales tax and broker fee)"));
+        assert!(text.contains("building saves"));
+    }
+
+    #[test]
+    fn test_unpriced_material_leaves_costs_open() {
+        let job = ManufacturingJob {
+            product_basis: PriceBasis::Buy,
+            ..ManufacturingJob::default()
+        };
+        let materials = vec![material(34, 1000, &job, Some(5.0)), material(35, 100, &job, None)];
+        let profit = ManufacturingProfit::new(
+            &rifter_blueprint(),
+            "Rifter (587)".to_string(),
+            &job,
+            materials,
+            None,
+            Some(14_000.0),
+            None,
+        );
+
+        assert_eq!(profit.material_cost, None);
+        assert_eq!(profit.profit, None);
+        assert_eq!(profit.product_unit_price, Some(14_000.0));
+        assert_eq!(profit.buy_cost, None);
+        // Tax and surcharge still apply without a cost index
+        assert!((profit.job_cost - 5000.0 * 0.0425).abs() < 1e-6);
+        let text = format_manufacturing_profit(&profit);
+        assert!(text.contains("no published cost index"));
+        assert!(text.contains("No hub price for: type 35"));
+    }
+
+    #[test]
+    fn test_job_validation_and_cost_index_lookup() {
+        assert!(ManufacturingJob::default().validate().is_ok());
+        for job in [
+            ManufacturingJob { runs: 0, ..ManufacturingJob::default() },
+            ManufacturingJob { material_efficiency: 11, ..ManufacturingJob::default() },
+            ManufacturingJob { time_efficiency: -2, ..ManufacturingJob::default() },
+            ManufacturingJob { facility_tax_percent: 100.0, ..ManufacturingJob::default() },
+        ] {
+            assert!(job.validate().is_err());
+        }
+
+        let systems = vec![IndustrySystem {
+            solar_system_id: 30000142,
+            cost_indices: vec![
+                IndustryCostIndex { activity: "invention".to_string(), cost_index: 0.08 },
+                IndustryCostIndex { activity: "manufacturing".to_string(), cost_index: 0.12 },
+            ],
+        }];
+        assert_eq!(manufacturing_cost_index(&systems, 30000142), Some(0.12));
+        assert_eq!(manufacturing_cost_index(&systems, 30002187), None);
+    }
+
+    #[test]
+    fn test_station_price_ignores_other_locations() {
+        let order = |location_id: i64, is_buy_order: bool, price: f64| {
+            market_order(price as i64, is_buy_order, price).with_location(location_id).with_volume(10)
+        };
+        let book = MarketOrderBook::new(vec![
+            order(60003760, false, 5.0),
+            order(60003760, false, 4.5),
+            order(60003760, true, 4.0),
+            order(1035466617946, false, 3.0),
+        ]);
+        assert_eq!(station_price(&book, 60003760, PriceBasis::Sell), Some(4.5));
+        assert_eq!(station_price(&book, 60003760, PriceBasis::Buy), Some(4.0));
+        assert_eq!(station_price(&book, 60008494, PriceBasis::Sell), None);
+    }
+}
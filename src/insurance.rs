@@ -0,0 +1,259 @@
+//! Ship insurance analysis for TraderGrader
+//!
+//! CCP pays out insurance from a reference price that moves far slower than
+//! the market, so when mineral prices fall a hull can cost less to buy or
+//! build than Platinum insurance pays for losing it. That gap is a floor
+//! under hull prices, a check that loss-mitigation plans price insurance
+//! right, and the classic insurance fraud opportunity. Each level of a ship's
+//! insurance from ESI `/insurance/prices/` is set against the hull's lowest
+//! sell order at a trade hub and, with a local SDE, its blueprint materials
+//! bought there.
+
+use crate::cache::CacheKey;
+use crate::error::{Result, TraderGraderError};
+use crate::hubs::TradeHub;
+use crate::industry::{material_quantity, MAX_MATERIAL_EFFICIENCY};
+use crate::market::MarketClient;
+use crate::types::{InsuranceAnalysis, InsuranceLevelPrice, InsuranceLevelValue, InsurancePrice};
+
+/// The insurance level paying out the most
+pub const PLATINUM: &str = "Platinum";
+
+impl InsuranceAnalysis {
+    /// Sets a ship's insurance levels against its hull price and build cost
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::hubs::TradeHub;
+    /// use tradergrader::types::{InsuranceAnalysis, InsuranceLevelPrice};
+    ///
+    /// let levels = [InsuranceLevelPrice { name: "Platinum".to_string(), cost: 150_000.0, payout: 500_000.0 }];
+    /// let analysis = InsuranceAnalysis::new(587, String::new(), TradeHub::Jita, &levels, Some(300_000.0), None, 0);
+    ///
+    /// assert_eq!(analysis.levels[0].net_payout, 350_000.0);
+    /// assert_eq!(analysis.levels[0].gain_vs_market, Some(50_000.0));
+    /// assert!(analysis.platinum_exceeds_cost);
+    /// ```
+    pub fn new(
+        type_id: i32,
+        type_label: String,
+        hub: TradeHub,
+        levels: &[InsuranceLevelPrice],
+        market_price: Option<f64>,
+        build_cost: Option<f64>,
+        material_efficiency: i32,
+    ) -> Self {
+        let mut levels: Vec<InsuranceLevelValue> = levels
+            .iter()
+            .map(|level| {
+                let net_payout = level.payout - level.cost;
+                InsuranceLevelValue {
+                    name: level.name.clone(),
+                    cost: level.cost,
+                    payout: level.payout,
+                    net_payout,
+                    gain_vs_market: market_price.map(|price| net_payout - price),
+                    gain_vs_build: build_cost.map(|cost| net_payout - cost),
+                }
+            })
+            .collect();
+        levels.sort_by(|a, b| a.cost.total_cmp(&b.cost));
+
+        let platinum_exceeds_cost = levels.iter().any(|level| {
+            level.name == PLATINUM
+                && (level.gain_vs_market.is_some_and(|gain| gain > 0.0)
+                    || level.gain_vs_build.is_some_and(|gain| gain > 0.0))
+        });
+        Self {
+            type_id,
+            type_label,
+            hub: hub.to_string(),
+            market_price,
+            build_cost,
+            material_efficiency,
+            levels,
+            platinum_exceeds_cost,
+        }
+    }
+}
+
+impl MarketClient {
+    /// Fetches the insurance levels of every ship
+    pub async fn fetch_insurance_prices(&self) -> Result<Vec<InsurancePrice>> {
+        self.esi().get_cached("/insurance/prices/", &CacheKey::insurance_prices(), "insurance")
+            .await
+    }
+
+    /// Compares a ship's insurance payouts with buying or building its hull at a trade hub
+    ///
+    /// Only orders at the hub's trade station count. The build cost needs a
+    /// local SDE for the blueprint and leaves out the job installation fee.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # use tradergrader::hubs::TradeHub;
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// // Rifter
+    /// let analysis = client.insurance_analysis(587, TradeHub::Jita, 10).await?;
+    /// if analysis.platinum_exceeds_cost {
+    ///     println!("Platinum insurance pays more than a {} costs", analysis.type_label);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn insurance_analysis(
+        &self,
+        type_id: i32,
+        hub: TradeHub,
+        material_efficiency: i32,
+    ) -> Result<InsuranceAnalysis> {
+        if !(0..=MAX_MATERIAL_EFFICIENCY).contains(&material_efficiency) {
+            return Err(TraderGraderError::InvalidArgument {
+                field: "material_efficiency".to_string(),
+                reason: format!("must be between 0 and {MAX_MATERIAL_EFFICIENCY}"),
+            });
+        }
+        let insurance = self.fetch_insurance_prices().await?;
+        let Some(price) = insurance.iter().find(|price| price.type_id == type_id) else {
+            return Err(TraderGraderError::InvalidArgument {
+                field: "type_id".to_string(),
+                reason: format!("type {type_id} can't be insured; only ships can"),
+            });
+        };
+
+        let blueprint = self.static_data().and_then(|sde| sde.blueprint_for_product(type_id));
+        let mut type_ids = vec![type_id];
+        if let Some(blueprint) = blueprint {
+            type_ids.extend(blueprint.materials.iter().map(|(material, _)| *material));
+        }
+        let (_, sell_prices) = self.station_prices(hub, type_ids).await;
+        let build_cost = blueprint.and_then(|blueprint| {
+            blueprint
+                .materials
+                .iter()
+                .map(|&(material, quantity)| {
+                    sell_prices
+                        .get(&material)
+                        .map(|price| price * material_quantity(quantity, 1, material_efficiency) as f64)
+                })
+                .sum::<Option<f64>>()
+                .map(|cost| cost / blueprint.product_quantity.max(1) as f64)
+        });
+
+        Ok(InsuranceAnalysis::new(
+            type_id,
+            self.type_label(type_id).await,
+            hub,
+            &price.levels,
+            sell_prices.get(&type_id).copied(),
+            build_cost,
+            material_efficiency,
+        ))
+    }
+}
+
+/// Formats a ship's insurance levels against its hull cost
+pub(crate) fn format_insurance_analysis(analysis: &InsuranceAnalysis) -> String {
+    let isk = |value: Option<f64>| value.map_or_else(|| "-".to_string(), |v| format!("{v:.2}"));
+    let mut report = format!(
+        "Insurance for {} at {}\n\nHull at the lowest sell order: {}\n\
+         Build cost at ME {} (materials at sell orders): {}\n\n\
+         | Level | Premium | Payout | Net payout | Gain vs market | Gain vs build |\n\
+         |---|---|---|---|---|---|\n",
+        analysis.type_label,
+        analysis.hub,
+        isk(analysis.market_price),
+        analysis.material_efficiency,
+        isk(analysis.build_cost)
+    );
+    for level in &analysis.levels {
+        report.push_str(&format!(
+            "| {} | {:.2} | {:.2} | {:.2} | {} | {} |\n",
+            level.name,
+            level.cost,
+            level.payout,
+            level.net_payout,
+            isk(level.gain_vs_market),
+            isk(level.gain_vs_build)
+        ));
+    }
+
+    if analysis.platinum_exceeds_cost {
+        report.push_str(
+            "\n⚠️ Platinum insurance pays out more than the hull costs: losing an insured hull is profitable, \
+             which props up its price.\n",
+        );
+    } else if analysis.market_price.is_some() || analysis.build_cost.is_some() {
+        report.push_str("\nPlatinum insurance pays out less than the hull costs.\n");
+    } else {
+        report.push_str(&format!("\nThe hull has no sell orders at {}, so it can't be compared.\n", analysis.hub));
+    }
+    if analysis.build_cost.is_none() {
+        report.push_str(
+            "\nNo build cost: it needs TRADERGRADER_SDE_PATH for the blueprint and sell orders for every material.\n",
+        );
+    } else {
+        report.push_str("\nJob installation fees aren't included in the build cost.\n");
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn levels() -> Vec<InsuranceLevelPrice> {
+        [("Platinum", 150_000.0, 500_000.0), ("Basic", 20_000.0, 200_000.0)]
+            .into_iter()
+            .map(|(name, cost, payout)| InsuranceLevelPrice {
+                name: name.to_string(),
+                cost,
+                payout,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_analysis() {
+        let analysis =
+            InsuranceAnalysis::new(587, String::new(), TradeHub::Jita, &levels(), Some(400_000.0), Some(320_000.0), 10);
+        assert_eq!(analysis.levels.iter().map(|l| l.name.as_str()).collect::<Vec<_>>(), ["Basic", "Platinum"]);
+        assert_eq!(analysis.levels[1].gain_vs_market, Some(-50_000.0));
+        assert_eq!(analysis.levels[1].gain_vs_build, Some(30_000.0));
+        // Building beats the payout even though buying doesn't
+        assert!(analysis.platinum_exceeds_cost);
+
+        let costly = InsuranceAnalysis::new(587, String::new(), TradeHub::Jita, &levels(), Some(400_000.0), None, 0);
+        assert!(!costly.platinum_exceeds_cost);
+        let unpriced = InsuranceAnalysis::new(587, String::new(), TradeHub::Jita, &levels(), None, None, 0);
+        assert!(!unpriced.platinum_exceeds_cost);
+        assert_eq!(unpriced.levels[0].gain_vs_market, None);
+    }
+
+    #[test]
+    fn test_insurance_prices_deserialize_from_esi() {
+        let prices: Vec<InsurancePrice> = serde_json::from_str(
+            r#"[{"levels": [{"cost": 6525.34, "name": "Basic", "payout": 21751.14},
+                            {"cost": 39152.04, "name": "Platinum", "payout": 130506.79}], "type_id": 587}]"#,
+        )
+        .unwrap();
+        assert_eq!(prices[0].type_id, 587);
+        assert_eq!(prices[0].levels[1].name, PLATINUM);
+        assert_eq!(prices[0].levels[1].payout, 130506.79);
+    }
+
+    #[test]
+    fn test_format() {
+        let analysis =
+            InsuranceAnalysis::new(587, "Rifter (587)".to_string(), TradeHub::Jita, &levels(), Some(300_000.0), None, 0);
+        let report = format_insurance_analysis(&analysis);
+        assert!(report.starts_with("Insurance for Rifter (587) at Jita"));
+        assert!(report.contains("| Platinum | 150000.00 | 500000.00 | 350000.00 | 50000.00 | - |"));
+        assert!(report.contains("losing an insured hull is profitable"));
+        assert!(report.contains("needs TRADERGRADER_SDE_PATH"));
+    }
+}
@@ -0,0 +1,451 @@
+//! Trade journal for TraderGrader
+//!
+//! The portfolio says what stock is worth; the journal says what trading has
+//! actually earned. A [`TradeJournal`] keeps executed buys and sells in a JSON
+//! file, entered by hand or imported from a character's wallet transactions,
+//! and [`TradeReport`] replays them per item to find realized profit after
+//! fees, ISK per hour and how often a sale made money.
+//!
+//! Sold units are costed at the average price of the units held when they
+//! were sold, the same pooling the portfolio uses.
+
+use crate::error::{Result, TraderGraderError};
+use crate::fees::{FeeSchedules, TradingSkills};
+use crate::market::MarketClient;
+use crate::types::{ItemTradeStats, JournalTrade, TradeReport, TradeSide, WalletTransaction};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Most trades the journal keeps; the oldest are dropped beyond this
+pub const MAX_JOURNAL_TRADES: usize = 20_000;
+
+/// Shortest trading span ISK per hour is reported for, in hours
+const MIN_RATE_HOURS: f64 = 1.0;
+
+impl JournalTrade {
+    /// A trade entered by hand, checked for sensible values
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::{JournalTrade, TradeSide};
+    ///
+    /// let trade = JournalTrade::new("2025-06-01T12:00:00Z", 34, TradeSide::Buy, 1_000, 5.0, 12.5)?;
+    /// assert_eq!(trade.transaction_id, None);
+    /// assert!(JournalTrade::new("yesterday", 34, TradeSide::Buy, 1_000, 5.0, 0.0).is_err());
+    /// # Ok::<(), tradergrader::TraderGraderError>(())
+    /// ```
+    pub fn new(date: &str, type_id: i32, side: TradeSide, quantity: i64, unit_price: f64, fees: f64) -> Result<Self> {
+        let invalid = |field: &str, reason: &str| {
+            Err(TraderGraderError::InvalidArgument {
+                field: field.to_string(),
+                reason: reason.to_string(),
+            })
+        };
+        let date = match DateTime::parse_from_rfc3339(date.trim()) {
+            Ok(date) => date.with_timezone(&Utc).to_rfc3339(),
+            Err(_) => return invalid("date", "must be an RFC 3339 timestamp such as 2025-06-01T12:00:00Z"),
+        };
+        if type_id <= 0 {
+            return Err(TraderGraderError::InvalidTypeId { type_id });
+        }
+        if quantity <= 0 {
+            return invalid("quantity", "must be positive");
+        }
+        if !unit_price.is_finite() || unit_price <= 0.0 {
+            return invalid("unit_price", "must be positive");
+        }
+        if !fees.is_finite() || fees < 0.0 {
+            return invalid("fees", "must be zero or more");
+        }
+        Ok(Self {
+            date,
+            type_id,
+            side,
+            quantity,
+            unit_price,
+            fees,
+            transaction_id: None,
+        })
+    }
+
+    /// A trade from a wallet transaction, with sales tax on sells estimated
+    /// from the fee schedule in effect that day
+    ///
+    /// Wallet transactions don't say which order they filled, so broker fees
+    /// can't be recovered and are left out.
+    pub fn from_wallet_transaction(
+        transaction: &WalletTransaction,
+        schedules: &FeeSchedules,
+        skills: &TradingSkills,
+    ) -> Result<Self> {
+        let side = if transaction.is_buy { TradeSide::Buy } else { TradeSide::Sell };
+        let mut trade = Self::new(
+            &transaction.date,
+            transaction.type_id,
+            side,
+            transaction.quantity as i64,
+            transaction.unit_price,
+            0.0,
+        )?;
+        if side == TradeSide::Sell {
+            let day = DateTime::parse_from_rfc3339(&trade.date).map(|d| d.date_naive()).unwrap_or_default();
+            trade.fees = schedules.for_date(day).sales_tax(trade.value(), skills);
+        }
+        trade.transaction_id = Some(transaction.transaction_id);
+        Ok(trade)
+    }
+
+    /// Quantity × unit price, before fees
+    pub fn value(&self) -> f64 {
+        self.quantity as f64 * self.unit_price
+    }
+}
+
+/// The server's trade journal, optionally saved to a JSON file
+///
+/// Every change is written straight to the file, so trades survive restarts.
+#[derive(Debug, Default)]
+pub struct TradeJournal {
+    path: Option<PathBuf>,
+    trades: Mutex<Vec<JournalTrade>>,
+}
+
+/// File layout of a saved journal
+#[derive(Deserialize, Serialize)]
+struct SavedJournal {
+    trades: Vec<JournalTrade>,
+}
+
+impl TradeJournal {
+    /// A journal that lives only as long as the process
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Opens the journal saved at `path`, starting empty if the file doesn't exist yet
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let trades = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice::<SavedJournal>(&bytes)?.trades,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => {
+                return Err(TraderGraderError::InternalError(format!(
+                    "Failed to read trade journal {}: {e}",
+                    path.display()
+                )))
+            }
+        };
+        Ok(Self {
+            path: Some(path),
+            trades: Mutex::new(trades),
+        })
+    }
+
+    /// Where the journal is saved, if anywhere
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Every trade, oldest first
+    pub fn trades(&self) -> Vec<JournalTrade> {
+        self.trades.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Records trades, skipping imported ones already in the journal
+    ///
+    /// Returns how many were added.
+    pub fn record(&self, new_trades: impl IntoIterator<Item = JournalTrade>) -> Result<usize> {
+        let mut trades = self.trades.lock().unwrap_or_else(|e| e.into_inner());
+        let mut known: HashSet<i64> = trades.iter().filter_map(|t| t.transaction_id).collect();
+        let mut updated = trades.clone();
+        let before = updated.len();
+        for trade in new_trades {
+            if trade.transaction_id.is_none_or(|id| known.insert(id)) {
+                updated.push(trade);
+            }
+        }
+        let added = updated.len() - before;
+        if added == 0 {
+            return Ok(0);
+        }
+        updated.sort_by(|a, b| a.date.cmp(&b.date));
+        updated.drain(..updated.len().saturating_sub(MAX_JOURNAL_TRADES));
+        self.save(&updated)?;
+        *trades = updated;
+        Ok(added)
+    }
+
+    /// Writes the journal to a temporary file and renames it over the old one
+    fn save(&self, trades: &[JournalTrade]) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let saved = SavedJournal {
+            trades: trades.to_vec(),
+        };
+        let write_error = |e: std::io::Error| {
+            TraderGraderError::InternalError(format!("Failed to save trade journal {}: {e}", path.display()))
+        };
+        let temp = path.with_extension("json.tmp");
+        std::fs::write(&temp, serde_json::to_vec_pretty(&saved)?).map_err(write_error)?;
+        std::fs::rename(&temp, path).map_err(write_error)
+    }
+}
+
+impl TradeReport {
+    /// Replays trades in date order to find each item's realized results
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::{JournalTrade, TradeReport, TradeSide};
+    ///
+    /// let trades = vec![
+    ///     JournalTrade::new("2025-06-01T10:00:00Z", 34, TradeSide::Buy, 1_000, 5.0, 0.0)?,
+    ///     JournalTrade::new("2025-06-01T12:00:00Z", 34, TradeSide::Sell, 1_000, 6.0, 200.0)?,
+    /// ];
+    /// let report = TradeReport::from_trades(&trades);
+    /// assert_eq!(report.total_realized_profit, 800.0);
+    /// assert_eq!(report.isk_per_hour, Some(400.0));
+    /// # Ok::<(), tradergrader::TraderGraderError>(())
+    /// ```
+    pub fn from_trades(trades: &[JournalTrade]) -> Self {
+        let mut ordered: Vec<&JournalTrade> = trades.iter().collect();
+        ordered.sort_by(|a, b| a.date.cmp(&b.date));
+
+        let mut by_item: BTreeMap<i32, Vec<&JournalTrade>> = BTreeMap::new();
+        for trade in &ordered {
+            by_item.entry(trade.type_id).or_default().push(trade);
+        }
+        let mut items: Vec<ItemTradeStats> =
+            by_item.into_iter().map(|(type_id, trades)| item_stats(type_id, &trades)).collect();
+        items.sort_by(|a, b| b.realized_profit.total_cmp(&a.realized_profit));
+
+        let total_realized_profit = items.iter().map(|i| i.realized_profit).sum();
+        let sells: usize = items.iter().map(|i| i.sells).sum();
+        let winning: usize = items.iter().map(|i| i.winning_sells).sum();
+        Self {
+            trades: trades.len(),
+            total_realized_profit,
+            total_fees_paid: items.iter().map(|i| i.fees_paid).sum(),
+            win_rate_percent: (sells > 0).then(|| winning as f64 / sells as f64 * 100.0),
+            isk_per_hour: hourly_rate(total_realized_profit, &ordered),
+            items,
+        }
+    }
+}
+
+/// Realized results for one item's trades, oldest first
+fn item_stats(type_id: i32, trades: &[&JournalTrade]) -> ItemTradeStats {
+    let mut stats = ItemTradeStats {
+        type_id,
+        buys: 0,
+        sells: 0,
+        units_bought: 0,
+        units_sold: 0,
+        realized_profit: 0.0,
+        fees_paid: 0.0,
+        winning_sells: 0,
+        win_rate_percent: None,
+        isk_per_hour: None,
+        open_units: 0,
+        unmatched_units_sold: 0,
+    };
+    // Units held and what they cost, fees on the buys included
+    let (mut held, mut held_cost) = (0i64, 0.0);
+    for trade in trades {
+        stats.fees_paid += trade.fees;
+        match trade.side {
+            TradeSide::Buy => {
+                stats.buys += 1;
+                stats.units_bought += trade.quantity;
+                held += trade.quantity;
+                held_cost += trade.value() + trade.fees;
+            }
+            TradeSide::Sell => {
+                stats.sells += 1;
+                stats.units_sold += trade.quantity;
+                let matched = trade.quantity.min(held);
+                stats.unmatched_units_sold += trade.quantity - matched;
+                let cost = if held > 0 { held_cost * matched as f64 / held as f64 } else { 0.0 };
+                held -= matched;
+                held_cost -= cost;
+
+                // Only the matched part of the sale has a known cost
+                let share = matched as f64 / trade.quantity as f64;
+                let profit = (trade.value() - trade.fees) * share - cost;
+                stats.realized_profit += profit;
+                if matched > 0 && profit > 0.0 {
+                    stats.winning_sells += 1;
+                }
+            }
+        }
+    }
+    stats.open_units = held;
+    stats.win_rate_percent = (stats.sells > 0).then(|| stats.winning_sells as f64 / stats.sells as f64 * 100.0);
+    stats.isk_per_hour = hourly_rate(stats.realized_profit, trades);
+    stats
+}
+
+/// Profit per hour between the first and last of date-ordered trades
+fn hourly_rate(profit: f64, trades: &[&JournalTrade]) -> Option<f64> {
+    let date = |trade: Option<&&JournalTrade>| trade.and_then(|t| DateTime::parse_from_rfc3339(&t.date).ok());
+    let (first, last) = (date(trades.first())?, date(trades.last())?);
+    let hours = (last - first).num_seconds() as f64 / 3600.0;
+    (hours >= MIN_RATE_HOURS).then(|| profit / hours)
+}
+
+impl MarketClient {
+    /// Fetches a character's wallet transactions as journal trades
+    ///
+    /// Sales tax on sells is estimated with the given skills from the fee
+    /// schedule in effect on the day of the sale.
+    pub async fn wallet_trades(&self, character_id: i64, skills: &TradingSkills) -> Result<Vec<JournalTrade>> {
+        let schedules = FeeSchedules::default();
+        self.fetch_wallet_transactions(character_id)
+            .await?
+            .iter()
+            .map(|transaction| JournalTrade::from_wallet_transaction(transaction, &schedules, skills))
+            .collect()
+    }
+
+    /// Generates a formatted trade report with item names
+    pub async fn trade_report_summary(&self, report: &TradeReport) -> String {
+        let ids: Vec<i64> = report.items.iter().map(|i| i.type_id as i64).collect();
+        let names = self.resolve_names(&ids).await.unwrap_or_default();
+        format_trade_report(report, |type_id| match names.get(&(type_id as i64)) {
+            Some(name) => format!("{} ({type_id})", name.name),
+            None => format!("Type {type_id}"),
+        })
+    }
+}
+
+/// Formats a trade report as totals followed by a per-item table
+fn format_trade_report(report: &TradeReport, label: impl Fn(i32) -> String) -> String {
+    if report.trades == 0 {
+        return "The trade journal is empty. Record trades with record_trade.".to_string();
+    }
+    let rate = |rate: Option<f64>| rate.map_or_else(|| "-".to_string(), |r| format!("{r:.0}"));
+    let percent = |p: Option<f64>| p.map_or_else(|| "-".to_string(), |p| format!("{p:.0}%"));
+    let mut text = format!(
+        "Trade Report ({} trades):\nRealized profit: {:+.2} ISK after {:.2} ISK in fees\nWin rate: {}\nISK/hour: {}\n\n\
+         | Item | Buys | Sells | Units bought | Units sold | Realized profit | Fees | Win rate | ISK/hour | Open units |\n\
+         |---|---|---|---|---|---|---|---|---|---|\n",
+        report.trades,
+        report.total_realized_profit,
+        report.total_fees_paid,
+        percent(report.win_rate_percent),
+        rate(report.isk_per_hour)
+    );
+    for item in &report.items {
+        text.push_str(&format!(
+            "| {} | {} | {} | {} | {} | {:+.2} | {:.2} | {} | {} | {} |\n",
+            label(item.type_id),
+            item.buys,
+            item.sells,
+            item.units_bought,
+            item.units_sold,
+            item.realized_profit,
+            item.fees_paid,
+            percent(item.win_rate_percent),
+            rate(item.isk_per_hour),
+            item.open_units
+        ));
+    }
+    let unmatched: i64 = report.items.iter().map(|i| i.unmatched_units_sold).sum();
+    if unmatched > 0 {
+        text.push_str(&format!(
+            "\n{unmatched} units were sold with no recorded purchase and are left out of the profit.\n"
+        ));
+    }
+    text.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(hour: u32, type_id: i32, side: TradeSide, quantity: i64, unit_price: f64, fees: f64) -> JournalTrade {
+        JournalTrade::new(&format!("2025-06-01T{hour:02}:00:00Z"), type_id, side, quantity, unit_price, fees).unwrap()
+    }
+
+    #[test]
+    fn test_realized_profit_uses_average_cost() {
+        let trades = vec![
+            trade(0, 34, TradeSide::Buy, 100, 4.0, 10.0),
+            trade(1, 34, TradeSide::Buy, 100, 6.0, 10.0),
+            // 150 units at an average cost of 5.10 (fees included)
+            trade(2, 34, TradeSide::Sell, 150, 6.0, 15.0),
+            trade(3, 34, TradeSide::Sell, 50, 4.0, 5.0),
+            // Losing sale of another item, partly unmatched
+            trade(0, 35, TradeSide::Buy, 10, 100.0, 0.0),
+            trade(4, 35, TradeSide::Sell, 20, 90.0, 0.0),
+        ];
+        let report = TradeReport::from_trades(&trades);
+
+        let tritanium = &report.items[0];
+        assert_eq!(tritanium.type_id, 34);
+        assert!((tritanium.realized_profit - (900.0 - 15.0 - 765.0 + 200.0 - 5.0 - 255.0)).abs() < 1e-9);
+        assert_eq!(tritanium.fees_paid, 40.0);
+        assert_eq!(tritanium.win_rate_percent, Some(50.0));
+        assert_eq!(tritanium.open_units, 0);
+        assert!((tritanium.isk_per_hour.unwrap() - tritanium.realized_profit / 3.0).abs() < 1e-9);
+
+        let pyerite = &report.items[1];
+        assert_eq!(pyerite.realized_profit, -100.0);
+        assert_eq!(pyerite.unmatched_units_sold, 10);
+        assert!((report.win_rate_percent.unwrap() - 100.0 / 3.0).abs() < 1e-9);
+
+        let text = format_trade_report(&report, |id| format!("Type {id}"));
+        assert!(text.contains("Trade Report (6 trades)"));
+        assert!(text.contains("10 units were sold with no recorded purchase"));
+    }
+
+    #[test]
+    fn test_journal_persists_and_skips_known_transactions() {
+        let path = std::env::temp_dir().join(format!("tradergrader-journal-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let transaction = WalletTransaction {
+            client_id: 1,
+            date: "2025-06-02T08:00:00Z".to_string(),
+            is_buy: false,
+            is_personal: true,
+            journal_ref_id: 7,
+            location_id: 60003760,
+            quantity: 10,
+            transaction_id: 99,
+            type_id: 34,
+            unit_price: 10.0,
+        };
+        let imported =
+            JournalTrade::from_wallet_transaction(&transaction, &FeeSchedules::default(), &TradingSkills::default())
+                .unwrap();
+        assert_eq!(imported.side, TradeSide::Sell);
+        // 7.5% sales tax without Accounting under the 2024 schedule
+        assert!((imported.fees - 7.5).abs() < 1e-9);
+
+        let journal = TradeJournal::open(&path).unwrap();
+        assert_eq!(journal.record([trade(5, 34, TradeSide::Buy, 10, 5.0, 0.0), imported.clone()]).unwrap(), 2);
+        assert_eq!(journal.record([imported]).unwrap(), 0);
+
+        let reopened = TradeJournal::open(&path).unwrap();
+        assert_eq!(reopened.trades().len(), 2);
+        assert_eq!(reopened.trades()[1].transaction_id, Some(99));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_rejects_bad_trades() {
+        assert!(JournalTrade::new("2025-06-01T00:00:00Z", 0, TradeSide::Buy, 1, 1.0, 0.0).is_err());
+        assert!(JournalTrade::new("2025-06-01T00:00:00Z", 34, TradeSide::Buy, 0, 1.0, 0.0).is_err());
+        assert!(JournalTrade::new("2025-06-01T00:00:00Z", 34, TradeSide::Buy, 1, 0.0, 0.0).is_err());
+        assert!(JournalTrade::new("2025-06-01T00:00:00Z", 34, TradeSide::Buy, 1, 1.0, -1.0).is_err());
+        assert_eq!("Sold".parse::<TradeSide>(), Ok(TradeSide::Sell));
+        assert!("hold".parse::<TradeSide>().is_err());
+    }
+}
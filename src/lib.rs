@@ -6,11 +6,15 @@
 //! # Features
 //! 
 //! - Real-time EVE Online market data via ESI API
-//! - Historical price analysis and trend detection
+//! - Historical price analysis, trend detection and technical indicators
 //! - Market opportunity identification
-//! - Caching for optimal performance
+//! - Caching for optimal performance, with optional offline static data from a local SDE
+//! - ESI-compliant rate limiting that can be shared across clients
 //! - Full MCP (Model Context Protocol) compliance
 
+// The tools/list schema is one large json! literal
+#![recursion_limit = "256"]
+
 use serde_json::Value;
 use std::io::{self, BufRead, Write};
 
@@ -22,15 +26,109 @@ pub mod mcp;
 pub mod server;
 pub mod cache;
 pub mod rate_limit;
+pub mod auth;
+pub mod esi;
+pub mod indicators;
+pub mod universe;
+pub mod hauling;
+pub mod fees;
+pub mod scan;
+pub mod orderbook;
+pub mod market_groups;
+pub mod sde;
+pub mod history;
+pub mod hubs;
+pub mod returns;
+pub mod range;
+pub mod trend;
+pub mod watchlist;
+pub mod validation;
+pub mod passthrough;
+pub mod logging;
+pub mod limits;
+pub mod matrix;
+pub mod telemetry;
+pub mod grade;
+pub mod config;
+pub mod export;
+pub mod singleflight;
+pub mod prefetch;
+pub mod alerts;
+pub mod resources;
+pub mod analysis;
+pub mod anomaly;
+pub mod correlation;
+pub mod portfolio;
+pub mod journal;
+pub mod industry;
+pub mod flow;
+pub mod undercut;
+pub mod movers;
+pub mod spread;
+pub mod locations;
+pub mod multi_region;
+pub mod health;
+pub mod status;
+pub mod paging;
+pub mod orders;
+pub mod fill;
+pub mod listing;
+pub mod breakout;
+pub mod backtest;
+pub mod events;
+pub mod speculation;
+pub mod courier;
+pub mod route;
+pub mod snapshot;
+pub mod archive;
+pub mod state;
+pub mod search;
+pub mod plex;
+pub mod mineral_index;
+pub mod fuel;
+pub mod lp;
+pub mod contracts;
+pub mod insurance;
+pub mod sovereignty;
+#[cfg(test)]
+pub(crate) mod test_support;
 
 // Re-export commonly used types
 pub use error::{TraderGraderError, Result};
-pub use types::{MarketOrder, MarketHistory, MarketType, PriceAnalysis};
+pub use types::{
+    AnomalyMetric, AnomalyReport, BacktestExit, BacktestResult, BacktestTrade, Breakout, BreakoutDirection,
+    BreakoutReport, Candle, CharacterOrder, CompressionQuote, ConstellationInfo, ContractItem, ContractScan,
+    ContractValuation, CourierQuote, CourierRouteRate, DataFreshness, DepthBand, DogmaAttributeValue, ExtraFields,
+    FillEstimate, ForecastModel, ForecastPoint, FuelBlockAnalysis, FuelBlockMargin, GradeComponent, HaulingAnalysis,
+    HaulingOpportunity, HistoryStats, HubComparison, HubQuote, IceCompression, IndexComponent, IndexPoint,
+    IndustryCostIndex, IndustrySystem, InjectionBracket, InsuranceAnalysis, InsuranceLevelPrice,
+    InsuranceLevelValue, InsurancePrice, ItemComparison, ItemCorrelation, ItemFlow, ItemMatch, ItemPerformance,
+    ItemSearch, ItemTradeStats, JournalTrade, JumpFreighterProfit, JumpLeg, LiquidityScore, ListingAdvice,
+    LpOfferValue, LpRequiredItem, LpStoreOffer, LpStoreValue, ManufacturingMaterial, ManufacturingProfit,
+    MarketAccess, MarketAnomaly, MarketGroupInfo, MarketHistory, MarketOrder, MarketPrice, MarketScan,
+    MarketSnapshot, MarketType, MineralIndex, ModelForecast, MultiRegionSummary, OrderBookDepth, OrderFilter,
+    OrderListing, OrderSort, OrderType, OrderUndercutStatus, OrderWall, Period, PlexDashboard, PlexMarketItem,
+    PortfolioPosition, PortfolioValuation, Position, PositionValuation, PriceAnalysis, PriceBasis, PriceForecast,
+    PriceLevel, PriceMatrix, PriceMatrixCell, PriceMatrixRow, PriceMover, PriceTrend, PublicContract,
+    RegionActivity, RegionContext, RegionFlowReport, RegionInfo, RegionQuote, RoutePlan, RouteRisk, RouteSegment,
+    RouteSystem, ScanResult, ScanSort, SecurityClass, ServerStatus, SkillExtraction, SnapshotItem, SovereignHolder,
+    SovereigntySystem, SpeculationReaction, SpeculationScreen, SpreadHistory, SpreadPoint, StationInfo,
+    StructureInfo, SystemActivity, SystemInfo, SystemJumps, SystemKills, SystemRisk, TechnicalIndicators,
+    TimeframeTrend, TopMovers, TradeGrade, TradeReport, TradeSide, TrendAgreement, TrendDirection, TrendEvidence,
+    TypeInfo, UndercutEstimate, UndercutSideStats, UniverseName, WalletTransaction, Watchlist, YearOverYear,
+    YearWindow,
+};
 pub use market::MarketClient;
 pub use mcp::McpHandler;
 pub use server::StandaloneMcpServer;
-pub use cache::{CacheKey, CacheItem, CacheBackend, CacheBackendExt, CacheConfig, CacheBackendType, CacheStats, EsiHeaderParser, InMemoryCacheBackend};
-pub use rate_limit::{EsiRateLimiter, RateLimitConfig, EsiRateLimitInfo};
+pub use cache::{
+    CacheKey, CacheItem, CacheBackend, CacheBackendExt, CacheConfig, CacheBackendType, CacheStats, EsiHeaderParser,
+    InMemoryCacheBackend, StaleRead,
+};
+pub use rate_limit::{EndpointClass, ErrorBudgetStatus, EsiRateLimiter, RateLimitConfig, EsiRateLimitInfo, RetryTelemetry};
+pub use auth::{CharacterToken, EveSso, LoginRequest, SsoConfig};
+pub use esi::{DeprecationNotice, EsiClient, EsiConfig, EsiDiagnostics, IpPreference, ProxyConfig};
+pub use trend::TrendConfig;
 
 /// Main TraderGrader application
 #[derive(Debug)]
@@ -68,15 +166,19 @@ impl TraderGraderApplication {
                 Ok(message) => {
                     let response = self.mcp_handler.handle_message(message).await;
                     
-                    // Only send response if it's not null (notifications return null)
+                    // Log notifications go out first, then the response unless it's null (notifications return null)
+                    let mut outgoing = self.mcp_handler.drain_notifications();
                     if !response.is_null() {
-                        let response_str = serde_json::to_string(&response)?;
+                        outgoing.push(response);
+                    }
+                    for message in outgoing {
+                        let response_str = serde_json::to_string(&message)?;
                         writeln!(stdout, "{response_str}")?;
-                        stdout.flush()?;
                     }
+                    stdout.flush()?;
                 }
                 Err(e) => {
-                    eprintln!("Failed to parse message: {e}");
+                    tracing::warn!("Failed to parse message: {e}");
                 }
             }
         }
@@ -0,0 +1,242 @@
+//! Response size guardrails for TraderGrader
+//!
+//! An agent asking for everything at once (a whole region's unfiltered orders,
+//! every public contract) used to make the server buffer and cache all of it,
+//! growing RSS until the host stepped in. [`ResponseLimits`] bounds the size of
+//! any single ESI response body, the size of an item written to the cache, and
+//! how much ESI data one tool call may read in total. Going over a limit fails
+//! with [`TraderGraderError::ResultTooLarge`], which tells the caller to narrow
+//! the query, instead of exhausting memory.
+
+use crate::error::{Result, TraderGraderError};
+use reqwest::Response;
+use std::cell::Cell;
+use std::future::Future;
+
+/// Default cap on a single ESI response body: 16 MiB
+pub const DEFAULT_MAX_RESPONSE_BYTES: usize = 16 * 1024 * 1024;
+
+/// Default cap on one cached item: 4 MiB
+pub const DEFAULT_MAX_CACHED_ITEM_BYTES: usize = 4 * 1024 * 1024;
+
+/// Default ESI data one tool call may read: 64 MiB
+pub const DEFAULT_TOOL_BUDGET_BYTES: usize = 64 * 1024 * 1024;
+
+/// Size limits applied to ESI responses, cache writes and tool calls
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResponseLimits {
+    /// Largest ESI response body read, in bytes
+    pub max_response_bytes: usize,
+    /// Largest item written to the cache; bigger results are returned but not cached
+    pub max_cached_item_bytes: usize,
+    /// Total ESI response bytes one tool call may read
+    pub tool_budget_bytes: usize,
+}
+
+impl Default for ResponseLimits {
+    fn default() -> Self {
+        Self {
+            max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES,
+            max_cached_item_bytes: DEFAULT_MAX_CACHED_ITEM_BYTES,
+            tool_budget_bytes: DEFAULT_TOOL_BUDGET_BYTES,
+        }
+    }
+}
+
+impl ResponseLimits {
+    /// Create the default limits
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tighter limits for small hosts: 4 MiB responses, 1 MiB cache items, 16 MiB per tool call
+    pub fn constrained() -> Self {
+        Self {
+            max_response_bytes: 4 * 1024 * 1024,
+            max_cached_item_bytes: 1024 * 1024,
+            tool_budget_bytes: 16 * 1024 * 1024,
+        }
+    }
+
+    /// No limits, for trusted batch jobs
+    pub fn unlimited() -> Self {
+        Self {
+            max_response_bytes: usize::MAX,
+            max_cached_item_bytes: usize::MAX,
+            tool_budget_bytes: usize::MAX,
+        }
+    }
+
+    /// Load limits from the environment, keeping defaults for unset values
+    ///
+    /// Reads `TRADERGRADER_MAX_RESPONSE_BYTES`, `TRADERGRADER_MAX_CACHED_ITEM_BYTES`
+    /// and `TRADERGRADER_TOOL_BUDGET_BYTES`.
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            max_response_bytes: env_bytes("TRADERGRADER_MAX_RESPONSE_BYTES", defaults.max_response_bytes),
+            max_cached_item_bytes: env_bytes("TRADERGRADER_MAX_CACHED_ITEM_BYTES", defaults.max_cached_item_bytes),
+            tool_budget_bytes: env_bytes("TRADERGRADER_TOOL_BUDGET_BYTES", defaults.tool_budget_bytes),
+        }
+    }
+}
+
+fn env_bytes(name: &str, default: usize) -> usize {
+    match std::env::var(name) {
+        Ok(value) => value.trim().parse().unwrap_or_else(|_| {
+            tracing::warn!("Ignoring {name}={value}: expected a number of bytes");
+            default
+        }),
+        Err(_) => default,
+    }
+}
+
+/// Bytes of ESI data read so far by the current tool call
+struct ToolBudget {
+    limit: usize,
+    used: Cell<usize>,
+}
+
+tokio::task_local! {
+    static TOOL_BUDGET: ToolBudget;
+}
+
+/// Runs `future` with a budget on the ESI response bytes it may read
+///
+/// Every response body read through the market client while `future` runs
+/// counts against the budget, including concurrent requests made from the same
+/// task. Once the total passes `budget_bytes` the read fails with
+/// [`TraderGraderError::ResultTooLarge`].
+///
+/// # Examples
+///
+/// ```no_run
+/// # use tradergrader::{MarketClient, Result};
+/// use tradergrader::limits::with_tool_budget;
+///
+/// # async fn example() -> Result<()> {
+/// let client = MarketClient::new();
+/// let orders = with_tool_budget(8 * 1024 * 1024, client.fetch_market_orders(10000002, Some(34))).await?;
+/// println!("{} orders", orders.len());
+/// # Ok(())
+/// # }
+/// ```
+pub async fn with_tool_budget<F: Future>(budget_bytes: usize, future: F) -> F::Output {
+    let budget = ToolBudget {
+        limit: budget_bytes,
+        used: Cell::new(0),
+    };
+    TOOL_BUDGET.scope(budget, future).await
+}
+
+/// Counts `bytes` against the current tool budget, if one is set
+fn charge_tool_budget(bytes: usize) -> Result<()> {
+    TOOL_BUDGET
+        .try_with(|budget| {
+            let used = budget.used.get().saturating_add(bytes);
+            budget.used.set(used);
+            if used > budget.limit {
+                Err(TraderGraderError::ResultTooLarge {
+                    what: "ESI data read by this tool call".to_string(),
+                    limit_bytes: budget.limit,
+                })
+            } else {
+                Ok(())
+            }
+        })
+        .unwrap_or(Ok(()))
+}
+
+/// Reads a response body, stopping as soon as it passes `limit` bytes or the tool budget
+///
+/// A `Content-Length` over the limit is rejected before anything is read.
+pub(crate) async fn read_body(mut response: Response, limit: usize) -> Result<Vec<u8>> {
+    let too_large = |response: &Response| TraderGraderError::ResultTooLarge {
+        what: format!("ESI response from {}", response.url().path()),
+        limit_bytes: limit,
+    };
+    let declared = response.content_length().unwrap_or(0);
+    if declared > limit as u64 {
+        return Err(too_large(&response));
+    }
+
+    let mut body = Vec::with_capacity(declared as usize);
+    while let Some(chunk) = response.chunk().await? {
+        if body.len() + chunk.len() > limit {
+            return Err(too_large(&response));
+        }
+        charge_tool_budget(chunk.len())?;
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets() {
+        let defaults = ResponseLimits::new();
+        assert_eq!(defaults.max_response_bytes, DEFAULT_MAX_RESPONSE_BYTES);
+        assert!(ResponseLimits::constrained().tool_budget_bytes < defaults.tool_budget_bytes);
+        assert_eq!(ResponseLimits::unlimited().max_cached_item_bytes, usize::MAX);
+    }
+
+    #[tokio::test]
+    async fn test_tool_budget() {
+        // Outside a tool call nothing is counted
+        assert!(charge_tool_budget(usize::MAX).is_ok());
+
+        let result = with_tool_budget(100, async {
+            charge_tool_budget(60)?;
+            charge_tool_budget(40)?;
+            charge_tool_budget(1)
+        })
+        .await;
+        let error = result.unwrap_err();
+        assert!(matches!(error, TraderGraderError::ResultTooLarge { limit_bytes: 100, .. }));
+        assert!(error.to_string().contains("narrow your query"));
+
+        // Each call gets a fresh budget
+        assert!(with_tool_budget(100, async { charge_tool_budget(100) }).await.is_ok());
+    }
+
+    /// Serves one HTTP response on a local port, chunked unless `content_length` is set
+    async fn serve(body: &'static str, content_length: bool) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let _ = socket.read(&mut [0; 1024]).await;
+            let response = if content_length {
+                format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{body}", body.len())
+            } else {
+                format!("HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n{:x}\r\n{body}\r\n0\r\n\r\n", body.len())
+            };
+            let _ = socket.write_all(response.as_bytes()).await;
+        });
+        format!("http://{address}/markets/10000002/orders/")
+    }
+
+    #[tokio::test]
+    async fn test_read_body_limits() {
+        let url = serve("[1,2,3]", true).await;
+        let body = read_body(reqwest::get(&url).await.unwrap(), 64).await.unwrap();
+        assert_eq!(body, b"[1,2,3]");
+
+        let url = serve("[1,2,3]", true).await;
+        let error = read_body(reqwest::get(&url).await.unwrap(), 4).await.unwrap_err();
+        assert!(error.to_string().contains("/markets/10000002/orders/ is over the 4 byte limit"));
+
+        // Without a Content-Length the body is cut off while streaming
+        let url = serve("[1,2,3]", false).await;
+        assert!(read_body(reqwest::get(&url).await.unwrap(), 4).await.is_err());
+
+        let url = serve("[1,2,3]", false).await;
+        let response = reqwest::get(&url).await.unwrap();
+        assert!(with_tool_budget(4, read_body(response, 64)).await.is_err());
+    }
+}
@@ -0,0 +1,376 @@
+//! Listing price advice for TraderGrader
+//!
+//! "Undercut the best order by 0.01 ISK" is the usual advice, but the order
+//! book's shape often says otherwise. Prices are limited to four significant
+//! digits, so the smallest undercut grows with the price; a lone small order
+//! well ahead of the rest isn't worth chasing when listing just ahead of the
+//! next level fills almost as soon; a wall of volume at the best price can be
+//! jumped by one tick; and a crowded top means expecting to be undercut
+//! quickly. [`ListingAdvice::from_book`] reads these from the best
+//! [`TOP_LEVELS`] price levels and suggests a price with its place in the
+//! queue.
+
+use crate::error::{Result, TraderGraderError};
+use crate::market::{median, MarketClient};
+use crate::orderbook::{BookSide, MarketOrderBook};
+use crate::types::{ListingAdvice, PriceLevel, TradeSide};
+
+/// Best price levels read for the book's shape
+pub const TOP_LEVELS: usize = 10;
+
+/// How many times the median level's volume a level needs to count as a wall
+const WALL_MULTIPLE: f64 = 5.0;
+
+/// Gap, in percent of the price, behind a small best order that makes it not worth undercutting
+const LONE_ORDER_GAP_PERCENT: f64 = 2.0;
+
+/// How close to the best price, in percent, an order counts as crowding it
+const CLUSTER_PERCENT: f64 = 1.0;
+
+/// Orders near the best price from which the top counts as crowded
+const CROWDED_ORDERS: usize = 5;
+
+/// Smallest price change allowed at `price`
+///
+/// Order prices carry at most four significant digits, and never less than
+/// 0.01 ISK.
+///
+/// # Examples
+///
+/// ```
+/// use tradergrader::listing::price_tick;
+///
+/// assert_eq!(price_tick(5.43), 0.01);
+/// assert_eq!(price_tick(1_234.5), 1.0);
+/// assert_eq!(price_tick(2_345_000.0), 1_000.0);
+/// ```
+pub fn price_tick(price: f64) -> f64 {
+    if price <= 0.0 {
+        return 0.01;
+    }
+    10f64.powi(price.log10().floor() as i32 - 3).max(0.01)
+}
+
+/// Rounds `price` onto the allowed price grid, down for sell orders and up for buy orders
+fn snap(price: f64, side: TradeSide) -> f64 {
+    let tick = price_tick(price);
+    let steps = price / tick;
+    let steps = match side {
+        TradeSide::Sell => (steps + 1e-9).floor(),
+        TradeSide::Buy => (steps - 1e-9).ceil(),
+    };
+    (steps * tick * 100.0).round() / 100.0
+}
+
+fn book_side(side: TradeSide) -> BookSide {
+    match side {
+        TradeSide::Sell => BookSide::Ask,
+        TradeSide::Buy => BookSide::Bid,
+    }
+}
+
+/// Whether `price` fills no later than `other` on a side: cheaper sells, higher buys
+fn at_or_better(side: TradeSide, price: f64, other: f64) -> bool {
+    match side {
+        TradeSide::Sell => price <= other,
+        TradeSide::Buy => price >= other,
+    }
+}
+
+impl ListingAdvice {
+    /// Suggests a price for a new order on one side of `book`
+    ///
+    /// With a `station_id`, only orders at that station or structure are
+    /// considered. The returned advice's `type_label` is a placeholder;
+    /// [`MarketClient::suggest_listing_price`] fills in the item name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::orderbook::MarketOrderBook;
+    /// use tradergrader::{ListingAdvice, MarketOrder, TradeSide};
+    ///
+    /// let order = |order_id, price, volume_remain| MarketOrder {
+    ///     order_id,
+    ///     type_id: 34,
+    ///     location_id: 60003760,
+    ///     system_id: 30000142,
+    ///     volume_total: volume_remain,
+    ///     volume_remain,
+    ///     min_volume: 1,
+    ///     price,
+    ///     is_buy_order: false,
+    ///     duration: 90,
+    ///     issued: "2025-06-01T00:00:00Z".to_string(),
+    ///     range: "region".to_string(),
+    ///     extra: Default::default(),
+    /// };
+    /// let book = MarketOrderBook::new(vec![order(1, 1_250.0, 10), order(2, 1_251.0, 500)]);
+    /// let advice = ListingAdvice::from_book(10000002, 34, TradeSide::Sell, None, &book)?;
+    /// // Four significant digits: the smallest undercut at 1,250 ISK is 1 ISK
+    /// assert_eq!(advice.suggested_price, 1_249.0);
+    /// assert_eq!(advice.queue_position, 1);
+    /// # Ok::<(), tradergrader::TraderGraderError>(())
+    /// ```
+    pub fn from_book(
+        region_id: i32,
+        type_id: i32,
+        side: TradeSide,
+        station_id: Option<i64>,
+        book: &MarketOrderBook,
+    ) -> Result<Self> {
+        let station_book;
+        let book = match station_id {
+            Some(station_id) => {
+                station_book = MarketOrderBook::new(book.at_location(station_id).cloned().collect());
+                &station_book
+            }
+            None => book,
+        };
+        let levels = book.levels(book_side(side));
+        let top = &levels[..levels.len().min(TOP_LEVELS)];
+        let Some(best) = top.first() else {
+            let orders = match side {
+                TradeSide::Sell => "sell orders",
+                TradeSide::Buy => "buy orders",
+            };
+            return Err(TraderGraderError::InsufficientData(format!("No {orders} to price a listing against")));
+        };
+        let opposite_price = match side {
+            TradeSide::Sell => book.best_bid(),
+            TradeSide::Buy => book.best_ask(),
+        };
+
+        let steps: Vec<f64> = top.windows(2).map(|pair| (pair[1].price - pair[0].price).abs()).collect();
+        let typical_step = median(&steps);
+        let orders_near_best = top
+            .iter()
+            .filter(|l| (l.price - best.price).abs() <= best.price * CLUSTER_PERCENT / 100.0)
+            .map(|l| l.order_count)
+            .sum();
+        let volumes: Vec<f64> = top.iter().map(|l| l.volume as f64).collect();
+        let median_volume = median(&volumes).unwrap_or(0.0);
+        let wall = (top.len() >= 3)
+            .then(|| top.iter().find(|l| l.volume as f64 >= median_volume * WALL_MULTIPLE))
+            .flatten()
+            .cloned();
+
+        let gap_percent = top.get(1).map(|next| (next.price - best.price).abs() / best.price * 100.0);
+        let (target, mut rationale) = match (top.get(1), gap_percent) {
+            (Some(next), Some(gap)) if gap >= LONE_ORDER_GAP_PERCENT && (best.volume as f64) < median_volume => (
+                next,
+                format!(
+                    "The best order is a lone {} units {gap:.1}% ahead of the rest; rather than give that margin away, \
+                     list one tick ahead of the next level and let it fill first",
+                    best.volume
+                ),
+            ),
+            _ if wall.as_ref() == Some(best) => (
+                best,
+                format!("A wall of {} units sits at the best price; one tick ahead of it fills first", best.volume),
+            ),
+            _ => (best, "One tick ahead of the best order fills first".to_string()),
+        };
+        let improve = match side {
+            TradeSide::Sell => -price_tick(target.price),
+            TradeSide::Buy => price_tick(target.price),
+        };
+        let mut suggested_price = snap(target.price + improve, side);
+        if opposite_price.is_some_and(|opposite| at_or_better(side, suggested_price, opposite)) {
+            // Crossing the spread would trade at once instead of listing
+            suggested_price = best.price;
+            rationale = "The spread is too thin to step ahead without trading at once, so join the best price".to_string();
+        }
+        if orders_near_best >= CROWDED_ORDERS {
+            rationale.push_str(&format!(
+                ". {orders_near_best} orders are within {CLUSTER_PERCENT:.0}% of the best price, so expect to be undercut soon"
+            ));
+        }
+
+        let ahead: Vec<&PriceLevel> =
+            levels.iter().take_while(|l| at_or_better(side, l.price, suggested_price)).collect();
+        Ok(Self {
+            region_id,
+            type_id,
+            type_label: format!("Type {type_id}"),
+            station_id,
+            side,
+            best_price: best.price,
+            opposite_price,
+            tick_size: price_tick(best.price),
+            typical_step,
+            orders_near_best,
+            wall,
+            suggested_price,
+            queue_position: ahead.iter().map(|l| l.order_count).sum::<usize>() + 1,
+            units_ahead: ahead.iter().map(|l| l.volume).sum(),
+            rationale,
+        })
+    }
+}
+
+impl MarketClient {
+    /// Suggests a price for a new sell or buy order from the current order book
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result, TradeSide};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// // Selling Tritanium at Jita 4-4
+    /// let advice = client.suggest_listing_price(10000002, 34, TradeSide::Sell, Some(60003760)).await?;
+    /// println!("List at {:.2} ISK, position {} in the queue", advice.suggested_price, advice.queue_position);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn suggest_listing_price(
+        &self,
+        region_id: i32,
+        type_id: i32,
+        side: TradeSide,
+        station_id: Option<i64>,
+    ) -> Result<ListingAdvice> {
+        let book = self.fetch_order_book(region_id, Some(type_id)).await?;
+        let mut advice = ListingAdvice::from_book(region_id, type_id, side, station_id, &book)?;
+        advice.type_label = self.type_label(type_id).await;
+        Ok(advice)
+    }
+
+    /// Generates a formatted listing price suggestion with the book shape behind it
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result, TradeSide};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// println!("{}", client.get_listing_price_summary(10000002, 34, TradeSide::Buy, None).await?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_listing_price_summary(
+        &self,
+        region_id: i32,
+        type_id: i32,
+        side: TradeSide,
+        station_id: Option<i64>,
+    ) -> Result<String> {
+        let advice = self.suggest_listing_price(region_id, type_id, side, station_id).await?;
+        let market = match station_id {
+            Some(station_id) => match self.resolve_location_names(&[station_id]).await.remove(&station_id) {
+                Some(name) => name,
+                None => format!("Station {station_id}"),
+            },
+            None => format!("Region {region_id}"),
+        };
+        Ok(format_listing_advice(&format!("Listing Price for {} in {market}", advice.type_label), &advice))
+    }
+}
+
+/// Formats a listing suggestion and the shape of the book behind it
+fn format_listing_advice(title: &str, advice: &ListingAdvice) -> String {
+    let (verb, orders, opposite) = match advice.side {
+        TradeSide::Sell => ("Sell", "sell orders", "best buy"),
+        TradeSide::Buy => ("Buy", "buy orders", "best sell"),
+    };
+    let mut report = format!(
+        "{title}:\n\
+         \n\
+         {verb} order at {:.2} ISK, position {} in the queue ({} units ahead)\n\
+         Why: {}\n\
+         \n\
+         Book shape ({orders}):\n\
+         Best price: {:.2} ISK{}\n\
+         Smallest price step: {:.2} ISK{}\n\
+         Orders within {CLUSTER_PERCENT:.0}% of the best price: {}\n",
+        advice.suggested_price,
+        advice.queue_position,
+        advice.units_ahead,
+        advice.rationale,
+        advice.best_price,
+        advice.opposite_price.map(|p| format!(", {opposite} {p:.2} ISK")).unwrap_or_default(),
+        advice.tick_size,
+        advice
+            .typical_step
+            .map(|step| format!("; the best levels are a median {step:.2} ISK apart"))
+            .unwrap_or_default(),
+        advice.orders_near_best,
+    );
+    if let Some(wall) = &advice.wall {
+        report.push_str(&format!(
+            "Wall: {} units at {:.2} ISK across {} orders\n",
+            wall.volume, wall.price, wall.order_count
+        ));
+    }
+    report.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::market_order;
+    use crate::types::MarketOrder;
+
+    fn order(order_id: i64, is_buy_order: bool, price: f64, volume_remain: i32) -> MarketOrder {
+        market_order(order_id, is_buy_order, price).with_volume(volume_remain).with_issued("2025-06-01T00:00:00Z")
+    }
+
+    #[test]
+    fn test_price_grid() {
+        assert_eq!(price_tick(0.5), 0.01);
+        assert_eq!(price_tick(999.9), 0.1);
+        assert_eq!(snap(1_249.7, TradeSide::Sell), 1_249.0);
+        assert_eq!(snap(1_249.2, TradeSide::Buy), 1_250.0);
+        assert_eq!(snap(5.42, TradeSide::Sell), 5.42);
+        // Crossing a power of ten tightens the grid
+        assert_eq!(snap(1_000.0 - 1.0, TradeSide::Sell), 999.0);
+    }
+
+    #[test]
+    fn test_lone_order_is_not_chased() {
+        let book = MarketOrderBook::new(vec![
+            order(1, false, 5.00, 10),
+            order(2, false, 5.30, 1_000),
+            order(3, false, 5.31, 2_000),
+            order(4, false, 5.35, 1_500),
+            order(5, true, 4.00, 1_000),
+        ]);
+        let advice = ListingAdvice::from_book(10000002, 34, TradeSide::Sell, None, &book).unwrap();
+        assert_eq!(advice.suggested_price, 5.29);
+        assert_eq!(advice.queue_position, 2);
+        assert_eq!(advice.units_ahead, 10);
+        assert!(advice.rationale.starts_with("The best order is a lone 10 units 6.0% ahead"));
+
+        let text = format_listing_advice("Listing Price for Tritanium (34) in Region 10000002", &advice);
+        assert!(text.contains("Sell order at 5.29 ISK, position 2 in the queue (10 units ahead)"));
+        assert!(text.contains("Best price: 5.00 ISK, best buy 4.00 ISK"));
+    }
+
+    #[test]
+    fn test_wall_and_crowded_buy_side() {
+        let mut orders: Vec<MarketOrder> =
+            (0..5).map(|i| order(i, true, 10.00 - i as f64 * 0.01, 100)).collect();
+        orders[0].volume_remain = 10_000;
+        orders.push(order(10, false, 10.50, 100));
+        let book = MarketOrderBook::new(orders);
+        let advice = ListingAdvice::from_book(10000002, 34, TradeSide::Buy, None, &book).unwrap();
+        assert_eq!(advice.suggested_price, 10.01);
+        assert_eq!(advice.queue_position, 1);
+        assert_eq!(advice.wall.as_ref().map(|w| w.volume), Some(10_000));
+        assert!((advice.typical_step.unwrap() - 0.01).abs() < 1e-9);
+        assert!(advice.rationale.starts_with("A wall of 10000 units"));
+        assert!(advice.rationale.contains("5 orders are within 1% of the best price"));
+    }
+
+    #[test]
+    fn test_thin_spread_joins_best_price() {
+        let book = MarketOrderBook::new(vec![order(1, false, 5.01, 100), order(2, true, 5.00, 100)]);
+        let advice = ListingAdvice::from_book(10000002, 34, TradeSide::Sell, None, &book).unwrap();
+        assert_eq!(advice.suggested_price, 5.01);
+        assert_eq!(advice.queue_position, 2);
+        assert!(advice.rationale.contains("join the best price"));
+
+        assert!(ListingAdvice::from_book(10000002, 34, TradeSide::Sell, Some(60008494), &book).is_err());
+    }
+}
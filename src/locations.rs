@@ -0,0 +1,314 @@
+//! Named location presets for TraderGrader
+//!
+//! Models remember "Jita" far better than 10000002, 30000142 and 60003760.
+//! A [`LocationRegistry`] maps friendly aliases to a region and, for trade
+//! hubs and user presets, a system and station. Tool arguments naming a
+//! region, system or station may use an alias wherever an ID is expected;
+//! aliases are swapped for IDs before the arguments are validated, so the
+//! tools themselves only ever see numbers.
+//!
+//! Built-in aliases cover the five trade hubs and the regions most traders
+//! work in. More come from the `[locations]` config section:
+//!
+//! ```toml
+//! [locations.home]
+//! region_id = 10000002
+//! system_id = 30000144      # Perimeter
+//! station_id = 1028858195912
+//! ```
+
+use crate::error::{Result, TraderGraderError};
+use crate::hubs::TradeHub;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Regions with an alias of their own, besides the hub regions
+const REGION_ALIASES: [(&str, i32); 11] = [
+    ("the-forge", 10000002),
+    ("domain", 10000043),
+    ("sinq-laison", 10000032),
+    ("heimatar", 10000030),
+    ("metropolis", 10000042),
+    ("lonetrek", 10000016),
+    ("the-citadel", 10000033),
+    ("essence", 10000064),
+    ("verge-vendor", 10000068),
+    ("molden-heath", 10000028),
+    ("delve", 10000060),
+];
+
+/// What kind of ID a tool argument holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocationKind {
+    Region,
+    System,
+    Station,
+}
+
+impl LocationKind {
+    /// The kind of ID an argument holds, judged by its name
+    pub fn of_field(field: &str) -> Option<Self> {
+        match field {
+            "region_id" | "region_ids" => Some(Self::Region),
+            "system_id" | "origin_system_id" | "destination_system_id" => Some(Self::System),
+            "station_id" | "start_location_id" | "end_location_id" => Some(Self::Station),
+            _ => None,
+        }
+    }
+
+    /// The argument usually holding this kind of ID
+    fn field(&self) -> &'static str {
+        match self {
+            Self::Region => "region_id",
+            Self::System => "system_id",
+            Self::Station => "station_id",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Region => "region",
+            Self::System => "solar system",
+            Self::Station => "station",
+        }
+    }
+}
+
+/// Where an alias points
+///
+/// Region aliases only carry a region; hub and user presets usually name a
+/// system and station as well.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LocationPreset {
+    pub region_id: i32,
+    #[serde(default)]
+    pub system_id: Option<i32>,
+    #[serde(default)]
+    pub station_id: Option<i64>,
+}
+
+impl LocationPreset {
+    /// A preset naming only a region
+    pub fn region(region_id: i32) -> Self {
+        Self {
+            region_id,
+            system_id: None,
+            station_id: None,
+        }
+    }
+
+    /// The ID of the given kind, if the preset names one
+    pub fn id(&self, kind: LocationKind) -> Option<i64> {
+        match kind {
+            LocationKind::Region => Some(i64::from(self.region_id)),
+            LocationKind::System => self.system_id.map(i64::from),
+            LocationKind::Station => self.station_id,
+        }
+    }
+}
+
+impl From<TradeHub> for LocationPreset {
+    fn from(hub: TradeHub) -> Self {
+        Self {
+            region_id: hub.region_id(),
+            system_id: Some(hub.system_id()),
+            station_id: Some(hub.station_id()),
+        }
+    }
+}
+
+impl std::str::FromStr for LocationPreset {
+    type Err = String;
+
+    /// Parses `region_id[:system_id[:station_id]]`
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let mut parts = s.trim().split(':').map(str::trim);
+        let invalid = || format!("expected region_id[:system_id[:station_id]], got '{s}'");
+        let region_id = parts.next().and_then(|p| p.parse().ok()).ok_or_else(invalid)?;
+        let system_id = parts.next().map(|p| p.parse().map_err(|_| invalid())).transpose()?;
+        let station_id = parts.next().map(|p| p.parse().map_err(|_| invalid())).transpose()?;
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+        Ok(Self {
+            region_id,
+            system_id,
+            station_id,
+        })
+    }
+}
+
+/// Aliases for regions, systems and stations, usable in place of their IDs
+///
+/// # Examples
+///
+/// ```
+/// use tradergrader::locations::{LocationKind, LocationPreset, LocationRegistry};
+///
+/// let mut locations = LocationRegistry::default();
+/// locations.insert("Home Base", LocationPreset::region(10000016));
+///
+/// assert_eq!(locations.resolve_id("jita", LocationKind::Station)?, 60003760);
+/// assert_eq!(locations.resolve_id("The Forge", LocationKind::Region)?, 10000002);
+/// assert_eq!(locations.resolve_id("home_base", LocationKind::Region)?, 10000016);
+/// assert!(locations.resolve_id("the-forge", LocationKind::System).is_err());
+/// # Ok::<(), tradergrader::TraderGraderError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocationRegistry {
+    presets: BTreeMap<String, LocationPreset>,
+}
+
+impl Default for LocationRegistry {
+    /// The built-in trade hub and region aliases
+    fn default() -> Self {
+        let mut registry = Self {
+            presets: BTreeMap::new(),
+        };
+        for hub in TradeHub::ALL {
+            registry.insert(&hub.to_string(), hub.into());
+        }
+        for (alias, region_id) in REGION_ALIASES {
+            registry.insert(alias, LocationPreset::region(region_id));
+        }
+        registry
+    }
+}
+
+impl LocationRegistry {
+    /// Adds an alias, replacing any preset already under that name
+    pub fn insert(&mut self, alias: &str, preset: LocationPreset) {
+        self.presets.insert(normalize(alias), preset);
+    }
+
+    /// The preset an alias names; case, spaces and underscores don't matter
+    pub fn get(&self, alias: &str) -> Option<&LocationPreset> {
+        self.presets.get(&normalize(alias))
+    }
+
+    /// Every alias with its preset, alphabetically
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &LocationPreset)> {
+        self.presets.iter().map(|(alias, preset)| (alias.as_str(), preset))
+    }
+
+    /// The ID of `kind` an alias stands for; numeric strings pass through
+    pub fn resolve_id(&self, alias: &str, kind: LocationKind) -> Result<i64> {
+        if let Ok(id) = alias.trim().parse::<i64>() {
+            return Ok(id);
+        }
+        let invalid = |reason: String| TraderGraderError::InvalidArgument {
+            field: kind.field().to_string(),
+            reason,
+        };
+        let preset = self.get(alias).ok_or_else(|| {
+            invalid(format!(
+                "is '{alias}', which is not a known location; use an ID or one of: {}",
+                self.presets.keys().cloned().collect::<Vec<_>>().join(", ")
+            ))
+        })?;
+        preset
+            .id(kind)
+            .ok_or_else(|| invalid(format!("is '{alias}', which doesn't name a {}", kind.label())))
+    }
+
+    /// Replaces aliases in location arguments with the IDs they stand for
+    ///
+    /// Only string values of region, system and station arguments (and the
+    /// items of `region_ids`) are touched; anything else is left for the
+    /// schema validation to judge.
+    pub fn resolve_arguments(&self, arguments: &mut Value) -> Result<()> {
+        let Some(fields) = arguments.as_object_mut() else {
+            return Ok(());
+        };
+        for (field, value) in fields.iter_mut() {
+            let Some(kind) = LocationKind::of_field(field) else {
+                continue;
+            };
+            let values: Vec<&mut Value> = match value {
+                Value::Array(items) => items.iter_mut().collect(),
+                value => vec![value],
+            };
+            for value in values {
+                if let Some(alias) = value.as_str() {
+                    let id = self.resolve_id(alias, kind).map_err(|e| match e {
+                        TraderGraderError::InvalidArgument { reason, .. } => TraderGraderError::InvalidArgument {
+                            field: field.clone(),
+                            reason,
+                        },
+                        other => other,
+                    })?;
+                    *value = Value::from(id);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Lowercases an alias and joins its words with hyphens
+fn normalize(alias: &str) -> String {
+    alias
+        .split(|c: char| c.is_whitespace() || c == '_' || c == '-')
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_built_in_aliases() {
+        let locations = LocationRegistry::default();
+        assert_eq!(locations.get("Amarr"), Some(&TradeHub::Amarr.into()));
+        assert_eq!(locations.get("sinq laison"), Some(&LocationPreset::region(10000032)));
+        assert_eq!(locations.resolve_id("jita", LocationKind::Region).unwrap(), 10000002);
+        assert_eq!(locations.resolve_id("30000142", LocationKind::System).unwrap(), 30000142);
+        assert!(locations.resolve_id("delve", LocationKind::Station).is_err());
+        let error = locations.resolve_id("perimeter", LocationKind::System).unwrap_err();
+        assert!(error.to_string().contains("the-forge"));
+    }
+
+    #[test]
+    fn test_resolve_arguments() {
+        let locations = LocationRegistry::default();
+        let mut arguments = json!({
+            "region_id": "Domain",
+            "station_id": "amarr",
+            "region_ids": ["jita", 10000032, "heimatar"],
+            "type_id": 34,
+            "hub": "jita"
+        });
+        locations.resolve_arguments(&mut arguments).unwrap();
+        assert_eq!(
+            arguments,
+            json!({
+                "region_id": 10000043,
+                "station_id": 60008494,
+                "region_ids": [10000002, 10000032, 10000030],
+                "type_id": 34,
+                "hub": "jita"
+            })
+        );
+
+        let mut arguments = json!({"system_id": "the-forge"});
+        let error = locations.resolve_arguments(&mut arguments).unwrap_err();
+        assert!(error.to_string().contains("system_id"));
+    }
+
+    #[test]
+    fn test_parse_preset() {
+        assert_eq!("10000002".parse::<LocationPreset>(), Ok(LocationPreset::region(10000002)));
+        assert_eq!(
+            " 10000002:30000142:60003760 ".parse::<LocationPreset>(),
+            Ok(TradeHub::Jita.into())
+        );
+        assert!("jita".parse::<LocationPreset>().is_err());
+        assert!("1:2:3:4".parse::<LocationPreset>().is_err());
+    }
+}
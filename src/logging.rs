@@ -0,0 +1,215 @@
+//! MCP log notifications for TraderGrader
+//!
+//! MCP clients usually hide a server's stderr, so the server advertises the
+//! `logging` capability and reports what it is doing as `notifications/message`
+//! events: ESI requests and their status, cache hits and misses, retries after
+//! rate limiting, and tool calls. [`McpLogger`] queues the events; the stdio
+//! server loop writes them out ahead of each response. The threshold starts at
+//! `TRADERGRADER_LOG_LEVEL` (default `warning`) and clients can change it with
+//! `logging/setLevel`.
+
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// Most events kept waiting to be written; older ones are dropped first
+pub const MAX_PENDING_LOG_MESSAGES: usize = 500;
+
+/// Syslog severity levels, as used by MCP logging
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Notice,
+    #[default]
+    Warning,
+    Error,
+    Critical,
+    Alert,
+    Emergency,
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Debug => "debug",
+            Self::Info => "info",
+            Self::Notice => "notice",
+            Self::Warning => "warning",
+            Self::Error => "error",
+            Self::Critical => "critical",
+            Self::Alert => "alert",
+            Self::Emergency => "emergency",
+        };
+        f.write_str(name)
+    }
+}
+
+impl std::str::FromStr for LogLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "debug" => Ok(Self::Debug),
+            "info" => Ok(Self::Info),
+            "notice" => Ok(Self::Notice),
+            "warning" => Ok(Self::Warning),
+            "error" => Ok(Self::Error),
+            "critical" => Ok(Self::Critical),
+            "alert" => Ok(Self::Alert),
+            "emergency" => Ok(Self::Emergency),
+            other => Err(format!("Unknown log level: {other}")),
+        }
+    }
+}
+
+/// Queue of MCP log notifications shared by the handler and the market client
+///
+/// Cloning is cheap and clones share the queue and level.
+#[derive(Debug, Clone, Default)]
+pub struct McpLogger {
+    state: Arc<LoggerState>,
+}
+
+#[derive(Debug, Default)]
+struct LoggerState {
+    level: Mutex<LogLevel>,
+    pending: Mutex<VecDeque<Value>>,
+}
+
+impl McpLogger {
+    /// Creates a logger that keeps events at `level` and above
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_json::json;
+    /// use tradergrader::logging::{LogLevel, McpLogger};
+    ///
+    /// let logger = McpLogger::new(LogLevel::Info);
+    /// logger.log(LogLevel::Debug, "cache", json!({"event": "cache_hit"}));
+    /// logger.log(LogLevel::Warning, "esi", json!({"event": "retry"}));
+    ///
+    /// let events = logger.drain();
+    /// assert_eq!(events.len(), 1);
+    /// assert_eq!(events[0]["method"], "notifications/message");
+    /// assert_eq!(events[0]["params"]["level"], "warning");
+    /// ```
+    pub fn new(level: LogLevel) -> Self {
+        let logger = Self::default();
+        logger.set_level(level);
+        logger
+    }
+
+    /// Creates a logger with the level from `TRADERGRADER_LOG_LEVEL`, or `warning`
+    pub fn from_env() -> Self {
+        let level = match std::env::var("TRADERGRADER_LOG_LEVEL") {
+            Ok(level) => level.parse().unwrap_or_else(|e| {
+                tracing::warn!("{e}; logging at warning");
+                LogLevel::Warning
+            }),
+            Err(_) => LogLevel::Warning,
+        };
+        Self::new(level)
+    }
+
+    /// The lowest level currently sent
+    pub fn level(&self) -> LogLevel {
+        *self.state.level.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Changes the lowest level sent, as requested by `logging/setLevel`
+    pub fn set_level(&self, level: LogLevel) {
+        *self.state.level.lock().unwrap_or_else(|e| e.into_inner()) = level;
+    }
+
+    /// Whether events at `level` are currently sent
+    pub fn enabled(&self, level: LogLevel) -> bool {
+        level >= self.level()
+    }
+
+    /// Queues a `notifications/message` event if `level` is enabled
+    pub fn log(&self, level: LogLevel, logger: &str, data: Value) {
+        if !self.enabled(level) {
+            return;
+        }
+        let mut pending = self.state.pending.lock().unwrap_or_else(|e| e.into_inner());
+        if pending.len() >= MAX_PENDING_LOG_MESSAGES {
+            pending.pop_front();
+        }
+        pending.push_back(json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/message",
+            "params": {
+                "level": level.to_string(),
+                "logger": logger,
+                "data": data
+            }
+        }));
+    }
+
+    /// Queues a notification other than a log message, e.g. `notifications/resources/updated`
+    ///
+    /// These are sent whatever the log level.
+    pub fn notify(&self, method: &str, params: Value) {
+        let mut pending = self.state.pending.lock().unwrap_or_else(|e| e.into_inner());
+        if pending.len() >= MAX_PENDING_LOG_MESSAGES {
+            pending.pop_front();
+        }
+        pending.push_back(json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params
+        }));
+    }
+
+        /// Takes every queued event, oldest first
+    pub fn drain(&self) -> Vec<Value> {
+        self.state
+            .pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .drain(..)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_parsing_and_order() {
+        assert_eq!("Notice".parse::<LogLevel>(), Ok(LogLevel::Notice));
+        assert!("verbose".parse::<LogLevel>().is_err());
+        assert!(LogLevel::Debug < LogLevel::Info && LogLevel::Alert < LogLevel::Emergency);
+        assert_eq!(LogLevel::default().to_string(), "warning");
+    }
+
+    #[test]
+    fn test_set_level_filters_events() {
+        let logger = McpLogger::new(LogLevel::Error);
+        let shared = logger.clone();
+        shared.log(LogLevel::Warning, "esi", json!("dropped"));
+        assert!(logger.drain().is_empty());
+
+        logger.set_level(LogLevel::Debug);
+        shared.log(LogLevel::Debug, "cache", json!({"event": "cache_miss"}));
+        let events = logger.drain();
+        assert_eq!(events[0]["params"]["logger"], "cache");
+        assert_eq!(events[0]["params"]["data"]["event"], "cache_miss");
+        assert!(logger.drain().is_empty());
+    }
+
+    #[test]
+    fn test_queue_is_bounded() {
+        let logger = McpLogger::new(LogLevel::Debug);
+        for i in 0..MAX_PENDING_LOG_MESSAGES + 10 {
+            logger.log(LogLevel::Info, "esi", json!(i));
+        }
+        let events = logger.drain();
+        assert_eq!(events.len(), MAX_PENDING_LOG_MESSAGES);
+        assert_eq!(events[0]["params"]["data"], 10);
+    }
+}
@@ -0,0 +1,349 @@
+//! Loyalty point store valuation for TraderGrader
+//!
+//! Mission runners and faction warfare pilots earn loyalty points with an NPC
+//! corporation, spent in its LP store on offers that also ask ISK and often
+//! items. What a point is worth is what the offer's output sells for, less
+//! everything else the offer costs, so each offer is priced at a trade hub
+//! station: required items bought from sell orders, the output sold to buy
+//! orders or listed, after sales tax and broker fee. Offers are ranked by ISK
+//! per LP, the number LP store shoppers compare.
+
+use crate::cache::CacheKey;
+use crate::error::{Result, TraderGraderError};
+use crate::fees::TradingProfile;
+use crate::hubs::TradeHub;
+use crate::market::MarketClient;
+use crate::types::{LpOfferValue, LpStoreOffer, LpStoreValue, PriceBasis};
+use std::collections::{BTreeSet, HashMap};
+
+/// NPC corporation IDs, the only corporations with LP stores
+pub const NPC_CORPORATION_ID_RANGE: std::ops::RangeInclusive<i32> = 1_000_000..=1_999_999;
+
+/// Offers listed unless the caller asks for more
+pub const DEFAULT_OFFER_LIMIT: usize = 25;
+
+/// Most offers one valuation lists
+pub const MAX_OFFER_LIMIT: usize = 200;
+
+impl LpOfferValue {
+    /// Prices an LP store offer from unit prices at a hub
+    ///
+    /// `prices` holds the lowest sell order of each required item;
+    /// `output_price` is the offer's item on the `basis` side of the book.
+    /// Selling to a buy order pays sales tax, listing also the broker fee.
+    /// Profit and ISK per LP are `None` when anything has no price.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use tradergrader::fees::TradingProfile;
+    /// use tradergrader::types::{LpOfferValue, LpRequiredItem, LpStoreOffer, PriceBasis};
+    ///
+    /// let offer = LpStoreOffer {
+    ///     offer_id: 1,
+    ///     type_id: 31716,
+    ///     quantity: 1,
+    ///     lp_cost: 1_000,
+    ///     isk_cost: 500_000.0,
+    ///     ak_cost: None,
+    ///     required_items: vec![LpRequiredItem { type_id: 34, quantity: 100 }],
+    /// };
+    /// let prices = HashMap::from([(34, 5.0)]);
+    /// let trading = TradingProfile::default();
+    /// let value = LpOfferValue::new(&offer, String::new(), &prices, Some(2_000_000.0), PriceBasis::Buy, &trading);
+    ///
+    /// assert_eq!(value.required_items_cost, Some(500.0));
+    /// let profit = 2_000_000.0 - value.selling_fees.unwrap() - 500_000.0 - 500.0;
+    /// assert_eq!(value.isk_per_lp, Some(profit / 1_000.0));
+    /// ```
+    pub fn new(
+        offer: &LpStoreOffer,
+        type_label: String,
+        prices: &HashMap<i32, f64>,
+        output_price: Option<f64>,
+        basis: PriceBasis,
+        trading: &TradingProfile,
+    ) -> Self {
+        let unpriced_items: Vec<i32> = offer
+            .required_items
+            .iter()
+            .map(|item| item.type_id)
+            .filter(|type_id| !prices.contains_key(type_id))
+            .collect();
+        let required_items_cost = unpriced_items.is_empty().then(|| {
+            offer
+                .required_items
+                .iter()
+                .map(|item| prices[&item.type_id] * item.quantity as f64)
+                .sum::<f64>()
+        });
+
+        let output_value = output_price.map(|price| price * offer.quantity as f64);
+        let fees = trading.current_fees();
+        let skills = &trading.skills;
+        let selling_fees = output_value.map(|value| match basis {
+            PriceBasis::Buy => fees.sales_tax(value, skills),
+            PriceBasis::Sell => fees.sales_tax(value, skills) + fees.broker_fee(value, skills),
+        });
+        let profit = output_value
+            .zip(selling_fees)
+            .zip(required_items_cost)
+            .map(|((value, fees), items)| value - fees - offer.isk_cost - items);
+
+        Self {
+            offer_id: offer.offer_id,
+            type_id: offer.type_id,
+            type_label,
+            quantity: offer.quantity,
+            lp_cost: offer.lp_cost,
+            isk_cost: offer.isk_cost,
+            required_items_cost,
+            output_value,
+            selling_fees,
+            profit,
+            isk_per_lp: profit.filter(|_| offer.lp_cost > 0).map(|profit| profit / offer.lp_cost as f64),
+            unpriced_items,
+        }
+    }
+}
+
+impl MarketClient {
+    /// Fetches the offers in an NPC corporation's loyalty point store
+    pub async fn fetch_lp_store_offers(&self, corporation_id: i32) -> Result<Vec<LpStoreOffer>> {
+        self.esi().get_cached(
+            &format!("/loyalty/stores/{corporation_id}/offers/"),
+            &CacheKey::loyalty_store(corporation_id),
+            "loyalty",
+        )
+        .await
+    }
+
+    /// Prices every offer in a corporation's LP store at a trade hub, best ISK per LP first
+    ///
+    /// Only orders at the hub's trade station count. Items whose order book
+    /// can't be fetched count as unpriced, as do blueprint copies, which
+    /// can't be sold on the market. At most `limit` offers are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # use tradergrader::fees::TradingProfile;
+    /// # use tradergrader::hubs::TradeHub;
+    /// # use tradergrader::types::PriceBasis;
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// // Federation Navy
+    /// let store = client
+    ///     .lp_store_value(1000120, TradeHub::Jita, PriceBasis::Buy, 10, &TradingProfile::default())
+    ///     .await?;
+    /// for offer in &store.offers {
+    ///     println!("{}: {:?} ISK/LP", offer.type_label, offer.isk_per_lp);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn lp_store_value(
+        &self,
+        corporation_id: i32,
+        hub: TradeHub,
+        output_basis: PriceBasis,
+        limit: usize,
+        trading: &TradingProfile,
+    ) -> Result<LpStoreValue> {
+        if !NPC_CORPORATION_ID_RANGE.contains(&corporation_id) {
+            return Err(TraderGraderError::InvalidArgument {
+                field: "corporation_id".to_string(),
+                reason: "must be an NPC corporation; player corporations have no LP store".to_string(),
+            });
+        }
+        let offers = self.fetch_lp_store_offers(corporation_id).await?;
+
+        let mut type_ids: BTreeSet<i32> = BTreeSet::new();
+        for offer in &offers {
+            type_ids.insert(offer.type_id);
+            type_ids.extend(offer.required_items.iter().map(|item| item.type_id));
+        }
+        let (buy_prices, sell_prices) = self.station_prices(hub, type_ids).await;
+        let output_prices = match output_basis {
+            PriceBasis::Buy => &buy_prices,
+            PriceBasis::Sell => &sell_prices,
+        };
+
+        let mut ids: Vec<i64> = offers.iter().map(|offer| offer.type_id as i64).collect();
+        ids.push(corporation_id as i64);
+        let names = self.resolve_names(&ids).await.unwrap_or_else(|e| {
+            tracing::debug!("Couldn't resolve LP store names: {e}");
+            HashMap::new()
+        });
+        let label = |type_id: i32| match names.get(&(type_id as i64)) {
+            Some(name) => format!("{} ({type_id})", name.name),
+            None => format!("Type {type_id}"),
+        };
+
+        let mut valued: Vec<LpOfferValue> = offers
+            .iter()
+            .map(|offer| {
+                LpOfferValue::new(
+                    offer,
+                    label(offer.type_id),
+                    &sell_prices,
+                    output_prices.get(&offer.type_id).copied(),
+                    output_basis,
+                    trading,
+                )
+            })
+            .collect();
+        valued.sort_by(|a, b| {
+            let isk_per_lp = |v: &LpOfferValue| v.isk_per_lp.unwrap_or(f64::NEG_INFINITY);
+            isk_per_lp(b).total_cmp(&isk_per_lp(a))
+        });
+        valued.truncate(limit.clamp(1, MAX_OFFER_LIMIT));
+
+        Ok(LpStoreValue {
+            corporation_id,
+            corporation_name: names.get(&(corporation_id as i64)).map(|name| name.name.clone()),
+            hub: hub.to_string(),
+            output_basis,
+            total_offers: offers.len(),
+            offers: valued,
+        })
+    }
+}
+
+/// Formats an LP store valuation as a table, best ISK per LP first
+pub(crate) fn format_lp_store_value(store: &LpStoreValue) -> String {
+    let corporation = store
+        .corporation_name
+        .clone()
+        .unwrap_or_else(|| format!("Corporation {}", store.corporation_id));
+    if store.offers.is_empty() {
+        return format!("{corporation} has no LP store offers.");
+    }
+
+    let isk = |value: Option<f64>| value.map_or_else(|| "-".to_string(), |v| format!("{v:.2}"));
+    let output = match store.output_basis {
+        PriceBasis::Buy => "sold to buy orders",
+        PriceBasis::Sell => "listed at sell orders",
+    };
+    let mut report = format!(
+        "{corporation} LP Store at {}, required items bought from sell orders, output {output}:\n\n\
+         | Offer | Qty | LP | ISK | Items | Output | Selling fees | Profit | ISK/LP |\n\
+         |---|---|---|---|---|---|---|---|---|\n",
+        store.hub
+    );
+    for o in &store.offers {
+        report.push_str(&format!(
+            "| {} | {} | {} | {:.2} | {} | {} | {} | {} | {} |\n",
+            o.type_label,
+            o.quantity,
+            o.lp_cost,
+            o.isk_cost,
+            isk(o.required_items_cost),
+            isk(o.output_value),
+            isk(o.selling_fees),
+            isk(o.profit),
+            isk(o.isk_per_lp)
+        ));
+    }
+    if store.offers.len() < store.total_offers {
+        report.push_str(&format!("\nShowing {} of {} offers.\n", store.offers.len(), store.total_offers));
+    }
+    if store.offers.iter().any(|o| o.isk_per_lp.is_none()) {
+        report.push_str(
+            "\nOffers without an ISK/LP have no orders at the hub for their output or a required item; \
+             blueprint copies are never on the market.\n",
+        );
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::LpRequiredItem;
+
+    fn offer() -> LpStoreOffer {
+        LpStoreOffer {
+            offer_id: 7,
+            type_id: 17703,
+            quantity: 10,
+            lp_cost: 2_000,
+            isk_cost: 1_000_000.0,
+            ak_cost: None,
+            required_items: vec![LpRequiredItem {
+                type_id: 34,
+                quantity: 1_000,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_offer_value() {
+        let trading = TradingProfile::default();
+        let prices = HashMap::from([(34, 4.0)]);
+        let value = |prices: &HashMap<i32, f64>, output: Option<f64>, basis: PriceBasis| {
+            LpOfferValue::new(&offer(), String::new(), prices, output, basis, &trading)
+        };
+        let listed = value(&prices, Some(500_000.0), PriceBasis::Sell);
+        let fees = trading.current_fees();
+        let expected_fees = fees.sales_tax(5_000_000.0, &trading.skills) + fees.broker_fee(5_000_000.0, &trading.skills);
+        assert_eq!(listed.output_value, Some(5_000_000.0));
+        assert_eq!(listed.selling_fees, Some(expected_fees));
+        assert_eq!(listed.profit, Some(5_000_000.0 - expected_fees - 1_000_000.0 - 4_000.0));
+        assert_eq!(listed.isk_per_lp, Some(listed.profit.unwrap() / 2_000.0));
+
+        // Selling instantly skips the broker fee
+        let instant = value(&prices, Some(500_000.0), PriceBasis::Buy);
+        assert!(instant.profit.unwrap() > listed.profit.unwrap());
+
+        let unpriced = value(&HashMap::new(), Some(500_000.0), PriceBasis::Buy);
+        assert_eq!(unpriced.unpriced_items, vec![34]);
+        assert_eq!(unpriced.isk_per_lp, None);
+        let no_output = value(&prices, None, PriceBasis::Buy);
+        assert_eq!(no_output.required_items_cost, Some(4_000.0));
+        assert_eq!(no_output.isk_per_lp, None);
+    }
+
+    #[test]
+    fn test_offers_deserialize_from_esi() {
+        let offers: Vec<LpStoreOffer> = serde_json::from_str(
+            r#"[{"ak_cost": 50, "isk_cost": 2400000, "lp_cost": 2400, "offer_id": 3584, "quantity": 1,
+                 "required_items": [{"quantity": 1, "type_id": 2048}], "type_id": 31716},
+                {"isk_cost": 0, "lp_cost": 125, "offer_id": 3600, "quantity": 5000,
+                 "required_items": [], "type_id": 21898}]"#,
+        )
+        .unwrap();
+        assert_eq!(offers[0].ak_cost, Some(50));
+        assert_eq!(offers[0].isk_cost, 2_400_000.0);
+        assert_eq!(offers[0].required_items[0].type_id, 2048);
+        assert_eq!(offers[1].ak_cost, None);
+    }
+
+    #[test]
+    fn test_format() {
+        let trading = TradingProfile::default();
+        let value = LpOfferValue::new(
+            &offer(),
+            "Republic Fleet EMP S (17703)".to_string(),
+            &HashMap::from([(34, 4.0)]),
+            None,
+            PriceBasis::Buy,
+            &trading,
+        );
+        let store = LpStoreValue {
+            corporation_id: 1000049,
+            corporation_name: Some("Republic Fleet".to_string()),
+            hub: "Rens".to_string(),
+            output_basis: PriceBasis::Buy,
+            total_offers: 3,
+            offers: vec![value],
+        };
+        let report = format_lp_store_value(&store);
+        assert!(report.starts_with("Republic Fleet LP Store at Rens, required items bought from sell orders"));
+        assert!(report.contains("| Republic Fleet EMP S (17703) | 10 | 2000 | 1000000.00 | 4000.00 | - | - | - | - |"));
+        assert!(report.contains("Showing 1 of 3 offers."));
+        assert!(report.contains("blueprint copies are never on the market"));
+    }
+}
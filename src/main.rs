@@ -1,17 +1,39 @@
-use tradergrader::StandaloneMcpServer;
+use tradergrader::{McpHandler, StandaloneMcpServer};
+use tradergrader::config::TraderGraderConfig;
+use tradergrader::export::ExportCommand;
+use tradergrader::telemetry::{init_tracing, TracingConfig};
 use std::env;
+use std::path::PathBuf;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args: Vec<String> = env::args().collect();
+    // Trace output goes to stderr or a file; stdout carries the MCP protocol
+    if let Err(e) = init_tracing(&TracingConfig::from_env()) {
+        eprintln!("Tracing disabled: {e}");
+    }
+
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let config_path = match args.iter().position(|arg| arg == "--config") {
+        Some(i) if i + 1 < args.len() => Some(PathBuf::from(args.drain(i..=i + 1).nth(1).unwrap_or_default())),
+        Some(_) => return Err("--config needs a file path".into()),
+        None => None,
+    };
+    let config = TraderGraderConfig::load(config_path.as_deref())?;
+
+    // `history` and `orders` print one export and exit instead of serving MCP
+    if let Some(command) = ExportCommand::parse(&args)? {
+        let handler = McpHandler::from_config(&config)?;
+        print!("{}", command.run(&handler.market_client).await?);
+        return Ok(());
+    }
+
+    let server = StandaloneMcpServer::from_config(&config)?;
     
-    if args.len() > 1 && args[1] == "--health" {
-        let server = StandaloneMcpServer::new();
+    if args.iter().any(|arg| arg == "--health") {
         server.health_check().await?;
         return Ok(());
     }
     
-    let server = StandaloneMcpServer::new();
     server.run().await?;
     Ok(())
 }
@@ -1,9 +1,40 @@
-use crate::cache::{CacheBackend, CacheBackendExt, CacheConfig, CacheKey, EsiHeaderParser};
+use crate::archive::HistoryArchive;
+use crate::auth::EveSso;
+use crate::cache::{CacheBackend, CacheConfig, CacheKey, EsiHeaderParser};
 use crate::error::Result;
+use crate::esi::{EsiClient, EsiConfig, EsiDiagnostics};
+use crate::history::format_freshness;
+use crate::indicators;
+use crate::limits::ResponseLimits;
+use crate::logging::McpLogger;
+use crate::orderbook::{MarketOrderBook, MAX_INDEXED_BOOKS};
+use crate::passthrough::EsiAllowlist;
 use crate::rate_limit::{EsiRateLimiter, RateLimitConfig};
-use crate::types::{MarketHistory, MarketOrder, PriceAnalysis};
-use reqwest::Client;
+use crate::sde::StaticData;
+use crate::trend::TrendConfig;
+use crate::types::{
+    CharacterOrder, CourierRouteRate, DataFreshness, DepthBand, LiquidityScore, MarketHistory, MarketOrder, OrderBookDepth, OrderUndercutStatus, OrderWall,
+    PriceAnalysis, PriceLevel, PublicContract, TechnicalIndicators, TrendEvidence, WalletTransaction,
+};
+use chrono::NaiveDate;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use std::time::Duration;
+
+/// Percentage bands around the mid-price reported by order book depth analysis
+const DEPTH_BAND_PERCENTS: [f64; 3] = [1.0, 5.0, 10.0];
+
+/// A price level is a wall when it holds this many times the median level volume
+const WALL_MEDIAN_MULTIPLE: f64 = 5.0;
+
+/// Minimum number of levels on a side before walls are detected
+const WALL_MIN_LEVELS: usize = 3;
+
+/// Days of history used for liquidity scoring
+const LIQUIDITY_WINDOW_DAYS: usize = 30;
+
+/// Days of history checked for gaps by price analysis
+const FRESHNESS_WINDOW_DAYS: u32 = 30;
 
 /// Market data client for EVE Online ESI API
 /// 
@@ -11,9 +42,18 @@ use std::sync::Arc;
 /// and perform market analysis using the EVE Online ESI (EVE Swagger Interface) API.
 #[derive(Debug)]
 pub struct MarketClient {
-    http_client: Client,
-    cache: Option<Arc<dyn CacheBackend>>,
-    rate_limiter: EsiRateLimiter,
+    /// Typed ESI access: requests, rate limiting, pagination and caching
+    esi: EsiClient,
+    /// Indexed order books, kept for the order cache TTL
+    order_books: moka::future::Cache<(i32, Option<i32>), Arc<MarketOrderBook>>,
+    /// Local SDE consulted before ESI for static lookups
+    static_data: Option<Arc<StaticData>>,
+    /// Archive history fetches are appended to and read back from
+    history_archive: Option<Arc<HistoryArchive>>,
+    /// Route prefixes reachable through `esi_get`
+    esi_allowlist: EsiAllowlist,
+    /// Rules behind the trend label of price analysis
+    trend_config: TrendConfig,
 }
 
 impl MarketClient {
@@ -59,17 +99,65 @@ impl MarketClient {
     /// # Ok::<(), tradergrader::TraderGraderError>(())
     /// ```
     pub fn with_configs(cache_config: CacheConfig, rate_limit_config: RateLimitConfig) -> Result<Self> {
+        let rate_limiter = EsiRateLimiter::shared(rate_limit_config)?;
+        Self::with_shared_rate_limiter(cache_config, rate_limiter)
+    }
+
+    /// Creates a new MarketClient that draws from an existing rate limiter
+    /// 
+    /// All clients created with the same limiter share one ESI quota, which keeps
+    /// a process with several clients (e.g. one per cache configuration) within
+    /// ESI limits as a whole.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `cache_config` - Configuration for cache backend (enabled/disabled, capacity, TTL)
+    /// * `rate_limiter` - Shared ESI rate limiter
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use std::sync::Arc;
+    /// use tradergrader::{MarketClient, CacheConfig, EsiRateLimiter, RateLimitConfig};
+    /// 
+    /// let limiter = EsiRateLimiter::shared(RateLimitConfig::default())?;
+    /// let first = MarketClient::with_shared_rate_limiter(CacheConfig::default(), Arc::clone(&limiter))?;
+    /// let second = MarketClient::with_shared_rate_limiter(CacheConfig::default(), limiter)?;
+    /// assert!(Arc::ptr_eq(first.rate_limiter(), second.rate_limiter()));
+    /// # Ok::<(), tradergrader::TraderGraderError>(())
+    /// ```
+    pub fn with_shared_rate_limiter(cache_config: CacheConfig, rate_limiter: Arc<EsiRateLimiter>) -> Result<Self> {
         let cache = cache_config.create_backend()?;
-        let rate_limiter = EsiRateLimiter::new(rate_limit_config)?;
-        
-        Ok(Self {
-            http_client: Client::builder()
-                .user_agent("TraderGrader/0.1.0 (https://github.com/fuuijin/tradergrader)")
-                .build()
-                .expect("Failed to create HTTP client"),
-            cache,
-            rate_limiter,
-        })
+        Ok(Self::from_parts(cache, rate_limiter, EsiConfig::default())?.with_serve_stale(cache_config.serve_stale))
+    }
+
+    /// Creates a new MarketClient with cache, rate limit and HTTP client configuration
+    /// 
+    /// Use this to tune connection pooling, keep-alive and HTTP/2 behavior, e.g.
+    /// for long-running daemons that would otherwise see latency spikes when
+    /// idle connections to ESI are dropped.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use tradergrader::{MarketClient, CacheConfig, EsiConfig, RateLimitConfig};
+    /// 
+    /// let client = MarketClient::with_esi_config(
+    ///     CacheConfig::default(),
+    ///     RateLimitConfig::default(),
+    ///     EsiConfig::high_throughput(),
+    /// )?;
+    /// assert!(client.esi_config().http2_prior_knowledge);
+    /// # Ok::<(), tradergrader::TraderGraderError>(())
+    /// ```
+    pub fn with_esi_config(
+        cache_config: CacheConfig,
+        rate_limit_config: RateLimitConfig,
+        esi_config: EsiConfig,
+    ) -> Result<Self> {
+        let cache = cache_config.create_backend()?;
+        let rate_limiter = EsiRateLimiter::shared(rate_limit_config)?;
+        Ok(Self::from_parts(cache, rate_limiter, esi_config)?.with_serve_stale(cache_config.serve_stale))
     }
 
     /// Creates a new MarketClient with custom cache backend
@@ -91,31 +179,264 @@ impl MarketClient {
     /// let client = MarketClient::with_cache(cache);
     /// ```
     pub fn with_cache(cache: Arc<dyn CacheBackend>) -> Self {
-        Self {
-            http_client: Client::builder()
-                .user_agent("TraderGrader/0.1.0 (https://github.com/fuuijin/tradergrader)")
-                .build()
-                .expect("Failed to create HTTP client"),
-            cache: Some(cache),
-            rate_limiter: EsiRateLimiter::default().expect("Failed to create rate limiter"),
-        }
+        Self::from_parts(Some(cache), Arc::new(EsiRateLimiter::default()), EsiConfig::default())
+            .expect("Failed to create HTTP client")
     }
 
     /// Creates a new MarketClient without caching
     pub fn without_cache() -> Self {
+        Self::from_parts(None, Arc::new(EsiRateLimiter::default()), EsiConfig::default())
+            .expect("Failed to create HTTP client")
+    }
+
+    /// Assembles a client from an optional cache, a (possibly shared) rate limiter
+    /// and the HTTP client configuration
+    fn from_parts(
+        cache: Option<Arc<dyn CacheBackend>>,
+        rate_limiter: Arc<EsiRateLimiter>,
+        esi_config: EsiConfig,
+    ) -> Result<Self> {
+        Ok(Self::from_esi(EsiClient::new(cache, rate_limiter, esi_config)?))
+    }
+
+    /// Creates a new MarketClient that analyzes data from an existing ESI client
+    /// 
+    /// All requests, caching and rate limiting happen in the [`EsiClient`];
+    /// this client only adds analysis on top.
+    pub fn from_esi(esi: EsiClient) -> Self {
         Self {
-            http_client: Client::builder()
-                .user_agent("TraderGrader/0.1.0 (https://github.com/fuuijin/tradergrader)")
-                .build()
-                .expect("Failed to create HTTP client"),
-            cache: None,
-            rate_limiter: EsiRateLimiter::default().expect("Failed to create rate limiter"),
+            esi,
+            order_books: moka::future::Cache::builder()
+                .max_capacity(MAX_INDEXED_BOOKS)
+                .time_to_live(EsiHeaderParser::recommended_ttl_for_data_type("orders"))
+                .build(),
+            static_data: None,
+            history_archive: None,
+            esi_allowlist: EsiAllowlist::default(),
+            trend_config: TrendConfig::default(),
         }
     }
 
+    /// Get the ESI client the analysis draws its data from
+    pub fn esi(&self) -> &EsiClient {
+        &self.esi
+    }
+
+    /// Get the HTTP client configuration used for ESI requests
+    pub fn esi_config(&self) -> &EsiConfig {
+        self.esi.config()
+    }
+
+    /// Reports the ESI connection settings in effect (user agent, DNS, pooling)
+    pub fn diagnostics(&self) -> EsiDiagnostics {
+        self.esi.diagnostics()
+    }
+
+    /// Opens connections to ESI in the background so the first tool call is fast
+    /// 
+    /// Returns `None` when pre-warming is disabled (`prewarm_connections == 0`),
+    /// otherwise a handle resolving to the number of connections established.
+    /// Must be called from within a Tokio runtime.
+    pub fn spawn_prewarm(&self) -> Option<tokio::task::JoinHandle<usize>> {
+        self.esi.spawn_prewarm()
+    }
+
+    /// Get the rate limiter used by this client
+    /// 
+    /// Clone the returned `Arc` to share the limiter with other clients.
+    pub fn rate_limiter(&self) -> &Arc<EsiRateLimiter> {
+        self.esi.rate_limiter()
+    }
+
+    /// Attaches an EVE SSO client, enabling authenticated ESI endpoints
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use std::sync::Arc;
+    /// use tradergrader::{EveSso, MarketClient, SsoConfig};
+    /// 
+    /// let sso = EveSso::new(SsoConfig::new("my-client-id".to_string()))?;
+    /// let client = MarketClient::new().with_authenticator(Arc::new(sso));
+    /// assert!(client.authenticator().is_some());
+    /// # Ok::<(), tradergrader::TraderGraderError>(())
+    /// ```
+    pub fn with_authenticator(mut self, auth: Arc<EveSso>) -> Self {
+        self.esi = self.esi.with_authenticator(auth);
+        self
+    }
+
+    /// Get the EVE SSO client, if authentication is configured
+    pub fn authenticator(&self) -> Option<&Arc<EveSso>> {
+        self.esi.authenticator()
+    }
+
+    /// Attaches a local SDE used for type, system and name lookups before ESI
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use std::sync::Arc;
+    /// use tradergrader::MarketClient;
+    /// use tradergrader::sde::StaticData;
+    /// 
+    /// let client = MarketClient::new().with_static_data(Arc::new(StaticData::default()));
+    /// assert!(client.static_data().is_some());
+    /// ```
+    pub fn with_static_data(mut self, static_data: Arc<StaticData>) -> Self {
+        self.static_data = Some(static_data);
+        self
+    }
+
+    /// Get the local SDE, if one is loaded
+    pub fn static_data(&self) -> Option<&Arc<StaticData>> {
+        self.static_data.as_ref()
+    }
+
+    /// Attaches an archive that keeps history beyond ESI's 13 months
+    pub fn with_history_archive(mut self, archive: Arc<HistoryArchive>) -> Self {
+        self.history_archive = Some(archive);
+        self
+    }
+
+    /// Get the history archive, if one is attached
+    pub fn history_archive(&self) -> Option<&Arc<HistoryArchive>> {
+        self.history_archive.as_ref()
+    }
+
+    /// Replaces the route prefixes reachable through [`esi_get`](Self::esi_get)
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use tradergrader::MarketClient;
+    /// use tradergrader::passthrough::EsiAllowlist;
+    /// 
+    /// let client = MarketClient::new().with_esi_allowlist(EsiAllowlist::new(["/markets/", "/wars/"]));
+    /// assert!(client.esi_allowlist().allows("/wars/"));
+    /// ```
+    pub fn with_esi_allowlist(mut self, allowlist: EsiAllowlist) -> Self {
+        self.esi_allowlist = allowlist;
+        self
+    }
+
+    /// Get the route prefixes reachable through [`esi_get`](Self::esi_get)
+    pub fn esi_allowlist(&self) -> &EsiAllowlist {
+        &self.esi_allowlist
+    }
+
+    /// Replaces the rules behind the trend label of [`analyze_price_trends`](Self::analyze_price_trends)
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use tradergrader::{MarketClient, TrendConfig};
+    /// 
+    /// // Call trends on smaller moves, without waiting for volume
+    /// let config = TrendConfig {
+    ///     change_percent: 1.0,
+    ///     strong_change_percent: 3.0,
+    ///     require_volume_confirmation: false,
+    ///     ..TrendConfig::default()
+    /// };
+    /// let client = MarketClient::new().with_trend_config(config);
+    /// assert_eq!(client.trend_config().change_percent, 1.0);
+    /// ```
+    pub fn with_trend_config(mut self, config: TrendConfig) -> Self {
+        self.trend_config = config;
+        self
+    }
+
+    /// Get the rules behind the trend label of price analysis
+    pub fn trend_config(&self) -> &TrendConfig {
+        &self.trend_config
+    }
+
+    /// Reports ESI requests, cache lookups and retries to an MCP logger
+    pub fn with_logger(mut self, logger: McpLogger) -> Self {
+        self.esi = self.esi.with_logger(logger);
+        self
+    }
+
+    /// Get the MCP logger, if one is attached
+    pub fn logger(&self) -> Option<&McpLogger> {
+        self.esi.logger()
+    }
+
+    /// Replace the response size limits
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use tradergrader::MarketClient;
+    /// use tradergrader::limits::ResponseLimits;
+    /// 
+    /// let client = MarketClient::new().with_response_limits(ResponseLimits::constrained());
+    /// assert_eq!(client.response_limits().max_cached_item_bytes, 1024 * 1024);
+    /// ```
+    pub fn with_response_limits(mut self, limits: ResponseLimits) -> Self {
+        self.esi = self.esi.with_response_limits(limits);
+        self
+    }
+
+    /// Replace how many ESI requests may be open at once
+    /// 
+    /// The pages of a multi-page pull, such as every order in The Forge, are
+    /// fetched this many at a time. Requests still pass the rate limiter first,
+    /// so a higher limit never exceeds the ESI quota. Values below 1 are raised to 1.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use tradergrader::MarketClient;
+    /// 
+    /// let client = MarketClient::new().with_max_concurrent_requests(16);
+    /// assert_eq!(client.max_concurrent_requests(), 16);
+    /// ```
+    pub fn with_max_concurrent_requests(mut self, max_concurrent_requests: usize) -> Self {
+        self.esi = self.esi.with_max_concurrent_requests(max_concurrent_requests);
+        self
+    }
+
+    /// Get how many ESI requests may be open at once
+    pub fn max_concurrent_requests(&self) -> usize {
+        self.esi.max_concurrent_requests()
+    }
+
+    /// Get the response size limits
+    pub fn response_limits(&self) -> &ResponseLimits {
+        self.esi.response_limits()
+    }
+
+    /// Serve cached data up to `max_stale` past its expiry when ESI fails
+    /// 
+    /// Clients built from a [`CacheConfig`] take this from
+    /// [`CacheConfig::serve_stale`]; set it here for custom cache backends.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use std::time::Duration;
+    /// use tradergrader::{CacheConfig, MarketClient};
+    /// 
+    /// let config = CacheConfig::default().with_serve_stale(Duration::from_secs(3600));
+    /// let client = MarketClient::with_cache_config(config)?;
+    /// assert_eq!(client.serve_stale(), Some(Duration::from_secs(3600)));
+    /// # Ok::<(), tradergrader::TraderGraderError>(())
+    /// ```
+    pub fn with_serve_stale(mut self, max_stale: Option<Duration>) -> Self {
+        self.esi = self.esi.with_serve_stale(max_stale);
+        self
+    }
+
+    /// Get how long past expiry cached data may be served when ESI fails
+    pub fn serve_stale(&self) -> Option<Duration> {
+        self.esi.serve_stale()
+    }
+
     /// Check if caching is enabled for this client
     pub fn has_cache(&self) -> bool {
-        self.cache.is_some()
+        self.esi.has_cache()
     }
 
     /// Fetches current market orders for a specific region and optional item type
@@ -147,62 +468,659 @@ impl MarketClient {
         region_id: i32,
         type_id: Option<i32>,
     ) -> Result<Vec<MarketOrder>> {
-        let cache_key = CacheKey::market_orders(region_id, type_id);
+        self.esi.market_orders(region_id, type_id).await
+    }
 
-        // Try to get from cache first
-        if let Some(cache) = &self.cache {
-            if let Some(cached_item) = cache.get::<Vec<MarketOrder>>(&cache_key).await? {
-                return Ok(cached_item.data);
-            }
+    /// Fetches market orders as a typed order book
+    /// 
+    /// The book splits bids from asks, sorts each side by price and groups
+    /// orders by station once, so summaries, depth analysis and undercut checks
+    /// on the same book don't repeat that work. Books are reused for the order
+    /// cache TTL when caching is enabled.
+    /// 
+    /// # Examples
+    /// 
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # use tradergrader::orderbook::BookSide;
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// let book = client.fetch_order_book(10000002, Some(34)).await?;
+    /// if let Some(ask) = book.best_ask() {
+    ///     println!("Cheapest Tritanium: {ask:.2} ISK");
+    /// }
+    /// if let Some(vwap) = book.vwap(BookSide::Ask, 1_000_000) {
+    ///     println!("Average price for 1M units: {vwap:.2} ISK");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn fetch_order_book(&self, region_id: i32, type_id: Option<i32>) -> Result<Arc<MarketOrderBook>> {
+        let key = (region_id, type_id);
+        if let Some(book) = self.order_books.get(&key).await {
+            return Ok(book);
         }
 
-        // Not in cache, fetch from ESI with rate limiting
-        let mut url = format!("https://esi.evetech.net/latest/markets/{region_id}/orders/");
+        let orders = self.fetch_market_orders(region_id, type_id).await?;
+        let book = Arc::new(MarketOrderBook::new(orders));
+        if self.esi.has_cache() {
+            self.order_books.insert(key, Arc::clone(&book)).await;
+        }
+        Ok(book)
+    }
 
-        if let Some(tid) = type_id {
-            url = format!("{url}?type_id={tid}");
+    /// Fetches historical market data for a specific item in a region
+    /// 
+    /// Returns up to 13 months of historical daily market data including
+    /// average price, highest/lowest prices, volume, and order count.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `region_id` - The EVE Online region ID
+    /// * `type_id` - The item type ID to get history for
+    /// 
+    /// # Returns
+    /// 
+    /// Returns a vector of `MarketHistory` structs with daily market data
+    /// 
+    /// # Examples
+    /// 
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// let history = client.fetch_market_history(10000002, 34).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn fetch_market_history(
+        &self,
+        region_id: i32,
+        type_id: i32,
+    ) -> Result<Vec<MarketHistory>> {
+        self.esi.market_history(region_id, type_id).await
+    }
+
+    /// Fetches all market orders in a player-owned structure (citadel market)
+    /// 
+    /// Player structure markets such as Perimeter's Tranquility Trading Tower are
+    /// not part of the public regional order books. This uses the authenticated
+    /// `/markets/structures/{structure_id}/` endpoint and fetches every page. The
+    /// request is made as an authenticated character holding the
+    /// `esi-markets.structure_markets.v1` scope who has docking access.
+    /// 
+    /// # Examples
+    /// 
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # async fn example(client: MarketClient) -> Result<()> {
+    /// // Tranquility Trading Tower in Perimeter
+    /// let orders = client.fetch_structure_orders(1028858195912).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn fetch_structure_orders(&self, structure_id: i64) -> Result<Vec<MarketOrder>> {
+        self.esi.structure_orders(structure_id).await
+    }
+
+    /// Generates a market summary for one item in a player-owned structure
+    /// 
+    /// # Examples
+    /// 
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # async fn example(client: MarketClient) -> Result<()> {
+    /// let summary = client.get_structure_market_summary(1028858195912, 34).await?;
+    /// println!("{}", summary);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_structure_market_summary(&self, structure_id: i64, type_id: i32) -> Result<String> {
+        let orders: Vec<MarketOrder> = self
+            .fetch_structure_orders(structure_id)
+            .await?
+            .into_iter()
+            .filter(|o| o.type_id == type_id)
+            .collect();
+
+        let book = MarketOrderBook::new(orders);
+        let structure = match self.fetch_structure(structure_id).await {
+            Ok(structure) => format!("{} ({structure_id})", structure.name),
+            Err(_) => format!("Structure {structure_id}"),
+        };
+        Ok(format_order_summary(
+            &format!("Market Summary for {} in {structure}", self.type_label(type_id).await),
+            &book,
+            &self.best_order_locations(&book).await,
+        ))
+    }
+
+    /// Fetches all public contracts in a region (every page)
+    /// 
+    /// # Examples
+    /// 
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// let contracts = client.fetch_public_contracts(10000002).await?;
+    /// let couriers = contracts.iter().filter(|c| c.contract_type == "courier").count();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn fetch_public_contracts(&self, region_id: i32) -> Result<Vec<PublicContract>> {
+        self.esi.public_contracts(region_id).await
+    }
+
+    /// Aggregates outstanding courier contracts into going rates per route
+    /// 
+    /// Only courier contracts with a positive volume and reward are counted.
+    /// Routes are sorted by contract count, busiest first.
+    /// 
+    /// # Examples
+    /// 
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// let contracts = client.fetch_public_contracts(10000002).await?;
+    /// for route in MarketClient::courier_route_rates(&contracts) {
+    ///     println!("{} -> {}: {:.0} ISK/m³", route.start_location_id, route.end_location_id, route.median_isk_per_m3);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn courier_route_rates(contracts: &[PublicContract]) -> Vec<CourierRouteRate> {
+        let mut routes: HashMap<(i64, i64), Vec<&PublicContract>> = HashMap::new();
+        for contract in contracts.iter().filter(|c| c.contract_type == "courier") {
+            let (Some(start), Some(end)) = (contract.start_location_id, contract.end_location_id) else {
+                continue;
+            };
+            if contract.volume.unwrap_or(0.0) > 0.0 && contract.reward.unwrap_or(0.0) > 0.0 {
+                routes.entry((start, end)).or_default().push(contract);
+            }
         }
 
-        let response = self.rate_limiter.execute_with_retry(|| async {
-            Ok(self.http_client.get(&url).send().await?)
-        }).await?;
+        let mut rates: Vec<CourierRouteRate> = routes
+            .into_iter()
+            .map(|((start_location_id, end_location_id), contracts)| {
+                let isk_per_m3: Vec<f64> = contracts
+                    .iter()
+                    .map(|c| c.reward.unwrap_or(0.0) / c.volume.unwrap_or(1.0))
+                    .collect();
+                let reward_to_collateral: Vec<f64> = contracts
+                    .iter()
+                    .filter(|c| c.collateral.unwrap_or(0.0) > 0.0)
+                    .map(|c| c.reward.unwrap_or(0.0) / c.collateral.unwrap_or(1.0) * 100.0)
+                    .collect();
+                let days: Vec<f64> = contracts
+                    .iter()
+                    .filter_map(|c| c.days_to_complete.map(f64::from))
+                    .collect();
+
+                CourierRouteRate {
+                    start_location_id,
+                    end_location_id,
+                    contract_count: contracts.len(),
+                    median_isk_per_m3: median(&isk_per_m3).unwrap_or(0.0),
+                    min_isk_per_m3: isk_per_m3.iter().copied().fold(f64::INFINITY, f64::min),
+                    max_isk_per_m3: isk_per_m3.iter().copied().fold(0.0, f64::max),
+                    median_reward: median(&contracts.iter().map(|c| c.reward.unwrap_or(0.0)).collect::<Vec<_>>())
+                        .unwrap_or(0.0),
+                    median_volume: median(&contracts.iter().map(|c| c.volume.unwrap_or(0.0)).collect::<Vec<_>>())
+                        .unwrap_or(0.0),
+                    median_collateral: median(
+                        &contracts.iter().map(|c| c.collateral.unwrap_or(0.0)).collect::<Vec<_>>(),
+                    )
+                    .unwrap_or(0.0),
+                    median_reward_to_collateral_percent: median(&reward_to_collateral),
+                    median_days_to_complete: median(&days),
+                }
+            })
+            .collect();
+
+        rates.sort_by(|a, b| {
+            b.contract_count
+                .cmp(&a.contract_count)
+                .then(a.start_location_id.cmp(&b.start_location_id))
+                .then(a.end_location_id.cmp(&b.end_location_id))
+        });
+        rates
+    }
 
-        if !response.status().is_success() {
-            return Err(
-                format!("ESI API request failed with status: {}", response.status()).into(),
-            );
+    /// Generates a report of going courier rates in a region
+    /// 
+    /// # Arguments
+    /// 
+    /// * `region_id` - Region whose public contracts are scanned
+    /// * `start_location_id` - Only include routes starting at this station or structure
+    /// * `end_location_id` - Only include routes ending at this station or structure
+    /// * `limit` - Maximum number of routes to report
+    /// 
+    /// # Examples
+    /// 
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// // Routes out of Jita 4-4
+    /// let report = client.courier_market_rates(10000002, Some(60003760), None, 10).await?;
+    /// println!("{}", report);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn courier_market_rates(
+        &self,
+        region_id: i32,
+        start_location_id: Option<i64>,
+        end_location_id: Option<i64>,
+        limit: usize,
+    ) -> Result<String> {
+        let contracts = self.fetch_public_contracts(region_id).await?;
+        let rates: Vec<CourierRouteRate> = Self::courier_route_rates(&contracts)
+            .into_iter()
+            .filter(|r| start_location_id.is_none_or(|id| r.start_location_id == id))
+            .filter(|r| end_location_id.is_none_or(|id| r.end_location_id == id))
+            .take(limit)
+            .collect();
+
+        Ok(format_courier_rates(
+            &format!("Courier Market Rates in Region {region_id}"),
+            &rates,
+        ))
+    }
+
+    /// Fetches the open market orders of an authenticated character
+    /// 
+    /// Requires the `esi-markets.read_character_orders.v1` scope.
+    /// 
+    /// # Examples
+    /// 
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # async fn example(client: MarketClient) -> Result<()> {
+    /// let my_orders = client.fetch_character_orders(2112625428).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn fetch_character_orders(&self, character_id: i64) -> Result<Vec<CharacterOrder>> {
+        self.esi.character_orders(character_id).await
+    }
+
+    /// Fetches an authenticated character's recent market transactions
+    /// 
+    /// Requires the `esi-wallet.read_character_wallet.v1` scope. ESI returns
+    /// up to 2500 transactions from the last 30 days, newest first.
+    /// 
+    /// # Examples
+    /// 
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # async fn example(client: MarketClient) -> Result<()> {
+    /// for transaction in client.fetch_wallet_transactions(2112625428).await? {
+    ///     println!("{} x{} at {}", transaction.type_id, transaction.quantity, transaction.unit_price);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn fetch_wallet_transactions(&self, character_id: i64) -> Result<Vec<WalletTransaction>> {
+        self.esi.wallet_transactions(character_id).await
+    }
+
+    /// Checks which of a character's orders have been undercut or outbid
+    /// 
+    /// Each order is compared against the current regional order book for its
+    /// item, considering only competing orders at the same location. The
+    /// character's other orders never count as competition.
+    /// 
+    /// # Examples
+    /// 
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # async fn example(client: MarketClient) -> Result<()> {
+    /// for status in client.check_order_undercuts(2112625428).await? {
+    ///     if status.is_undercut {
+    ///         println!("Order {} undercut by {:?} ISK", status.order.order_id, status.undercut_by);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn check_order_undercuts(&self, character_id: i64) -> Result<Vec<OrderUndercutStatus>> {
+        let my_orders = self.fetch_character_orders(character_id).await?;
+        let own_order_ids: HashSet<i64> = my_orders.iter().map(|o| o.order_id).collect();
+
+        // Fetch each regional order book only once
+        let mut books: HashMap<(i32, i32), Arc<MarketOrderBook>> = HashMap::new();
+        for order in &my_orders {
+            if let std::collections::hash_map::Entry::Vacant(entry) = books.entry((order.region_id, order.type_id)) {
+                entry.insert(self.fetch_order_book(order.region_id, Some(order.type_id)).await?);
+            }
         }
 
-        // Extract headers before consuming response
-        let headers = response.headers().clone();
-        let orders: Vec<MarketOrder> = response.json().await?;
-
-        // Cache the result using ESI headers
-        if let Some(cache) = &self.cache {
-            let cache_item = EsiHeaderParser::create_cache_item_from_response(
-                orders.clone(),
-                &headers,
-                "orders",
-            );
-            let _ = cache.set(&cache_key, cache_item).await; // Ignore cache errors
+        Ok(my_orders
+            .into_iter()
+            .map(|order| {
+                let book = &books[&(order.region_id, order.type_id)];
+                undercut_status(order, book, &own_order_ids)
+            })
+            .collect())
+    }
+
+    /// Generates a comprehensive market summary with buy/sell order analysis
+    /// 
+    /// Analyzes current market orders to provide best buy/sell prices, spreads,
+    /// and order depth information in a human-readable format.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `region_id` - The EVE Online region ID
+    /// * `type_id` - The item type ID to analyze
+    /// 
+    /// # Returns
+    /// 
+    /// Returns a formatted string containing market analysis
+    /// 
+    /// # Examples
+    /// 
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// let summary = client.get_market_summary(10000002, 34).await?;
+    /// println!("{}", summary);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_market_summary(&self, region_id: i32, type_id: i32) -> Result<String> {
+        let cache_key = CacheKey::market_summary(region_id, type_id);
+
+        if let Some(summary) = self.esi.cached::<String>(&cache_key).await? {
+            return Ok(summary);
         }
 
-        Ok(orders)
+        // Not in cache, compute summary
+        let book = self.fetch_order_book(region_id, Some(type_id)).await?;
+
+        let summary = format_order_summary(
+            &format!("Market Summary for {} in Region {region_id}", self.type_label(type_id).await),
+            &book,
+            &self.best_order_locations(&book).await,
+        );
+
+        // Cache the summary using recommended TTL for summary data
+        self.esi.store_cached(&cache_key, summary.clone(), "summary").await;
+
+        Ok(summary)
     }
 
-    /// Fetches historical market data for a specific item in a region
+    /// Analyzes price trends from historical market data
     /// 
-    /// Returns up to 13 months of historical daily market data including
-    /// average price, highest/lowest prices, volume, and order count.
+    /// Calculates daily, weekly, and monthly price changes, volatility metrics,
+    /// and determines the overall trend direction (bullish, bearish, or sideways).
     /// 
     /// # Arguments
     /// 
     /// * `region_id` - The EVE Online region ID
-    /// * `type_id` - The item type ID to get history for
+    /// * `type_id` - The item type ID to analyze
     /// 
     /// # Returns
     /// 
-    /// Returns a vector of `MarketHistory` structs with daily market data
+    /// Returns a `PriceAnalysis` struct with comprehensive trend metrics
+    /// 
+    /// # Examples
+    /// 
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// let analysis = client.analyze_price_trends(10000002, 34).await?;
+    /// println!("Current trend: {}", analysis.trend);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn analyze_price_trends(
+        &self,
+        region_id: i32,
+        type_id: i32,
+    ) -> Result<PriceAnalysis> {
+        let cache_key = CacheKey::price_analysis(region_id, type_id);
+
+        if let Some(analysis) = self.esi.cached::<PriceAnalysis>(&cache_key).await? {
+            return Ok(analysis);
+        }
+
+        // Not in cache, compute analysis over everything archived as well
+        let history = self.fetch_full_history(region_id, type_id).await?;
+        let analysis = Self::analyze_history_with(history, &self.trend_config)?;
+
+        // Cache the analysis using recommended TTL for analysis data
+        self.esi.store_cached(&cache_key, analysis.clone(), "analysis").await;
+
+        Ok(analysis)
+    }
+
+    /// Computes a `PriceAnalysis` from already-fetched market history
+    /// 
+    /// This is the pure analysis step behind `analyze_price_trends`, exposed so that
+    /// callers who obtain history themselves (custom scans, archived data, tests)
+    /// can run the same calculations without going through ESI or the cache.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `history` - Daily market history in any order; it is sorted newest first internally
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use tradergrader::{MarketClient, MarketHistory};
+    /// 
+    /// let history = vec![MarketHistory {
+    ///     average: 5.0,
+    ///     date: "2025-06-22".to_string(),
+    ///     highest: 5.5,
+    ///     lowest: 4.5,
+    ///     order_count: 100,
+    ///     volume: 1_000_000,
+    ///     extra: Default::default(),
+    /// }];
+    /// let analysis = MarketClient::analyze_history(history)?;
+    /// assert_eq!(analysis.current_price, 5.0);
+    /// # Ok::<(), tradergrader::TraderGraderError>(())
+    /// ```
+    pub fn analyze_history(history: Vec<MarketHistory>) -> Result<PriceAnalysis> {
+        Self::analyze_history_with(history, &TrendConfig::default())
+    }
+
+    /// Computes a `PriceAnalysis` with the trend labeled by `trend_config`
+    pub fn analyze_history_with(history: Vec<MarketHistory>, trend_config: &TrendConfig) -> Result<PriceAnalysis> {
+        if history.is_empty() {
+            return Err("No historical data available".into());
+        }
+
+        // Sort by date (newest first)
+        let mut sorted_history = history;
+        sorted_history.sort_by(|a, b| b.date.cmp(&a.date));
+
+        let current_price = sorted_history[0].average;
+        let indicators = TechnicalIndicators::from_history(&sorted_history);
+
+        // Changes are measured against calendar dates, since ESI leaves out days without trades
+        let dated: Vec<(NaiveDate, f64)> = sorted_history
+            .iter()
+            .filter_map(|h| NaiveDate::parse_from_str(&h.date, "%Y-%m-%d").ok().map(|date| (date, h.average)))
+            .collect();
+        let change_since = |days: i64| match average_days_before(&dated, days) {
+            Some(then) if then > 0.0 => (current_price - then, (current_price - then) / then * 100.0),
+            _ => (0.0, 0.0),
+        };
+        let (day_change, day_change_percent) = change_since(1);
+        let (week_change, week_change_percent) = change_since(7);
+        let (month_change, month_change_percent) = change_since(30);
+
+        // Calculate volatility (standard deviation of last 30 days)
+        let recent_prices: Vec<f64> = sorted_history.iter().take(30).map(|h| h.average).collect();
+
+        let mean_price = recent_prices.iter().sum::<f64>() / recent_prices.len() as f64;
+        let variance = recent_prices
+            .iter()
+            .map(|price| (price - mean_price).powi(2))
+            .sum::<f64>()
+            / recent_prices.len() as f64;
+        let volatility = variance.sqrt();
+        let volatility_percent = if mean_price > 0.0 {
+            volatility / mean_price * 100.0
+        } else {
+            0.0
+        };
+
+        // Return-based measures work on the same 30-day window, oldest first
+        let window: Vec<MarketHistory> = sorted_history.iter().take(30).rev().cloned().collect();
+        let window_prices: Vec<f64> = window.iter().map(|h| h.average).collect();
+        let annualized_volatility_percent = indicators::annualized_volatility(&window_prices).unwrap_or(0.0);
+        let average_true_range = indicators::average_true_range(&window, indicators::ATR_PERIOD).unwrap_or(0.0);
+
+        let (trend, trend_evidence) = trend_config.classify(&sorted_history);
+
+        Ok(PriceAnalysis {
+            current_price,
+            day_change,
+            day_change_percent,
+            week_change,
+            week_change_percent,
+            month_change,
+            month_change_percent,
+            volatility,
+            volatility_percent,
+            annualized_volatility_percent,
+            average_true_range,
+            trend,
+            trend_evidence,
+            indicators,
+            data_freshness: DataFreshness::from_history(&sorted_history, FRESHNESS_WINDOW_DAYS).unwrap_or_default(),
+        })
+    }
+
+    /// Generates a formatted technical indicator report
+    /// 
+    /// Reports 7/30-day SMA and EMA, 14-day RSI, MACD and Bollinger bands
+    /// calculated from the item's daily history, with a short reading of each.
+    /// 
+    /// # Examples
+    /// 
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// let report = client.get_technical_indicators_summary(10000002, 34).await?;
+    /// println!("{}", report);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_technical_indicators_summary(&self, region_id: i32, type_id: i32) -> Result<String> {
+        let analysis = self.analyze_price_trends(region_id, type_id).await?;
+        Ok(format_technical_indicators(
+            &format!("Technical Indicators for {} in Region {region_id}", self.type_label(type_id).await),
+            analysis.current_price,
+            &analysis.indicators,
+        ))
+    }
+
+    /// Scores how liquid an item is from its history and current orders
+    /// 
+    /// Averages the last 30 days of volume, order count, ISK turnover and daily
+    /// price range, then combines them into a 0-100 score weighted 40% on
+    /// turnover (log scale, 1M ISK/day = 0 up to 10B ISK/day = 100), 20% on order
+    /// count, 25% on spread (0% = 100 down to 20% = 0) and 15% on the share of
+    /// days with trades. The current spread replaces the historical one when orders
+    /// on both sides exist.
+    /// 
+    /// # Examples
+    /// 
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// let history = client.fetch_market_history(10000002, 34).await?;
+    /// let orders = client.fetch_market_orders(10000002, Some(34)).await?;
+    /// let liquidity = MarketClient::liquidity_score(history, &orders)?;
+    /// println!("Liquidity: {:.0}/100 ({})", liquidity.score, liquidity.rating);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn liquidity_score(history: Vec<MarketHistory>, orders: &[MarketOrder]) -> Result<LiquidityScore> {
+        if history.is_empty() {
+            return Err("No historical data available".into());
+        }
+
+        let mut sorted_history = history;
+        sorted_history.sort_by(|a, b| b.date.cmp(&a.date));
+        let window: Vec<&MarketHistory> = sorted_history.iter().take(LIQUIDITY_WINDOW_DAYS).collect();
+        let days = window.len() as f64;
+
+        let avg_daily_volume = window.iter().map(|h| h.volume as f64).sum::<f64>() / days;
+        let avg_daily_order_count = window.iter().map(|h| h.order_count as f64).sum::<f64>() / days;
+        let avg_daily_turnover = window.iter().map(|h| h.volume as f64 * h.average).sum::<f64>() / days;
+        let avg_spread_percent = window
+            .iter()
+            .filter(|h| h.average > 0.0)
+            .map(|h| (h.highest - h.lowest) / h.average * 100.0)
+            .sum::<f64>()
+            / days;
+        let active_days_percent = window.iter().filter(|h| h.volume > 0).count() as f64 / days * 100.0;
+
+        let best_bid = orders
+            .iter()
+            .filter(|o| o.is_buy_order)
+            .map(|o| o.price)
+            .fold(None, |best: Option<f64>, p| Some(best.map_or(p, |b| b.max(p))));
+        let best_ask = orders
+            .iter()
+            .filter(|o| !o.is_buy_order)
+            .map(|o| o.price)
+            .fold(None, |best: Option<f64>, p| Some(best.map_or(p, |b| b.min(p))));
+        let current_spread_percent = match (best_bid, best_ask) {
+            (Some(bid), Some(ask)) if ask > 0.0 => Some((ask - bid) / ask * 100.0),
+            _ => None,
+        };
+
+        let turnover_score = if avg_daily_turnover > 0.0 {
+            ((avg_daily_turnover.log10() - 6.0) / 4.0 * 100.0).clamp(0.0, 100.0)
+        } else {
+            0.0
+        };
+        let order_score = if avg_daily_order_count > 1.0 {
+            (avg_daily_order_count.log10() / 4.0 * 100.0).clamp(0.0, 100.0)
+        } else {
+            0.0
+        };
+        let spread = current_spread_percent.unwrap_or(avg_spread_percent).max(0.0);
+        let spread_score = ((1.0 - spread / 20.0) * 100.0).clamp(0.0, 100.0);
+
+        let score = turnover_score * 0.40 + order_score * 0.20 + spread_score * 0.25 + active_days_percent * 0.15;
+        let rating = match score {
+            s if s >= 80.0 => "Very High",
+            s if s >= 60.0 => "High",
+            s if s >= 40.0 => "Moderate",
+            s if s >= 20.0 => "Low",
+            _ => "Very Low",
+        }
+        .to_string();
+
+        Ok(LiquidityScore {
+            days: window.len(),
+            avg_daily_volume,
+            avg_daily_order_count,
+            avg_daily_turnover,
+            avg_spread_percent,
+            current_spread_percent,
+            active_days_percent,
+            score,
+            rating,
+        })
+    }
+
+    /// Generates a formatted liquidity report for an item
     /// 
     /// # Examples
     /// 
@@ -210,69 +1128,55 @@ impl MarketClient {
     /// # use tradergrader::{MarketClient, Result};
     /// # async fn example() -> Result<()> {
     /// let client = MarketClient::new();
-    /// let history = client.fetch_market_history(10000002, 34).await?;
+    /// let report = client.get_liquidity_score_summary(10000002, 34).await?;
+    /// println!("{}", report);
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn fetch_market_history(
-        &self,
-        region_id: i32,
-        type_id: i32,
-    ) -> Result<Vec<MarketHistory>> {
-        let cache_key = CacheKey::market_history(region_id, type_id);
-
-        // Try to get from cache first
-        if let Some(cache) = &self.cache {
-            if let Some(cached_item) = cache.get::<Vec<MarketHistory>>(&cache_key).await? {
-                return Ok(cached_item.data);
-            }
-        }
-
-        // Not in cache, fetch from ESI with rate limiting
-        let url = format!(
-            "https://esi.evetech.net/latest/markets/{region_id}/history/?type_id={type_id}"
-        );
-
-        let response = self.rate_limiter.execute_with_retry(|| async {
-            Ok(self.http_client.get(&url).send().await?)
-        }).await?;
-
-        if !response.status().is_success() {
-            return Err(
-                format!("ESI API request failed with status: {}", response.status()).into(),
-            );
-        }
-
-        // Extract headers before consuming response
-        let headers = response.headers().clone();
-        let history: Vec<MarketHistory> = response.json().await?;
-
-        // Cache the result using ESI headers
-        if let Some(cache) = &self.cache {
-            let cache_item = EsiHeaderParser::create_cache_item_from_response(
-                history.clone(),
-                &headers,
-                "history",
-            );
-            let _ = cache.set(&cache_key, cache_item).await; // Ignore cache errors
-        }
+    pub async fn get_liquidity_score_summary(&self, region_id: i32, type_id: i32) -> Result<String> {
+        let history = self.fetch_market_history(region_id, type_id).await?;
+        let book = self.fetch_order_book(region_id, Some(type_id)).await?;
+        let liquidity = Self::liquidity_score(history, book.orders())?;
 
-        Ok(history)
+        Ok(format!(
+            "Liquidity Score for {} in Region {}: {:.0}/100 ({})\n\
+            \n\
+            Based on the last {} days:\n\
+            Average Daily Volume: {:.0} units\n\
+            Average Daily Orders: {:.0}\n\
+            Average Daily Turnover: {:.2} ISK\n\
+            Average Daily Range: {:.2}%\n\
+            Current Spread: {}\n\
+            Days With Trades: {:.0}%",
+            self.type_label(type_id).await,
+            region_id,
+            liquidity.score,
+            liquidity.rating,
+            liquidity.days,
+            liquidity.avg_daily_volume,
+            liquidity.avg_daily_order_count,
+            liquidity.avg_daily_turnover,
+            liquidity.avg_spread_percent,
+            liquidity
+                .current_spread_percent
+                .map(|s| format!("{s:.2}%"))
+                .unwrap_or_else(|| "n/a (one side of the book is empty)".to_string()),
+            liquidity.active_days_percent,
+        ))
     }
 
-    /// Generates a comprehensive market summary with buy/sell order analysis
+    /// Analyzes order book depth for a set of orders
     /// 
-    /// Analyzes current market orders to provide best buy/sell prices, spreads,
-    /// and order depth information in a human-readable format.
+    /// Buckets buy and sell orders by price level, measures cumulative depth at
+    /// ±1%, ±5% and ±10% of the mid-price, flags walls (levels holding at least
+    /// five times the median level volume on their side) and, when `target_price`
+    /// is given, totals the volume and ISK sitting between the current best price
+    /// and the target.
     /// 
     /// # Arguments
     /// 
-    /// * `region_id` - The EVE Online region ID
-    /// * `type_id` - The item type ID to analyze
-    /// 
-    /// # Returns
-    /// 
-    /// Returns a formatted string containing market analysis
+    /// * `orders` - Orders for a single item, normally from one region
+    /// * `target_price` - Optional price to measure the distance to
     /// 
     /// # Examples
     /// 
@@ -280,80 +1184,114 @@ impl MarketClient {
     /// # use tradergrader::{MarketClient, Result};
     /// # async fn example() -> Result<()> {
     /// let client = MarketClient::new();
-    /// let summary = client.get_market_summary(10000002, 34).await?;
-    /// println!("{}", summary);
+    /// let orders = client.fetch_market_orders(10000002, Some(34)).await?;
+    /// let depth = MarketClient::analyze_order_book_depth(&orders, Some(4.5))?;
+    /// println!("ISK to push price to 4.5: {:?}", depth.isk_to_target);
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn get_market_summary(&self, region_id: i32, type_id: i32) -> Result<String> {
-        let cache_key = CacheKey::market_summary(region_id, type_id);
+    pub fn analyze_order_book_depth(orders: &[MarketOrder], target_price: Option<f64>) -> Result<OrderBookDepth> {
+        Self::analyze_market_order_book(&MarketOrderBook::new(orders.to_vec()), target_price)
+    }
 
-        // Try to get from cache first
-        if let Some(cache) = &self.cache {
-            if let Some(cached_item) = cache.get::<String>(&cache_key).await? {
-                return Ok(cached_item.data);
-            }
+    /// Analyzes order book depth using a book's precomputed price levels
+    /// 
+    /// Same analysis as [`analyze_order_book_depth`](Self::analyze_order_book_depth)
+    /// without re-sorting the book.
+    pub fn analyze_market_order_book(book: &MarketOrderBook, target_price: Option<f64>) -> Result<OrderBookDepth> {
+        if book.is_empty() {
+            return Err("No market orders available".into());
         }
 
-        // Not in cache, compute summary
-        let orders = self.fetch_market_orders(region_id, Some(type_id)).await?;
+        let buy_levels = book.buy_levels().to_vec();
+        let sell_levels = book.sell_levels().to_vec();
 
-        let buy_orders: Vec<&MarketOrder> = orders.iter().filter(|o| o.is_buy_order).collect();
-        let sell_orders: Vec<&MarketOrder> = orders.iter().filter(|o| !o.is_buy_order).collect();
+        let best_bid = buy_levels.first().map(|l| l.price);
+        let best_ask = sell_levels.first().map(|l| l.price);
+        let mid_price = match (best_bid, best_ask) {
+            (Some(bid), Some(ask)) => Some((bid + ask) / 2.0),
+            _ => None,
+        };
 
-        let highest_buy = buy_orders
-            .iter()
-            .max_by(|a, b| a.price.partial_cmp(&b.price).unwrap());
-        let lowest_sell = sell_orders
-            .iter()
-            .min_by(|a, b| a.price.partial_cmp(&b.price).unwrap());
+        let bands = match mid_price {
+            Some(mid) => DEPTH_BAND_PERCENTS
+                .iter()
+                .map(|&percent| {
+                    let low = mid * (1.0 - percent / 100.0);
+                    let high = mid * (1.0 + percent / 100.0);
+                    let (buy_volume, buy_isk) = level_totals(buy_levels.iter().filter(|l| l.price >= low));
+                    let (sell_volume, sell_isk) = level_totals(sell_levels.iter().filter(|l| l.price <= high));
+                    DepthBand {
+                        percent,
+                        buy_volume,
+                        buy_isk,
+                        sell_volume,
+                        sell_isk,
+                    }
+                })
+                .collect(),
+            None => Vec::new(),
+        };
 
-        let summary = format!(
-            "Market Summary for Type {} in Region {}:\n\
-            Total Orders: {}\n\
-            Buy Orders: {}\n\
-            Sell Orders: {}\n\
-            Highest Buy: {:.2} ISK\n\
-            Lowest Sell: {:.2} ISK\n\
-            Spread: {:.2} ISK",
-            type_id,
-            region_id,
-            orders.len(),
-            buy_orders.len(),
-            sell_orders.len(),
-            highest_buy.map(|o| o.price).unwrap_or(0.0),
-            lowest_sell.map(|o| o.price).unwrap_or(0.0),
-            if let (Some(sell), Some(buy)) = (lowest_sell, highest_buy) {
-                sell.price - buy.price
-            } else {
-                0.0
+        let mut walls = detect_walls(&buy_levels, true);
+        walls.extend(detect_walls(&sell_levels, false));
+
+        // Pushing the price up means buying out every ask below the target;
+        // pushing it down means filling every bid above it
+        let (volume_to_target, isk_to_target) = match target_price {
+            Some(target) => {
+                let (volume, isk) = match (best_bid, best_ask) {
+                    (_, Some(ask)) if target > ask => {
+                        level_totals(sell_levels.iter().filter(|l| l.price < target))
+                    }
+                    (Some(bid), _) if target < bid => {
+                        level_totals(buy_levels.iter().filter(|l| l.price > target))
+                    }
+                    _ => (0, 0.0),
+                };
+                (Some(volume), Some(isk))
             }
-        );
-
-        // Cache the summary using recommended TTL for summary data
-        if let Some(cache) = &self.cache {
-            use crate::cache::CacheItem;
-            let ttl = EsiHeaderParser::recommended_ttl_for_data_type("summary");
-            let cache_item = CacheItem::new(summary.clone(), ttl);
-            let _ = cache.set(&cache_key, cache_item).await; // Ignore cache errors
-        }
+            None => (None, None),
+        };
 
-        Ok(summary)
+        Ok(OrderBookDepth {
+            best_bid,
+            best_ask,
+            mid_price,
+            buy_levels,
+            sell_levels,
+            bands,
+            walls,
+            target_price,
+            volume_to_target,
+            isk_to_target,
+        })
     }
 
-    /// Analyzes price trends from historical market data
-    /// 
-    /// Calculates daily, weekly, and monthly price changes, volatility metrics,
-    /// and determines the overall trend direction (bullish, bearish, or sideways).
+    /// Fetches orders for an item and analyzes its order book depth
     /// 
-    /// # Arguments
-    /// 
-    /// * `region_id` - The EVE Online region ID
-    /// * `type_id` - The item type ID to analyze
-    /// 
-    /// # Returns
+    /// # Examples
     /// 
-    /// Returns a `PriceAnalysis` struct with comprehensive trend metrics
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// let depth = client.get_order_book_depth(10000002, 34, None).await?;
+    /// println!("Walls: {}", depth.walls.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_order_book_depth(
+        &self,
+        region_id: i32,
+        type_id: i32,
+        target_price: Option<f64>,
+    ) -> Result<OrderBookDepth> {
+        let book = self.fetch_order_book(region_id, Some(type_id)).await?;
+        Self::analyze_market_order_book(&book, target_price)
+    }
+
+    /// Generates a formatted order book depth report
     /// 
     /// # Examples
     /// 
@@ -361,114 +1299,22 @@ impl MarketClient {
     /// # use tradergrader::{MarketClient, Result};
     /// # async fn example() -> Result<()> {
     /// let client = MarketClient::new();
-    /// let analysis = client.analyze_price_trends(10000002, 34).await?;
-    /// println!("Current trend: {}", analysis.trend);
+    /// let report = client.get_order_book_depth_summary(10000002, 34, Some(4.0)).await?;
+    /// println!("{}", report);
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn analyze_price_trends(
+    pub async fn get_order_book_depth_summary(
         &self,
         region_id: i32,
         type_id: i32,
-    ) -> Result<PriceAnalysis> {
-        let cache_key = CacheKey::price_analysis(region_id, type_id);
-
-        // Try to get from cache first
-        if let Some(cache) = &self.cache {
-            if let Some(cached_item) = cache.get::<PriceAnalysis>(&cache_key).await? {
-                return Ok(cached_item.data);
-            }
-        }
-
-        // Not in cache, compute analysis
-        let history = self.fetch_market_history(region_id, type_id).await?;
-
-        if history.is_empty() {
-            return Err("No historical data available".into());
-        }
-
-        // Sort by date (newest first)
-        let mut sorted_history = history;
-        sorted_history.sort_by(|a, b| b.date.cmp(&a.date));
-
-        let current_price = sorted_history[0].average;
-
-        // Calculate changes (day, week, month)
-        let day_change = if sorted_history.len() > 1 {
-            current_price - sorted_history[1].average
-        } else {
-            0.0
-        };
-
-        let week_change = if sorted_history.len() > 7 {
-            current_price - sorted_history[7].average
-        } else {
-            0.0
-        };
-
-        let month_change = if sorted_history.len() > 30 {
-            current_price - sorted_history[30].average
-        } else {
-            0.0
-        };
-
-        // Calculate volatility (standard deviation of last 30 days)
-        let recent_prices: Vec<f64> = sorted_history.iter().take(30).map(|h| h.average).collect();
-
-        let mean_price = recent_prices.iter().sum::<f64>() / recent_prices.len() as f64;
-        let variance = recent_prices
-            .iter()
-            .map(|price| (price - mean_price).powi(2))
-            .sum::<f64>()
-            / recent_prices.len() as f64;
-        let volatility = variance.sqrt();
-
-        // Determine trend
-        let trend = if week_change > current_price * 0.05 {
-            "Strong Upward".to_string()
-        } else if week_change > current_price * 0.02 {
-            "Upward".to_string()
-        } else if week_change < -current_price * 0.05 {
-            "Strong Downward".to_string()
-        } else if week_change < -current_price * 0.02 {
-            "Downward".to_string()
-        } else {
-            "Stable".to_string()
-        };
-
-        let analysis = PriceAnalysis {
-            current_price,
-            day_change,
-            day_change_percent: if sorted_history.len() > 1 {
-                (day_change / sorted_history[1].average) * 100.0
-            } else {
-                0.0
-            },
-            week_change,
-            week_change_percent: if sorted_history.len() > 7 {
-                (week_change / sorted_history[7].average) * 100.0
-            } else {
-                0.0
-            },
-            month_change,
-            month_change_percent: if sorted_history.len() > 30 {
-                (month_change / sorted_history[30].average) * 100.0
-            } else {
-                0.0
-            },
-            volatility,
-            trend,
-        };
-
-        // Cache the analysis using recommended TTL for analysis data
-        if let Some(cache) = &self.cache {
-            use crate::cache::CacheItem;
-            let ttl = EsiHeaderParser::recommended_ttl_for_data_type("analysis");
-            let cache_item = CacheItem::new(analysis.clone(), ttl);
-            let _ = cache.set(&cache_key, cache_item).await; // Ignore cache errors
-        }
-
-        Ok(analysis)
+        target_price: Option<f64>,
+    ) -> Result<String> {
+        let depth = self.get_order_book_depth(region_id, type_id, target_price).await?;
+        Ok(format_order_book_depth(
+            &format!("Order Book Depth for {} in Region {region_id}", self.type_label(type_id).await),
+            &depth,
+        ))
     }
 
     /// Generates a formatted price history summary with trend analysis
@@ -500,7 +1346,7 @@ impl MarketClient {
         let analysis = self.analyze_price_trends(region_id, type_id).await?;
 
         let summary = format!(
-            "Price Analysis for Type {} in Region {}:\n\
+            "Price Analysis for {} in Region {}:\n\
             Current Price: {:.2} ISK\n\
             \n\
             Changes:\n\
@@ -508,9 +1354,12 @@ impl MarketClient {
             Weekly: {:.2} ISK ({:+.2}%)\n\
             Monthly: {:.2} ISK ({:+.2}%)\n\
             \n\
-            Volatility: {:.2} ISK\n\
-            Trend: {}",
-            type_id,
+            Volatility: {:.2} ISK ({:.2}% of mean, {:.1}% annualized)\n\
+            Average True Range (14d): {:.2} ISK\n\
+            Trend: {} ({})\n\
+            \n\
+            {}",
+            self.type_label(type_id).await,
             region_id,
             analysis.current_price,
             analysis.day_change,
@@ -520,13 +1369,300 @@ impl MarketClient {
             analysis.month_change,
             analysis.month_change_percent,
             analysis.volatility,
-            analysis.trend
+            analysis.volatility_percent,
+            analysis.annualized_volatility_percent,
+            analysis.average_true_range,
+            analysis.trend,
+            format_trend_evidence(&analysis.trend_evidence, self.trend_config.window_days),
+            format_freshness(&analysis.data_freshness, chrono::Utc::now().date_naive())
         );
 
         Ok(summary)
     }
 }
 
+/// Describes the price and volume changes behind a trend label
+fn format_trend_evidence(evidence: &TrendEvidence, window_days: u32) -> String {
+    let volume = match evidence.volume_change_percent {
+        Some(change) if evidence.volume_confirmed => format!("volume {change:+.1}%, confirmed"),
+        Some(change) => format!("volume {change:+.1}%"),
+        None => "no earlier volume".to_string(),
+    };
+    format!(
+        "volume-weighted price {:+.2}% over {window_days} days, {volume}",
+        evidence.price_change_percent
+    )
+}
+
+/// Average on the newest day at least `days` before the newest dated row
+///
+/// `dated` is newest first. Falls back to an earlier day when that one had no
+/// trades, and is `None` when the history doesn't reach back that far.
+fn average_days_before(dated: &[(NaiveDate, f64)], days: i64) -> Option<f64> {
+    let (newest, _) = dated.first()?;
+    let target = *newest - chrono::Duration::days(days);
+    dated.iter().find(|(date, _)| *date <= target).map(|(_, average)| *average)
+}
+
+/// Sums volume and ISK value over price levels
+fn level_totals<'a>(levels: impl Iterator<Item = &'a PriceLevel>) -> (i64, f64) {
+    levels.fold((0, 0.0), |(volume, isk), level| {
+        (volume + level.volume, isk + level.price * level.volume as f64)
+    })
+}
+
+/// Flags price levels that dwarf the median level on the same side
+fn detect_walls(levels: &[PriceLevel], is_buy: bool) -> Vec<OrderWall> {
+    if levels.len() < WALL_MIN_LEVELS {
+        return Vec::new();
+    }
+
+    let mut volumes: Vec<i64> = levels.iter().map(|l| l.volume).collect();
+    volumes.sort_unstable();
+    let median = volumes[volumes.len() / 2].max(1) as f64;
+
+    levels
+        .iter()
+        .filter(|l| l.volume as f64 >= median * WALL_MEDIAN_MULTIPLE)
+        .map(|l| OrderWall {
+            is_buy_order: is_buy,
+            price: l.price,
+            volume: l.volume,
+            size_vs_median: l.volume as f64 / median,
+        })
+        .collect()
+}
+
+/// Median of a set of values, `None` when empty
+pub(crate) fn median(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let mid = sorted.len() / 2;
+    Some(if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    })
+}
+
+/// Formats courier route rates as a text report
+fn format_courier_rates(title: &str, rates: &[CourierRouteRate]) -> String {
+    if rates.is_empty() {
+        return format!("{title}:\nNo outstanding courier contracts found");
+    }
+
+    let mut report = format!("{title}:\n");
+    for rate in rates {
+        report.push_str(&format!(
+            "\n{} -> {} ({} contracts)\n\
+            ISK/m³: {:.2} median ({:.2} - {:.2})\n\
+            Reward: {:.2} ISK median for {:.0} m³\n\
+            Collateral: {:.2} ISK median{}\n\
+            Days to complete: {}\n",
+            rate.start_location_id,
+            rate.end_location_id,
+            rate.contract_count,
+            rate.median_isk_per_m3,
+            rate.min_isk_per_m3,
+            rate.max_isk_per_m3,
+            rate.median_reward,
+            rate.median_volume,
+            rate.median_collateral,
+            rate.median_reward_to_collateral_percent
+                .map(|p| format!(" (reward {p:.2}% of collateral)"))
+                .unwrap_or_default(),
+            rate.median_days_to_complete
+                .map(|d| format!("{d:.0}"))
+                .unwrap_or_else(|| "n/a".to_string()),
+        ));
+    }
+    report.trim_end().to_string()
+}
+
+/// Formats technical indicators with a short interpretation of each
+fn format_technical_indicators(title: &str, current_price: f64, indicators: &TechnicalIndicators) -> String {
+    let value = |v: Option<f64>| v.map(|v| format!("{v:.2}")).unwrap_or_else(|| "n/a".to_string());
+
+    let rsi_reading = match indicators.rsi_14 {
+        Some(rsi) if rsi >= 70.0 => " (overbought)",
+        Some(rsi) if rsi <= 30.0 => " (oversold)",
+        Some(_) => " (neutral)",
+        None => "",
+    };
+    let macd_reading = match indicators.macd_histogram {
+        Some(histogram) if histogram > 0.0 => " (bullish momentum)",
+        Some(histogram) if histogram < 0.0 => " (bearish momentum)",
+        _ => "",
+    };
+    let band_reading = match (indicators.bollinger_upper, indicators.bollinger_lower) {
+        (Some(upper), _) if current_price > upper => " (price above upper band)",
+        (_, Some(lower)) if current_price < lower => " (price below lower band)",
+        _ => "",
+    };
+
+    format!(
+        "{}:\n\
+        Current Price: {:.2} ISK\n\
+        \n\
+        Moving Averages:\n\
+        SMA 7: {} / SMA 30: {}\n\
+        EMA 7: {} / EMA 30: {}\n\
+        \n\
+        Momentum:\n\
+        RSI 14: {}{}\n\
+        MACD: {} / Signal: {} / Histogram: {}{}\n\
+        \n\
+        Bollinger Bands (20, 2σ):\n\
+        Upper: {} / Middle: {} / Lower: {}\n\
+        Width: {}{}",
+        title,
+        current_price,
+        value(indicators.sma_7),
+        value(indicators.sma_30),
+        value(indicators.ema_7),
+        value(indicators.ema_30),
+        value(indicators.rsi_14),
+        rsi_reading,
+        value(indicators.macd),
+        value(indicators.macd_signal),
+        value(indicators.macd_histogram),
+        macd_reading,
+        value(indicators.bollinger_upper),
+        value(indicators.bollinger_middle),
+        value(indicators.bollinger_lower),
+        indicators
+            .bollinger_width
+            .map(|w| format!("{:.2}%", w * 100.0))
+            .unwrap_or_else(|| "n/a".to_string()),
+        band_reading,
+    )
+}
+
+/// Formats an order book depth analysis as a text report
+pub(crate) fn format_order_book_depth(title: &str, depth: &OrderBookDepth) -> String {
+    let price = |p: Option<f64>| p.map(|p| format!("{p:.2} ISK")).unwrap_or_else(|| "n/a".to_string());
+
+    let mut report = format!(
+        "{}:\n\
+        Best Bid: {} ({} levels)\n\
+        Best Ask: {} ({} levels)\n\
+        Mid Price: {}\n",
+        title,
+        price(depth.best_bid),
+        depth.buy_levels.len(),
+        price(depth.best_ask),
+        depth.sell_levels.len(),
+        price(depth.mid_price),
+    );
+
+    if !depth.bands.is_empty() {
+        report.push_str("\nCumulative Depth:\n");
+        for band in &depth.bands {
+            report.push_str(&format!(
+                "±{:.0}%: {} bought ({:.2} ISK) / {} sold ({:.2} ISK)\n",
+                band.percent, band.buy_volume, band.buy_isk, band.sell_volume, band.sell_isk
+            ));
+        }
+    }
+
+    report.push_str("\nWalls:\n");
+    if depth.walls.is_empty() {
+        report.push_str("None detected\n");
+    }
+    for wall in &depth.walls {
+        report.push_str(&format!(
+            "{} wall at {:.2} ISK: {} units ({:.1}x median level)\n",
+            if wall.is_buy_order { "Buy" } else { "Sell" },
+            wall.price,
+            wall.volume,
+            wall.size_vs_median
+        ));
+    }
+
+    if let (Some(target), Some(volume), Some(isk)) =
+        (depth.target_price, depth.volume_to_target, depth.isk_to_target)
+    {
+        report.push_str(&format!(
+            "\nTo move price to {target:.2} ISK: {volume} units ({isk:.2} ISK) must trade\n"
+        ));
+    }
+
+    report.trim_end().to_string()
+}
+
+/// Formats best buy/sell, order counts and spread for a set of orders
+///
+/// The best orders are labelled with their station's name when `locations` has it.
+pub(crate) fn format_order_summary(title: &str, book: &MarketOrderBook, locations: &HashMap<i64, String>) -> String {
+    let highest_buy = book.best_buy();
+    let lowest_sell = book.best_sell();
+    let at = |order: Option<&MarketOrder>| {
+        order
+            .and_then(|o| locations.get(&o.location_id))
+            .map(|name| format!(" at {name}"))
+            .unwrap_or_default()
+    };
+
+    format!(
+        "{}:\n\
+        Total Orders: {}\n\
+        Buy Orders: {}\n\
+        Sell Orders: {}\n\
+        Highest Buy: {:.2} ISK{}\n\
+        Lowest Sell: {:.2} ISK{}\n\
+        Spread: {:.2} ISK",
+        title,
+        book.len(),
+        book.buy_count(),
+        book.sell_count(),
+        highest_buy.map(|o| o.price).unwrap_or(0.0),
+        at(highest_buy),
+        lowest_sell.map(|o| o.price).unwrap_or(0.0),
+        at(lowest_sell),
+        if let (Some(sell), Some(buy)) = (lowest_sell, highest_buy) {
+            sell.price - buy.price
+        } else {
+            0.0
+        }
+    )
+}
+
+/// Compares a character order against competing orders at the same location
+fn undercut_status(
+    order: CharacterOrder,
+    book: &MarketOrderBook,
+    own_order_ids: &HashSet<i64>,
+) -> OrderUndercutStatus {
+    let competitors: Vec<f64> = book
+        .at_location(order.location_id)
+        .filter(|o| o.is_buy_order == order.is_buy_order)
+        .filter(|o| !own_order_ids.contains(&o.order_id))
+        .map(|o| o.price)
+        .collect();
+
+    // Sellers compete on the lowest price, buyers on the highest
+    let best_competitor_price = if order.is_buy_order {
+        competitors.iter().copied().reduce(f64::max)
+    } else {
+        competitors.iter().copied().reduce(f64::min)
+    };
+
+    let undercut_by = best_competitor_price
+        .map(|best| if order.is_buy_order { best - order.price } else { order.price - best })
+        .filter(|delta| *delta > 0.0);
+
+    OrderUndercutStatus {
+        is_undercut: undercut_by.is_some(),
+        best_competitor_price,
+        undercut_by,
+        competitor_count: competitors.len(),
+        order,
+    }
+}
+
 impl Default for MarketClient {
     fn default() -> Self {
         Self::new()
@@ -536,6 +1672,9 @@ impl Default for MarketClient {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::TraderGraderError;
+    use crate::test_support::{daily_history, history_day, market_order};
+    use crate::types::PriceTrend;
     // Tests use parent scope types
 
     #[test]
@@ -608,7 +1747,7 @@ mod tests {
 
     #[test]
     fn test_volatility_calculation() {
-        let prices = vec![100.0, 105.0, 95.0, 102.0, 98.0];
+        let prices = [100.0, 105.0, 95.0, 102.0, 98.0];
         let mean = prices.iter().sum::<f64>() / prices.len() as f64;
         
         let variance = prices.iter()
@@ -621,6 +1760,355 @@ mod tests {
         assert!(mean > 90.0 && mean < 110.0);
     }
 
+    #[test]
+    fn test_analyze_history_without_network() {
+        let history: Vec<MarketHistory> = (1..=10)
+            .map(|day| {
+                let average = 100.0 + day as f64;
+                history_day(&format!("2025-06-{day:02}"), average, 1000).with_range(average - 5.0, average + 5.0)
+            })
+            .collect();
+
+        let analysis = MarketClient::analyze_history(history.clone()).expect("Should analyze history");
+        assert_eq!(analysis.current_price, 110.0);
+        assert_eq!(analysis.day_change, 1.0);
+        assert_eq!(analysis.week_change, 7.0);
+        // The last seven days average 4.9% above the three before them
+        assert_eq!(analysis.trend, PriceTrend::Upward);
+        assert!(analysis.trend_evidence.volume_confirmed);
+
+        let eager = TrendConfig {
+            strong_change_percent: 4.0,
+            ..TrendConfig::default()
+        };
+        let analysis = MarketClient::analyze_history_with(history, &eager).unwrap();
+        assert_eq!(analysis.trend, PriceTrend::StrongUpward);
+
+        assert!(MarketClient::analyze_history(Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_analyze_history_measures_changes_by_date() {
+        // Ten days with no trades on the 5th and 6th; a week back from the 10th is the 3rd
+        let history: Vec<MarketHistory> = (1..=10)
+            .filter(|day| *day != 5 && *day != 6)
+            .map(|day| history_day(&format!("2025-06-{day:02}"), 100.0 + day as f64, 1000))
+            .collect();
+
+        let analysis = MarketClient::analyze_history(history.clone()).expect("Should analyze history");
+        assert_eq!(analysis.week_change, 7.0);
+        assert_eq!(analysis.week_change_percent, 7.0 / 103.0 * 100.0);
+        assert_eq!(analysis.month_change, 0.0);
+        assert_eq!(analysis.data_freshness.last_data_date, "2025-06-10");
+        assert_eq!((analysis.data_freshness.days_missing, analysis.data_freshness.longest_gap_days), (2, 2));
+
+        // A missing reference day falls back to the last traded day before it
+        let without_ninth: Vec<MarketHistory> = history.into_iter().filter(|h| h.date != "2025-06-09").collect();
+        let analysis = MarketClient::analyze_history(without_ninth).expect("Should analyze history");
+        assert_eq!(analysis.day_change, 2.0);
+    }
+
+    #[tokio::test]
+    async fn test_analysis_over_cached_esi_data() {
+        use crate::cache::{CacheBackendExt, CacheItem, InMemoryCacheBackend};
+
+        // History already in the cache is analyzed without a request to ESI
+        let cache = Arc::new(InMemoryCacheBackend::new(100, None));
+        let history: Vec<MarketHistory> = (1..=10)
+            .map(|day| history_day(&format!("2025-06-{day:02}"), 100.0 + day as f64, 1000))
+            .collect();
+        cache
+            .set(&CacheKey::market_history(10000002, 34), CacheItem::new(history, Duration::from_secs(3600)))
+            .await
+            .unwrap();
+        let limiter = EsiRateLimiter::shared(RateLimitConfig::testing()).unwrap();
+        let client = MarketClient::from_esi(EsiClient::new(Some(cache), limiter, EsiConfig::default()).unwrap());
+
+        let analysis = client.analyze_price_trends(10000002, 34).await.unwrap();
+        assert_eq!(analysis.current_price, 110.0);
+        assert_eq!(analysis.trend, PriceTrend::Upward);
+    }
+
+    fn test_character_order(order_id: i64, is_buy_order: bool, price: f64) -> CharacterOrder {
+        CharacterOrder {
+            duration: 90,
+            escrow: None,
+            is_buy_order,
+            is_corporation: false,
+            issued: "2025-06-22T10:00:00Z".to_string(),
+            location_id: 60003760,
+            min_volume: None,
+            order_id,
+            price,
+            range: "region".to_string(),
+            region_id: 10000002,
+            type_id: 34,
+            volume_remain: 100,
+            volume_total: 100,
+        }
+    }
+
+    #[test]
+    fn test_undercut_status_sell_order() {
+        let book = vec![
+            market_order(1, false, 10.0), // our own order
+            market_order(2, false, 9.5),  // undercutting competitor
+            market_order(3, false, 8.0).with_location(60008494),  // cheaper, but in another station
+            market_order(4, true, 12.0),  // buy side, irrelevant
+        ];
+        let own: HashSet<i64> = [1].into_iter().collect();
+
+        let status = undercut_status(test_character_order(1, false, 10.0), &MarketOrderBook::new(book), &own);
+        assert!(status.is_undercut);
+        assert_eq!(status.best_competitor_price, Some(9.5));
+        assert_eq!(status.undercut_by, Some(0.5));
+        assert_eq!(status.competitor_count, 1);
+    }
+
+    #[test]
+    fn test_undercut_status_buy_order() {
+        let book = vec![
+            market_order(1, true, 10.0),
+            market_order(2, true, 9.0),
+        ];
+        let own: HashSet<i64> = [1].into_iter().collect();
+
+        let status = undercut_status(test_character_order(1, true, 10.0), &MarketOrderBook::new(book.clone()), &own);
+        assert!(!status.is_undercut);
+        assert_eq!(status.best_competitor_price, Some(9.0));
+        assert!(status.undercut_by.is_none());
+
+        let alone = undercut_status(test_character_order(1, true, 10.0), &MarketOrderBook::new(book[..1].to_vec()), &own);
+        assert!(!alone.is_undercut);
+        assert_eq!(alone.competitor_count, 0);
+    }
+
+    #[test]
+    fn test_format_order_summary() {
+        let orders = vec![
+            market_order(1, true, 9.0),
+            market_order(2, false, 10.0),
+            market_order(3, false, 11.0),
+        ];
+
+        let book = MarketOrderBook::new(orders);
+        let summary = format_order_summary("Market Summary for Type 34 in Structure 1", &book, &HashMap::new());
+        assert!(summary.starts_with("Market Summary for Type 34 in Structure 1:"));
+        assert!(summary.contains("Total Orders: 3"));
+        assert!(summary.contains("Highest Buy: 9.00 ISK\n"));
+        assert!(summary.contains("Lowest Sell: 10.00 ISK\n"));
+
+        let jita = HashMap::from([(60003760, "Jita IV - Moon 4 - Caldari Navy Assembly Plant".to_string())]);
+        let summary = format_order_summary("Market Summary for Type 34 in Region 10000002", &book, &jita);
+        assert!(summary.contains("Lowest Sell: 10.00 ISK at Jita IV - Moon 4 - Caldari Navy Assembly Plant\n"));
+        assert!(summary.contains("Spread: 1.00 ISK"));
+    }
+
+    #[test]
+    fn test_analyze_history_includes_indicators() {
+        let history: Vec<MarketHistory> = daily_history("2025-05-01", (0..40).map(|day| (100.0 + day as f64, 1_000)))
+            .into_iter()
+            .map(|day| {
+                let average = day.average;
+                day.with_range(average - 1.0, average + 1.0)
+            })
+            .collect();
+
+        let analysis = MarketClient::analyze_history(history).unwrap();
+        assert_eq!(analysis.indicators.sma_7, Some(136.0));
+        assert!(analysis.volatility_percent > 0.0);
+        assert!(analysis.annualized_volatility_percent > 0.0);
+        // Each day spans 2 ISK and gaps 1 ISK from the previous average
+        assert_eq!(analysis.average_true_range, 2.0);
+        assert_eq!(analysis.indicators.rsi_14, Some(100.0));
+        assert!(analysis.indicators.macd_histogram.is_some());
+
+        let report = format_technical_indicators("Technical Indicators", analysis.current_price, &analysis.indicators);
+        assert!(report.contains("RSI 14: 100.00 (overbought)"));
+        assert!(report.contains("SMA 7: 136.00"));
+    }
+
+    fn test_courier_contract(contract_id: i64, route: (i64, i64), reward: f64, volume: f64) -> PublicContract {
+        PublicContract {
+            contract_id,
+            contract_type: "courier".to_string(),
+            date_issued: "2025-06-22T10:00:00Z".to_string(),
+            date_expired: "2025-07-06T10:00:00Z".to_string(),
+            issuer_id: 1,
+            issuer_corporation_id: 2,
+            for_corporation: false,
+            title: None,
+            start_location_id: Some(route.0),
+            end_location_id: Some(route.1),
+            price: None,
+            buyout: None,
+            reward: Some(reward),
+            collateral: Some(reward * 20.0),
+            volume: Some(volume),
+            days_to_complete: Some(3),
+        }
+    }
+
+    #[test]
+    fn test_courier_route_rates() {
+        let jita_amarr = (60003760, 60008494);
+        let jita_dodixie = (60003760, 60011866);
+        let mut contracts = vec![
+            test_courier_contract(1, jita_amarr, 1_000_000.0, 1_000.0),
+            test_courier_contract(2, jita_amarr, 3_000_000.0, 1_000.0),
+            test_courier_contract(3, jita_amarr, 100_000_000.0, 1_000.0),
+            test_courier_contract(4, jita_dodixie, 500_000.0, 1_000.0),
+            // Ignored: no volume
+            test_courier_contract(5, jita_dodixie, 500_000.0, 0.0),
+        ];
+        let mut exchange = test_courier_contract(6, jita_amarr, 1.0, 1.0);
+        exchange.contract_type = "item_exchange".to_string();
+        contracts.push(exchange);
+
+        let rates = MarketClient::courier_route_rates(&contracts);
+        assert_eq!(rates.len(), 2);
+
+        let busiest = &rates[0];
+        assert_eq!((busiest.start_location_id, busiest.end_location_id), jita_amarr);
+        assert_eq!(busiest.contract_count, 3);
+        // The 100M outlier doesn't move the median
+        assert_eq!(busiest.median_isk_per_m3, 3_000.0);
+        assert_eq!(busiest.max_isk_per_m3, 100_000.0);
+        assert_eq!(busiest.median_reward_to_collateral_percent, Some(5.0));
+        assert_eq!(busiest.median_days_to_complete, Some(3.0));
+
+        assert_eq!(rates[1].contract_count, 1);
+
+        let report = format_courier_rates("Courier Market Rates in Region 10000002", &rates);
+        assert!(report.contains("60003760 -> 60008494 (3 contracts)"));
+        assert!(format_courier_rates("Courier", &[]).contains("No outstanding courier contracts"));
+    }
+
+    #[test]
+    fn test_median() {
+        assert_eq!(median(&[]), None);
+        assert_eq!(median(&[3.0, 1.0, 2.0]), Some(2.0));
+        assert_eq!(median(&[4.0, 1.0, 2.0, 3.0]), Some(2.5));
+    }
+
+    #[test]
+    fn test_liquidity_score_liquid_item() {
+        // Tritanium-like: billions of ISK per day, thousands of orders, tight spread
+        let history: Vec<MarketHistory> = (1..=30)
+            .map(|day| {
+                history_day(&format!("2025-06-{day:02}"), 5.0, 2_000_000_000)
+                    .with_range(4.95, 5.05)
+                    .with_order_count(8_000)
+            })
+            .collect();
+        let orders = vec![market_order(1, true, 4.99), market_order(2, false, 5.0)];
+
+        let liquidity = MarketClient::liquidity_score(history, &orders).unwrap();
+        assert_eq!(liquidity.days, 30);
+        assert_eq!(liquidity.avg_daily_turnover, 10_000_000_000.0);
+        assert!((liquidity.avg_spread_percent - 2.0).abs() < 1e-9);
+        assert!((liquidity.current_spread_percent.unwrap() - 0.2).abs() < 1e-9);
+        assert_eq!(liquidity.active_days_percent, 100.0);
+        assert!(liquidity.score > 95.0);
+        assert_eq!(liquidity.rating, "Very High");
+    }
+
+    #[test]
+    fn test_liquidity_score_illiquid_item() {
+        let mut history: Vec<MarketHistory> = (1..=30)
+            .map(|day| {
+                history_day(&format!("2025-06-{day:02}"), 1_000_000.0, 0)
+                    .with_range(990_000.0, 1_010_000.0)
+                    .with_order_count(0)
+            })
+            .collect();
+        history[0].volume = 1;
+        history[0].order_count = 1;
+
+        let liquidity = MarketClient::liquidity_score(history, &[]).unwrap();
+        assert!(liquidity.current_spread_percent.is_none());
+        assert!(liquidity.score < 30.0);
+        assert!(MarketClient::liquidity_score(Vec::new(), &[]).is_err());
+    }
+
+    #[test]
+    fn test_order_book_depth_levels_and_bands() {
+        let mut orders = vec![
+            market_order(1, true, 99.5),
+            market_order(2, true, 99.5),
+            market_order(3, true, 94.0),
+            market_order(4, false, 100.5),
+            market_order(5, false, 104.0),
+            market_order(6, false, 120.0),
+        ];
+        orders[5].volume_remain = 50;
+
+        let depth = MarketClient::analyze_order_book_depth(&orders, None).unwrap();
+        assert_eq!(depth.best_bid, Some(99.5));
+        assert_eq!(depth.best_ask, Some(100.5));
+        assert_eq!(depth.mid_price, Some(100.0));
+
+        // Equal prices collapse into one level
+        assert_eq!(depth.buy_levels.len(), 2);
+        assert_eq!(depth.buy_levels[0].order_count, 2);
+        assert_eq!(depth.buy_levels[0].volume, 200);
+
+        let one = &depth.bands[0];
+        assert_eq!(one.percent, 1.0);
+        assert_eq!(one.buy_volume, 200);
+        assert_eq!(one.sell_volume, 100);
+
+        let ten = &depth.bands[2];
+        assert_eq!(ten.buy_volume, 300);
+        assert_eq!(ten.sell_volume, 200);
+        assert!(depth.volume_to_target.is_none());
+    }
+
+    #[test]
+    fn test_order_book_depth_walls_and_target() {
+        let mut orders: Vec<MarketOrder> = (0..5)
+            .map(|i| market_order(i, false, 10.0 + i as f64))
+            .collect();
+        orders[3].volume_remain = 5_000;
+        orders.push(market_order(10, true, 9.0));
+
+        let depth = MarketClient::analyze_order_book_depth(&orders, Some(12.5)).unwrap();
+        assert_eq!(depth.walls.len(), 1);
+        assert!(!depth.walls[0].is_buy_order);
+        assert_eq!(depth.walls[0].price, 13.0);
+
+        // Asks at 10, 11 and 12 must be bought to reach 12.5
+        assert_eq!(depth.volume_to_target, Some(300));
+        assert_eq!(depth.isk_to_target, Some(3_300.0));
+
+        // A target inside the spread needs no trading
+        let depth = MarketClient::analyze_order_book_depth(&orders, Some(9.5)).unwrap();
+        assert_eq!(depth.volume_to_target, Some(0));
+
+        let report = format_order_book_depth("Order Book Depth for Type 34 in Region 1", &depth);
+        assert!(report.contains("Sell wall at 13.00 ISK"));
+        assert!(report.contains("To move price to 9.50 ISK"));
+    }
+
+    #[test]
+    fn test_order_book_depth_requires_orders() {
+        assert!(MarketClient::analyze_order_book_depth(&[], None).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_structure_orders_require_authentication() {
+        let client = MarketClient::without_cache();
+        let result = client.fetch_structure_orders(1028858195912).await;
+        assert!(matches!(result, Err(TraderGraderError::AuthenticationError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_character_orders_require_authentication() {
+        let client = MarketClient::without_cache();
+        let result = client.fetch_character_orders(1).await;
+        assert!(matches!(result, Err(TraderGraderError::AuthenticationError(_))));
+    }
+
     #[test]
     fn test_market_client_cache_configurations() {
         use crate::cache::CacheConfig;
@@ -659,12 +2147,67 @@ mod tests {
             .expect("Should create client with custom configs");
         
         assert!(client.has_cache());
-        assert_eq!(client.rate_limiter.config().requests_per_second, 50); // Conservative setting
+        assert_eq!(client.rate_limiter().config().requests_per_second, 50); // Conservative setting
         
         // Test default configurations
         let default_client = MarketClient::new();
         assert!(default_client.has_cache());
-        assert_eq!(default_client.rate_limiter.config().requests_per_second, 100); // Default ESI limit
+        assert_eq!(default_client.rate_limiter().config().requests_per_second, 100); // Default ESI limit
+    }
+
+    #[test]
+    fn test_market_client_esi_config() {
+        use crate::cache::CacheConfig;
+        use crate::rate_limit::RateLimitConfig;
+
+        let client = MarketClient::new();
+        assert_eq!(client.esi_config(), &EsiConfig::default());
+
+        let tuned = MarketClient::with_esi_config(
+            CacheConfig::disabled(),
+            RateLimitConfig::default(),
+            EsiConfig::minimal(),
+        )
+        .expect("Should create client with ESI config");
+        assert_eq!(tuned.esi_config().pool_max_idle_per_host, 1);
+
+        let diagnostics = tuned.diagnostics();
+        assert_eq!(diagnostics.pool_max_idle_per_host, 1);
+        assert_eq!(diagnostics.dns_cached_hosts, 0);
+    }
+
+    #[tokio::test]
+    async fn test_spawn_prewarm_disabled() {
+        use crate::cache::CacheConfig;
+        use crate::rate_limit::RateLimitConfig;
+
+        let client = MarketClient::with_esi_config(
+            CacheConfig::disabled(),
+            RateLimitConfig::default(),
+            EsiConfig::minimal(),
+        )
+        .expect("Should create client");
+        assert!(client.spawn_prewarm().is_none());
+    }
+
+    #[test]
+    fn test_market_clients_share_rate_limiter() {
+        use crate::cache::CacheConfig;
+        use crate::rate_limit::RateLimitConfig;
+
+        let limiter = EsiRateLimiter::shared(RateLimitConfig::conservative())
+            .expect("Should create shared limiter");
+        let first = MarketClient::with_shared_rate_limiter(CacheConfig::default(), Arc::clone(&limiter))
+            .expect("Should create first client");
+        let second = MarketClient::with_shared_rate_limiter(CacheConfig::disabled(), Arc::clone(&limiter))
+            .expect("Should create second client");
+
+        assert!(Arc::ptr_eq(first.rate_limiter(), second.rate_limiter()));
+        assert_eq!(Arc::strong_count(&limiter), 3);
+
+        // Independently configured clients get their own limiter
+        let independent = MarketClient::new();
+        assert!(!Arc::ptr_eq(first.rate_limiter(), independent.rate_limiter()));
     }
 }
 
@@ -0,0 +1,250 @@
+//! Market group browsing for TraderGrader
+//!
+//! ESI's `/markets/groups/` tree is how the in-game market browser organizes
+//! items (Materials > Minerals > Tritanium). Browsing it lets a caller go from
+//! a category name to type IDs without knowing them up front. The tree only
+//! changes with game patches, so groups are cached for a week; the first full
+//! listing fetches every group once.
+
+use crate::cache::CacheKey;
+use crate::error::Result;
+use crate::market::MarketClient;
+use crate::paging::Page;
+use crate::scan::SCAN_CONCURRENCY;
+use crate::types::MarketGroupInfo;
+use futures::stream::{self, StreamExt, TryStreamExt};
+
+/// Groups listed per page unless the caller asks for more
+pub const DEFAULT_GROUPS_PAGE: usize = 50;
+
+/// Items listed per page of a market group unless the caller asks for more
+pub const DEFAULT_GROUP_TYPES_PAGE: usize = 100;
+
+impl MarketClient {
+    /// Fetches a market group's name, parent and item types
+    pub async fn fetch_market_group(&self, market_group_id: i32) -> Result<MarketGroupInfo> {
+        self.esi().get_static(
+            &format!("/markets/groups/{market_group_id}/"),
+            &CacheKey::universe("market_group", market_group_id as i64),
+            "types",
+        )
+        .await
+    }
+
+    /// Fetches the IDs of every market group
+    pub async fn fetch_market_group_ids(&self) -> Result<Vec<i32>> {
+        self.esi().get_static("/markets/groups/", &CacheKey::universe("market_groups", 0), "types")
+            .await
+    }
+
+    /// Fetches every market group
+    ///
+    /// The assembled list is cached as a whole, so only the first call after a
+    /// week pays for fetching each group.
+    pub async fn fetch_market_groups(&self) -> Result<Vec<MarketGroupInfo>> {
+        let cache_key = CacheKey::universe("market_group_tree", 0);
+        if let Some(groups) = self.esi().cached::<Vec<MarketGroupInfo>>(&cache_key).await? {
+            return Ok(groups);
+        }
+
+        let ids = self.fetch_market_group_ids().await?;
+        let mut groups: Vec<MarketGroupInfo> = stream::iter(ids)
+            .map(|id| self.fetch_market_group(id))
+            .buffer_unordered(SCAN_CONCURRENCY)
+            .try_collect()
+            .await?;
+        groups.sort_by_key(|g| g.market_group_id);
+
+        self.esi().store_cached(&cache_key, groups.clone(), "types").await;
+        Ok(groups)
+    }
+
+    /// Lists market groups under a parent, or matching a name search
+    ///
+    /// Without a parent or search the top-level categories are returned. A
+    /// search matches group names case-insensitively anywhere in the tree.
+    /// Only the groups on `page` are listed, with a note when there are more.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// use tradergrader::paging::Page;
+    ///
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// let report = client.list_market_groups_summary(None, Some("minerals"), Page::first(50)).await?;
+    /// println!("{}", report);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn list_market_groups_summary(
+        &self,
+        parent_group_id: Option<i32>,
+        search: Option<&str>,
+        page: Page,
+    ) -> Result<String> {
+        let groups = self.fetch_market_groups().await?;
+        let (title, listed) = match (search, parent_group_id) {
+            (Some(query), _) => (format!("Market groups matching \"{query}\""), search_groups(&groups, query)),
+            (None, Some(parent)) => {
+                let name = groups
+                    .iter()
+                    .find(|g| g.market_group_id == parent)
+                    .map_or_else(|| parent.to_string(), |g| format!("{} ({parent})", g.name));
+                (format!("Market groups in {name}"), child_groups(&groups, Some(parent)))
+            }
+            (None, None) => ("Top-level market groups".to_string(), child_groups(&groups, None)),
+        };
+        Ok(format_market_groups(&title, &listed, &groups, page))
+    }
+
+    /// Lists the item types on `page` of a market group, by name
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// use tradergrader::paging::Page;
+    ///
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// // Minerals
+    /// let report = client.get_market_group_types_summary(1857, Page::first(100)).await?;
+    /// println!("{}", report);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_market_group_types_summary(&self, market_group_id: i32, page: Page) -> Result<String> {
+        let group = self.fetch_market_group(market_group_id).await?;
+        if group.types.is_empty() {
+            return Ok(format!(
+                "{} ({}) has no items of its own; list its subgroups with list_market_groups",
+                group.name, market_group_id
+            ));
+        }
+
+        let ids: Vec<i64> = group.types.iter().map(|&id| id as i64).collect();
+        let names = self.resolve_names(&ids).await?;
+        let mut items: Vec<(String, i32)> = group
+            .types
+            .iter()
+            .map(|&id| {
+                let name = names.get(&(id as i64)).map_or_else(|| format!("Type {id}"), |n| n.name.clone());
+                (name, id)
+            })
+            .collect();
+        items.sort();
+
+        let mut report = format!("{} ({}): {} items\n", group.name, market_group_id, items.len());
+        for (name, id) in page.slice(&items) {
+            report.push_str(&format!("- {name} ({id})\n"));
+        }
+        if let Some(marker) = page.marker(items.len(), "items") {
+            report.push_str(&format!("{marker}\n"));
+        }
+        Ok(report.trim_end().to_string())
+    }
+}
+
+/// Groups directly under `parent` (top-level groups for `None`), by name
+fn child_groups(groups: &[MarketGroupInfo], parent: Option<i32>) -> Vec<&MarketGroupInfo> {
+    let mut children: Vec<&MarketGroupInfo> = groups.iter().filter(|g| g.parent_group_id == parent).collect();
+    children.sort_by(|a, b| a.name.cmp(&b.name));
+    children
+}
+
+/// Groups whose name contains `query`, ignoring case, by name
+fn search_groups<'a>(groups: &'a [MarketGroupInfo], query: &str) -> Vec<&'a MarketGroupInfo> {
+    let query = query.to_lowercase();
+    let mut matches: Vec<&MarketGroupInfo> = groups
+        .iter()
+        .filter(|g| g.name.to_lowercase().contains(&query))
+        .collect();
+    matches.sort_by(|a, b| a.name.cmp(&b.name));
+    matches
+}
+
+/// Formats a list of groups with their item and subgroup counts
+fn format_market_groups(title: &str, listed: &[&MarketGroupInfo], all: &[MarketGroupInfo], page: Page) -> String {
+    if listed.is_empty() {
+        return format!("{title}: none found");
+    }
+
+    let mut report = format!("{title}:\n");
+    for group in page.slice(listed) {
+        let subgroups = all
+            .iter()
+            .filter(|g| g.parent_group_id == Some(group.market_group_id))
+            .count();
+        let contents = match (group.types.len(), subgroups) {
+            (0, n) => format!("{n} subgroups"),
+            (items, 0) => format!("{items} items"),
+            (items, n) => format!("{items} items, {n} subgroups"),
+        };
+        report.push_str(&format!("- {} ({}): {}\n", group.name, group.market_group_id, contents));
+    }
+    if let Some(marker) = page.marker(listed.len(), "groups") {
+        report.push_str(&format!("{marker}\n"));
+    }
+    report.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(market_group_id: i32, name: &str, parent_group_id: Option<i32>, types: Vec<i32>) -> MarketGroupInfo {
+        MarketGroupInfo {
+            market_group_id,
+            name: name.to_string(),
+            description: String::new(),
+            parent_group_id,
+            types,
+        }
+    }
+
+    fn tree() -> Vec<MarketGroupInfo> {
+        vec![
+            group(533, "Materials", None, vec![]),
+            group(4, "Ships", None, vec![]),
+            group(1857, "Minerals", Some(533), vec![34, 35, 36]),
+            group(1031, "Raw Materials", Some(533), vec![]),
+            group(1855, "Ice Ores", Some(1031), vec![16262]),
+        ]
+    }
+
+    #[test]
+    fn test_child_groups() {
+        let groups = tree();
+        let top: Vec<&str> = child_groups(&groups, None).iter().map(|g| g.name.as_str()).collect();
+        assert_eq!(top, vec!["Materials", "Ships"]);
+
+        let materials: Vec<i32> = child_groups(&groups, Some(533)).iter().map(|g| g.market_group_id).collect();
+        assert_eq!(materials, vec![1857, 1031]);
+    }
+
+    #[test]
+    fn test_search_groups() {
+        let groups = tree();
+        let found: Vec<i32> = search_groups(&groups, "MINERAL").iter().map(|g| g.market_group_id).collect();
+        assert_eq!(found, vec![1857]);
+        assert_eq!(search_groups(&groups, "materials").len(), 2);
+        assert!(search_groups(&groups, "drones").is_empty());
+    }
+
+    #[test]
+    fn test_format_market_groups() {
+        let groups = tree();
+        let materials = child_groups(&groups, Some(533));
+        let report = format_market_groups("Market groups in Materials (533)", &materials, &groups, Page::first(50));
+        assert!(report.contains("- Minerals (1857): 3 items"));
+        assert!(report.contains("- Raw Materials (1031): 1 subgroups"));
+
+        let report = format_market_groups("Market groups in Materials (533)", &materials, &groups, Page::first(1));
+        assert!(!report.contains("Raw Materials"));
+        assert!(report.ends_with("Showing 1–1 of 2 groups; pass offset=1 for more"));
+
+        assert_eq!(format_market_groups("Nothing", &[], &groups, Page::first(50)), "Nothing: none found");
+    }
+}
@@ -0,0 +1,336 @@
+//! Cross-region price matrix for TraderGrader
+//!
+//! Traders keep spreadsheets of item prices across hubs and regions to spot
+//! where an item is cheap and where it sells dear. The matrix puts the best
+//! sell (and optionally buy) price of every requested item in every requested
+//! region into one table and marks each row's cheapest and dearest region.
+//! Prices are region-wide best orders; use `compare_trade_hubs` for station-only
+//! hub prices.
+
+use crate::error::{Result, TraderGraderError};
+use crate::market::MarketClient;
+use crate::scan::SCAN_CONCURRENCY;
+use crate::types::{PriceMatrix, PriceMatrixCell, PriceMatrixRow};
+use futures::stream::{self, StreamExt};
+use std::collections::HashMap;
+
+/// Most item-region cells in one matrix; each cell is one order book fetch
+pub const MAX_MATRIX_CELLS: usize = 200;
+
+impl MarketClient {
+    /// Builds a matrix of best prices for items across regions
+    ///
+    /// Order books are fetched concurrently (at most [`SCAN_CONCURRENCY`] at
+    /// once). A region that fails to fetch leaves an error in its cell rather
+    /// than failing the whole matrix.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// // Tritanium and Pyerite in The Forge, Domain and Sinq Laison
+    /// let matrix = client.price_matrix(&[34, 35], &[10000002, 10000043, 10000032], true).await?;
+    /// for row in &matrix.rows {
+    ///     println!("{}: cheapest in {:?}", row.type_label, row.min_sell_region_id);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn price_matrix(
+        &self,
+        type_ids: &[i32],
+        region_ids: &[i32],
+        include_buy: bool,
+    ) -> Result<PriceMatrix> {
+        if type_ids.is_empty() || region_ids.is_empty() {
+            return Err(TraderGraderError::InvalidParams(
+                "A price matrix needs at least one item and one region".to_string(),
+            ));
+        }
+        let cells = type_ids.len() * region_ids.len();
+        if cells > MAX_MATRIX_CELLS {
+            return Err(TraderGraderError::InvalidParams(format!(
+                "{} items x {} regions is {cells} cells, more than the {MAX_MATRIX_CELLS} allowed",
+                type_ids.len(),
+                region_ids.len()
+            )));
+        }
+
+        let pairs = type_ids.iter().flat_map(|&type_id| {
+            region_ids
+                .iter()
+                .map(move |&region_id| (type_id, region_id))
+        });
+        let mut fetched: HashMap<(i32, i32), PriceMatrixCell> = stream::iter(pairs)
+            .map(|(type_id, region_id)| async move {
+                let cell = match self.fetch_order_book(region_id, Some(type_id)).await {
+                    Ok(book) => PriceMatrixCell {
+                        region_id,
+                        best_sell: book.best_ask(),
+                        best_buy: book.best_bid(),
+                        error: None,
+                    },
+                    Err(e) => PriceMatrixCell {
+                        region_id,
+                        best_sell: None,
+                        best_buy: None,
+                        error: Some(e.to_string()),
+                    },
+                };
+                ((type_id, region_id), cell)
+            })
+            .buffer_unordered(SCAN_CONCURRENCY)
+            .collect()
+            .await;
+
+        let ids: Vec<i64> = type_ids
+            .iter()
+            .chain(region_ids)
+            .map(|&id| id as i64)
+            .collect();
+        let names = self.resolve_names(&ids).await.unwrap_or_default();
+        let name = |id: i32| names.get(&(id as i64)).map(|n| n.name.clone());
+
+        let rows = type_ids
+            .iter()
+            .map(|&type_id| {
+                let cells = region_ids
+                    .iter()
+                    .filter_map(|&region_id| fetched.remove(&(type_id, region_id)))
+                    .collect();
+                let type_label = match name(type_id) {
+                    Some(item) => format!("{item} ({type_id})"),
+                    None => format!("Type {type_id}"),
+                };
+                matrix_row(type_id, type_label, cells)
+            })
+            .collect();
+
+        Ok(PriceMatrix {
+            region_ids: region_ids.to_vec(),
+            region_labels: region_ids
+                .iter()
+                .map(|&id| name(id).unwrap_or_else(|| format!("Region {id}")))
+                .collect(),
+            include_buy,
+            rows,
+        })
+    }
+}
+
+/// Builds a row and marks its extremes
+fn matrix_row(type_id: i32, type_label: String, cells: Vec<PriceMatrixCell>) -> PriceMatrixRow {
+    let sell = extremes(&cells, |cell| cell.best_sell);
+    let buy = extremes(&cells, |cell| cell.best_buy);
+    let sell_spread_percent = match sell {
+        Some(((_, low), (_, high))) if low > 0.0 => Some((high - low) / low * 100.0),
+        _ => None,
+    };
+
+    PriceMatrixRow {
+        type_id,
+        type_label,
+        cells,
+        min_sell_region_id: sell.map(|((region_id, _), _)| region_id),
+        max_sell_region_id: sell.map(|(_, (region_id, _))| region_id),
+        min_buy_region_id: buy.map(|((region_id, _), _)| region_id),
+        max_buy_region_id: buy.map(|(_, (region_id, _))| region_id),
+        sell_spread_percent,
+    }
+}
+
+/// Lowest and highest priced `(region_id, price)`, unless fewer than two regions have a price
+fn extremes(
+    cells: &[PriceMatrixCell],
+    price: impl Fn(&PriceMatrixCell) -> Option<f64>,
+) -> Option<((i32, f64), (i32, f64))> {
+    let priced: Vec<(i32, f64)> = cells
+        .iter()
+        .filter_map(|cell| price(cell).map(|p| (cell.region_id, p)))
+        .collect();
+    if priced.len() < 2 {
+        return None;
+    }
+    let min = priced.iter().copied().min_by(|a, b| a.1.total_cmp(&b.1))?;
+    let max = priced.iter().copied().max_by(|a, b| a.1.total_cmp(&b.1))?;
+    Some((min, max))
+}
+
+/// Formats a price matrix as Markdown tables, one for sell prices and one for buy prices
+pub(crate) fn format_price_matrix(matrix: &PriceMatrix) -> String {
+    let mut report = format!(
+        "Price Matrix: {} items x {} regions (region-wide best orders; (min) and (max) mark each row's extremes)\n",
+        matrix.rows.len(),
+        matrix.region_ids.len()
+    );
+
+    report.push_str("\nBest sell prices (ISK):\n");
+    report.push_str(&price_table(
+        matrix,
+        |cell| cell.best_sell,
+        |row| (row.min_sell_region_id, row.max_sell_region_id),
+        true,
+    ));
+    if matrix.include_buy {
+        report.push_str("\nBest buy prices (ISK):\n");
+        report.push_str(&price_table(
+            matrix,
+            |cell| cell.best_buy,
+            |row| (row.min_buy_region_id, row.max_buy_region_id),
+            false,
+        ));
+    }
+
+    let failures: Vec<String> = matrix
+        .rows
+        .iter()
+        .flat_map(|row| {
+            row.cells.iter().filter_map(move |cell| {
+                cell.error
+                    .as_ref()
+                    .map(|e| format!("{} in region {} ({e})", row.type_label, cell.region_id))
+            })
+        })
+        .collect();
+    if !failures.is_empty() {
+        report.push_str(&format!("\nFailed: {}\n", failures.join(", ")));
+    }
+    report.trim_end().to_string()
+}
+
+/// One Markdown table of prices, optionally with the cross-region sell spread column
+fn price_table(
+    matrix: &PriceMatrix,
+    price: impl Fn(&PriceMatrixCell) -> Option<f64>,
+    marks: impl Fn(&PriceMatrixRow) -> (Option<i32>, Option<i32>),
+    with_spread: bool,
+) -> String {
+    let mut header = vec!["Item".to_string()];
+    header.extend(matrix.region_labels.iter().cloned());
+    if with_spread {
+        header.push("Spread".to_string());
+    }
+    let mut table = format!(
+        "| {} |\n|{}\n",
+        header.join(" | "),
+        "---|".repeat(header.len())
+    );
+
+    for row in &matrix.rows {
+        let (min, max) = marks(row);
+        let mut columns = vec![row.type_label.clone()];
+        for cell in &row.cells {
+            let text = match (price(cell), &cell.error) {
+                (Some(p), _) if Some(cell.region_id) == min => format!("{p:.2} (min)"),
+                (Some(p), _) if Some(cell.region_id) == max => format!("{p:.2} (max)"),
+                (Some(p), _) => format!("{p:.2}"),
+                (None, Some(_)) => "error".to_string(),
+                (None, None) => "-".to_string(),
+            };
+            columns.push(text);
+        }
+        if with_spread {
+            columns.push(
+                row.sell_spread_percent
+                    .map(|s| format!("{s:.1}%"))
+                    .unwrap_or_else(|| "-".to_string()),
+            );
+        }
+        table.push_str(&format!("| {} |\n", columns.join(" | ")));
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cell(region_id: i32, best_sell: Option<f64>, best_buy: Option<f64>) -> PriceMatrixCell {
+        PriceMatrixCell {
+            region_id,
+            best_sell,
+            best_buy,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_row_extremes() {
+        let row = matrix_row(
+            34,
+            "Tritanium (34)".to_string(),
+            vec![
+                cell(10000002, Some(5.0), Some(4.5)),
+                cell(10000043, Some(6.0), Some(4.0)),
+                cell(10000032, None, Some(4.8)),
+            ],
+        );
+        assert_eq!(row.min_sell_region_id, Some(10000002));
+        assert_eq!(row.max_sell_region_id, Some(10000043));
+        assert_eq!(row.min_buy_region_id, Some(10000043));
+        assert_eq!(row.max_buy_region_id, Some(10000032));
+        assert_eq!(row.sell_spread_percent, Some(20.0));
+
+        // A single priced region has nothing to compare against
+        let row = matrix_row(
+            35,
+            "Pyerite (35)".to_string(),
+            vec![cell(10000002, Some(9.0), None), cell(10000043, None, None)],
+        );
+        assert_eq!(
+            (row.min_sell_region_id, row.max_sell_region_id),
+            (None, None)
+        );
+        assert_eq!(row.sell_spread_percent, None);
+    }
+
+    #[test]
+    fn test_format_price_matrix() {
+        let mut failed = cell(10000043, None, None);
+        failed.error = Some("timeout".to_string());
+        let matrix = PriceMatrix {
+            region_ids: vec![10000002, 10000043],
+            region_labels: vec!["The Forge".to_string(), "Domain".to_string()],
+            include_buy: true,
+            rows: vec![
+                matrix_row(
+                    34,
+                    "Tritanium (34)".to_string(),
+                    vec![
+                        cell(10000002, Some(5.0), Some(4.5)),
+                        cell(10000043, Some(5.5), Some(4.0)),
+                    ],
+                ),
+                matrix_row(
+                    35,
+                    "Pyerite (35)".to_string(),
+                    vec![cell(10000002, Some(9.0), None), failed],
+                ),
+            ],
+        };
+        let report = format_price_matrix(&matrix);
+        assert!(report.contains("| Item | The Forge | Domain | Spread |"));
+        assert!(report.contains("| Tritanium (34) | 5.00 (min) | 5.50 (max) | 10.0% |"));
+        assert!(report.contains("| Pyerite (35) | 9.00 | error | - |"));
+        assert!(report.contains("Best buy prices (ISK):\n| Item | The Forge | Domain |\n"));
+        assert!(report.contains("| Tritanium (34) | 4.50 (max) | 4.00 (min) |"));
+        assert!(report.contains("Failed: Pyerite (35) in region 10000043 (timeout)"));
+    }
+
+    #[tokio::test]
+    async fn test_matrix_size_is_bounded() {
+        let client = MarketClient::without_cache();
+        assert!(client.price_matrix(&[], &[10000002], false).await.is_err());
+        let type_ids: Vec<i32> = (1..=41).collect();
+        let error = client
+            .price_matrix(
+                &type_ids,
+                &[10000002, 10000043, 10000032, 10000030, 10000042],
+                false,
+            )
+            .await;
+        assert!(error.unwrap_err().to_string().contains("205 cells"));
+    }
+}
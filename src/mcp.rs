@@ -1,5 +1,76 @@
+use crate::alerts::{parse_alert_side, AlertMonitor};
+use crate::anomaly::{DEFAULT_ANOMALY_THRESHOLD, DEFAULT_ANOMALY_WINDOW};
+use crate::archive::{
+    format_year_over_year, HistoryArchive, HistoryArchiver, DEFAULT_ARCHIVE_INTERVAL, DEFAULT_YOY_WINDOW, MAX_YOY_WINDOW,
+    MIN_YOY_WINDOW,
+};
+use crate::auth::{EveSso, SsoConfig};
+use crate::backtest::BacktestRules;
+use crate::breakout::DEFAULT_BREAKOUT_WINDOW;
+use crate::cache::{track_stale_reads, StaleRead};
+use crate::config::TraderGraderConfig;
+use crate::contracts::{
+    format_contract_scan, ContractFilter, DEFAULT_CONTRACT_LIMIT, MAX_CONTRACT_LIMIT, VALUED_CONTRACTS,
+};
+use crate::correlation::DEFAULT_COMPARISON_DAYS;
+use crate::courier::{format_courier_quote, CourierRates};
+use crate::error::{Result, TraderGraderError};
+use crate::events::{self, GameEvent};
+use crate::export::{history_csv, recent_history};
+use crate::fees::{TradingProfile, TradingVenue};
+use crate::flow::{DEFAULT_FLOW_DAYS, STAPLE_TYPE_IDS};
+use crate::fuel::format_fuel_block_analysis;
+use crate::grade::{format_trade_grade, GradeWeights, ProposedTrade};
+use crate::hauling::{
+    format_hauling_analysis, format_jump_freighter_profit, HaulCargo, JumpFreighter, JumpFuelConfig, RouteFlag,
+    DEFAULT_CARGO_CAPACITY_M3,
+};
+use crate::health::HealthLevel;
+use crate::history::{aggregate_history, format_candles, format_history_stats, parse_history_date, HistoryRange};
+use crate::hubs::{format_hub_comparison, TradeHub};
+use crate::industry::{ManufacturingJob, MAX_JOB_RUNS, MAX_MATERIAL_EFFICIENCY, MAX_TIME_EFFICIENCY};
+use crate::insurance::format_insurance_analysis;
+use crate::journal::TradeJournal;
+use crate::limits::{self, ResponseLimits};
+use crate::locations::{LocationKind, LocationRegistry};
+use crate::logging::{LogLevel, McpLogger};
+use crate::lp::{format_lp_store_value, DEFAULT_OFFER_LIMIT, MAX_OFFER_LIMIT, NPC_CORPORATION_ID_RANGE};
 use crate::market::MarketClient;
+use crate::market_groups::{DEFAULT_GROUPS_PAGE, DEFAULT_GROUP_TYPES_PAGE};
+use crate::matrix::format_price_matrix;
+use crate::mineral_index::{default_weights, format_mineral_index, DEFAULT_INDEX_DAYS, MAX_INDEX_DAYS, MAX_INDEX_ITEMS};
+use crate::movers::{format_top_movers, DEFAULT_MOVERS_LIMIT};
+use crate::orders::DEFAULT_ORDERS_PAGE;
+use crate::paging::{max_chars_argument, truncate_output, Page, DEFAULT_MAX_CHARS, MIN_MAX_CHARS};
+use crate::passthrough::EsiAllowlist;
+use crate::plex::{format_plex_dashboard, DEFAULT_DASHBOARD_DAYS, MAX_DASHBOARD_DAYS};
+use crate::portfolio::{format_portfolio_valuation, PortfolioStore};
+use crate::prefetch::{PrefetchConfig, PrefetchTarget, Prefetcher};
+use crate::resources::{list_resources, watchlist_json, ResourceUri, WATCHLIST_URI};
+use crate::route::{format_route_plan, format_route_risk};
+use crate::scan::LevelFilter;
+use crate::sde::StaticData;
+use crate::search::{format_item_search, DEFAULT_SEARCH_LIMIT, MAX_SEARCH_LIMIT};
+use crate::snapshot::{format_market_snapshot, SnapshotConfig, SnapshotRefresher, SnapshotStore};
+use crate::sovereignty::format_region_context;
+use crate::speculation::{
+    format_speculation_screen, DEFAULT_SPECULATION_WINDOW, MAX_SPECULATION_WINDOW, MIN_SPECULATION_WINDOW,
+};
+use crate::spread::{DEFAULT_SPREAD_WEEKS, MIN_SPREAD_WEEKS};
+use crate::state::{ImportMode, StateDocument};
+use crate::undercut::{DEFAULT_UNDERCUT_SAMPLES, MAX_UNDERCUT_SAMPLES};
+use crate::universe::{REGION_ID_RANGE, SYSTEM_ID_RANGE};
+use crate::validation::validate_arguments;
+use crate::types::{
+    HistoryStats, JournalTrade, MarketHistory, MarketSnapshot, OrderFilter, OrderSort, OrderType, Period, PriceBasis, ScanSort,
+    TradeReport, TradeSide, Watchlist,
+};
+use crate::watchlist::WatchlistStore;
 use serde_json::{Value, json};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tracing::Instrument;
 
 /// MCP protocol handler for TraderGrader
 /// 
@@ -8,9 +79,35 @@ use serde_json::{Value, json};
 /// This is the core component that bridges MCP requests to EVE Online market data.
 #[derive(Debug)]
 pub struct McpHandler {
-    pub market_client: MarketClient,
+    pub market_client: Arc<MarketClient>,
+    logger: McpLogger,
     server_name: String,
     server_version: String,
+    /// Targets `start_prefetch` warms when called without any
+    prefetch_defaults: PrefetchConfig,
+    prefetch: Mutex<Option<Prefetcher>>,
+    alerts: AlertMonitor,
+    /// The watchlist served as MCP resources
+    watchlist: Arc<WatchlistStore>,
+    /// Positions valued by portfolio_value
+    portfolio: PortfolioStore,
+    /// Executed trades reported on by trade_report
+    journal: TradeJournal,
+    /// Resource URIs the client subscribed to
+    subscriptions: Mutex<HashSet<String>>,
+    /// Aliases accepted in place of region, system and station IDs
+    locations: LocationRegistry,
+    /// Skills, standings and venue profitability tools price fees for
+    trading_profile: Mutex<TradingProfile>,
+    /// Rates price_courier_contract uses for any rate a call leaves out
+    courier_rates: CourierRates,
+    /// Basket and hubs of the market snapshot
+    snapshot_config: SnapshotConfig,
+    /// The latest market snapshot, served by get_market_snapshot and as a resource
+    snapshot: SnapshotStore,
+    snapshot_refresher: Option<SnapshotRefresher>,
+    /// Archives the watchlist's history daily when a history archive is configured
+    history_archiver: Option<HistoryArchiver>,
 }
 
 impl McpHandler {
@@ -28,13 +125,180 @@ impl McpHandler {
     /// let handler = McpHandler::new("TraderGrader".to_string(), "0.1.0".to_string());
     /// ```
     pub fn new(name: String, version: String) -> Self {
+        let mut market_client = MarketClient::new();
+
+        // Authenticated tools are only available when an SSO application is configured
+        if let Some(sso_config) = SsoConfig::from_env() {
+            match EveSso::new(sso_config.with_esi_config(market_client.esi_config())) {
+                Ok(sso) => market_client = market_client.with_authenticator(Arc::new(sso)),
+                Err(e) => tracing::warn!("EVE SSO disabled: {e}"),
+            }
+        }
+
+        // Static lookups come from a local SDE when one is configured, otherwise ESI
+        if let Some(static_data) = StaticData::from_env() {
+            match static_data {
+                Ok(sde) => market_client = market_client.with_static_data(Arc::new(sde)),
+                Err(e) => tracing::warn!("Local SDE disabled: {e}"),
+            }
+        }
+
+        if let Some(allowlist) = EsiAllowlist::from_env() {
+            market_client = market_client.with_esi_allowlist(allowlist);
+        }
+        market_client = market_client.with_response_limits(ResponseLimits::from_env());
+
+        Self::with_market_client(name, version, market_client)
+    }
+
+    /// Creates an MCP protocol handler from loaded configuration
+    /// 
+    /// Builds the market client from the cache, rate limit, ESI, limit and
+    /// trend settings, attaches the configured SDE and ESI allowlist, and starts
+    /// the MCP logger at the configured level. SSO still comes from the environment.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use tradergrader::McpHandler;
+    /// use tradergrader::config::TraderGraderConfig;
+    /// 
+    /// let config = TraderGraderConfig::from_toml_str("[server]\nname = \"Desk\"\n")?;
+    /// let handler = McpHandler::from_config(&config)?;
+    /// # Ok::<(), tradergrader::TraderGraderError>(())
+    /// ```
+    pub fn from_config(config: &TraderGraderConfig) -> Result<Self> {
+        let mut market_client =
+            MarketClient::with_esi_config(config.cache.clone(), config.rate_limit.clone(), config.esi.clone())?
+                .with_response_limits(config.limits)
+                .with_esi_allowlist(config.server.esi_allowlist.clone())
+                .with_trend_config(config.trend);
+
+        if let Some(sso_config) = SsoConfig::from_env() {
+            match EveSso::new(sso_config.with_esi_config(market_client.esi_config())) {
+                Ok(sso) => market_client = market_client.with_authenticator(Arc::new(sso)),
+                Err(e) => tracing::warn!("EVE SSO disabled: {e}"),
+            }
+        }
+
+        if let Some(path) = &config.server.sde_path {
+            match StaticData::load(path) {
+                Ok(sde) => market_client = market_client.with_static_data(Arc::new(sde)),
+                Err(e) => tracing::warn!("Local SDE disabled: {e}"),
+            }
+        }
+
+        if let Some(path) = &config.server.history_archive_path {
+            match HistoryArchive::open(path) {
+                Ok(archive) => market_client = market_client.with_history_archive(Arc::new(archive)),
+                Err(e) => tracing::warn!("History archive disabled: {e}"),
+            }
+        }
+
+        let mut handler =
+            Self::with_market_client(config.server.name.clone(), config.server.version.clone(), market_client);
+        handler.logger.set_level(config.server.log_level);
+        handler.prefetch_defaults = config.prefetch.clone();
+        handler.locations = config.locations.clone();
+        handler.courier_rates = config.courier;
+        handler.snapshot_config = config.snapshot.clone();
+        if let Some(path) = &config.server.watchlist_path {
+            match WatchlistStore::open(path) {
+                Ok(watchlist) => handler.watchlist = Arc::new(watchlist),
+                Err(e) => tracing::warn!("Watchlist will not be saved: {e}"),
+            }
+        }
+        if let Some(path) = &config.server.portfolio_path {
+            match PortfolioStore::open(path) {
+                Ok(portfolio) => handler.portfolio = portfolio,
+                Err(e) => tracing::warn!("Portfolio will not be saved: {e}"),
+            }
+        }
+        if let Some(path) = &config.server.journal_path {
+            match TradeJournal::open(path) {
+                Ok(journal) => handler.journal = journal,
+                Err(e) => tracing::warn!("Trade journal will not be saved: {e}"),
+            }
+        }
+
+        // Configured targets are kept warm from startup when there is a runtime to run on
+        if !config.prefetch.targets.is_empty() && tokio::runtime::Handle::try_current().is_ok() {
+            match Prefetcher::start(Arc::clone(&handler.market_client), config.prefetch.clone()) {
+                Ok(prefetcher) => *handler.prefetch.get_mut().unwrap_or_else(|e| e.into_inner()) = Some(prefetcher),
+                Err(e) => tracing::warn!("Prefetching disabled: {e}"),
+            }
+        }
+        if config.snapshot.enabled && tokio::runtime::Handle::try_current().is_ok() {
+            let store = handler.snapshot.clone();
+            match SnapshotRefresher::start(Arc::clone(&handler.market_client), config.snapshot.clone(), store) {
+                Ok(refresher) => handler.snapshot_refresher = Some(refresher),
+                Err(e) => tracing::warn!("Market snapshot refresh disabled: {e}"),
+            }
+        }
+        if handler.market_client.history_archive().is_some() && tokio::runtime::Handle::try_current().is_ok() {
+            let watchlist = Arc::clone(&handler.watchlist);
+            match HistoryArchiver::start(Arc::clone(&handler.market_client), watchlist, DEFAULT_ARCHIVE_INTERVAL) {
+                Ok(archiver) => handler.history_archiver = Some(archiver),
+                Err(e) => tracing::warn!("Watchlist history archiving disabled: {e}"),
+            }
+        }
+        Ok(handler)
+    }
+
+    /// Creates a new MCP protocol handler backed by a preconfigured market client
+    /// 
+    /// Use this to drive the handler programmatically with custom cache or
+    /// rate limit settings, e.g. when embedding TraderGrader in another service.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use tradergrader::{McpHandler, MarketClient};
+    /// let handler = McpHandler::with_market_client(
+    ///     "TraderGrader".to_string(),
+    ///     "0.1.0".to_string(),
+    ///     MarketClient::without_cache(),
+    /// );
+    /// ```
+    pub fn with_market_client(name: String, version: String, market_client: MarketClient) -> Self {
+        // The handler and client must share one logger so client events reach the MCP client
+        let (market_client, logger) = match market_client.logger().cloned() {
+            Some(logger) => (market_client, logger),
+            None => {
+                let logger = McpLogger::from_env();
+                (market_client.with_logger(logger.clone()), logger)
+            }
+        };
         Self {
-            market_client: MarketClient::new(),
+            market_client: Arc::new(market_client),
+            logger,
             server_name: name,
             server_version: version,
+            prefetch_defaults: PrefetchConfig::default(),
+            prefetch: Mutex::new(None),
+            alerts: AlertMonitor::default(),
+            watchlist: Arc::new(WatchlistStore::in_memory()),
+            portfolio: PortfolioStore::in_memory(),
+            journal: TradeJournal::in_memory(),
+            subscriptions: Mutex::new(HashSet::new()),
+            locations: LocationRegistry::default(),
+            trading_profile: Mutex::new(TradingProfile::default()),
+            courier_rates: CourierRates::default(),
+            snapshot_config: SnapshotConfig::default(),
+            snapshot: SnapshotStore::default(),
+            snapshot_refresher: None,
+            history_archiver: None,
         }
     }
 
+    /// Takes the log notifications queued since the last call
+    /// 
+    /// Server loops write these to the client before each response, so
+    /// events raised while handling a request arrive ahead of its result.
+    pub fn drain_notifications(&self) -> Vec<Value> {
+        self.logger.drain()
+    }
+
     /// Handles incoming MCP protocol messages
     /// 
     /// This is the main entry point for processing MCP JSON-RPC messages.
@@ -74,6 +338,11 @@ impl McpHandler {
                 "notifications/cancelled" => self.handle_cancelled(&message),
                 "tools/list" => self.handle_tools_list(&message),
                 "tools/call" => self.handle_tool_call(&message).await,
+                "logging/setLevel" => self.handle_set_level(&message),
+                "resources/list" => self.handle_resources_list(&message),
+                "resources/read" => self.handle_resources_read(&message).await,
+                "resources/subscribe" => self.handle_resources_subscribe(&message, true),
+                "resources/unsubscribe" => self.handle_resources_subscribe(&message, false),
                 "ping" => self.handle_ping(&message),
                 _ => json!({
                     "jsonrpc": "2.0",
@@ -106,6 +375,11 @@ impl McpHandler {
                 "capabilities": {
                     "tools": {
                         "listChanged": false
+                    },
+                    "logging": {},
+                    "resources": {
+                        "subscribe": true,
+                        "listChanged": true
                     }
                 },
                 "serverInfo": {
@@ -132,403 +406,4153 @@ impl McpHandler {
         })
     }
 
-    /// Handle tools/list request - return available tools
-    fn handle_tools_list(&self, message: &Value) -> Value {
-        json!({
-            "jsonrpc": "2.0",
-            "id": message.get("id"),
-            "result": {
-                "tools": [
-                    {
-                        "name": "health_check",
-                        "description": "Check if the TraderGrader MCP server is running",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {},
-                            "required": []
-                        }
-                    },
-                    {
-                        "name": "get_market_orders",
-                        "description": "Fetch current market orders for a specific region and optionally filter by item type",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "region_id": {
-                                    "type": "integer",
-                                    "description": "EVE Online region ID (e.g., 10000002 for The Forge)"
-                                },
-                                "type_id": {
-                                    "type": "integer",
-                                    "description": "Optional item type ID to filter orders"
-                                }
-                            },
-                            "required": ["region_id"]
-                        }
-                    },
-                    {
-                        "name": "get_market_summary",
-                        "description": "Get a summary of market data including buy/sell orders and price spread for a specific item type in a region",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "region_id": {
-                                    "type": "integer",
-                                    "description": "EVE Online region ID (e.g., 10000002 for The Forge)"
-                                },
-                                "type_id": {
-                                    "type": "integer",
-                                    "description": "Item type ID to analyze"
-                                }
-                            },
-                            "required": ["region_id", "type_id"]
-                        }
-                    },
-                    {
-                        "name": "get_market_history",
-                        "description": "Fetch historical market data (price, volume, order count) for a specific item in a region",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "region_id": {
-                                    "type": "integer",
-                                    "description": "EVE Online region ID (e.g., 10000002 for The Forge)"
-                                },
-                                "type_id": {
-                                    "type": "integer",
-                                    "description": "Item type ID to get history for"
-                                }
-                            },
-                            "required": ["region_id", "type_id"]
-                        }
-                    },
-                    {
-                        "name": "get_price_analysis",
-                        "description": "Analyze price trends including daily/weekly/monthly changes, volatility, and trend direction",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "region_id": {
-                                    "type": "integer",
-                                    "description": "EVE Online region ID (e.g., 10000002 for The Forge)"
-                                },
-                                "type_id": {
-                                    "type": "integer",
-                                    "description": "Item type ID to analyze trends for"
-                                }
-                            },
-                            "required": ["region_id", "type_id"]
-                        }
-                    }
-                ]
-            }
-        })
-    }
+    /// Handle logging/setLevel request - change the lowest level of log notifications sent
+    fn handle_set_level(&self, message: &Value) -> Value {
+        let level = message
+            .get("params")
+            .and_then(|p| p.get("level"))
+            .and_then(|l| l.as_str())
+            .ok_or_else(|| "Missing level".to_string())
+            .and_then(str::parse::<LogLevel>);
 
-    /// Handle tools/call request - execute specific tool
-    async fn handle_tool_call(&self, message: &Value) -> Value {
-        if let Some(params) = message.get("params") {
-            if let Some(name) = params.get("name").and_then(|n| n.as_str()) {
-                match name {
-                    "health_check" => self.handle_health_check(message),
-                    "get_market_orders" => self.handle_get_market_orders(message, params).await,
-                    "get_market_summary" => self.handle_get_market_summary(message, params).await,
-                    "get_market_history" => self.handle_get_market_history(message, params).await,
-                    "get_price_analysis" => self.handle_get_price_analysis(message, params).await,
-                    _ => json!({
-                        "jsonrpc": "2.0",
-                        "id": message.get("id"),
-                        "error": {
-                            "code": -32601,
-                            "message": format!("Unknown tool: {}", name)
-                        }
-                    }),
-                }
-            } else {
+        match level {
+            Ok(level) => {
+                self.logger.set_level(level);
                 json!({
                     "jsonrpc": "2.0",
                     "id": message.get("id"),
-                    "error": {
-                        "code": -32602,
-                        "message": "Invalid tool call parameters"
-                    }
+                    "result": {}
                 })
             }
-        } else {
-            json!({
+            Err(e) => json!({
                 "jsonrpc": "2.0",
                 "id": message.get("id"),
                 "error": {
                     "code": -32602,
-                    "message": "Missing parameters"
+                    "message": e
                 }
-            })
+            }),
         }
     }
 
-    /// Handle health check tool
-    fn handle_health_check(&self, message: &Value) -> Value {
+    /// Handle resources/list request - the watchlist, the market snapshot and a market summary per watched item
+    fn handle_resources_list(&self, message: &Value) -> Value {
         json!({
             "jsonrpc": "2.0",
             "id": message.get("id"),
             "result": {
-                "content": [{
-                    "type": "text",
-                    "text": format!("✅ {} v{} is healthy and running!\nTimestamp: {}",
-                        self.server_name,
-                        self.server_version,
-                        chrono::Utc::now().to_rfc3339()
-                    )
-                }]
+                "resources": list_resources(&self.watchlist)
             }
         })
     }
 
-    /// Handle get_market_orders tool
-    async fn handle_get_market_orders(&self, message: &Value, params: &Value) -> Value {
-        if let Some(arguments) = params.get("arguments") {
-            let region_id = arguments
-                .get("region_id")
-                .and_then(|v| v.as_i64())
-                .unwrap_or(0) as i32;
-            let type_id = arguments
-                .get("type_id")
-                .and_then(|v| v.as_i64())
-                .map(|v| v as i32);
-
-            match self.market_client.fetch_market_orders(region_id, type_id).await {
-                Ok(orders) => json!({
-                    "jsonrpc": "2.0",
-                    "id": message.get("id"),
-                    "result": {
-                        "content": [{
-                            "type": "text",
-                            "text": format!("Found {} market orders for region {}", orders.len(), region_id)
-                        }]
-                    }
-                }),
-                Err(e) => json!({
+    /// Handle resources/read request
+    ///
+    /// Unknown URIs get the MCP "resource not found" error (-32002); market
+    /// summaries that can't be fetched get an internal error.
+    async fn handle_resources_read(&self, message: &Value) -> Value {
+        let uri = message.get("params").and_then(|p| p.get("uri")).and_then(|u| u.as_str()).unwrap_or_default();
+        let contents = match uri.parse::<ResourceUri>() {
+            Ok(ResourceUri::Watchlist) => serde_json::to_string_pretty(&watchlist_json(&self.watchlist))
+                .map(|text| (text, "application/json"))
+                .map_err(TraderGraderError::from),
+            Ok(ResourceUri::MarketSummary { region_id, type_id }) => self
+                .market_client
+                .get_market_summary(region_id, type_id)
+                .await
+                .map(|text| (text, "text/plain")),
+            Ok(ResourceUri::MarketSnapshot) => match self.market_snapshot(false).await {
+                Ok(snapshot) => serde_json::to_string_pretty(&snapshot)
+                    .map(|text| (text, "application/json"))
+                    .map_err(TraderGraderError::from),
+                Err(e) => Err(e),
+            },
+            Err(e) => {
+                return json!({
                     "jsonrpc": "2.0",
                     "id": message.get("id"),
                     "error": {
-                        "code": -32603,
-                        "message": format!("Failed to fetch market orders: {}", e)
+                        "code": -32002,
+                        "message": "Resource not found",
+                        "data": { "uri": uri, "reason": e.to_string() }
                     }
-                }),
+                })
             }
-        } else {
-            json!({
+        };
+        match contents {
+            Ok((text, mime_type)) => json!({
+                "jsonrpc": "2.0",
+                "id": message.get("id"),
+                "result": {
+                    "contents": [{ "uri": uri, "mimeType": mime_type, "text": text }]
+                }
+            }),
+            Err(e) => json!({
                 "jsonrpc": "2.0",
                 "id": message.get("id"),
                 "error": {
-                    "code": -32602,
-                    "message": "Missing arguments for get_market_orders"
+                    "code": -32603,
+                    "message": format!("Failed to read {uri}: {e}")
                 }
-            })
+            }),
         }
     }
 
-    /// Handle get_market_summary tool
-    async fn handle_get_market_summary(&self, message: &Value, params: &Value) -> Value {
-        if let Some(arguments) = params.get("arguments") {
-            let region_id = arguments
-                .get("region_id")
-                .and_then(|v| v.as_i64())
-                .unwrap_or(0) as i32;
-            let type_id = arguments
-                .get("type_id")
-                .and_then(|v| v.as_i64())
-                .unwrap_or(0) as i32;
-
-            match self.market_client.get_market_summary(region_id, type_id).await {
-                Ok(summary) => json!({
-                    "jsonrpc": "2.0",
-                    "id": message.get("id"),
-                    "result": {
-                        "content": [{
-                            "type": "text",
-                            "text": summary
-                        }]
-                    }
-                }),
-                Err(e) => json!({
-                    "jsonrpc": "2.0",
-                    "id": message.get("id"),
-                    "error": {
-                        "code": -32603,
-                        "message": format!("Failed to get market summary: {}", e)
-                    }
-                }),
-            }
-        } else {
-            json!({
+    /// Handle resources/subscribe and resources/unsubscribe requests
+    fn handle_resources_subscribe(&self, message: &Value, subscribe: bool) -> Value {
+        let uri = message.get("params").and_then(|p| p.get("uri")).and_then(|u| u.as_str()).unwrap_or_default();
+        if let Err(e) = uri.parse::<ResourceUri>() {
+            return json!({
                 "jsonrpc": "2.0",
                 "id": message.get("id"),
                 "error": {
-                    "code": -32602,
-                    "message": "Missing arguments for get_market_summary"
+                    "code": -32002,
+                    "message": "Resource not found",
+                    "data": { "uri": uri, "reason": e.to_string() }
                 }
-            })
+            });
+        }
+        let mut subscriptions = self.subscriptions.lock().unwrap_or_else(|e| e.into_inner());
+        if subscribe {
+            subscriptions.insert(uri.to_string());
+        } else {
+            subscriptions.remove(uri);
+        }
+        json!({
+            "jsonrpc": "2.0",
+            "id": message.get("id"),
+            "result": {}
+        })
+    }
+
+    /// Tells the client the resource list changed, and subscribers that the watchlist did
+    fn watchlist_changed(&self) {
+        self.logger.notify("notifications/resources/list_changed", json!({}));
+        if self.subscriptions.lock().unwrap_or_else(|e| e.into_inner()).contains(WATCHLIST_URI) {
+            self.logger.notify("notifications/resources/updated", json!({ "uri": WATCHLIST_URI }));
         }
     }
 
-    /// Handle get_market_history tool
-    async fn handle_get_market_history(&self, message: &Value, params: &Value) -> Value {
-        if let Some(arguments) = params.get("arguments") {
-            let region_id = arguments
-                .get("region_id")
-                .and_then(|v| v.as_i64())
-                .unwrap_or(0) as i32;
-            let type_id = arguments
-                .get("type_id")
-                .and_then(|v| v.as_i64())
-                .unwrap_or(0) as i32;
-            
-            match self.market_client.fetch_market_history(region_id, type_id).await {
-                Ok(history) => {
-                    let history_text = if history.is_empty() {
-                        "No historical data available".to_string()
-                    } else {
-                        let recent_days = history.iter().take(10);
-                        let mut text = format!("Recent {} days of market history:\n", std::cmp::min(history.len(), 10));
-                        for day in recent_days {
-                            text.push_str(&format!(
-                                "{}: Avg: {:.2} ISK, High: {:.2} ISK, Low: {:.2} ISK, Volume: {}\n",
-                                day.date, day.average, day.highest, day.lowest, day.volume
-                            ));
-                        }
-                        text
-                    };
-                    
-                    json!({
-                        "jsonrpc": "2.0",
-                        "id": message.get("id"),
-                        "result": {
-                            "content": [{
-                                "type": "text",
-                                "text": history_text
-                            }]
-                        }
-                    })
-                },
-                Err(e) => json!({
-                    "jsonrpc": "2.0",
-                    "id": message.get("id"),
-                    "error": {
-                        "code": -32603,
-                        "message": format!("Failed to fetch market history: {}", e)
-                    }
-                })
+    /// Handle tools/list request - return available tools
+    fn handle_tools_list(&self, message: &Value) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "id": message.get("id"),
+            "result": {
+                "tools": tool_definitions()
             }
-        } else {
-            json!({
+        })
+    }
+
+    /// Handle tools/call request - execute specific tool
+    ///
+    /// Malformed calls (no tool name, unknown tool, arguments that don't
+    /// match the tool's schema) get JSON-RPC protocol errors with the matching
+    /// code and any structured `data`, before the tool makes any ESI request. Failures while running the tool come back as a tool
+    /// result with `isError: true`, so the model can read what went wrong and
+    /// react, as the MCP spec asks.
+    async fn handle_tool_call(&self, message: &Value) -> Value {
+        let Some(params) = message.get("params") else {
+            return json!({
                 "jsonrpc": "2.0",
                 "id": message.get("id"),
                 "error": {
                     "code": -32602,
-                    "message": "Missing arguments for get_market_history"
+                    "message": "Missing parameters"
                 }
-            })
-        }
-    }
-
-    /// Handle get_price_analysis tool
-    async fn handle_get_price_analysis(&self, message: &Value, params: &Value) -> Value {
-        if let Some(arguments) = params.get("arguments") {
-            let region_id = arguments
-                .get("region_id")
-                .and_then(|v| v.as_i64())
-                .unwrap_or(0) as i32;
-            let type_id = arguments
-                .get("type_id")
-                .and_then(|v| v.as_i64())
-                .unwrap_or(0) as i32;
-            
-            match self.market_client.get_price_history_summary(region_id, type_id).await {
-                Ok(analysis) => json!({
-                    "jsonrpc": "2.0",
-                    "id": message.get("id"),
-                    "result": {
-                        "content": [{
-                            "type": "text",
-                            "text": analysis
-                        }]
-                    }
-                }),
-                Err(e) => json!({
+            });
+        };
+        let Some(name) = params.get("name").and_then(|n| n.as_str()) else {
+            return json!({
+                "jsonrpc": "2.0",
+                "id": message.get("id"),
+                "error": {
+                    "code": -32602,
+                    "message": "Invalid tool call parameters"
+                }
+            });
+        };
+        let Some(tool) = tool_definitions()
+            .as_array()
+            .and_then(|tools| tools.iter().find(|tool| tool["name"] == name))
+        else {
+            return json!({
+                "jsonrpc": "2.0",
+                "id": message.get("id"),
+                "error": {
+                    "code": -32601,
+                    "message": format!("Unknown tool: {}", name)
+                }
+            });
+        };
+        // Location aliases become IDs first, so they pass the schema's bounds
+        let mut params = params.clone();
+        if let Some(arguments) = params.get_mut("arguments") {
+            if let Err(e) = self.locations.resolve_arguments(arguments) {
+                return json!({
                     "jsonrpc": "2.0",
                     "id": message.get("id"),
-                    "error": {
-                        "code": -32603,
-                        "message": format!("Failed to get price analysis: {}", e)
-                    }
-                })
+                    "error": e.to_rpc_error(&format!("Invalid arguments for {name}"))
+                });
             }
-        } else {
+        }
+        let params = &params;
+        if let Err(e) = validate_arguments(&tool["inputSchema"], params.get("arguments")) {
+            return json!({
+                "jsonrpc": "2.0",
+                "id": message.get("id"),
+                "error": e.to_rpc_error(&format!("Invalid arguments for {name}"))
+            });
+        }
+
+        let started = std::time::Instant::now();
+        // Unfiltered requests fail with "result too large" instead of exhausting memory
+        let budget = self.market_client.response_limits().tool_budget_bytes;
+        let span = tracing::info_span!("tool_call", tool = name);
+        let ((context, result), stale_reads) = track_stale_reads(limits::with_tool_budget(budget, async {
+            match name {
+                "health_check" => ("Health check failed", Ok(self.handle_health_check().await)),
+                "get_diagnostics" => ("Failed to get diagnostics", Ok(self.handle_get_diagnostics())),
+                "get_eve_status" => (
+                    "Failed to get EVE server status",
+                    self.market_client.get_eve_status_summary().await,
+                ),
+                "get_market_orders" => ("Failed to fetch market orders", self.handle_get_market_orders(params).await),
+                "get_market_summary" => ("Failed to get market summary", self.handle_get_market_summary(params).await),
+                "search_items" => ("Failed to search items", self.handle_search_items(params).await),
+                "get_market_history" => ("Failed to fetch market history", self.handle_get_market_history(params).await),
+                "get_price_analysis" => ("Failed to get price analysis", self.handle_get_price_analysis(params).await),
+                "get_technical_indicators" => (
+                    "Failed to get technical indicators",
+                    self.handle_get_technical_indicators(params).await,
+                ),
+                "get_trend_agreement" => ("Failed to get trend agreement", self.handle_get_trend_agreement(params).await),
+                "forecast_price" => ("Failed to forecast price", self.handle_forecast_price(params).await),
+                "detect_anomalies" => ("Failed to detect anomalies", self.handle_detect_anomalies(params).await),
+                "detect_breakouts" => ("Failed to detect breakouts", self.handle_detect_breakouts(params).await),
+                "compare_year_over_year" => ("Failed to compare years", self.handle_compare_year_over_year(params).await),
+                "backtest_strategy" => ("Failed to backtest strategy", self.handle_backtest_strategy(params).await),
+                "screen_speculation" => ("Failed to screen speculation", self.handle_screen_speculation(params).await),
+                "compare_items" => ("Failed to compare items", self.handle_compare_items(params).await),
+                "estimate_undercut_rate" => (
+                    "Failed to estimate undercut rate",
+                    self.handle_estimate_undercut_rate(params).await,
+                ),
+                "get_spread_history" => (
+                    "Failed to get spread history",
+                    self.handle_get_spread_history(params).await,
+                ),
+                "get_top_movers" => ("Failed to rank top movers", self.handle_get_top_movers(params).await),
+                "region_activity_report" => (
+                    "Failed to build region activity report",
+                    self.handle_region_activity_report(params).await,
+                ),
+                "get_liquidity_score" => ("Failed to get liquidity score", self.handle_get_liquidity_score(params).await),
+                "get_order_book_depth" => ("Failed to get order book depth", self.handle_get_order_book_depth(params).await),
+                "courier_market_rates" => (
+                    "Failed to get courier market rates",
+                    self.handle_courier_market_rates(params).await,
+                ),
+                "scan_public_contracts" => (
+                    "Failed to scan public contracts",
+                    self.handle_scan_public_contracts(params).await,
+                ),
+                "compare_trade_hubs" => ("Failed to compare trade hubs", self.handle_compare_trade_hubs(params).await),
+                "get_market_snapshot" => ("Failed to get market snapshot", self.handle_get_market_snapshot(params).await),
+                "get_plex_dashboard" => ("Failed to get PLEX dashboard", self.handle_get_plex_dashboard(params).await),
+                "get_mineral_index" => ("Failed to get mineral index", self.handle_get_mineral_index(params).await),
+                "price_matrix" => ("Failed to build price matrix", self.handle_price_matrix(params).await),
+                "get_multi_region_summary" => (
+                    "Failed to get multi-region summary",
+                    self.handle_get_multi_region_summary(params).await,
+                ),
+                "grade_trade" => ("Failed to grade trade", self.handle_grade_trade(params).await),
+                "set_trading_profile" => {
+                    ("Failed to set trading profile", self.handle_set_trading_profile(params).await)
+                }
+                "get_trading_profile" => ("Failed to get trading profile", self.trading_profile(None).map(|p| p.to_text())),
+                "estimate_time_to_sell" => {
+                    ("Failed to estimate time to sell", self.handle_estimate_time_to_sell(params).await)
+                }
+                "estimate_buy_fill_time" => {
+                    ("Failed to estimate buy fill time", self.handle_estimate_buy_fill_time(params).await)
+                }
+                "suggest_listing_price" => {
+                    ("Failed to suggest listing price", self.handle_suggest_listing_price(params).await)
+                }
+                "export_market_history" => (
+                    "Failed to export market history",
+                    self.handle_export_market_history(params).await,
+                ),
+                "start_prefetch" => ("Failed to start prefetching", self.handle_start_prefetch(params)),
+                "stop_prefetch" => ("Failed to stop prefetching", self.handle_stop_prefetch()),
+                "set_price_alert" => ("Failed to set price alert", self.handle_set_price_alert(params)),
+                "list_alerts" => ("Failed to list alerts", Ok(self.handle_list_alerts())),
+                "remove_alert" => ("Failed to remove alert", self.handle_remove_alert(params)),
+                "watch_items" => ("Failed to update watchlist", self.handle_watch_items(params, true)),
+                "unwatch_items" => ("Failed to update watchlist", self.handle_watch_items(params, false)),
+                "export_watchlist" => ("Failed to export watchlist", self.handle_export_watchlist(params)),
+                "export_state" => ("Failed to export state", self.handle_export_state()),
+                "import_state" => ("Failed to import state", self.handle_import_state(params)),
+                "import_watchlist" => ("Failed to import watchlist", self.handle_import_watchlist(params).await),
+                "portfolio_add" => ("Failed to add to portfolio", self.handle_portfolio_add(params)),
+                "portfolio_remove" => ("Failed to remove from portfolio", self.handle_portfolio_remove(params)),
+                "portfolio_value" => ("Failed to value portfolio", self.handle_portfolio_value(params).await),
+                "record_trade" => ("Failed to record trade", self.handle_record_trade(params)),
+                "import_wallet_trades" => (
+                    "Failed to import wallet transactions",
+                    self.handle_import_wallet_trades(params).await,
+                ),
+                "trade_report" => ("Failed to build trade report", self.handle_trade_report(params).await),
+                "manufacturing_profit" => (
+                    "Failed to price manufacturing job",
+                    self.handle_manufacturing_profit(params).await,
+                ),
+                "fuel_block_analysis" => ("Failed to analyze fuel blocks", self.handle_fuel_block_analysis(params).await),
+                "lp_store_value" => ("Failed to value LP store", self.handle_lp_store_value(params).await),
+                "insurance_analysis" => ("Failed to analyze insurance", self.handle_insurance_analysis(params).await),
+                "get_region_activity" => ("Failed to get region activity", self.handle_get_region_activity(params).await),
+                "scan_market" => ("Failed to scan market", self.handle_scan_market(params).await),
+                "list_market_groups" => ("Failed to list market groups", self.handle_list_market_groups(params).await),
+                "get_market_group_types" => (
+                    "Failed to get market group types",
+                    self.handle_get_market_group_types(params).await,
+                ),
+                "jf_route_profit" => (
+                    "Failed to calculate jump freighter profit",
+                    self.handle_jf_route_profit(params).await,
+                ),
+                "plan_route" => ("Failed to plan route", self.handle_plan_route(params).await),
+                "get_region_context" => ("Failed to get region context", self.handle_get_region_context(params).await),
+                "get_route_risk" => ("Failed to assess route risk", self.handle_get_route_risk(params).await),
+                "hauling_analysis" => ("Failed to analyze hauling route", self.handle_hauling_analysis(params).await),
+                "price_courier_contract" => (
+                    "Failed to price courier contract",
+                    self.handle_price_courier_contract(params).await,
+                ),
+                "esi_get" => ("Failed to fetch ESI route", self.handle_esi_get(params).await),
+                "get_structure_market_summary" => (
+                    "Failed to get structure market summary",
+                    self.handle_get_structure_market_summary(params).await,
+                ),
+                "authenticate_character" => ("Authentication failed", self.handle_authenticate_character(params).await),
+                "get_my_orders" => ("Failed to get character orders", self.handle_get_my_orders(params).await),
+                // Every declared tool has an arm above; this only catches a missing one
+                _ => ("Tool call failed", Err(TraderGraderError::InternalError(format!("No handler for tool {name}")))),
+            }
+        }))
+        .instrument(span)
+        .await;
+        // Upstream 5xx errors during downtime or an outage say so instead of just the status code
+        let max_chars = max_chars_argument(params.get("arguments"));
+        let result = match result {
+            Ok(text) => Ok(with_stale_notice(truncate_output(text, max_chars), &stale_reads)),
+            Err(e) => Err(self.market_client.explain_outage(e).await),
+        };
+
+        let elapsed_ms = started.elapsed().as_millis() as u64;
+        match &result {
+            Ok(_) => tracing::info!(tool = name, elapsed_ms, "tool call finished"),
+            Err(e) => tracing::warn!(tool = name, elapsed_ms, error = %e, "tool call failed"),
+        }
+        let (level, outcome) = match &result {
+            Ok(_) => (LogLevel::Info, "ok".to_string()),
+            Err(e) => (LogLevel::Error, e.to_string()),
+        };
+        self.logger.log(
+            level,
+            "tools",
             json!({
+                "event": "tool_call",
+                "tool": name,
+                "elapsed_ms": elapsed_ms,
+                "outcome": outcome
+            }),
+        );
+
+        match result {
+            Ok(text) => json!({
                 "jsonrpc": "2.0",
                 "id": message.get("id"),
-                "error": {
-                    "code": -32602,
-                    "message": "Missing arguments for get_price_analysis"
+                "result": {
+                    "content": [{
+                        "type": "text",
+                        "text": text
+                    }]
                 }
-            })
+            }),
+            Err(e) if e.is_invalid_request() => json!({
+                "jsonrpc": "2.0",
+                "id": message.get("id"),
+                "error": e.to_rpc_error(context)
+            }),
+            Err(e) => json!({
+                "jsonrpc": "2.0",
+                "id": message.get("id"),
+                "result": {
+                    "content": [{
+                        "type": "text",
+                        "text": format!("{context}: {e}")
+                    }],
+                    "isError": true
+                }
+            }),
         }
     }
 
-    /// Handle cancellation notifications
-    fn handle_cancelled(&self, _message: &Value) -> Value {
-        // Notifications don't require responses
-        json!(null)
+    /// Handle health check tool
+    async fn handle_health_check(&self) -> String {
+        self.health_check().await.1
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use serde_json::json;
+    /// Checks the cache, ESI and rate limiter the tools depend on
+    ///
+    /// Returns the overall level and the report as text, headed with the
+    /// server's name and version.
+    pub async fn health_check(&self) -> (HealthLevel, String) {
+        let report = self.market_client.health_check().await;
+        (report.level(), report.to_text(&self.server_name, &self.server_version))
+    }
 
-    #[test]
-    fn test_mcp_handler_creation() {
-        let handler = McpHandler::new("TestServer".to_string(), "1.0.0".to_string());
-        assert_eq!(handler.server_name, "TestServer");
-        assert_eq!(handler.server_version, "1.0.0");
+    /// Handle get_diagnostics tool
+    fn handle_get_diagnostics(&self) -> String {
+        self.market_client.diagnostics().to_text()
     }
 
-    #[test]
-    fn test_initialize_message() {
-        let handler = McpHandler::new("TestServer".to_string(), "1.0.0".to_string());
-        let message = json!({
-            "jsonrpc": "2.0",
-            "id": 1,
-            "method": "initialize",
-            "params": {}
-        });
-        
-        let response = handler.handle_initialize(&message);
-        
-        assert_eq!(response["jsonrpc"], "2.0");
-        assert_eq!(response["id"], 1);
-        assert_eq!(response["result"]["protocolVersion"], "2025-03-26");
-        assert_eq!(response["result"]["serverInfo"]["name"], "TestServer");
+    /// Handle get_market_orders tool
+    async fn handle_get_market_orders(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "get_market_orders")?;
+        let region_id = parse_region_id(required_arg(arguments, "region_id")?)?;
+        let type_id = arguments.get("type_id").map(parse_type_id).transpose()?;
+        let filter = OrderFilter {
+            order_type: match arguments.get("order_type").and_then(|v| v.as_str()) {
+                Some(order_type) => order_type.parse().map_err(TraderGraderError::InvalidParams)?,
+                None => OrderType::default(),
+            },
+            min_volume: arguments.get("min_volume").and_then(|v| v.as_i64()).map(|v| v.min(i32::MAX as i64) as i32),
+            max_price: arguments.get("max_price").and_then(|v| v.as_f64()),
+            sort_by: match arguments.get("sort_by").and_then(|v| v.as_str()) {
+                Some(sort) => sort.parse().map_err(TraderGraderError::InvalidParams)?,
+                None => OrderSort::default(),
+            },
+        };
+
+        let page = Page::from_arguments(Some(arguments), DEFAULT_ORDERS_PAGE);
+
+        match arguments.get("format").and_then(|v| v.as_str()) {
+            Some(format) if format.eq_ignore_ascii_case("json") => {
+                let listing = self.market_client.query_market_orders(region_id, type_id, &filter).await?;
+                let locations = self.market_client.page_locations(&listing, page).await;
+                Ok(serde_json::to_string_pretty(&listing.to_json(page, &locations))?)
+            }
+            _ => {
+                self.market_client
+                    .get_market_orders_summary(region_id, type_id, &filter, page)
+                    .await
+            }
+        }
     }
 
-    #[test]
-    fn test_tools_list_message() {
-        let handler = McpHandler::new("TestServer".to_string(), "1.0.0".to_string());
-        let message = json!({
-            "jsonrpc": "2.0",
-            "id": 2,
-            "method": "tools/list"
-        });
-        
-        let response = handler.handle_tools_list(&message);
-        
-        assert_eq!(response["jsonrpc"], "2.0");
-        assert_eq!(response["id"], 2);
-        assert!(response["result"]["tools"].is_array());
-        
-        let tools = response["result"]["tools"].as_array().unwrap();
+    /// Handle get_market_summary tool
+    async fn handle_get_market_summary(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "get_market_summary")?;
+        let region_id = parse_region_id(required_arg(arguments, "region_id")?)?;
+        let type_id = parse_type_id(required_arg(arguments, "type_id")?)?;
+
+        match arguments.get("station_id").and_then(|v| v.as_i64()) {
+            Some(station_id) => {
+                self.market_client
+                    .get_station_market_summary(region_id, type_id, station_id)
+                    .await
+            }
+            None => self.market_client.get_market_summary(region_id, type_id).await,
+        }
+    }
+
+    /// Handle search_items tool
+    async fn handle_search_items(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "search_items")?;
+        let query = required_arg(arguments, "query")?.as_str().unwrap_or_default();
+        let limit = arguments
+            .get("limit")
+            .and_then(|v| v.as_u64())
+            .map_or(DEFAULT_SEARCH_LIMIT, |limit| limit as usize);
+
+        Ok(format_item_search(&self.market_client.search_items(query, limit).await?))
+    }
+
+    /// Handle get_market_history tool
+    async fn handle_get_market_history(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "get_market_history")?;
+        let region_id = parse_region_id(required_arg(arguments, "region_id")?)?;
+        let type_id = parse_type_id(required_arg(arguments, "type_id")?)?;
+        // `period` is the older name of `granularity`
+        let granularity = arguments
+            .get("granularity")
+            .or_else(|| arguments.get("period"))
+            .and_then(|v| v.as_str())
+            .map(str::parse::<Period>)
+            .transpose()
+            .map_err(TraderGraderError::InvalidParams)?;
+        let date = |field: &str| {
+            arguments.get(field).and_then(|v| v.as_str()).map(|d| parse_history_date(field, d)).transpose()
+        };
+        let range = HistoryRange {
+            from: date("from")?,
+            to: date("to")?,
+            days: arguments.get("days").and_then(|v| v.as_u64()).map(|days| days.min(u32::MAX as u64) as u32),
+        };
+        let page = |default_limit| Page::from_arguments(Some(arguments), default_limit);
+
+        range.validate()?;
+        let history = range.select(&self.market_client.fetch_market_history(region_id, type_id).await?);
+        // Turnover up to the end of the range, appended to either listing
+        let stats = HistoryStats::from_history(&history)
+            .map(|stats| format!("\n\n{}", format_history_stats(&stats)))
+            .unwrap_or_default();
+
+        if granularity.is_some() || !range.is_unbounded() {
+            let granularity = granularity.unwrap_or_default();
+            let candles = aggregate_history(&history, granularity);
+            let title = match granularity {
+                Period::Daily => "Daily market history",
+                Period::Weekly => "Weekly market history",
+                Period::Monthly => "Monthly market history",
+            };
+            // A date range shows every candle in it unless limited
+            let page = page(if range.is_unbounded() { 12 } else { usize::MAX });
+            return Ok(format!("{}{stats}", format_candles(title, &candles, page)));
+        }
+
+        let history_text = if history.is_empty() {
+            "No historical data available".to_string()
+        } else {
+            let page = page(10);
+            let newest_first: Vec<&MarketHistory> = history.iter().rev().collect();
+            let days = page.slice(&newest_first);
+            let mut text = format!("Recent {} days of market history:\n", days.len());
+            for day in days {
+                text.push_str(&format!(
+                    "{}: Avg: {:.2} ISK, High: {:.2} ISK, Low: {:.2} ISK, Volume: {}, Turnover: {:.2} ISK\n",
+                    day.date,
+                    day.average,
+                    day.highest,
+                    day.lowest,
+                    day.volume,
+                    day.average * day.volume as f64
+                ));
+            }
+            if let Some(marker) = page.marker(history.len(), "days") {
+                text.push_str(&format!("{marker}\n"));
+            }
+            format!("{}{stats}", text.trim_end())
+        };
+        Ok(history_text)
+    }
+
+    /// Handle get_price_analysis tool
+    async fn handle_get_price_analysis(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "get_price_analysis")?;
+        let region_id = parse_region_id(required_arg(arguments, "region_id")?)?;
+        let type_id = parse_type_id(required_arg(arguments, "type_id")?)?;
+
+        self.market_client.get_price_history_summary(region_id, type_id).await
+    }
+
+    /// Handle get_technical_indicators tool
+    async fn handle_get_technical_indicators(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "get_technical_indicators")?;
+        let region_id = parse_region_id(required_arg(arguments, "region_id")?)?;
+        let type_id = parse_type_id(required_arg(arguments, "type_id")?)?;
+
+        self.market_client
+            .get_technical_indicators_summary(region_id, type_id)
+            .await
+    }
+
+    /// Handle get_trend_agreement tool
+    async fn handle_get_trend_agreement(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "get_trend_agreement")?;
+        let region_id = parse_region_id(required_arg(arguments, "region_id")?)?;
+        let type_id = parse_type_id(required_arg(arguments, "type_id")?)?;
+
+        self.market_client.get_trend_agreement_summary(region_id, type_id).await
+    }
+
+    /// Handle forecast_price tool
+    async fn handle_forecast_price(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "forecast_price")?;
+        let region_id = parse_region_id(required_arg(arguments, "region_id")?)?;
+        let type_id = parse_type_id(required_arg(arguments, "type_id")?)?;
+        let horizon = arguments.get("horizon_days").and_then(|v| v.as_u64()).unwrap_or(7) as usize;
+
+        self.market_client.get_price_forecast_summary(region_id, type_id, horizon).await
+    }
+
+    /// Handle detect_anomalies tool
+    async fn handle_detect_anomalies(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "detect_anomalies")?;
+        let region_id = parse_region_id(required_arg(arguments, "region_id")?)?;
+        let type_id = parse_type_id(required_arg(arguments, "type_id")?)?;
+        let window = arguments
+            .get("window_days")
+            .and_then(|v| v.as_u64())
+            .map_or(DEFAULT_ANOMALY_WINDOW, |days| days as usize);
+        let threshold = arguments
+            .get("std_devs")
+            .and_then(|v| v.as_f64())
+            .unwrap_or(DEFAULT_ANOMALY_THRESHOLD);
+
+        self.market_client.get_anomaly_summary(region_id, type_id, window, threshold).await
+    }
+
+    /// Handle detect_breakouts tool
+    async fn handle_detect_breakouts(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "detect_breakouts")?;
+        let region_id = parse_region_id(required_arg(arguments, "region_id")?)?;
+        let type_id = parse_type_id(required_arg(arguments, "type_id")?)?;
+        let window = arguments
+            .get("window_days")
+            .and_then(|v| v.as_u64())
+            .map_or(DEFAULT_BREAKOUT_WINDOW, |days| days as usize);
+
+        self.market_client.get_breakout_summary(region_id, type_id, window).await
+    }
+
+    /// Handle compare_year_over_year tool
+    async fn handle_compare_year_over_year(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "compare_year_over_year")?;
+        let region_id = parse_region_id(required_arg(arguments, "region_id")?)?;
+        let type_id = parse_type_id(required_arg(arguments, "type_id")?)?;
+        let window = arguments
+            .get("window_days")
+            .and_then(|v| v.as_u64())
+            .map_or(DEFAULT_YOY_WINDOW, |days| days as usize);
+
+        let comparison = self.market_client.year_over_year(region_id, type_id, window).await?;
+        Ok(format_year_over_year(&comparison, &self.market_client.type_label(type_id).await))
+    }
+
+    /// Handle backtest_strategy tool
+    async fn handle_backtest_strategy(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "backtest_strategy")?;
+        let region_id = parse_region_id(required_arg(arguments, "region_id")?)?;
+        let type_id = parse_type_id(required_arg(arguments, "type_id")?)?;
+        let number = |name: &str| arguments.get(name).and_then(|v| v.as_f64());
+        let defaults = BacktestRules::default();
+        let rules = BacktestRules {
+            sma_days: arguments
+                .get("sma_days")
+                .and_then(|v| v.as_u64())
+                .map_or(defaults.sma_days, |days| days as usize),
+            entry_discount_percent: number("entry_discount_percent").unwrap_or(defaults.entry_discount_percent),
+            take_profit_percent: number("take_profit_percent").unwrap_or(defaults.take_profit_percent),
+            stop_loss_percent: number("stop_loss_percent"),
+            max_holding_days: arguments.get("max_holding_days").and_then(|v| v.as_i64()),
+            position_isk: number("position_isk").unwrap_or(defaults.position_isk),
+        };
+        let trading = self.trading_profile(Some(arguments))?;
+
+        self.market_client.get_backtest_summary(region_id, type_id, &rules, &trading).await
+    }
+
+    /// Handle screen_speculation tool
+    async fn handle_screen_speculation(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "screen_speculation")?;
+        let region_id = parse_region_id(required_arg(arguments, "region_id")?)?;
+        let event = GameEvent::parse(required_arg(arguments, "event")?.as_str().unwrap_or_default())?;
+        let type_ids = parse_type_ids(arguments)?;
+        let market_group_id = arguments.get("market_group_id").and_then(|v| v.as_i64()).map(|v| v as i32);
+        let window = arguments
+            .get("window_days")
+            .and_then(|v| v.as_u64())
+            .map_or(DEFAULT_SPECULATION_WINDOW, |days| days as usize);
+        let limit = arguments
+            .get("limit")
+            .and_then(|v| v.as_u64())
+            .map_or(DEFAULT_MOVERS_LIMIT, |limit| limit as usize);
+
+        let screen = match (type_ids.is_empty(), market_group_id) {
+            (false, _) => self.market_client.screen_speculation(region_id, &type_ids, &event, window).await?,
+            (true, Some(group_id)) => {
+                self.market_client
+                    .screen_speculation_in_group(region_id, group_id, &event, window)
+                    .await?
+            }
+            (true, None) => {
+                return Err(TraderGraderError::InvalidParams(
+                    "Provide either type_ids or market_group_id".to_string(),
+                ))
+            }
+        };
+        Ok(format_speculation_screen(&screen, limit))
+    }
+
+    /// Handle compare_items tool
+    async fn handle_compare_items(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "compare_items")?;
+        let region_id = parse_region_id(required_arg(arguments, "region_id")?)?;
+        let type_ids = parse_type_ids(arguments)?;
+        let days = arguments
+            .get("days")
+            .and_then(|v| v.as_u64())
+            .map_or(DEFAULT_COMPARISON_DAYS, |days| days as usize);
+
+        self.market_client.get_item_comparison_summary(region_id, &type_ids, days).await
+    }
+
+    /// Handle estimate_undercut_rate tool
+    async fn handle_estimate_undercut_rate(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "estimate_undercut_rate")?;
+        let region_id = parse_region_id(required_arg(arguments, "region_id")?)?;
+        let type_id = parse_type_id(required_arg(arguments, "type_id")?)?;
+        let station_id = arguments.get("station_id").and_then(|v| v.as_i64());
+        let samples = arguments
+            .get("samples")
+            .and_then(|v| v.as_u64())
+            .map_or(DEFAULT_UNDERCUT_SAMPLES, |samples| samples as usize);
+
+        self.market_client
+            .get_undercut_rate_summary(region_id, type_id, station_id, samples)
+            .await
+    }
+
+    /// Handle get_spread_history tool
+    async fn handle_get_spread_history(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "get_spread_history")?;
+        let region_id = parse_region_id(required_arg(arguments, "region_id")?)?;
+        let type_id = parse_type_id(required_arg(arguments, "type_id")?)?;
+        let weeks = arguments
+            .get("weeks")
+            .and_then(|v| v.as_u64())
+            .map_or(DEFAULT_SPREAD_WEEKS, |weeks| weeks as usize);
+
+        self.market_client.get_spread_history_summary(region_id, type_id, weeks).await
+    }
+
+    /// Handle get_top_movers tool
+    async fn handle_get_top_movers(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "get_top_movers")?;
+        let region_id = parse_region_id(required_arg(arguments, "region_id")?)?;
+        let type_ids = parse_type_ids(arguments)?;
+        let market_group_id = arguments.get("market_group_id").and_then(|v| v.as_i64()).map(|v| v as i32);
+        let period = match arguments.get("period").and_then(|v| v.as_str()) {
+            Some(period) => period.parse::<Period>().map_err(TraderGraderError::InvalidParams)?,
+            None => Period::Daily,
+        };
+        let min_volume = arguments.get("min_volume").and_then(|v| v.as_i64()).unwrap_or(0);
+        let limit = arguments
+            .get("limit")
+            .and_then(|v| v.as_u64())
+            .map_or(DEFAULT_MOVERS_LIMIT, |limit| limit as usize);
+
+        let movers = match (type_ids.is_empty(), market_group_id) {
+            (false, _) => self.market_client.top_movers(region_id, &type_ids, period, min_volume).await?,
+            (true, Some(group_id)) => {
+                self.market_client
+                    .top_movers_in_group(region_id, group_id, period, min_volume)
+                    .await?
+            }
+            (true, None) => {
+                return Err(TraderGraderError::InvalidParams(
+                    "Provide either type_ids or market_group_id".to_string(),
+                ))
+            }
+        };
+        Ok(format_top_movers(&movers, limit))
+    }
+
+    /// Handle region_activity_report tool
+    async fn handle_region_activity_report(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "region_activity_report")?;
+        let region_id = parse_region_id(required_arg(arguments, "region_id")?)?;
+        let mut type_ids = parse_type_ids(arguments)?;
+        if type_ids.is_empty() {
+            type_ids = STAPLE_TYPE_IDS.to_vec();
+        }
+        let days = arguments
+            .get("days")
+            .and_then(|v| v.as_u64())
+            .map_or(DEFAULT_FLOW_DAYS, |days| days as usize);
+
+        self.market_client.get_region_flow_summary(region_id, &type_ids, days).await
+    }
+
+    /// Handle get_liquidity_score tool
+    async fn handle_get_liquidity_score(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "get_liquidity_score")?;
+        let region_id = parse_region_id(required_arg(arguments, "region_id")?)?;
+        let type_id = parse_type_id(required_arg(arguments, "type_id")?)?;
+
+        self.market_client.get_liquidity_score_summary(region_id, type_id).await
+    }
+
+    /// Handle get_order_book_depth tool
+    async fn handle_get_order_book_depth(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "get_order_book_depth")?;
+        let region_id = parse_region_id(required_arg(arguments, "region_id")?)?;
+        let type_id = parse_type_id(required_arg(arguments, "type_id")?)?;
+        let target_price = arguments.get("target_price").and_then(|v| v.as_f64());
+
+        match arguments.get("station_id").and_then(|v| v.as_i64()) {
+            Some(station_id) => {
+                self.market_client
+                    .get_station_order_book_depth_summary(region_id, type_id, station_id, target_price)
+                    .await
+            }
+            None => {
+                self.market_client
+                    .get_order_book_depth_summary(region_id, type_id, target_price)
+                    .await
+            }
+        }
+    }
+
+    /// Handle scan_public_contracts tool
+    async fn handle_scan_public_contracts(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "scan_public_contracts")?;
+        let region_id = parse_region_id(required_arg(arguments, "region_id")?)?;
+        let hub = match arguments.get("hub").and_then(|v| v.as_str()) {
+            Some(hub) => hub.parse::<TradeHub>().map_err(TraderGraderError::InvalidParams)?,
+            None => TradeHub::Jita,
+        };
+        let filter = ContractFilter {
+            item_exchange_only: arguments
+                .get("item_exchange_only")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(true),
+            max_price: arguments.get("max_price").and_then(|v| v.as_f64()),
+        };
+        let limit = arguments
+            .get("limit")
+            .and_then(|v| v.as_u64())
+            .map_or(DEFAULT_CONTRACT_LIMIT, |limit| limit as usize);
+
+        let scan = self
+            .market_client
+            .scan_public_contracts(region_id, hub, &filter, limit)
+            .await?;
+        Ok(format_contract_scan(&scan))
+    }
+
+    /// Handle courier_market_rates tool
+    async fn handle_courier_market_rates(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "courier_market_rates")?;
+        let region_id = parse_region_id(required_arg(arguments, "region_id")?)?;
+        let start_location_id = arguments.get("start_location_id").and_then(|v| v.as_i64());
+        let end_location_id = arguments.get("end_location_id").and_then(|v| v.as_i64());
+        let limit = arguments
+            .get("limit")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(10) as usize;
+
+        self.market_client
+            .courier_market_rates(region_id, start_location_id, end_location_id, limit)
+            .await
+    }
+
+    /// Handle list_market_groups tool
+    async fn handle_list_market_groups(&self, params: &Value) -> Result<String> {
+        let arguments = params.get("arguments");
+        let parent_group_id = arguments
+            .and_then(|a| a.get("parent_group_id"))
+            .and_then(|v| v.as_i64())
+            .map(|v| v as i32);
+        let search = arguments
+            .and_then(|a| a.get("search"))
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.trim().is_empty());
+
+        let page = Page::from_arguments(arguments, DEFAULT_GROUPS_PAGE);
+
+        self.market_client.list_market_groups_summary(parent_group_id, search, page).await
+    }
+
+    /// Handle get_market_group_types tool
+    async fn handle_get_market_group_types(&self, params: &Value) -> Result<String> {
+        let market_group_id = params
+            .get("arguments")
+            .and_then(|a| a.get("market_group_id"))
+            .and_then(|v| v.as_i64())
+            .ok_or_else(|| {
+                TraderGraderError::InvalidParams("Missing market_group_id for get_market_group_types".to_string())
+            })?;
+
+        let page = Page::from_arguments(params.get("arguments"), DEFAULT_GROUP_TYPES_PAGE);
+
+        self.market_client.get_market_group_types_summary(market_group_id as i32, page).await
+    }
+
+    /// Handle scan_market tool
+    async fn handle_scan_market(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "scan_market")?;
+        let region_id = parse_region_id(required_arg(arguments, "region_id")?)?;
+        let type_ids = parse_type_ids(arguments)?;
+        let market_group_id = arguments.get("market_group_id").and_then(|v| v.as_i64()).map(|v| v as i32);
+        let limit = arguments.get("limit").and_then(|v| v.as_u64()).unwrap_or(20) as usize;
+        let sort_by = match arguments.get("sort_by").and_then(|v| v.as_str()) {
+            Some(sort) => sort.parse::<ScanSort>().map_err(TraderGraderError::InvalidParams)?,
+            None => ScanSort::default(),
+        };
+        let level = |name: &str| arguments.get(name).and_then(|v| v.as_i64()).map(|v| v as i32);
+        let filter = LevelFilter {
+            min_meta_level: level("min_meta_level"),
+            max_meta_level: level("max_meta_level"),
+            tech_level: level("tech_level"),
+        };
+
+        let scan = match (type_ids.is_empty(), market_group_id) {
+            (false, _) => {
+                let type_ids = self.market_client.filter_by_level(&type_ids, &filter).await?;
+                if type_ids.is_empty() {
+                    return Ok(format!("None of the given item types are {filter}."));
+                }
+                self.market_client.scan_types(region_id, &type_ids, sort_by).await?
+            }
+            (true, Some(group_id)) => {
+                self.market_client.scan_market_group(region_id, group_id, sort_by, &filter).await?
+            }
+            (true, None) => {
+                return Err(TraderGraderError::InvalidParams(
+                    "scan_market needs type_ids or market_group_id".to_string(),
+                ))
+            }
+        };
+        Ok(MarketClient::format_market_scan(&scan, limit))
+    }
+
+    /// Handle compare_trade_hubs tool
+    async fn handle_compare_trade_hubs(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "compare_trade_hubs")?;
+        let type_id = parse_type_id(required_arg(arguments, "type_id")?)?;
+        let hubs = parse_hubs(arguments)?;
+
+        let comparison = self.market_client.compare_trade_hubs(type_id, &hubs).await?;
+        Ok(format_hub_comparison(&comparison))
+    }
+
+    /// Handle get_market_snapshot tool
+    async fn handle_get_market_snapshot(&self, params: &Value) -> Result<String> {
+        let refresh = params
+            .get("arguments")
+            .and_then(|arguments| arguments.get("refresh"))
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let snapshot = self.market_snapshot(refresh).await?;
+        Ok(format_market_snapshot(&snapshot))
+    }
+
+    /// Handle get_plex_dashboard tool
+    async fn handle_get_plex_dashboard(&self, params: &Value) -> Result<String> {
+        let arguments = params.get("arguments");
+        let region_id = match arguments.and_then(|a| a.get("region_id")) {
+            Some(region_id) => parse_region_id(region_id)?,
+            None => TradeHub::Jita.region_id(),
+        };
+        let days = arguments
+            .and_then(|a| a.get("days"))
+            .and_then(|v| v.as_u64())
+            .map_or(DEFAULT_DASHBOARD_DAYS, |days| days as usize);
+        let trading = self.trading_profile(arguments)?;
+
+        let dashboard = self.market_client.plex_dashboard(region_id, days, &trading).await?;
+        Ok(format_plex_dashboard(&dashboard))
+    }
+
+    /// Handle get_mineral_index tool
+    async fn handle_get_mineral_index(&self, params: &Value) -> Result<String> {
+        let arguments = params.get("arguments");
+        let region_id = match arguments.and_then(|a| a.get("region_id")) {
+            Some(region_id) => parse_region_id(region_id)?,
+            None => TradeHub::Jita.region_id(),
+        };
+        let days = arguments
+            .and_then(|a| a.get("days"))
+            .and_then(|v| v.as_u64())
+            .map_or(DEFAULT_INDEX_DAYS, |days| days as usize);
+        let weights = match arguments.and_then(|a| a.get("weights")).and_then(|v| v.as_object()) {
+            Some(weights) => weights
+                .iter()
+                .map(|(type_id, weight)| {
+                    let invalid = || TraderGraderError::InvalidArgument {
+                        field: format!("weights.{type_id}"),
+                        reason: "keys must be type IDs and values numbers".to_string(),
+                    };
+                    let type_id = type_id.parse::<i32>().ok().filter(|id| *id > 0).ok_or_else(invalid)?;
+                    Ok((type_id, weight.as_f64().ok_or_else(invalid)?))
+                })
+                .collect::<Result<Vec<_>>>()?,
+            None => default_weights(),
+        };
+
+        let index = self.market_client.mineral_index(region_id, &weights, days).await?;
+        Ok(format_mineral_index(&index))
+    }
+
+    /// The stored market snapshot, rebuilt first when missing, older than the refresh interval or `refresh` is set
+    ///
+    /// With the background refresher running the stored one is nearly always fresh,
+    /// so reads cost no ESI requests.
+    async fn market_snapshot(&self, refresh: bool) -> Result<MarketSnapshot> {
+        if !refresh {
+            if let Some(snapshot) = self.snapshot.fresh(self.snapshot_config.refresh_interval) {
+                return Ok(snapshot);
+            }
+        }
+        let snapshot = self.market_client.market_snapshot(&self.snapshot_config).await?;
+        self.snapshot.store(snapshot.clone());
+        Ok(snapshot)
+    }
+
+    /// Handle price_matrix tool
+    async fn handle_price_matrix(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "price_matrix")?;
+        let type_ids = parse_type_ids(arguments)?;
+        let region_ids = match arguments.get("region_ids").and_then(|v| v.as_array()) {
+            Some(ids) => ids.iter().map(parse_region_id).collect::<Result<Vec<_>>>()?,
+            None => TradeHub::ALL.iter().map(TradeHub::region_id).collect(),
+        };
+        let include_buy = arguments.get("include_buy").and_then(|v| v.as_bool()).unwrap_or(false);
+
+        let matrix = self.market_client.price_matrix(&type_ids, &region_ids, include_buy).await?;
+        Ok(format_price_matrix(&matrix))
+    }
+
+    /// Handle get_multi_region_summary tool
+    async fn handle_get_multi_region_summary(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "get_multi_region_summary")?;
+        let type_id = parse_type_id(required_arg(arguments, "type_id")?)?;
+        let region_ids = match arguments.get("region_ids").and_then(|v| v.as_array()) {
+            Some(ids) => ids.iter().map(parse_region_id).collect::<Result<Vec<_>>>()?,
+            None => TradeHub::ALL.iter().map(TradeHub::region_id).collect(),
+        };
+
+        self.market_client.get_multi_region_summary(type_id, &region_ids).await
+    }
+
+    /// The session's trading profile, with any skills, standings or venue passed to this call applied on top
+    fn trading_profile(&self, arguments: Option<&Value>) -> Result<TradingProfile> {
+        let mut profile = *self.trading_profile.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(arguments) = arguments {
+            apply_profile_arguments(&mut profile, arguments)?;
+        }
+        Ok(profile)
+    }
+
+    /// Handle set_trading_profile tool
+    async fn handle_set_trading_profile(&self, params: &Value) -> Result<String> {
+        let profile = self.trading_profile(params.get("arguments"))?;
+        *self.trading_profile.lock().unwrap_or_else(|e| e.into_inner()) = profile;
+        Ok(format!("{}\n\nSaved for this session; fee-aware tools will use it.", profile.to_text()))
+    }
+
+    /// Handle grade_trade tool
+    async fn handle_grade_trade(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "grade_trade")?;
+        let trade = ProposedTrade {
+            region_id: parse_region_id(required_arg(arguments, "region_id")?)?,
+            type_id: parse_type_id(required_arg(arguments, "type_id")?)?,
+            buy_price: arguments.get("buy_price").and_then(|v| v.as_f64()),
+            sell_price: arguments.get("sell_price").and_then(|v| v.as_f64()),
+            quantity: arguments.get("quantity").and_then(|v| v.as_i64()),
+        };
+        let trading = self.trading_profile(Some(arguments))?;
+        let mut weights = match arguments.get("profile").and_then(|v| v.as_str()) {
+            Some(profile) => profile.parse::<GradeWeights>().map_err(TraderGraderError::InvalidParams)?,
+            None => GradeWeights::default(),
+        };
+        if let Some(overrides) = arguments.get("weights").and_then(|v| v.as_object()) {
+            for (component, weight) in overrides {
+                let weight = weight.as_f64().ok_or_else(|| TraderGraderError::InvalidArgument {
+                    field: format!("weights.{component}"),
+                    reason: "must be a number".to_string(),
+                })?;
+                weights.set(component, weight)?;
+            }
+        }
+
+        let grade = self.market_client.grade_trade(&trade, &trading, &weights).await?;
+        Ok(format_trade_grade(&grade))
+    }
+
+    /// Handle estimate_time_to_sell tool
+    async fn handle_estimate_time_to_sell(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "estimate_time_to_sell")?;
+        let region_id = parse_region_id(required_arg(arguments, "region_id")?)?;
+        let type_id = parse_type_id(required_arg(arguments, "type_id")?)?;
+        let quantity = required_arg(arguments, "quantity")?.as_i64().ok_or_else(|| TraderGraderError::InvalidArgument {
+            field: "quantity".to_string(),
+            reason: "must be an integer".to_string(),
+        })?;
+        let price = arguments.get("price").and_then(|v| v.as_f64());
+        self.market_client.get_time_to_sell_summary(region_id, type_id, quantity, price).await
+    }
+
+    /// Handle estimate_buy_fill_time tool
+    async fn handle_estimate_buy_fill_time(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "estimate_buy_fill_time")?;
+        let region_id = parse_region_id(required_arg(arguments, "region_id")?)?;
+        let type_id = parse_type_id(required_arg(arguments, "type_id")?)?;
+        let quantity = required_arg(arguments, "quantity")?.as_i64().ok_or_else(|| TraderGraderError::InvalidArgument {
+            field: "quantity".to_string(),
+            reason: "must be an integer".to_string(),
+        })?;
+        let price = arguments.get("price").and_then(|v| v.as_f64());
+        self.market_client.get_buy_fill_time_summary(region_id, type_id, quantity, price).await
+    }
+
+    /// Handle suggest_listing_price tool
+    async fn handle_suggest_listing_price(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "suggest_listing_price")?;
+        let region_id = parse_region_id(required_arg(arguments, "region_id")?)?;
+        let type_id = parse_type_id(required_arg(arguments, "type_id")?)?;
+        let side = match arguments.get("side").and_then(|v| v.as_str()) {
+            Some(side) => side.parse::<TradeSide>().map_err(TraderGraderError::InvalidParams)?,
+            None => TradeSide::Sell,
+        };
+        let station_id = arguments.get("station_id").and_then(|v| v.as_i64());
+        self.market_client.get_listing_price_summary(region_id, type_id, side, station_id).await
+    }
+
+    /// Handle export_market_history tool
+    async fn handle_export_market_history(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "export_market_history")?;
+        let region_id = parse_region_id(required_arg(arguments, "region_id")?)?;
+        let type_id = parse_type_id(required_arg(arguments, "type_id")?)?;
+        let days = arguments.get("days").and_then(|v| v.as_u64()).map(|days| days as usize);
+
+        let history = self.market_client.fetch_market_history(region_id, type_id).await?;
+        history_csv(&recent_history(&history, days))
+    }
+
+    /// Handle start_prefetch tool
+    fn handle_start_prefetch(&self, params: &Value) -> Result<String> {
+        let no_arguments = Value::Object(Default::default());
+        let arguments = params.get("arguments").unwrap_or(&no_arguments);
+
+        let mut targets = match arguments.get("targets").and_then(|v| v.as_array()) {
+            Some(targets) => targets
+                .iter()
+                .map(|target| {
+                    Ok(PrefetchTarget::new(
+                        parse_region_id(required_arg(target, "region_id")?)?,
+                        parse_type_id(required_arg(target, "type_id")?)?,
+                    ))
+                })
+                .collect::<Result<Vec<_>>>()?,
+            None => Vec::new(),
+        };
+        if let Some(code) = arguments.get("watchlist_code").and_then(|v| v.as_str()) {
+            let region_id = arguments.get("region_id").map(parse_region_id).transpose()?;
+            let watchlist = Watchlist::from_code(code)?;
+            targets.extend(PrefetchConfig::from_watchlist(&watchlist, region_id)?.targets);
+        }
+        if targets.is_empty() {
+            targets = self.prefetch_defaults.targets.clone();
+        }
+        if targets.is_empty() {
+            return Err(TraderGraderError::InvalidParams(
+                "Give targets or a watchlist_code to prefetch; no [prefetch] targets are configured".to_string(),
+            ));
+        }
+        let mut seen = std::collections::HashSet::new();
+        targets.retain(|target| seen.insert(*target));
+
+        let mut config = PrefetchConfig {
+            targets,
+            ..self.prefetch_defaults.clone()
+        };
+        if let Some(include_history) = arguments.get("include_history").and_then(|v| v.as_bool()) {
+            config = config.with_history(include_history);
+        }
+        if let Some(secs) = arguments.get("lead_time_secs").and_then(|v| v.as_u64()) {
+            config = config.with_lead_time(Duration::from_secs(secs));
+        }
+
+        let prefetcher = Prefetcher::start(Arc::clone(&self.market_client), config)?;
+        let config = prefetcher.config().clone();
+        let previous = self.prefetch.lock().unwrap_or_else(|e| e.into_inner()).replace(prefetcher);
+
+        let mut text = format!(
+            "Prefetching {} for {} item(s), refreshing {}s before the cached data expires:\n",
+            if config.include_history { "orders and history" } else { "orders" },
+            config.targets.len(),
+            config.lead_time.as_secs()
+        );
+        for target in config.targets.iter().take(10) {
+            text.push_str(&format!("- type {} in region {}\n", target.type_id, target.region_id));
+        }
+        if config.targets.len() > 10 {
+            text.push_str(&format!("- ...and {} more\n", config.targets.len() - 10));
+        }
+        if let Some(previous) = previous {
+            let stats = previous.stop();
+            text.push_str(&format!(
+                "\nReplaced the previous prefetch ({} refreshes, {} failures).",
+                stats.refreshes, stats.failures
+            ));
+        }
+        Ok(text)
+    }
+
+    /// Handle stop_prefetch tool
+    fn handle_stop_prefetch(&self) -> Result<String> {
+        let Some(prefetcher) = self.prefetch.lock().unwrap_or_else(|e| e.into_inner()).take() else {
+            return Ok("No prefetch is running.".to_string());
+        };
+        let items = prefetcher.config().targets.len();
+        let stats = prefetcher.stop();
+        let mut text = format!(
+            "Stopped prefetching {items} item(s) after {} checks: {} refreshes, {} failures.",
+            stats.checks, stats.refreshes, stats.failures
+        );
+        if let Some(error) = stats.last_error {
+            text.push_str(&format!("\nLast error: {error}"));
+        }
+        Ok(text)
+    }
+
+    /// Handle set_price_alert tool
+    fn handle_set_price_alert(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "set_price_alert")?;
+        let region_id = parse_region_id(required_arg(arguments, "region_id")?)?;
+        let type_id = parse_type_id(required_arg(arguments, "type_id")?)?;
+        let condition = required_arg(arguments, "condition")?.as_str().unwrap_or_default().parse()?;
+        let threshold = required_arg(arguments, "threshold")?.as_f64().unwrap_or_default();
+        let side = parse_alert_side(arguments.get("side").and_then(|v| v.as_str()).unwrap_or("sell"))?;
+
+        let alert = self.alerts.add(region_id, type_id, side, condition, threshold)?;
+        self.alerts.ensure_polling(Arc::clone(&self.market_client), self.logger.clone());
+        Ok(format!(
+            "Alert #{} set: {}.\nYou'll get an alert-level notification when it triggers; remove it with remove_alert.",
+            alert.id,
+            alert.describe()
+        ))
+    }
+
+    /// Handle list_alerts tool
+    fn handle_list_alerts(&self) -> String {
+        let alerts = self.alerts.list();
+        if alerts.is_empty() {
+            return "No price alerts are set.".to_string();
+        }
+        let mut text = format!("{} price alert(s):\n", alerts.len());
+        for alert in alerts {
+            text.push_str(&format!("#{}: {}", alert.id, alert.describe()));
+            if let Some(price) = alert.last_price {
+                text.push_str(&format!(", last seen {price:.2} ISK"));
+            }
+            if let Some(triggered) = alert.last_triggered {
+                text.push_str(&format!(", last triggered {}", triggered.format("%Y-%m-%d %H:%M UTC")));
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Handle remove_alert tool
+    fn handle_remove_alert(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "remove_alert")?;
+        let id = required_arg(arguments, "alert_id")?.as_u64().unwrap_or_default();
+        if self.alerts.remove(id) {
+            Ok(format!("Removed alert #{id}."))
+        } else {
+            Err(TraderGraderError::InvalidArgument {
+                field: "alert_id".to_string(),
+                reason: format!("no alert #{id}; list_alerts shows the current ones"),
+            })
+        }
+    }
+
+    /// Handle watch_items and unwatch_items tools
+    fn handle_watch_items(&self, params: &Value, watch: bool) -> Result<String> {
+        let tool = if watch { "watch_items" } else { "unwatch_items" };
+        let arguments = required_arguments(params, tool)?;
+        let region_id = parse_region_id(required_arg(arguments, "region_id")?)?;
+        required_arg(arguments, "type_ids")?;
+        let type_ids = parse_type_ids(arguments)?;
+
+        let changed = if watch {
+            self.watchlist.add(region_id, &type_ids)?
+        } else {
+            self.watchlist.remove(region_id, &type_ids)?
+        };
+        if changed > 0 {
+            self.watchlist_changed();
+        }
+        let (verb, preposition) = if watch { ("Added", "to") } else { ("Removed", "from") };
+        Ok(format!(
+            "{verb} {changed} item(s) {preposition} the watchlist for region {region_id}; it now holds {} item(s).\n\
+             Read them as resources from {WATCHLIST_URI}.",
+            self.watchlist.entries().len()
+        ))
+    }
+
+    /// Handle export_watchlist tool
+    fn handle_export_watchlist(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "export_watchlist")?;
+        let name = required_arg(arguments, "name")?.as_str().unwrap_or_default();
+        let type_ids = parse_type_ids(arguments)?;
+        let region_id = arguments.get("region_id").map(parse_region_id).transpose()?;
+
+        let watchlist = Watchlist::new(name, region_id, type_ids)?;
+        Ok(format!(
+            "Share code for watchlist \"{}\" ({} items):\n{}\n\nAnyone can load it with import_watchlist.",
+            watchlist.name,
+            watchlist.type_ids.len(),
+            watchlist.export_code()?
+        ))
+    }
+
+    /// Handle import_watchlist tool
+    async fn handle_import_watchlist(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "import_watchlist")?;
+        let code = required_arg(arguments, "code")?.as_str().unwrap_or_default();
+
+        let watchlist = Watchlist::from_code(code)?;
+        Ok(self.market_client.watchlist_summary(&watchlist).await)
+    }
+
+    /// Handle export_state tool
+    fn handle_export_state(&self) -> Result<String> {
+        let profile = self.trading_profile(None)?;
+        let document = StateDocument::capture(&self.watchlist, &self.alerts, &self.portfolio, profile);
+        Ok(serde_json::to_string_pretty(&document)?)
+    }
+
+    /// Handle import_state tool
+    fn handle_import_state(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "import_state")?;
+        let document = match required_arg(arguments, "state")? {
+            Value::String(text) => StateDocument::parse(text)?,
+            object => StateDocument::parse(&object.to_string())?,
+        };
+        let mode = match arguments.get("mode").and_then(|v| v.as_str()) {
+            Some(mode) => mode.parse::<ImportMode>()?,
+            None => ImportMode::default(),
+        };
+
+        let imported = document.import_into(&self.watchlist, &self.alerts, &self.portfolio, mode)?;
+        if let Some(profile) = imported.trading_profile {
+            *self.trading_profile.lock().unwrap_or_else(|e| e.into_inner()) = profile;
+        }
+        if !self.alerts.list().is_empty() {
+            self.alerts.ensure_polling(Arc::clone(&self.market_client), self.logger.clone());
+        }
+        if mode == ImportMode::Replace || imported.watchlist_items > 0 {
+            self.watchlist_changed();
+        }
+        Ok(format!(
+            "Imported state exported {} ({mode}): {} new watched item(s), {} alert(s), {} portfolio position(s){}.",
+            document.exported_at.format("%Y-%m-%d %H:%M UTC"),
+            imported.watchlist_items,
+            imported.alerts,
+            imported.positions,
+            if imported.trading_profile.is_some() { " and the trading profile" } else { "" }
+        ))
+    }
+
+    /// Handle portfolio_add tool
+    fn handle_portfolio_add(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "portfolio_add")?;
+        let type_id = parse_type_id(required_arg(arguments, "type_id")?)?;
+        let quantity = required_arg(arguments, "quantity")?.as_i64().unwrap_or_default();
+        let unit_cost = required_arg(arguments, "unit_cost")?.as_f64().unwrap_or_default();
+
+        let position = self.portfolio.add(type_id, quantity, unit_cost)?;
+        Ok(format!(
+            "Added {quantity} units of type {type_id} to the portfolio; the position is now {} units at an average cost \
+             of {:.2} ISK ({:.2} ISK in total).",
+            position.quantity,
+            position.average_cost(),
+            position.cost_basis
+        ))
+    }
+
+    /// Handle portfolio_remove tool
+    fn handle_portfolio_remove(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "portfolio_remove")?;
+        let type_id = parse_type_id(required_arg(arguments, "type_id")?)?;
+        let quantity = arguments.get("quantity").and_then(|v| v.as_i64());
+
+        Ok(match self.portfolio.remove(type_id, quantity)? {
+            Some(left) => format!(
+                "Removed {} units of type {type_id}; {} units remain at an average cost of {:.2} ISK.",
+                quantity.unwrap_or_default(),
+                left.quantity,
+                left.average_cost()
+            ),
+            None => format!("Removed type {type_id} from the portfolio."),
+        })
+    }
+
+    /// Handle portfolio_value tool
+    async fn handle_portfolio_value(&self, params: &Value) -> Result<String> {
+        let region_id = match params.get("arguments").and_then(|a| a.get("region_id")) {
+            Some(region_id) => parse_region_id(region_id)?,
+            None => TradeHub::Jita.region_id(),
+        };
+
+        let valuation = self.market_client.value_portfolio(region_id, self.portfolio.positions()).await?;
+        Ok(format_portfolio_valuation(&valuation))
+    }
+
+    /// Handle record_trade tool
+    fn handle_record_trade(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "record_trade")?;
+        let type_id = parse_type_id(required_arg(arguments, "type_id")?)?;
+        let side = required_arg(arguments, "side")?
+            .as_str()
+            .unwrap_or_default()
+            .parse::<TradeSide>()
+            .map_err(TraderGraderError::InvalidParams)?;
+        let quantity = required_arg(arguments, "quantity")?.as_i64().unwrap_or_default();
+        let unit_price = required_arg(arguments, "unit_price")?.as_f64().unwrap_or_default();
+        let fees = arguments.get("fees").and_then(|v| v.as_f64()).unwrap_or(0.0);
+        let date = match arguments.get("date").and_then(|v| v.as_str()) {
+            Some(date) => date.to_string(),
+            None => chrono::Utc::now().to_rfc3339(),
+        };
+
+        let trade = JournalTrade::new(&date, type_id, side, quantity, unit_price, fees)?;
+        let summary = format!(
+            "Recorded {side} of {quantity} x type {type_id} at {unit_price:.2} ISK ({fees:.2} ISK fees) on {}.",
+            trade.date
+        );
+        self.journal.record([trade])?;
+        Ok(summary)
+    }
+
+    /// Handle import_wallet_trades tool
+    async fn handle_import_wallet_trades(&self, params: &Value) -> Result<String> {
+        let arguments = params.get("arguments");
+        let character_id = arguments.and_then(|a| a.get("character_id")).and_then(|v| v.as_i64());
+        let skills = self.trading_profile(arguments)?.skills;
+
+        let sso = self.market_client.authenticator().ok_or_else(|| {
+            TraderGraderError::AuthenticationError(
+                "EVE SSO is not configured; set TRADERGRADER_SSO_CLIENT_ID and use authenticate_character".to_string(),
+            )
+        })?;
+        let character_id = sso.resolve_character(character_id)?;
+        let trades = self.market_client.wallet_trades(character_id, &skills).await?;
+        let fetched = trades.len();
+        let added = self.journal.record(trades)?;
+        Ok(format!(
+            "Imported {added} new trade(s) from {fetched} wallet transaction(s); the rest were already in the journal.\n\
+             Sales tax on sells is estimated from the fee schedule of the day; broker fees aren't in wallet \
+             transactions, so record them with record_trade if they matter."
+        ))
+    }
+
+    /// Handle trade_report tool
+    async fn handle_trade_report(&self, params: &Value) -> Result<String> {
+        let type_ids = match params.get("arguments") {
+            Some(arguments) => parse_type_ids(arguments)?,
+            None => Vec::new(),
+        };
+
+        let trades: Vec<JournalTrade> = self
+            .journal
+            .trades()
+            .into_iter()
+            .filter(|t| type_ids.is_empty() || type_ids.contains(&t.type_id))
+            .collect();
+        let report = TradeReport::from_trades(&trades);
+        Ok(self.market_client.trade_report_summary(&report).await)
+    }
+
+    /// Handle fuel_block_analysis tool
+    async fn handle_fuel_block_analysis(&self, params: &Value) -> Result<String> {
+        let arguments = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+        let hubs = parse_hubs(&arguments)?;
+        let material_efficiency = arguments
+            .get("material_efficiency")
+            .and_then(|v| v.as_i64())
+            .map_or(MAX_MATERIAL_EFFICIENCY, |me| me as i32);
+        let trading = self.trading_profile(Some(&arguments))?;
+
+        let analysis = self.market_client.fuel_block_analysis(&hubs, material_efficiency, &trading).await?;
+        Ok(format_fuel_block_analysis(&analysis))
+    }
+
+    /// Handle lp_store_value tool
+    async fn handle_lp_store_value(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "lp_store_value")?;
+        let corporation_id = required_arg(arguments, "corporation_id")?
+            .as_i64()
+            .ok_or_else(|| TraderGraderError::InvalidParams("corporation_id must be an integer".to_string()))?
+            as i32;
+        let hub = match arguments.get("hub").and_then(|v| v.as_str()) {
+            Some(hub) => hub.parse::<TradeHub>().map_err(TraderGraderError::InvalidParams)?,
+            None => TradeHub::Jita,
+        };
+        let output_basis = match arguments.get("output_price").and_then(|v| v.as_str()) {
+            Some(basis) => basis.parse::<PriceBasis>().map_err(TraderGraderError::InvalidParams)?,
+            None => PriceBasis::Buy,
+        };
+        let limit = arguments
+            .get("limit")
+            .and_then(|v| v.as_u64())
+            .map_or(DEFAULT_OFFER_LIMIT, |limit| limit as usize);
+        let trading = self.trading_profile(Some(arguments))?;
+
+        let store = self
+            .market_client
+            .lp_store_value(corporation_id, hub, output_basis, limit, &trading)
+            .await?;
+        Ok(format_lp_store_value(&store))
+    }
+
+    /// Handle insurance_analysis tool
+    async fn handle_insurance_analysis(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "insurance_analysis")?;
+        let type_id = parse_type_id(required_arg(arguments, "type_id")?)?;
+        let hub = match arguments.get("hub").and_then(|v| v.as_str()) {
+            Some(hub) => hub.parse::<TradeHub>().map_err(TraderGraderError::InvalidParams)?,
+            None => TradeHub::Jita,
+        };
+        let material_efficiency = arguments
+            .get("material_efficiency")
+            .and_then(|v| v.as_i64())
+            .map_or(0, |me| me as i32);
+
+        let analysis = self.market_client.insurance_analysis(type_id, hub, material_efficiency).await?;
+        Ok(format_insurance_analysis(&analysis))
+    }
+
+    /// Handle manufacturing_profit tool
+    async fn handle_manufacturing_profit(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "manufacturing_profit")?;
+        let type_id = parse_type_id(required_arg(arguments, "type_id")?)?;
+        let defaults = ManufacturingJob::default();
+        let basis = |field: &str, default: PriceBasis| match arguments.get(field).and_then(|v| v.as_str()) {
+            Some(basis) => basis.parse::<PriceBasis>().map_err(TraderGraderError::InvalidParams),
+            None => Ok(default),
+        };
+        let job = ManufacturingJob {
+            runs: arguments.get("runs").and_then(|v| v.as_i64()).unwrap_or(defaults.runs),
+            material_efficiency: arguments
+                .get("material_efficiency")
+                .and_then(|v| v.as_i64())
+                .map_or(defaults.material_efficiency, |me| me as i32),
+            time_efficiency: arguments
+                .get("time_efficiency")
+                .and_then(|v| v.as_i64())
+                .map_or(defaults.time_efficiency, |te| te as i32),
+            hub: match arguments.get("hub").and_then(|v| v.as_str()) {
+                Some(hub) => hub.parse::<TradeHub>().map_err(TraderGraderError::InvalidParams)?,
+                None => defaults.hub,
+            },
+            system_id: arguments.get("system_id").and_then(|v| v.as_i64()).map(|id| id as i32),
+            facility_tax_percent: arguments
+                .get("facility_tax_percent")
+                .and_then(|v| v.as_f64())
+                .unwrap_or(defaults.facility_tax_percent),
+            input_basis: basis("input_price", defaults.input_basis)?,
+            product_basis: basis("product_price", defaults.product_basis)?,
+            trading: self.trading_profile(Some(arguments))?,
+        };
+
+        self.market_client.get_manufacturing_profit_summary(type_id, &job).await
+    }
+
+    /// Handle get_region_activity tool
+    async fn handle_get_region_activity(&self, params: &Value) -> Result<String> {
+        let region_ids = params
+            .get("arguments")
+            .and_then(|a| a.get("region_ids"))
+            .and_then(|v| v.as_array())
+            .map(|ids| ids.iter().map(parse_region_id).collect::<Result<Vec<_>>>())
+            .transpose()?
+            .unwrap_or_default();
+
+        if region_ids.is_empty() {
+            return Err(TraderGraderError::InvalidParams(
+                "Missing region_ids for get_region_activity".to_string(),
+            ));
+        }
+
+        self.market_client.get_region_activity_summary(&region_ids).await
+    }
+
+    /// Handle jf_route_profit tool
+    async fn handle_jf_route_profit(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "jf_route_profit")?;
+        let waypoints: Vec<i32> = arguments
+            .get("waypoints")
+            .and_then(|v| v.as_array())
+            .map(|ids| ids.iter().filter_map(|id| id.as_i64()).map(|id| id as i32).collect())
+            .unwrap_or_default();
+        let ship = arguments
+            .get("ship")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .parse::<JumpFreighter>()
+            .map_err(TraderGraderError::InvalidParams)?;
+        let cargo = HaulCargo {
+            source_region_id: parse_region_id(required_arg(arguments, "source_region_id")?)?,
+            destination_region_id: parse_region_id(required_arg(arguments, "destination_region_id")?)?,
+            type_id: parse_type_id(required_arg(arguments, "type_id")?)?,
+            quantity: arguments.get("quantity").and_then(|v| v.as_i64()).unwrap_or(0),
+        };
+        let mut fuel = JumpFuelConfig::default();
+        if let Some(level) = arguments.get("skill_level").and_then(|v| v.as_u64()) {
+            fuel.jump_fuel_conservation = level.min(5) as u8;
+            fuel.jump_freighters = level.min(5) as u8;
+        }
+
+        let profit = self.market_client.jf_route_profit(&waypoints, ship, &fuel, &cargo).await?;
+        Ok(format_jump_freighter_profit(&profit))
+    }
+
+    /// Handle get_region_context tool
+    async fn handle_get_region_context(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "get_region_context")?;
+        let region_id = parse_region_id(required_arg(arguments, "region_id")?)?;
+        let context = self.market_client.region_context(region_id).await?;
+        Ok(format_region_context(&context))
+    }
+
+    /// Handle plan_route tool
+    async fn handle_plan_route(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "plan_route")?;
+        let system_arg = |name: &str| required_arg(arguments, name).map(|v| v.as_i64().unwrap_or(0) as i32);
+        let origin_system_id = system_arg("origin_system_id")?;
+        let destination_system_id = system_arg("destination_system_id")?;
+        let flag = match arguments.get("route_flag").and_then(|v| v.as_str()) {
+            Some(flag) => flag.parse::<RouteFlag>().map_err(TraderGraderError::InvalidParams)?,
+            None => RouteFlag::default(),
+        };
+
+        let plan = self.market_client.plan_route(origin_system_id, destination_system_id, flag).await?;
+        Ok(format_route_plan(&plan))
+    }
+
+    /// Handle get_route_risk tool
+    async fn handle_get_route_risk(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "get_route_risk")?;
+        let system_arg = |name: &str| required_arg(arguments, name).map(|v| v.as_i64().unwrap_or(0) as i32);
+        let origin_system_id = system_arg("origin_system_id")?;
+        let destination_system_id = system_arg("destination_system_id")?;
+        let flag = match arguments.get("route_flag").and_then(|v| v.as_str()) {
+            Some(flag) => flag.parse::<RouteFlag>().map_err(TraderGraderError::InvalidParams)?,
+            None => RouteFlag::default(),
+        };
+
+        let risk = self.market_client.get_route_risk(origin_system_id, destination_system_id, flag).await?;
+        Ok(format_route_risk(&risk))
+    }
+
+    /// Handle hauling_analysis tool
+    async fn handle_hauling_analysis(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "hauling_analysis")?;
+        let int_arg = |name: &str| arguments.get(name).and_then(|v| v.as_i64()).unwrap_or(0) as i32;
+        let origin_system_id = int_arg("origin_system_id");
+        let destination_system_id = int_arg("destination_system_id");
+        let cargo_capacity_m3 = arguments
+            .get("cargo_capacity_m3")
+            .and_then(|v| v.as_f64())
+            .unwrap_or(DEFAULT_CARGO_CAPACITY_M3);
+        let limit = arguments.get("limit").and_then(|v| v.as_u64()).unwrap_or(20) as usize;
+        let skills = self.trading_profile(Some(arguments))?.skills;
+        let flag = match arguments.get("route_flag").and_then(|v| v.as_str()) {
+            Some(flag) => flag.parse::<RouteFlag>().map_err(TraderGraderError::InvalidParams)?,
+            None => RouteFlag::default(),
+        };
+
+        let mut type_ids = parse_type_ids(arguments)?;
+        if type_ids.is_empty() {
+            if let Some(group_id) = arguments.get("market_group_id").and_then(|v| v.as_i64()) {
+                type_ids = self.market_client.fetch_market_group(group_id as i32).await?.types;
+            }
+        }
+
+        let analysis = self
+            .market_client
+            .hauling_analysis(origin_system_id, destination_system_id, &type_ids, cargo_capacity_m3, flag, &skills)
+            .await?;
+        Ok(format_hauling_analysis(&analysis, limit))
+    }
+
+    /// Handle price_courier_contract tool
+    async fn handle_price_courier_contract(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "price_courier_contract")?;
+        let system_arg = |name: &str| required_arg(arguments, name).map(|v| v.as_i64().unwrap_or(0) as i32);
+        let origin_system_id = system_arg("origin_system_id")?;
+        let destination_system_id = system_arg("destination_system_id")?;
+        let number = |name: &str| arguments.get(name).and_then(|v| v.as_f64());
+        let volume_m3 = required_arg(arguments, "volume_m3")?.as_f64().unwrap_or_default();
+        let collateral = number("collateral").unwrap_or(0.0);
+        let flag = match arguments.get("route_flag").and_then(|v| v.as_str()) {
+            Some(flag) => flag.parse::<RouteFlag>().map_err(TraderGraderError::InvalidParams)?,
+            None => RouteFlag::Secure,
+        };
+        let defaults = self.courier_rates;
+        let rates = CourierRates {
+            isk_per_jump: number("isk_per_jump").unwrap_or(defaults.isk_per_jump),
+            isk_per_m3_per_jump: number("isk_per_m3_per_jump").unwrap_or(defaults.isk_per_m3_per_jump),
+            collateral_percent: number("collateral_percent").unwrap_or(defaults.collateral_percent),
+            low_sec_multiplier: number("low_sec_multiplier").unwrap_or(defaults.low_sec_multiplier),
+            null_sec_multiplier: number("null_sec_multiplier").unwrap_or(defaults.null_sec_multiplier),
+            minimum_reward: number("minimum_reward").unwrap_or(defaults.minimum_reward),
+        };
+
+        let quote = self
+            .market_client
+            .price_courier_contract(origin_system_id, destination_system_id, volume_m3, collateral, flag, &rates)
+            .await?;
+        Ok(format_courier_quote(&quote, flag))
+    }
+
+    /// Handle esi_get tool
+    async fn handle_esi_get(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "esi_get")?;
+        let path = required_arg(arguments, "path")?.as_str().unwrap_or_default();
+        let query: Vec<(String, String)> = arguments
+            .get("params")
+            .and_then(|v| v.as_object())
+            .map(|params| {
+                params
+                    .iter()
+                    .map(|(name, value)| match value {
+                        Value::String(text) => (name.clone(), text.clone()),
+                        other => (name.clone(), other.to_string()),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let query: Vec<(&str, &str)> = query.iter().map(|(name, value)| (name.as_str(), value.as_str())).collect();
+
+        let data = self.market_client.esi_get(path, &query).await?;
+        let mut text = serde_json::to_string_pretty(&data)?;
+        if text.len() > MAX_ESI_GET_TEXT {
+            let cut = (0..=MAX_ESI_GET_TEXT).rev().find(|i| text.is_char_boundary(*i)).unwrap_or(0);
+            text.truncate(cut);
+            text.push_str("\n… (truncated; narrow the request with query parameters such as type_id or page)");
+        }
+        Ok(text)
+    }
+
+    /// Handle get_structure_market_summary tool
+    async fn handle_get_structure_market_summary(&self, params: &Value) -> Result<String> {
+        let arguments = required_arguments(params, "get_structure_market_summary")?;
+        let structure_id = arguments
+            .get("structure_id")
+            .and_then(|v| v.as_i64())
+            .unwrap_or(0);
+        let type_id = parse_type_id(required_arg(arguments, "type_id")?)?;
+
+        self.market_client.get_structure_market_summary(structure_id, type_id).await
+    }
+
+    /// Handle authenticate_character tool
+    async fn handle_authenticate_character(&self, params: &Value) -> Result<String> {
+        let sso = self.market_client.authenticator().ok_or_else(|| {
+            TraderGraderError::AuthenticationError(
+                "EVE SSO is not configured. Set TRADERGRADER_SSO_CLIENT_ID (and optionally TRADERGRADER_SSO_CALLBACK_URL, TRADERGRADER_TOKEN_STORE) and restart the server".to_string(),
+            )
+        })?;
+
+        let arguments = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+        let (code, state) = match arguments.get("redirect_url").and_then(|v| v.as_str()) {
+            Some(redirect_url) => parse_callback_url(redirect_url),
+            None => (
+                arguments.get("code").and_then(|v| v.as_str()).map(str::to_string),
+                arguments.get("state").and_then(|v| v.as_str()).map(str::to_string),
+            ),
+        };
+
+        match (code, state) {
+            (Some(code), Some(state)) => sso.complete_login(&code, &state).await.map(|token| {
+                format!(
+                    "✅ Authenticated {} (character ID {})\nGranted scopes: {}\nToken expires: {}",
+                    token.character_name,
+                    token.character_id,
+                    token.scopes.join(", "),
+                    token.expires_at.to_rfc3339()
+                )
+            }),
+            _ => sso.start_login().map(|login| {
+                format!(
+                    "Open this URL in a browser and log in with EVE SSO:\n{}\n\n\
+                    After logging in, call authenticate_character again with the full redirect URL \
+                    (or its code and state values). State: {}",
+                    login.url, login.state
+                )
+            }),
+        }
+    }
+
+    /// Handle get_my_orders tool
+    async fn handle_get_my_orders(&self, params: &Value) -> Result<String> {
+        let character_id = params
+            .get("arguments")
+            .and_then(|a| a.get("character_id"))
+            .and_then(|v| v.as_i64());
+
+        let sso = self.market_client.authenticator().ok_or_else(|| {
+            TraderGraderError::AuthenticationError(
+                "EVE SSO is not configured; set TRADERGRADER_SSO_CLIENT_ID and use authenticate_character".to_string(),
+            )
+        })?;
+        let character_id = sso.resolve_character(character_id)?;
+        let statuses = self.market_client.check_order_undercuts(character_id).await?;
+
+        if statuses.is_empty() {
+            return Ok("No open market orders".to_string());
+        }
+
+        let undercut = statuses.iter().filter(|s| s.is_undercut).count();
+        let ids: Vec<i64> = statuses
+            .iter()
+            .flat_map(|s| [s.order.type_id as i64, s.order.location_id])
+            .collect();
+        let names = self.market_client.resolve_names(&ids).await.unwrap_or_default();
+        let name = |id: i64| names.get(&id).map_or_else(|| id.to_string(), |n| n.name.clone());
+        let mut text = format!(
+            "{} open orders, {} undercut/outbid:\n",
+            statuses.len(),
+            undercut
+        );
+        for status in &statuses {
+            let order = &status.order;
+            let side = if order.is_buy_order { "BUY " } else { "SELL" };
+            let verdict = match (status.undercut_by, status.best_competitor_price) {
+                (Some(delta), Some(best)) => format!(
+                    "⚠️ {} by {:.2} ISK (best competitor {:.2} ISK)",
+                    if order.is_buy_order { "outbid" } else { "undercut" },
+                    delta,
+                    best
+                ),
+                (None, Some(_)) => format!("✅ best price ({} competitors)", status.competitor_count),
+                _ => "✅ no competition at this location".to_string(),
+            };
+            text.push_str(&format!(
+                "{} {} @ {:.2} ISK, {}/{} remaining, {}: {}\n",
+                side,
+                name(order.type_id as i64),
+                order.price,
+                order.volume_remain,
+                order.volume_total,
+                name(order.location_id),
+                verdict
+            ));
+        }
+        Ok(text)
+    }
+
+    /// Handle cancellation notifications
+    fn handle_cancelled(&self, _message: &Value) -> Value {
+        // Notifications don't require responses
+        json!(null)
+    }
+}
+
+/// Longest esi_get response returned to the client, in bytes
+const MAX_ESI_GET_TEXT: usize = 20_000;
+
+/// The tool call's `arguments`, which the tool can't run without
+fn required_arguments<'a>(params: &'a Value, tool: &str) -> Result<&'a Value> {
+    params
+        .get("arguments")
+        .ok_or_else(|| TraderGraderError::InvalidParams(format!("Missing arguments for {tool}")))
+}
+
+/// A named argument the tool can't run without
+fn required_arg<'a>(arguments: &'a Value, name: &str) -> Result<&'a Value> {
+    arguments
+        .get(name)
+        .ok_or_else(|| TraderGraderError::InvalidParams(format!("Missing {name}")))
+}
+
+/// Validates a region ID argument
+fn parse_region_id(value: &Value) -> Result<i32> {
+    match value.as_i64() {
+        Some(id) if REGION_ID_RANGE.contains(&id) => Ok(id as i32),
+        Some(id) => Err(TraderGraderError::InvalidRegionId { region_id: id as i32 }),
+        None => Err(TraderGraderError::InvalidParams(format!("Region ID must be an integer, got {value}"))),
+    }
+}
+
+/// Validates a type ID argument
+fn parse_type_id(value: &Value) -> Result<i32> {
+    match value.as_i64() {
+        Some(id) if id > 0 && id <= i32::MAX as i64 => Ok(id as i32),
+        Some(id) => Err(TraderGraderError::InvalidTypeId { type_id: id as i32 }),
+        None => Err(TraderGraderError::InvalidParams(format!("Type ID must be an integer, got {value}"))),
+    }
+}
+
+/// Validates the optional `type_ids` list argument
+/// Applies the skill, standing and venue arguments of a call to a trading profile
+fn apply_profile_arguments(profile: &mut TradingProfile, arguments: &Value) -> Result<()> {
+    for (name, level) in [
+        ("accounting_level", &mut profile.skills.accounting),
+        ("broker_relations_level", &mut profile.skills.broker_relations),
+    ] {
+        if let Some(value) = arguments.get(name).and_then(|v| v.as_u64()) {
+            *level = value.min(5) as u8;
+        }
+    }
+    for (name, standing) in [
+        ("faction_standing", &mut profile.skills.faction_standing),
+        ("corporation_standing", &mut profile.skills.corporation_standing),
+    ] {
+        if let Some(value) = arguments.get(name).and_then(|v| v.as_f64()) {
+            if !(-10.0..=10.0).contains(&value) {
+                return Err(TraderGraderError::InvalidArgument {
+                    field: name.to_string(),
+                    reason: "must be between -10 and 10".to_string(),
+                });
+            }
+            *standing = value;
+        }
+    }
+
+    let broker_fee = arguments.get("structure_broker_fee_percent").and_then(|v| v.as_f64());
+    if broker_fee.is_some_and(|fee| !(0.0..100.0).contains(&fee)) {
+        return Err(TraderGraderError::InvalidArgument {
+            field: "structure_broker_fee_percent".to_string(),
+            reason: "must be at least 0 and below 100".to_string(),
+        });
+    }
+    profile.venue = match (arguments.get("venue").and_then(|v| v.as_str()), broker_fee) {
+        (Some("npc_station"), _) => TradingVenue::NpcStation,
+        (Some("structure") | None, Some(broker_fee_percent)) => TradingVenue::Structure { broker_fee_percent },
+        (Some("structure"), None) if matches!(profile.venue, TradingVenue::Structure { .. }) => profile.venue,
+        (Some("structure"), None) => {
+            return Err(TraderGraderError::InvalidArgument {
+                field: "structure_broker_fee_percent".to_string(),
+                reason: "is required when trading at a structure".to_string(),
+            })
+        }
+        (None, None) => profile.venue,
+        (Some(other), _) => {
+            return Err(TraderGraderError::InvalidParams(format!(
+                "Unknown venue '{other}'; expected npc_station or structure"
+            )))
+        }
+    };
+    Ok(())
+}
+
+/// Trade hubs named in `hubs`, or all five when none are given
+fn parse_hubs(arguments: &Value) -> Result<Vec<TradeHub>> {
+    match arguments.get("hubs").and_then(|v| v.as_array()) {
+        Some(names) => names
+            .iter()
+            .filter_map(|name| name.as_str())
+            .map(str::parse::<TradeHub>)
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(TraderGraderError::InvalidParams),
+        None => Ok(TradeHub::ALL.to_vec()),
+    }
+}
+
+fn parse_type_ids(arguments: &Value) -> Result<Vec<i32>> {
+    match arguments.get("type_ids").and_then(|v| v.as_array()) {
+        Some(ids) => ids.iter().map(parse_type_id).collect(),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Appends a note to a tool result that was built from stale cached data
+fn with_stale_notice(text: String, stale_reads: &[StaleRead]) -> String {
+    let Some(oldest) = stale_reads.iter().max_by_key(|read| read.age) else {
+        return text;
+    };
+    format!(
+        "{text}\n\n⚠️ Stale data: ESI is unavailable ({}), so {} cached result(s) were used; the oldest was fetched {} minutes ago.",
+        oldest.reason,
+        stale_reads.len(),
+        oldest.age.as_secs() / 60
+    )
+}
+
+/// Every tool with its JSON Schema, as listed by tools/list
+///
+/// Arguments are validated against these schemas before a tool runs, so the
+/// `minimum`/`maximum` bounds are enforced as well as advertised.
+fn tool_definitions() -> &'static Value {
+    static TOOLS: OnceLock<Value> = OnceLock::new();
+    TOOLS.get_or_init(|| {
+        let mut tools = json!([
+            {
+                "name": "health_check",
+                "description": "Check the TraderGrader MCP server and its dependencies: cache read-back, a live ESI /status/ request and the rate limiter's error budget, each rated pass, warn or fail",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {},
+                    "required": []
+                }
+            },
+            {
+                "name": "get_eve_status",
+                "description": "Get Tranquility's status: players online, server version, uptime, whether VIP mode is on and when the next daily downtime (11:00 UTC) starts; says so when EVE is down",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {},
+                    "required": []
+                }
+            },
+            {
+                "name": "get_diagnostics",
+                "description": "Report ESI connection diagnostics: user agent, DNS caching, IP family preference, connection pool settings and configuration warnings",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {},
+                    "required": []
+                }
+            },
+            {
+                "name": "get_market_orders",
+                "description": "List current market orders (price, remaining volume, location, range, issue date) in a region, optionally for one item type, filtered by side, remaining volume and price and sorted by price, volume or issue date. Reports how many orders match",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "region_id": {
+                            "type": "integer",
+                            "minimum": *REGION_ID_RANGE.start(),
+                            "maximum": *REGION_ID_RANGE.end(),
+                            "description": "EVE Online region ID (e.g., 10000002 for The Forge)"
+                        },
+                        "type_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Optional item type ID to filter orders"
+                        },
+                        "order_type": {
+                            "type": "string",
+                            "enum": ["buy", "sell", "all"],
+                            "description": "Which side of the market to list (default: all)"
+                        },
+                        "min_volume": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Only orders with at least this many units remaining"
+                        },
+                        "max_price": {
+                            "type": "number",
+                            "exclusiveMinimum": 0,
+                            "description": "Only orders priced at or below this many ISK per unit"
+                        },
+                        "sort_by": {
+                            "type": "string",
+                            "enum": ["price", "volume", "issued"],
+                            "description": "Order of the results: price (cheapest sells, then highest buys), volume (most units remaining first) or issued (newest first); default: price"
+                        },
+                        "limit": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Maximum number of orders to return (default 20)"
+                        },
+                        "offset": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "description": "Number of matching orders to skip, for paging (default 0)"
+                        },
+                        "format": {
+                            "type": "string",
+                            "enum": ["table", "json"],
+                            "description": "table (default) for a Markdown table, json for the orders with their counts as a JSON object"
+                        }
+                    },
+                    "required": ["region_id"]
+                }
+            },
+            {
+                "name": "get_market_summary",
+                "description": "Get a summary of market data including buy/sell orders and price spread for a specific item type in a region",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "region_id": {
+                            "type": "integer",
+                            "minimum": *REGION_ID_RANGE.start(),
+                            "maximum": *REGION_ID_RANGE.end(),
+                            "description": "EVE Online region ID (e.g., 10000002 for The Forge)"
+                        },
+                        "type_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Item type ID to analyze"
+                        },
+                        "station_id": {
+                            "type": "integer",
+                            "description": "Optional station to sell from: only buy orders whose range reaches it are counted (e.g., 60003760 for Jita 4-4)"
+                        }
+                    },
+                    "required": ["region_id", "type_id"]
+                }
+            },
+            {
+                "name": "search_items",
+                "description": "Find items by name and get their type IDs, ranked best match first with each item's market group. Tolerates typos and partial names (\"tritanum\" finds Tritanium) when a local SDE is loaded; otherwise only exact names are found",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "query": {
+                            "type": "string",
+                            "description": "Item name or part of one, at least three characters, e.g. \"tritanum\" or \"skill injector\""
+                        },
+                        "limit": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "maximum": MAX_SEARCH_LIMIT,
+                            "description": format!("Most matches to return (default: {DEFAULT_SEARCH_LIMIT})")
+                        }
+                    },
+                    "required": ["query"]
+                }
+            },
+            {
+                "name": "get_market_history",
+                "description": "Fetch historical market data (price, volume, order count) for a specific item in a region. Without options it shows the 10 most recent days; set days or from/to to pick a range and granularity to aggregate weekly or monthly. Includes ISK turnover, 7 and 30-day averages and the volume trend",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "region_id": {
+                            "type": "integer",
+                            "minimum": *REGION_ID_RANGE.start(),
+                            "maximum": *REGION_ID_RANGE.end(),
+                            "description": "EVE Online region ID (e.g., 10000002 for The Forge)"
+                        },
+                        "type_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Item type ID to get history for"
+                        },
+                        "granularity": {
+                            "type": "string",
+                            "enum": ["daily", "weekly", "monthly"],
+                            "description": "Aggregate the daily rows into OHLC candles of this length, newest first (default: daily when a range is given)"
+                        },
+                        "period": {
+                            "type": "string",
+                            "enum": ["daily", "weekly", "monthly"],
+                            "description": "Older name for granularity"
+                        },
+                        "days": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Only the last N days, ending at `to` or the newest day of data"
+                        },
+                        "from": {
+                            "type": "string",
+                            "format": "date",
+                            "description": "First day to include, YYYY-MM-DD"
+                        },
+                        "to": {
+                            "type": "string",
+                            "format": "date",
+                            "description": "Last day to include, YYYY-MM-DD"
+                        },
+                        "limit": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Maximum number of candles or days to report, newest first (default 12 candles, or all of them when days, from or to is set; 10 days without granularity)"
+                        },
+                        "offset": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "description": "Number of the newest candles or days to skip, for paging (default 0)"
+                        }
+                    },
+                    "required": ["region_id", "type_id"]
+                }
+            },
+            {
+                "name": "get_price_analysis",
+                "description": "Analyze price trends including daily/weekly/monthly changes, volatility, and trend direction",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "region_id": {
+                            "type": "integer",
+                            "minimum": *REGION_ID_RANGE.start(),
+                            "maximum": *REGION_ID_RANGE.end(),
+                            "description": "EVE Online region ID (e.g., 10000002 for The Forge)"
+                        },
+                        "type_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Item type ID to analyze trends for"
+                        }
+                    },
+                    "required": ["region_id", "type_id"]
+                }
+            },
+            {
+                "name": "get_technical_indicators",
+                "description": "Get technical indicators for an item: 7/30-day SMA and EMA, 14-day RSI, MACD and Bollinger bands from daily history",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "region_id": {
+                            "type": "integer",
+                            "minimum": *REGION_ID_RANGE.start(),
+                            "maximum": *REGION_ID_RANGE.end(),
+                            "description": "EVE Online region ID (e.g., 10000002 for The Forge)"
+                        },
+                        "type_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Item type ID to analyze"
+                        }
+                    },
+                    "required": ["region_id", "type_id"]
+                }
+            },
+            {
+                "name": "get_trend_agreement",
+                "description": "Check whether an item's 7-day, 30-day and 90-day price trends agree: fitted change and strength per timeframe, a -100 to +100 conviction score and a reading such as \"strong uptrend across all timeframes\" or \"short-term bounce in a downtrend\"",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "region_id": {
+                            "type": "integer",
+                            "minimum": *REGION_ID_RANGE.start(),
+                            "maximum": *REGION_ID_RANGE.end(),
+                            "description": "EVE Online region ID (e.g., 10000002 for The Forge)"
+                        },
+                        "type_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Item type ID to analyze"
+                        }
+                    },
+                    "required": ["region_id", "type_id"]
+                }
+            },
+            {
+                "name": "forecast_price",
+                "description": "Forecast an item's price 7 or 30 days ahead with linear regression and Holt-Winters smoothing (weekly seasonality), with 80% and 95% confidence bands. A statistical extrapolation of past prices, not a prediction",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "region_id": {
+                            "type": "integer",
+                            "minimum": *REGION_ID_RANGE.start(),
+                            "maximum": *REGION_ID_RANGE.end(),
+                            "description": "EVE Online region ID (e.g., 10000002 for The Forge)"
+                        },
+                        "type_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Item type ID to forecast"
+                        },
+                        "horizon_days": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "maximum": 30,
+                            "description": "Days ahead to forecast, usually 7 or 30 (default: 7)"
+                        }
+                    },
+                    "required": ["region_id", "type_id"]
+                }
+            },
+            {
+                "name": "detect_anomalies",
+                "description": "Flag days where an item's price or traded volume moved more than N standard deviations from the preceding days' baseline, to spot manipulation, speculation spikes or patch-driven shifts",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "region_id": {
+                            "type": "integer",
+                            "minimum": *REGION_ID_RANGE.start(),
+                            "maximum": *REGION_ID_RANGE.end(),
+                            "description": "EVE Online region ID (e.g., 10000002 for The Forge)"
+                        },
+                        "type_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Item type ID to check"
+                        },
+                        "std_devs": {
+                            "type": "number",
+                            "exclusiveMinimum": 0,
+                            "maximum": 10,
+                            "description": "Standard deviations from the baseline that count as an anomaly (default: 3)"
+                        },
+                        "window_days": {
+                            "type": "integer",
+                            "minimum": 7,
+                            "maximum": 180,
+                            "description": "Days in the rolling baseline before each checked day (default: 30)"
+                        }
+                    },
+                    "required": ["region_id", "type_id"]
+                }
+            },
+            {
+                "name": "detect_breakouts",
+                "description": "Check whether an item's price has broken out of its N-day high-low (Donchian) channel, a momentum signal for speculation on patch announcements: the current channel, whether the latest day broke above or below it, and past breakouts with their volume",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "region_id": {
+                            "type": "integer",
+                            "minimum": *REGION_ID_RANGE.start(),
+                            "maximum": *REGION_ID_RANGE.end(),
+                            "description": "EVE Online region ID (e.g., 10000002 for The Forge)"
+                        },
+                        "type_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Item type ID to check"
+                        },
+                        "window_days": {
+                            "type": "integer",
+                            "minimum": 5,
+                            "maximum": 180,
+                            "description": "Days of history in the channel each day is compared against (default: 20)"
+                        }
+                    },
+                    "required": ["region_id", "type_id"]
+                }
+            },
+            {
+                "name": "compare_year_over_year",
+                "description": "Compare an item's average price and daily volume over the last N days with the same days in each earlier year, e.g. to see whether PLEX is dearer than this time last year. Reaches back beyond ESI's 13 months when a history archive is configured and the server is built with the history-archive feature",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "region_id": {
+                            "type": "integer",
+                            "minimum": *REGION_ID_RANGE.start(),
+                            "maximum": *REGION_ID_RANGE.end(),
+                            "description": "EVE Online region ID (e.g., 10000002 for The Forge)"
+                        },
+                        "type_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Item type ID to compare"
+                        },
+                        "window_days": {
+                            "type": "integer",
+                            "minimum": MIN_YOY_WINDOW,
+                            "maximum": MAX_YOY_WINDOW,
+                            "description": format!("Days compared in each year (default: {DEFAULT_YOY_WINDOW})")
+                        }
+                    },
+                    "required": ["region_id", "type_id"]
+                }
+            },
+            {
+                "name": "backtest_strategy",
+                "description": "Replay an item's daily history against simple trading rules (buy when the price is a given percentage under its N-day moving average, sell at a profit target, stop loss or holding limit) and report hypothetical profit after fees, max drawdown, win rate and trade count against buying and holding. Trades fill at daily averages, so results are a best case",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "region_id": {
+                            "type": "integer",
+                            "minimum": *REGION_ID_RANGE.start(),
+                            "maximum": *REGION_ID_RANGE.end(),
+                            "description": "EVE Online region ID (e.g., 10000002 for The Forge)"
+                        },
+                        "type_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Item type ID to backtest"
+                        },
+                        "sma_days": {
+                            "type": "integer",
+                            "minimum": 2,
+                            "maximum": 180,
+                            "description": "Days in the moving average entries are measured against (default: 30)"
+                        },
+                        "entry_discount_percent": {
+                            "type": "number",
+                            "minimum": 0,
+                            "maximum": 90,
+                            "description": "Buy when the price is this far under the moving average, in percent (default: 5)"
+                        },
+                        "take_profit_percent": {
+                            "type": "number",
+                            "exclusiveMinimum": 0,
+                            "description": "Sell once the price is this far above the entry, in percent (default: 10)"
+                        },
+                        "stop_loss_percent": {
+                            "type": "number",
+                            "exclusiveMinimum": 0,
+                            "exclusiveMaximum": 100,
+                            "description": "Sell once the price is this far below the entry, in percent (default: no stop loss)"
+                        },
+                        "max_holding_days": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Sell after this many days whatever the price (default: no limit)"
+                        },
+                        "position_isk": {
+                            "type": "number",
+                            "exclusiveMinimum": 0,
+                            "description": "ISK committed to each trade (default: 100,000,000)"
+                        },
+                        "accounting_level": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "maximum": 5,
+                            "description": "Accounting skill level 0-5, reducing sales tax (default: from the trading profile)"
+                        },
+                        "broker_relations_level": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "maximum": 5,
+                            "description": "Broker Relations skill level 0-5, reducing broker fees (default: from the trading profile)"
+                        }
+                    },
+                    "required": ["region_id", "type_id"]
+                }
+            },
+            {
+                "name": "screen_speculation",
+                "description": "Rank a basket of items by how their price and daily volume reacted to a game event, comparing the days before it with the days from it and scoring each move against the item's usual noise, to find what speculators bought into last time",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "region_id": {
+                            "type": "integer",
+                            "minimum": *REGION_ID_RANGE.start(),
+                            "maximum": *REGION_ID_RANGE.end(),
+                            "description": "EVE Online region ID (e.g., 10000002 for The Forge)"
+                        },
+                        "event": {
+                            "type": "string",
+                            "description": format!("Event date (YYYY-MM-DD) or a bundled expansion: {}", events::known_names())
+                        },
+                        "type_ids": {
+                            "type": "array",
+                            "items": {"type": "integer", "minimum": 1},
+                            "description": "Item type IDs to screen, up to 200"
+                        },
+                        "market_group_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Screen every item in this market group instead of a list"
+                        },
+                        "window_days": {
+                            "type": "integer",
+                            "minimum": MIN_SPECULATION_WINDOW,
+                            "maximum": MAX_SPECULATION_WINDOW,
+                            "description": format!("Days compared on each side of the event (default: {DEFAULT_SPECULATION_WINDOW})")
+                        },
+                        "limit": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "maximum": 50,
+                            "description": "Items to list (default: 10)"
+                        }
+                    },
+                    "required": ["region_id", "event"]
+                }
+            },
+            {
+                "name": "compare_items",
+                "description": "Compare two or more items in a region: aligns their daily history by date and reports the Pearson correlation of their daily returns and how each performed against the others (e.g., how PLEX tracks Skill Injectors)",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "region_id": {
+                            "type": "integer",
+                            "minimum": *REGION_ID_RANGE.start(),
+                            "maximum": *REGION_ID_RANGE.end(),
+                            "description": "EVE Online region ID (e.g., 10000002 for The Forge)"
+                        },
+                        "type_ids": {
+                            "type": "array",
+                            "items": {"type": "integer", "minimum": 1},
+                            "description": "Two to ten item type IDs to compare (e.g., [44992, 40520])"
+                        },
+                        "days": {
+                            "type": "integer",
+                            "minimum": 10,
+                            "maximum": 400,
+                            "description": "Most recent shared trading days to compare (default: 90)"
+                        }
+                    },
+                    "required": ["region_id", "type_ids"]
+                }
+            },
+            {
+                "name": "estimate_undercut_rate",
+                "description": "Estimate how often an item's best buy and sell prices get beaten by sampling its order book a few times, waiting for ESI's roughly five-minute order cache between samples, to judge whether a 0.01 ISK war is worth fighting. Each extra sample adds about five minutes",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "region_id": {
+                            "type": "integer",
+                            "minimum": *REGION_ID_RANGE.start(),
+                            "maximum": *REGION_ID_RANGE.end(),
+                            "description": "EVE Online region ID (e.g., 10000002 for The Forge)"
+                        },
+                        "type_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Item type ID to sample"
+                        },
+                        "station_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Only count orders at this station or structure (e.g., 60003760 for Jita 4-4); default: the whole region"
+                        },
+                        "samples": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "maximum": MAX_UNDERCUT_SAMPLES,
+                            "description": "Order book samples to take (default: 2); one sample only reports how many top orders were updated in the last hour"
+                        }
+                    },
+                    "required": ["region_id", "type_id"]
+                }
+            },
+            {
+                "name": "get_spread_history",
+                "description": "Rebuild an item's approximate weekly spread from the daily highest and lowest traded prices in market history, and report whether the current spread is durable, a one-week fluke, or trending wider or narrower",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "region_id": {
+                            "type": "integer",
+                            "minimum": *REGION_ID_RANGE.start(),
+                            "maximum": *REGION_ID_RANGE.end(),
+                            "description": "EVE Online region ID (e.g., 10000002 for The Forge)"
+                        },
+                        "type_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Item type ID (e.g., 34 for Tritanium)"
+                        },
+                        "weeks": {
+                            "type": "integer",
+                            "minimum": MIN_SPREAD_WEEKS,
+                            "maximum": 56,
+                            "description": "Weeks of spread history to report (default: 12)"
+                        }
+                    },
+                    "required": ["region_id", "type_id"]
+                }
+            },
+            {
+                "name": "get_top_movers",
+                "description": "Rank the biggest gainers and losers by percent change in average price over the last day, week or month, across a list of items or every item in a market group, from cached daily history",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "region_id": {
+                            "type": "integer",
+                            "minimum": *REGION_ID_RANGE.start(),
+                            "maximum": *REGION_ID_RANGE.end(),
+                            "description": "EVE Online region ID (e.g., 10000002 for The Forge)"
+                        },
+                        "type_ids": {
+                            "type": "array",
+                            "items": {"type": "integer", "minimum": 1},
+                            "description": "Item type IDs to rank, up to 200"
+                        },
+                        "market_group_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Rank every item in this market group instead of a list"
+                        },
+                        "period": {
+                            "type": "string",
+                            "enum": ["daily", "weekly", "monthly"],
+                            "description": "Change over the last day, 7 days or 30 days (default: daily)"
+                        },
+                        "min_volume": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "description": "Skip items that traded fewer units than this over the period (default: 0)"
+                        },
+                        "limit": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "maximum": 50,
+                            "description": "Gainers and losers to list (default: 10 each)"
+                        }
+                    },
+                    "required": ["region_id"]
+                }
+            },
+            {
+                "name": "region_activity_report",
+                "description": "Characterize a region's market from the recent history of a basket of staple items: total ISK traded per day, each item's share, the biggest price movers and items that stopped trading, to help pick secondary hubs to seed",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "region_id": {
+                            "type": "integer",
+                            "minimum": *REGION_ID_RANGE.start(),
+                            "maximum": *REGION_ID_RANGE.end(),
+                            "description": "EVE Online region ID (e.g., 10000043 for Domain)"
+                        },
+                        "type_ids": {
+                            "type": "array",
+                            "items": {"type": "integer", "minimum": 1},
+                            "description": "Items to sample, up to 50 (default: minerals, PLEX, skill injectors, fuel blocks, ice products and nanite paste)"
+                        },
+                        "days": {
+                            "type": "integer",
+                            "minimum": 7,
+                            "maximum": 400,
+                            "description": "Days of history to roll up (default: 30)"
+                        }
+                    },
+                    "required": ["region_id"]
+                }
+            },
+            {
+                "name": "get_liquidity_score",
+                "description": "Score how liquid an item is (0-100) from average daily volume, order count, ISK turnover, spread and trading consistency over the last 30 days",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "region_id": {
+                            "type": "integer",
+                            "minimum": *REGION_ID_RANGE.start(),
+                            "maximum": *REGION_ID_RANGE.end(),
+                            "description": "EVE Online region ID (e.g., 10000002 for The Forge)"
+                        },
+                        "type_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Item type ID to analyze"
+                        }
+                    },
+                    "required": ["region_id", "type_id"]
+                }
+            },
+            {
+                "name": "get_order_book_depth",
+                "description": "Analyze order book depth for an item: volume per price level, cumulative depth at ±1%/±5%/±10% of mid-price, order walls, and the volume/ISK between the current price and an optional target price",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "region_id": {
+                            "type": "integer",
+                            "minimum": *REGION_ID_RANGE.start(),
+                            "maximum": *REGION_ID_RANGE.end(),
+                            "description": "EVE Online region ID (e.g., 10000002 for The Forge)"
+                        },
+                        "type_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Item type ID to analyze"
+                        },
+                        "target_price": {
+                            "type": "number",
+                            "exclusiveMinimum": 0,
+                            "description": "Optional price to measure how much volume and ISK sits between it and the current best price"
+                        },
+                        "station_id": {
+                            "type": "integer",
+                            "description": "Optional station to sell from: only buy orders whose range reaches it are counted"
+                        }
+                    },
+                    "required": ["region_id", "type_id"]
+                }
+            },
+            {
+                "name": "courier_market_rates",
+                "description": "Aggregate outstanding public courier contracts per route in a region to show the going ISK per m³, reward, collateral norms and days to complete",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "region_id": {
+                            "type": "integer",
+                            "minimum": *REGION_ID_RANGE.start(),
+                            "maximum": *REGION_ID_RANGE.end(),
+                            "description": "Region whose public contracts to scan (e.g., 10000002 for The Forge)"
+                        },
+                        "start_location_id": {
+                            "type": "integer",
+                            "description": "Only include routes starting at this station or structure (e.g., 60003760 for Jita 4-4)"
+                        },
+                        "end_location_id": {
+                            "type": "integer",
+                            "description": "Only include routes ending at this station or structure"
+                        },
+                        "limit": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Maximum number of routes to report (default 10)"
+                        }
+                    },
+                    "required": ["region_id"]
+                }
+            },
+            {
+                "name": "scan_public_contracts",
+                "description": format!("Find public item exchange contracts in a region priced under their items' market value: the {VALUED_CONTRACTS} newest contracts passing the filters have their items priced at a trade hub's sell orders (and buy orders, for the profit of accepting and selling instantly), less any items the acceptor must supply, ranked by discount"),
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "region_id": {
+                            "type": "integer",
+                            "minimum": *REGION_ID_RANGE.start(),
+                            "maximum": *REGION_ID_RANGE.end(),
+                            "description": "Region whose public contracts to scan (e.g., 10000002 for The Forge)"
+                        },
+                        "hub": {
+                            "type": "string",
+                            "enum": ["jita", "amarr", "dodixie", "rens", "hek"],
+                            "description": "Hub whose prices the contracts' items are valued at (default: jita)"
+                        },
+                        "item_exchange_only": {
+                            "type": "boolean",
+                            "description": "Leave out auctions; an auction is valued at its buyout, or its starting bid without one (default: true)"
+                        },
+                        "max_price": {
+                            "type": "number",
+                            "exclusiveMinimum": 0,
+                            "description": "Only contracts asking at most this many ISK"
+                        },
+                        "limit": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "maximum": MAX_CONTRACT_LIMIT,
+                            "description": format!("Contracts to list (default: {DEFAULT_CONTRACT_LIMIT})")
+                        }
+                    },
+                    "required": ["region_id"]
+                }
+            },
+            {
+                "name": "list_market_groups",
+                "description": "Browse the in-game market group tree: top-level categories, the subgroups of a group, or groups whose name matches a search (e.g., \"Minerals\"). Use the group IDs with get_market_group_types or scan_market",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "parent_group_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "List the subgroups of this market group (omit for top-level categories)"
+                        },
+                        "search": {
+                            "type": "string",
+                            "description": "Case-insensitive group name search across the whole tree"
+                        },
+                        "limit": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Maximum number of groups to list (default 50)"
+                        },
+                        "offset": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "description": "Number of groups to skip, for paging (default 0)"
+                        }
+                    }
+                }
+            },
+            {
+                "name": "get_market_group_types",
+                "description": "List the items (names and type IDs) in a market group, e.g. all Minerals",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "market_group_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Market group ID (e.g., 1857 for Minerals)"
+                        },
+                        "limit": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Maximum number of items to list (default 100)"
+                        },
+                        "offset": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "description": "Number of items to skip, for paging (default 0)"
+                        }
+                    },
+                    "required": ["market_group_id"]
+                }
+            },
+            {
+                "name": "scan_market",
+                "description": "Scan many items in one region at once (a list of type IDs or a whole market group) and rank them by spread %, average daily volume, or profit potential (spread x daily volume). Filter by meta or tech level to scan e.g. only the T2 items of a market group",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "region_id": {
+                            "type": "integer",
+                            "minimum": *REGION_ID_RANGE.start(),
+                            "maximum": *REGION_ID_RANGE.end(),
+                            "description": "EVE Online region ID (e.g., 10000002 for The Forge)"
+                        },
+                        "type_ids": {
+                            "type": "array",
+                            "items": {"type": "integer", "minimum": 1},
+                            "description": "Item type IDs to scan (up to 200)"
+                        },
+                        "market_group_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Market group whose item types to scan, used when type_ids is not given"
+                        },
+                        "min_meta_level": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "description": "Only scan items of at least this meta level (items without one count as 0)"
+                        },
+                        "max_meta_level": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "description": "Only scan items of at most this meta level"
+                        },
+                        "tech_level": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "maximum": 3,
+                            "description": "Only scan items of this tech level (1, 2 or 3; items without one count as 1)"
+                        },
+                        "sort_by": {
+                            "type": "string",
+                            "enum": ["spread_percent", "volume", "profit_potential"],
+                            "description": "Ranking order (default: profit_potential)"
+                        },
+                        "limit": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Number of rows to show (default: 20)"
+                        }
+                    },
+                    "required": ["region_id"]
+                }
+            },
+            {
+                "name": "compare_trade_hubs",
+                "description": "Compare one item across the major trade hubs (Jita, Amarr, Dodixie, Rens, Hek): best buy and sell at each hub station, spread, units listed and regional daily volume, plus the cheapest hub to buy from and the best hub to sell to",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "type_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Item type ID to compare (e.g., 34 for Tritanium)"
+                        },
+                        "hubs": {
+                            "type": "array",
+                            "items": {"type": "string", "enum": ["jita", "amarr", "dodixie", "rens", "hek"]},
+                            "description": "Hubs to include (default: all five)"
+                        }
+                    },
+                    "required": ["type_id"]
+                }
+            },
+            {
+                "name": "get_market_snapshot",
+                "description": "One-call market overview: best buy and sell, spread and daily volume of a configured basket of items (minerals, PLEX, fuel blocks and other staples by default) at every trade hub, with each item's cheapest hub and best bid. Served from a snapshot refreshed in the background, so it is fast and costs no ESI requests when fresh",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "refresh": {
+                            "type": "boolean",
+                            "description": "Rebuild the snapshot now instead of serving the stored one (default: false)"
+                        }
+                    },
+                    "required": []
+                }
+            },
+            {
+                "name": "get_mineral_index",
+                "description": "A composite price index of minerals (or any basket of items, such as ores) in a region: 100 on the first day of the window and priced daily after, weighted by each item's share of the basket's value, with the daily change and each item's move since the base day",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "region_id": {
+                            "type": "integer",
+                            "minimum": *REGION_ID_RANGE.start(),
+                            "maximum": *REGION_ID_RANGE.end(),
+                            "description": "Region to price in (default: 10000002, The Forge)"
+                        },
+                        "days": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "maximum": MAX_INDEX_DAYS,
+                            "description": format!("Days the index covers (default: {DEFAULT_INDEX_DAYS})")
+                        },
+                        "weights": {
+                            "type": "object",
+                            "description": format!("Basket as type ID to weight, e.g. {{\"34\": 2, \"35\": 1}}, up to {MAX_INDEX_ITEMS} items (default: the eight minerals, Tritanium to Morphite, equally weighted)")
+                        }
+                    },
+                    "required": []
+                }
+            },
+            {
+                "name": "get_plex_dashboard",
+                "description": "PLEX and skill injector economy in one call: prices, averages and volume of PLEX, Large and Small Skill Injectors and Skill Extractors, the profit of extracting skill points into an injector and selling it, and what injected skill points cost at each skill point bracket",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "region_id": {
+                            "type": "integer",
+                            "minimum": *REGION_ID_RANGE.start(),
+                            "maximum": *REGION_ID_RANGE.end(),
+                            "description": "Region to price in (default: 10000002, The Forge)"
+                        },
+                        "days": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "maximum": MAX_DASHBOARD_DAYS,
+                            "description": format!("Days of trading averaged (default: {DEFAULT_DASHBOARD_DAYS})")
+                        },
+                        "accounting_level": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "maximum": 5,
+                            "description": "Accounting skill level 0-5, reducing sales tax on the injector (default: from the trading profile)"
+                        },
+                        "broker_relations_level": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "maximum": 5,
+                            "description": "Broker Relations skill level 0-5, reducing the broker fee of listing the injector (default: from the trading profile)"
+                        }
+                    },
+                    "required": []
+                }
+            },
+            {
+                "name": "price_matrix",
+                "description": "Build a cross-region price table: the best sell price (and optionally buy price) of each item in each region, with each row's cheapest and dearest region marked and the spread between them",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "type_ids": {
+                            "type": "array",
+                            "items": {"type": "integer", "minimum": 1},
+                            "description": "Item type IDs, one row each (e.g., [34, 35, 36])"
+                        },
+                        "region_ids": {
+                            "type": "array",
+                            "items": {"type": "integer", "minimum": *REGION_ID_RANGE.start(), "maximum": *REGION_ID_RANGE.end()},
+                            "description": "Region IDs, one column each (default: the five trade hub regions); at most 200 cells in total"
+                        },
+                        "include_buy": {
+                            "type": "boolean",
+                            "description": "Also report best buy prices (default: false)"
+                        }
+                    },
+                    "required": ["type_ids"]
+                }
+            },
+            {
+                "name": "get_multi_region_summary",
+                "description": "Compare one item's order books across several regions in a single call: best buy and sell, spread, order counts and listed volumes per region, with the cheapest sell and highest buy region marked",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "type_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "EVE Online item type ID (e.g., 34 for Tritanium)"
+                        },
+                        "region_ids": {
+                            "type": "array",
+                            "items": {"type": "integer", "minimum": *REGION_ID_RANGE.start(), "maximum": *REGION_ID_RANGE.end()},
+                            "description": "Region IDs to compare, fetched concurrently (default: the five trade hub regions); at most 30"
+                        }
+                    },
+                    "required": ["type_id"]
+                }
+            },
+            {
+                "name": "set_trading_profile",
+                "description": "Set the skills, standings and trading venue this session's fee-aware tools (grade_trade, manufacturing_profit, hauling_analysis, import_wallet_trades) price sales tax and broker fees for. Only the fields passed change; a call can still override them with its own skill levels",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "accounting_level": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "maximum": 5,
+                            "description": "Accounting skill level 0-5, reducing sales tax"
+                        },
+                        "broker_relations_level": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "maximum": 5,
+                            "description": "Broker Relations skill level 0-5, reducing NPC station broker fees"
+                        },
+                        "faction_standing": {
+                            "type": "number",
+                            "minimum": -10,
+                            "maximum": 10,
+                            "description": "Standing towards the station owner's faction, reducing NPC station broker fees"
+                        },
+                        "corporation_standing": {
+                            "type": "number",
+                            "minimum": -10,
+                            "maximum": 10,
+                            "description": "Standing towards the station owner's corporation, reducing NPC station broker fees"
+                        },
+                        "venue": {
+                            "type": "string",
+                            "enum": ["npc_station", "structure"],
+                            "description": "Where orders are placed; structures charge their owner's broker fee, which skills and standings don't reduce"
+                        },
+                        "structure_broker_fee_percent": {
+                            "type": "number",
+                            "minimum": 0,
+                            "exclusiveMaximum": 100,
+                            "description": "Broker fee the structure charges, in percent; implies venue structure"
+                        }
+                    }
+                }
+            },
+            {
+                "name": "get_trading_profile",
+                "description": "Show this session's trading profile and the sales tax and broker fee it pays under the current fee schedule",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {},
+                    "required": []
+                }
+            },
+            {
+                "name": "grade_trade",
+                "description": "Grade a proposed station trade (buy order, then resell with a sell order in the same region) from A to F, with a breakdown of margin after fees, liquidity, competition, volatility, time to fill and risk scores and their weights",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "region_id": {
+                            "type": "integer",
+                            "minimum": *REGION_ID_RANGE.start(),
+                            "maximum": *REGION_ID_RANGE.end(),
+                            "description": "Region to trade in (e.g., 10000002 for The Forge)"
+                        },
+                        "type_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Item type ID to trade (e.g., 34 for Tritanium)"
+                        },
+                        "buy_price": {
+                            "type": "number",
+                            "exclusiveMinimum": 0,
+                            "description": "Price of the buy order (default: 0.01 ISK over the best buy order)"
+                        },
+                        "sell_price": {
+                            "type": "number",
+                            "exclusiveMinimum": 0,
+                            "description": "Price of the sell order (default: 0.01 ISK under the best sell order)"
+                        },
+                        "quantity": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Units to trade (default: 10% of average daily volume)"
+                        },
+                        "accounting_level": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "maximum": 5,
+                            "description": "Accounting skill level 0-5, reducing sales tax (default: from the trading profile)"
+                        },
+                        "broker_relations_level": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "maximum": 5,
+                            "description": "Broker Relations skill level 0-5, reducing broker fees (default: from the trading profile)"
+                        },
+                        "profile": {
+                            "type": "string",
+                            "enum": ["balanced", "conservative", "aggressive"],
+                            "description": "Preset component weights (default: balanced)"
+                        },
+                        "weights": {
+                            "type": "object",
+                            "description": "Override individual weights on top of the profile, e.g. {\"margin\": 0.5, \"risk\": 0.1}; components are margin, liquidity, competition, volatility, time_to_fill and risk"
+                        }
+                    },
+                    "required": ["region_id", "type_id"]
+                }
+            },
+            {
+                "name": "estimate_time_to_sell",
+                "description": "Estimate how long selling a stack of an item at a given price would take, from the last 30 days' volume traded at or above that price and the sell orders priced at or below it, which fill first",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "region_id": {
+                            "type": "integer",
+                            "minimum": *REGION_ID_RANGE.start(),
+                            "maximum": *REGION_ID_RANGE.end(),
+                            "description": "Region to sell in (e.g., 10000002 for The Forge)"
+                        },
+                        "type_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Item type ID to sell (e.g., 34 for Tritanium)"
+                        },
+                        "quantity": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Units to sell"
+                        },
+                        "price": {
+                            "type": "number",
+                            "exclusiveMinimum": 0,
+                            "description": "Price of the sell order (default: 0.01 ISK under the best sell order)"
+                        }
+                    },
+                    "required": ["region_id", "type_id", "quantity"]
+                }
+            },
+            {
+                "name": "estimate_buy_fill_time",
+                "description": "Estimate how long a buy order for a stack of an item at a given price would take to fill, from the last 30 days' volume traded at or below that price scaled by the buy/sell order ratio, and the buy orders priced at or above it, which fill first",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "region_id": {
+                            "type": "integer",
+                            "minimum": *REGION_ID_RANGE.start(),
+                            "maximum": *REGION_ID_RANGE.end(),
+                            "description": "Region to buy in (e.g., 10000002 for The Forge)"
+                        },
+                        "type_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Item type ID to buy (e.g., 34 for Tritanium)"
+                        },
+                        "quantity": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Units to buy"
+                        },
+                        "price": {
+                            "type": "number",
+                            "exclusiveMinimum": 0,
+                            "description": "Price of the buy order (default: 0.01 ISK over the best buy order)"
+                        }
+                    },
+                    "required": ["region_id", "type_id", "quantity"]
+                }
+            },
+            {
+                "name": "suggest_listing_price",
+                "description": "Suggest a price for a new sell or buy order from the shape of the order book (the four-significant-digit price step, lone orders not worth chasing, volume walls and crowding near the best price), with the listing's expected place in the queue",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "region_id": {
+                            "type": "integer",
+                            "minimum": *REGION_ID_RANGE.start(),
+                            "maximum": *REGION_ID_RANGE.end(),
+                            "description": "Region to list in (e.g., 10000002 for The Forge)"
+                        },
+                        "type_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Item type ID to list (e.g., 34 for Tritanium)"
+                        },
+                        "side": {
+                            "type": "string",
+                            "enum": ["sell", "buy"],
+                            "description": "Kind of order to list (default: sell)"
+                        },
+                        "station_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Only compete with orders at this station or structure (e.g., 60003760 for Jita 4-4); default: the whole region"
+                        }
+                    },
+                    "required": ["region_id", "type_id"]
+                }
+            },
+            {
+                "name": "export_market_history",
+                "description": "Export daily market history for an item in a region as CSV (date, average, highest, lowest, order_count, volume), oldest day first, for pasting into a spreadsheet",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "region_id": {
+                            "type": "integer",
+                            "minimum": *REGION_ID_RANGE.start(),
+                            "maximum": *REGION_ID_RANGE.end(),
+                            "description": "EVE Online region ID (e.g., 10000002 for The Forge)"
+                        },
+                        "type_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Item type ID to export history for"
+                        },
+                        "days": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Only export the most recent N days (default: all, about 13 months)"
+                        }
+                    },
+                    "required": ["region_id", "type_id"]
+                }
+            },
+            {
+                "name": "start_prefetch",
+                "description": "Keep orders and history for a list of items warm in the cache, refreshing them in the background just before they expire so later lookups are instant. Replaces any running prefetch; without targets or a watchlist, the configured [prefetch] targets are used",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "targets": {
+                            "type": "array",
+                            "description": "Region and item pairs to keep warm",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "region_id": {
+                                        "type": "integer",
+                                        "minimum": *REGION_ID_RANGE.start(),
+                                        "maximum": *REGION_ID_RANGE.end()
+                                    },
+                                    "type_id": {
+                                        "type": "integer",
+                                        "minimum": 1
+                                    }
+                                },
+                                "required": ["region_id", "type_id"]
+                            }
+                        },
+                        "watchlist_code": {
+                            "type": "string",
+                            "description": "Share code from export_watchlist; its items are added to the targets"
+                        },
+                        "region_id": {
+                            "type": "integer",
+                            "minimum": *REGION_ID_RANGE.start(),
+                            "maximum": *REGION_ID_RANGE.end(),
+                            "description": "Region for a watchlist that doesn't name one"
+                        },
+                        "include_history": {
+                            "type": "boolean",
+                            "description": "Refresh market history as well as orders (default: true)"
+                        },
+                        "lead_time_secs": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "description": "Refresh this many seconds before cached data expires (default: 10)"
+                        }
+                    }
+                }
+            },
+            {
+                "name": "stop_prefetch",
+                "description": "Stop the background prefetch started by start_prefetch and report how many refreshes it made",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {},
+                    "required": []
+                }
+            },
+            {
+                "name": "set_price_alert",
+                "description": "Set a price alert, e.g. PLEX sell below 3.2M in The Forge. A background task checks the best price and sends an alert-level notifications/message when the price crosses the threshold",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "region_id": {
+                            "type": "integer",
+                            "minimum": *REGION_ID_RANGE.start(),
+                            "maximum": *REGION_ID_RANGE.end(),
+                            "description": "EVE Online region ID (e.g., 10000002 for The Forge)"
+                        },
+                        "type_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Item type ID to watch"
+                        },
+                        "side": {
+                            "type": "string",
+                            "enum": ["sell", "buy"],
+                            "description": "Watch the lowest sell order or the highest buy order (default: sell)"
+                        },
+                        "condition": {
+                            "type": "string",
+                            "enum": ["below", "above"],
+                            "description": "Trigger when the price drops below or rises above the threshold"
+                        },
+                        "threshold": {
+                            "type": "number",
+                            "exclusiveMinimum": 0,
+                            "description": "Price in ISK"
+                        }
+                    },
+                    "required": ["region_id", "type_id", "condition", "threshold"]
+                }
+            },
+            {
+                "name": "list_alerts",
+                "description": "List the price alerts that are set, with the last price seen and when each last triggered",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {},
+                    "required": []
+                }
+            },
+            {
+                "name": "remove_alert",
+                "description": "Remove a price alert by the ID shown by set_price_alert or list_alerts",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "alert_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "ID of the alert to remove"
+                        }
+                    },
+                    "required": ["alert_id"]
+                }
+            },
+            {
+                "name": "watch_items",
+                "description": "Add items to the server's saved watchlist for a region. Each watched item is exposed as an MCP resource with its latest market summary, so clients can read or subscribe instead of calling tools",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "region_id": {
+                            "type": "integer",
+                            "minimum": *REGION_ID_RANGE.start(),
+                            "maximum": *REGION_ID_RANGE.end(),
+                            "description": "EVE Online region ID (e.g., 10000002 for The Forge)"
+                        },
+                        "type_ids": {
+                            "type": "array",
+                            "items": {"type": "integer", "minimum": 1},
+                            "description": "Item type IDs to watch"
+                        }
+                    },
+                    "required": ["region_id", "type_ids"]
+                }
+            },
+            {
+                "name": "unwatch_items",
+                "description": "Remove items from the server's saved watchlist for a region",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "region_id": {
+                            "type": "integer",
+                            "minimum": *REGION_ID_RANGE.start(),
+                            "maximum": *REGION_ID_RANGE.end(),
+                            "description": "EVE Online region ID"
+                        },
+                        "type_ids": {
+                            "type": "array",
+                            "items": {"type": "integer", "minimum": 1},
+                            "description": "Item type IDs to stop watching"
+                        }
+                    },
+                    "required": ["region_id", "type_ids"]
+                }
+            },
+            {
+                "name": "export_watchlist",
+                "description": "Package a list of items as a compact share code that corpmates can paste into chat and load with import_watchlist",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "name": {
+                            "type": "string",
+                            "description": "Watchlist name (e.g., \"Ice products\")"
+                        },
+                        "type_ids": {
+                            "type": "array",
+                            "items": {"type": "integer", "minimum": 1},
+                            "description": "Item type IDs to include (up to 500)"
+                        },
+                        "region_id": {
+                            "type": "integer",
+                            "minimum": *REGION_ID_RANGE.start(),
+                            "maximum": *REGION_ID_RANGE.end(),
+                            "description": "Region the list is meant for (optional)"
+                        }
+                    },
+                    "required": ["name", "type_ids"]
+                }
+            },
+            {
+                "name": "import_watchlist",
+                "description": "Validate a watchlist share code from export_watchlist and list its items by name, with the type IDs ready for other tools",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "code": {
+                            "type": "string",
+                            "description": "Share code starting with TGW1."
+                        }
+                    },
+                    "required": ["code"]
+                }
+            },
+            {
+                "name": "export_state",
+                "description": "Export the server's watchlist, price alerts, portfolio and trading profile as one versioned JSON document, to back them up or move them to another machine with import_state",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {},
+                    "required": []
+                }
+            },
+            {
+                "name": "import_state",
+                "description": "Load a document from export_state. The whole document is validated before anything changes; merge adds to the current state (positions are pooled at average cost) and replace clears the watchlist, alerts and portfolio first",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "state": {
+                            "type": ["string", "object"],
+                            "description": "The exported document, as JSON text or an object"
+                        },
+                        "mode": {
+                            "type": "string",
+                            "enum": ["merge", "replace"],
+                            "description": "How to combine with the current state (default: merge)"
+                        }
+                    },
+                    "required": ["state"]
+                }
+            },
+            {
+                "name": "portfolio_add",
+                "description": "Record bought units of an item in the server's saved portfolio. Repeat purchases are pooled at their average cost",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "type_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Item type ID bought"
+                        },
+                        "quantity": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Units bought"
+                        },
+                        "unit_cost": {
+                            "type": "number",
+                            "minimum": 0,
+                            "description": "ISK paid per unit, including any fees you want counted in the cost basis"
+                        }
+                    },
+                    "required": ["type_id", "quantity", "unit_cost"]
+                }
+            },
+            {
+                "name": "portfolio_remove",
+                "description": "Take sold or moved units out of the portfolio, keeping the average cost of what remains",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "type_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Item type ID to remove"
+                        },
+                        "quantity": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Units to remove (default: the whole position)"
+                        }
+                    },
+                    "required": ["type_id"]
+                }
+            },
+            {
+                "name": "portfolio_value",
+                "description": "Value every portfolio position against a region's current orders: market value and unrealized P&L at the best sell price, and liquidation value at the best buy price",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "region_id": {
+                            "type": "integer",
+                            "minimum": *REGION_ID_RANGE.start(),
+                            "maximum": *REGION_ID_RANGE.end(),
+                            "description": "Region to price positions in (default: 10000002, The Forge)"
+                        }
+                    },
+                    "required": []
+                }
+            },
+            {
+                "name": "record_trade",
+                "description": "Record an executed buy or sell in the server's saved trade journal, for realized P&L in trade_report",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "type_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Item type ID traded"
+                        },
+                        "side": {
+                            "type": "string",
+                            "enum": ["buy", "sell"],
+                            "description": "Whether the units were bought or sold"
+                        },
+                        "quantity": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Units traded"
+                        },
+                        "unit_price": {
+                            "type": "number",
+                            "exclusiveMinimum": 0,
+                            "description": "ISK per unit"
+                        },
+                        "fees": {
+                            "type": "number",
+                            "minimum": 0,
+                            "description": "Broker fees and sales tax paid on the trade, in ISK (default: 0)"
+                        },
+                        "date": {
+                            "type": "string",
+                            "description": "When the trade executed, as RFC 3339 (e.g., 2025-06-01T12:00:00Z; default: now)"
+                        }
+                    },
+                    "required": ["type_id", "side", "quantity", "unit_price"]
+                }
+            },
+            {
+                "name": "import_wallet_trades",
+                "description": "Import an authenticated character's wallet transactions from the last 30 days into the trade journal, skipping ones already imported. Sales tax on sells is estimated; broker fees aren't available",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "character_id": {
+                            "type": "integer",
+                            "description": "Character ID to import; optional when exactly one character is authenticated"
+                        },
+                        "accounting_level": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "maximum": 5,
+                            "description": "Accounting skill level 0-5, for estimating sales tax (default: from the trading profile)"
+                        }
+                    },
+                    "required": []
+                }
+            },
+            {
+                "name": "trade_report",
+                "description": "Report realized profit after fees, fees paid, ISK/hour and win rate per item from the trade journal, costing sold units at the average price paid",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "type_ids": {
+                            "type": "array",
+                            "items": {"type": "integer", "minimum": 1},
+                            "description": "Only report these items (default: every item in the journal)"
+                        }
+                    },
+                    "required": []
+                }
+            },
+            {
+                "name": "manufacturing_profit",
+                "description": "Price a manufacturing job at a trade hub: blueprint materials from the local SDE after material efficiency, priced at the hub station, plus the job fee from the system's manufacturing cost index, facility tax and SCC surcharge, compared with the product's hub price to give the build-vs-buy margin. Requires TRADERGRADER_SDE_PATH",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "type_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Item to build, or its blueprint (e.g., 587 for Rifter)"
+                        },
+                        "runs": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "maximum": MAX_JOB_RUNS,
+                            "description": "Number of job runs (default: 1)"
+                        },
+                        "material_efficiency": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "maximum": MAX_MATERIAL_EFFICIENCY,
+                            "description": "Blueprint material efficiency (default: 0)"
+                        },
+                        "time_efficiency": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "maximum": MAX_TIME_EFFICIENCY,
+                            "description": "Blueprint time efficiency (default: 0)"
+                        },
+                        "hub": {
+                            "type": "string",
+                            "enum": ["jita", "amarr", "dodixie", "rens", "hek"],
+                            "description": "Hub materials are bought and the product is sold at (default: jita)"
+                        },
+                        "system_id": {
+                            "type": "integer",
+                            "minimum": *SYSTEM_ID_RANGE.start(),
+                            "maximum": *SYSTEM_ID_RANGE.end(),
+                            "description": "Solar system the job is installed in, for its cost index (default: the hub's system)"
+                        },
+                        "facility_tax_percent": {
+                            "type": "number",
+                            "minimum": 0,
+                            "maximum": 50,
+                            "description": "Facility tax in percent (default: 0.25, NPC stations)"
+                        },
+                        "input_price": {
+                            "type": "string",
+                            "enum": ["buy", "sell"],
+                            "description": "Price materials at the highest buy order or the lowest sell order (default: sell)"
+                        },
+                        "product_price": {
+                            "type": "string",
+                            "enum": ["buy", "sell"],
+                            "description": "Value the product at the highest buy order or the lowest sell order (default: sell)"
+                        }
+                    },
+                    "required": ["type_id"]
+                }
+            },
+            {
+                "name": "fuel_block_analysis",
+                "description": "Price fuel block production at each trade hub: one blueprint run's ice products and planetary materials bought from the hub's sell orders against the 40 blocks listed there, after sales tax and broker fee, for all four racial blocks, most profitable first. Also shows how much more compressed ice sells for than raw ice at each hub. Recipes come from the local SDE when loaded",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "hubs": {
+                            "type": "array",
+                            "items": {"type": "string", "enum": ["jita", "amarr", "dodixie", "rens", "hek"]},
+                            "description": "Hubs to price at (default: all five)"
+                        },
+                        "material_efficiency": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "maximum": MAX_MATERIAL_EFFICIENCY,
+                            "description": format!("Blueprint material efficiency (default: {MAX_MATERIAL_EFFICIENCY}, as fuel block blueprints are cheap to research)")
+                        },
+                        "accounting_level": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "maximum": 5,
+                            "description": "Accounting skill level 0-5, reducing sales tax (default: from the trading profile)"
+                        },
+                        "broker_relations_level": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "maximum": 5,
+                            "description": "Broker Relations skill level 0-5, reducing broker fees (default: from the trading profile)"
+                        }
+                    },
+                    "required": []
+                }
+            },
+            {
+                "name": "lp_store_value",
+                "description": "Rank an NPC corporation's loyalty point store by ISK per LP at a trade hub: each offer's required items bought from the hub's sell orders and its output sold there, after sales tax and broker fee, less the offer's ISK cost, divided by its LP cost",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "corporation_id": {
+                            "type": "integer",
+                            "minimum": *NPC_CORPORATION_ID_RANGE.start(),
+                            "maximum": *NPC_CORPORATION_ID_RANGE.end(),
+                            "description": "NPC corporation whose store to value (e.g., 1000120 for Federation Navy)"
+                        },
+                        "hub": {
+                            "type": "string",
+                            "enum": ["jita", "amarr", "dodixie", "rens", "hek"],
+                            "description": "Hub items are bought and output sold at (default: jita)"
+                        },
+                        "output_price": {
+                            "type": "string",
+                            "enum": ["buy", "sell"],
+                            "description": "Sell the output instantly to the highest buy order or list it at the lowest sell order (default: buy)"
+                        },
+                        "limit": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "maximum": MAX_OFFER_LIMIT,
+                            "description": format!("Offers to list (default: {DEFAULT_OFFER_LIMIT})")
+                        },
+                        "accounting_level": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "maximum": 5,
+                            "description": "Accounting skill level 0-5, reducing sales tax (default: from the trading profile)"
+                        },
+                        "broker_relations_level": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "maximum": 5,
+                            "description": "Broker Relations skill level 0-5, reducing broker fees (default: from the trading profile)"
+                        }
+                    },
+                    "required": ["corporation_id"]
+                }
+            },
+            {
+                "name": "insurance_analysis",
+                "description": "Compare a ship's insurance levels from ESI with its hull's lowest sell order at a trade hub and, with a local SDE, the cost of its blueprint materials there, flagging hulls whose Platinum payout after the premium exceeds what they cost to buy or build",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "type_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Ship to analyze (e.g., 587 for Rifter)"
+                        },
+                        "hub": {
+                            "type": "string",
+                            "enum": ["jita", "amarr", "dodixie", "rens", "hek"],
+                            "description": "Hub the hull and its materials are priced at (default: jita)"
+                        },
+                        "material_efficiency": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "maximum": MAX_MATERIAL_EFFICIENCY,
+                            "description": "Blueprint material efficiency for the build cost (default: 0)"
+                        }
+                    },
+                    "required": ["type_id"]
+                }
+            },
+            {
+                "name": "get_region_activity",
+                "description": "Compare player activity across regions using last-hour jumps, ship/pod kills and NPC kills, rolled into a demand index so stocking decisions can favor regions with real activity",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "region_ids": {
+                            "type": "array",
+                            "items": {"type": "integer", "minimum": *REGION_ID_RANGE.start(), "maximum": *REGION_ID_RANGE.end()},
+                            "description": "Region IDs to compare (e.g., [10000002, 10000043, 10000032])"
+                        }
+                    },
+                    "required": ["region_ids"]
+                }
+            },
+            {
+                "name": "get_region_context",
+                "description": "Whether a region's market is reachable: its high-, low- and null-sec system counts, who holds its systems (empire factions, NPC pirates or player alliances, from the sovereignty map) and its NPC stations, summed up as the safest way to reach its market. Check this before trusting prices in an unfamiliar region",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "region_id": {
+                            "type": "integer",
+                            "minimum": *REGION_ID_RANGE.start(),
+                            "maximum": *REGION_ID_RANGE.end(),
+                            "description": "Region to describe (e.g., 10000060 for Delve)"
+                        }
+                    },
+                    "required": ["region_id"]
+                }
+            },
+            {
+                "name": "plan_route",
+                "description": "Plan the gate route between two systems through ESI: jumps, every system crossed with its security status, and the low- and null-sec stretches along the way",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "origin_system_id": {
+                            "type": "integer",
+                            "minimum": *SYSTEM_ID_RANGE.start(),
+                            "maximum": *SYSTEM_ID_RANGE.end(),
+                            "description": "Solar system to start from (e.g., 30000142 for Jita)"
+                        },
+                        "destination_system_id": {
+                            "type": "integer",
+                            "minimum": *SYSTEM_ID_RANGE.start(),
+                            "maximum": *SYSTEM_ID_RANGE.end(),
+                            "description": "Solar system to travel to (e.g., 30002187 for Amarr)"
+                        },
+                        "route_flag": {
+                            "type": "string",
+                            "enum": ["shortest", "secure", "insecure"],
+                            "description": "Fewest jumps, stay in high-sec where possible, or prefer low- and null-sec (default: shortest)"
+                        }
+                    },
+                    "required": ["origin_system_id", "destination_system_id"]
+                }
+            },
+            {
+                "name": "get_route_risk",
+                "description": "Rate the danger of a gate route from the last hour of ship and pod kills and jumps in each system on it, flagging likely gate camps (pod kills that are a large share of traffic), with a 0-100 risk score",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "origin_system_id": {
+                            "type": "integer",
+                            "minimum": *SYSTEM_ID_RANGE.start(),
+                            "maximum": *SYSTEM_ID_RANGE.end(),
+                            "description": "Solar system to start from (e.g., 30000142 for Jita)"
+                        },
+                        "destination_system_id": {
+                            "type": "integer",
+                            "minimum": *SYSTEM_ID_RANGE.start(),
+                            "maximum": *SYSTEM_ID_RANGE.end(),
+                            "description": "Solar system to travel to (e.g., 30002187 for Amarr)"
+                        },
+                        "route_flag": {
+                            "type": "string",
+                            "enum": ["shortest", "secure", "insecure"],
+                            "description": "Route to rate (default: shortest)"
+                        }
+                    },
+                    "required": ["origin_system_id", "destination_system_id"]
+                }
+            },
+            {
+                "name": "hauling_analysis",
+                "description": "Find items worth gate hauling between two systems' markets (e.g., Jita to Amarr): route length from ESI, units that can be bought below the destination's buy orders and fit in the cargo hold, and profit per jump and per m³",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "origin_system_id": {
+                            "type": "integer",
+                            "minimum": *SYSTEM_ID_RANGE.start(),
+                            "maximum": *SYSTEM_ID_RANGE.end(),
+                            "description": "Solar system to buy in (e.g., 30000142 for Jita)"
+                        },
+                        "destination_system_id": {
+                            "type": "integer",
+                            "minimum": *SYSTEM_ID_RANGE.start(),
+                            "maximum": *SYSTEM_ID_RANGE.end(),
+                            "description": "Solar system to sell in (e.g., 30002187 for Amarr)"
+                        },
+                        "type_ids": {
+                            "type": "array",
+                            "items": {"type": "integer", "minimum": 1},
+                            "description": "Item type IDs to evaluate"
+                        },
+                        "market_group_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Market group whose item types to evaluate, used when type_ids is not given"
+                        },
+                        "cargo_capacity_m3": {
+                            "type": "number",
+                            "exclusiveMinimum": 0,
+                            "description": "Cargo hold size in m³ (default: 1,000,000)"
+                        },
+                        "route_flag": {
+                            "type": "string",
+                            "enum": ["shortest", "secure", "insecure"],
+                            "description": "Route preference (default: shortest)"
+                        },
+                        "accounting_level": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "maximum": 5,
+                            "description": "Accounting skill level 0-5, reducing sales tax (default: from the trading profile)"
+                        },
+                        "limit": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Number of items to show (default: 20)"
+                        }
+                    },
+                    "required": ["origin_system_id", "destination_system_id"]
+                }
+            },
+            {
+                "name": "price_courier_contract",
+                "description": "Suggest a reward for a courier contract from the gate route's length and the security of the systems it crosses, the cargo volume and the collateral, at per-jump and per-m³ rates set in the [courier] config section or given here",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "origin_system_id": {
+                            "type": "integer",
+                            "minimum": *SYSTEM_ID_RANGE.start(),
+                            "maximum": *SYSTEM_ID_RANGE.end(),
+                            "description": "Solar system the cargo is picked up in (e.g., 30000142 for Jita)"
+                        },
+                        "destination_system_id": {
+                            "type": "integer",
+                            "minimum": *SYSTEM_ID_RANGE.start(),
+                            "maximum": *SYSTEM_ID_RANGE.end(),
+                            "description": "Solar system the cargo is delivered to (e.g., 30002187 for Amarr)"
+                        },
+                        "volume_m3": {
+                            "type": "number",
+                            "minimum": 0,
+                            "description": "Cargo volume in m³"
+                        },
+                        "collateral": {
+                            "type": "number",
+                            "minimum": 0,
+                            "description": "Collateral in ISK the hauler puts up (default: 0)"
+                        },
+                        "route_flag": {
+                            "type": "string",
+                            "enum": ["shortest", "secure", "insecure"],
+                            "description": "Route the hauler flies (default: secure)"
+                        },
+                        "isk_per_jump": {
+                            "type": "number",
+                            "minimum": 0,
+                            "description": "ISK per high-sec jump (default: from config, 250,000)"
+                        },
+                        "isk_per_m3_per_jump": {
+                            "type": "number",
+                            "minimum": 0,
+                            "description": "ISK per m³ per high-sec jump (default: from config, 1)"
+                        },
+                        "collateral_percent": {
+                            "type": "number",
+                            "minimum": 0,
+                            "description": "Percent of the collateral added to the reward (default: from config, 1)"
+                        },
+                        "low_sec_multiplier": {
+                            "type": "number",
+                            "minimum": 1,
+                            "description": "High-sec jumps one low-sec jump counts as (default: from config, 2)"
+                        },
+                        "null_sec_multiplier": {
+                            "type": "number",
+                            "minimum": 1,
+                            "description": "High-sec jumps one null-sec jump counts as (default: from config, 3)"
+                        },
+                        "minimum_reward": {
+                            "type": "number",
+                            "minimum": 0,
+                            "description": "Smallest reward to suggest (default: from config, 1,000,000)"
+                        }
+                    },
+                    "required": ["origin_system_id", "destination_system_id", "volume_m3"]
+                }
+            },
+            {
+                "name": "jf_route_profit",
+                "description": "Calculate jump freighter hauling profit after isotope fuel: light-year legs between waypoint systems, fuel per jump with skills, fuel priced in Jita, and cargo bought in one region and sold in another",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "waypoints": {
+                            "type": "array",
+                            "items": {"type": "integer", "minimum": *SYSTEM_ID_RANGE.start(), "maximum": *SYSTEM_ID_RANGE.end()},
+                            "description": "Solar system IDs: origin, any cyno midpoints, destination"
+                        },
+                        "ship": {
+                            "type": "string",
+                            "enum": ["ark", "anshar", "nomad", "rhea"],
+                            "description": "Jump freighter hull, which determines the isotope burned"
+                        },
+                        "source_region_id": {
+                            "type": "integer",
+                            "minimum": *REGION_ID_RANGE.start(),
+                            "maximum": *REGION_ID_RANGE.end(),
+                            "description": "Region to buy the cargo in (e.g., 10000002 for The Forge)"
+                        },
+                        "destination_region_id": {
+                            "type": "integer",
+                            "minimum": *REGION_ID_RANGE.start(),
+                            "maximum": *REGION_ID_RANGE.end(),
+                            "description": "Region to sell the cargo in"
+                        },
+                        "type_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Item type ID of the cargo"
+                        },
+                        "quantity": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Units of cargo"
+                        },
+                        "skill_level": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "maximum": 5,
+                            "description": "Jump Fuel Conservation and Jump Freighters skill level (0-5, default 4)"
+                        }
+                    },
+                    "required": ["waypoints", "ship", "source_region_id", "destination_region_id", "type_id", "quantity"]
+                }
+            },
+            {
+                "name": "esi_get",
+                "description": "Advanced: fetch any allowlisted public ESI GET route as raw JSON (e.g. /markets/prices/, /universe/systems/30000142/, /industry/systems/), through the server's cache and rate limiter. Use when no dedicated tool covers the data",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "path": {
+                            "type": "string",
+                            "description": "Route path after the ESI version, e.g. /markets/10000002/orders/"
+                        },
+                        "params": {
+                            "type": "object",
+                            "description": "Query parameters, e.g. {\"type_id\": 34, \"page\": 2}"
+                        }
+                    },
+                    "required": ["path"]
+                }
+            },
+            {
+                "name": "get_structure_market_summary",
+                "description": "Get a market summary for an item in a player-owned structure market (e.g. Tranquility Trading Tower). Requires an authenticated character with structure market access",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "structure_id": {
+                            "type": "integer",
+                            "description": "Structure ID (e.g., 1028858195912 for Tranquility Trading Tower)"
+                        },
+                        "type_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Item type ID to analyze"
+                        }
+                    },
+                    "required": ["structure_id", "type_id"]
+                }
+            },
+            {
+                "name": "authenticate_character",
+                "description": "Log in an EVE character with EVE SSO. Call without arguments to get a login URL, then call again with the redirect URL (or code and state) to complete the login",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "redirect_url": {
+                            "type": "string",
+                            "description": "Full callback URL the browser was redirected to after logging in"
+                        },
+                        "code": {
+                            "type": "string",
+                            "description": "Authorization code from the callback URL"
+                        },
+                        "state": {
+                            "type": "string",
+                            "description": "State value from the callback URL"
+                        }
+                    },
+                    "required": []
+                }
+            },
+            {
+                "name": "get_my_orders",
+                "description": "List an authenticated character's open market orders and report which have been undercut or outbid at their station, and by how much",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "character_id": {
+                            "type": "integer",
+                            "description": "Character ID to check; optional when exactly one character is authenticated"
+                        }
+                    },
+                    "required": []
+                }
+            }
+        ]);
+        for tool in tools.as_array_mut().into_iter().flatten() {
+            advertise_location_aliases(&mut tool["inputSchema"]);
+            add_max_chars(&mut tool["inputSchema"]);
+        }
+        tools
+    })
+}
+
+/// Lets a schema's region, system and station arguments take an alias as well as an ID
+fn advertise_location_aliases(schema: &mut Value) {
+    let Some(properties) = schema["properties"].as_object_mut() else {
+        return;
+    };
+    for (field, property) in properties.iter_mut() {
+        let Some(kind) = LocationKind::of_field(field) else {
+            continue;
+        };
+        let example = match kind {
+            LocationKind::Region => "\"the-forge\" or \"jita\"",
+            LocationKind::System | LocationKind::Station => "\"jita\"",
+        };
+        let target = if property["type"] == "array" { &mut property["items"] } else { &mut *property };
+        target["type"] = json!(["integer", "string"]);
+        if let Some(description) = property["description"].as_str() {
+            property["description"] = json!(format!("{description}; a location alias such as {example} also works"));
+        }
+    }
+}
+
+/// Lets every tool take a `max_chars` cap on its output
+fn add_max_chars(schema: &mut Value) {
+    if let Some(properties) = schema["properties"].as_object_mut() {
+        properties.insert(
+            "max_chars".to_string(),
+            json!({
+                "type": "integer",
+                "minimum": MIN_MAX_CHARS,
+                "description": format!(
+                    "Cut the output to this many characters at a line boundary, with a truncation note (default {DEFAULT_MAX_CHARS})"
+                )
+            }),
+        );
+    }
+}
+
+/// Extract the `code` and `state` query parameters from an SSO callback URL
+fn parse_callback_url(redirect_url: &str) -> (Option<String>, Option<String>) {
+    match reqwest::Url::parse(redirect_url) {
+        Ok(url) => {
+            let mut code = None;
+            let mut state = None;
+            for (key, value) in url.query_pairs() {
+                match key.as_ref() {
+                    "code" => code = Some(value.into_owned()),
+                    "state" => state = Some(value.into_owned()),
+                    _ => {}
+                }
+            }
+            (code, state)
+        }
+        Err(_) => (None, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::SNAPSHOT_URI;
+    use crate::test_support::{history_day, market_order};
+    use crate::types::{MarketHistory, MarketOrder};
+    use serde_json::json;
+
+    #[test]
+    fn test_mcp_handler_creation() {
+        let handler = McpHandler::new("TestServer".to_string(), "1.0.0".to_string());
+        assert_eq!(handler.server_name, "TestServer");
+        assert_eq!(handler.server_version, "1.0.0");
+    }
+
+    #[test]
+    fn test_initialize_message() {
+        let handler = McpHandler::new("TestServer".to_string(), "1.0.0".to_string());
+        let message = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": {}
+        });
+        
+        let response = handler.handle_initialize(&message);
+        
+        assert_eq!(response["jsonrpc"], "2.0");
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["result"]["protocolVersion"], "2025-03-26");
+        assert_eq!(response["result"]["serverInfo"]["name"], "TestServer");
+    }
+
+    #[test]
+    fn test_tools_list_message() {
+        let handler = McpHandler::new("TestServer".to_string(), "1.0.0".to_string());
+        let message = json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "tools/list"
+        });
+        
+        let response = handler.handle_tools_list(&message);
+        
+        assert_eq!(response["jsonrpc"], "2.0");
+        assert_eq!(response["id"], 2);
+        assert!(response["result"]["tools"].is_array());
+        
+        let tools = response["result"]["tools"].as_array().unwrap();
         assert!(tools.len() >= 5); // Should have at least 5 tools
         
         // Check for expected tool names
@@ -583,6 +4607,278 @@ mod tests {
         assert_eq!(response, json!(null));
     }
 
+    #[test]
+    fn test_parse_callback_url() {
+        let (code, state) = parse_callback_url("http://localhost:8080/callback?code=abc123&state=xyz");
+        assert_eq!(code.as_deref(), Some("abc123"));
+        assert_eq!(state.as_deref(), Some("xyz"));
+
+        let (code, state) = parse_callback_url("not a url");
+        assert!(code.is_none() && state.is_none());
+    }
+
+    #[test]
+    fn test_authenticate_character_without_sso() {
+        let handler = McpHandler::with_market_client(
+            "TestServer".to_string(),
+            "1.0.0".to_string(),
+            MarketClient::without_cache(),
+        );
+        let message = json!({
+            "jsonrpc": "2.0",
+            "id": 5,
+            "method": "tools/call",
+            "params": {"name": "authenticate_character", "arguments": {}}
+        });
+
+        let response = tokio_test::block_on(handler.handle_message(message));
+        assert!(response.get("error").is_none());
+        assert_eq!(response["result"]["isError"], true);
+        let text = response["result"]["content"][0]["text"].as_str().unwrap();
+        assert!(text.contains("EVE SSO is not configured"));
+    }
+
+    #[test]
+    fn test_arguments_validated_against_schema() {
+        let handler = McpHandler::with_market_client(
+            "TestServer".to_string(),
+            "1.0.0".to_string(),
+            MarketClient::without_cache(),
+        );
+        let call = |arguments: Value| {
+            tokio_test::block_on(handler.handle_message(json!({
+                "jsonrpc": "2.0",
+                "id": 6,
+                "method": "tools/call",
+                "params": {"name": "get_market_summary", "arguments": arguments}
+            })))
+        };
+
+        let response = call(json!({"region_id": 42, "type_id": 34}));
+        assert_eq!(response["error"]["code"], -32602);
+        assert_eq!(
+            response["error"]["message"],
+            "Invalid arguments for get_market_summary: region_id must be at least 10000001, got 42"
+        );
+        assert_eq!(response["error"]["data"], json!({"kind": "invalid_argument", "field": "region_id"}));
+
+        let response = call(json!({"region_id": 10000002, "type_id": 0}));
+        assert_eq!(response["error"]["data"]["field"], "type_id");
+
+        let response = call(json!({"region_id": 10000002}));
+        assert_eq!(response["error"]["code"], -32602);
+        assert!(response["error"]["message"].as_str().unwrap().ends_with("type_id is required"));
+
+        let response = call(json!({"region_id": 10000002, "type_id": "tritanium"}));
+        assert!(response["error"]["message"].as_str().unwrap().ends_with("type_id must be an integer, got \"tritanium\""));
+    }
+
+    #[test]
+    fn test_max_chars_truncates_output() {
+        let handler = McpHandler::new("TestServer".to_string(), "1.0.0".to_string());
+        let call = |arguments: Value| {
+            tokio_test::block_on(handler.handle_message(json!({
+                "jsonrpc": "2.0",
+                "id": 6,
+                "method": "tools/call",
+                "params": {"name": "get_diagnostics", "arguments": arguments}
+            })))
+        };
+
+        let full = call(json!({}));
+        let full = full["result"]["content"][0]["text"].as_str().unwrap();
+        assert!(full.chars().count() > MIN_MAX_CHARS);
+        assert!(!full.contains("[Truncated"));
+
+        let response = call(json!({"max_chars": MIN_MAX_CHARS}));
+        let text = response["result"]["content"][0]["text"].as_str().unwrap();
+        assert!(text.contains(&format!("of {} characters", full.chars().count())));
+        assert!(full.starts_with(text.split("\n\n[Truncated").next().unwrap()));
+
+        let response = call(json!({"max_chars": 10}));
+        assert_eq!(response["error"]["data"]["field"], "max_chars");
+    }
+
+    #[test]
+    fn test_trading_profile_is_kept_for_the_session() {
+        let handler = McpHandler::new("TestServer".to_string(), "1.0.0".to_string());
+        let call = |name: &str, arguments: Value| {
+            tokio_test::block_on(handler.handle_message(json!({
+                "jsonrpc": "2.0",
+                "id": 7,
+                "method": "tools/call",
+                "params": {"name": name, "arguments": arguments}
+            })))
+        };
+
+        call("set_trading_profile", json!({"accounting_level": 5, "structure_broker_fee_percent": 0.5}));
+        let response = call("get_trading_profile", json!({}));
+        let text = response["result"]["content"][0]["text"].as_str().unwrap();
+        assert!(text.contains("Accounting: 5"));
+        assert!(text.contains("Venue: structure (0.50% broker fee)"));
+
+        // A call's own arguments apply on top without changing the stored profile
+        let overridden = handler.trading_profile(Some(&json!({"accounting_level": 2}))).unwrap();
+        assert_eq!(overridden.skills.accounting, 2);
+        assert_eq!(handler.trading_profile(None).unwrap().skills.accounting, 5);
+
+        let response = call("set_trading_profile", json!({"venue": "npc_station", "faction_standing": 11}));
+        assert_eq!(response["error"]["data"]["field"], "faction_standing");
+        let response = call("set_trading_profile", json!({"venue": "npc_station"}));
+        let text = response["result"]["content"][0]["text"].as_str().unwrap();
+        assert!(text.contains("Venue: NPC station"));
+    }
+
+    #[test]
+    fn test_location_aliases_resolve_before_validation() {
+        let handler = McpHandler::with_market_client(
+            "TestServer".to_string(),
+            "1.0.0".to_string(),
+            MarketClient::without_cache(),
+        );
+        let call = |arguments: Value| {
+            tokio_test::block_on(handler.handle_message(json!({
+                "jsonrpc": "2.0",
+                "id": 6,
+                "method": "tools/call",
+                "params": {"name": "get_market_summary", "arguments": arguments}
+            })))
+        };
+
+        // A hub names a station but a region alias doesn't
+        let response = call(json!({"region_id": "jita", "type_id": 34, "station_id": "the-forge"}));
+        assert_eq!(response["error"]["data"]["field"], "station_id");
+        assert!(response["error"]["message"].as_str().unwrap().contains("doesn't name a station"));
+
+        let response = call(json!({"region_id": "perimeter", "type_id": 34}));
+        assert_eq!(response["error"]["code"], -32602);
+        assert!(response["error"]["message"].as_str().unwrap().contains("not a known location"));
+
+        let summary = tool_definitions().as_array().unwrap().iter().find(|t| t["name"] == "get_market_summary").unwrap();
+        assert_eq!(summary["inputSchema"]["properties"]["region_id"]["type"], json!(["integer", "string"]));
+    }
+
+    /// A handler whose client has Tritanium's orders and history in The Forge cached, so no request reaches ESI
+    fn handler_with_cached_market(orders: Vec<MarketOrder>, history: Vec<MarketHistory>) -> McpHandler {
+        use crate::cache::{CacheBackendExt, CacheItem, CacheKey, InMemoryCacheBackend};
+        use crate::esi::{EsiClient, EsiConfig};
+        use crate::rate_limit::{EsiRateLimiter, RateLimitConfig};
+
+        let cache = Arc::new(InMemoryCacheBackend::new(100, None));
+        tokio_test::block_on(cache.set(
+            &CacheKey::market_orders(10000002, Some(34)),
+            CacheItem::new(orders, Duration::from_secs(3600)),
+        ))
+        .unwrap();
+        tokio_test::block_on(cache.set(
+            &CacheKey::market_history(10000002, 34),
+            CacheItem::new(history, Duration::from_secs(3600)),
+        ))
+        .unwrap();
+        let limiter = EsiRateLimiter::shared(RateLimitConfig::testing()).unwrap();
+        let client = MarketClient::from_esi(EsiClient::new(Some(cache), limiter, EsiConfig::default()).unwrap());
+        McpHandler::with_market_client("TestServer".to_string(), "1.0.0".to_string(), client)
+    }
+
+    /// Calls a tool and returns the text of a tool error, failing on a protocol error or success
+    fn tool_error_text(handler: &McpHandler, name: &str, arguments: Value) -> String {
+        let message = json!({
+            "jsonrpc": "2.0",
+            "id": 9,
+            "method": "tools/call",
+            "params": {"name": name, "arguments": arguments}
+        });
+        let response = tokio_test::block_on(handler.handle_message(message));
+        assert!(response.get("error").is_none(), "{name} returned a protocol error: {response}");
+        assert_eq!(response["result"]["isError"], true);
+        response["result"]["content"][0]["text"].as_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_thin_market_is_tool_error() {
+        // Only a buy order: nothing on the sell side to list against
+        let history = vec![history_day("2025-06-01", 5.0, 100)];
+        let handler = handler_with_cached_market(vec![market_order(1, true, 5.0)], history.clone());
+        let text = tool_error_text(&handler, "suggest_listing_price", json!({"region_id": 10000002, "type_id": 34}));
+        assert!(text.contains("No sell orders to price a listing against"));
+
+        let sell = json!({"region_id": 10000002, "type_id": 34, "quantity": 1000});
+        let text = tool_error_text(&handler, "estimate_time_to_sell", sell);
+        assert!(text.contains("No sell orders to price against"));
+
+        // Only a sell order: nothing on the buy side to outbid
+        let handler = handler_with_cached_market(vec![market_order(1, false, 6.0)], history);
+        let buy = json!({"region_id": 10000002, "type_id": 34, "quantity": 1000});
+        let text = tool_error_text(&handler, "estimate_buy_fill_time", buy);
+        assert!(text.contains("No buy orders to price against"));
+    }
+
+    #[test]
+    fn test_unknown_tool_is_protocol_error() {
+        let handler = McpHandler::new("TestServer".to_string(), "1.0.0".to_string());
+        let message = json!({
+            "jsonrpc": "2.0",
+            "id": 7,
+            "method": "tools/call",
+            "params": {"name": "no_such_tool", "arguments": {}}
+        });
+
+        let response = tokio_test::block_on(handler.handle_message(message));
+        assert_eq!(response["error"]["code"], -32601);
+        assert!(response.get("result").is_none());
+    }
+
+    #[test]
+    fn test_required_fields_are_declared() {
+        // Every required field must be a declared property, or validation could never pass
+        for tool in tool_definitions().as_array().unwrap() {
+            let schema = &tool["inputSchema"];
+            for required in schema["required"].as_array().into_iter().flatten() {
+                let field = required.as_str().unwrap();
+                assert!(
+                    schema["properties"].get(field).is_some(),
+                    "{} requires undeclared {field}",
+                    tool["name"]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_logging_set_level_and_tool_events() {
+        let handler = McpHandler::with_market_client(
+            "TestServer".to_string(),
+            "1.0.0".to_string(),
+            MarketClient::without_cache().with_logger(McpLogger::new(LogLevel::Warning)),
+        );
+        let initialize = handler.handle_initialize(&json!({"jsonrpc": "2.0", "id": 1, "method": "initialize"}));
+        assert!(initialize["result"]["capabilities"]["logging"].is_object());
+
+        let set_level = |level: &str| {
+            tokio_test::block_on(handler.handle_message(json!({
+                "jsonrpc": "2.0",
+                "id": 8,
+                "method": "logging/setLevel",
+                "params": {"level": level}
+            })))
+        };
+        assert_eq!(set_level("loud")["error"]["code"], -32602);
+        assert_eq!(set_level("info")["result"], json!({}));
+
+        tokio_test::block_on(handler.handle_message(json!({
+            "jsonrpc": "2.0",
+            "id": 9,
+            "method": "tools/call",
+            "params": {"name": "health_check"}
+        })));
+        let events = handler.drain_notifications();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["method"], "notifications/message");
+        assert_eq!(events[0]["params"]["level"], "info");
+        assert_eq!(events[0]["params"]["data"]["tool"], "health_check");
+        assert!(handler.drain_notifications().is_empty());
+    }
+
     #[test]
     fn test_cancelled_notification() {
         let handler = McpHandler::new("TestServer".to_string(), "1.0.0".to_string());
@@ -593,4 +4889,132 @@ mod tests {
         let response = handler.handle_cancelled(&message);
         assert_eq!(response, json!(null));
     }
+
+    #[tokio::test]
+    async fn test_start_and_stop_prefetch() {
+        let handler = McpHandler::with_market_client("TestServer".to_string(), "1.0.0".to_string(), MarketClient::new());
+        let call = |name: &str, arguments: Value| {
+            json!({
+                "jsonrpc": "2.0",
+                "id": 6,
+                "method": "tools/call",
+                "params": {"name": name, "arguments": arguments}
+            })
+        };
+        let text = |response: &Value| response["result"]["content"][0]["text"].as_str().unwrap().to_string();
+
+        let response = handler.handle_message(call("stop_prefetch", json!({}))).await;
+        assert_eq!(text(&response), "No prefetch is running.");
+
+        // Nothing to prefetch without targets or configured defaults
+        let response = handler.handle_message(call("start_prefetch", json!({}))).await;
+        assert_eq!(response["error"]["code"], -32602);
+
+        let targets = json!({"targets": [{"region_id": 10000002, "type_id": 34}], "include_history": false});
+        let response = handler.handle_message(call("start_prefetch", targets)).await;
+        assert!(text(&response).contains("Prefetching orders for 1 item(s)"), "{}", text(&response));
+
+        let response = handler.handle_message(call("stop_prefetch", json!({}))).await;
+        assert!(text(&response).starts_with("Stopped prefetching 1 item(s)"));
+        assert!(handler.prefetch.lock().unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_export_and_import_state() {
+        let new_handler =
+            || McpHandler::with_market_client("TestServer".to_string(), "1.0.0".to_string(), MarketClient::new());
+        let call = |name: &str, arguments: Value| {
+            json!({
+                "jsonrpc": "2.0",
+                "id": 7,
+                "method": "tools/call",
+                "params": {"name": name, "arguments": arguments}
+            })
+        };
+        let text = |response: &Value| response["result"]["content"][0]["text"].as_str().unwrap().to_string();
+
+        let source = new_handler();
+        source.watchlist.add(10000002, &[34, 35]).unwrap();
+        source.portfolio.add(34, 500, 4.0).unwrap();
+        let exported = text(&source.handle_message(call("export_state", json!({}))).await);
+        assert!(exported.contains("\"format\": \"tradergrader-state\""));
+
+        let target = new_handler();
+        let response = target.handle_message(call("import_state", json!({"state": exported}))).await;
+        assert!(text(&response).contains("2 new watched item(s), 0 alert(s), 1 portfolio position(s)"), "{response}");
+        assert_eq!(target.watchlist.entries().len(), 2);
+
+        let response = target.handle_message(call("import_state", json!({"state": {"format": "other"}}))).await;
+        assert_eq!(response["error"]["code"], -32602);
+    }
+
+    #[tokio::test]
+    async fn test_price_alert_tools() {
+        let handler = McpHandler::with_market_client(
+            "TestServer".to_string(),
+            "1.0.0".to_string(),
+            MarketClient::without_cache(),
+        );
+        let call = |name: &str, arguments: Value| {
+            json!({
+                "jsonrpc": "2.0",
+                "id": 7,
+                "method": "tools/call",
+                "params": {"name": name, "arguments": arguments}
+            })
+        };
+        let text = |response: &Value| response["result"]["content"][0]["text"].as_str().unwrap().to_string();
+
+        let plex = json!({"region_id": 10000002, "type_id": 44992, "condition": "below", "threshold": 3200000.0});
+        let response = handler.handle_message(call("set_price_alert", plex)).await;
+        assert!(text(&response).starts_with("Alert #1 set: sell price of type 44992"), "{}", text(&response));
+
+        let response = handler.handle_message(call("list_alerts", json!({}))).await;
+        assert!(text(&response).contains("#1: sell price of type 44992 in region 10000002 below 3200000.00 ISK"));
+
+        let response = handler.handle_message(call("remove_alert", json!({"alert_id": 1}))).await;
+        assert_eq!(text(&response), "Removed alert #1.");
+        let response = handler.handle_message(call("remove_alert", json!({"alert_id": 1}))).await;
+        assert_eq!(response["error"]["code"], -32602);
+        let response = handler.handle_message(call("list_alerts", json!({}))).await;
+        assert_eq!(text(&response), "No price alerts are set.");
+    }
+
+    #[tokio::test]
+    async fn test_watchlist_resources() {
+        let handler = McpHandler::with_market_client(
+            "TestServer".to_string(),
+            "1.0.0".to_string(),
+            MarketClient::without_cache(),
+        );
+        let request = |method: &str, params: Value| json!({"jsonrpc": "2.0", "id": 8, "method": method, "params": params});
+        let initialize = handler.handle_initialize(&request("initialize", json!({})));
+        assert_eq!(initialize["result"]["capabilities"]["resources"]["subscribe"], true);
+
+        let response = handler.handle_message(request("resources/list", json!({}))).await;
+        assert_eq!(response["result"]["resources"].as_array().unwrap().len(), 2);
+        let response = handler.handle_message(request("resources/subscribe", json!({"uri": WATCHLIST_URI}))).await;
+        assert_eq!(response["result"], json!({}));
+
+        let watch = json!({"name": "watch_items", "arguments": {"region_id": 10000002, "type_ids": [34, 35]}});
+        let response = handler.handle_message(request("tools/call", watch)).await;
+        assert!(response["result"]["content"][0]["text"].as_str().unwrap().starts_with("Added 2 item(s)"));
+        let notifications: Vec<Value> = handler.drain_notifications().into_iter().map(|n| n["method"].clone()).collect();
+        assert!(notifications.contains(&json!("notifications/resources/list_changed")));
+        assert!(notifications.contains(&json!("notifications/resources/updated")));
+
+        let response = handler.handle_message(request("resources/list", json!({}))).await;
+        let uris: Vec<&str> =
+            response["result"]["resources"].as_array().unwrap().iter().map(|r| r["uri"].as_str().unwrap()).collect();
+        assert_eq!(uris[1], SNAPSHOT_URI);
+        assert_eq!(uris[2], "tradergrader://market/10000002/34/summary");
+
+        let response = handler.handle_message(request("resources/read", json!({"uri": WATCHLIST_URI}))).await;
+        let contents = &response["result"]["contents"][0];
+        assert_eq!(contents["mimeType"], "application/json");
+        assert!(contents["text"].as_str().unwrap().contains("10000002"));
+
+        let response = handler.handle_message(request("resources/read", json!({"uri": "tradergrader://nope"}))).await;
+        assert_eq!(response["error"]["code"], -32002);
+    }
 }
\ No newline at end of file
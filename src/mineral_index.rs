@@ -0,0 +1,348 @@
+//! Mineral price index for TraderGrader
+//!
+//! A single number for "are minerals getting dearer", in the spirit of CCP's
+//! monthly economic report: a fixed basket priced every day against its price
+//! on a base day, so the index starts at 100 and 103 means the basket costs 3%
+//! more. Each item's weight is its share of the basket's value on the base
+//! day, so a mineral weighted 2 moves the index twice as much as one weighted
+//! 1 whatever their unit prices. Days an item didn't trade carry its last
+//! price forward. Any items can make up the basket, ores or compressed ores
+//! included; by default it is the eight minerals, equally weighted.
+
+use crate::error::{Result, TraderGraderError};
+use crate::market::MarketClient;
+use crate::scan::SCAN_CONCURRENCY;
+use crate::types::{IndexComponent, IndexPoint, MarketHistory, MineralIndex};
+use chrono::{Duration, NaiveDate};
+use futures::stream::{self, StreamExt};
+use std::collections::BTreeMap;
+
+/// Tritanium to Megacyte, and Morphite
+pub const MINERAL_TYPE_IDS: [i32; 8] = [34, 35, 36, 37, 38, 39, 40, 11399];
+
+/// Days of index values computed unless asked otherwise
+pub const DEFAULT_INDEX_DAYS: usize = 30;
+
+/// Most days of index values computed
+pub const MAX_INDEX_DAYS: usize = 365;
+
+/// Most items in one basket
+pub const MAX_INDEX_ITEMS: usize = 30;
+
+/// Most daily values listed in the text report
+const MAX_LISTED_DAYS: usize = 30;
+
+/// The default basket: every mineral with weight 1
+pub fn default_weights() -> Vec<(i32, f64)> {
+    MINERAL_TYPE_IDS.iter().map(|&type_id| (type_id, 1.0)).collect()
+}
+
+/// Checks a basket has items, not too many, no repeats and positive weights
+pub fn validate_weights(weights: &[(i32, f64)]) -> Result<()> {
+    let invalid = |reason: String| TraderGraderError::InvalidArgument {
+        field: "weights".to_string(),
+        reason,
+    };
+    if weights.is_empty() || weights.len() > MAX_INDEX_ITEMS {
+        return Err(invalid(format!("must list between 1 and {MAX_INDEX_ITEMS} items")));
+    }
+    for (index, (type_id, weight)) in weights.iter().enumerate() {
+        if !weight.is_finite() || *weight <= 0.0 {
+            return Err(invalid(format!("weight of type {type_id} must be above 0, got {weight}")));
+        }
+        if weights[..index].iter().any(|(other, _)| other == type_id) {
+            return Err(invalid(format!("type {type_id} is listed twice")));
+        }
+    }
+    Ok(())
+}
+
+impl MineralIndex {
+    /// Builds the index from item histories given as `(type_id, label, history)`
+    ///
+    /// The window covers the last `days` days up to the most recent date any
+    /// item traded. The base day is the first day in the window every item has
+    /// a price on, carried forward from earlier history where needed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::{MarketHistory, MineralIndex};
+    ///
+    /// let day = |date: &str, average: f64| MarketHistory {
+    ///     average,
+    ///     date: date.to_string(),
+    ///     highest: average,
+    ///     lowest: average,
+    ///     order_count: 1,
+    ///     volume: 1_000,
+    ///     extra: Default::default(),
+    /// };
+    /// let items = vec![
+    ///     (34, "Tritanium".to_string(), vec![day("2025-06-01", 4.0), day("2025-06-02", 5.0)]),
+    ///     (35, "Pyerite".to_string(), vec![day("2025-06-01", 10.0)]),
+    /// ];
+    ///
+    /// // Tritanium up 25%, Pyerite flat, equally weighted
+    /// let index = MineralIndex::from_histories(10000002, &[(34, 1.0), (35, 1.0)], &items, 30)?;
+    /// assert_eq!(index.base_date, "2025-06-01");
+    /// assert_eq!(index.points.last().unwrap().value, 112.5);
+    /// # Ok::<(), tradergrader::TraderGraderError>(())
+    /// ```
+    pub fn from_histories(
+        region_id: i32,
+        weights: &[(i32, f64)],
+        items: &[(i32, String, Vec<MarketHistory>)],
+        days: usize,
+    ) -> Result<Self> {
+        validate_weights(weights)?;
+        if !(1..=MAX_INDEX_DAYS).contains(&days) {
+            return Err(TraderGraderError::InvalidArgument {
+                field: "days".to_string(),
+                reason: format!("must be between 1 and {MAX_INDEX_DAYS}"),
+            });
+        }
+
+        let parse = |date: &str| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok();
+        let prices: Vec<BTreeMap<NaiveDate, f64>> = weights
+            .iter()
+            .map(|(type_id, _)| {
+                items
+                    .iter()
+                    .find(|(id, _, _)| id == type_id)
+                    .map(|(_, _, history)| {
+                        history
+                            .iter()
+                            .filter(|h| h.average > 0.0)
+                            .filter_map(|h| Some((parse(&h.date)?, h.average)))
+                            .collect()
+                    })
+                    .unwrap_or_default()
+            })
+            .collect();
+        let price_on = |series: &BTreeMap<NaiveDate, f64>, date: NaiveDate| {
+            series.range(..=date).next_back().map(|(_, price)| *price)
+        };
+
+        let end = prices
+            .iter()
+            .filter_map(|series| series.keys().next_back().copied())
+            .max()
+            .ok_or_else(|| TraderGraderError::InvalidParams("None of the items has any history".to_string()))?;
+        let start = end - Duration::days(days as i64 - 1);
+        let base_date = start
+            .iter_days()
+            .take_while(|date| *date <= end)
+            .find(|date| prices.iter().all(|series| price_on(series, *date).is_some()))
+            .ok_or_else(|| {
+                let unpriced: Vec<String> = weights
+                    .iter()
+                    .zip(&prices)
+                    .filter(|(_, series)| price_on(series, end).is_none())
+                    .map(|((type_id, _), _)| type_id.to_string())
+                    .collect();
+                TraderGraderError::InvalidParams(format!(
+                    "No price history for type(s) {} in region {region_id}",
+                    unpriced.join(", ")
+                ))
+            })?;
+        let base_prices: Vec<f64> = prices.iter().map(|series| price_on(series, base_date).unwrap_or(0.0)).collect();
+        let total_weight: f64 = weights.iter().map(|(_, weight)| weight).sum();
+
+        let value_on = |date: NaiveDate| {
+            let weighted: f64 = weights
+                .iter()
+                .zip(&prices)
+                .zip(&base_prices)
+                .map(|(((_, weight), series), base)| weight * price_on(series, date).unwrap_or(*base) / base)
+                .sum();
+            100.0 * weighted / total_weight
+        };
+        let mut points: Vec<IndexPoint> = Vec::new();
+        for date in base_date.iter_days().take_while(|date| *date <= end) {
+            let value = value_on(date);
+            let change_percent = points.last().map(|previous| (value / previous.value - 1.0) * 100.0);
+            points.push(IndexPoint {
+                date: date.to_string(),
+                value,
+                change_percent,
+            });
+        }
+
+        let components = weights
+            .iter()
+            .zip(&prices)
+            .zip(&base_prices)
+            .map(|(((type_id, weight), series), &base_price)| {
+                let latest_price = price_on(series, end).unwrap_or(base_price);
+                IndexComponent {
+                    type_id: *type_id,
+                    type_label: items
+                        .iter()
+                        .find(|(id, _, _)| id == type_id)
+                        .map_or_else(|| format!("Type {type_id}"), |(_, label, _)| label.clone()),
+                    weight_percent: weight / total_weight * 100.0,
+                    base_price,
+                    latest_price,
+                    change_percent: (latest_price / base_price - 1.0) * 100.0,
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            region_id,
+            base_date: base_date.to_string(),
+            points,
+            components,
+        })
+    }
+
+    /// The latest index value
+    pub fn latest(&self) -> Option<&IndexPoint> {
+        self.points.last()
+    }
+}
+
+impl MarketClient {
+    /// Prices a weighted basket of items in a region as a daily index, 100 on the base day
+    ///
+    /// Histories are fetched concurrently; the index fails if any can't be,
+    /// since leaving an item out would change what it measures.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # use tradergrader::mineral_index::{default_weights, DEFAULT_INDEX_DAYS};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// let index = client.mineral_index(10000002, &default_weights(), DEFAULT_INDEX_DAYS).await?;
+    /// println!("Mineral index: {:.2}", index.latest().unwrap().value);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn mineral_index(&self, region_id: i32, weights: &[(i32, f64)], days: usize) -> Result<MineralIndex> {
+        validate_weights(weights)?;
+        let items = stream::iter(weights.iter().map(|(type_id, _)| *type_id))
+            .map(|type_id| async move {
+                let history = self.fetch_market_history(region_id, type_id).await?;
+                Ok((type_id, self.type_label(type_id).await, history))
+            })
+            .buffered(SCAN_CONCURRENCY)
+            .collect::<Vec<Result<(i32, String, Vec<MarketHistory>)>>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>>>()?;
+
+        MineralIndex::from_histories(region_id, weights, &items, days)
+    }
+}
+
+/// Formats the index with its components and recent daily values
+pub(crate) fn format_mineral_index(index: &MineralIndex) -> String {
+    let change = |percent: Option<f64>| percent.map_or_else(|| "-".to_string(), |p| format!("{p:+.2}%"));
+    let Some(latest) = index.latest() else {
+        return format!("No index values for region {}.", index.region_id);
+    };
+    let period_change = (latest.value / 100.0 - 1.0) * 100.0;
+    let mut report = format!(
+        "Mineral Price Index for Region {}: {:.2} on {} ({} on the day, {:+.2}% since {}, base 100)\n\n\
+         | Item | Weight | Base price | Latest price | Change |\n\
+         |---|---|---|---|---|\n",
+        index.region_id,
+        latest.value,
+        latest.date,
+        change(latest.change_percent),
+        period_change,
+        index.base_date
+    );
+    for component in &index.components {
+        report.push_str(&format!(
+            "| {} | {:.1}% | {:.2} | {:.2} | {:+.2}% |\n",
+            component.type_label,
+            component.weight_percent,
+            component.base_price,
+            component.latest_price,
+            component.change_percent
+        ));
+    }
+
+    report.push_str("\n| Date | Index | Daily change |\n|---|---|---|\n");
+    let skipped = index.points.len().saturating_sub(MAX_LISTED_DAYS);
+    for point in index.points.iter().skip(skipped).rev() {
+        report.push_str(&format!("| {} | {:.2} | {} |\n", point.date, point.value, change(point.change_percent)));
+    }
+    if skipped > 0 {
+        report.push_str(&format!("... and {skipped} earlier days\n"));
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::history_day;
+
+    fn items() -> Vec<(i32, String, Vec<MarketHistory>)> {
+        vec![
+            (
+                34,
+                "Tritanium".to_string(),
+                vec![
+                    history_day("2025-05-20", 4.0, 100),
+                    history_day("2025-06-02", 5.0, 100),
+                    history_day("2025-06-03", 6.0, 100),
+                ],
+            ),
+            (
+                38,
+                "Nocxium".to_string(),
+                vec![history_day("2025-06-02", 800.0, 100), history_day("2025-06-03", 720.0, 100)],
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_index_weights_and_carry_forward() {
+        // Nocxium first trades on the 2nd, so that is the base day
+        let index = MineralIndex::from_histories(10000002, &[(34, 3.0), (38, 1.0)], &items(), 5).unwrap();
+        assert_eq!(index.base_date, "2025-06-02");
+        assert_eq!(index.points.len(), 2);
+        assert_eq!(index.points[0].value, 100.0);
+        assert_eq!(index.points[0].change_percent, None);
+        // Tritanium +20% at 75%, Nocxium -10% at 25%
+        let latest = index.latest().unwrap();
+        assert!((latest.value - 112.5).abs() < 1e-9);
+        assert!((latest.change_percent.unwrap() - 12.5).abs() < 1e-9);
+        assert_eq!(index.components[0].weight_percent, 75.0);
+        assert!((index.components[1].change_percent + 10.0).abs() < 1e-9);
+
+        // Tritanium's May price carries forward to the start of a longer window
+        let carried = MineralIndex::from_histories(10000002, &[(34, 1.0)], &items(), 10).unwrap();
+        assert_eq!(carried.base_date, "2025-05-25");
+        assert_eq!(carried.points.len(), 10);
+        assert_eq!(carried.points[7].value, 100.0);
+        assert_eq!(carried.points[8].value, 125.0);
+    }
+
+    #[test]
+    fn test_invalid_baskets() {
+        assert!(validate_weights(&[]).is_err());
+        assert!(validate_weights(&[(34, 0.0)]).is_err());
+        assert!(validate_weights(&[(34, 1.0), (34, 2.0)]).is_err());
+        assert!(validate_weights(&default_weights()).is_ok());
+        let missing = MineralIndex::from_histories(10000002, &[(34, 1.0), (40, 1.0)], &items(), 5).unwrap_err();
+        assert!(missing.to_string().contains("No price history for type(s) 40"));
+    }
+
+    #[test]
+    fn test_format() {
+        let index = MineralIndex::from_histories(10000002, &[(34, 3.0), (38, 1.0)], &items(), 5).unwrap();
+        let report = format_mineral_index(&index);
+        assert!(report.starts_with(
+            "Mineral Price Index for Region 10000002: 112.50 on 2025-06-03 (+12.50% on the day, +12.50% since 2025-06-02, base 100)"
+        ));
+        assert!(report.contains("| Tritanium | 75.0% | 5.00 | 6.00 | +20.00% |"));
+        assert!(report.contains("| 2025-06-02 | 100.00 | - |"));
+    }
+}
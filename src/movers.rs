@@ -0,0 +1,301 @@
+//! Top price movers for TraderGrader
+//!
+//! Ranks a basket of items (a market group or a list) by how far their
+//! average price moved over the last day, week or month, biggest gainers and
+//! losers first. Only daily history is needed, which the client caches for an
+//! hour, so re-running a scan over the same basket costs no ESI requests.
+
+use crate::error::Result;
+use crate::market::MarketClient;
+use crate::scan::{MAX_SCAN_TYPES, SCAN_CONCURRENCY};
+use crate::types::{MarketHistory, Period, PriceMover, TopMovers};
+use chrono::{Duration, NaiveDate};
+use futures::stream::{self, StreamExt};
+
+/// Default number of gainers and losers listed
+pub const DEFAULT_MOVERS_LIMIT: usize = 10;
+
+/// Days a period's change is measured over
+pub fn lookback_days(period: Period) -> i64 {
+    match period {
+        Period::Daily => 1,
+        Period::Weekly => 7,
+        Period::Monthly => 30,
+    }
+}
+
+impl PriceMover {
+    /// Measures an item's price change over `period` up to `end`
+    ///
+    /// The change runs from the last trading day at least a period before
+    /// `end` to the last trading day up to `end`. Items that didn't trade
+    /// within the period, or have no earlier price to compare with, give `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use tradergrader::{MarketHistory, Period, PriceMover};
+    ///
+    /// let day = |date: &str, average: f64| MarketHistory {
+    ///     average,
+    ///     date: date.to_string(),
+    ///     highest: average,
+    ///     lowest: average,
+    ///     order_count: 1,
+    ///     volume: 100,
+    ///     extra: Default::default(),
+    /// };
+    /// let history = vec![day("2025-06-01", 80.0), day("2025-06-07", 90.0), day("2025-06-08", 100.0)];
+    /// let end = NaiveDate::from_ymd_opt(2025, 6, 8).unwrap();
+    ///
+    /// let week = PriceMover::from_history(34, "Tritanium".to_string(), &history, Period::Weekly, end).unwrap();
+    /// assert_eq!(week.change_percent, 25.0);
+    /// ```
+    pub fn from_history(
+        type_id: i32,
+        type_label: String,
+        history: &[MarketHistory],
+        period: Period,
+        end: NaiveDate,
+    ) -> Option<Self> {
+        let lookback = lookback_days(period);
+        let start = end - Duration::days(lookback);
+        let mut days: Vec<(NaiveDate, &MarketHistory)> = history
+            .iter()
+            .filter(|h| h.average > 0.0)
+            .filter_map(|h| Some((NaiveDate::parse_from_str(&h.date, "%Y-%m-%d").ok()?, h)))
+            .filter(|(date, _)| *date <= end)
+            .collect();
+        days.sort_by_key(|(date, _)| *date);
+
+        let (to_date, to) = *days.last()?;
+        let (from_date, from) = *days.iter().rev().find(|(date, _)| *date <= start)?;
+        if to_date <= start {
+            return None;
+        }
+        Some(Self {
+            type_id,
+            type_label,
+            from_date: from_date.to_string(),
+            to_date: to_date.to_string(),
+            from_price: from.average,
+            to_price: to.average,
+            change_percent: (to.average / from.average - 1.0) * 100.0,
+            volume: days.iter().filter(|(date, _)| *date > start).map(|(_, h)| h.volume).sum(),
+        })
+    }
+}
+
+impl TopMovers {
+    /// Ranks histories given as `(type_id, label, history)` by price change over `period`
+    ///
+    /// Changes run up to the most recent date any item traded. Items that
+    /// traded fewer than `min_volume` units over the period are left out.
+    pub fn from_histories(
+        region_id: i32,
+        items: &[(i32, String, Vec<MarketHistory>)],
+        period: Period,
+        min_volume: i64,
+    ) -> Self {
+        let end = items
+            .iter()
+            .flat_map(|(_, _, history)| history.iter())
+            .filter_map(|h| NaiveDate::parse_from_str(&h.date, "%Y-%m-%d").ok())
+            .max();
+        let mut movers: Vec<PriceMover> = match end {
+            Some(end) => items
+                .iter()
+                .filter_map(|(type_id, label, history)| {
+                    PriceMover::from_history(*type_id, label.clone(), history, period, end)
+                })
+                .filter(|m| m.volume >= min_volume)
+                .collect(),
+            None => Vec::new(),
+        };
+        movers.sort_by(|a, b| b.change_percent.total_cmp(&a.change_percent).then(a.type_id.cmp(&b.type_id)));
+
+        let unranked = items.len() - movers.len();
+        let split = movers.partition_point(|m| m.change_percent > 0.0);
+        let mut losers: Vec<PriceMover> = movers.split_off(split).into_iter().filter(|m| m.change_percent < 0.0).collect();
+        losers.reverse();
+        Self {
+            region_id,
+            period,
+            end_date: end.map(|d| d.to_string()),
+            gainers: movers,
+            losers,
+            unranked,
+            failures: Vec::new(),
+        }
+    }
+}
+
+impl MarketClient {
+    /// Ranks the biggest gainers and losers among `type_ids` in a region
+    ///
+    /// Histories are fetched concurrently (at most [`SCAN_CONCURRENCY`] at
+    /// once) through the history cache. Items that fail to fetch are listed
+    /// in [`TopMovers::failures`] instead of failing the scan.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Period, Result};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// // Minerals in The Forge over the last week
+    /// let movers = client.top_movers(10000002, &[34, 35, 36, 37, 38, 39, 40], Period::Weekly, 0).await?;
+    /// if let Some(best) = movers.gainers.first() {
+    ///     println!("{} {:+.1}%", best.type_label, best.change_percent);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn top_movers(
+        &self,
+        region_id: i32,
+        type_ids: &[i32],
+        period: Period,
+        min_volume: i64,
+    ) -> Result<TopMovers> {
+        if type_ids.is_empty() {
+            return Err("No item types to rank".into());
+        }
+        if type_ids.len() > MAX_SCAN_TYPES {
+            return Err(format!("Too many item types to rank ({}, max {MAX_SCAN_TYPES})", type_ids.len()).into());
+        }
+
+        let fetched: Vec<(i32, Result<Vec<MarketHistory>>)> = stream::iter(type_ids.iter().copied())
+            .map(|type_id| async move { (type_id, self.fetch_market_history(region_id, type_id).await) })
+            .buffer_unordered(SCAN_CONCURRENCY)
+            .collect()
+            .await;
+
+        let mut items = Vec::new();
+        let mut failures = Vec::new();
+        for (type_id, outcome) in fetched {
+            match outcome {
+                Ok(history) => items.push((type_id, self.type_label(type_id).await, history)),
+                Err(e) => failures.push((type_id, e.to_string())),
+            }
+        }
+        failures.sort_by_key(|(type_id, _)| *type_id);
+
+        let mut movers = TopMovers::from_histories(region_id, &items, period, min_volume);
+        movers.failures = failures;
+        Ok(movers)
+    }
+
+    /// Ranks the biggest gainers and losers in a market group
+    pub async fn top_movers_in_group(
+        &self,
+        region_id: i32,
+        market_group_id: i32,
+        period: Period,
+        min_volume: i64,
+    ) -> Result<TopMovers> {
+        let group = self.fetch_market_group(market_group_id).await?;
+        if group.types.is_empty() {
+            return Err(format!("Market group {} ({}) has no item types", group.name, market_group_id).into());
+        }
+        self.top_movers(region_id, &group.types, period, min_volume).await
+    }
+}
+
+/// Formats the top `limit` gainers and losers
+pub(crate) fn format_top_movers(movers: &TopMovers, limit: usize) -> String {
+    let mut report = format!(
+        "Top {}-Day Movers in Region {}{}:\n",
+        lookback_days(movers.period),
+        movers.region_id,
+        movers.end_date.as_ref().map(|d| format!(" (to {d})")).unwrap_or_default()
+    );
+    for (title, list) in [("Gainers", &movers.gainers), ("Losers", &movers.losers)] {
+        report.push_str(&format!("\n{title}:\n"));
+        if list.is_empty() {
+            report.push_str("None\n");
+        }
+        for (rank, m) in list.iter().take(limit).enumerate() {
+            report.push_str(&format!(
+                "{}. {} {:+.2}%: {:.2} -> {:.2} ISK ({} to {}, {} units traded)\n",
+                rank + 1,
+                m.type_label,
+                m.change_percent,
+                m.from_price,
+                m.to_price,
+                m.from_date,
+                m.to_date,
+                m.volume
+            ));
+        }
+    }
+    if movers.unranked > 0 {
+        report.push_str(&format!(
+            "\n{} items were not ranked (no trades in the period, too little volume or no earlier price)\n",
+            movers.unranked
+        ));
+    }
+    if !movers.failures.is_empty() {
+        let failed: Vec<String> = movers.failures.iter().map(|(id, e)| format!("{id} ({e})")).collect();
+        report.push_str(&format!("\nFailed to fetch: {}\n", failed.join(", ")));
+    }
+    report.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::history_day;
+
+    fn history(days: &[(u32, f64, i64)]) -> Vec<MarketHistory> {
+        days.iter().map(|&(day, average, volume)| history_day(&format!("2025-04-{day:02}"), average, volume)).collect()
+    }
+
+    #[test]
+    fn test_daily_and_weekly_changes() {
+        let prices = history(&[(1, 100.0, 10), (8, 110.0, 10), (9, 121.0, 10), (10, 99.0, 10)]);
+        let end = NaiveDate::from_ymd_opt(2025, 4, 10).unwrap();
+
+        let day = PriceMover::from_history(34, "A".to_string(), &prices, Period::Daily, end).unwrap();
+        assert_eq!((day.from_date.as_str(), day.to_date.as_str()), ("2025-04-09", "2025-04-10"));
+        assert!((day.change_percent + 18.181818).abs() < 1e-4);
+        assert_eq!(day.volume, 10);
+
+        // No trade on the 3rd, so the week starts from the 1st
+        let week = PriceMover::from_history(34, "A".to_string(), &prices, Period::Weekly, end).unwrap();
+        assert_eq!(week.from_date, "2025-04-01");
+        assert!((week.change_percent + 1.0).abs() < 1e-9);
+        assert_eq!(week.volume, 30);
+
+        // Nothing a month back to compare with, and nothing traded since the 1st
+        assert!(PriceMover::from_history(34, "A".to_string(), &prices, Period::Monthly, end).is_none());
+        let stale = history(&[(1, 100.0, 10), (2, 120.0, 10)]);
+        assert!(PriceMover::from_history(35, "B".to_string(), &stale, Period::Daily, end).is_none());
+    }
+
+    #[test]
+    fn test_ranks_gainers_and_losers() {
+        let items = vec![
+            (1, "Up a lot".to_string(), history(&[(1, 10.0, 100), (2, 15.0, 100)])),
+            (2, "Up a little".to_string(), history(&[(1, 10.0, 100), (2, 10.5, 100)])),
+            (3, "Flat".to_string(), history(&[(1, 10.0, 100), (2, 10.0, 100)])),
+            (4, "Down a lot".to_string(), history(&[(1, 10.0, 100), (2, 5.0, 100)])),
+            (5, "Down a little".to_string(), history(&[(1, 10.0, 100), (2, 9.0, 100)])),
+            (6, "Thin".to_string(), history(&[(1, 10.0, 1), (2, 30.0, 1)])),
+        ];
+        let movers = TopMovers::from_histories(10000002, &items, Period::Daily, 10);
+
+        let ids = |list: &[PriceMover]| list.iter().map(|m| m.type_id).collect::<Vec<_>>();
+        assert_eq!(ids(&movers.gainers), vec![1, 2]);
+        assert_eq!(ids(&movers.losers), vec![4, 5]);
+        assert_eq!(movers.unranked, 1);
+        assert_eq!(movers.end_date.as_deref(), Some("2025-04-02"));
+
+        let text = format_top_movers(&movers, 1);
+        assert!(text.contains("Top 1-Day Movers in Region 10000002 (to 2025-04-02)"));
+        assert!(text.contains("1. Up a lot +50.00%"));
+        assert!(!text.contains("Up a little"));
+        assert!(text.contains("1. Down a lot -50.00%"));
+    }
+}
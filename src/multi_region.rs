@@ -0,0 +1,253 @@
+//! Multi-region order summaries for TraderGrader
+//!
+//! Comparing one item across several regions used to take a tool call per
+//! region, which MCP clients make one after another. A multi-region summary
+//! fetches every region's order book at once (the rate limiter still paces
+//! the requests) and puts best prices, order counts and listed volumes side
+//! by side. Figures are region-wide; use `compare_trade_hubs` for station-only
+//! hub prices.
+
+use crate::error::{Result, TraderGraderError};
+use crate::market::MarketClient;
+use crate::orderbook::MarketOrderBook;
+use crate::scan::SCAN_CONCURRENCY;
+use crate::types::{MultiRegionSummary, RegionQuote};
+use futures::stream::{self, StreamExt};
+use std::sync::Arc;
+
+/// Most regions compared in one summary; each region is one order book fetch
+pub const MAX_SUMMARY_REGIONS: usize = 30;
+
+impl RegionQuote {
+    /// Summarizes a region's order book for one item
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::orderbook::MarketOrderBook;
+    /// use tradergrader::{MarketOrder, RegionQuote};
+    ///
+    /// let order = |is_buy_order: bool, price: f64, volume_remain: i32| MarketOrder {
+    ///     duration: 90,
+    ///     is_buy_order,
+    ///     issued: "2025-06-01T00:00:00Z".to_string(),
+    ///     location_id: 60003760,
+    ///     min_volume: 1,
+    ///     order_id: price as i64,
+    ///     price,
+    ///     range: "station".to_string(),
+    ///     system_id: 30000142,
+    ///     type_id: 34,
+    ///     volume_remain,
+    ///     volume_total: volume_remain,
+    ///     extra: Default::default(),
+    /// };
+    /// let book = MarketOrderBook::new(vec![order(true, 4.0, 1_000), order(false, 5.0, 500), order(false, 6.0, 500)]);
+    ///
+    /// let quote = RegionQuote::from_book(10000002, "The Forge".to_string(), &book);
+    /// assert_eq!(quote.spread_percent, Some(20.0));
+    /// assert_eq!((quote.sell_orders, quote.sell_volume), (2, 1_000));
+    /// ```
+    pub fn from_book(region_id: i32, region_label: String, book: &MarketOrderBook) -> Self {
+        let best_buy = book.best_bid();
+        let best_sell = book.best_ask();
+        let spread_percent = match (book.spread(), best_sell) {
+            (Some(spread), Some(sell)) if sell > 0.0 => Some(spread / sell * 100.0),
+            _ => None,
+        };
+        Self {
+            region_id,
+            region_label,
+            best_buy,
+            best_sell,
+            spread_percent,
+            buy_orders: book.buy_count(),
+            sell_orders: book.sell_count(),
+            buy_volume: book.buys().map(|o| o.volume_remain as i64).sum(),
+            sell_volume: book.sells().map(|o| o.volume_remain as i64).sum(),
+        }
+    }
+}
+
+impl MultiRegionSummary {
+    /// Collects region quotes and marks the cheapest sell and highest buy region
+    pub fn new(type_id: i32, type_label: String, quotes: Vec<RegionQuote>, failures: Vec<(i32, String)>) -> Self {
+        let extreme = |price: fn(&RegionQuote) -> Option<f64>, highest: bool| {
+            let priced: Vec<(i32, f64)> = quotes.iter().filter_map(|q| price(q).map(|p| (q.region_id, p))).collect();
+            if priced.len() < 2 {
+                return None;
+            }
+            let best = if highest {
+                priced.into_iter().max_by(|a, b| a.1.total_cmp(&b.1))
+            } else {
+                priced.into_iter().min_by(|a, b| a.1.total_cmp(&b.1))
+            };
+            best.map(|(region_id, _)| region_id)
+        };
+        Self {
+            type_id,
+            type_label,
+            cheapest_sell_region_id: extreme(|q| q.best_sell, false),
+            highest_buy_region_id: extreme(|q| q.best_buy, true),
+            quotes,
+            failures,
+        }
+    }
+}
+
+impl MarketClient {
+    /// Compares one item's order books across regions
+    ///
+    /// Order books are fetched concurrently (at most [`SCAN_CONCURRENCY`] at
+    /// once) through the cache and rate limiter. Repeated regions are fetched
+    /// once. Regions that fail to fetch are listed in
+    /// [`MultiRegionSummary::failures`] instead of failing the summary.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// // PLEX in The Forge, Domain and Sinq Laison
+    /// let summary = client.multi_region_summary(44992, &[10000002, 10000043, 10000032]).await?;
+    /// println!("Cheapest in {:?}", summary.cheapest_sell_region_id);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn multi_region_summary(&self, type_id: i32, region_ids: &[i32]) -> Result<MultiRegionSummary> {
+        let mut regions: Vec<i32> = Vec::new();
+        for &region_id in region_ids {
+            if !regions.contains(&region_id) {
+                regions.push(region_id);
+            }
+        }
+        if regions.is_empty() {
+            return Err(TraderGraderError::InvalidParams("No regions to compare".to_string()));
+        }
+        if regions.len() > MAX_SUMMARY_REGIONS {
+            return Err(TraderGraderError::InvalidParams(format!(
+                "Too many regions to compare ({}, max {MAX_SUMMARY_REGIONS})",
+                regions.len()
+            )));
+        }
+
+        let fetched: Vec<(i32, Result<Arc<MarketOrderBook>>)> = stream::iter(regions.iter().copied())
+            .map(|region_id| async move { (region_id, self.fetch_order_book(region_id, Some(type_id)).await) })
+            .buffered(SCAN_CONCURRENCY)
+            .collect()
+            .await;
+
+        let ids: Vec<i64> = regions.iter().map(|&id| id as i64).collect();
+        let names = self.resolve_names(&ids).await.unwrap_or_default();
+        let mut quotes = Vec::new();
+        let mut failures = Vec::new();
+        for (region_id, outcome) in fetched {
+            match outcome {
+                Ok(book) => {
+                    let label = names
+                        .get(&(region_id as i64))
+                        .map(|n| n.name.clone())
+                        .unwrap_or_else(|| format!("Region {region_id}"));
+                    quotes.push(RegionQuote::from_book(region_id, label, &book));
+                }
+                Err(e) => failures.push((region_id, e.to_string())),
+            }
+        }
+
+        Ok(MultiRegionSummary::new(type_id, self.type_label(type_id).await, quotes, failures))
+    }
+
+    /// Generates a formatted multi-region comparison
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// println!("{}", client.get_multi_region_summary(34, &[10000002, 10000043]).await?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_multi_region_summary(&self, type_id: i32, region_ids: &[i32]) -> Result<String> {
+        Ok(format_multi_region_summary(&self.multi_region_summary(type_id, region_ids).await?))
+    }
+}
+
+/// Formats region quotes as a Markdown table, marking the best regions to buy from and sell to
+fn format_multi_region_summary(summary: &MultiRegionSummary) -> String {
+    let price = |p: Option<f64>, best: bool| match p {
+        Some(p) if best => format!("**{p:.2}**"),
+        Some(p) => format!("{p:.2}"),
+        None => "-".to_string(),
+    };
+    let mut report = format!(
+        "Multi-Region Summary for {} (region-wide orders; bold marks the cheapest sell and highest buy):\n\n\
+         | Region | Best buy | Best sell | Spread | Buy orders | Sell orders | Units wanted | Units for sale |\n\
+         |---|---|---|---|---|---|---|---|\n",
+        summary.type_label
+    );
+    for q in &summary.quotes {
+        report.push_str(&format!(
+            "| {} ({}) | {} | {} | {} | {} | {} | {} | {} |\n",
+            q.region_label,
+            q.region_id,
+            price(q.best_buy, summary.highest_buy_region_id == Some(q.region_id)),
+            price(q.best_sell, summary.cheapest_sell_region_id == Some(q.region_id)),
+            q.spread_percent.map(|s| format!("{s:.2}%")).unwrap_or_else(|| "n/a".to_string()),
+            q.buy_orders,
+            q.sell_orders,
+            q.buy_volume,
+            q.sell_volume
+        ));
+    }
+    if !summary.failures.is_empty() {
+        let failed: Vec<String> = summary.failures.iter().map(|(id, e)| format!("{id} ({e})")).collect();
+        report.push_str(&format!("\nFailed to fetch: {}\n", failed.join(", ")));
+    }
+    report.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quote(region_id: i32, best_buy: Option<f64>, best_sell: Option<f64>) -> RegionQuote {
+        RegionQuote {
+            region_id,
+            region_label: format!("Region {region_id}"),
+            best_buy,
+            best_sell,
+            spread_percent: None,
+            buy_orders: 1,
+            sell_orders: 1,
+            buy_volume: 10,
+            sell_volume: 20,
+        }
+    }
+
+    #[test]
+    fn test_marks_best_regions() {
+        let quotes = vec![
+            quote(10000002, Some(4.0), Some(5.0)),
+            quote(10000043, Some(4.5), Some(5.5)),
+            quote(10000032, None, Some(4.8)),
+        ];
+        let summary = MultiRegionSummary::new(34, "Tritanium (34)".to_string(), quotes, vec![(10000030, "timeout".to_string())]);
+        assert_eq!(summary.cheapest_sell_region_id, Some(10000032));
+        assert_eq!(summary.highest_buy_region_id, Some(10000043));
+
+        let text = format_multi_region_summary(&summary);
+        assert!(text.contains("| Region 10000032 (10000032) | - | **4.80** | n/a | 1 | 1 | 10 | 20 |"));
+        assert!(text.contains("| Region 10000043 (10000043) | **4.50** | 5.50 |"));
+        assert!(text.contains("Failed to fetch: 10000030 (timeout)"));
+    }
+
+    #[test]
+    fn test_single_region_has_no_extremes() {
+        let summary = MultiRegionSummary::new(34, "A".to_string(), vec![quote(10000002, Some(4.0), Some(5.0))], vec![]);
+        assert_eq!(summary.cheapest_sell_region_id, None);
+        assert_eq!(summary.highest_buy_region_id, None);
+    }
+}
@@ -0,0 +1,323 @@
+//! Typed order books for TraderGrader
+//!
+//! A regional order book can hold well over 100,000 orders. Summaries, depth
+//! analysis, wall detection and undercut checks all need the same views of it
+//! (bids and asks sorted by price, orders grouped by station), so a
+//! [`MarketOrderBook`] builds those indexes once per fetch and the client
+//! keeps it for the order cache TTL, letting every tool share the work.
+//! Library users get the same type from
+//! [`MarketClient::fetch_order_book`](crate::market::MarketClient::fetch_order_book)
+//! instead of filtering `Vec<MarketOrder>` themselves.
+
+use crate::types::{MarketOrder, PriceLevel};
+use std::collections::HashMap;
+
+/// Maximum number of indexed order books kept in memory per client
+pub const MAX_INDEXED_BOOKS: u64 = 256;
+
+/// One side of an order book
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookSide {
+    /// Buy orders, best (highest) price first
+    Bid,
+    /// Sell orders, best (lowest) price first
+    Ask,
+}
+
+/// An order book split into sorted bids and asks, with location indexes
+///
+/// # Examples
+///
+/// ```
+/// use tradergrader::orderbook::{BookSide, MarketOrderBook};
+///
+/// let book = MarketOrderBook::new(Vec::new());
+/// assert!(book.best_bid().is_none());
+/// assert_eq!(book.depth(BookSide::Ask, 5.0), 0);
+/// assert!(book.vwap(BookSide::Ask, 100).is_none());
+/// ```
+#[derive(Debug, Clone)]
+pub struct MarketOrderBook {
+    orders: Vec<MarketOrder>,
+    /// Indexes of buy orders, highest price first
+    buys: Vec<usize>,
+    /// Indexes of sell orders, lowest price first
+    sells: Vec<usize>,
+    /// Indexes of orders at each station or structure
+    by_location: HashMap<i64, Vec<usize>>,
+    buy_levels: Vec<PriceLevel>,
+    sell_levels: Vec<PriceLevel>,
+}
+
+impl MarketOrderBook {
+    /// Indexes a set of orders
+    pub fn new(orders: Vec<MarketOrder>) -> Self {
+        let by_price = |a: &usize, b: &usize| {
+            orders[*a]
+                .price
+                .partial_cmp(&orders[*b].price)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        };
+        let (mut buys, mut sells): (Vec<usize>, Vec<usize>) = (0..orders.len()).partition(|&i| orders[i].is_buy_order);
+        buys.sort_by(|a, b| by_price(b, a));
+        sells.sort_by(by_price);
+
+        let mut by_location: HashMap<i64, Vec<usize>> = HashMap::new();
+        for (i, order) in orders.iter().enumerate() {
+            by_location.entry(order.location_id).or_default().push(i);
+        }
+
+        let buy_levels = price_levels(buys.iter().map(|&i| &orders[i]));
+        let sell_levels = price_levels(sells.iter().map(|&i| &orders[i]));
+
+        Self {
+            orders,
+            buys,
+            sells,
+            by_location,
+            buy_levels,
+            sell_levels,
+        }
+    }
+
+    /// Every order in the book, in fetch order
+    pub fn orders(&self) -> &[MarketOrder] {
+        &self.orders
+    }
+
+    /// Number of orders in the book
+    pub fn len(&self) -> usize {
+        self.orders.len()
+    }
+
+    /// Whether the book holds no orders
+    pub fn is_empty(&self) -> bool {
+        self.orders.is_empty()
+    }
+
+    /// Buy orders, highest price first
+    pub fn buys(&self) -> impl Iterator<Item = &MarketOrder> + '_ {
+        self.buys.iter().map(|&i| &self.orders[i])
+    }
+
+    /// Sell orders, lowest price first
+    pub fn sells(&self) -> impl Iterator<Item = &MarketOrder> + '_ {
+        self.sells.iter().map(|&i| &self.orders[i])
+    }
+
+    /// Number of buy orders
+    pub fn buy_count(&self) -> usize {
+        self.buys.len()
+    }
+
+    /// Number of sell orders
+    pub fn sell_count(&self) -> usize {
+        self.sells.len()
+    }
+
+    /// Highest buy order
+    pub fn best_buy(&self) -> Option<&MarketOrder> {
+        self.buys().next()
+    }
+
+    /// Lowest sell order
+    pub fn best_sell(&self) -> Option<&MarketOrder> {
+        self.sells().next()
+    }
+
+    /// Buy volume aggregated by price, highest price first
+    pub fn buy_levels(&self) -> &[PriceLevel] {
+        &self.buy_levels
+    }
+
+    /// Sell volume aggregated by price, lowest price first
+    pub fn sell_levels(&self) -> &[PriceLevel] {
+        &self.sell_levels
+    }
+
+    /// Orders on one side, best price first
+    pub fn side(&self, side: BookSide) -> impl Iterator<Item = &MarketOrder> + '_ {
+        let indexes = match side {
+            BookSide::Bid => &self.buys,
+            BookSide::Ask => &self.sells,
+        };
+        indexes.iter().map(|&i| &self.orders[i])
+    }
+
+    /// Volume aggregated by price on one side, best price first
+    pub fn levels(&self, side: BookSide) -> &[PriceLevel] {
+        match side {
+            BookSide::Bid => &self.buy_levels,
+            BookSide::Ask => &self.sell_levels,
+        }
+    }
+
+    /// Highest buy price
+    pub fn best_bid(&self) -> Option<f64> {
+        self.buy_levels.first().map(|l| l.price)
+    }
+
+    /// Lowest sell price
+    pub fn best_ask(&self) -> Option<f64> {
+        self.sell_levels.first().map(|l| l.price)
+    }
+
+    /// Gap between the best ask and the best bid
+    pub fn spread(&self) -> Option<f64> {
+        Some(self.best_ask()? - self.best_bid()?)
+    }
+
+    /// Units available on one side within `percent` of its best price
+    pub fn depth(&self, side: BookSide, percent: f64) -> i64 {
+        let levels = self.levels(side);
+        let Some(best) = levels.first().map(|l| l.price) else {
+            return 0;
+        };
+        let limit = match side {
+            BookSide::Bid => best * (1.0 - percent / 100.0),
+            BookSide::Ask => best * (1.0 + percent / 100.0),
+        };
+        levels
+            .iter()
+            .take_while(|l| match side {
+                BookSide::Bid => l.price >= limit,
+                BookSide::Ask => l.price <= limit,
+            })
+            .map(|l| l.volume)
+            .sum()
+    }
+
+    /// ISK value of filling `quantity` units against one side, best price first
+    ///
+    /// Returns `None` when the side doesn't hold enough volume.
+    pub fn fill_cost(&self, side: BookSide, quantity: i64) -> Option<f64> {
+        let mut remaining = quantity;
+        let mut total = 0.0;
+        for level in self.levels(side) {
+            if remaining <= 0 {
+                break;
+            }
+            let filled = remaining.min(level.volume);
+            total += filled as f64 * level.price;
+            remaining -= filled;
+        }
+        (remaining <= 0).then_some(total)
+    }
+
+    /// Volume-weighted average price of filling `quantity` units against one side
+    ///
+    /// Returns `None` when `quantity` isn't positive or the side doesn't hold
+    /// enough volume.
+    pub fn vwap(&self, side: BookSide, quantity: i64) -> Option<f64> {
+        if quantity <= 0 {
+            return None;
+        }
+        self.fill_cost(side, quantity).map(|cost| cost / quantity as f64)
+    }
+
+    /// Orders at one station or structure
+    pub fn at_location(&self, location_id: i64) -> impl Iterator<Item = &MarketOrder> + '_ {
+        self.by_location
+            .get(&location_id)
+            .into_iter()
+            .flatten()
+            .map(|&i| &self.orders[i])
+    }
+
+    /// Stations and structures with at least one order
+    pub fn locations(&self) -> impl Iterator<Item = i64> + '_ {
+        self.by_location.keys().copied()
+    }
+}
+
+/// Aggregates orders already sorted best price first into price levels
+fn price_levels<'a>(sorted: impl Iterator<Item = &'a MarketOrder>) -> Vec<PriceLevel> {
+    let mut levels: Vec<PriceLevel> = Vec::new();
+    for order in sorted {
+        match levels.last_mut() {
+            Some(level) if level.price == order.price => {
+                level.volume += order.volume_remain as i64;
+                level.order_count += 1;
+            }
+            _ => levels.push(PriceLevel {
+                price: order.price,
+                volume: order.volume_remain as i64,
+                order_count: 1,
+            }),
+        }
+    }
+    levels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::market_order;
+
+    fn order(order_id: i64, is_buy_order: bool, price: f64, location_id: i64) -> MarketOrder {
+        market_order(order_id, is_buy_order, price).with_location(location_id)
+    }
+
+    #[test]
+    fn test_price_indexes() {
+        let book = MarketOrderBook::new(vec![
+            order(1, true, 4.0, 1),
+            order(2, false, 6.0, 1),
+            order(3, true, 4.5, 2),
+            order(4, false, 5.0, 2),
+            order(5, false, 5.0, 1),
+        ]);
+
+        assert_eq!(book.len(), 5);
+        assert_eq!((book.buy_count(), book.sell_count()), (2, 3));
+        assert_eq!(book.best_buy().map(|o| o.order_id), Some(3));
+        assert_eq!(book.best_sell().map(|o| o.price), Some(5.0));
+        assert_eq!(book.buys().map(|o| o.order_id).collect::<Vec<_>>(), vec![3, 1]);
+        assert_eq!(book.sells().map(|o| o.price).collect::<Vec<_>>(), vec![5.0, 5.0, 6.0]);
+
+        assert_eq!(book.sell_levels().len(), 2);
+        assert_eq!(book.sell_levels()[0].volume, 200);
+        assert_eq!(book.sell_levels()[0].order_count, 2);
+        assert_eq!(book.buy_levels()[0].price, 4.5);
+        assert_eq!(book.side(BookSide::Bid).map(|o| o.order_id).collect::<Vec<_>>(), vec![3, 1]);
+    }
+
+    #[test]
+    fn test_bid_ask_helpers() {
+        let book = MarketOrderBook::new(vec![
+            order(1, true, 4.0, 1),
+            order(2, false, 6.0, 1),
+            order(3, true, 4.5, 2),
+            order(4, false, 5.0, 2),
+            order(5, false, 5.0, 1),
+        ]);
+
+        assert_eq!(book.best_bid(), Some(4.5));
+        assert_eq!(book.best_ask(), Some(5.0));
+        assert_eq!(book.spread(), Some(0.5));
+
+        assert_eq!(book.depth(BookSide::Ask, 10.0), 200);
+        assert_eq!(book.depth(BookSide::Ask, 20.0), 300);
+        assert_eq!(book.depth(BookSide::Bid, 5.0), 100);
+
+        assert_eq!(book.fill_cost(BookSide::Ask, 250), Some(200.0 * 5.0 + 50.0 * 6.0));
+        assert_eq!(book.vwap(BookSide::Ask, 250), Some(5.2));
+        assert_eq!(book.vwap(BookSide::Bid, 200), Some(4.25));
+        assert_eq!(book.vwap(BookSide::Bid, 201), None);
+        assert_eq!(book.vwap(BookSide::Ask, 0), None);
+    }
+
+    #[test]
+    fn test_location_index() {
+        let book = MarketOrderBook::new(vec![order(1, true, 4.0, 1), order(2, false, 6.0, 2), order(3, false, 5.0, 1)]);
+
+        let mut at_one: Vec<i64> = book.at_location(1).map(|o| o.order_id).collect();
+        at_one.sort();
+        assert_eq!(at_one, vec![1, 3]);
+        assert_eq!(book.at_location(99).count(), 0);
+
+        let mut locations: Vec<i64> = book.locations().collect();
+        locations.sort();
+        assert_eq!(locations, vec![1, 2]);
+    }
+}
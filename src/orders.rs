@@ -0,0 +1,366 @@
+//! Market order filtering for TraderGrader
+//!
+//! A busy region lists hundreds of thousands of orders, far more than an MCP
+//! client can use. An [`OrderFilter`] narrows a region's orders to one side of
+//! the market, a minimum remaining volume and a price ceiling, and sorts what
+//! is left by price, volume or issue date. Filtering runs on the cached order
+//! list, so differently filtered calls for the same region share one ESI fetch.
+//! A page of the result is returned as a table or as JSON, with the number of
+//! matching orders alongside and station names next to location IDs.
+
+use crate::error::Result;
+use crate::market::MarketClient;
+use crate::paging::Page;
+use crate::types::{MarketOrder, OrderFilter, OrderListing, OrderSort, OrderType};
+use serde_json::{json, Value};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Orders listed per page unless the caller asks for more
+pub const DEFAULT_ORDERS_PAGE: usize = 20;
+
+impl OrderFilter {
+    /// Whether an order passes the filter
+    pub fn matches(&self, order: &MarketOrder) -> bool {
+        let side = match self.order_type {
+            OrderType::Buy => order.is_buy_order,
+            OrderType::Sell => !order.is_buy_order,
+            OrderType::All => true,
+        };
+        side && self.min_volume.is_none_or(|min| order.volume_remain >= min)
+            && self.max_price.is_none_or(|max| order.price <= max)
+    }
+
+    /// The orders passing the filter, sorted
+    ///
+    /// Sorting by price puts sell orders first, cheapest first, then buy
+    /// orders, highest first. Volume and issue date sort largest and newest
+    /// first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::{MarketOrder, OrderFilter, OrderType};
+    ///
+    /// let order = |order_id: i64, is_buy_order: bool, price: f64| MarketOrder {
+    ///     duration: 90,
+    ///     is_buy_order,
+    ///     issued: "2025-06-01T00:00:00Z".to_string(),
+    ///     location_id: 60003760,
+    ///     min_volume: 1,
+    ///     order_id,
+    ///     price,
+    ///     range: "station".to_string(),
+    ///     system_id: 30000142,
+    ///     type_id: 34,
+    ///     volume_remain: 1_000,
+    ///     volume_total: 1_000,
+    ///     extra: Default::default(),
+    /// };
+    /// let orders = vec![order(1, false, 6.0), order(2, true, 4.0), order(3, false, 5.0), order(4, false, 9.0)];
+    ///
+    /// let filter = OrderFilter {
+    ///     order_type: OrderType::Sell,
+    ///     max_price: Some(8.0),
+    ///     ..OrderFilter::default()
+    /// };
+    /// let ids: Vec<i64> = filter.apply(&orders).iter().map(|o| o.order_id).collect();
+    /// assert_eq!(ids, vec![3, 1]);
+    /// ```
+    pub fn apply(&self, orders: &[MarketOrder]) -> Vec<MarketOrder> {
+        let mut matching: Vec<MarketOrder> = orders.iter().filter(|o| self.matches(o)).cloned().collect();
+        matching.sort_by(|a, b| self.compare(a, b).then(a.order_id.cmp(&b.order_id)));
+        matching
+    }
+
+    fn compare(&self, a: &MarketOrder, b: &MarketOrder) -> Ordering {
+        match self.sort_by {
+            OrderSort::Price => a.is_buy_order.cmp(&b.is_buy_order).then_with(|| {
+                if a.is_buy_order {
+                    b.price.total_cmp(&a.price)
+                } else {
+                    a.price.total_cmp(&b.price)
+                }
+            }),
+            OrderSort::Volume => b.volume_remain.cmp(&a.volume_remain),
+            // RFC 3339 timestamps in UTC sort as strings
+            OrderSort::Issued => b.issued.cmp(&a.issued),
+        }
+    }
+}
+
+impl fmt::Display for OrderFilter {
+    /// Describes the filter, e.g. "sell orders, at least 100 units, at most 5.00 ISK, by price"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self.order_type {
+            OrderType::Buy => "buy orders",
+            OrderType::Sell => "sell orders",
+            OrderType::All => "buy and sell orders",
+        })?;
+        if let Some(min_volume) = self.min_volume {
+            write!(f, ", at least {min_volume} units")?;
+        }
+        if let Some(max_price) = self.max_price {
+            write!(f, ", at most {max_price:.2} ISK")?;
+        }
+        f.write_str(match self.sort_by {
+            OrderSort::Price => ", by price",
+            OrderSort::Volume => ", by volume",
+            OrderSort::Issued => ", newest first",
+        })
+    }
+}
+
+impl OrderListing {
+    /// A page of the matching orders as JSON, with counts and paging metadata
+    ///
+    /// Orders whose location is in `locations` get a `location_name`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use tradergrader::paging::Page;
+    /// use tradergrader::{OrderFilter, OrderListing};
+    ///
+    /// let listing = OrderListing {
+    ///     region_id: 10000002,
+    ///     type_id: Some(34),
+    ///     filter: OrderFilter::default(),
+    ///     total_orders: 0,
+    ///     orders: Vec::new(),
+    /// };
+    /// let json = listing.to_json(Page::first(20), &HashMap::new());
+    /// assert_eq!(json["matching_orders"], 0);
+    /// assert_eq!(json["filter"]["sort_by"], "price");
+    /// ```
+    pub fn to_json(&self, page: Page, locations: &HashMap<i64, String>) -> Value {
+        let shown = page.slice(&self.orders);
+        let orders: Vec<Value> = shown
+            .iter()
+            .map(|order| {
+                let mut value = json!(order);
+                if let Some(name) = locations.get(&order.location_id) {
+                    value["location_name"] = json!(name);
+                }
+                value
+            })
+            .collect();
+        json!({
+            "region_id": self.region_id,
+            "type_id": self.type_id,
+            "filter": self.filter,
+            "total_orders": self.total_orders,
+            "matching_orders": self.orders.len(),
+            "offset": page.offset,
+            "returned": shown.len(),
+            "orders": orders
+        })
+    }
+}
+
+impl MarketClient {
+    /// Fetches a region's market orders and keeps those passing `filter`, sorted
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, OrderFilter, OrderType, Result};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// let filter = OrderFilter {
+    ///     order_type: OrderType::Sell,
+    ///     min_volume: Some(1_000),
+    ///     ..OrderFilter::default()
+    /// };
+    /// // Tritanium sell orders of at least 1,000 units in The Forge, cheapest first
+    /// let listing = client.query_market_orders(10000002, Some(34), &filter).await?;
+    /// println!("{} of {} orders match", listing.orders.len(), listing.total_orders);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn query_market_orders(
+        &self,
+        region_id: i32,
+        type_id: Option<i32>,
+        filter: &OrderFilter,
+    ) -> Result<OrderListing> {
+        let orders = self.fetch_market_orders(region_id, type_id).await?;
+        Ok(OrderListing {
+            region_id,
+            type_id,
+            filter: *filter,
+            total_orders: orders.len(),
+            orders: filter.apply(&orders),
+        })
+    }
+
+    /// Generates a table of the matching orders on `page`, naming their stations
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, OrderFilter, Result};
+    /// use tradergrader::paging::Page;
+    ///
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// let report = client
+    ///     .get_market_orders_summary(10000002, Some(34), &OrderFilter::default(), Page::first(20))
+    ///     .await?;
+    /// println!("{}", report);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_market_orders_summary(
+        &self,
+        region_id: i32,
+        type_id: Option<i32>,
+        filter: &OrderFilter,
+        page: Page,
+    ) -> Result<String> {
+        let listing = self.query_market_orders(region_id, type_id, filter).await?;
+        let label = match type_id {
+            Some(type_id) => self.type_label(type_id).await,
+            None => "all items".to_string(),
+        };
+        let locations = self.page_locations(&listing, page).await;
+        Ok(format_order_listing(&listing, &label, page, &locations))
+    }
+
+    /// Names for the locations of the orders on `page`
+    pub async fn page_locations(&self, listing: &OrderListing, page: Page) -> HashMap<i64, String> {
+        let ids: Vec<i64> = page.slice(&listing.orders).iter().map(|o| o.location_id).collect();
+        self.resolve_location_names(&ids).await
+    }
+}
+
+/// Formats a page of orders as a Markdown table under a line of counts
+fn format_order_listing(
+    listing: &OrderListing,
+    type_label: &str,
+    page: Page,
+    locations: &HashMap<i64, String>,
+) -> String {
+    let mut report = format!(
+        "Market orders for {type_label} in region {}: {} of {} match ({})\n",
+        listing.region_id,
+        listing.orders.len(),
+        listing.total_orders,
+        listing.filter
+    );
+    if listing.orders.is_empty() {
+        return report.trim_end().to_string();
+    }
+    // Item-wide listings mix types, so each row says which
+    let all_types = listing.type_id.is_none();
+    report.push_str(if all_types {
+        "\n| Type | Side | Price | Remaining | Min volume | Location | Range | Issued |\n|---|---|---|---|---|---|---|---|\n"
+    } else {
+        "\n| Side | Price | Remaining | Min volume | Location | Range | Issued |\n|---|---|---|---|---|---|---|\n"
+    });
+    for order in page.slice(&listing.orders) {
+        if all_types {
+            report.push_str(&format!("| {} ", order.type_id));
+        }
+        report.push_str(&format!(
+            "| {} | {:.2} | {}/{} | {} | {} | {} | {} |\n",
+            if order.is_buy_order { "buy" } else { "sell" },
+            order.price,
+            order.volume_remain,
+            order.volume_total,
+            order.min_volume,
+            locations.get(&order.location_id).cloned().unwrap_or_else(|| order.location_id.to_string()),
+            order.range,
+            order.issued
+        ));
+    }
+    if let Some(marker) = page.marker(listing.orders.len(), "orders") {
+        report.push_str(&format!("{marker}\n"));
+    }
+    report.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::market_order;
+
+    fn order(order_id: i64, is_buy_order: bool, price: f64, volume_remain: i32, issued: &str) -> MarketOrder {
+        market_order(order_id, is_buy_order, price).with_volume(volume_remain).with_issued(issued)
+    }
+
+    fn orders() -> Vec<MarketOrder> {
+        vec![
+            order(1, false, 5.5, 100, "2025-06-01T10:00:00Z"),
+            order(2, true, 4.0, 5_000, "2025-06-03T10:00:00Z"),
+            order(3, false, 5.0, 50, "2025-06-02T10:00:00Z"),
+            order(4, true, 4.5, 200, "2025-05-30T10:00:00Z"),
+        ]
+    }
+
+    fn ids(orders: &[MarketOrder]) -> Vec<i64> {
+        orders.iter().map(|o| o.order_id).collect()
+    }
+
+    #[test]
+    fn test_sort_orders() {
+        let by = |sort_by| OrderFilter {
+            sort_by,
+            ..OrderFilter::default()
+        };
+        assert_eq!(ids(&by(OrderSort::Price).apply(&orders())), vec![3, 1, 4, 2]);
+        assert_eq!(ids(&by(OrderSort::Volume).apply(&orders())), vec![2, 4, 1, 3]);
+        assert_eq!(ids(&by(OrderSort::Issued).apply(&orders())), vec![2, 3, 1, 4]);
+    }
+
+    #[test]
+    fn test_filter_orders() {
+        let filter = OrderFilter {
+            order_type: OrderType::Buy,
+            min_volume: Some(150),
+            max_price: Some(4.75),
+            sort_by: OrderSort::Price,
+        };
+        assert_eq!(ids(&filter.apply(&orders())), vec![4, 2]);
+        assert_eq!(filter.to_string(), "buy orders, at least 150 units, at most 4.75 ISK, by price");
+
+        let cheap = OrderFilter {
+            max_price: Some(4.25),
+            ..OrderFilter::default()
+        };
+        assert_eq!(ids(&cheap.apply(&orders())), vec![2]);
+        assert_eq!("Both".parse::<OrderType>(), Ok(OrderType::All));
+        assert!("newest".parse::<OrderSort>().is_ok());
+        assert!("cheapest".parse::<OrderSort>().is_err());
+    }
+
+    #[test]
+    fn test_format_order_listing() {
+        let filter = OrderFilter {
+            order_type: OrderType::Sell,
+            ..OrderFilter::default()
+        };
+        let listing = OrderListing {
+            region_id: 10000002,
+            type_id: Some(34),
+            filter,
+            total_orders: 4,
+            orders: filter.apply(&orders()),
+        };
+        let jita = HashMap::from([(60003760, "Jita IV - Moon 4 - Caldari Navy Assembly Plant".to_string())]);
+        let report = format_order_listing(&listing, "Tritanium (34)", Page::first(1), &jita);
+        assert!(report.starts_with("Market orders for Tritanium (34) in region 10000002: 2 of 4 match (sell orders, by price)"));
+        assert!(report.contains("| sell | 5.00 | 50/50 | 1 | Jita IV - Moon 4 - Caldari Navy Assembly Plant | region |"));
+        assert!(!report.contains("5.50"));
+        assert!(report.ends_with("Showing 1–1 of 2 orders; pass offset=1 for more"));
+
+        let json = listing.to_json(Page { offset: 1, limit: 5 }, &jita);
+        assert_eq!(json["total_orders"], 4);
+        assert_eq!(json["returned"], 1);
+        assert_eq!(json["orders"][0]["order_id"], 1);
+        assert_eq!(json["orders"][0]["location_name"], "Jita IV - Moon 4 - Caldari Navy Assembly Plant");
+        assert!(listing.to_json(Page::first(5), &HashMap::new())["orders"][0].get("location_name").is_none());
+    }
+}
@@ -0,0 +1,152 @@
+//! Tool output paging and truncation for TraderGrader
+//!
+//! Listings such as a year of daily history or a large market group can run
+//! past what an MCP client can fit in its context. Listing tools take `offset`
+//! and `limit` arguments, read into a [`Page`], and say how many entries there
+//! are in total and how to get the next page. Every tool also takes
+//! `max_chars`: longer output is cut at a line boundary by
+//! [`truncate_output`], with a marker saying how much was left out.
+
+use serde_json::Value;
+
+/// Output longer than this many characters is truncated unless `max_chars` says otherwise
+pub const DEFAULT_MAX_CHARS: usize = 50_000;
+
+/// Smallest `max_chars` accepted, so the truncation marker always has a line to follow
+pub const MIN_MAX_CHARS: usize = 200;
+
+/// Which entries of a listing a tool call asked for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Page {
+    /// Entries skipped from the start of the listing
+    pub offset: usize,
+    /// Most entries shown
+    pub limit: usize,
+}
+
+impl Page {
+    /// The first `limit` entries
+    pub fn first(limit: usize) -> Self {
+        Self { offset: 0, limit }
+    }
+
+    /// Reads the `offset` and `limit` tool arguments, defaulting to the first `default_limit` entries
+    pub fn from_arguments(arguments: Option<&Value>, default_limit: usize) -> Self {
+        let arg = |name: &str| {
+            arguments
+                .and_then(|a| a.get(name))
+                .and_then(|v| v.as_u64())
+                .map(|v| usize::try_from(v).unwrap_or(usize::MAX))
+        };
+        Self {
+            offset: arg("offset").unwrap_or(0),
+            limit: arg("limit").unwrap_or(default_limit),
+        }
+    }
+
+    /// The entries of `items` on this page
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::paging::Page;
+    ///
+    /// let items = [1, 2, 3, 4, 5];
+    /// let page = Page { offset: 3, limit: 10 };
+    /// assert_eq!(page.slice(&items), &[4, 5]);
+    /// assert_eq!(page.marker(items.len(), "items").as_deref(), Some("Showing 4–5 of 5 items"));
+    /// assert_eq!(Page::first(10).marker(items.len(), "items"), None);
+    /// assert_eq!(
+    ///     Page::first(2).marker(items.len(), "items").as_deref(),
+    ///     Some("Showing 1–2 of 5 items; pass offset=2 for more")
+    /// );
+    /// ```
+    pub fn slice<'a, T>(&self, items: &'a [T]) -> &'a [T] {
+        let start = self.offset.min(items.len());
+        let end = start.saturating_add(self.limit).min(items.len());
+        &items[start..end]
+    }
+
+    /// A line saying which entries are shown, or `None` when the page shows all of them
+    pub fn marker(&self, total: usize, noun: &str) -> Option<String> {
+        if self.offset == 0 && self.limit >= total {
+            return None;
+        }
+        if self.offset >= total {
+            return Some(format!("No {noun} at offset {}; there are {total} in total", self.offset));
+        }
+        let end = self.offset.saturating_add(self.limit).min(total);
+        let more = if end < total { format!("; pass offset={end} for more") } else { String::new() };
+        Some(format!("Showing {}–{end} of {total} {noun}{more}", self.offset + 1))
+    }
+}
+
+/// Reads the `max_chars` tool argument
+pub fn max_chars_argument(arguments: Option<&Value>) -> usize {
+    arguments
+        .and_then(|a| a.get("max_chars"))
+        .and_then(|v| v.as_u64())
+        .map_or(DEFAULT_MAX_CHARS, |v| usize::try_from(v).unwrap_or(usize::MAX))
+        .max(MIN_MAX_CHARS)
+}
+
+/// Cuts `text` to at most `max_chars` characters, ending on a whole line, and says what was dropped
+///
+/// # Examples
+///
+/// ```
+/// use tradergrader::paging::truncate_output;
+///
+/// let text = "line one\nline two\nline three".to_string();
+/// assert_eq!(truncate_output(text.clone(), 100), text);
+/// assert_eq!(
+///     truncate_output(text, 20),
+///     "line one\nline two\n\n[Truncated: showing 17 of 28 characters; raise max_chars or narrow the request]"
+/// );
+/// ```
+pub fn truncate_output(text: String, max_chars: usize) -> String {
+    let total = text.chars().count();
+    if total <= max_chars {
+        return text;
+    }
+    let cut = text.char_indices().nth(max_chars).map_or(text.len(), |(i, _)| i);
+    // Keep whole lines when there is at least one; a single huge line is cut mid-line
+    let kept = match text[..cut].rfind('\n') {
+        Some(newline) if newline > 0 => &text[..newline],
+        _ => &text[..cut],
+    };
+    format!(
+        "{kept}\n\n[Truncated: showing {} of {total} characters; raise max_chars or narrow the request]",
+        kept.chars().count()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_page_from_arguments() {
+        let arguments = json!({"offset": 20, "limit": 10});
+        assert_eq!(Page::from_arguments(Some(&arguments), 5), Page { offset: 20, limit: 10 });
+        assert_eq!(Page::from_arguments(None, 5), Page::first(5));
+
+        let page = Page { offset: 20, limit: 10 };
+        assert_eq!(page.marker(25, "days").as_deref(), Some("Showing 21–25 of 25 days"));
+        assert_eq!(page.marker(40, "days").as_deref(), Some("Showing 21–30 of 40 days; pass offset=30 for more"));
+        assert_eq!(page.marker(5, "days").as_deref(), Some("No days at offset 20; there are 5 in total"));
+        assert!(page.slice(&[1, 2, 3]).is_empty());
+    }
+
+    #[test]
+    fn test_truncate_output() {
+        assert_eq!(max_chars_argument(Some(&json!({"max_chars": 10}))), MIN_MAX_CHARS);
+        assert_eq!(max_chars_argument(None), DEFAULT_MAX_CHARS);
+
+        let long_line = "é".repeat(300);
+        let truncated = truncate_output(long_line, MIN_MAX_CHARS);
+        assert!(truncated.starts_with(&"é".repeat(MIN_MAX_CHARS)));
+        assert!(truncated.ends_with("showing 200 of 300 characters; raise max_chars or narrow the request]"));
+    }
+}
@@ -0,0 +1,219 @@
+//! Guarded access to arbitrary public ESI GET routes
+//!
+//! ESI has far more public routes than TraderGrader wraps. [`MarketClient::esi_get`]
+//! lets power users reach them without leaving the crate's protections: every
+//! request goes through the shared rate limiter and the cache, only routes
+//! under an allowlist of prefixes are reachable, and paths and query parameters
+//! are checked so a request can't smuggle in another host, a traversal or an
+//! access token. The allowlist defaults to read-only public data and can be
+//! replaced with `TRADERGRADER_ESI_ALLOWLIST`.
+
+use crate::cache::CacheKey;
+use crate::error::{Result, TraderGraderError};
+use crate::market::MarketClient;
+use serde_json::Value;
+
+/// Route prefixes reachable through [`MarketClient::esi_get`] by default
+pub const DEFAULT_ESI_ALLOWLIST: [&str; 10] = [
+    "/markets/",
+    "/universe/",
+    "/route/",
+    "/status/",
+    "/industry/",
+    "/insurance/",
+    "/incursions/",
+    "/sovereignty/",
+    "/dogma/",
+    "/contracts/public/",
+];
+
+/// Query parameters that are never forwarded, since they would carry credentials
+const FORBIDDEN_PARAMS: [&str; 1] = ["token"];
+
+/// Route prefixes that [`MarketClient::esi_get`] may request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EsiAllowlist {
+    prefixes: Vec<String>,
+}
+
+impl Default for EsiAllowlist {
+    fn default() -> Self {
+        Self::new(DEFAULT_ESI_ALLOWLIST)
+    }
+}
+
+impl EsiAllowlist {
+    /// Creates an allowlist from route prefixes such as `/markets/`
+    ///
+    /// Prefixes are normalized to start and end with `/`, so `markets`
+    /// allows `/markets/prices/` but not `/marketsfoo/`. A lone `/` allows
+    /// every public route.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::passthrough::EsiAllowlist;
+    ///
+    /// let allowlist = EsiAllowlist::new(["markets", "/universe/types"]);
+    /// assert!(allowlist.allows("/markets/prices/"));
+    /// assert!(allowlist.allows("/universe/types/34/"));
+    /// assert!(!allowlist.allows("/universe/regions/"));
+    /// ```
+    pub fn new<I, S>(prefixes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let prefixes = prefixes
+            .into_iter()
+            .map(|prefix| prefix.as_ref().trim().trim_matches('/').to_string())
+            .map(|prefix| if prefix.is_empty() { "/".to_string() } else { format!("/{prefix}/") })
+            .collect();
+        Self { prefixes }
+    }
+
+    /// Load the allowlist from `TRADERGRADER_ESI_ALLOWLIST` (comma-separated prefixes)
+    pub fn from_env() -> Option<Self> {
+        let prefixes = std::env::var("TRADERGRADER_ESI_ALLOWLIST").ok()?;
+        Some(Self::new(prefixes.split(',').filter(|p| !p.trim().is_empty())))
+    }
+
+    /// The normalized prefixes
+    pub fn prefixes(&self) -> &[String] {
+        &self.prefixes
+    }
+
+    /// Whether a normalized route path is under one of the prefixes
+    pub fn allows(&self, path: &str) -> bool {
+        self.prefixes.iter().any(|prefix| path.starts_with(prefix.as_str()))
+    }
+}
+
+impl MarketClient {
+    /// Fetches any allowlisted public ESI route as raw JSON
+    ///
+    /// `path` is relative to the configured ESI version, e.g.
+    /// `/markets/prices/`; a missing trailing slash is added. Query
+    /// parameters are passed separately. Responses are cached for as long as
+    /// ESI's `Expires` header allows, and requests count against the shared
+    /// rate limit like any other.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// let prices = client.esi_get("/markets/prices/", &[]).await?;
+    /// let status = client.esi_get("/status", &[("datasource", "tranquility")]).await?;
+    /// println!("{} prices, {} players online", prices.as_array().map_or(0, Vec::len), status["players"]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn esi_get(&self, path: &str, params: &[(&str, &str)]) -> Result<Value> {
+        let path = normalize_path(path)?;
+        if !self.esi_allowlist().allows(&path) {
+            return Err(TraderGraderError::InvalidArgument {
+                field: "path".to_string(),
+                reason: format!(
+                    "{path} is not allowed; allowed prefixes: {}",
+                    self.esi_allowlist().prefixes().join(", ")
+                ),
+            });
+        }
+        let route = with_query(&path, params)?;
+        self.esi().get_cached::<Value>(&route, &CacheKey::esi_get(&route), "esi_get").await
+    }
+}
+
+/// Checks a route path only contains plain path segments and adds the trailing slash
+fn normalize_path(path: &str) -> Result<String> {
+    let invalid = |reason: &str| TraderGraderError::InvalidArgument {
+        field: "path".to_string(),
+        reason: reason.to_string(),
+    };
+    let path = path.trim();
+    if !path.starts_with('/') {
+        return Err(invalid("must start with /"));
+    }
+    if !path.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | '_' | '-')) {
+        return Err(invalid("may only contain letters, digits, '/', '_' and '-'; pass query parameters in params"));
+    }
+    if path.contains("//") {
+        return Err(invalid("must not contain empty segments"));
+    }
+    Ok(if path.ends_with('/') { path.to_string() } else { format!("{path}/") })
+}
+
+/// Appends URL-encoded query parameters to a route, sorted so equal requests share a cache entry
+fn with_query(path: &str, params: &[(&str, &str)]) -> Result<String> {
+    if let Some((name, _)) = params
+        .iter()
+        .find(|(name, _)| FORBIDDEN_PARAMS.iter().any(|f| f.eq_ignore_ascii_case(name)))
+    {
+        return Err(TraderGraderError::InvalidArgument {
+            field: format!("params.{name}"),
+            reason: "can't be forwarded; only public routes are supported".to_string(),
+        });
+    }
+    if params.is_empty() {
+        return Ok(path.to_string());
+    }
+    let mut params = params.to_vec();
+    params.sort();
+    let mut query = reqwest::Url::parse("http://query.invalid/").expect("Static URL is valid");
+    query.query_pairs_mut().extend_pairs(params);
+    Ok(format!("{path}?{}", query.query().unwrap_or_default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allowlist() {
+        let allowlist = EsiAllowlist::default();
+        assert!(allowlist.allows("/markets/10000002/orders/"));
+        assert!(allowlist.allows("/contracts/public/10000002/"));
+        assert!(!allowlist.allows("/characters/90000001/assets/"));
+        assert!(!allowlist.allows("/contracts/"));
+
+        let everything = EsiAllowlist::new(["/"]);
+        assert!(everything.allows("/alliances/"));
+        assert_eq!(EsiAllowlist::new([" wars "]).prefixes(), ["/wars/"]);
+    }
+
+    #[test]
+    fn test_path_normalization() {
+        assert_eq!(normalize_path("/markets/prices").unwrap(), "/markets/prices/");
+        assert_eq!(normalize_path(" /status/ ").unwrap(), "/status/");
+        assert!(normalize_path("markets/prices/").is_err());
+        assert!(normalize_path("/markets/../characters/1/").is_err());
+        assert!(normalize_path("/markets/prices/?token=x").is_err());
+        assert!(normalize_path("//evil.example/").is_err());
+        assert!(normalize_path("/markets/%2e%2e/").is_err());
+    }
+
+    #[test]
+    fn test_query_parameters() {
+        assert_eq!(with_query("/markets/10000002/orders/", &[]).unwrap(), "/markets/10000002/orders/");
+        assert_eq!(
+            with_query("/markets/10000002/orders/", &[("type_id", "34"), ("order_type", "sell")]).unwrap(),
+            "/markets/10000002/orders/?order_type=sell&type_id=34"
+        );
+        assert_eq!(
+            with_query("/universe/ids/", &[("name", "Jita & Amarr")]).unwrap(),
+            "/universe/ids/?name=Jita+%26+Amarr"
+        );
+        let error = with_query("/status/", &[("Token", "abc")]).unwrap_err();
+        assert!(matches!(error, TraderGraderError::InvalidArgument { ref field, .. } if field == "params.Token"));
+    }
+
+    #[tokio::test]
+    async fn test_disallowed_route_is_rejected_before_any_request() {
+        let client = MarketClient::without_cache().with_esi_allowlist(EsiAllowlist::new(["/status/"]));
+        let error = client.esi_get("/markets/prices/", &[]).await.unwrap_err();
+        assert!(error.to_string().contains("is not allowed"));
+        assert!(error.is_invalid_request());
+    }
+}
@@ -0,0 +1,347 @@
+//! PLEX and skill injector dashboard for TraderGrader
+//!
+//! PLEX, skill injectors and skill extractors are one market: a Skill
+//! Extractor pulls 500,000 skill points out of a character into a Large Skill
+//! Injector, so whether extracting pays depends on the gap between the two
+//! prices, and what an injector is worth to a buyer depends on how many skill
+//! points they already have, since injectors give less past 5M, 50M and 80M.
+//! The dashboard prices all four items in one region and works out both
+//! sides, after the sales tax and broker fee of the caller's trading profile.
+
+use crate::error::Result;
+use crate::fees::TradingProfile;
+use crate::market::MarketClient;
+use crate::orderbook::MarketOrderBook;
+use crate::scan::SCAN_CONCURRENCY;
+use crate::types::{InjectionBracket, MarketHistory, PlexDashboard, PlexMarketItem, SkillExtraction};
+use futures::stream::{self, StreamExt};
+
+/// PLEX
+pub const PLEX_TYPE_ID: i32 = 44992;
+
+/// Skill Extractor
+pub const SKILL_EXTRACTOR_TYPE_ID: i32 = 40519;
+
+/// Large Skill Injector
+pub const LARGE_SKILL_INJECTOR_TYPE_ID: i32 = 40520;
+
+/// Small Skill Injector
+pub const SMALL_SKILL_INJECTOR_TYPE_ID: i32 = 45635;
+
+/// Skill points one extractor pulls out, making one Large Skill Injector
+pub const SP_PER_EXTRACTION: i64 = 500_000;
+
+/// Days of history the dashboard averages unless asked otherwise
+pub const DEFAULT_DASHBOARD_DAYS: usize = 30;
+
+/// Most days of history the dashboard averages
+pub const MAX_DASHBOARD_DAYS: usize = 365;
+
+/// Skill points a Large Skill Injector gives a character below each total (none for the top bracket)
+///
+/// A Small Skill Injector gives a fifth as many.
+pub const INJECTION_BRACKETS: [(Option<i64>, i64); 4] = [
+    (Some(5_000_000), 500_000),
+    (Some(50_000_000), 400_000),
+    (Some(80_000_000), 300_000),
+    (None, 150_000),
+];
+
+/// Items on the dashboard, in the order they are listed
+const DASHBOARD_TYPE_IDS: [i32; 4] = [
+    PLEX_TYPE_ID,
+    LARGE_SKILL_INJECTOR_TYPE_ID,
+    SMALL_SKILL_INJECTOR_TYPE_ID,
+    SKILL_EXTRACTOR_TYPE_ID,
+];
+
+impl PlexMarketItem {
+    /// Prices an item from its order book and the last `days` days of trading in its history
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::{MarketHistory, PlexMarketItem};
+    /// use tradergrader::orderbook::MarketOrderBook;
+    ///
+    /// let history: Vec<MarketHistory> = (1..=10)
+    ///     .map(|day| MarketHistory {
+    ///         average: 5_000_000.0 + day as f64 * 10_000.0,
+    ///         date: format!("2025-06-{day:02}"),
+    ///         highest: 5_200_000.0,
+    ///         lowest: 4_900_000.0,
+    ///         order_count: 1_000,
+    ///         volume: 50_000,
+    ///         extra: Default::default(),
+    ///     })
+    ///     .collect();
+    ///
+    /// let item = PlexMarketItem::from_market(44992, "PLEX (44992)".to_string(), &MarketOrderBook::new(Vec::new()), &history, 5);
+    /// assert_eq!(item.avg_daily_volume, 50_000.0);
+    /// assert_eq!(item.average_price, Some(5_080_000.0));
+    /// assert!(item.change_percent.unwrap() > 0.0);
+    /// assert_eq!(item.best_sell, None);
+    /// ```
+    pub fn from_market(
+        type_id: i32,
+        type_label: String,
+        book: &MarketOrderBook,
+        history: &[MarketHistory],
+        days: usize,
+    ) -> Self {
+        let mut history: Vec<&MarketHistory> = history.iter().collect();
+        history.sort_by(|a, b| a.date.cmp(&b.date));
+        let window = &history[history.len().saturating_sub(days.max(1))..];
+
+        let volume: i64 = window.iter().map(|h| h.volume).sum();
+        let turnover: f64 = window.iter().map(|h| h.average * h.volume as f64).sum();
+        let change_percent = match (window.first(), window.last()) {
+            (Some(first), Some(last)) if window.len() > 1 && first.average > 0.0 => {
+                Some((last.average / first.average - 1.0) * 100.0)
+            }
+            _ => None,
+        };
+
+        Self {
+            type_id,
+            type_label,
+            best_buy: book.best_bid(),
+            best_sell: book.best_ask(),
+            average_price: (volume > 0).then(|| turnover / volume as f64),
+            change_percent,
+            avg_daily_volume: if window.is_empty() { 0.0 } else { volume as f64 / window.len() as f64 },
+        }
+    }
+}
+
+impl PlexDashboard {
+    /// Works out extraction profit and injection costs from the items' prices
+    ///
+    /// The extractor is bought from the lowest sell order. The injector is
+    /// either sold instantly to the highest buy order, paying sales tax, or
+    /// listed at the lowest sell order, also paying the broker fee.
+    pub fn new(region_id: i32, days: usize, items: Vec<PlexMarketItem>, trading: &TradingProfile) -> Self {
+        let item = |type_id: i32| items.iter().find(|i| i.type_id == type_id);
+        let plex = item(PLEX_TYPE_ID);
+        let large = item(LARGE_SKILL_INJECTOR_TYPE_ID);
+        let small = item(SMALL_SKILL_INJECTOR_TYPE_ID);
+
+        let fees = trading.current_fees();
+        let skills = &trading.skills;
+        let extractor_cost = item(SKILL_EXTRACTOR_TYPE_ID).and_then(|i| i.best_sell);
+        let instant_sale = large
+            .and_then(|i| i.best_buy)
+            .map(|price| price - fees.sales_tax(price, skills));
+        let listed_sale = large
+            .and_then(|i| i.best_sell)
+            .map(|price| price - fees.sales_tax(price, skills) - fees.broker_fee(price, skills));
+        let extraction = SkillExtraction {
+            extractor_cost,
+            instant_profit: instant_sale.zip(extractor_cost).map(|(sale, cost)| sale - cost),
+            listed_profit: listed_sale.zip(extractor_cost).map(|(sale, cost)| sale - cost),
+        };
+
+        let injection = INJECTION_BRACKETS
+            .iter()
+            .map(|&(below_sp, large_sp)| InjectionBracket {
+                below_sp,
+                large_injector_sp: large_sp,
+                large_isk_per_sp: large.and_then(|i| i.best_sell).map(|price| price / large_sp as f64),
+                small_isk_per_sp: small.and_then(|i| i.best_sell).map(|price| price / (large_sp / 5) as f64),
+            })
+            .collect();
+
+        let injector_in_plex = large
+            .and_then(|i| i.best_sell)
+            .zip(plex.and_then(|i| i.best_sell))
+            .filter(|&(_, plex)| plex > 0.0)
+            .map(|(injector, plex)| injector / plex);
+
+        Self {
+            region_id,
+            days,
+            items,
+            injector_in_plex,
+            extraction,
+            injection,
+        }
+    }
+}
+
+impl MarketClient {
+    /// Prices PLEX, skill injectors and extractors in one region, with extraction profit and injection costs
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # use tradergrader::fees::TradingProfile;
+    /// # use tradergrader::plex::DEFAULT_DASHBOARD_DAYS;
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// let dashboard = client.plex_dashboard(10000002, DEFAULT_DASHBOARD_DAYS, &TradingProfile::default()).await?;
+    /// println!("Extraction profit: {:?} ISK", dashboard.extraction.listed_profit);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn plex_dashboard(&self, region_id: i32, days: usize, trading: &TradingProfile) -> Result<PlexDashboard> {
+        let days = days.clamp(1, MAX_DASHBOARD_DAYS);
+        let items = stream::iter(DASHBOARD_TYPE_IDS)
+            .map(|type_id| async move {
+                let book = self.fetch_order_book(region_id, Some(type_id)).await?;
+                let history = self.fetch_market_history(region_id, type_id).await?;
+                Ok(PlexMarketItem::from_market(
+                    type_id,
+                    self.type_label(type_id).await,
+                    &book,
+                    &history,
+                    days,
+                ))
+            })
+            .buffered(SCAN_CONCURRENCY)
+            .collect::<Vec<Result<PlexMarketItem>>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(PlexDashboard::new(region_id, days, items, trading))
+    }
+}
+
+/// Formats the dashboard as an item table followed by extraction and injection
+pub(crate) fn format_plex_dashboard(dashboard: &PlexDashboard) -> String {
+    let isk = |value: Option<f64>| value.map_or_else(|| "-".to_string(), |v| format!("{v:.2}"));
+    let mut report = format!(
+        "PLEX & Skill Injector Dashboard for Region {} (last {} days of trading):\n\n\
+         | Item | Best buy | Best sell | Average | Change | Daily volume |\n\
+         |---|---|---|---|---|---|\n",
+        dashboard.region_id, dashboard.days
+    );
+    for item in &dashboard.items {
+        report.push_str(&format!(
+            "| {} | {} | {} | {} | {} | {:.0} |\n",
+            item.type_label,
+            isk(item.best_buy),
+            isk(item.best_sell),
+            isk(item.average_price),
+            item.change_percent.map_or_else(|| "-".to_string(), |change| format!("{change:+.1}%")),
+            item.avg_daily_volume
+        ));
+    }
+    if let Some(ratio) = dashboard.injector_in_plex {
+        report.push_str(&format!("\nA Large Skill Injector sells for {ratio:.1} PLEX.\n"));
+    }
+
+    let extraction = &dashboard.extraction;
+    report.push_str(&format!(
+        "\nExtracting {SP_PER_EXTRACTION} SP into a Large Skill Injector:\n\
+         Skill Extractor: {} ISK at the lowest sell order\n\
+         Sold instantly: {} ISK profit after sales tax\n\
+         Listed: {} ISK profit after sales tax and broker fee\n",
+        isk(extraction.extractor_cost),
+        isk(extraction.instant_profit),
+        isk(extraction.listed_profit)
+    ));
+    if extraction.listed_profit.is_some_and(|profit| profit <= 0.0) {
+        report.push_str("Extracting doesn't pay at these prices.\n");
+    }
+
+    report.push_str(
+        "\nInjecting, ISK per skill point at the lowest sell orders:\n\n\
+         | Character SP | SP per large | Large injector | Small injector |\n\
+         |---|---|---|---|\n",
+    );
+    let per_sp = |value: Option<f64>| value.map_or_else(|| "-".to_string(), |v| format!("{v:.0} ISK/SP"));
+    let millions = |sp: i64| format!("{}M", sp / 1_000_000);
+    let mut floor = None;
+    for bracket in &dashboard.injection {
+        let range = match (floor, bracket.below_sp) {
+            (None, Some(below)) => format!("Under {}", millions(below)),
+            (Some(from), Some(below)) => format!("{} to {}", millions(from), millions(below)),
+            (Some(from), None) => format!("{} and up", millions(from)),
+            (None, None) => "Any".to_string(),
+        };
+        report.push_str(&format!(
+            "| {range} | {} | {} | {} |\n",
+            bracket.large_injector_sp,
+            per_sp(bracket.large_isk_per_sp),
+            per_sp(bracket.small_isk_per_sp)
+        ));
+        floor = bracket.below_sp;
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fees::TradingSkills;
+
+    fn trading() -> TradingProfile {
+        TradingProfile {
+            skills: TradingSkills::max_skills(),
+            ..TradingProfile::default()
+        }
+    }
+
+    fn item(type_id: i32, best_buy: f64, best_sell: f64) -> PlexMarketItem {
+        PlexMarketItem {
+            type_id,
+            type_label: format!("Type {type_id}"),
+            best_buy: Some(best_buy),
+            best_sell: Some(best_sell),
+            average_price: Some(best_sell),
+            change_percent: Some(1.5),
+            avg_daily_volume: 1_000.0,
+        }
+    }
+
+    fn dashboard() -> PlexDashboard {
+        let items = vec![
+            item(PLEX_TYPE_ID, 4_900_000.0, 5_000_000.0),
+            item(LARGE_SKILL_INJECTOR_TYPE_ID, 790_000_000.0, 800_000_000.0),
+            item(SMALL_SKILL_INJECTOR_TYPE_ID, 150_000_000.0, 170_000_000.0),
+            item(SKILL_EXTRACTOR_TYPE_ID, 380_000_000.0, 400_000_000.0),
+        ];
+        PlexDashboard::new(10000002, 30, items, &trading())
+    }
+
+    #[test]
+    fn test_extraction_and_injection() {
+        let dashboard = dashboard();
+        let fees = trading().current_fees();
+        let skills = TradingSkills::max_skills();
+
+        let instant = 790_000_000.0 - fees.sales_tax(790_000_000.0, &skills) - 400_000_000.0;
+        assert_eq!(dashboard.extraction.instant_profit, Some(instant));
+        let listed = 800_000_000.0
+            - fees.sales_tax(800_000_000.0, &skills)
+            - fees.broker_fee(800_000_000.0, &skills)
+            - 400_000_000.0;
+        assert_eq!(dashboard.extraction.listed_profit, Some(listed));
+        assert_eq!(dashboard.injector_in_plex, Some(160.0));
+
+        assert_eq!(dashboard.injection.len(), 4);
+        assert_eq!(dashboard.injection[0].large_isk_per_sp, Some(1_600.0));
+        assert_eq!(dashboard.injection[0].small_isk_per_sp, Some(1_700.0));
+        assert_eq!(dashboard.injection[3].below_sp, None);
+        assert_eq!(dashboard.injection[3].large_isk_per_sp, Some(800_000_000.0 / 150_000.0));
+
+        // Without a market for the extractor there is nothing to work out
+        let unpriced = PlexDashboard::new(10000002, 30, vec![item(PLEX_TYPE_ID, 1.0, 2.0)], &TradingProfile::default());
+        assert_eq!(unpriced.extraction.listed_profit, None);
+        assert_eq!(unpriced.injection[0].large_isk_per_sp, None);
+    }
+
+    #[test]
+    fn test_format() {
+        let report = format_plex_dashboard(&dashboard());
+        assert!(report.contains("| Type 44992 | 4900000.00 | 5000000.00 | 5000000.00 | +1.5% | 1000 |"));
+        assert!(report.contains("A Large Skill Injector sells for 160.0 PLEX."));
+        assert!(report.contains("Skill Extractor: 400000000.00 ISK"));
+        assert!(report.contains("| Under 5M | 500000 | 1600 ISK/SP | 1700 ISK/SP |"));
+        assert!(report.contains("| 50M to 80M | 300000 |"));
+        assert!(report.contains("| 80M and up | 150000 |"));
+        assert!(!report.contains("doesn't pay"));
+    }
+}
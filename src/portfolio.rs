@@ -0,0 +1,373 @@
+//! Portfolio tracking for TraderGrader
+//!
+//! Traders sitting on stock want to know what it's worth today and whether
+//! they're up or down on it. A [`PortfolioStore`] keeps positions (item,
+//! quantity and total cost) in a JSON file, and [`MarketClient::value_portfolio`]
+//! prices them against a region's current orders: at the best sell for market
+//! value and unrealized P&L, and at the best buy for what dumping everything
+//! right now would fetch.
+
+use crate::error::{Result, TraderGraderError};
+use crate::market::MarketClient;
+use crate::scan::SCAN_CONCURRENCY;
+use crate::types::{PortfolioPosition, PortfolioValuation, PositionValuation};
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Most distinct items a portfolio may hold
+pub const MAX_PORTFOLIO_POSITIONS: usize = 200;
+
+/// The server's portfolio: one position per item, optionally saved to a JSON file
+///
+/// Every change is written straight to the file, so positions survive restarts.
+#[derive(Debug, Default)]
+pub struct PortfolioStore {
+    path: Option<PathBuf>,
+    positions: Mutex<BTreeMap<i32, PortfolioPosition>>,
+}
+
+/// File layout of a saved portfolio
+#[derive(Deserialize, Serialize)]
+struct SavedPortfolio {
+    positions: Vec<PortfolioPosition>,
+}
+
+impl PortfolioStore {
+    /// A portfolio that lives only as long as the process
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Opens the portfolio saved at `path`, starting empty if the file doesn't exist yet
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let positions = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice::<SavedPortfolio>(&bytes)?
+                .positions
+                .into_iter()
+                .map(|position| (position.type_id, position))
+                .collect(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => {
+                return Err(TraderGraderError::InternalError(format!(
+                    "Failed to read portfolio {}: {e}",
+                    path.display()
+                )))
+            }
+        };
+        Ok(Self {
+            path: Some(path),
+            positions: Mutex::new(positions),
+        })
+    }
+
+    /// Where the portfolio is saved, if anywhere
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Every position, in type ID order
+    pub fn positions(&self) -> Vec<PortfolioPosition> {
+        self.positions.lock().unwrap_or_else(|e| e.into_inner()).values().cloned().collect()
+    }
+
+    /// Adds bought units to an item's position, returning the position after the purchase
+    ///
+    /// Units bought at different prices are pooled at their average cost.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::portfolio::PortfolioStore;
+    ///
+    /// let store = PortfolioStore::in_memory();
+    /// store.add(34, 1_000, 5.0)?;
+    /// let position = store.add(34, 1_000, 7.0)?;
+    /// assert_eq!(position.quantity, 2_000);
+    /// assert_eq!(position.average_cost(), 6.0);
+    ///
+    /// // Selling half keeps the average cost
+    /// let left = store.remove(34, Some(1_000))?.unwrap();
+    /// assert_eq!(left.cost_basis, 6_000.0);
+    /// # Ok::<(), tradergrader::TraderGraderError>(())
+    /// ```
+    pub fn add(&self, type_id: i32, quantity: i64, unit_cost: f64) -> Result<PortfolioPosition> {
+        if type_id <= 0 {
+            return Err(TraderGraderError::InvalidTypeId { type_id });
+        }
+        if quantity <= 0 {
+            return Err(TraderGraderError::InvalidArgument {
+                field: "quantity".to_string(),
+                reason: "must be positive".to_string(),
+            });
+        }
+        if !unit_cost.is_finite() || unit_cost < 0.0 {
+            return Err(TraderGraderError::InvalidArgument {
+                field: "unit_cost".to_string(),
+                reason: "must be zero or more".to_string(),
+            });
+        }
+        let mut positions = self.positions.lock().unwrap_or_else(|e| e.into_inner());
+        let mut updated = positions.clone();
+        let position = updated.entry(type_id).or_insert(PortfolioPosition {
+            type_id,
+            quantity: 0,
+            cost_basis: 0.0,
+        });
+        position.quantity = position.quantity.saturating_add(quantity);
+        position.cost_basis += quantity as f64 * unit_cost;
+        let position = position.clone();
+        if updated.len() > MAX_PORTFOLIO_POSITIONS {
+            return Err(TraderGraderError::InvalidParams(format!(
+                "The portfolio can hold at most {MAX_PORTFOLIO_POSITIONS} items"
+            )));
+        }
+        self.save(&updated)?;
+        *positions = updated;
+        Ok(position)
+    }
+
+    /// Takes units out of an item's position, or the whole position when `quantity` is `None`
+    ///
+    /// Returns what is left of the position, or `None` once it's gone. The
+    /// cost basis shrinks in proportion, keeping the average cost.
+    pub fn remove(&self, type_id: i32, quantity: Option<i64>) -> Result<Option<PortfolioPosition>> {
+        let mut positions = self.positions.lock().unwrap_or_else(|e| e.into_inner());
+        let mut updated = positions.clone();
+        let Some(position) = updated.get_mut(&type_id) else {
+            return Err(TraderGraderError::InvalidParams(format!("Type {type_id} is not in the portfolio")));
+        };
+        let quantity = quantity.unwrap_or(position.quantity);
+        if quantity <= 0 || quantity > position.quantity {
+            return Err(TraderGraderError::InvalidArgument {
+                field: "quantity".to_string(),
+                reason: format!("must be between 1 and the {} units held", position.quantity),
+            });
+        }
+        let average_cost = position.average_cost();
+        position.quantity -= quantity;
+        position.cost_basis = position.quantity as f64 * average_cost;
+        let left = (position.quantity > 0).then(|| position.clone());
+        if left.is_none() {
+            updated.remove(&type_id);
+        }
+        self.save(&updated)?;
+        *positions = updated;
+        Ok(left)
+    }
+
+    /// Writes the portfolio to a temporary file and renames it over the old one
+    fn save(&self, positions: &BTreeMap<i32, PortfolioPosition>) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let saved = SavedPortfolio {
+            positions: positions.values().cloned().collect(),
+        };
+        let write_error = |e: std::io::Error| {
+            TraderGraderError::InternalError(format!("Failed to save portfolio {}: {e}", path.display()))
+        };
+        let temp = path.with_extension("json.tmp");
+        std::fs::write(&temp, serde_json::to_vec_pretty(&saved)?).map_err(write_error)?;
+        std::fs::rename(&temp, path).map_err(write_error)
+    }
+}
+
+impl PositionValuation {
+    /// Prices a position at the given best buy and sell
+    pub fn new(position: PortfolioPosition, type_label: String, best_buy: Option<f64>, best_sell: Option<f64>) -> Self {
+        let units = position.quantity as f64;
+        let market_value = best_sell.map(|price| price * units);
+        let unrealized_pnl = market_value.map(|value| value - position.cost_basis);
+        let unrealized_pnl_percent = unrealized_pnl
+            .filter(|_| position.cost_basis > 0.0)
+            .map(|pnl| pnl / position.cost_basis * 100.0);
+        Self {
+            type_label,
+            best_buy,
+            best_sell,
+            market_value,
+            liquidation_value: best_buy.map(|price| price * units),
+            unrealized_pnl,
+            unrealized_pnl_percent,
+            error: None,
+            position,
+        }
+    }
+}
+
+impl PortfolioValuation {
+    /// Totals valued positions
+    pub fn new(region_id: i32, positions: Vec<PositionValuation>) -> Self {
+        Self {
+            region_id,
+            total_cost_basis: positions.iter().map(|p| p.position.cost_basis).sum(),
+            total_market_value: positions.iter().filter_map(|p| p.market_value).sum(),
+            total_liquidation_value: positions.iter().filter_map(|p| p.liquidation_value).sum(),
+            total_unrealized_pnl: positions.iter().filter_map(|p| p.unrealized_pnl).sum(),
+            positions,
+        }
+    }
+}
+
+impl MarketClient {
+    /// Values positions against a region's current best orders
+    ///
+    /// Order books are fetched concurrently; a position whose book fails to
+    /// fetch is reported with its error and left out of the totals.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, PortfolioPosition, Result};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// let positions = vec![PortfolioPosition { type_id: 44992, quantity: 500, cost_basis: 2_500_000_000.0 }];
+    /// let valuation = client.value_portfolio(10000002, positions).await?;
+    /// println!("Up {:.0} ISK", valuation.total_unrealized_pnl);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn value_portfolio(
+        &self,
+        region_id: i32,
+        positions: Vec<PortfolioPosition>,
+    ) -> Result<PortfolioValuation> {
+        let valued = stream::iter(positions)
+            .map(|position| async move {
+                let type_label = self.type_label(position.type_id).await;
+                match self.fetch_order_book(region_id, Some(position.type_id)).await {
+                    Ok(book) => PositionValuation::new(position, type_label, book.best_bid(), book.best_ask()),
+                    Err(e) => PositionValuation {
+                        error: Some(e.to_string()),
+                        ..PositionValuation::new(position, type_label, None, None)
+                    },
+                }
+            })
+            .buffered(SCAN_CONCURRENCY)
+            .collect()
+            .await;
+        Ok(PortfolioValuation::new(region_id, valued))
+    }
+}
+
+/// Formats a valuation as a Markdown table with totals
+pub(crate) fn format_portfolio_valuation(valuation: &PortfolioValuation) -> String {
+    if valuation.positions.is_empty() {
+        return "The portfolio is empty. Add positions with portfolio_add.".to_string();
+    }
+    let isk = |value: Option<f64>| value.map_or_else(|| "-".to_string(), |v| format!("{v:.2}"));
+    let mut report = format!(
+        "Portfolio Valuation in Region {} ({} positions):\n\n\
+         | Item | Quantity | Avg cost | Best sell | Market value | Unrealized P&L | Liquidation value |\n\
+         |---|---|---|---|---|---|---|\n",
+        valuation.region_id,
+        valuation.positions.len()
+    );
+    for p in &valuation.positions {
+        let pnl = match (p.unrealized_pnl, p.unrealized_pnl_percent) {
+            (Some(pnl), Some(percent)) => format!("{pnl:+.2} ({percent:+.1}%)"),
+            (Some(pnl), None) => format!("{pnl:+.2}"),
+            _ => "-".to_string(),
+        };
+        report.push_str(&format!(
+            "| {} | {} | {:.2} | {} | {} | {pnl} | {} |\n",
+            p.type_label,
+            p.position.quantity,
+            p.position.average_cost(),
+            isk(p.best_sell),
+            isk(p.market_value),
+            isk(p.liquidation_value)
+        ));
+    }
+
+    report.push_str(&format!(
+        "\nCost basis: {:.2} ISK\nMarket value (best sell): {:.2} ISK\nUnrealized P&L: {:+.2} ISK\n\
+         Liquidation value (best buy): {:.2} ISK\n",
+        valuation.total_cost_basis,
+        valuation.total_market_value,
+        valuation.total_unrealized_pnl,
+        valuation.total_liquidation_value
+    ));
+    let unpriced: Vec<String> = valuation
+        .positions
+        .iter()
+        .filter(|p| p.market_value.is_none())
+        .map(|p| match &p.error {
+            Some(e) => format!("{} ({e})", p.type_label),
+            None => format!("{} (no sell orders)", p.type_label),
+        })
+        .collect();
+    if !unpriced.is_empty() {
+        report.push_str(&format!("Not in market value or P&L: {}\n", unpriced.join(", ")));
+    }
+    report.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_pools_costs_and_persists() {
+        let path = std::env::temp_dir().join(format!("tradergrader-portfolio-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let store = PortfolioStore::open(&path).unwrap();
+        store.add(34, 100, 4.0).unwrap();
+        store.add(34, 300, 8.0).unwrap();
+        store.add(44992, 2, 5_000_000.0).unwrap();
+        assert!(store.add(35, 0, 1.0).is_err());
+        assert!(store.add(35, 10, -1.0).is_err());
+        assert!(store.remove(36, None).is_err());
+        assert!(store.remove(34, Some(401)).is_err());
+
+        let left = store.remove(34, Some(200)).unwrap().unwrap();
+        assert_eq!(left.quantity, 200);
+        assert_eq!(left.cost_basis, 1_400.0);
+        assert_eq!(store.remove(44992, None).unwrap(), None);
+
+        let reopened = PortfolioStore::open(&path).unwrap();
+        assert_eq!(reopened.positions(), vec![left]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_valuation_and_report() {
+        let tritanium = PortfolioPosition {
+            type_id: 34,
+            quantity: 1_000,
+            cost_basis: 4_000.0,
+        };
+        let plex = PortfolioPosition {
+            type_id: 44992,
+            quantity: 10,
+            cost_basis: 50_000_000.0,
+        };
+        let valuation = PortfolioValuation::new(
+            10000002,
+            vec![
+                PositionValuation::new(tritanium, "Tritanium (34)".to_string(), Some(4.5), Some(5.0)),
+                PositionValuation {
+                    error: Some("ESI unavailable".to_string()),
+                    ..PositionValuation::new(plex, "PLEX (44992)".to_string(), None, None)
+                },
+            ],
+        );
+        let first = &valuation.positions[0];
+        assert_eq!(first.market_value, Some(5_000.0));
+        assert_eq!(first.unrealized_pnl, Some(1_000.0));
+        assert_eq!(first.unrealized_pnl_percent, Some(25.0));
+        assert_eq!(valuation.total_cost_basis, 50_004_000.0);
+        assert_eq!(valuation.total_market_value, 5_000.0);
+        assert_eq!(valuation.total_liquidation_value, 4_500.0);
+        assert_eq!(valuation.total_unrealized_pnl, 1_000.0);
+
+        let report = format_portfolio_valuation(&valuation);
+        assert!(report.contains("| Tritanium (34) | 1000 | 4.00 | 5.00 | 5000.00 | +1000.00 (+25.0%) | 4500.00 |"));
+        assert!(report.contains("Not in market value or P&L: PLEX (44992) (ESI unavailable)"));
+    }
+}
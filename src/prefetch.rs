@@ -0,0 +1,347 @@
+//! Cache warming for TraderGrader
+//!
+//! Interactive tool calls are slow whenever they land on an expired cache entry
+//! and have to wait for ESI. A [`Prefetcher`] keeps a watchlist of region and
+//! item pairs warm: it runs in the background, sleeps until the first cached
+//! orders or history for a target are about to expire, and refreshes them from
+//! ESI just before they do, so tool calls for those items are served from cache.
+//!
+//! Refreshes go through the client's rate limiter and request coalescing like
+//! any other fetch, and a failed refresh is retried on the next wake-up.
+
+use crate::cache::{track_stale_reads, CacheKey};
+use crate::error::{Result, TraderGraderError};
+use crate::market::MarketClient;
+use crate::types::{MarketHistory, MarketOrder, Watchlist};
+use futures::stream::{self, StreamExt};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tokio::task::JoinHandle;
+
+/// Most region/item pairs one prefetcher may keep warm
+pub const MAX_PREFETCH_TARGETS: usize = 200;
+
+/// Default time before expiry at which an entry is refreshed
+pub const DEFAULT_PREFETCH_LEAD_TIME: Duration = Duration::from_secs(10);
+
+/// Default longest sleep between checks, so new data and failures are picked up
+pub const DEFAULT_PREFETCH_MAX_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Shortest sleep between checks, so a failing target can't spin the loop
+const MIN_PREFETCH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Targets refreshed at once; the rate limiter still applies to each request
+const PREFETCH_CONCURRENCY: usize = 4;
+
+/// One region and item pair to keep warm
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PrefetchTarget {
+    pub region_id: i32,
+    pub type_id: i32,
+}
+
+impl PrefetchTarget {
+    /// Create a target for an item in a region
+    pub fn new(region_id: i32, type_id: i32) -> Self {
+        Self { region_id, type_id }
+    }
+}
+
+impl FromStr for PrefetchTarget {
+    type Err = TraderGraderError;
+
+    /// Parses `region_id:type_id`, e.g. `10000002:34`
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || TraderGraderError::InvalidArgument {
+            field: "prefetch target".to_string(),
+            reason: format!("expected region_id:type_id, got '{s}'"),
+        };
+        let (region, item) = s.trim().split_once(':').ok_or_else(invalid)?;
+        Ok(Self {
+            region_id: region.trim().parse().map_err(|_| invalid())?,
+            type_id: item.trim().parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// What the prefetcher keeps warm and how early it refreshes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrefetchConfig {
+    /// Region and item pairs to keep warm
+    pub targets: Vec<PrefetchTarget>,
+    /// Refresh market history as well as orders
+    pub include_history: bool,
+    /// How long before expiry an entry is refreshed
+    pub lead_time: Duration,
+    /// Longest sleep between checks
+    pub max_interval: Duration,
+}
+
+impl Default for PrefetchConfig {
+    fn default() -> Self {
+        Self {
+            targets: Vec::new(),
+            include_history: true,
+            lead_time: DEFAULT_PREFETCH_LEAD_TIME,
+            max_interval: DEFAULT_PREFETCH_MAX_INTERVAL,
+        }
+    }
+}
+
+impl PrefetchConfig {
+    /// Keep orders and history for `targets` warm with the default timings
+    pub fn new(targets: Vec<PrefetchTarget>) -> Self {
+        Self {
+            targets,
+            ..Self::default()
+        }
+    }
+
+    /// Keep a watchlist warm, in its own region or `default_region` when it has none
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::prefetch::PrefetchConfig;
+    /// use tradergrader::Watchlist;
+    ///
+    /// let watchlist = Watchlist::new("Minerals", None, vec![34, 35, 36])?;
+    /// let config = PrefetchConfig::from_watchlist(&watchlist, Some(10000002))?;
+    /// assert_eq!(config.targets.len(), 3);
+    /// # Ok::<(), tradergrader::TraderGraderError>(())
+    /// ```
+    pub fn from_watchlist(watchlist: &Watchlist, default_region: Option<i32>) -> Result<Self> {
+        let region_id = watchlist
+            .region_id
+            .or(default_region)
+            .ok_or_else(|| TraderGraderError::InvalidArgument {
+                field: "region_id".to_string(),
+                reason: format!("watchlist '{}' has no region, so one must be given", watchlist.name),
+            })?;
+        Ok(Self::new(
+            watchlist.type_ids.iter().map(|&type_id| PrefetchTarget::new(region_id, type_id)).collect(),
+        ))
+    }
+
+    /// Sets how long before expiry entries are refreshed
+    pub fn with_lead_time(mut self, lead_time: Duration) -> Self {
+        self.lead_time = lead_time;
+        self
+    }
+
+    /// Sets whether market history is refreshed as well as orders
+    pub fn with_history(mut self, include_history: bool) -> Self {
+        self.include_history = include_history;
+        self
+    }
+
+    /// Checks the target list is non-empty and within [`MAX_PREFETCH_TARGETS`]
+    pub fn validate(&self) -> Result<()> {
+        if self.targets.is_empty() {
+            return Err(TraderGraderError::InvalidArgument {
+                field: "targets".to_string(),
+                reason: "at least one region/item pair is needed".to_string(),
+            });
+        }
+        if self.targets.len() > MAX_PREFETCH_TARGETS {
+            return Err(TraderGraderError::InvalidArgument {
+                field: "targets".to_string(),
+                reason: format!("at most {MAX_PREFETCH_TARGETS} region/item pairs can be prefetched"),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Parses a comma-separated `region_id:type_id` list, as in `TRADERGRADER_PREFETCH`
+pub fn parse_prefetch_targets(value: &str) -> Result<Vec<PrefetchTarget>> {
+    value.split(',').filter(|s| !s.trim().is_empty()).map(str::parse).collect()
+}
+
+/// Counters describing what a prefetcher has done
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PrefetchStats {
+    /// Times the prefetcher woke up and checked its targets
+    pub checks: u64,
+    /// Cache entries refreshed from ESI
+    pub refreshes: u64,
+    /// Refreshes that failed, including ones answered from stale cache
+    pub failures: u64,
+    /// Most recent refresh error
+    pub last_error: Option<String>,
+    /// When the last check finished
+    pub last_check: Option<SystemTime>,
+}
+
+/// A running background prefetch, stopped when dropped
+#[derive(Debug)]
+pub struct Prefetcher {
+    config: PrefetchConfig,
+    stats: Arc<Mutex<PrefetchStats>>,
+    task: JoinHandle<()>,
+}
+
+impl Prefetcher {
+    /// Starts keeping `config.targets` warm in `client`'s cache
+    ///
+    /// Fails when the target list is invalid or the client has no cache to warm.
+    /// Must be called from within a Tokio runtime.
+    pub fn start(client: Arc<MarketClient>, config: PrefetchConfig) -> Result<Self> {
+        config.validate()?;
+        if !client.has_cache() {
+            return Err(TraderGraderError::CacheError {
+                message: "Prefetching needs the cache enabled".to_string(),
+            });
+        }
+        let stats = Arc::new(Mutex::new(PrefetchStats::default()));
+        let task = tokio::spawn(run_prefetch(client, config.clone(), Arc::clone(&stats)));
+        Ok(Self { config, stats, task })
+    }
+
+    /// What this prefetcher keeps warm
+    pub fn config(&self) -> &PrefetchConfig {
+        &self.config
+    }
+
+    /// Snapshot of the prefetcher's counters
+    pub fn stats(&self) -> PrefetchStats {
+        self.stats.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Stops the background task and returns its final counters
+    pub fn stop(self) -> PrefetchStats {
+        self.task.abort();
+        self.stats()
+    }
+}
+
+impl Drop for Prefetcher {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn run_prefetch(client: Arc<MarketClient>, config: PrefetchConfig, stats: Arc<Mutex<PrefetchStats>>) {
+    loop {
+        let next_due = prefetch_once(&client, &config, &stats).await;
+        let sleep = next_due.clamp(MIN_PREFETCH_INTERVAL, config.max_interval.max(MIN_PREFETCH_INTERVAL));
+        tokio::time::sleep(sleep).await;
+    }
+}
+
+/// Refreshes every entry within the lead time of expiring, returning how long until the next one is
+async fn prefetch_once(client: &MarketClient, config: &PrefetchConfig, stats: &Mutex<PrefetchStats>) -> Duration {
+    let next_due = stream::iter(config.targets.iter().copied())
+        .map(|target| prefetch_target(client, config, target, stats))
+        .buffer_unordered(PREFETCH_CONCURRENCY)
+        .fold(config.max_interval, |next, due| async move { next.min(due) })
+        .await;
+
+    let mut stats = stats.lock().unwrap_or_else(|e| e.into_inner());
+    stats.checks += 1;
+    stats.last_check = Some(SystemTime::now());
+    next_due
+}
+
+/// Refreshes one target's entries if due, returning how long until they next are
+async fn prefetch_target(
+    client: &MarketClient,
+    config: &PrefetchConfig,
+    target: PrefetchTarget,
+    stats: &Mutex<PrefetchStats>,
+) -> Duration {
+    let PrefetchTarget { region_id, type_id } = target;
+    let orders_key = CacheKey::market_orders(region_id, Some(type_id));
+    let mut next_due = match client.esi().cached_ttl::<Vec<MarketOrder>>(&orders_key).await {
+        Some(ttl) if ttl > config.lead_time => ttl - config.lead_time,
+        _ => {
+            let refreshed = track_stale_reads(client.esi().refresh_market_orders(region_id, Some(type_id))).await;
+            record_refresh(stats, &orders_key, refreshed.0.map(|_| ()), refreshed.1.is_empty());
+            refreshed_due(client.esi().cached_ttl::<Vec<MarketOrder>>(&orders_key).await, config)
+        }
+    };
+
+    if config.include_history {
+        let history_key = CacheKey::market_history(region_id, type_id);
+        let history_due = match client.esi().cached_ttl::<Vec<MarketHistory>>(&history_key).await {
+            Some(ttl) if ttl > config.lead_time => ttl - config.lead_time,
+            _ => {
+                let refreshed = track_stale_reads(client.esi().refresh_market_history(region_id, type_id)).await;
+                record_refresh(stats, &history_key, refreshed.0.map(|_| ()), refreshed.1.is_empty());
+                refreshed_due(client.esi().cached_ttl::<Vec<MarketHistory>>(&history_key).await, config)
+            }
+        };
+        next_due = next_due.min(history_due);
+    }
+    next_due
+}
+
+/// When a just-refreshed entry is next due; entries ESI won't let us cache are retried at the longest interval
+fn refreshed_due(ttl: Option<Duration>, config: &PrefetchConfig) -> Duration {
+    match ttl {
+        Some(ttl) => ttl.saturating_sub(config.lead_time),
+        None => config.max_interval,
+    }
+}
+
+fn record_refresh(stats: &Mutex<PrefetchStats>, key: &CacheKey, result: Result<()>, fresh: bool) {
+    let mut stats = stats.lock().unwrap_or_else(|e| e.into_inner());
+    match result {
+        Ok(()) if fresh => stats.refreshes += 1,
+        Ok(()) => {
+            stats.failures += 1;
+            stats.last_error = Some(format!("{key}: ESI unavailable, stale cache kept"));
+        }
+        Err(e) => {
+            tracing::debug!("prefetch of {key} failed: {e}");
+            stats.failures += 1;
+            stats.last_error = Some(format!("{key}: {e}"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_targets() {
+        let targets = parse_prefetch_targets("10000002:34, 10000043:35,").unwrap();
+        assert_eq!(targets, vec![PrefetchTarget::new(10000002, 34), PrefetchTarget::new(10000043, 35)]);
+        assert!(parse_prefetch_targets("10000002").is_err());
+        assert!(parse_prefetch_targets("10000002:tritanium").is_err());
+    }
+
+    #[test]
+    fn test_config_validation() {
+        assert!(PrefetchConfig::default().validate().is_err());
+        let too_many = (0..=MAX_PREFETCH_TARGETS as i32).map(|id| PrefetchTarget::new(10000002, id)).collect();
+        assert!(PrefetchConfig::new(too_many).validate().is_err());
+
+        let watchlist = Watchlist::new("Minerals", None, vec![34, 35]).unwrap();
+        assert!(PrefetchConfig::from_watchlist(&watchlist, None).is_err());
+        let config = PrefetchConfig::from_watchlist(&watchlist, Some(10000002)).unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.targets[1], PrefetchTarget::new(10000002, 35));
+    }
+
+    #[tokio::test]
+    async fn test_start_requires_cache() {
+        use crate::cache::CacheConfig;
+        use crate::esi::EsiConfig;
+        use crate::rate_limit::RateLimitConfig;
+
+        let client = MarketClient::with_esi_config(
+            CacheConfig::disabled(),
+            RateLimitConfig::testing(),
+            EsiConfig::minimal(),
+        )
+        .unwrap();
+        let config = PrefetchConfig::new(vec![PrefetchTarget::new(10000002, 34)]);
+        assert!(matches!(
+            Prefetcher::start(Arc::new(client), config),
+            Err(TraderGraderError::CacheError { .. })
+        ));
+    }
+}
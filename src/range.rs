@@ -0,0 +1,216 @@
+//! Buy order range interpretation for TraderGrader
+//!
+//! A buy order only accepts items sold within its range of the station it was
+//! placed at: the same station, the same solar system, a number of stargate
+//! jumps, or anywhere in the region. The highest buy order in a region is often
+//! out of reach for a seller at a given station, so summaries and depth
+//! analysis "from a station" keep only the buy orders that station can fill.
+
+use crate::error::Result;
+use crate::hauling::RouteFlag;
+use crate::market::{format_order_book_depth, format_order_summary, MarketClient};
+use crate::orderbook::MarketOrderBook;
+use crate::scan::SCAN_CONCURRENCY;
+use crate::types::MarketOrder;
+use futures::stream::{self, StreamExt};
+use std::collections::{BTreeSet, HashMap};
+
+/// How far from its station a buy order accepts sales
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderRange {
+    /// Only at the order's own station
+    Station,
+    /// Anywhere in the order's solar system
+    SolarSystem,
+    /// Within this many stargate jumps of the order's system
+    Jumps(u8),
+    /// Anywhere in the order's region
+    Region,
+}
+
+impl OrderRange {
+    /// Whether a sale at `location_id` in `system_id` can fill `order`
+    ///
+    /// `jumps` is the stargate distance between the order's system and the
+    /// seller's; it is only consulted for jump ranges, and an unknown distance
+    /// counts as out of range. The seller is assumed to be in the order's region.
+    pub fn reaches(&self, order: &MarketOrder, location_id: i64, system_id: i32, jumps: Option<usize>) -> bool {
+        match self {
+            Self::Station => order.location_id == location_id,
+            Self::SolarSystem => order.system_id == system_id,
+            Self::Jumps(max) => order.system_id == system_id || jumps.is_some_and(|j| j <= *max as usize),
+            Self::Region => true,
+        }
+    }
+}
+
+impl std::str::FromStr for OrderRange {
+    type Err = String;
+
+    /// Parses ESI's `range` values: `station`, `solarsystem`, `region` or a jump count
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "station" => Ok(Self::Station),
+            "solarsystem" => Ok(Self::SolarSystem),
+            "region" => Ok(Self::Region),
+            jumps => jumps
+                .parse::<u8>()
+                .map(Self::Jumps)
+                .map_err(|_| format!("Unknown order range: {s}")),
+        }
+    }
+}
+
+impl MarketOrder {
+    /// The order's range; unrecognized values are treated as station-only
+    pub fn order_range(&self) -> OrderRange {
+        self.range.parse().unwrap_or(OrderRange::Station)
+    }
+}
+
+impl MarketClient {
+    /// Fetches an item's order book as seen by a seller at one station
+    ///
+    /// Sell orders are kept as they are; buy orders are kept only when their
+    /// range reaches the station. Jump distances come from ESI's route endpoint
+    /// and are cached, so repeated calls for the same station are cheap. Player
+    /// structures can't be looked up without authentication, so their system is
+    /// taken from orders placed there and the call fails when there are none.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// // Tritanium buy orders a seller in Jita 4-4 can actually fill
+    /// let book = client.station_order_book(10000002, 34, 60003760).await?;
+    /// println!("Best reachable buy: {:?}", book.best_bid());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn station_order_book(&self, region_id: i32, type_id: i32, station_id: i64) -> Result<MarketOrderBook> {
+        let book = self.fetch_order_book(region_id, Some(type_id)).await?;
+        let system_id = match book.at_location(station_id).next() {
+            Some(order) => order.system_id,
+            None if i32::try_from(station_id).is_ok() => self.fetch_station(station_id).await?.system_id,
+            None => return Err(format!("No orders at structure {station_id} to locate it").into()),
+        };
+
+        let jumps = self.jumps_to_buy_orders(&book, system_id).await;
+        let reachable = book
+            .buys()
+            .filter(|o| {
+                o.order_range()
+                    .reaches(o, station_id, system_id, jumps.get(&o.system_id).copied())
+            })
+            .chain(book.sells())
+            .cloned()
+            .collect();
+        Ok(MarketOrderBook::new(reachable))
+    }
+
+    /// Generates a market summary counting only buy orders reachable from a station
+    pub async fn get_station_market_summary(&self, region_id: i32, type_id: i32, station_id: i64) -> Result<String> {
+        let book = self.station_order_book(region_id, type_id, station_id).await?;
+        let locations = self.best_order_locations(&book).await;
+        let station = match self.resolve_location_names(&[station_id]).await.remove(&station_id) {
+            Some(name) => format!("{name} ({station_id})"),
+            None => format!("station {station_id}"),
+        };
+        Ok(format_order_summary(
+            &format!(
+                "Market Summary for {} in Region {region_id} (buy orders reachable from {station})",
+                self.type_label(type_id).await
+            ),
+            &book,
+            &locations,
+        ))
+    }
+
+    /// Generates an order book depth report counting only buy orders reachable from a station
+    pub async fn get_station_order_book_depth_summary(
+        &self,
+        region_id: i32,
+        type_id: i32,
+        station_id: i64,
+        target_price: Option<f64>,
+    ) -> Result<String> {
+        let book = self.station_order_book(region_id, type_id, station_id).await?;
+        let depth = Self::analyze_market_order_book(&book, target_price)?;
+        Ok(format_order_book_depth(
+            &format!(
+                "Order Book Depth for {} in Region {region_id} (buy orders reachable from station {station_id})",
+                self.type_label(type_id).await
+            ),
+            &depth,
+        ))
+    }
+
+    /// Jump distances from `system_id` to the systems of buy orders with jump ranges
+    ///
+    /// Systems whose route can't be fetched are left out, which makes their
+    /// orders count as out of range.
+    async fn jumps_to_buy_orders(&self, book: &MarketOrderBook, system_id: i32) -> HashMap<i32, usize> {
+        let systems: BTreeSet<i32> = book
+            .buys()
+            .filter(|o| o.system_id != system_id && matches!(o.order_range(), OrderRange::Jumps(_)))
+            .map(|o| o.system_id)
+            .collect();
+
+        stream::iter(systems)
+            .map(|order_system| async move {
+                let route = self.fetch_route(order_system, system_id, RouteFlag::Shortest).await;
+                (order_system, route.map(|r| r.len().saturating_sub(1)))
+            })
+            .buffer_unordered(SCAN_CONCURRENCY)
+            .filter_map(|(order_system, jumps)| async move { jumps.ok().map(|j| (order_system, j)) })
+            .collect()
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::market_order;
+
+    fn buy_order(range: &str, location_id: i64, system_id: i32) -> MarketOrder {
+        MarketOrder {
+            range: range.to_string(),
+            system_id,
+            ..market_order(1, true, 5.0).with_location(location_id)
+        }
+    }
+
+    #[test]
+    fn test_range_parsing() {
+        assert_eq!("station".parse::<OrderRange>(), Ok(OrderRange::Station));
+        assert_eq!("solarsystem".parse::<OrderRange>(), Ok(OrderRange::SolarSystem));
+        assert_eq!("region".parse::<OrderRange>(), Ok(OrderRange::Region));
+        assert_eq!("10".parse::<OrderRange>(), Ok(OrderRange::Jumps(10)));
+        assert!("constellation".parse::<OrderRange>().is_err());
+        assert_eq!(buy_order("galaxy", 1, 1).order_range(), OrderRange::Station);
+    }
+
+    #[test]
+    fn test_reachability() {
+        let (jita_4_4, jita, perimeter) = (60003760, 30000142, 30000144);
+
+        let station = buy_order("station", jita_4_4, jita);
+        assert!(station.order_range().reaches(&station, jita_4_4, jita, Some(0)));
+        assert!(!station.order_range().reaches(&station, 60003761, jita, Some(0)));
+
+        let system = buy_order("solarsystem", jita_4_4, jita);
+        assert!(system.order_range().reaches(&system, 60003761, jita, None));
+        assert!(!system.order_range().reaches(&system, 60000001, perimeter, Some(1)));
+
+        let five_jumps = buy_order("5", jita_4_4, jita);
+        assert!(five_jumps.order_range().reaches(&five_jumps, 60000001, perimeter, Some(1)));
+        assert!(!five_jumps.order_range().reaches(&five_jumps, 60000001, perimeter, Some(6)));
+        assert!(!five_jumps.order_range().reaches(&five_jumps, 60000001, perimeter, None));
+
+        let region = buy_order("region", jita_4_4, jita);
+        assert!(region.order_range().reaches(&region, 60000001, perimeter, None));
+    }
+}
@@ -2,19 +2,139 @@
 //!
 //! Implements rate limiting to respect EVE Online's ESI API limits:
 //! - 100 requests per second global limit
-//! - Exponential backoff for rate limit errors
+//! - Exponential backoff with jitter for rate limit errors, honoring `Retry-After`
 //! - ESI header parsing for remaining quota tracking
+//! - A shared error-limit budget that pauses all requests before ESI bans the IP
+//! - Optional per-endpoint-class quotas, so e.g. a history-heavy scan can't use
+//!   up the whole global quota and starve order lookups
+//!
+//! ESI allows 100 failed requests per window and blocks the IP once they run
+//! out. Every response reports the errors left in `x-esi-error-limit-remain`;
+//! once that drops below [`RateLimitConfig::error_budget_threshold`], the
+//! limiter holds back every request until the window resets.
+//!
+//! A single [`EsiRateLimiter`] can be shared between several `MarketClient`s by
+//! wrapping it in an `Arc`, so that all clients in a process draw from the same
+//! quota:
+//!
+//! ```
+//! use std::sync::Arc;
+//! use tradergrader::{CacheConfig, EsiRateLimiter, MarketClient, RateLimitConfig};
+//!
+//! let limiter = EsiRateLimiter::shared(RateLimitConfig::conservative())?;
+//! let orders_client = MarketClient::with_shared_rate_limiter(CacheConfig::default(), Arc::clone(&limiter))?;
+//! let history_client = MarketClient::with_shared_rate_limiter(CacheConfig::disabled(), limiter)?;
+//! # Ok::<(), tradergrader::TraderGraderError>(())
+//! ```
 
 use crate::error::{Result, TraderGraderError};
 use governor::{Quota, RateLimiter};
 use reqwest::{header::HeaderMap, Response, StatusCode};
+use std::collections::HashMap;
+use std::fmt;
 use std::num::NonZeroU32;
-use std::sync::Arc;
-use std::time::Duration;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tokio::time::sleep;
 
+/// Group of ESI endpoints sharing a rate limit policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum EndpointClass {
+    /// Regional and structure market orders
+    Orders,
+    /// Regional market history, which ESI throttles hardest
+    History,
+    /// Universe lookups: names, types, systems, routes
+    Universe,
+    /// Public contracts
+    Contracts,
+    /// Authenticated character endpoints
+    Character,
+    /// Everything else
+    Other,
+}
+
+impl EndpointClass {
+    /// Every class, in display order
+    pub const ALL: [EndpointClass; 6] = [
+        Self::Orders,
+        Self::History,
+        Self::Universe,
+        Self::Contracts,
+        Self::Character,
+        Self::Other,
+    ];
+
+    /// Classifies an ESI URL or path
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::rate_limit::EndpointClass;
+    ///
+    /// let url = "https://esi.evetech.net/latest/markets/10000002/history/?type_id=34";
+    /// assert_eq!(EndpointClass::from_url(url), EndpointClass::History);
+    /// assert_eq!(EndpointClass::from_url("/markets/structures/1028858195912/"), EndpointClass::Orders);
+    /// assert_eq!(EndpointClass::from_url("/status/"), EndpointClass::Other);
+    /// ```
+    pub fn from_url(url: &str) -> Self {
+        let path = url.split('?').next().unwrap_or(url);
+        if path.contains("/markets/") {
+            if path.contains("/history/") {
+                Self::History
+            } else if path.contains("/orders/") || path.contains("/markets/structures/") {
+                Self::Orders
+            } else {
+                Self::Other
+            }
+        } else if path.contains("/universe/") || path.contains("/route/") {
+            Self::Universe
+        } else if path.contains("/contracts/") {
+            Self::Contracts
+        } else if path.contains("/characters/") {
+            Self::Character
+        } else {
+            Self::Other
+        }
+    }
+
+    /// Name used in configuration
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Orders => "orders",
+            Self::History => "history",
+            Self::Universe => "universe",
+            Self::Contracts => "contracts",
+            Self::Character => "character",
+            Self::Other => "other",
+        }
+    }
+}
+
+impl fmt::Display for EndpointClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for EndpointClass {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let name = s.trim().to_ascii_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|class| class.as_str() == name)
+            .ok_or_else(|| {
+                let names: Vec<&str> = Self::ALL.iter().map(|class| class.as_str()).collect();
+                format!("Unknown endpoint class '{s}', expected one of {}", names.join(", "))
+            })
+    }
+}
+
 /// ESI API rate limiter configuration
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct RateLimitConfig {
     /// Requests per second limit (ESI default: 100)
     pub requests_per_second: u32,
@@ -24,6 +144,16 @@ pub struct RateLimitConfig {
     pub base_delay_ms: u64,
     /// Maximum delay between retries (seconds)
     pub max_delay_seconds: u64,
+    /// Pause all requests until the error window resets when fewer errors than this remain (0 disables)
+    pub error_budget_threshold: u32,
+    /// Fraction of each backoff delay that is randomized (0.0 to 1.0), so concurrent retries spread out
+    pub jitter: f64,
+    /// Requests per second allowed for each endpoint class, on top of the global limit
+    ///
+    /// Classes without an entry are only bound by `requests_per_second`.
+    pub endpoint_limits: HashMap<EndpointClass, u32>,
+    /// Most ESI requests a client has open at once, such as the pages of a regional order pull
+    pub max_concurrent_requests: usize,
 }
 
 impl Default for RateLimitConfig {
@@ -33,6 +163,11 @@ impl Default for RateLimitConfig {
             max_retries: 3,
             base_delay_ms: 100,
             max_delay_seconds: 30,
+            error_budget_threshold: 10,
+            jitter: 0.5,
+            // ESI throttles market history well below the global limit
+            endpoint_limits: HashMap::from([(EndpointClass::History, 20)]),
+            max_concurrent_requests: 8,
         }
     }
 }
@@ -45,6 +180,10 @@ impl RateLimitConfig {
             max_retries: 5,
             base_delay_ms: 200,
             max_delay_seconds: 60,
+            error_budget_threshold: 20,
+            jitter: 0.5,
+            endpoint_limits: HashMap::from([(EndpointClass::History, 10), (EndpointClass::Contracts, 10)]),
+            max_concurrent_requests: 4,
         }
     }
 
@@ -55,15 +194,83 @@ impl RateLimitConfig {
             max_retries: 1,
             base_delay_ms: 10,
             max_delay_seconds: 1,
+            error_budget_threshold: 0,
+            jitter: 0.0,
+            endpoint_limits: HashMap::new(),
+            max_concurrent_requests: 32,
+        }
+    }
+
+    /// Sets the requests per second allowed for one endpoint class
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::RateLimitConfig;
+    /// use tradergrader::rate_limit::EndpointClass;
+    ///
+    /// let config = RateLimitConfig::default().with_endpoint_limit(EndpointClass::Universe, 30);
+    /// assert_eq!(config.endpoint_limits[&EndpointClass::Universe], 30);
+    /// ```
+    pub fn with_endpoint_limit(mut self, class: EndpointClass, requests_per_second: u32) -> Self {
+        self.endpoint_limits.insert(class, requests_per_second);
+        self
+    }
+
+    /// Requests per second allowed for one endpoint class
+    ///
+    /// The configured limit, or the whole global limit when there is none.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::RateLimitConfig;
+    /// use tradergrader::rate_limit::EndpointClass;
+    ///
+    /// let config = RateLimitConfig::default();
+    /// assert_eq!(config.endpoint_limit(EndpointClass::History), 20);
+    /// assert_eq!(config.endpoint_limit(EndpointClass::Orders), 100);
+    /// ```
+    pub fn endpoint_limit(&self, class: EndpointClass) -> u32 {
+        match self.endpoint_limits.get(&class) {
+            Some(&requests_per_second) => requests_per_second,
+            None => self.requests_per_second,
         }
     }
 }
 
+type DirectLimiter =
+    RateLimiter<governor::state::direct::NotKeyed, governor::state::InMemoryState, governor::clock::DefaultClock>;
+
 /// ESI rate limiter that respects API quotas and handles errors
 #[derive(Debug)]
 pub struct EsiRateLimiter {
-    limiter: Arc<RateLimiter<governor::state::direct::NotKeyed, governor::state::InMemoryState, governor::clock::DefaultClock>>,
+    limiter: Arc<DirectLimiter>,
+    /// One limiter per endpoint class, in [`EndpointClass::ALL`] order
+    ///
+    /// Not a governor keyed limiter: that applies one quota to every key,
+    /// while classes need their own (history far below orders).
+    class_limiters: Vec<DirectLimiter>,
     config: RateLimitConfig,
+    error_budget: Mutex<ErrorBudget>,
+}
+
+/// Last error-limit state reported by ESI
+#[derive(Debug, Default)]
+struct ErrorBudget {
+    remaining: Option<u32>,
+    resets_at: Option<Instant>,
+}
+
+/// Snapshot of the shared ESI error-limit budget
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ErrorBudgetStatus {
+    /// Errors ESI will still accept in the current window, if any response has reported it
+    pub remaining: Option<u32>,
+    /// Time until the window resets
+    pub resets_in: Option<Duration>,
+    /// Whether requests are held back until the reset
+    pub paused: bool,
 }
 
 impl EsiRateLimiter {
@@ -75,26 +282,91 @@ impl EsiRateLimiter {
                     "Rate limit must be greater than 0".to_string()
                 ))?
         );
+        if config.max_concurrent_requests == 0 {
+            return Err(TraderGraderError::InternalError(
+                "Concurrent request limit must be greater than 0".to_string(),
+            ));
+        }
         
         let limiter = RateLimiter::direct(quota);
+        let class_limiters = EndpointClass::ALL
+            .iter()
+            .map(|&class| {
+                let rate = NonZeroU32::new(config.endpoint_limit(class)).ok_or_else(|| {
+                    TraderGraderError::InternalError(format!("Rate limit for {class} endpoints must be greater than 0"))
+                })?;
+                Ok(RateLimiter::direct(Quota::per_second(rate)))
+            })
+            .collect::<Result<_>>()?;
         
         Ok(Self {
             limiter: Arc::new(limiter),
+            class_limiters,
             config,
+            error_budget: Mutex::new(ErrorBudget::default()),
         })
     }
 
-    /// Create a default ESI rate limiter
-    pub fn default() -> Result<Self> {
-        Self::new(RateLimitConfig::default())
+    /// Create a rate limiter wrapped in an `Arc` for sharing between clients
+    pub fn shared(config: RateLimitConfig) -> Result<Arc<Self>> {
+        Ok(Arc::new(Self::new(config)?))
     }
 
     /// Wait for rate limit permission before making a request
+    ///
+    /// Also waits out the error-limit window while the error budget is exhausted.
     pub async fn acquire(&self) -> Result<()> {
+        self.acquire_for(EndpointClass::Other).await
+    }
+
+    /// Wait for permission to call an endpoint of `class`
+    ///
+    /// The class quota is taken before the global one, so requests held back
+    /// by their class don't hold up other classes.
+    pub async fn acquire_for(&self, class: EndpointClass) -> Result<()> {
+        let status = self.error_budget();
+        if let (true, Some(resets_in)) = (status.paused, status.resets_in) {
+            tracing::warn!(remaining = status.remaining, ?resets_in, "ESI error budget low, pausing requests until reset");
+            sleep(resets_in).await;
+        }
+        // ALL lists the classes in declaration order
+        self.class_limiters[class as usize].until_ready().await;
         self.limiter.until_ready().await;
         Ok(())
     }
 
+    /// Current state of the ESI error-limit budget shared by everything using this limiter
+    pub fn error_budget(&self) -> ErrorBudgetStatus {
+        let budget = self.error_budget.lock().unwrap_or_else(|e| e.into_inner());
+        let resets_in = budget
+            .resets_at
+            .map(|at| at.saturating_duration_since(Instant::now()))
+            .filter(|left| !left.is_zero());
+        let paused = match (budget.remaining, resets_in) {
+            (Some(remaining), Some(_)) => remaining < self.config.error_budget_threshold,
+            _ => false,
+        };
+        ErrorBudgetStatus {
+            remaining: resets_in.and(budget.remaining),
+            resets_in,
+            paused,
+        }
+    }
+
+    /// Records the error-limit headers of an ESI response
+    pub fn record_error_limit(&self, headers: &HeaderMap) {
+        let info = self.parse_rate_limit_headers(headers);
+        if let (Some(remaining), Some(reset)) = (info.remaining, info.reset_time) {
+            self.set_error_budget(remaining, reset);
+        }
+    }
+
+    fn set_error_budget(&self, remaining: u32, reset: Duration) {
+        let mut budget = self.error_budget.lock().unwrap_or_else(|e| e.into_inner());
+        budget.remaining = Some(remaining);
+        budget.resets_at = Some(Instant::now() + reset);
+    }
+
     /// Get the rate limit configuration
     pub fn config(&self) -> &RateLimitConfig {
         &self.config
@@ -106,13 +378,13 @@ impl EsiRateLimiter {
             return false;
         }
 
-        match status {
-            StatusCode::TOO_MANY_REQUESTS => true,
-            StatusCode::SERVICE_UNAVAILABLE => true,
-            StatusCode::BAD_GATEWAY => true,
-            StatusCode::GATEWAY_TIMEOUT => true,
-            _ => false,
-        }
+        matches!(
+            status,
+            StatusCode::TOO_MANY_REQUESTS
+                | StatusCode::SERVICE_UNAVAILABLE
+                | StatusCode::BAD_GATEWAY
+                | StatusCode::GATEWAY_TIMEOUT
+        )
     }
 
     /// Calculate delay for exponential backoff
@@ -123,6 +395,20 @@ impl EsiRateLimiter {
         Duration::from_millis(delay_ms.min(max_delay_ms))
     }
 
+    /// Exponential backoff delay with up to `jitter` of it taken off at random
+    ///
+    /// The result lies between `(1 - jitter)` times and exactly
+    /// [`calculate_backoff_delay`](Self::calculate_backoff_delay), so tasks
+    /// failing together don't all retry at the same instant.
+    pub fn jittered_backoff_delay(&self, attempt: u32) -> Duration {
+        let delay = self.calculate_backoff_delay(attempt);
+        let jitter = self.config.jitter.clamp(0.0, 1.0);
+        if jitter == 0.0 {
+            return delay;
+        }
+        delay.mul_f64(1.0 - jitter * rand::random::<f64>())
+    }
+
     /// Parse ESI rate limit headers from response
     pub fn parse_rate_limit_headers(&self, headers: &HeaderMap) -> EsiRateLimitInfo {
         let remaining = headers
@@ -139,8 +425,7 @@ impl EsiRateLimiter {
         let retry_after = headers
             .get("retry-after")
             .and_then(|v| v.to_str().ok())
-            .and_then(|s| s.parse::<u64>().ok())
-            .map(Duration::from_secs);
+            .and_then(parse_retry_after);
 
         EsiRateLimitInfo {
             remaining,
@@ -149,21 +434,108 @@ impl EsiRateLimiter {
         }
     }
 
+    /// Error for an unsuccessful ESI response
+    ///
+    /// ESI answers 429 when the request rate is too high and 420 when too many
+    /// requests have failed; both become [`TraderGraderError::RateLimitError`]
+    /// carrying the advised wait. 502, 503 and 504 mean ESI or the game server
+    /// is down and become [`TraderGraderError::EsiUnavailable`]. Anything else
+    /// is a plain ESI API error. When
+    /// the response came back from [`execute_with_retry`](Self::execute_with_retry)
+    /// after retrying, the message says how many attempts were made and how
+    /// long was spent waiting.
+    pub fn error_for_status(&self, response: &Response) -> TraderGraderError {
+        let status = response.status();
+        let retries = match response.extensions().get::<RetryTelemetry>() {
+            Some(telemetry) if telemetry.attempts > 1 => format!(
+                " after {} attempts ({:.1}s of backoff)",
+                telemetry.attempts,
+                telemetry.total_delay.as_secs_f64()
+            ),
+            _ => String::new(),
+        };
+        if status == StatusCode::TOO_MANY_REQUESTS || status.as_u16() == 420 {
+            let info = self.parse_rate_limit_headers(response.headers());
+            return TraderGraderError::RateLimitError {
+                message: format!("ESI API request failed with status: {status}{retries}"),
+                retry_after_secs: info.retry_after.or(info.reset_time).map(|d| d.as_secs()),
+            };
+        }
+        if matches!(
+            status,
+            StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT
+        ) {
+            return TraderGraderError::EsiUnavailable {
+                message: format!("ESI request failed with status: {status}{retries}"),
+                retry_after_secs: None,
+            };
+        }
+        TraderGraderError::EsiApiError {
+            message: format!("request failed with status: {status}{retries}"),
+        }
+    }
+
     /// Execute a request with automatic retry and rate limiting
     pub async fn execute_with_retry<F, Fut>(&self, request_fn: F) -> Result<Response>
     where
         F: Fn() -> Fut,
         Fut: std::future::Future<Output = Result<Response>>,
+    {
+        self.execute_with_retry_observed(request_fn, |_, _, _| {}).await
+    }
+
+    /// Execute a request with automatic retry, reporting each retry to `on_retry`
+    ///
+    /// `on_retry` receives the failed status, the delay before the next
+    /// attempt and the number of the attempt that failed (starting at 1).
+    /// Retries wait for the server's `Retry-After` when it sends one, unless
+    /// that is longer than `max_delay_seconds`, in which case the response is
+    /// returned straight away. The returned response carries a
+    /// [`RetryTelemetry`] extension.
+    pub async fn execute_with_retry_observed<F, Fut, R>(&self, request_fn: F, on_retry: R) -> Result<Response>
+    where
+        F: Fn() -> Fut,
+        Fut: std::future::Future<Output = Result<Response>>,
+        R: Fn(StatusCode, Duration, u32),
+    {
+        self.execute_classified(EndpointClass::Other, request_fn, on_retry).await
+    }
+
+    /// Execute a request to an endpoint of `class`, with retries reported to `on_retry`
+    ///
+    /// Behaves like [`execute_with_retry_observed`](Self::execute_with_retry_observed)
+    /// but draws every attempt from the class quota as well as the global one.
+    pub async fn execute_classified<F, Fut, R>(
+        &self,
+        class: EndpointClass,
+        request_fn: F,
+        on_retry: R,
+    ) -> Result<Response>
+    where
+        F: Fn() -> Fut,
+        Fut: std::future::Future<Output = Result<Response>>,
+        R: Fn(StatusCode, Duration, u32),
     {
         let mut attempt = 0;
+        let mut total_delay = Duration::ZERO;
 
         loop {
             // Wait for rate limit permission
-            self.acquire().await?;
+            self.acquire_for(class).await?;
 
             // Execute the request
-            let response = request_fn().await?;
+            let mut response = request_fn().await?;
+            response.extensions_mut().insert(RetryTelemetry {
+                attempts: attempt + 1,
+                total_delay,
+            });
             let status = response.status();
+            self.record_error_limit(response.headers());
+            if status.as_u16() == 420 {
+                // Error limited: nothing goes out until the window resets
+                let reset = self.parse_rate_limit_headers(response.headers()).reset_time;
+                self.set_error_budget(0, reset.unwrap_or(Duration::from_secs(60)));
+            }
 
             // If successful, return response
             if status.is_success() {
@@ -179,27 +551,66 @@ impl EsiRateLimiter {
             let rate_limit_info = self.parse_rate_limit_headers(response.headers());
             
             // Calculate delay (prefer retry-after header if present)
-            let delay = if let Some(retry_after) = rate_limit_info.retry_after {
-                retry_after
-            } else {
-                self.calculate_backoff_delay(attempt)
+            let delay = match rate_limit_info.retry_after {
+                Some(retry_after) if retry_after > Duration::from_secs(self.config.max_delay_seconds) => {
+                    // Waiting that long would stall the caller; let it see the error instead
+                    return Ok(response);
+                }
+                Some(retry_after) => retry_after,
+                None => self.jittered_backoff_delay(attempt),
             };
 
-            // Log retry attempt (in production, you'd use proper logging)
-            eprintln!(
-                "ESI request failed with status {}, retrying in {:?} (attempt {})",
-                status, delay, attempt + 1
-            );
+            tracing::warn!(%status, ?delay, attempt = attempt + 1, "ESI request failed, retrying");
+            on_retry(status, delay, attempt + 1);
 
             // Wait before retry
             sleep(delay).await;
+            total_delay += delay;
             attempt += 1;
         }
     }
 }
 
+impl Default for EsiRateLimiter {
+    /// Create a rate limiter with the default ESI limits
+    fn default() -> Self {
+        Self::new(RateLimitConfig::default()).expect("Default rate limit config is valid")
+    }
+}
+
+/// Parses a `Retry-After` value, given either in seconds or as an HTTP date
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use tradergrader::rate_limit::parse_retry_after;
+///
+/// assert_eq!(parse_retry_after("30"), Some(Duration::from_secs(30)));
+/// // Dates in the past mean "retry now"
+/// assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), Some(Duration::ZERO));
+/// assert_eq!(parse_retry_after("soon"), None);
+/// ```
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    Some((date.with_timezone(&chrono::Utc) - chrono::Utc::now()).to_std().unwrap_or_default())
+}
+
+/// How much retrying went into an ESI response, attached to it as an extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RetryTelemetry {
+    /// Requests sent, including the first
+    pub attempts: u32,
+    /// Time spent waiting between attempts
+    pub total_delay: Duration,
+}
+
 /// Information extracted from ESI rate limit headers
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct EsiRateLimitInfo {
     /// Remaining requests in current window
     pub remaining: Option<u32>,
@@ -209,16 +620,6 @@ pub struct EsiRateLimitInfo {
     pub retry_after: Option<Duration>,
 }
 
-impl Default for EsiRateLimitInfo {
-    fn default() -> Self {
-        Self {
-            remaining: None,
-            reset_time: None,
-            retry_after: None,
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -231,6 +632,29 @@ mod tests {
         assert_eq!(limiter.config.requests_per_second, 100);
     }
 
+    #[test]
+    fn test_rate_limiter_constructors() {
+        let limiter = EsiRateLimiter::default();
+        assert_eq!(limiter.config(), &RateLimitConfig::default());
+
+        let zero = RateLimitConfig {
+            requests_per_second: 0,
+            ..RateLimitConfig::default()
+        };
+        assert!(EsiRateLimiter::new(zero.clone()).is_err());
+        assert!(EsiRateLimiter::shared(zero).is_err());
+        let no_slots = RateLimitConfig {
+            max_concurrent_requests: 0,
+            ..RateLimitConfig::default()
+        };
+        assert!(EsiRateLimiter::new(no_slots).is_err());
+
+        let shared = EsiRateLimiter::shared(RateLimitConfig::conservative()).expect("Should create shared limiter");
+        let other = Arc::clone(&shared);
+        assert!(Arc::ptr_eq(&shared, &other));
+        assert_eq!(other.config().requests_per_second, 50);
+    }
+
     #[tokio::test]
     async fn test_rate_limiter_acquire() {
         let config = RateLimitConfig::testing(); // High limit for fast test
@@ -298,6 +722,131 @@ mod tests {
         assert_eq!(info.retry_after, Some(Duration::from_secs(30)));
     }
 
+    #[tokio::test]
+    async fn test_error_budget_pauses_requests() {
+        let limiter = EsiRateLimiter::new(RateLimitConfig::default()).expect("Should create rate limiter");
+        assert_eq!(limiter.error_budget(), ErrorBudgetStatus::default());
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-esi-error-limit-remain", "45".parse().unwrap());
+        headers.insert("x-esi-error-limit-reset", "60".parse().unwrap());
+        limiter.record_error_limit(&headers);
+        let status = limiter.error_budget();
+        assert_eq!(status.remaining, Some(45));
+        assert!(!status.paused);
+
+        limiter.set_error_budget(3, Duration::from_millis(80));
+        assert!(limiter.error_budget().paused);
+        let start = Instant::now();
+        limiter.acquire().await.expect("Should acquire");
+        assert!(start.elapsed() >= Duration::from_millis(70));
+
+        // The window has reset, so nothing is held back
+        assert!(!limiter.error_budget().paused);
+        assert_eq!(limiter.error_budget().remaining, None);
+
+        // A zero threshold never pauses
+        let testing = EsiRateLimiter::new(RateLimitConfig::testing()).expect("Should create rate limiter");
+        testing.set_error_budget(0, Duration::from_secs(60));
+        assert!(!testing.error_budget().paused);
+    }
+
+    #[test]
+    fn test_jittered_backoff_stays_in_range() {
+        let config = RateLimitConfig {
+            base_delay_ms: 1000,
+            max_delay_seconds: 60,
+            jitter: 0.5,
+            ..RateLimitConfig::default()
+        };
+        let limiter = EsiRateLimiter::new(config).expect("Should create rate limiter");
+
+        let delays: Vec<Duration> = (0..50).map(|_| limiter.jittered_backoff_delay(1)).collect();
+        assert!(delays.iter().all(|d| *d >= Duration::from_millis(1000) && *d <= Duration::from_millis(2000)));
+        assert!(delays.iter().any(|d| *d != delays[0]), "delays should vary");
+
+        // Without jitter the delay is the plain exponential backoff
+        let steady = EsiRateLimiter::new(RateLimitConfig::testing()).expect("Should create rate limiter");
+        assert_eq!(steady.jittered_backoff_delay(2), steady.calculate_backoff_delay(2));
+    }
+
+    #[test]
+    fn test_retry_after_http_date() {
+        let in_a_minute = (chrono::Utc::now() + chrono::Duration::seconds(60)).to_rfc2822();
+        let wait = parse_retry_after(&in_a_minute).expect("Should parse date");
+        assert!(wait > Duration::from_secs(55) && wait <= Duration::from_secs(60));
+
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after", " 12 ".parse().unwrap());
+        let limiter = EsiRateLimiter::default();
+        assert_eq!(limiter.parse_rate_limit_headers(&headers).retry_after, Some(Duration::from_secs(12)));
+    }
+
+    #[test]
+    fn test_endpoint_classes() {
+        let orders = "https://esi.evetech.net/latest/markets/10000002/orders/?type_id=34";
+        assert_eq!(EndpointClass::from_url(orders), EndpointClass::Orders);
+        assert_eq!(EndpointClass::from_url("/markets/groups/"), EndpointClass::Other);
+        assert_eq!(EndpointClass::from_url("/universe/names/"), EndpointClass::Universe);
+        assert_eq!(EndpointClass::from_url("/route/30000142/30002187/"), EndpointClass::Universe);
+        assert_eq!(EndpointClass::from_url("/contracts/public/10000002/"), EndpointClass::Contracts);
+        assert_eq!(EndpointClass::from_url("/characters/2112625428/orders/"), EndpointClass::Character);
+        for (index, class) in EndpointClass::ALL.into_iter().enumerate() {
+            assert_eq!(class.as_str().parse::<EndpointClass>(), Ok(class));
+            // The limiter indexes its class quotas by discriminant
+            assert_eq!(class as usize, index);
+        }
+        assert!("killmails".parse::<EndpointClass>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_endpoint_quota_does_not_starve_other_classes() {
+        let config = RateLimitConfig::testing().with_endpoint_limit(EndpointClass::History, 2);
+        let limiter = EsiRateLimiter::new(config).expect("Should create rate limiter");
+
+        // Use up the history burst
+        limiter.acquire_for(EndpointClass::History).await.expect("Should acquire");
+        limiter.acquire_for(EndpointClass::History).await.expect("Should acquire");
+
+        // Orders are still served immediately while history waits for its quota
+        let start = Instant::now();
+        limiter.acquire_for(EndpointClass::Orders).await.expect("Should acquire");
+        assert!(start.elapsed() < Duration::from_millis(50));
+
+        let start = Instant::now();
+        limiter.acquire_for(EndpointClass::History).await.expect("Should acquire");
+        assert!(start.elapsed() >= Duration::from_millis(300));
+
+        let zero = RateLimitConfig::default().with_endpoint_limit(EndpointClass::Universe, 0);
+        assert!(EsiRateLimiter::new(zero).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unconfigured_class_uses_full_global_rate() {
+        // Only history has a quota, as in the default config
+        let config = RateLimitConfig {
+            requests_per_second: 10,
+            endpoint_limits: HashMap::from([(EndpointClass::History, 2)]),
+            ..RateLimitConfig::testing()
+        };
+        assert_eq!(config.endpoint_limit(EndpointClass::Orders), 10);
+        let limiter = EsiRateLimiter::new(config).expect("Should create rate limiter");
+
+        // Orders get the whole global burst without waiting on a class quota
+        let start = Instant::now();
+        for _ in 0..10 {
+            limiter.acquire_for(EndpointClass::Orders).await.expect("Should acquire");
+        }
+        assert!(start.elapsed() < Duration::from_millis(50));
+
+        // Beyond that they wait for the global quota like everything else
+        let start = Instant::now();
+        limiter.acquire_for(EndpointClass::Orders).await.expect("Should acquire");
+        assert!(start.elapsed() >= Duration::from_millis(50));
+
+        assert_eq!(RateLimitConfig::default().endpoint_limit(EndpointClass::Orders), 100);
+    }
+
     #[test]
     fn test_conservative_config() {
         let config = RateLimitConfig::conservative();
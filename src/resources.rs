@@ -0,0 +1,140 @@
+//! MCP resources for TraderGrader
+//!
+//! Clients that want the same data over and over (the items a trader is
+//! watching and how their markets look) can read it as resources instead of
+//! calling tools. The server's [`WatchlistStore`] is one resource, and every
+//! region and item pair on it adds a market summary resource, served from the
+//! cache when it is warm. The market snapshot of the configured basket across
+//! the trade hubs is one more. Clients may subscribe to the watchlist to be
+//! told when it changes.
+
+use crate::error::{Result, TraderGraderError};
+use crate::watchlist::WatchlistStore;
+use serde_json::{json, Value};
+use std::fmt;
+use std::str::FromStr;
+
+/// URI of the watchlist resource
+pub const WATCHLIST_URI: &str = "tradergrader://watchlist";
+
+/// URI of the market snapshot resource
+pub const SNAPSHOT_URI: &str = "tradergrader://snapshot";
+
+/// Prefix of the market summary resource URIs
+const MARKET_URI_PREFIX: &str = "tradergrader://market/";
+
+/// A resource the server can read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceUri {
+    /// The watchlist itself, as JSON
+    Watchlist,
+    /// `tradergrader://market/{region_id}/{type_id}/summary`
+    MarketSummary { region_id: i32, type_id: i32 },
+    /// The latest market snapshot, as JSON
+    MarketSnapshot,
+}
+
+impl fmt::Display for ResourceUri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Watchlist => f.write_str(WATCHLIST_URI),
+            Self::MarketSummary { region_id, type_id } => write!(f, "{MARKET_URI_PREFIX}{region_id}/{type_id}/summary"),
+            Self::MarketSnapshot => f.write_str(SNAPSHOT_URI),
+        }
+    }
+}
+
+impl FromStr for ResourceUri {
+    type Err = TraderGraderError;
+
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::resources::ResourceUri;
+    ///
+    /// let uri: ResourceUri = "tradergrader://market/10000002/34/summary".parse()?;
+    /// assert_eq!(uri, ResourceUri::MarketSummary { region_id: 10000002, type_id: 34 });
+    /// assert_eq!(uri.to_string(), "tradergrader://market/10000002/34/summary");
+    /// # Ok::<(), tradergrader::TraderGraderError>(())
+    /// ```
+    fn from_str(uri: &str) -> Result<Self> {
+        if uri == WATCHLIST_URI {
+            return Ok(Self::Watchlist);
+        }
+        if uri == SNAPSHOT_URI {
+            return Ok(Self::MarketSnapshot);
+        }
+        let summary = uri
+            .strip_prefix(MARKET_URI_PREFIX)
+            .and_then(|rest| rest.strip_suffix("/summary"))
+            .and_then(|ids| ids.split_once('/'))
+            .and_then(|(region, item)| Some((region.parse().ok()?, item.parse().ok()?)));
+        match summary {
+            Some((region_id, type_id)) => Ok(Self::MarketSummary { region_id, type_id }),
+            None => Err(TraderGraderError::InvalidParams(format!("Unknown resource: {uri}"))),
+        }
+    }
+}
+
+/// The `resources` array of a `resources/list` result
+pub fn list_resources(watchlist: &WatchlistStore) -> Vec<Value> {
+    let mut resources = vec![
+        json!({
+            "uri": WATCHLIST_URI,
+            "name": "Watchlist",
+            "description": "Items the server is watching, by region",
+            "mimeType": "application/json"
+        }),
+        json!({
+            "uri": SNAPSHOT_URI,
+            "name": "Market snapshot",
+            "description": "Best prices, spreads and volumes of the configured basket across the trade hubs",
+            "mimeType": "application/json"
+        }),
+    ];
+    for (region_id, type_id) in watchlist.entries() {
+        resources.push(json!({
+            "uri": ResourceUri::MarketSummary { region_id, type_id }.to_string(),
+            "name": format!("Market summary for type {type_id} in region {region_id}"),
+            "description": "Latest market summary, served from cache when fresh",
+            "mimeType": "text/plain"
+        }));
+    }
+    resources
+}
+
+/// The watchlist resource's JSON body
+pub fn watchlist_json(watchlist: &WatchlistStore) -> Value {
+    let regions: Vec<Value> = watchlist
+        .regions()
+        .into_iter()
+        .map(|(region_id, type_ids)| json!({"region_id": region_id, "type_ids": type_ids}))
+        .collect();
+    json!({ "regions": regions })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uris_round_trip() {
+        assert_eq!(WATCHLIST_URI.parse::<ResourceUri>().unwrap(), ResourceUri::Watchlist);
+        assert_eq!(SNAPSHOT_URI.parse::<ResourceUri>().unwrap().to_string(), SNAPSHOT_URI);
+        for uri in ["tradergrader://market/10000002/summary", "tradergrader://market/x/34/summary", "file:///etc"] {
+            assert!(uri.parse::<ResourceUri>().is_err(), "{uri}");
+        }
+    }
+
+    #[test]
+    fn test_list_follows_watchlist() {
+        let watchlist = WatchlistStore::in_memory();
+        assert_eq!(list_resources(&watchlist).len(), 2);
+
+        watchlist.add(10000002, &[34, 35]).unwrap();
+        let resources = list_resources(&watchlist);
+        assert_eq!(resources.len(), 4);
+        assert_eq!(resources[3]["uri"], "tradergrader://market/10000002/35/summary");
+        assert_eq!(watchlist_json(&watchlist)["regions"][0]["type_ids"], json!([34, 35]));
+    }
+}
@@ -0,0 +1,165 @@
+//! Return series for TraderGrader
+//!
+//! Forecasting, correlation, risk and backtesting all start from the same
+//! day-over-day returns of average prices. This module computes them once, in
+//! one way: simple and log returns, cumulative return curves and rolling
+//! statistics. Returns are fractions (0.05 is +5%); multiply by 100 for reports.
+
+use crate::types::MarketHistory;
+
+/// Daily average prices from history in any order, oldest first
+pub fn daily_prices(history: &[MarketHistory]) -> Vec<f64> {
+    let mut days: Vec<&MarketHistory> = history.iter().collect();
+    days.sort_by(|a, b| a.date.cmp(&b.date));
+    days.iter().map(|h| h.average).collect()
+}
+
+/// Day-over-day simple returns, `p[t] / p[t-1] - 1`
+///
+/// Pairs involving a non-positive price are skipped, so the result can be
+/// shorter than `prices.len() - 1`.
+///
+/// # Examples
+///
+/// ```
+/// use tradergrader::returns::simple_returns;
+///
+/// let returns = simple_returns(&[100.0, 110.0, 99.0]);
+/// assert!((returns[0] - 0.10).abs() < 1e-9);
+/// assert!((returns[1] + 0.10).abs() < 1e-9);
+/// ```
+pub fn simple_returns(prices: &[f64]) -> Vec<f64> {
+    valid_pairs(prices).map(|(previous, price)| price / previous - 1.0).collect()
+}
+
+/// Day-over-day log returns, `ln(p[t] / p[t-1])`
+///
+/// Log returns add up over time, which makes them the better input for
+/// volatility and correlation. Pairs involving a non-positive price are skipped.
+pub fn log_returns(prices: &[f64]) -> Vec<f64> {
+    valid_pairs(prices).map(|(previous, price)| (price / previous).ln()).collect()
+}
+
+/// Log returns of daily average prices from history in any order
+pub fn history_log_returns(history: &[MarketHistory]) -> Vec<f64> {
+    log_returns(&daily_prices(history))
+}
+
+/// Return since the first price at each point, `p[t] / p[0] - 1`
+///
+/// The series has one entry per price and starts at 0. It is empty when the
+/// first price isn't positive.
+///
+/// # Examples
+///
+/// ```
+/// use tradergrader::returns::cumulative_returns;
+///
+/// assert_eq!(cumulative_returns(&[100.0, 150.0, 50.0]), vec![0.0, 0.5, -0.5]);
+/// ```
+pub fn cumulative_returns(prices: &[f64]) -> Vec<f64> {
+    match prices.first() {
+        Some(&first) if first > 0.0 => prices.iter().map(|p| p / first - 1.0).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Arithmetic mean, or `None` for an empty series
+pub fn mean(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    Some(values.iter().sum::<f64>() / values.len() as f64)
+}
+
+/// Sample standard deviation, or `None` with fewer than two values
+pub fn std_dev(values: &[f64]) -> Option<f64> {
+    if values.len() < 2 {
+        return None;
+    }
+    let mean = mean(values)?;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (values.len() - 1) as f64;
+    Some(variance.sqrt())
+}
+
+/// Mean of each `window`-long run of values, one entry per complete window
+pub fn rolling_mean(values: &[f64], window: usize) -> Vec<f64> {
+    rolling(values, window, mean)
+}
+
+/// Sample standard deviation of each `window`-long run of values
+///
+/// Windows shorter than two values have no standard deviation, so the result
+/// is empty when `window < 2`.
+pub fn rolling_std_dev(values: &[f64], window: usize) -> Vec<f64> {
+    rolling(values, window, std_dev)
+}
+
+/// Applies `stat` to each complete window
+fn rolling(values: &[f64], window: usize, stat: fn(&[f64]) -> Option<f64>) -> Vec<f64> {
+    if window == 0 {
+        return Vec::new();
+    }
+    values.windows(window).filter_map(stat).collect()
+}
+
+/// Consecutive price pairs where both prices are positive
+fn valid_pairs(prices: &[f64]) -> impl Iterator<Item = (f64, f64)> + '_ {
+    prices
+        .windows(2)
+        .filter(|w| w[0] > 0.0 && w[1] > 0.0)
+        .map(|w| (w[0], w[1]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::history_day;
+
+    #[test]
+    fn test_simple_and_log_returns() {
+        let prices = [100.0, 125.0, 0.0, 100.0, 50.0];
+        // The pairs touching 0.0 are skipped
+        assert_eq!(simple_returns(&prices), vec![0.25, -0.5]);
+        let logs = log_returns(&prices);
+        assert!((logs[0] - 1.25f64.ln()).abs() < 1e-12);
+        assert!((logs[1] - 0.5f64.ln()).abs() < 1e-12);
+        assert!(simple_returns(&[100.0]).is_empty());
+    }
+
+    #[test]
+    fn test_history_is_sorted_before_returns() {
+        let history = vec![
+            history_day("2025-06-03", 121.0, 1),
+            history_day("2025-06-01", 100.0, 1),
+            history_day("2025-06-02", 110.0, 1),
+        ];
+        assert_eq!(daily_prices(&history), vec![100.0, 110.0, 121.0]);
+        let returns = history_log_returns(&history);
+        assert_eq!(returns.len(), 2);
+        assert!((returns[0] - returns[1]).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_cumulative_returns() {
+        assert_eq!(cumulative_returns(&[50.0, 75.0, 100.0]), vec![0.0, 0.5, 1.0]);
+        assert!(cumulative_returns(&[0.0, 1.0]).is_empty());
+        assert!(cumulative_returns(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_rolling_statistics() {
+        let values = [1.0, 2.0, 3.0, 4.0, 5.0];
+        assert_eq!(rolling_mean(&values, 2), vec![1.5, 2.5, 3.5, 4.5]);
+        assert_eq!(rolling_mean(&values, 6), Vec::<f64>::new());
+        assert_eq!(rolling_mean(&values, 0), Vec::<f64>::new());
+
+        let deviations = rolling_std_dev(&values, 3);
+        assert_eq!(deviations, vec![1.0, 1.0, 1.0]);
+        assert!(rolling_std_dev(&values, 1).is_empty());
+
+        assert_eq!(mean(&[]), None);
+        assert_eq!(std_dev(&[1.0]), None);
+        assert!((std_dev(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]).unwrap() - 2.138089935).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,468 @@
+//! Gate route planning for TraderGrader
+//!
+//! Wraps ESI `/route/` and looks up every system on the route, so a route
+//! comes back with its jumps, the systems crossed and the low- and null-sec
+//! stretches a hauler has to get through. The hauling and courier tools price
+//! their routes from the same plan.
+//!
+//! A plan can also be overlaid with the last hour of ship and pod kills and
+//! jumps from ESI to rate its risk. ESI has no notion of a gate camp, so pod
+//! kills that are a large share of a system's traffic stand in for one.
+
+use crate::error::Result;
+use crate::hauling::RouteFlag;
+use crate::market::MarketClient;
+use crate::scan::SCAN_CONCURRENCY;
+use crate::types::{
+    RoutePlan, RouteRisk, RouteSegment, RouteSystem, SecurityClass, SystemInfo, SystemJumps, SystemKills, SystemRisk,
+};
+use crate::universe::system_activity;
+use futures::stream::{self, StreamExt, TryStreamExt};
+
+/// System risk score from which a system is called out as dangerous
+pub const DANGEROUS_SYSTEM_SCORE: f64 = 20.0;
+
+/// Risk of a quiet low- and null-sec system, where nothing stops a hostile from shooting
+const LOW_SEC_BASELINE: f64 = 5.0;
+const NULL_SEC_BASELINE: f64 = 10.0;
+
+/// Risk added per ship and per pod kill in the last hour
+const SHIP_KILL_POINTS: f64 = 2.0;
+const POD_KILL_POINTS: f64 = 4.0;
+
+/// Risk added when the kills look like a gate camp
+const CAMP_POINTS: f64 = 20.0;
+
+/// Pod kills, and kills per jump into the system, from which kills look like a camp
+const CAMP_MIN_POD_KILLS: i64 = 2;
+const CAMP_KILL_SHARE: f64 = 0.05;
+
+/// Highest risk one system can score, since an hour of kills is never certain death
+const MAX_SYSTEM_SCORE: f64 = 90.0;
+
+impl RoutePlan {
+    /// Builds a plan from the systems on a route, origin first
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::hauling::RouteFlag;
+    /// use tradergrader::{Position, RoutePlan, SecurityClass, SystemInfo};
+    ///
+    /// let system = |system_id: i32, name: &str, security_status: f64| SystemInfo {
+    ///     system_id,
+    ///     name: name.to_string(),
+    ///     constellation_id: 1,
+    ///     security_status,
+    ///     position: Position { x: 0.0, y: 0.0, z: 0.0 },
+    ///     stations: Vec::new(),
+    /// };
+    /// let systems = [system(1, "Jita", 0.9), system(2, "Urlen", 0.4), system(3, "Sirppala", 0.3), system(4, "Niyabainen", 0.9)];
+    ///
+    /// let plan = RoutePlan::from_systems(&systems, RouteFlag::Shortest)?;
+    /// assert_eq!(plan.jumps, 3);
+    /// assert_eq!(plan.segments.len(), 1);
+    /// assert_eq!(plan.segments[0].security_class, SecurityClass::LowSec);
+    /// assert_eq!((plan.segments[0].from_system.as_str(), plan.segments[0].system_count), ("Urlen", 2));
+    /// # Ok::<(), tradergrader::TraderGraderError>(())
+    /// ```
+    pub fn from_systems(systems: &[SystemInfo], flag: RouteFlag) -> Result<Self> {
+        let (Some(origin), Some(destination)) = (systems.first(), systems.last()) else {
+            return Err("A route needs at least one system".into());
+        };
+        let systems: Vec<RouteSystem> = systems
+            .iter()
+            .map(|system| RouteSystem {
+                system_id: system.system_id,
+                name: system.name.clone(),
+                security_status: system.security_status,
+                security_class: SecurityClass::from_security_status(system.security_status),
+            })
+            .collect();
+
+        let mut segments: Vec<RouteSegment> = Vec::new();
+        let mut previous_class = SecurityClass::HighSec;
+        for system in &systems {
+            match segments.last_mut() {
+                Some(segment) if system.security_class == previous_class && previous_class != SecurityClass::HighSec => {
+                    segment.to_system = system.name.clone();
+                    segment.system_count += 1;
+                    segment.lowest_security_status = segment.lowest_security_status.min(system.security_status);
+                }
+                _ if system.security_class != SecurityClass::HighSec => segments.push(RouteSegment {
+                    security_class: system.security_class,
+                    from_system: system.name.clone(),
+                    to_system: system.name.clone(),
+                    system_count: 1,
+                    lowest_security_status: system.security_status,
+                }),
+                _ => {}
+            }
+            previous_class = system.security_class;
+        }
+
+        Ok(Self {
+            origin_system_id: origin.system_id,
+            destination_system_id: destination.system_id,
+            flag: flag.as_str().to_string(),
+            jumps: systems.len() - 1,
+            systems,
+            segments,
+        })
+    }
+
+    /// Jumps into systems of `class`, not counting the origin
+    pub fn jumps_into(&self, class: SecurityClass) -> usize {
+        self.systems.iter().skip(1).filter(|system| system.security_class == class).count()
+    }
+
+    /// The system with the lowest security status on the route
+    pub fn lowest_security_system(&self) -> Option<&RouteSystem> {
+        self.systems.iter().min_by(|a, b| a.security_status.total_cmp(&b.security_status))
+    }
+}
+
+impl RouteRisk {
+    /// Rates each system on `plan` from the last hour of kills and jumps
+    ///
+    /// A system's score starts from its security band and grows with ship
+    /// and pod kills, with extra weight when they look like a gate camp. The
+    /// route's score is the chance, reading system scores as percentages,
+    /// that at least one system on it goes wrong.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::hauling::RouteFlag;
+    /// use tradergrader::{Position, RoutePlan, RouteRisk, SystemInfo, SystemJumps, SystemKills};
+    ///
+    /// let system = |system_id: i32, security_status: f64| SystemInfo {
+    ///     system_id,
+    ///     name: format!("System {system_id}"),
+    ///     constellation_id: 1,
+    ///     security_status,
+    ///     position: Position { x: 0.0, y: 0.0, z: 0.0 },
+    ///     stations: Vec::new(),
+    /// };
+    /// let plan = RoutePlan::from_systems(&[system(1, 0.9), system(2, 0.5), system(3, 0.9)], RouteFlag::Shortest)?;
+    /// // Four ships and three pods died in the middle system out of 40 jumps
+    /// let kills = vec![SystemKills { system_id: 2, ship_kills: 4, pod_kills: 3, npc_kills: 0 }];
+    /// let jumps = vec![SystemJumps { system_id: 2, ship_jumps: 40 }];
+    ///
+    /// let risk = RouteRisk::from_plan(&plan, &kills, &jumps);
+    /// assert!(risk.systems[1].likely_camp);
+    /// assert_eq!(risk.systems[1].risk_score, 40.0);
+    /// assert_eq!(risk.rating, "High");
+    /// # Ok::<(), tradergrader::TraderGraderError>(())
+    /// ```
+    pub fn from_plan(plan: &RoutePlan, kills: &[SystemKills], jumps: &[SystemJumps]) -> Self {
+        let activity = system_activity(kills, jumps);
+        let systems: Vec<SystemRisk> = plan
+            .systems
+            .iter()
+            .map(|system| {
+                let activity = activity.get(&system.system_id).cloned().unwrap_or_default();
+                let kills = activity.ship_kills + activity.pod_kills;
+                let likely_camp = activity.pod_kills >= CAMP_MIN_POD_KILLS
+                    && kills as f64 >= CAMP_KILL_SHARE * activity.ship_jumps.max(1) as f64;
+                let baseline = match system.security_class {
+                    SecurityClass::HighSec => 0.0,
+                    SecurityClass::LowSec => LOW_SEC_BASELINE,
+                    SecurityClass::NullSec => NULL_SEC_BASELINE,
+                };
+                let score = baseline
+                    + activity.ship_kills as f64 * SHIP_KILL_POINTS
+                    + activity.pod_kills as f64 * POD_KILL_POINTS
+                    + if likely_camp { CAMP_POINTS } else { 0.0 };
+                SystemRisk {
+                    system_id: system.system_id,
+                    name: system.name.clone(),
+                    security_status: system.security_status,
+                    security_class: system.security_class,
+                    ship_kills: activity.ship_kills,
+                    pod_kills: activity.pod_kills,
+                    ship_jumps: activity.ship_jumps,
+                    likely_camp,
+                    risk_score: score.min(MAX_SYSTEM_SCORE),
+                }
+            })
+            .collect();
+
+        let safe_passage: f64 = systems.iter().map(|system| 1.0 - system.risk_score / 100.0).product();
+        let risk_score = (1.0 - safe_passage) * 100.0;
+        let rating = match risk_score {
+            score if score < 10.0 => "Low",
+            score if score < 30.0 => "Moderate",
+            score if score < 60.0 => "High",
+            _ => "Extreme",
+        };
+
+        Self {
+            origin_system_id: plan.origin_system_id,
+            destination_system_id: plan.destination_system_id,
+            flag: plan.flag.clone(),
+            jumps: plan.jumps,
+            systems,
+            risk_score,
+            rating: rating.to_string(),
+        }
+    }
+
+    /// Systems scoring at least [`DANGEROUS_SYSTEM_SCORE`], in route order
+    pub fn dangerous_systems(&self) -> impl Iterator<Item = &SystemRisk> {
+        self.systems.iter().filter(|system| system.risk_score >= DANGEROUS_SYSTEM_SCORE)
+    }
+}
+
+impl MarketClient {
+    /// Plans the gate route between two systems, with the security of every system on it
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # use tradergrader::hauling::RouteFlag;
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// // Jita to Amarr staying in high-sec
+    /// let plan = client.plan_route(30000142, 30002187, RouteFlag::Secure).await?;
+    /// println!("{} jumps, {} low/null-sec stretches", plan.jumps, plan.segments.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn plan_route(
+        &self,
+        origin_system_id: i32,
+        destination_system_id: i32,
+        flag: RouteFlag,
+    ) -> Result<RoutePlan> {
+        let route = self.fetch_route(origin_system_id, destination_system_id, flag).await?;
+        let systems: Vec<SystemInfo> = stream::iter(route)
+            .map(|system_id| self.fetch_system(system_id))
+            .buffered(SCAN_CONCURRENCY)
+            .try_collect()
+            .await?;
+        RoutePlan::from_systems(&systems, flag)
+    }
+
+    /// Rates a planned route from the last hour of kills and jumps in its systems
+    pub async fn assess_route_risk(&self, plan: &RoutePlan) -> Result<RouteRisk> {
+        let kills = self.fetch_system_kills().await?;
+        let jumps = self.fetch_system_jumps().await?;
+        Ok(RouteRisk::from_plan(plan, &kills, &jumps))
+    }
+
+    /// Plans the route between two systems and rates its risk
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # use tradergrader::hauling::RouteFlag;
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// let risk = client.get_route_risk(30000142, 30002187, RouteFlag::Secure).await?;
+    /// println!("Risk {:.0}/100 ({})", risk.risk_score, risk.rating);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_route_risk(
+        &self,
+        origin_system_id: i32,
+        destination_system_id: i32,
+        flag: RouteFlag,
+    ) -> Result<RouteRisk> {
+        let plan = self.plan_route(origin_system_id, destination_system_id, flag).await?;
+        self.assess_route_risk(&plan).await
+    }
+}
+
+/// One line per low- or null-sec stretch, or a note that the route stays in high-sec
+pub(crate) fn format_route_segments(segments: &[RouteSegment]) -> String {
+    if segments.is_empty() {
+        return "The route stays in high-sec.".to_string();
+    }
+    let mut text = format!("{} low/null-sec stretches:", segments.len());
+    for segment in segments {
+        let systems = if segment.system_count == 1 {
+            segment.from_system.clone()
+        } else {
+            format!("{} -> {} ({} systems)", segment.from_system, segment.to_system, segment.system_count)
+        };
+        text.push_str(&format!(
+            "\n- {}: {systems}, lowest {:.1}",
+            segment.security_class, segment.lowest_security_status
+        ));
+    }
+    text
+}
+
+/// One line per dangerous system, or a note that none stood out
+pub(crate) fn format_dangerous_systems(risk: &RouteRisk) -> String {
+    let dangerous: Vec<String> = risk
+        .dangerous_systems()
+        .map(|system| {
+            format!(
+                "- {} {:.1}: {} ship and {} pod kills, {} jumps in the last hour{} (risk {:.0})",
+                system.name,
+                system.security_status,
+                system.ship_kills,
+                system.pod_kills,
+                system.ship_jumps,
+                if system.likely_camp { ", likely gate camp" } else { "" },
+                system.risk_score
+            )
+        })
+        .collect();
+    if dangerous.is_empty() {
+        "No dangerous systems in the last hour.".to_string()
+    } else {
+        format!("Dangerous systems:\n{}", dangerous.join("\n"))
+    }
+}
+
+/// Formats a route's risk with every system that saw kills
+pub(crate) fn format_route_risk(risk: &RouteRisk) -> String {
+    let origin = risk.systems.first().map_or("?", |s| s.name.as_str());
+    let destination = risk.systems.last().map_or("?", |s| s.name.as_str());
+    let mut text = format!(
+        "Route Risk from {origin} to {destination} ({}, {} jumps):\nRisk score: {:.0}/100 ({})\n\n",
+        risk.flag, risk.jumps, risk.risk_score, risk.rating
+    );
+    text.push_str(&format_dangerous_systems(risk));
+
+    let other_kills: Vec<String> = risk
+        .systems
+        .iter()
+        .filter(|system| system.risk_score < DANGEROUS_SYSTEM_SCORE && system.ship_kills + system.pod_kills > 0)
+        .map(|system| format!("{} ({} ship, {} pod)", system.name, system.ship_kills, system.pod_kills))
+        .collect();
+    if !other_kills.is_empty() {
+        text.push_str(&format!("\nOther systems with kills: {}", other_kills.join(", ")));
+    }
+    text.push_str(
+        "\n\nKills and jumps cover the last hour only; check the route again before undocking with valuable cargo.",
+    );
+    text
+}
+
+/// Formats a route plan with every system and its security status
+pub(crate) fn format_route_plan(plan: &RoutePlan) -> String {
+    let origin = plan.systems.first().map_or("?", |s| s.name.as_str());
+    let destination = plan.systems.last().map_or("?", |s| s.name.as_str());
+    let mut text = format!(
+        "Route from {origin} to {destination} ({}):\n{} jumps: {} high-sec, {} low-sec, {} null-sec\n\n",
+        plan.flag,
+        plan.jumps,
+        plan.jumps_into(SecurityClass::HighSec),
+        plan.jumps_into(SecurityClass::LowSec),
+        plan.jumps_into(SecurityClass::NullSec)
+    );
+    for (index, system) in plan.systems.iter().enumerate() {
+        let class = match system.security_class {
+            SecurityClass::HighSec => String::new(),
+            class => format!(" {class}"),
+        };
+        text.push_str(&format!(
+            "{index}. {} ({}) {:.1}{class}\n",
+            system.name, system.system_id, system.security_status
+        ));
+    }
+    text.push('\n');
+    text.push_str(&format_route_segments(&plan.segments));
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Position;
+
+    fn system(system_id: i32, security_status: f64) -> SystemInfo {
+        SystemInfo {
+            system_id,
+            name: format!("System {system_id}"),
+            constellation_id: 1,
+            security_status,
+            position: Position { x: 0.0, y: 0.0, z: 0.0 },
+            stations: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_segments_split_by_security_class() {
+        let systems = [
+            system(1, 0.9),
+            system(2, 0.3),
+            system(3, 0.1),
+            system(4, -0.2),
+            system(5, 0.6),
+            system(6, 0.2),
+        ];
+        let plan = RoutePlan::from_systems(&systems, RouteFlag::Insecure).unwrap();
+
+        let segments: Vec<(SecurityClass, &str, &str, usize)> = plan
+            .segments
+            .iter()
+            .map(|s| (s.security_class, s.from_system.as_str(), s.to_system.as_str(), s.system_count))
+            .collect();
+        assert_eq!(
+            segments,
+            vec![
+                (SecurityClass::LowSec, "System 2", "System 3", 2),
+                (SecurityClass::NullSec, "System 4", "System 4", 1),
+                (SecurityClass::LowSec, "System 6", "System 6", 1),
+            ]
+        );
+        assert_eq!(plan.segments[0].lowest_security_status, 0.1);
+        assert_eq!(plan.jumps_into(SecurityClass::LowSec), 3);
+        assert_eq!(plan.lowest_security_system().unwrap().system_id, 4);
+
+        let text = format_route_plan(&plan);
+        assert!(text.contains("5 jumps: 1 high-sec, 3 low-sec, 1 null-sec"));
+        assert!(text.contains("- low-sec: System 2 -> System 3 (2 systems), lowest 0.1"));
+        assert!(text.contains("3. System 4 (4) -0.2 null-sec"));
+    }
+
+    #[test]
+    fn test_high_sec_route_and_single_system() {
+        let plan = RoutePlan::from_systems(&[system(1, 1.0), system(2, 0.5)], RouteFlag::Secure).unwrap();
+        assert!(plan.segments.is_empty());
+        assert!(format_route_plan(&plan).ends_with("The route stays in high-sec."));
+
+        let plan = RoutePlan::from_systems(&[system(1, 0.9)], RouteFlag::Shortest).unwrap();
+        assert_eq!(plan.jumps, 0);
+        assert!(RoutePlan::from_systems(&[], RouteFlag::Shortest).is_err());
+    }
+
+    #[test]
+    fn test_route_risk_flags_camps_and_scores_quiet_routes_low() {
+        let plan =
+            RoutePlan::from_systems(&[system(1, 0.9), system(2, 0.4), system(3, -0.1)], RouteFlag::Insecure).unwrap();
+        let jumps = [
+            SystemJumps { system_id: 2, ship_jumps: 500 },
+            SystemJumps { system_id: 3, ship_jumps: 20 },
+        ];
+        // Busy low-sec with a few kills against quiet null-sec with pods dying
+        let kills = [
+            SystemKills { system_id: 2, ship_kills: 3, pod_kills: 2, npc_kills: 50 },
+            SystemKills { system_id: 3, ship_kills: 2, pod_kills: 2, npc_kills: 0 },
+        ];
+        let risk = RouteRisk::from_plan(&plan, &kills, &jumps);
+
+        let scores: Vec<(f64, bool)> = risk.systems.iter().map(|s| (s.risk_score, s.likely_camp)).collect();
+        assert_eq!(scores, vec![(0.0, false), (19.0, false), (42.0, true)]);
+        assert!((risk.risk_score - (1.0 - 0.81 * 0.58) * 100.0).abs() < 1e-9);
+        assert_eq!(risk.rating, "High");
+        let names: Vec<&str> = risk.dangerous_systems().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["System 3"]);
+
+        let text = format_route_risk(&risk);
+        assert!(text.contains("- System 3 -0.1: 2 ship and 2 pod kills, 20 jumps in the last hour, likely gate camp"));
+        assert!(text.contains("Other systems with kills: System 2 (3 ship, 2 pod)"));
+
+        let quiet = RouteRisk::from_plan(&plan, &[], &[]);
+        assert_eq!(quiet.rating, "Moderate");
+        assert!(format_route_risk(&quiet).contains("No dangerous systems in the last hour."));
+    }
+}
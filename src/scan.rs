@@ -0,0 +1,452 @@
+//! Batch market scanning for TraderGrader
+//!
+//! Fetches orders and history for many item types in one region concurrently
+//! and ranks them, so a whole market group can be screened in one call. Every
+//! request still goes through the client's shared rate limiter; the
+//! concurrency cap only bounds how many items are in flight at once.
+//!
+//! A [`LevelFilter`] narrows a scan to items of some meta or tech level, read
+//! from the local SDE or the type's dogma attributes, so "T2 ammo margins"
+//! doesn't need the caller to list every T2 charge.
+
+use crate::error::{Result, TraderGraderError};
+use crate::market::MarketClient;
+use crate::orderbook::MarketOrderBook;
+use crate::types::{MarketHistory, MarketScan, ScanResult, ScanSort, TypeInfo};
+use futures::stream::{self, StreamExt};
+use std::fmt;
+
+/// Maximum number of item types fetched at the same time
+pub const SCAN_CONCURRENCY: usize = 8;
+
+/// Maximum number of item types accepted in one scan
+pub const MAX_SCAN_TYPES: usize = 200;
+
+/// Days of history averaged for the volume column
+const VOLUME_WINDOW_DAYS: usize = 30;
+
+/// Meta and tech level bounds on the items a scan covers
+///
+/// Types without a meta level attribute count as meta level 0 and types
+/// without a tech level as tech level 1, as in game.
+///
+/// # Examples
+///
+/// ```
+/// use tradergrader::scan::LevelFilter;
+///
+/// let t2 = LevelFilter { tech_level: Some(2), ..LevelFilter::default() };
+/// assert!(t2.validate().is_ok());
+/// assert_eq!(t2.to_string(), "tech level 2");
+/// assert!(LevelFilter::default().is_empty());
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LevelFilter {
+    pub min_meta_level: Option<i32>,
+    pub max_meta_level: Option<i32>,
+    pub tech_level: Option<i32>,
+}
+
+impl LevelFilter {
+    /// Whether the filter lets every item through
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Checks the bounds are consistent
+    pub fn validate(&self) -> Result<()> {
+        if let (Some(min), Some(max)) = (self.min_meta_level, self.max_meta_level) {
+            if min > max {
+                return Err(TraderGraderError::InvalidArgument {
+                    field: "min_meta_level".to_string(),
+                    reason: format!("must not be above max_meta_level ({min} > {max})"),
+                });
+            }
+        }
+        if let Some(level) = self.tech_level.filter(|level| !(1..=3).contains(level)) {
+            return Err(TraderGraderError::InvalidArgument {
+                field: "tech_level".to_string(),
+                reason: format!("must be 1, 2 or 3, got {level}"),
+            });
+        }
+        Ok(())
+    }
+
+    /// Whether an item type passes the filter
+    pub fn matches(&self, info: &TypeInfo) -> bool {
+        let meta_level = info.meta_level.unwrap_or(0);
+        self.min_meta_level.is_none_or(|min| meta_level >= min)
+            && self.max_meta_level.is_none_or(|max| meta_level <= max)
+            && self.tech_level.is_none_or(|tech| info.tech_level.unwrap_or(1) == tech)
+    }
+}
+
+impl fmt::Display for LevelFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        match (self.min_meta_level, self.max_meta_level) {
+            (Some(min), Some(max)) if min == max => parts.push(format!("meta level {min}")),
+            (Some(min), Some(max)) => parts.push(format!("meta level {min}-{max}")),
+            (Some(min), None) => parts.push(format!("meta level {min}+")),
+            (None, Some(max)) => parts.push(format!("meta level up to {max}")),
+            (None, None) => {}
+        }
+        if let Some(tech) = self.tech_level {
+            parts.push(format!("tech level {tech}"));
+        }
+        if parts.is_empty() {
+            return f.write_str("any level");
+        }
+        f.write_str(&parts.join(", "))
+    }
+}
+
+impl MarketClient {
+    /// Scans many item types in one region and ranks them
+    ///
+    /// Orders and history for each type are fetched concurrently (at most
+    /// [`SCAN_CONCURRENCY`] types at once). Types that fail to fetch are listed
+    /// in [`MarketScan::failures`] instead of failing the whole scan.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result, ScanSort};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// // Minerals in The Forge
+    /// let scan = client
+    ///     .scan_types(10000002, &[34, 35, 36, 37, 38, 39, 40], ScanSort::ProfitPotential)
+    ///     .await?;
+    /// println!("Best: {}", scan.results[0].type_id);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn scan_types(&self, region_id: i32, type_ids: &[i32], sort_by: ScanSort) -> Result<MarketScan> {
+        if type_ids.is_empty() {
+            return Err("No item types to scan".into());
+        }
+        if type_ids.len() > MAX_SCAN_TYPES {
+            return Err(format!("Too many item types to scan ({}, max {MAX_SCAN_TYPES})", type_ids.len()).into());
+        }
+
+        let outcomes: Vec<(i32, Result<ScanResult>)> = stream::iter(type_ids.iter().copied())
+            .map(|type_id| async move { (type_id, self.scan_type(region_id, type_id).await) })
+            .buffer_unordered(SCAN_CONCURRENCY)
+            .collect()
+            .await;
+
+        let mut results = Vec::new();
+        let mut failures = Vec::new();
+        for (type_id, outcome) in outcomes {
+            match outcome {
+                Ok(result) => results.push(result),
+                Err(e) => failures.push((type_id, e.to_string())),
+            }
+        }
+        rank_results(&mut results, sort_by);
+        failures.sort_by_key(|(type_id, _)| *type_id);
+
+        Ok(MarketScan {
+            region_id,
+            sort_by,
+            results,
+            failures,
+        })
+    }
+
+    /// Scans every item type in a market group that passes a level filter
+    pub async fn scan_market_group(
+        &self,
+        region_id: i32,
+        market_group_id: i32,
+        sort_by: ScanSort,
+        filter: &LevelFilter,
+    ) -> Result<MarketScan> {
+        let group = self.fetch_market_group(market_group_id).await?;
+        if group.types.is_empty() {
+            return Err(format!("Market group {} ({}) has no item types", group.name, market_group_id).into());
+        }
+        let type_ids = self.filter_by_level(&group.types, filter).await?;
+        if type_ids.is_empty() {
+            return Err(
+                format!("No item types in market group {} ({}) are {filter}", group.name, market_group_id).into(),
+            );
+        }
+        self.scan_types(region_id, &type_ids, sort_by).await
+    }
+
+    /// Keeps the item types that pass a level filter, in their original order
+    ///
+    /// Levels come from the local SDE when loaded and from ESI type info
+    /// otherwise; types whose info can't be fetched are left out.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # use tradergrader::scan::LevelFilter;
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// let t2 = LevelFilter { tech_level: Some(2), ..LevelFilter::default() };
+    /// let kept = client.filter_by_level(&[2873, 2881, 2889], &t2).await?;
+    /// println!("{kept:?}");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn filter_by_level(&self, type_ids: &[i32], filter: &LevelFilter) -> Result<Vec<i32>> {
+        filter.validate()?;
+        if filter.is_empty() {
+            return Ok(type_ids.to_vec());
+        }
+        let kept: Vec<Option<i32>> = stream::iter(type_ids.iter().copied())
+            .map(|type_id| async move {
+                match self.fetch_type_info(type_id).await {
+                    Ok(info) => filter.matches(&info).then_some(type_id),
+                    Err(e) => {
+                        tracing::debug!("Skipping type {type_id} in level filter: {e}");
+                        None
+                    }
+                }
+            })
+            .buffered(SCAN_CONCURRENCY)
+            .collect()
+            .await;
+        Ok(kept.into_iter().flatten().collect())
+    }
+
+    /// Generates a formatted market scan table
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result, ScanSort};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// let scan = client.scan_types(10000002, &[34, 35, 36], ScanSort::Volume).await?;
+    /// println!("{}", MarketClient::format_market_scan(&scan, 10));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn format_market_scan(scan: &MarketScan, limit: usize) -> String {
+        let sort = match scan.sort_by {
+            ScanSort::SpreadPercent => "spread %",
+            ScanSort::Volume => "volume",
+            ScanSort::ProfitPotential => "profit potential",
+        };
+        let mut report = format!(
+            "Market Scan for Region {} ({} items, ranked by {}):\n",
+            scan.region_id,
+            scan.results.len(),
+            sort
+        );
+        for (rank, result) in scan.results.iter().take(limit).enumerate() {
+            report.push_str(&format!(
+                "\n{}. {}\n\
+                Buy: {} | Sell: {} | Spread: {}\n\
+                Avg Daily Volume: {:.0} units | Profit Potential: {:.2} ISK/day\n\
+                Orders: {} buy / {} sell\n",
+                rank + 1,
+                result.type_label,
+                format_price(result.best_buy),
+                format_price(result.best_sell),
+                result
+                    .spread_percent
+                    .map(|s| format!("{s:.2}%"))
+                    .unwrap_or_else(|| "n/a".to_string()),
+                result.avg_daily_volume,
+                result.profit_potential,
+                result.buy_orders,
+                result.sell_orders,
+            ));
+        }
+        if scan.results.len() > limit {
+            report.push_str(&format!("\n... and {} more\n", scan.results.len() - limit));
+        }
+        if !scan.failures.is_empty() {
+            let failed: Vec<String> = scan.failures.iter().map(|(id, e)| format!("{id} ({e})")).collect();
+            report.push_str(&format!("\nFailed: {}\n", failed.join(", ")));
+        }
+        report.trim_end().to_string()
+    }
+
+    /// Fetches orders and history for one type and summarizes them
+    async fn scan_type(&self, region_id: i32, type_id: i32) -> Result<ScanResult> {
+        let book = self.fetch_order_book(region_id, Some(type_id)).await?;
+        let history = self.fetch_market_history(region_id, type_id).await?;
+        let mut result = scan_result(type_id, &book, &history);
+        result.type_label = self.type_label(type_id).await;
+        Ok(result)
+    }
+}
+
+/// Summarizes one type's order book and recent history
+fn scan_result(type_id: i32, book: &MarketOrderBook, history: &[MarketHistory]) -> ScanResult {
+    let best_buy = book.best_buy().map(|o| o.price);
+    let best_sell = book.best_sell().map(|o| o.price);
+
+    let spread = match (best_buy, best_sell) {
+        (Some(buy), Some(sell)) => Some(sell - buy),
+        _ => None,
+    };
+    let spread_percent = match (spread, best_sell) {
+        (Some(spread), Some(sell)) if sell > 0.0 => Some(spread / sell * 100.0),
+        _ => None,
+    };
+
+    let avg_daily_volume = average_daily_volume(history);
+
+    ScanResult {
+        type_id,
+        type_label: format!("Type {type_id}"),
+        best_buy,
+        best_sell,
+        spread,
+        spread_percent,
+        buy_orders: book.buy_count(),
+        sell_orders: book.sell_count(),
+        avg_daily_volume,
+        profit_potential: spread.map_or(0.0, |s| s.max(0.0) * avg_daily_volume),
+    }
+}
+
+/// Average daily volume over the most recent [`VOLUME_WINDOW_DAYS`] days of history
+pub(crate) fn average_daily_volume(history: &[MarketHistory]) -> f64 {
+    let mut recent: Vec<&MarketHistory> = history.iter().collect();
+    recent.sort_by(|a, b| b.date.cmp(&a.date));
+    recent.truncate(VOLUME_WINDOW_DAYS);
+    if recent.is_empty() {
+        return 0.0;
+    }
+    recent.iter().map(|h| h.volume as f64).sum::<f64>() / recent.len() as f64
+}
+
+/// Sorts scan results best first; types without a spread sort last
+fn rank_results(results: &mut [ScanResult], sort_by: ScanSort) {
+    let key = |r: &ScanResult| match sort_by {
+        ScanSort::SpreadPercent => r.spread_percent.unwrap_or(f64::NEG_INFINITY),
+        ScanSort::Volume => r.avg_daily_volume,
+        ScanSort::ProfitPotential => r.profit_potential,
+    };
+    results.sort_by(|a, b| {
+        key(b)
+            .partial_cmp(&key(a))
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(a.type_id.cmp(&b.type_id))
+    });
+}
+
+/// Formats an optional price for the scan table
+fn format_price(price: Option<f64>) -> String {
+    price.map(|p| format!("{p:.2}")).unwrap_or_else(|| "-".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{history_day, market_order};
+    use crate::types::MarketOrder;
+
+    fn order(type_id: i32, is_buy_order: bool, price: f64) -> MarketOrder {
+        market_order(price as i64, is_buy_order, price).with_type(type_id).with_volume(1000)
+    }
+
+    #[test]
+    fn test_scan_result() {
+        let orders = vec![order(34, true, 4.0), order(34, true, 4.5), order(34, false, 5.0), order(34, false, 6.0)];
+        let history = vec![history_day("2024-01-01", 5.0, 100), history_day("2024-01-02", 5.0, 300)];
+        let result = scan_result(34, &MarketOrderBook::new(orders), &history);
+
+        assert_eq!(result.best_buy, Some(4.5));
+        assert_eq!(result.best_sell, Some(5.0));
+        assert_eq!(result.spread, Some(0.5));
+        assert_eq!(result.spread_percent, Some(10.0));
+        assert_eq!((result.buy_orders, result.sell_orders), (2, 2));
+        assert_eq!(result.avg_daily_volume, 200.0);
+        assert_eq!(result.profit_potential, 100.0);
+    }
+
+    #[test]
+    fn test_scan_result_one_sided_book() {
+        let result = scan_result(35, &MarketOrderBook::new(vec![order(35, false, 10.0)]), &[]);
+        assert_eq!(result.spread, None);
+        assert_eq!(result.spread_percent, None);
+        assert_eq!(result.profit_potential, 0.0);
+    }
+
+    #[test]
+    fn test_rank_results() {
+        let book = |orders: Vec<MarketOrder>| MarketOrderBook::new(orders);
+        let day = |volume: i64| [history_day("2024-01-01", 5.0, volume)];
+        let wide = scan_result(1, &book(vec![order(1, true, 5.0), order(1, false, 10.0)]), &day(10));
+        let busy = scan_result(2, &book(vec![order(2, true, 9.0), order(2, false, 10.0)]), &day(1000));
+        let empty = scan_result(3, &book(Vec::new()), &[]);
+        let mut results = vec![empty, wide, busy];
+
+        rank_results(&mut results, ScanSort::SpreadPercent);
+        assert_eq!(results.iter().map(|r| r.type_id).collect::<Vec<_>>(), vec![1, 2, 3]);
+
+        rank_results(&mut results, ScanSort::Volume);
+        assert_eq!(results[0].type_id, 2);
+
+        rank_results(&mut results, ScanSort::ProfitPotential);
+        assert_eq!(results.iter().map(|r| r.type_id).collect::<Vec<_>>(), vec![2, 1, 3]);
+
+        let scan = MarketScan {
+            region_id: 10000002,
+            sort_by: ScanSort::ProfitPotential,
+            results,
+            failures: vec![(4, "not found".to_string())],
+        };
+        let report = MarketClient::format_market_scan(&scan, 2);
+        assert!(report.contains("1. Type 2"));
+        assert!(report.contains("... and 1 more"));
+        assert!(report.contains("Failed: 4 (not found)"));
+    }
+
+    #[test]
+    fn test_level_filter() {
+        let item = |meta_level: Option<i32>, tech_level: Option<i32>| TypeInfo {
+            type_id: 1,
+            name: "Item".to_string(),
+            group_id: 1,
+            market_group_id: Some(1),
+            volume: None,
+            packaged_volume: None,
+            published: true,
+            meta_level,
+            tech_level,
+            dogma_attributes: Vec::new(),
+        };
+        let t2 = LevelFilter {
+            tech_level: Some(2),
+            ..LevelFilter::default()
+        };
+        assert!(t2.matches(&item(Some(5), Some(2))));
+        assert!(!t2.matches(&item(Some(0), None)));
+
+        let faction = LevelFilter {
+            min_meta_level: Some(6),
+            max_meta_level: Some(9),
+            tech_level: None,
+        };
+        assert!(faction.matches(&item(Some(8), Some(1))));
+        assert!(!faction.matches(&item(None, None)));
+        assert_eq!(faction.to_string(), "meta level 6-9");
+        assert!(LevelFilter::default().matches(&item(None, None)));
+
+        let inverted = LevelFilter {
+            min_meta_level: Some(5),
+            max_meta_level: Some(1),
+            tech_level: None,
+        };
+        assert!(inverted.validate().is_err());
+        assert!(LevelFilter { tech_level: Some(4), ..t2 }.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_scan_types_rejects_empty_input() {
+        let client = MarketClient::without_cache();
+        assert!(client.scan_types(10000002, &[], ScanSort::Volume).await.is_err());
+    }
+}
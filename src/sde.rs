@@ -0,0 +1,717 @@
+//! Local Static Data Export (SDE) lookups for TraderGrader
+//!
+//! CCP's Static Data Export holds item, region, solar system and station data
+//! that only changes with game patches. Loading a local copy gives instant,
+//! offline name, volume, meta level and tech level lookups, and the market client checks
+//! it before calling ESI, so bulk scans don't spend thousands of requests on
+//! names.
+//!
+//! Two formats are supported, both using the table and column names of the
+//! widely used Fuzzwork conversions:
+//!
+//! - a directory of CSV files (`invTypes.csv`, `mapRegions.csv`,
+//!   `mapSolarSystems.csv`, `staStations.csv`, and optionally `invVolumes.csv`,
+//!   `dgmTypeAttributes.csv` and the blueprint tables `industryActivity.csv`,
+//!   `industryActivityMaterials.csv` and `industryActivityProducts.csv`)
+//! - a SQLite database with the same tables (requires the opt-in `sde-sqlite`
+//!   feature, which compiles a bundled SQLite)
+//!
+//! Set `TRADERGRADER_SDE_PATH` to either to enable it for the MCP server.
+
+use crate::error::{Result, TraderGraderError};
+use crate::types::{Position, SystemInfo, TypeInfo, UniverseName};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Dogma attribute holding an item's meta level
+pub const META_LEVEL_ATTRIBUTE_ID: i32 = 633;
+
+/// Dogma attribute holding an item's tech level (1, 2 or 3)
+pub const TECH_LEVEL_ATTRIBUTE_ID: i32 = 422;
+
+/// Industry activity ID of manufacturing in the SDE blueprint tables
+pub const MANUFACTURING_ACTIVITY_ID: i32 = 1;
+
+/// An item type from the SDE
+#[derive(Debug, Clone, PartialEq)]
+pub struct SdeType {
+    pub type_id: i32,
+    pub name: String,
+    pub group_id: i32,
+    pub market_group_id: Option<i32>,
+    pub volume: Option<f64>,
+    /// Packaged volume, for types that shrink when packaged
+    pub packaged_volume: Option<f64>,
+    pub meta_level: Option<i32>,
+    pub tech_level: Option<i32>,
+    pub published: bool,
+}
+
+/// A solar system from the SDE
+#[derive(Debug, Clone, PartialEq)]
+pub struct SdeSystem {
+    pub system_id: i32,
+    pub name: String,
+    pub constellation_id: i32,
+    pub region_id: i32,
+    pub security_status: f64,
+    pub position: Position,
+}
+
+/// An NPC station from the SDE
+#[derive(Debug, Clone, PartialEq)]
+pub struct SdeStation {
+    pub station_id: i64,
+    pub name: String,
+    pub system_id: i32,
+    pub region_id: i32,
+}
+
+/// A blueprint's manufacturing activity from the SDE
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SdeBlueprint {
+    pub blueprint_type_id: i32,
+    pub product_type_id: i32,
+    /// Units produced by one run
+    pub product_quantity: i64,
+    /// Base duration of one run in seconds, before time efficiency
+    pub time: i64,
+    /// Materials for one run at ME 0, as (type ID, quantity)
+    pub materials: Vec<(i32, i64)>,
+}
+
+/// Static data loaded from a local SDE dump
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use tradergrader::MarketClient;
+/// use tradergrader::sde::StaticData;
+///
+/// let sde = StaticData::load("/data/sde/sqlite-latest.sqlite")?;
+/// println!("{} types loaded", sde.type_count());
+/// let client = MarketClient::new().with_static_data(Arc::new(sde));
+/// # Ok::<(), tradergrader::TraderGraderError>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct StaticData {
+    types: HashMap<i32, SdeType>,
+    regions: HashMap<i32, String>,
+    systems: HashMap<i32, SdeSystem>,
+    stations: HashMap<i64, SdeStation>,
+    blueprints: HashMap<i32, SdeBlueprint>,
+}
+
+impl StaticData {
+    /// Loads the SDE from `TRADERGRADER_SDE_PATH`, or returns `None` when it isn't set
+    pub fn from_env() -> Option<Result<Self>> {
+        let path = std::env::var("TRADERGRADER_SDE_PATH").ok()?;
+        if path.trim().is_empty() {
+            return None;
+        }
+        Some(Self::load(path.trim()))
+    }
+
+    /// Loads a CSV directory or a SQLite database, depending on what `path` is
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if path.is_dir() {
+            Self::from_csv_dir(path)
+        } else {
+            Self::from_sqlite(path)
+        }
+    }
+
+    /// Loads the SDE from a directory of Fuzzwork-style CSV files
+    pub fn from_csv_dir(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref();
+        let mut data = Self::default();
+
+        for_each_csv_row(&dir.join("invTypes.csv"), |row| {
+            let sde_type = SdeType {
+                type_id: row.i32("typeID")?,
+                name: row.string("typeName")?,
+                group_id: row.i32("groupID")?,
+                market_group_id: row.opt_i32("marketGroupID"),
+                volume: row.opt_f64("volume"),
+                packaged_volume: None,
+                meta_level: None,
+                tech_level: None,
+                published: row.opt_i32("published") == Some(1),
+            };
+            data.types.insert(sde_type.type_id, sde_type);
+            Ok(())
+        })?;
+
+        let volumes = dir.join("invVolumes.csv");
+        if volumes.exists() {
+            for_each_csv_row(&volumes, |row| {
+                data.set_packaged_volume(row.i32("typeID")?, row.opt_f64("volume"));
+                Ok(())
+            })?;
+        }
+
+        let attributes = dir.join("dgmTypeAttributes.csv");
+        if attributes.exists() {
+            for_each_csv_row(&attributes, |row| {
+                let attribute_id = row.opt_i32("attributeID");
+                if attribute_id == Some(META_LEVEL_ATTRIBUTE_ID) || attribute_id == Some(TECH_LEVEL_ATTRIBUTE_ID) {
+                    let level = row.opt_i32("valueInt").or_else(|| row.opt_f64("valueFloat").map(|v| v as i32));
+                    data.set_level(row.i32("typeID")?, attribute_id.unwrap_or_default(), level);
+                }
+                Ok(())
+            })?;
+        }
+
+        for_each_csv_row(&dir.join("mapRegions.csv"), |row| {
+            data.regions.insert(row.i32("regionID")?, row.string("regionName")?);
+            Ok(())
+        })?;
+
+        for_each_csv_row(&dir.join("mapSolarSystems.csv"), |row| {
+            let system = SdeSystem {
+                system_id: row.i32("solarSystemID")?,
+                name: row.string("solarSystemName")?,
+                constellation_id: row.i32("constellationID")?,
+                region_id: row.i32("regionID")?,
+                security_status: row.opt_f64("security").unwrap_or(0.0),
+                position: Position {
+                    x: row.opt_f64("x").unwrap_or(0.0),
+                    y: row.opt_f64("y").unwrap_or(0.0),
+                    z: row.opt_f64("z").unwrap_or(0.0),
+                },
+            };
+            data.systems.insert(system.system_id, system);
+            Ok(())
+        })?;
+
+        for_each_csv_row(&dir.join("staStations.csv"), |row| {
+            let station = SdeStation {
+                station_id: row.i64("stationID")?,
+                name: row.string("stationName")?,
+                system_id: row.i32("solarSystemID")?,
+                region_id: row.i32("regionID")?,
+            };
+            data.stations.insert(station.station_id, station);
+            Ok(())
+        })?;
+
+        // Blueprint tables are optional; only manufacturing is loaded
+        let products = dir.join("industryActivityProducts.csv");
+        if products.exists() {
+            for_each_csv_row(&products, |row| {
+                if row.opt_i32("activityID") == Some(MANUFACTURING_ACTIVITY_ID) {
+                    let blueprint = data.blueprint_mut(row.i32("typeID")?);
+                    blueprint.product_type_id = row.i32("productTypeID")?;
+                    blueprint.product_quantity = row.i64("quantity")?;
+                }
+                Ok(())
+            })?;
+        }
+        let materials = dir.join("industryActivityMaterials.csv");
+        if materials.exists() {
+            for_each_csv_row(&materials, |row| {
+                if row.opt_i32("activityID") == Some(MANUFACTURING_ACTIVITY_ID) {
+                    let material = (row.i32("materialTypeID")?, row.i64("quantity")?);
+                    data.blueprint_mut(row.i32("typeID")?).materials.push(material);
+                }
+                Ok(())
+            })?;
+        }
+        let activities = dir.join("industryActivity.csv");
+        if activities.exists() {
+            for_each_csv_row(&activities, |row| {
+                if row.opt_i32("activityID") == Some(MANUFACTURING_ACTIVITY_ID) {
+                    data.blueprint_mut(row.i32("typeID")?).time = row.i64("time")?;
+                }
+                Ok(())
+            })?;
+        }
+        data.drop_productless_blueprints();
+
+        Ok(data)
+    }
+
+    /// Loads the SDE from a Fuzzwork-style SQLite database
+    #[cfg(feature = "sde-sqlite")]
+    pub fn from_sqlite(path: impl AsRef<Path>) -> Result<Self> {
+        use rusqlite::{Connection, OpenFlags};
+
+        let sql_error = |e: rusqlite::Error| TraderGraderError::InternalError(format!("SDE database error: {e}"));
+        let conn = Connection::open_with_flags(path.as_ref(), OpenFlags::SQLITE_OPEN_READ_ONLY).map_err(sql_error)?;
+        let mut data = Self::default();
+
+        let mut stmt = conn
+            .prepare("SELECT typeID, typeName, groupID, marketGroupID, volume, published FROM invTypes")
+            .map_err(sql_error)?;
+        let types = stmt
+            .query_map([], |row| {
+                Ok(SdeType {
+                    type_id: row.get(0)?,
+                    name: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+                    group_id: row.get(2)?,
+                    market_group_id: row.get(3)?,
+                    volume: row.get(4)?,
+                    packaged_volume: None,
+                    meta_level: None,
+                    tech_level: None,
+                    published: row.get::<_, Option<i64>>(5)? == Some(1),
+                })
+            })
+            .map_err(sql_error)?;
+        for sde_type in types {
+            let sde_type = sde_type.map_err(sql_error)?;
+            data.types.insert(sde_type.type_id, sde_type);
+        }
+
+        // Packaged volumes and meta levels are optional tables
+        if let Ok(mut stmt) = conn.prepare("SELECT typeID, volume FROM invVolumes") {
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?))).map_err(sql_error)?;
+            for row in rows {
+                let (type_id, volume): (i32, Option<f64>) = row.map_err(sql_error)?;
+                data.set_packaged_volume(type_id, volume);
+            }
+        }
+        if let Ok(mut stmt) = conn.prepare(
+            "SELECT typeID, attributeID, COALESCE(valueInt, CAST(valueFloat AS INTEGER)) FROM dgmTypeAttributes \
+             WHERE attributeID IN (?1, ?2)",
+        ) {
+            let rows = stmt
+                .query_map([META_LEVEL_ATTRIBUTE_ID, TECH_LEVEL_ATTRIBUTE_ID], |row| {
+                    Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+                })
+                .map_err(sql_error)?;
+            for row in rows {
+                let (type_id, attribute_id, level): (i32, i32, Option<i32>) = row.map_err(sql_error)?;
+                data.set_level(type_id, attribute_id, level);
+            }
+        }
+
+        let mut stmt = conn.prepare("SELECT regionID, regionName FROM mapRegions").map_err(sql_error)?;
+        let regions = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?))).map_err(sql_error)?;
+        for region in regions {
+            let (region_id, name) = region.map_err(sql_error)?;
+            data.regions.insert(region_id, name);
+        }
+
+        let mut stmt = conn
+            .prepare("SELECT solarSystemID, solarSystemName, constellationID, regionID, security, x, y, z FROM mapSolarSystems")
+            .map_err(sql_error)?;
+        let systems = stmt
+            .query_map([], |row| {
+                Ok(SdeSystem {
+                    system_id: row.get(0)?,
+                    name: row.get(1)?,
+                    constellation_id: row.get(2)?,
+                    region_id: row.get(3)?,
+                    security_status: row.get::<_, Option<f64>>(4)?.unwrap_or(0.0),
+                    position: Position {
+                        x: row.get::<_, Option<f64>>(5)?.unwrap_or(0.0),
+                        y: row.get::<_, Option<f64>>(6)?.unwrap_or(0.0),
+                        z: row.get::<_, Option<f64>>(7)?.unwrap_or(0.0),
+                    },
+                })
+            })
+            .map_err(sql_error)?;
+        for system in systems {
+            let system = system.map_err(sql_error)?;
+            data.systems.insert(system.system_id, system);
+        }
+
+        let mut stmt = conn
+            .prepare("SELECT stationID, stationName, solarSystemID, regionID FROM staStations")
+            .map_err(sql_error)?;
+        let stations = stmt
+            .query_map([], |row| {
+                Ok(SdeStation {
+                    station_id: row.get(0)?,
+                    name: row.get(1)?,
+                    system_id: row.get(2)?,
+                    region_id: row.get(3)?,
+                })
+            })
+            .map_err(sql_error)?;
+        for station in stations {
+            let station = station.map_err(sql_error)?;
+            data.stations.insert(station.station_id, station);
+        }
+
+        // Blueprint tables are optional too; only manufacturing is loaded
+        if let Ok(mut stmt) =
+            conn.prepare("SELECT typeID, productTypeID, quantity FROM industryActivityProducts WHERE activityID = ?1")
+        {
+            let rows = stmt
+                .query_map([MANUFACTURING_ACTIVITY_ID], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+                .map_err(sql_error)?;
+            for row in rows {
+                let (blueprint_type_id, product_type_id, quantity): (i32, i32, i64) = row.map_err(sql_error)?;
+                let blueprint = data.blueprint_mut(blueprint_type_id);
+                blueprint.product_type_id = product_type_id;
+                blueprint.product_quantity = quantity;
+            }
+        }
+        if let Ok(mut stmt) =
+            conn.prepare("SELECT typeID, materialTypeID, quantity FROM industryActivityMaterials WHERE activityID = ?1")
+        {
+            let rows = stmt
+                .query_map([MANUFACTURING_ACTIVITY_ID], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+                .map_err(sql_error)?;
+            for row in rows {
+                let (blueprint_type_id, material_type_id, quantity): (i32, i32, i64) = row.map_err(sql_error)?;
+                data.blueprint_mut(blueprint_type_id).materials.push((material_type_id, quantity));
+            }
+        }
+        if let Ok(mut stmt) = conn.prepare("SELECT typeID, time FROM industryActivity WHERE activityID = ?1") {
+            let rows = stmt
+                .query_map([MANUFACTURING_ACTIVITY_ID], |row| Ok((row.get(0)?, row.get(1)?)))
+                .map_err(sql_error)?;
+            for row in rows {
+                let (blueprint_type_id, time): (i32, i64) = row.map_err(sql_error)?;
+                data.blueprint_mut(blueprint_type_id).time = time;
+            }
+        }
+        data.drop_productless_blueprints();
+
+        Ok(data)
+    }
+
+    /// Loads the SDE from a SQLite database (unavailable without the `sde-sqlite` feature)
+    #[cfg(not(feature = "sde-sqlite"))]
+    pub fn from_sqlite(path: impl AsRef<Path>) -> Result<Self> {
+        Err(TraderGraderError::InternalError(format!(
+            "Cannot load {}: SQLite SDE support requires the sde-sqlite feature; use a CSV directory instead",
+            path.as_ref().display()
+        )))
+    }
+
+    /// Number of item types loaded
+    pub fn type_count(&self) -> usize {
+        self.types.len()
+    }
+
+    /// Every item type loaded, in no particular order
+    pub fn types(&self) -> impl Iterator<Item = &SdeType> {
+        self.types.values()
+    }
+
+    /// Looks up an item type
+    pub fn get_type(&self, type_id: i32) -> Option<&SdeType> {
+        self.types.get(&type_id)
+    }
+
+    /// Looks up an item type in the shape ESI returns it
+    pub fn type_info(&self, type_id: i32) -> Option<TypeInfo> {
+        self.types.get(&type_id).map(|t| TypeInfo {
+            type_id: t.type_id,
+            name: t.name.clone(),
+            group_id: t.group_id,
+            market_group_id: t.market_group_id,
+            volume: t.volume,
+            packaged_volume: t.packaged_volume,
+            published: t.published,
+            meta_level: t.meta_level,
+            tech_level: t.tech_level,
+            dogma_attributes: Vec::new(),
+        })
+    }
+
+    /// Looks up a region's name
+    pub fn region_name(&self, region_id: i32) -> Option<&str> {
+        self.regions.get(&region_id).map(String::as_str)
+    }
+
+    /// Looks up a solar system
+    pub fn get_system(&self, system_id: i32) -> Option<&SdeSystem> {
+        self.systems.get(&system_id)
+    }
+
+    /// Looks up a solar system in the shape ESI returns it, including its stations
+    pub fn system_info(&self, system_id: i32) -> Option<SystemInfo> {
+        self.systems.get(&system_id).map(|s| {
+            let mut stations: Vec<i64> = self
+                .stations
+                .values()
+                .filter(|station| station.system_id == system_id)
+                .map(|station| station.station_id)
+                .collect();
+            stations.sort_unstable();
+            SystemInfo {
+                system_id: s.system_id,
+                name: s.name.clone(),
+                constellation_id: s.constellation_id,
+                security_status: s.security_status,
+                position: s.position,
+                stations,
+            }
+        })
+    }
+
+    /// Looks up an NPC station
+    pub fn get_station(&self, station_id: i64) -> Option<&SdeStation> {
+        self.stations.get(&station_id)
+    }
+
+    /// Resolves an ID to a name the way ESI `/universe/names/` would
+    pub fn name(&self, id: i64) -> Option<UniverseName> {
+        let resolved = |name: &str, category: &str| UniverseName {
+            id,
+            name: name.to_string(),
+            category: category.to_string(),
+        };
+        if let Some(station) = self.stations.get(&id) {
+            return Some(resolved(&station.name, "station"));
+        }
+        let id32 = i32::try_from(id).ok()?;
+        if let Some(t) = self.types.get(&id32) {
+            return Some(resolved(&t.name, "inventory_type"));
+        }
+        if let Some(system) = self.systems.get(&id32) {
+            return Some(resolved(&system.name, "solar_system"));
+        }
+        self.regions.get(&id32).map(|name| resolved(name, "region"))
+    }
+
+    /// Number of manufacturing blueprints loaded
+    pub fn blueprint_count(&self) -> usize {
+        self.blueprints.len()
+    }
+
+    /// Looks up a blueprint's manufacturing activity
+    pub fn get_blueprint(&self, blueprint_type_id: i32) -> Option<&SdeBlueprint> {
+        self.blueprints.get(&blueprint_type_id)
+    }
+
+    /// Finds the blueprint that manufactures a product
+    pub fn blueprint_for_product(&self, product_type_id: i32) -> Option<&SdeBlueprint> {
+        self.blueprints.values().find(|b| b.product_type_id == product_type_id)
+    }
+
+    fn blueprint_mut(&mut self, blueprint_type_id: i32) -> &mut SdeBlueprint {
+        self.blueprints.entry(blueprint_type_id).or_insert_with(|| SdeBlueprint {
+            blueprint_type_id,
+            ..SdeBlueprint::default()
+        })
+    }
+
+    /// Drops blueprints that list materials but no manufacturing product
+    fn drop_productless_blueprints(&mut self) {
+        self.blueprints.retain(|_, b| b.product_type_id != 0 && b.product_quantity > 0);
+    }
+
+    fn set_packaged_volume(&mut self, type_id: i32, volume: Option<f64>) {
+        if let Some(t) = self.types.get_mut(&type_id) {
+            t.packaged_volume = volume;
+        }
+    }
+
+    /// Sets the meta or tech level, whichever `attribute_id` holds
+    fn set_level(&mut self, type_id: i32, attribute_id: i32, level: Option<i32>) {
+        if let Some(t) = self.types.get_mut(&type_id) {
+            match attribute_id {
+                META_LEVEL_ATTRIBUTE_ID => t.meta_level = level,
+                TECH_LEVEL_ATTRIBUTE_ID => t.tech_level = level,
+                _ => {}
+            }
+        }
+    }
+}
+
+/// One CSV row with access by column name
+struct CsvRow<'a> {
+    columns: &'a HashMap<String, usize>,
+    record: &'a csv::StringRecord,
+}
+
+impl CsvRow<'_> {
+    /// Raw value of a column; empty and `None` cells count as missing
+    fn raw(&self, column: &str) -> Option<&str> {
+        let value = self.record.get(*self.columns.get(column)?)?.trim();
+        (!value.is_empty() && value != "None" && value != "\\N").then_some(value)
+    }
+
+    fn string(&self, column: &str) -> Result<String> {
+        self.raw(column)
+            .map(str::to_string)
+            .ok_or_else(|| TraderGraderError::InternalError(format!("SDE row is missing {column}")))
+    }
+
+    fn i32(&self, column: &str) -> Result<i32> {
+        self.opt_i32(column)
+            .ok_or_else(|| TraderGraderError::InternalError(format!("SDE row has no valid {column}")))
+    }
+
+    fn i64(&self, column: &str) -> Result<i64> {
+        self.raw(column)
+            .and_then(|v| v.parse().ok())
+            .ok_or_else(|| TraderGraderError::InternalError(format!("SDE row has no valid {column}")))
+    }
+
+    fn opt_i32(&self, column: &str) -> Option<i32> {
+        self.raw(column).and_then(|v| v.parse().ok())
+    }
+
+    fn opt_f64(&self, column: &str) -> Option<f64> {
+        self.raw(column).and_then(|v| v.parse().ok())
+    }
+}
+
+/// Calls `f` for every row of a CSV file with a header line
+fn for_each_csv_row(path: &Path, mut f: impl FnMut(&CsvRow) -> Result<()>) -> Result<()> {
+    let csv_error = |e: csv::Error| TraderGraderError::InternalError(format!("Failed to read {}: {e}", path.display()));
+    let mut reader = csv::Reader::from_path(path).map_err(csv_error)?;
+    let columns: HashMap<String, usize> = reader
+        .headers()
+        .map_err(csv_error)?
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (name.to_string(), i))
+        .collect();
+
+    let mut record = csv::StringRecord::new();
+    while reader.read_record(&mut record).map_err(csv_error)? {
+        f(&CsvRow {
+            columns: &columns,
+            record: &record,
+        })?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("tradergrader-sde-{name}-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write_csv_sde(dir: &Path) {
+        fs::write(
+            dir.join("invTypes.csv"),
+            "typeID,groupID,typeName,volume,published,marketGroupID\n\
+            34,18,Tritanium,0.01,1,1857\n\
+            587,25,Rifter,27289,1,64\n\
+            2046,1,Unpublished Thing,1,0,None\n",
+        )
+        .unwrap();
+        fs::write(dir.join("invVolumes.csv"), "typeID,volume\n587,2500\n").unwrap();
+        fs::write(
+            dir.join("dgmTypeAttributes.csv"),
+            "typeID,attributeID,valueInt,valueFloat\n587,633,None,0.0\n587,422,1,None\n587,4,None,1067000\n",
+        )
+        .unwrap();
+        fs::write(dir.join("mapRegions.csv"), "regionID,regionName\n10000002,The Forge\n").unwrap();
+        fs::write(
+            dir.join("mapSolarSystems.csv"),
+            "regionID,constellationID,solarSystemID,solarSystemName,x,y,z,security\n\
+            10000002,20000020,30000142,Jita,-1.29e17,6.07e16,1.17e17,0.9459\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("staStations.csv"),
+            "stationID,solarSystemID,regionID,stationName\n\
+            60003760,30000142,10000002,Jita IV - Moon 4 - Caldari Navy Assembly Plant\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("industryActivityProducts.csv"),
+            "typeID,activityID,productTypeID,quantity\n691,1,587,1\n691,8,691,1\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("industryActivityMaterials.csv"),
+            "typeID,activityID,materialTypeID,quantity\n691,1,34,32000\n691,1,35,6000\n691,5,34,10\n999,1,34,5\n",
+        )
+        .unwrap();
+        fs::write(dir.join("industryActivity.csv"), "typeID,activityID,time\n691,1,6000\n691,3,4200\n").unwrap();
+    }
+
+    #[test]
+    fn test_load_csv_directory() {
+        let dir = temp_dir("csv");
+        write_csv_sde(&dir);
+        let sde = StaticData::load(&dir).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(sde.type_count(), 3);
+        let rifter = sde.type_info(587).unwrap();
+        assert_eq!(rifter.cargo_volume(), 2500.0);
+        assert_eq!(rifter.meta_level, Some(0));
+        assert_eq!(rifter.tech_level, Some(1));
+        assert_eq!(sde.get_type(34).unwrap().tech_level, None);
+        assert_eq!(sde.get_type(2046).unwrap().market_group_id, None);
+        assert!(!sde.get_type(2046).unwrap().published);
+
+        assert_eq!(sde.region_name(10000002), Some("The Forge"));
+        let jita = sde.system_info(30000142).unwrap();
+        assert_eq!(jita.name, "Jita");
+        assert_eq!(jita.stations, vec![60003760]);
+        assert_eq!(sde.get_system(30000142).unwrap().region_id, 10000002);
+
+        assert_eq!(sde.name(34).unwrap().category, "inventory_type");
+        assert_eq!(sde.name(60003760).unwrap().category, "station");
+        assert_eq!(sde.name(30000142).unwrap().name, "Jita");
+        assert!(sde.name(1035466617946).is_none());
+
+        // Only manufacturing blueprints with a product are kept
+        assert_eq!(sde.blueprint_count(), 1);
+        let rifter_blueprint = sde.blueprint_for_product(587).unwrap();
+        assert_eq!(rifter_blueprint.blueprint_type_id, 691);
+        assert_eq!(rifter_blueprint.time, 6000);
+        assert_eq!(rifter_blueprint.materials, vec![(34, 32000), (35, 6000)]);
+        assert_eq!(sde.get_blueprint(691), Some(rifter_blueprint));
+        assert!(sde.get_blueprint(999).is_none());
+    }
+
+    #[test]
+    fn test_missing_csv_is_an_error() {
+        let dir = temp_dir("empty");
+        let result = StaticData::from_csv_dir(&dir);
+        fs::remove_dir_all(&dir).ok();
+        assert!(result.is_err());
+    }
+
+    #[cfg(feature = "sde-sqlite")]
+    #[test]
+    fn test_load_sqlite_database() {
+        let dir = temp_dir("sqlite");
+        let path = dir.join("sde.sqlite");
+        let conn = rusqlite::Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE invTypes (typeID INTEGER, groupID INTEGER, typeName TEXT, volume REAL, published INTEGER, marketGroupID INTEGER);
+            INSERT INTO invTypes VALUES (34, 18, 'Tritanium', 0.01, 1, 1857);
+            CREATE TABLE dgmTypeAttributes (typeID INTEGER, attributeID INTEGER, valueInt INTEGER, valueFloat REAL);
+            INSERT INTO dgmTypeAttributes VALUES (34, 633, NULL, 0.0), (34, 422, 1, NULL);
+            CREATE TABLE mapRegions (regionID INTEGER, regionName TEXT);
+            INSERT INTO mapRegions VALUES (10000002, 'The Forge');
+            CREATE TABLE mapSolarSystems (regionID INTEGER, constellationID INTEGER, solarSystemID INTEGER, solarSystemName TEXT, x REAL, y REAL, z REAL, security REAL);
+            INSERT INTO mapSolarSystems VALUES (10000002, 20000020, 30000142, 'Jita', 0, 0, 0, 0.9459);
+            CREATE TABLE staStations (stationID INTEGER, solarSystemID INTEGER, regionID INTEGER, stationName TEXT);
+            INSERT INTO staStations VALUES (60003760, 30000142, 10000002, 'Jita IV - Moon 4 - Caldari Navy Assembly Plant');
+            CREATE TABLE industryActivityProducts (typeID INTEGER, activityID INTEGER, productTypeID INTEGER, quantity INTEGER);
+            INSERT INTO industryActivityProducts VALUES (691, 1, 587, 1);
+            CREATE TABLE industryActivityMaterials (typeID INTEGER, activityID INTEGER, materialTypeID INTEGER, quantity INTEGER);
+            INSERT INTO industryActivityMaterials VALUES (691, 1, 34, 32000);",
+        )
+        .unwrap();
+        drop(conn);
+
+        let sde = StaticData::load(&path).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(sde.type_info(34).unwrap().name, "Tritanium");
+        assert_eq!(sde.type_info(34).unwrap().packaged_volume, None);
+        assert_eq!(sde.type_info(34).unwrap().meta_level, Some(0));
+        assert_eq!(sde.type_info(34).unwrap().tech_level, Some(1));
+        assert_eq!(sde.region_name(10000002), Some("The Forge"));
+        assert_eq!(sde.get_station(60003760).unwrap().system_id, 30000142);
+        let blueprint = sde.blueprint_for_product(587).unwrap();
+        assert_eq!(blueprint.materials, vec![(34, 32000)]);
+        assert_eq!(blueprint.time, 0);
+    }
+}
@@ -0,0 +1,318 @@
+//! Item search for TraderGrader
+//!
+//! Every market tool wants a type ID, but traders know items by name, and
+//! don't always spell it right. ESI's `/universe/ids/` resolves exact names
+//! only, so with a local SDE loaded every market item's name is also scored
+//! against the query: exact and prefix matches first, then whole words, then
+//! names within a few typos of the query by edit distance, so "tritanum"
+//! still finds Tritanium. Each match comes with its market group, which
+//! tells "Tritanium" the mineral from the skins and blueprints named after it.
+
+use crate::error::{Result, TraderGraderError};
+use crate::market::MarketClient;
+use crate::scan::SCAN_CONCURRENCY;
+use crate::types::{ItemMatch, ItemSearch};
+use futures::stream::{self, StreamExt};
+use serde::Deserialize;
+
+/// Matches returned unless the caller asks for more
+pub const DEFAULT_SEARCH_LIMIT: usize = 10;
+
+/// Most matches one search returns
+pub const MAX_SEARCH_LIMIT: usize = 50;
+
+/// Lowest score a name needs to be listed
+pub const MIN_MATCH_SCORE: f64 = 0.6;
+
+/// Shortest query searched; shorter ones match half the item database
+pub const MIN_QUERY_CHARS: usize = 3;
+
+/// Scores how well an item name matches a search query, from 0 to 1
+///
+/// Case and spacing are ignored. Exact names score 1, names starting with the
+/// query about 0.9, names containing it about 0.85, and names only close to
+/// it by edit distance at most 0.8. Below [`MIN_MATCH_SCORE`] there is no match.
+///
+/// # Examples
+///
+/// ```
+/// use tradergrader::search::match_score;
+///
+/// assert_eq!(match_score("tritanium", "Tritanium"), Some(1.0));
+/// // One letter missing still matches
+/// assert!(match_score("tritanum", "Tritanium").unwrap() > 0.7);
+/// // A word of a longer name matches, ranked under an exact name
+/// assert!(match_score("injector", "Large Skill Injector").unwrap() < 1.0);
+/// assert!(match_score("plex", "Pilot's Body Resculpt Certificate").is_none());
+/// ```
+pub fn match_score(query: &str, name: &str) -> Option<f64> {
+    let query = normalize(query);
+    let name = normalize(name);
+    if query.is_empty() || name.is_empty() {
+        return None;
+    }
+    if name == query {
+        return Some(1.0);
+    }
+    // How much of the name the query covers, to rank shorter names first
+    let coverage = query.chars().count() as f64 / name.chars().count() as f64;
+    if name.starts_with(&query) {
+        return Some(0.9 + 0.05 * coverage);
+    }
+    if name.contains(&query) {
+        let at_word = name.split(' ').any(|word| word.starts_with(query.split(' ').next().unwrap_or_default()));
+        return Some(if at_word { 0.85 } else { 0.75 } + 0.05 * coverage);
+    }
+
+    // Typos: compare the query with every run of as many words in the name
+    let words: Vec<&str> = name.split(' ').collect();
+    let width = query.split(' ').count().min(words.len());
+    let best = words
+        .windows(width)
+        .map(|window| similarity(&query, &window.join(" ")))
+        .chain(std::iter::once(similarity(&query, &name)))
+        .fold(0.0, f64::max);
+    let score = 0.8 * best + 0.05 * coverage.min(1.0);
+    (score >= MIN_MATCH_SCORE).then_some(score)
+}
+
+/// Ranks candidate `(type_id, name)` pairs against a query, best first
+///
+/// Equal scores go to the shorter name, then the lower type ID.
+pub fn rank_matches<'a>(
+    query: &str,
+    candidates: impl IntoIterator<Item = (i32, &'a str)>,
+    limit: usize,
+) -> Vec<(i32, &'a str, f64)> {
+    let mut ranked: Vec<(i32, &str, f64)> = candidates
+        .into_iter()
+        .filter_map(|(type_id, name)| match_score(query, name).map(|score| (type_id, name, score)))
+        .collect();
+    ranked.sort_by(|a, b| b.2.total_cmp(&a.2).then(a.1.len().cmp(&b.1.len())).then(a.0.cmp(&b.0)));
+    ranked.truncate(limit);
+    ranked
+}
+
+/// Lowercases and collapses whitespace
+fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// 1 minus the edit distance relative to the longer string
+fn similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+    1.0 - edit_distance(&a, &b) as f64 / longest as f64
+}
+
+/// Levenshtein distance, counting a swap of neighbouring letters as one edit
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut rows = vec![(0..=b.len()).collect::<Vec<usize>>()];
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            row[j] = (rows[i - 1][j] + 1).min(row[j - 1] + 1).min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(rows[i - 2][j - 2] + 1);
+            }
+        }
+        rows.push(row);
+    }
+    rows[a.len()][b.len()]
+}
+
+/// The part of an ESI `/universe/ids/` response naming item types
+#[derive(Deserialize)]
+struct UniverseIds {
+    #[serde(default)]
+    inventory_types: Vec<IdName>,
+}
+
+#[derive(Deserialize)]
+struct IdName {
+    id: i32,
+    name: String,
+}
+
+impl MarketClient {
+    /// Finds items by name, tolerating typos when a local SDE is loaded
+    ///
+    /// ESI only finds exact names; without an SDE that is all a search can do,
+    /// which [`ItemSearch::fuzzy`] reports.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// let search = client.search_items("tritanum", 5).await?;
+    /// for found in &search.matches {
+    ///     println!("{} ({}) in {:?}", found.name, found.type_id, found.market_group);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn search_items(&self, query: &str, limit: usize) -> Result<ItemSearch> {
+        let query = query.trim();
+        if query.chars().count() < MIN_QUERY_CHARS {
+            return Err(TraderGraderError::InvalidArgument {
+                field: "query".to_string(),
+                reason: format!("must be at least {MIN_QUERY_CHARS} characters"),
+            });
+        }
+        let limit = limit.clamp(1, MAX_SEARCH_LIMIT);
+
+        let mut found: Vec<(i32, String, f64)> = Vec::new();
+        match self.fetch_exact_type_ids(query).await {
+            Ok(exact) => found.extend(exact.into_iter().map(|item| (item.id, item.name, 1.0))),
+            // The SDE can still answer when ESI can't
+            Err(e) if self.static_data().is_some() => tracing::debug!("ESI name lookup for '{query}' failed: {e}"),
+            Err(e) => return Err(e),
+        }
+        if let Some(sde) = self.static_data() {
+            let candidates = sde
+                .types()
+                .filter(|t| t.published && t.market_group_id.is_some())
+                .map(|t| (t.type_id, t.name.as_str()));
+            for (type_id, name, score) in rank_matches(query, candidates, limit) {
+                if !found.iter().any(|(id, _, _)| *id == type_id) {
+                    found.push((type_id, name.to_string(), score));
+                }
+            }
+        }
+        found.sort_by(|a, b| b.2.total_cmp(&a.2).then(a.1.len().cmp(&b.1.len())));
+        found.truncate(limit);
+
+        let matches = stream::iter(found)
+            .map(|(type_id, name, score)| async move {
+                let market_group_id = match self.static_data().and_then(|sde| sde.get_type(type_id)) {
+                    Some(sde_type) => sde_type.market_group_id,
+                    None => self.fetch_type_info(type_id).await.ok().and_then(|info| info.market_group_id),
+                };
+                let market_group = match market_group_id {
+                    Some(group_id) => self.fetch_market_group(group_id).await.ok().map(|group| group.name),
+                    None => None,
+                };
+                ItemMatch {
+                    type_id,
+                    name,
+                    score,
+                    market_group_id,
+                    market_group,
+                }
+            })
+            .buffered(SCAN_CONCURRENCY)
+            .collect()
+            .await;
+
+        Ok(ItemSearch {
+            query: query.to_string(),
+            matches,
+            fuzzy: self.static_data().is_some(),
+        })
+    }
+
+    /// Looks up items whose name is exactly `name` (ignoring case) with ESI `/universe/ids/`
+    async fn fetch_exact_type_ids(&self, name: &str) -> Result<Vec<IdName>> {
+        let response = self.esi().post_public("/universe/ids/", &[name]).await?;
+        if !response.status().is_success() {
+            return Err(self.rate_limiter().error_for_status(&response));
+        }
+        let ids: UniverseIds = self.esi().read_json(response).await?;
+        Ok(ids.inventory_types)
+    }
+}
+
+/// Formats search results, one line per match
+pub(crate) fn format_item_search(search: &ItemSearch) -> String {
+    if search.matches.is_empty() {
+        let mut text = format!("No items found for \"{}\".", search.query);
+        if !search.fuzzy {
+            text.push_str(
+                " Without a local SDE only exact names can be found; check the spelling or set TRADERGRADER_SDE_PATH \
+                 for typo-tolerant search.",
+            );
+        }
+        return text;
+    }
+
+    let mut text = format!("Items matching \"{}\":\n", search.query);
+    for found in &search.matches {
+        text.push_str(&format!("\n- {} (type_id {})", found.name, found.type_id));
+        if let Some(group) = &found.market_group {
+            text.push_str(&format!(" in {group}"));
+        } else if found.market_group_id.is_none() {
+            text.push_str(" - not sold on the market");
+        }
+        if found.score < 1.0 {
+            text.push_str(&format!(", {:.0}% match", found.score * 100.0));
+        }
+    }
+    if !search.fuzzy {
+        text.push_str("\n\nOnly exact names were searched; set TRADERGRADER_SDE_PATH for typo-tolerant search.");
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NAMES: [(i32, &str); 6] = [
+        (34, "Tritanium"),
+        (35, "Pyerite"),
+        (44992, "PLEX"),
+        (40520, "Large Skill Injector"),
+        (45635, "Small Skill Injector"),
+        (60001, "Tritanium Skin Crate"),
+    ];
+
+    #[test]
+    fn test_ranks_typos_and_partial_names() {
+        let ranked = rank_matches("tritanum", NAMES, 5);
+        assert_eq!(ranked[0].0, 34);
+        assert!(ranked.iter().all(|(type_id, _, _)| *type_id != 35));
+
+        // Exact names beat longer ones containing them
+        let ranked = rank_matches("TRITANIUM", NAMES, 5);
+        assert_eq!(ranked.iter().map(|r| r.0).collect::<Vec<_>>(), [34, 60001]);
+        assert_eq!(ranked[0].2, 1.0);
+
+        let ranked = rank_matches("skill injecter", NAMES, 5);
+        assert_eq!(ranked.len(), 2);
+        assert!(rank_matches("zzz", NAMES, 5).is_empty());
+        assert_eq!(edit_distance(&['a', 'b'], &['b', 'a']), 1);
+    }
+
+    #[test]
+    fn test_format() {
+        let search = ItemSearch {
+            query: "tritanum".to_string(),
+            matches: vec![ItemMatch {
+                type_id: 34,
+                name: "Tritanium".to_string(),
+                score: 0.76,
+                market_group_id: Some(1857),
+                market_group: Some("Minerals".to_string()),
+            }],
+            fuzzy: true,
+        };
+        assert_eq!(
+            format_item_search(&search),
+            "Items matching \"tritanum\":\n\n- Tritanium (type_id 34) in Minerals, 76% match"
+        );
+
+        let empty = ItemSearch {
+            matches: Vec::new(),
+            fuzzy: false,
+            ..search
+        };
+        assert!(format_item_search(&empty).contains("Without a local SDE only exact names"));
+    }
+}
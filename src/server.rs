@@ -1,9 +1,13 @@
 //! Standalone MCP server implementation
 
+use crate::config::TraderGraderConfig;
+use crate::error::Result;
+use crate::health::HealthLevel;
 use crate::mcp::McpHandler;
 use serde_json::Value;
-use std::io::{self, BufRead, Write, BufReader, BufWriter};
-use tokio::time::{timeout, Duration};
+use std::io::{self, Write, BufWriter};
+use tokio::io::AsyncBufReadExt;
+use tokio::time::{interval, Duration};
 
 /// Standalone MCP server that can handle persistent connections
 /// 
@@ -24,9 +28,40 @@ impl StandaloneMcpServer {
     /// let server = StandaloneMcpServer::new();
     /// ```
     pub fn new() -> Self {
-        Self {
-            handler: McpHandler::new("TraderGrader".to_string(), "0.1.0".to_string()),
-        }
+        Self::with_handler(McpHandler::new("TraderGrader".to_string(), "0.1.0".to_string()))
+    }
+
+    /// Creates a standalone MCP server from loaded configuration
+    /// 
+    /// # Examples
+    /// 
+    /// ```no_run
+    /// use tradergrader::StandaloneMcpServer;
+    /// use tradergrader::config::TraderGraderConfig;
+    /// 
+    /// let config = TraderGraderConfig::load(None)?;
+    /// let server = StandaloneMcpServer::from_config(&config)?;
+    /// # Ok::<(), tradergrader::TraderGraderError>(())
+    /// ```
+    pub fn from_config(config: &TraderGraderConfig) -> Result<Self> {
+        Ok(Self::with_handler(McpHandler::from_config(config)?))
+    }
+
+    /// Creates a standalone MCP server around an existing handler
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use tradergrader::{McpHandler, MarketClient, StandaloneMcpServer};
+    /// let handler = McpHandler::with_market_client(
+    ///     "TraderGrader".to_string(),
+    ///     "0.1.0".to_string(),
+    ///     MarketClient::without_cache(),
+    /// );
+    /// let server = StandaloneMcpServer::with_handler(handler);
+    /// ```
+    pub fn with_handler(handler: McpHandler) -> Self {
+        Self { handler }
     }
 
     /// Runs the MCP server with proper connection handling
@@ -54,81 +89,78 @@ impl StandaloneMcpServer {
     /// # }
     /// ```
     pub async fn run(&self) -> anyhow::Result<()> {
-        eprintln!("TraderGrader MCP Server starting on stdio...");
+        tracing::info!("TraderGrader MCP Server starting on stdio");
+
+        // Warm up ESI connections in the background to avoid a slow first tool call
+        let _ = self.handler.market_client.spawn_prewarm();
+        // Warn early when starting during downtime, and fill the status cache
+        drop(self.handler.market_client.spawn_status_check());
         
-        let stdin = io::stdin();
         let stdout = io::stdout();
-        let mut reader = BufReader::new(stdin.lock());
+        let mut reader = tokio::io::BufReader::new(tokio::io::stdin()).lines();
         let mut writer = BufWriter::new(stdout.lock());
-        
-        let mut line = String::new();
-        
+
+        // Notifications raised between requests, such as price alerts, go out on this tick
+        let mut notification_tick = interval(Duration::from_secs(1));
+
         loop {
-            line.clear();
-            
-            // Read with timeout to handle client disconnections
-            match timeout(Duration::from_secs(1), async {
-                reader.read_line(&mut line)
-            }).await {
-                Ok(Ok(0)) => {
-                    // EOF - client disconnected
-                    eprintln!("Client disconnected");
-                    break;
-                }
-                Ok(Ok(_)) => {
-                    if line.trim().is_empty() {
-                        continue;
+            tokio::select! {
+                line = reader.next_line() => match line {
+                    Ok(None) => {
+                        // EOF - client disconnected
+                        tracing::info!("Client disconnected");
+                        break;
                     }
-                    
-                    // Process the message
-                    match serde_json::from_str::<Value>(&line) {
-                        Ok(message) => {
-                            let response = self.handler.handle_message(message).await;
-                            
-                            // Only send response if it's not null (notifications return null)
-                            if !response.is_null() {
-                                if let Ok(response_str) = serde_json::to_string(&response) {
-                                    if writeln!(writer, "{response_str}").is_err() {
-                                        eprintln!("Failed to write response");
-                                        break;
-                                    }
-                                    if writer.flush().is_err() {
-                                        eprintln!("Failed to flush response");
-                                        break;
-                                    }
+                    Ok(Some(line)) => {
+                        if line.trim().is_empty() {
+                            continue;
+                        }
+
+                        // Process the message
+                        match serde_json::from_str::<Value>(&line) {
+                            Ok(message) => {
+                                let response = self.handler.handle_message(message).await;
+                                let mut outgoing = self.handler.drain_notifications();
+
+                                // Only send response if it's not null (notifications return null)
+                                if !response.is_null() {
+                                    outgoing.push(response);
+                                }
+                                if !write_messages(&mut writer, outgoing) {
+                                    break;
                                 }
                             }
-                        }
-                        Err(e) => {
-                            eprintln!("Failed to parse message: {e}");
+                            Err(e) => {
+                                tracing::warn!("Failed to parse message: {e}");
+                            }
                         }
                     }
-                }
-                Ok(Err(e)) => {
-                    eprintln!("IO error: {e}");
-                    break;
-                }
-                Err(_) => {
-                    // Timeout - continue loop to check for new input
-                    continue;
+                    Err(e) => {
+                        tracing::error!("IO error: {e}");
+                        break;
+                    }
+                },
+                _ = notification_tick.tick() => {
+                    if !write_messages(&mut writer, self.handler.drain_notifications()) {
+                        break;
+                    }
                 }
             }
         }
         
-        eprintln!("MCP Server shutting down");
+        tracing::info!("MCP Server shutting down");
         Ok(())
     }
 
-    /// Performs a simple health check of the MCP server
+    /// Checks the server's dependencies and prints the report
     /// 
-    /// This method verifies that the server components are properly
-    /// initialized and ready to handle requests. It's useful for
-    /// monitoring and deployment verification.
+    /// Exercises the cache, ESI and the rate limiter as the `health_check`
+    /// tool does. It's useful for monitoring and deployment verification.
     /// 
     /// # Returns
     /// 
-    /// Returns `Ok(())` if the server is healthy, or an error if
-    /// there are any issues with server components.
+    /// Returns `Ok(())` unless a component failed; warnings (a disabled
+    /// cache, ESI downtime) still count as healthy.
     /// 
     /// # Examples
     /// 
@@ -141,11 +173,32 @@ impl StandaloneMcpServer {
     /// # }
     /// ```
     pub async fn health_check(&self) -> anyhow::Result<()> {
-        println!("TraderGrader MCP Server is healthy");
+        let (level, report) = self.handler.health_check().await;
+        println!("{report}");
+        if level == HealthLevel::Fail {
+            anyhow::bail!("TraderGrader MCP Server is unhealthy");
+        }
         Ok(())
     }
 }
 
+/// Writes each message as a line, returning false once the client can't be written to
+fn write_messages(writer: &mut impl Write, messages: Vec<Value>) -> bool {
+    for message in messages {
+        if let Ok(response_str) = serde_json::to_string(&message) {
+            if writeln!(writer, "{response_str}").is_err() {
+                tracing::error!("Failed to write response");
+                return false;
+            }
+            if writer.flush().is_err() {
+                tracing::error!("Failed to flush response");
+                return false;
+            }
+        }
+    }
+    true
+}
+
 impl Default for StandaloneMcpServer {
     fn default() -> Self {
         Self::new()
@@ -0,0 +1,183 @@
+//! In-flight request coalescing for TraderGrader
+//!
+//! Batch scans and concurrent tool calls often ask for the same region and
+//! item at the same moment. Without coordination every caller misses the cache
+//! and goes to ESI. [`SingleFlight`] lets the first caller for a key do the
+//! fetch while later callers for the same key wait and share its result.
+//!
+//! Only successes are shared: if the leading fetch fails (or is cancelled),
+//! each waiting caller runs its own fetch, so errors keep their exact type.
+
+use crate::error::Result;
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::Mutex;
+use tokio::sync::watch;
+
+/// Coalesces concurrent fetches of the same key onto one future
+#[derive(Debug)]
+pub struct SingleFlight<K, V> {
+    in_flight: Mutex<HashMap<K, watch::Receiver<Option<V>>>>,
+}
+
+impl<K, V> Default for SingleFlight<K, V> {
+    fn default() -> Self {
+        Self {
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+/// Removes a leader's entry when its fetch finishes or is dropped
+struct LeaderGuard<'a, K: Hash + Eq, V> {
+    in_flight: &'a Mutex<HashMap<K, watch::Receiver<Option<V>>>>,
+    key: Option<K>,
+}
+
+impl<K: Hash + Eq, V> Drop for LeaderGuard<'_, K, V> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.in_flight.lock().unwrap_or_else(|e| e.into_inner()).remove(&key);
+        }
+    }
+}
+
+enum Role<V> {
+    Leader(watch::Sender<Option<V>>),
+    Follower(watch::Receiver<Option<V>>),
+}
+
+impl<K, V> SingleFlight<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    /// Create an empty coalescer
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of keys with a fetch in flight
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Runs `fetch` for `key`, unless a fetch for it is already running, in which case its result is shared
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::singleflight::SingleFlight;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let flights: SingleFlight<i32, String> = SingleFlight::new();
+    /// let slow_fetch = || async {
+    ///     tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    ///     Ok("Tritanium".to_string())
+    /// };
+    /// let (a, b) = tokio::join!(
+    ///     flights.run(34, slow_fetch),
+    ///     flights.run(34, || async { Ok("fetched twice".to_string()) }),
+    /// );
+    /// assert_eq!(a?, "Tritanium");
+    /// assert_eq!(b?, "Tritanium");
+    /// # Ok::<(), tradergrader::TraderGraderError>(())
+    /// # })?;
+    /// # Ok::<(), tradergrader::TraderGraderError>(())
+    /// ```
+    pub async fn run<F, Fut>(&self, key: K, fetch: F) -> Result<V>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<V>>,
+    {
+        let role = {
+            let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+            match in_flight.get(&key) {
+                Some(receiver) => Role::Follower(receiver.clone()),
+                None => {
+                    let (sender, receiver) = watch::channel(None);
+                    in_flight.insert(key.clone(), receiver);
+                    Role::Leader(sender)
+                }
+            }
+        };
+
+        match role {
+            Role::Leader(sender) => {
+                let guard = LeaderGuard {
+                    in_flight: &self.in_flight,
+                    key: Some(key),
+                };
+                let result = fetch().await;
+                if let Ok(value) = &result {
+                    let _ = sender.send(Some(value.clone()));
+                }
+                drop(guard);
+                result
+            }
+            Role::Follower(mut receiver) => {
+                let shared = match receiver.wait_for(Option::is_some).await {
+                    Ok(value) => value.clone(),
+                    // The leader failed or was cancelled
+                    Err(_) => None,
+                };
+                match shared {
+                    Some(value) => {
+                        tracing::debug!("coalesced with an in-flight request");
+                        Ok(value)
+                    }
+                    None => fetch().await,
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::TraderGraderError;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_concurrent_fetches_are_coalesced() {
+        let flights: SingleFlight<&str, Vec<i32>> = SingleFlight::new();
+        let fetches = AtomicUsize::new(0);
+        let fetch = || async {
+            fetches.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Ok(vec![34, 35])
+        };
+
+        let results = futures::future::join_all((0..5).map(|_| flights.run("orders:10000002", fetch))).await;
+        assert!(results.iter().all(|r| r.as_ref().unwrap() == &vec![34, 35]));
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+        assert_eq!(flights.in_flight(), 0);
+
+        // Once finished, the next call fetches again
+        flights.run("orders:10000002", fetch).await.unwrap();
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_failures_are_not_shared() {
+        let flights: SingleFlight<i32, i32> = SingleFlight::new();
+        let attempts = AtomicUsize::new(0);
+        let fetch = || async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            match attempts.fetch_add(1, Ordering::SeqCst) {
+                0 => Err(TraderGraderError::EsiApiError {
+                    message: "request failed with status: 502 Bad Gateway".to_string(),
+                }),
+                n => Ok(n as i32),
+            }
+        };
+
+        let (leader, follower) = tokio::join!(flights.run(1, fetch), flights.run(1, fetch));
+        assert!(matches!(leader, Err(TraderGraderError::EsiApiError { .. })));
+        assert_eq!(follower.unwrap(), 1);
+        assert_eq!(flights.in_flight(), 0);
+    }
+}
@@ -0,0 +1,348 @@
+//! Market snapshots for TraderGrader
+//!
+//! An overview of the market, like "how do the staples look in every hub right
+//! now", costs an order book and a history fetch per item per hub, which is
+//! dozens of ESI requests for one question. A [`MarketSnapshot`] collects those
+//! once for a configured basket across the trade hubs, and a
+//! [`SnapshotRefresher`] rebuilds it in the background alongside prefetching,
+//! so clients read one stored snapshot instead of triggering the fetches.
+
+use crate::error::{Result, TraderGraderError};
+use crate::flow::STAPLE_TYPE_IDS;
+use crate::hubs::TradeHub;
+use crate::market::MarketClient;
+use crate::scan::SCAN_CONCURRENCY;
+use crate::types::{HubComparison, MarketSnapshot, SnapshotItem};
+use chrono::Utc;
+use futures::stream::{self, StreamExt};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// Most items one snapshot may cover
+pub const MAX_SNAPSHOT_ITEMS: usize = 50;
+
+/// Default time between snapshot rebuilds
+pub const DEFAULT_SNAPSHOT_REFRESH: Duration = Duration::from_secs(900);
+
+/// Shortest time between rebuilds; order books are only cached for five minutes anyway
+pub const MIN_SNAPSHOT_REFRESH: Duration = Duration::from_secs(300);
+
+/// Which items and hubs a snapshot covers and how often it is rebuilt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotConfig {
+    /// Rebuild the snapshot in the background from startup
+    pub enabled: bool,
+    /// The basket of items, in report order
+    pub type_ids: Vec<i32>,
+    /// Hubs each item is quoted at
+    pub hubs: Vec<TradeHub>,
+    /// How old a snapshot may get before it is rebuilt
+    pub refresh_interval: Duration,
+}
+
+impl Default for SnapshotConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            type_ids: STAPLE_TYPE_IDS.to_vec(),
+            hubs: TradeHub::ALL.to_vec(),
+            refresh_interval: DEFAULT_SNAPSHOT_REFRESH,
+        }
+    }
+}
+
+impl SnapshotConfig {
+    /// Checks the basket and hub list are non-empty and the basket within [`MAX_SNAPSHOT_ITEMS`]
+    pub fn validate(&self) -> Result<()> {
+        if self.type_ids.is_empty() || self.type_ids.len() > MAX_SNAPSHOT_ITEMS {
+            return Err(TraderGraderError::InvalidArgument {
+                field: "type_ids".to_string(),
+                reason: format!("between 1 and {MAX_SNAPSHOT_ITEMS} items can be snapshotted"),
+            });
+        }
+        if self.hubs.is_empty() {
+            return Err(TraderGraderError::InvalidArgument {
+                field: "hubs".to_string(),
+                reason: "at least one trade hub is needed".to_string(),
+            });
+        }
+        if self.refresh_interval < MIN_SNAPSHOT_REFRESH {
+            return Err(TraderGraderError::InvalidArgument {
+                field: "refresh_secs".to_string(),
+                reason: format!("must be at least {}", MIN_SNAPSHOT_REFRESH.as_secs()),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl SnapshotItem {
+    /// Summarizes one item's hub comparison: where it is cheapest, where it sells best and how far prices spread
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::{HubComparison, HubQuote, SnapshotItem};
+    ///
+    /// let quote = |hub: &str, best_buy: f64, best_sell: f64| HubQuote {
+    ///     hub: hub.to_string(),
+    ///     region_id: 0,
+    ///     station_id: 0,
+    ///     best_buy: Some(best_buy),
+    ///     best_sell: Some(best_sell),
+    ///     spread: Some(best_sell - best_buy),
+    ///     spread_percent: None,
+    ///     sell_volume: 0,
+    ///     buy_volume: 0,
+    ///     avg_daily_volume: 0.0,
+    /// };
+    /// let comparison = HubComparison {
+    ///     type_id: 34,
+    ///     type_label: "Tritanium (34)".to_string(),
+    ///     quotes: vec![quote("Jita", 3.9, 4.0), quote("Amarr", 4.1, 5.0)],
+    ///     failures: Vec::new(),
+    /// };
+    ///
+    /// let item = SnapshotItem::from_comparison(comparison);
+    /// assert_eq!(item.cheapest_hub.as_deref(), Some("Jita"));
+    /// assert_eq!(item.best_bid_hub.as_deref(), Some("Amarr"));
+    /// assert_eq!(item.sell_gap_percent, Some(25.0));
+    /// ```
+    pub fn from_comparison(comparison: HubComparison) -> Self {
+        let sells = || comparison.quotes.iter().filter_map(|q| q.best_sell.map(|p| (q, p)));
+        let cheapest = sells().min_by(|a, b| a.1.total_cmp(&b.1));
+        let dearest = sells().max_by(|a, b| a.1.total_cmp(&b.1));
+        let best_bid = comparison
+            .quotes
+            .iter()
+            .filter_map(|q| q.best_buy.map(|p| (q, p)))
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        let sell_gap_percent = match (cheapest, dearest) {
+            (Some((_, low)), Some((_, high))) if low > 0.0 => Some((high - low) / low * 100.0),
+            _ => None,
+        };
+
+        Self {
+            cheapest_hub: cheapest.map(|(q, _)| q.hub.clone()),
+            best_bid_hub: best_bid.map(|(q, _)| q.hub.clone()),
+            sell_gap_percent,
+            type_id: comparison.type_id,
+            type_label: comparison.type_label,
+            quotes: comparison.quotes,
+            failures: comparison.failures,
+        }
+    }
+}
+
+impl MarketSnapshot {
+    /// How long ago the snapshot was built
+    pub fn age(&self) -> Duration {
+        (Utc::now() - self.generated_at).to_std().unwrap_or_default()
+    }
+}
+
+impl MarketClient {
+    /// Quotes every basket item at every configured hub
+    ///
+    /// Items are fetched concurrently and read through the cache, so a snapshot
+    /// built while prefetching is running costs few ESI requests. Hubs that
+    /// fail for an item are listed in that item's failures.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # use tradergrader::snapshot::SnapshotConfig;
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// let snapshot = client.market_snapshot(&SnapshotConfig::default()).await?;
+    /// for item in &snapshot.items {
+    ///     println!("{}: cheapest in {:?}", item.type_label, item.cheapest_hub);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn market_snapshot(&self, config: &SnapshotConfig) -> Result<MarketSnapshot> {
+        config.validate()?;
+        let items = stream::iter(config.type_ids.iter().copied())
+            .map(|type_id| self.compare_trade_hubs(type_id, &config.hubs))
+            .buffered(SCAN_CONCURRENCY)
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .map(|comparison| comparison.map(SnapshotItem::from_comparison))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(MarketSnapshot {
+            generated_at: Utc::now(),
+            hubs: config.hubs.iter().map(TradeHub::to_string).collect(),
+            items,
+        })
+    }
+}
+
+/// The most recent snapshot, shared between the refresher and its readers
+#[derive(Debug, Clone, Default)]
+pub struct SnapshotStore {
+    latest: Arc<Mutex<Option<MarketSnapshot>>>,
+}
+
+impl SnapshotStore {
+    /// The stored snapshot, if one has been built
+    pub fn latest(&self) -> Option<MarketSnapshot> {
+        self.latest.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// The stored snapshot if it is younger than `max_age`
+    pub fn fresh(&self, max_age: Duration) -> Option<MarketSnapshot> {
+        self.latest().filter(|snapshot| snapshot.age() < max_age)
+    }
+
+    /// Replaces the stored snapshot
+    pub fn store(&self, snapshot: MarketSnapshot) {
+        *self.latest.lock().unwrap_or_else(|e| e.into_inner()) = Some(snapshot);
+    }
+}
+
+/// A running background snapshot rebuild, stopped when dropped
+#[derive(Debug)]
+pub struct SnapshotRefresher {
+    task: JoinHandle<()>,
+}
+
+impl SnapshotRefresher {
+    /// Starts rebuilding the snapshot into `store` every `config.refresh_interval`
+    ///
+    /// Fails when the configuration is invalid or the client has no cache, since
+    /// without one every rebuild would hit ESI for the whole basket.
+    /// Must be called from within a Tokio runtime.
+    pub fn start(client: Arc<MarketClient>, config: SnapshotConfig, store: SnapshotStore) -> Result<Self> {
+        config.validate()?;
+        if !client.has_cache() {
+            return Err(TraderGraderError::CacheError {
+                message: "Market snapshots need the cache enabled".to_string(),
+            });
+        }
+        let task = tokio::spawn(async move {
+            loop {
+                match client.market_snapshot(&config).await {
+                    Ok(snapshot) => store.store(snapshot),
+                    Err(e) => tracing::warn!("Market snapshot refresh failed: {e}"),
+                }
+                tokio::time::sleep(config.refresh_interval).await;
+            }
+        });
+        Ok(Self { task })
+    }
+}
+
+impl Drop for SnapshotRefresher {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Formats a snapshot as one line per item with each hub's best sell price
+pub(crate) fn format_market_snapshot(snapshot: &MarketSnapshot) -> String {
+    let mut text = format!(
+        "Market Snapshot across {} ({} items, built {} UTC, {} min ago):\n",
+        snapshot.hubs.join(", "),
+        snapshot.items.len(),
+        snapshot.generated_at.format("%Y-%m-%d %H:%M"),
+        snapshot.age().as_secs() / 60
+    );
+    for item in &snapshot.items {
+        let prices: Vec<String> = item
+            .quotes
+            .iter()
+            .map(|quote| match quote.best_sell {
+                Some(price) => format!("{} {price:.2}", quote.hub),
+                None => format!("{} -", quote.hub),
+            })
+            .collect();
+        text.push_str(&format!("\n{}: {}\n", item.type_label, prices.join(" | ")));
+
+        let mut notes = Vec::new();
+        if let Some(hub) = &item.cheapest_hub {
+            notes.push(format!("cheapest in {hub}"));
+        }
+        if let Some(hub) = &item.best_bid_hub {
+            notes.push(format!("best bid in {hub}"));
+        }
+        if let Some(gap) = item.sell_gap_percent {
+            notes.push(format!("sell prices {gap:.1}% apart"));
+        }
+        if !item.failures.is_empty() {
+            let failed: Vec<&str> = item.failures.iter().map(|(hub, _)| hub.as_str()).collect();
+            notes.push(format!("failed: {}", failed.join(", ")));
+        }
+        if !notes.is_empty() {
+            text.push_str(&format!("  {}\n", notes.join("; ")));
+        }
+    }
+    text.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::HubQuote;
+
+    fn quote(hub: &str, best_buy: Option<f64>, best_sell: Option<f64>) -> HubQuote {
+        HubQuote {
+            hub: hub.to_string(),
+            region_id: 0,
+            station_id: 0,
+            best_buy,
+            best_sell,
+            spread: None,
+            spread_percent: None,
+            sell_volume: 0,
+            buy_volume: 0,
+            avg_daily_volume: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_config_validation() {
+        assert!(SnapshotConfig::default().validate().is_ok());
+        let empty = SnapshotConfig {
+            type_ids: Vec::new(),
+            ..SnapshotConfig::default()
+        };
+        assert!(empty.validate().is_err());
+        let eager = SnapshotConfig {
+            refresh_interval: Duration::from_secs(60),
+            ..SnapshotConfig::default()
+        };
+        assert!(eager.validate().is_err());
+    }
+
+    #[test]
+    fn test_store_and_format() {
+        let item = SnapshotItem::from_comparison(HubComparison {
+            type_id: 34,
+            type_label: "Tritanium (34)".to_string(),
+            quotes: vec![quote("Jita", Some(3.9), Some(4.0)), quote("Hek", None, None)],
+            failures: vec![("Rens".to_string(), "timed out".to_string())],
+        });
+        assert_eq!(item.sell_gap_percent, Some(0.0));
+
+        let store = SnapshotStore::default();
+        assert!(store.latest().is_none());
+        store.store(MarketSnapshot {
+            generated_at: Utc::now() - chrono::Duration::minutes(20),
+            hubs: vec!["Jita".to_string(), "Hek".to_string(), "Rens".to_string()],
+            items: vec![item],
+        });
+        assert!(store.fresh(DEFAULT_SNAPSHOT_REFRESH).is_none());
+        let snapshot = store.fresh(Duration::from_secs(3600)).unwrap();
+
+        let text = format_market_snapshot(&snapshot);
+        assert!(text.contains("Market Snapshot across Jita, Hek, Rens (1 items"));
+        assert!(text.contains("20 min ago"));
+        assert!(text.contains("Tritanium (34): Jita 4.00 | Hek -"));
+        assert!(text.contains("cheapest in Jita; best bid in Jita; sell prices 0.0% apart; failed: Rens"));
+    }
+}
@@ -0,0 +1,304 @@
+//! Region accessibility context for TraderGrader
+//!
+//! A region's market data says nothing about whether a trader can use it:
+//! Delve's orders sit in alliance-held null-sec, most of them in player
+//! structures that only let their owners dock. Before trusting prices in an
+//! unfamiliar region it helps to know its security mix, who holds its
+//! systems (empire factions, NPC pirates or player alliances, from ESI
+//! `/sovereignty/map/`) and where it has NPC stations anyone can dock at.
+//! The safest of those stations sets how reachable the market is.
+
+use crate::cache::CacheKey;
+use crate::error::Result;
+use crate::market::MarketClient;
+use crate::scan::SCAN_CONCURRENCY;
+use crate::types::{
+    MarketAccess, RegionContext, RegionInfo, SecurityClass, SovereignHolder, SovereigntySystem, SystemInfo,
+};
+use futures::stream::{self, StreamExt, TryStreamExt};
+use std::collections::HashMap;
+
+impl RegionContext {
+    /// Summarizes a region's systems with the sovereignty map
+    ///
+    /// `names` resolves holder IDs; holders without one are listed unnamed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use tradergrader::types::{MarketAccess, RegionContext, RegionInfo, SovereigntySystem};
+    /// use tradergrader::{Position, SystemInfo};
+    ///
+    /// let region = RegionInfo { region_id: 10000060, name: "Delve".to_string(), constellations: Vec::new() };
+    /// let systems = [SystemInfo {
+    ///     system_id: 30004759,
+    ///     name: "1DQ1-A".to_string(),
+    ///     constellation_id: 20000696,
+    ///     security_status: -0.38,
+    ///     position: Position { x: 0.0, y: 0.0, z: 0.0 },
+    ///     stations: Vec::new(),
+    /// }];
+    /// let sovereignty = [SovereigntySystem {
+    ///     system_id: 30004759,
+    ///     alliance_id: Some(1354830081),
+    ///     corporation_id: Some(98388312),
+    ///     faction_id: None,
+    /// }];
+    ///
+    /// let context = RegionContext::new(&region, &systems, &sovereignty, &HashMap::new());
+    /// assert_eq!(context.nullsec_systems, 1);
+    /// assert_eq!(context.holders[0].kind, "alliance");
+    /// assert_eq!(context.access, MarketAccess::StructuresOnly);
+    /// ```
+    pub fn new(
+        region: &RegionInfo,
+        systems: &[SystemInfo],
+        sovereignty: &[SovereigntySystem],
+        names: &HashMap<i64, String>,
+    ) -> Self {
+        let holders_by_system: HashMap<i32, (&str, i32)> = sovereignty
+            .iter()
+            .filter_map(|sov| {
+                let holder = match (sov.alliance_id, sov.faction_id) {
+                    (Some(alliance_id), _) => ("alliance", alliance_id),
+                    (None, Some(faction_id)) => ("faction", faction_id),
+                    (None, None) => return None,
+                };
+                Some((sov.system_id, holder))
+            })
+            .collect();
+
+        let mut holders: Vec<SovereignHolder> = Vec::new();
+        let mut by_class: HashMap<SecurityClass, usize> = HashMap::new();
+        // Safest security band with an NPC station, and whether a faction holds any null-sec one
+        let mut safest_station: Option<SecurityClass> = None;
+        let mut npc_null_station = false;
+        for system in systems {
+            let class = SecurityClass::from_security_status(system.security_status);
+            *by_class.entry(class).or_default() += 1;
+
+            let holder = holders_by_system.get(&system.system_id);
+            if let Some(&(kind, holder_id)) = holder {
+                match holders.iter_mut().find(|h| h.holder_id == holder_id) {
+                    Some(existing) => existing.systems += 1,
+                    None => holders.push(SovereignHolder {
+                        holder_id,
+                        name: names.get(&(holder_id as i64)).cloned(),
+                        kind: kind.to_string(),
+                        systems: 1,
+                    }),
+                }
+            }
+
+            if !system.stations.is_empty() {
+                safest_station = Some(match safest_station {
+                    Some(SecurityClass::HighSec) => SecurityClass::HighSec,
+                    Some(SecurityClass::LowSec) if class == SecurityClass::NullSec => SecurityClass::LowSec,
+                    _ => class,
+                });
+                let alliance_held = holder.is_some_and(|(kind, _)| *kind == "alliance");
+                npc_null_station |= class == SecurityClass::NullSec && !alliance_held;
+            }
+        }
+        holders.sort_by(|a, b| b.systems.cmp(&a.systems).then(a.holder_id.cmp(&b.holder_id)));
+
+        let access = match safest_station {
+            Some(SecurityClass::HighSec) => MarketAccess::HighSec,
+            Some(SecurityClass::LowSec) => MarketAccess::LowSec,
+            Some(SecurityClass::NullSec) if npc_null_station => MarketAccess::NpcNullSec,
+            Some(SecurityClass::NullSec) => MarketAccess::SovereignNullSec,
+            None => MarketAccess::StructuresOnly,
+        };
+        let count = |class: SecurityClass| by_class.get(&class).copied().unwrap_or(0);
+        Self {
+            region_id: region.region_id,
+            name: region.name.clone(),
+            system_count: systems.len(),
+            highsec_systems: count(SecurityClass::HighSec),
+            lowsec_systems: count(SecurityClass::LowSec),
+            nullsec_systems: count(SecurityClass::NullSec),
+            unclaimed_systems: systems.len() - holders.iter().map(|h| h.systems).sum::<usize>(),
+            holders,
+            npc_stations: systems.iter().map(|system| system.stations.len()).sum(),
+            systems_with_stations: systems.iter().filter(|system| !system.stations.is_empty()).count(),
+            access,
+        }
+    }
+}
+
+impl MarketClient {
+    /// Fetches the alliance or faction holding every solar system
+    pub async fn fetch_sovereignty_map(&self) -> Result<Vec<SovereigntySystem>> {
+        self.esi().get_cached("/sovereignty/map/", &CacheKey::sovereignty_map(), "sovereignty")
+            .await
+    }
+
+    /// Describes a region's security, sovereignty and NPC stations
+    ///
+    /// Systems come from the local SDE when loaded, else from ESI (cached for a day).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// let context = client.region_context(10000060).await?;
+    /// println!("{}: market reachable through {}", context.name, context.access);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn region_context(&self, region_id: i32) -> Result<RegionContext> {
+        let region = self.fetch_region(region_id).await?;
+        let system_ids = self.fetch_region_systems(region_id).await?;
+        let systems: Vec<SystemInfo> = stream::iter(system_ids)
+            .map(|system_id| self.fetch_system(system_id))
+            .buffered(SCAN_CONCURRENCY)
+            .try_collect()
+            .await?;
+
+        let sovereignty = self.fetch_sovereignty_map().await?;
+        let mut holder_ids: Vec<i64> = sovereignty
+            .iter()
+            .filter(|sov| systems.iter().any(|system| system.system_id == sov.system_id))
+            .filter_map(|sov| sov.alliance_id.or(sov.faction_id).map(i64::from))
+            .collect();
+        holder_ids.sort_unstable();
+        holder_ids.dedup();
+        let names: HashMap<i64, String> = match self.resolve_names(&holder_ids).await {
+            Ok(names) => names.into_iter().map(|(id, name)| (id, name.name)).collect(),
+            Err(e) => {
+                tracing::debug!("Couldn't resolve sovereignty holder names: {e}");
+                HashMap::new()
+            }
+        };
+
+        Ok(RegionContext::new(&region, &systems, &sovereignty, &names))
+    }
+}
+
+/// Formats a region's accessibility context
+pub(crate) fn format_region_context(context: &RegionContext) -> String {
+    let mut report = format!(
+        "Region Context for {} ({})\n\n\
+         Systems: {} ({} high-sec, {} low-sec, {} null-sec)\n\
+         NPC stations: {} in {} systems\n",
+        context.name,
+        context.region_id,
+        context.system_count,
+        context.highsec_systems,
+        context.lowsec_systems,
+        context.nullsec_systems,
+        context.npc_stations,
+        context.systems_with_stations
+    );
+
+    if context.holders.is_empty() {
+        report.push_str("Sovereignty: no system is held by an alliance or faction\n");
+    } else {
+        report.push_str("\nSovereignty:\n");
+        for holder in &context.holders {
+            let name = match &holder.name {
+                Some(name) => format!("{name} ({})", holder.kind),
+                None => format!("{} {}", holder.kind, holder.holder_id),
+            };
+            report.push_str(&format!("- {name}: {} system(s)\n", holder.systems));
+        }
+        if context.unclaimed_systems > 0 {
+            report.push_str(&format!("- Unclaimed: {} system(s)\n", context.unclaimed_systems));
+        }
+    }
+
+    report.push_str(&format!("\nMarket access: {}. ", context.access));
+    report.push_str(match context.access {
+        MarketAccess::HighSec => "Anyone can reach and dock at the region's NPC stations, so its prices are usable.",
+        MarketAccess::LowSec => {
+            "The NPC stations are open to anyone, but reaching them means low-sec gates; price in the hauling risk."
+        }
+        MarketAccess::NpcNullSec => {
+            "The NPC stations are open to anyone, but only through null-sec, where bubbles and gate camps catch haulers."
+        }
+        MarketAccess::SovereignNullSec => {
+            "The few NPC stations sit in alliance-held space; most orders are likely in the holders' structures, \
+             which may not let outsiders dock."
+        }
+        MarketAccess::StructuresOnly => {
+            "Every order is in a player structure, so its prices are only usable with docking access there."
+        }
+    });
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Position;
+
+    fn system(system_id: i32, security_status: f64, stations: usize) -> SystemInfo {
+        SystemInfo {
+            system_id,
+            name: format!("System {system_id}"),
+            constellation_id: 1,
+            security_status,
+            position: Position { x: 0.0, y: 0.0, z: 0.0 },
+            stations: (0..stations as i64).map(|station| 60_000_000 + station).collect(),
+        }
+    }
+
+    fn sov(system_id: i32, alliance_id: Option<i32>, faction_id: Option<i32>) -> SovereigntySystem {
+        SovereigntySystem {
+            system_id,
+            alliance_id,
+            corporation_id: alliance_id.map(|_| 98_000_001),
+            faction_id,
+        }
+    }
+
+    fn region() -> RegionInfo {
+        RegionInfo {
+            region_id: 10000001,
+            name: "Derelik".to_string(),
+            constellations: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_context() {
+        let systems = [system(1, 0.6, 2), system(2, 0.3, 1), system(3, -0.2, 0), system(4, -0.5, 0)];
+        let sovereignty = [sov(1, None, Some(500007)), sov(2, None, Some(500007)), sov(3, Some(99), None)];
+        let names = HashMap::from([(500007, "Ammatar Mandate".to_string())]);
+        let context = RegionContext::new(&region(), &systems, &sovereignty, &names);
+
+        assert_eq!((context.highsec_systems, context.lowsec_systems, context.nullsec_systems), (1, 1, 2));
+        assert_eq!(context.holders[0].name.as_deref(), Some("Ammatar Mandate"));
+        assert_eq!(context.holders[0].systems, 2);
+        assert_eq!(context.holders[1].kind, "alliance");
+        assert_eq!(context.unclaimed_systems, 1);
+        assert_eq!((context.npc_stations, context.systems_with_stations), (3, 2));
+        assert_eq!(context.access, MarketAccess::HighSec);
+    }
+
+    #[test]
+    fn test_access() {
+        let access = |systems: &[SystemInfo], sovereignty: &[SovereigntySystem]| {
+            RegionContext::new(&region(), systems, sovereignty, &HashMap::new()).access
+        };
+        assert_eq!(access(&[system(1, -0.3, 1), system(2, 0.2, 1)], &[]), MarketAccess::LowSec);
+        // Guristas space: NPC null-sec held by a pirate faction
+        assert_eq!(access(&[system(1, -0.3, 1)], &[sov(1, None, Some(500010))]), MarketAccess::NpcNullSec);
+        assert_eq!(access(&[system(1, -0.3, 1)], &[sov(1, Some(99), None)]), MarketAccess::SovereignNullSec);
+        assert_eq!(access(&[system(1, -1.0, 0)], &[]), MarketAccess::StructuresOnly);
+    }
+
+    #[test]
+    fn test_format() {
+        let systems = [system(1, -0.3, 0)];
+        let context = RegionContext::new(&region(), &systems, &[sov(1, Some(99), None)], &HashMap::new());
+        let report = format_region_context(&context);
+        assert!(report.starts_with("Region Context for Derelik (10000001)"));
+        assert!(report.contains("Systems: 1 (0 high-sec, 0 low-sec, 1 null-sec)"));
+        assert!(report.contains("- alliance 99: 1 system(s)"));
+        assert!(report.contains("Market access: player structures only."));
+    }
+}
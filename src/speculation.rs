@@ -0,0 +1,363 @@
+//! Speculation screening around game events for TraderGrader
+//!
+//! Patches and expansions move prices, and the items that reacted to past
+//! ones are the usual candidates for speculating on the next. This compares a
+//! basket of items' volume-weighted prices and daily volume over a window
+//! before an event against the same window from the event on, and ranks the
+//! items by how far their price moved relative to the noise they showed
+//! before it, so a quiet item that jumped 8% outranks a volatile one that
+//! drifted 10%.
+
+use crate::error::{Result, TraderGraderError};
+use crate::events::GameEvent;
+use crate::market::MarketClient;
+use crate::returns;
+use crate::scan::{MAX_SCAN_TYPES, SCAN_CONCURRENCY};
+use crate::types::{MarketHistory, SpeculationReaction, SpeculationScreen};
+use chrono::{Duration, NaiveDate};
+use futures::stream::{self, StreamExt};
+
+/// Default calendar days compared on each side of an event
+pub const DEFAULT_SPECULATION_WINDOW: usize = 14;
+
+/// Fewest days compared on each side, enough to measure the noise before an event
+pub const MIN_SPECULATION_WINDOW: usize = 3;
+
+/// Most days compared on each side; longer windows pick up unrelated moves
+pub const MAX_SPECULATION_WINDOW: usize = 90;
+
+/// Cap on significance, reached when an item never moved before the event
+const MAX_SIGNIFICANCE: f64 = 10.0;
+
+/// Significance beyond which a reaction stands out from the item's usual noise
+const NOTABLE_SIGNIFICANCE: f64 = 2.0;
+
+impl SpeculationScreen {
+    /// Ranks items, given as `(type_id, label, history)`, by their reaction to `event`
+    ///
+    /// Items without trades on both sides of the event are listed in `skipped`;
+    /// fails only when `window` is out of range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::events::GameEvent;
+    /// use tradergrader::{MarketHistory, SpeculationScreen};
+    ///
+    /// // Two items flat through May; on June 1st one jumps 20%, the other 2%
+    /// let history = |jump: f64| -> Vec<MarketHistory> {
+    ///     (0..40)
+    ///         .map(|day| {
+    ///             let date = chrono::NaiveDate::from_ymd_opt(2025, 5, 1).unwrap() + chrono::Duration::days(day);
+    ///             let before = date < chrono::NaiveDate::from_ymd_opt(2025, 6, 1).unwrap();
+    ///             MarketHistory {
+    ///                 average: if before { 100.0 + (day % 2) as f64 } else { 100.0 * jump },
+    ///                 date: date.to_string(),
+    ///                 highest: 0.0,
+    ///                 lowest: 0.0,
+    ///                 order_count: 10,
+    ///                 volume: 1_000,
+    ///                 extra: Default::default(),
+    ///             }
+    ///         })
+    ///         .collect()
+    /// };
+    /// let items = vec![(1, "Steady".to_string(), history(1.02)), (2, "Jumpy".to_string(), history(1.2))];
+    ///
+    /// let event = GameEvent::parse("2025-06-01")?;
+    /// let screen = SpeculationScreen::from_histories(10000002, &event, &items, 14)?;
+    /// assert_eq!(screen.reactions[0].type_label, "Jumpy");
+    /// assert!(screen.reactions[0].price_change_percent > 18.0);
+    /// # Ok::<(), tradergrader::TraderGraderError>(())
+    /// ```
+    pub fn from_histories(
+        region_id: i32,
+        event: &GameEvent,
+        items: &[(i32, String, Vec<MarketHistory>)],
+        window: usize,
+    ) -> Result<Self> {
+        if !(MIN_SPECULATION_WINDOW..=MAX_SPECULATION_WINDOW).contains(&window) {
+            return Err(TraderGraderError::InvalidArgument {
+                field: "window_days".to_string(),
+                reason: format!("must be between {MIN_SPECULATION_WINDOW} and {MAX_SPECULATION_WINDOW}"),
+            });
+        }
+        let mut reactions = Vec::new();
+        let mut skipped = Vec::new();
+        for (type_id, type_label, history) in items {
+            match reaction(*type_id, type_label, history, event.date, window) {
+                Ok(reaction) => reactions.push(reaction),
+                Err(reason) => skipped.push((*type_id, reason)),
+            }
+        }
+        reactions.sort_by(|a, b| {
+            b.significance
+                .abs()
+                .total_cmp(&a.significance.abs())
+                .then(b.price_change_percent.abs().total_cmp(&a.price_change_percent.abs()))
+        });
+
+        Ok(Self {
+            region_id,
+            event: event.name.clone(),
+            event_date: event.date.to_string(),
+            window_days: window,
+            reactions,
+            skipped,
+        })
+    }
+}
+
+/// One item's move across the event, or why it can't be measured
+fn reaction(
+    type_id: i32,
+    type_label: &str,
+    history: &[MarketHistory],
+    event_date: NaiveDate,
+    window: usize,
+) -> std::result::Result<SpeculationReaction, String> {
+    let mut days: Vec<(NaiveDate, &MarketHistory)> = history
+        .iter()
+        .filter(|h| h.average > 0.0)
+        .filter_map(|h| NaiveDate::parse_from_str(&h.date, "%Y-%m-%d").ok().map(|date| (date, h)))
+        .collect();
+    days.sort_by_key(|(date, _)| *date);
+    let newest = days.last().map(|(date, _)| *date).ok_or("no history")?;
+    if newest < event_date {
+        return Err(format!("no history since the event (newest day {newest})"));
+    }
+
+    let window_length = Duration::days(window as i64);
+    let before: Vec<&MarketHistory> = days
+        .iter()
+        .filter(|(date, _)| *date >= event_date - window_length && *date < event_date)
+        .map(|(_, h)| *h)
+        .collect();
+    let after_end = newest.min(event_date + window_length - Duration::days(1));
+    let after: Vec<&MarketHistory> = days
+        .iter()
+        .filter(|(date, _)| *date >= event_date && *date <= after_end)
+        .map(|(_, h)| *h)
+        .collect();
+    if before.is_empty() {
+        return Err(format!("no trades in the {window} days before the event"));
+    }
+    if after.is_empty() {
+        return Err(format!("no trades in the {window} days from the event"));
+    }
+
+    let (price_before, volume_before) = weighted_price_and_volume(&before);
+    let (price_after, volume_after) = weighted_price_and_volume(&after);
+    let after_days = (after_end - event_date).num_days() + 1;
+    let (volume_before, volume_after) = (volume_before / window as f64, volume_after / after_days as f64);
+
+    let log_move = (price_after / price_before).ln();
+    let prices: Vec<f64> = before.iter().map(|h| h.average).collect();
+    let expected_noise = returns::std_dev(&returns::log_returns(&prices)).unwrap_or(0.0) * (after_days as f64).sqrt();
+    let significance = if expected_noise > 0.0 {
+        (log_move / expected_noise).clamp(-MAX_SIGNIFICANCE, MAX_SIGNIFICANCE)
+    } else if log_move == 0.0 {
+        0.0
+    } else {
+        MAX_SIGNIFICANCE.copysign(log_move)
+    };
+
+    Ok(SpeculationReaction {
+        type_id,
+        type_label: type_label.to_string(),
+        price_before,
+        price_after,
+        price_change_percent: (price_after / price_before - 1.0) * 100.0,
+        volume_before,
+        volume_after,
+        volume_change_percent: (volume_before > 0.0).then(|| (volume_after / volume_before - 1.0) * 100.0),
+        significance,
+    })
+}
+
+/// Volume-weighted average price (the plain mean when nothing traded) and total volume
+fn weighted_price_and_volume(days: &[&MarketHistory]) -> (f64, f64) {
+    let volume: f64 = days.iter().map(|h| h.volume as f64).sum();
+    let price = if volume > 0.0 {
+        days.iter().map(|h| h.average * h.volume as f64).sum::<f64>() / volume
+    } else {
+        days.iter().map(|h| h.average).sum::<f64>() / days.len() as f64
+    };
+    (price, volume)
+}
+
+impl MarketClient {
+    /// Ranks a basket of items by how strongly they reacted to `event`
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// use tradergrader::events::GameEvent;
+    ///
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// let event = GameEvent::parse("Equinox")?;
+    /// let screen = client.screen_speculation(10000002, &[34, 35, 36, 37], &event, 14).await?;
+    /// if let Some(top) = screen.reactions.first() {
+    ///     println!("{} moved {:+.1}%", top.type_label, top.price_change_percent);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn screen_speculation(
+        &self,
+        region_id: i32,
+        type_ids: &[i32],
+        event: &GameEvent,
+        window: usize,
+    ) -> Result<SpeculationScreen> {
+        if type_ids.is_empty() {
+            return Err("No item types to screen".into());
+        }
+        if type_ids.len() > MAX_SCAN_TYPES {
+            return Err(format!("Too many item types to screen ({}, max {MAX_SCAN_TYPES})", type_ids.len()).into());
+        }
+
+        let fetched: Vec<(i32, Result<Vec<MarketHistory>>)> = stream::iter(type_ids.iter().copied())
+            .map(|type_id| async move { (type_id, self.fetch_market_history(region_id, type_id).await) })
+            .buffer_unordered(SCAN_CONCURRENCY)
+            .collect()
+            .await;
+
+        let mut items = Vec::new();
+        let mut failures = Vec::new();
+        for (type_id, outcome) in fetched {
+            match outcome {
+                Ok(history) => items.push((type_id, self.type_label(type_id).await, history)),
+                Err(e) => failures.push((type_id, e.to_string())),
+            }
+        }
+
+        let mut screen = SpeculationScreen::from_histories(region_id, event, &items, window)?;
+        screen.skipped.extend(failures);
+        screen.skipped.sort_by_key(|(type_id, _)| *type_id);
+        Ok(screen)
+    }
+
+    /// Ranks the items of a market group by how strongly they reacted to `event`
+    pub async fn screen_speculation_in_group(
+        &self,
+        region_id: i32,
+        market_group_id: i32,
+        event: &GameEvent,
+        window: usize,
+    ) -> Result<SpeculationScreen> {
+        let group = self.fetch_market_group(market_group_id).await?;
+        if group.types.is_empty() {
+            return Err(format!("Market group {} ({}) has no item types", group.name, market_group_id).into());
+        }
+        self.screen_speculation(region_id, &group.types, event, window).await
+    }
+}
+
+/// Formats the top `limit` reactions and any skipped items
+pub(crate) fn format_speculation_screen(screen: &SpeculationScreen, limit: usize) -> String {
+    let event = if screen.event == screen.event_date {
+        screen.event_date.clone()
+    } else {
+        format!("{} ({})", screen.event, screen.event_date)
+    };
+    let mut report = format!(
+        "Speculation Screen for {event} in Region {}:\n{} days before against {} days from the event, ranked by how \
+         unusual the price move was\n",
+        screen.region_id, screen.window_days, screen.window_days
+    );
+    if screen.reactions.is_empty() {
+        report.push_str("\nNo item has trades on both sides of the event.\n");
+    }
+    for (rank, r) in screen.reactions.iter().take(limit).enumerate() {
+        let volume = r
+            .volume_change_percent
+            .map_or_else(|| "new volume".to_string(), |change| format!("volume {change:+.0}%"));
+        report.push_str(&format!(
+            "{}. {} {:+.2}% ({:.2} -> {:.2} ISK), {volume} ({:.0} -> {:.0}/day), {:+.1}σ{}\n",
+            rank + 1,
+            r.type_label,
+            r.price_change_percent,
+            r.price_before,
+            r.price_after,
+            r.volume_before,
+            r.volume_after,
+            r.significance,
+            if r.significance.abs() >= NOTABLE_SIGNIFICANCE { " notable" } else { "" }
+        ));
+    }
+    if screen.reactions.len() > limit {
+        report.push_str(&format!("... and {} more\n", screen.reactions.len() - limit));
+    }
+    if !screen.skipped.is_empty() {
+        report.push_str(&format!("\nSkipped {} items:\n", screen.skipped.len()));
+        for (type_id, reason) in &screen.skipped {
+            report.push_str(&format!("Type {type_id}: {reason}\n"));
+        }
+    }
+    report.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::daily_history;
+
+    /// Daily history from May 1st 2025, one `(average, volume)` per day
+    fn history(days: &[(f64, i64)]) -> Vec<MarketHistory> {
+        daily_history("2025-05-01", days.iter().copied())
+    }
+
+    fn event() -> GameEvent {
+        GameEvent::new("Patch", NaiveDate::from_ymd_opt(2025, 5, 11).unwrap())
+    }
+
+    #[test]
+    fn test_ranks_by_significance() {
+        // Ten noisy days then a 10% rise on double volume
+        let noisy: Vec<(f64, i64)> = (0..20)
+            .map(|d| match d {
+                0..10 if d % 2 == 0 => (90.0, 100),
+                0..10 => (110.0, 100),
+                _ => (110.0, 200),
+            })
+            .collect();
+        // Ten steady days then a 5% rise on the same volume
+        let quiet: Vec<(f64, i64)> = (0..20)
+            .map(|d| match d {
+                0..10 if d % 2 == 0 => (100.0, 100),
+                0..10 => (100.5, 100),
+                _ => (105.0, 100),
+            })
+            .collect();
+        let items = vec![(1, "Noisy".to_string(), history(&noisy)), (2, "Quiet".to_string(), history(&quiet))];
+        let screen = SpeculationScreen::from_histories(10000002, &event(), &items, 10).unwrap();
+
+        let order: Vec<&str> = screen.reactions.iter().map(|r| r.type_label.as_str()).collect();
+        assert_eq!(order, ["Quiet", "Noisy"]);
+        let noisy = &screen.reactions[1];
+        assert!((noisy.price_change_percent - 10.0).abs() < 1e-9);
+        assert_eq!(noisy.volume_change_percent, Some(100.0));
+
+        let text = format_speculation_screen(&screen, 10);
+        assert!(text.contains("Patch (2025-05-11)"));
+        assert!(text.contains("1. Quiet"));
+        assert!(text.contains("notable"));
+    }
+
+    #[test]
+    fn test_skips_items_without_both_sides() {
+        let before_only = history(&[(100.0, 10); 5]);
+        let after_only: Vec<MarketHistory> = history(&[(100.0, 10); 15]).split_off(10);
+        let items = vec![(1, "Old".to_string(), before_only), (2, "New".to_string(), after_only)];
+        let screen = SpeculationScreen::from_histories(10000002, &event(), &items, 7).unwrap();
+
+        assert!(screen.reactions.is_empty());
+        assert!(screen.skipped[0].1.contains("no history since the event"));
+        assert!(screen.skipped[1].1.contains("before the event"));
+        assert!(format_speculation_screen(&screen, 10).contains("No item has trades on both sides"));
+        assert!(SpeculationScreen::from_histories(10000002, &event(), &items, 2).is_err());
+    }
+}
@@ -0,0 +1,267 @@
+//! Spread history reconstruction for TraderGrader
+//!
+//! ESI doesn't publish past order books, but each day of market history
+//! carries the highest and lowest price anything traded at. Buy orders fill
+//! near the low and sell orders near the high, so the gap between them is a
+//! fair stand-in for the spread a station trader captured that day. Averaged
+//! by week, it shows whether a fat spread today is the item's normal margin
+//! or a one-off.
+
+use crate::analysis::LinearFit;
+use crate::error::{Result, TraderGraderError};
+use crate::history::period_start;
+use crate::market::MarketClient;
+use crate::returns;
+use crate::types::{MarketHistory, Period, SpreadHistory, SpreadPoint, TrendDirection};
+use chrono::NaiveDate;
+
+/// Default weeks of spread history reported
+pub const DEFAULT_SPREAD_WEEKS: usize = 12;
+
+/// Fewest weeks a spread history is built from
+pub const MIN_SPREAD_WEEKS: usize = 4;
+
+/// Smallest total change over the period, in percentage points, that counts as a trend
+const MIN_TREND_POINTS: f64 = 1.0;
+
+/// Standard deviations from the usual weekly spread that mark the latest week as unusual
+const UNUSUAL_Z_SCORE: f64 = 2.0;
+
+impl SpreadHistory {
+    /// Rebuilds weekly spreads from daily history in any order, keeping the last `weeks` weeks
+    ///
+    /// A day's spread is its highest traded price over its lowest, as a
+    /// percentage of the lowest. Days without a positive high and low are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::{MarketHistory, SpreadHistory};
+    ///
+    /// // Six weeks of trading at a steady 10% spread
+    /// let history: Vec<MarketHistory> = (0..42)
+    ///     .map(|day| MarketHistory {
+    ///         average: 105.0,
+    ///         date: (chrono::NaiveDate::from_ymd_opt(2025, 5, 5).unwrap() + chrono::Duration::days(day))
+    ///             .to_string(),
+    ///         highest: 110.0,
+    ///         lowest: 100.0,
+    ///         order_count: 10,
+    ///         volume: 1_000,
+    ///         extra: Default::default(),
+    ///     })
+    ///     .collect();
+    ///
+    /// let spreads = SpreadHistory::from_history(10000002, 34, "Tritanium".to_string(), &history, 4)?;
+    /// assert_eq!(spreads.weeks.len(), 4);
+    /// assert!((spreads.latest_spread_percent - 10.0).abs() < 1e-9);
+    /// # Ok::<(), tradergrader::TraderGraderError>(())
+    /// ```
+    pub fn from_history(
+        region_id: i32,
+        type_id: i32,
+        type_label: String,
+        history: &[MarketHistory],
+        weeks: usize,
+    ) -> Result<Self> {
+        if weeks < MIN_SPREAD_WEEKS {
+            return Err(TraderGraderError::InvalidArgument {
+                field: "weeks".to_string(),
+                reason: format!("must be at least {MIN_SPREAD_WEEKS}"),
+            });
+        }
+        let mut days: Vec<(NaiveDate, &MarketHistory)> = history
+            .iter()
+            .filter(|h| h.lowest > 0.0 && h.highest >= h.lowest)
+            .filter_map(|h| Some((NaiveDate::parse_from_str(&h.date, "%Y-%m-%d").ok()?, h)))
+            .collect();
+        days.sort_by_key(|(date, _)| *date);
+
+        let mut grouped: Vec<(NaiveDate, Vec<&MarketHistory>)> = Vec::new();
+        for (date, day) in days {
+            let start = period_start(date, Period::Weekly);
+            match grouped.last_mut() {
+                Some((current, rows)) if *current == start => rows.push(day),
+                _ => grouped.push((start, vec![day])),
+            }
+        }
+        let points: Vec<SpreadPoint> = grouped
+            .iter()
+            .skip(grouped.len().saturating_sub(weeks))
+            .map(|(start, rows)| {
+                let n = rows.len() as f64;
+                SpreadPoint {
+                    period_start: start.to_string(),
+                    days: rows.len(),
+                    avg_high: rows.iter().map(|h| h.highest).sum::<f64>() / n,
+                    avg_low: rows.iter().map(|h| h.lowest).sum::<f64>() / n,
+                    spread_percent: rows.iter().map(|h| (h.highest / h.lowest - 1.0) * 100.0).sum::<f64>() / n,
+                    volume: rows.iter().map(|h| h.volume).sum(),
+                }
+            })
+            .collect();
+        if points.len() < MIN_SPREAD_WEEKS {
+            return Err(format!(
+                "Need at least {MIN_SPREAD_WEEKS} weeks of history with high and low prices, found {}",
+                points.len()
+            )
+            .into());
+        }
+
+        let spreads: Vec<f64> = points.iter().map(|p| p.spread_percent).collect();
+        let (earlier, latest) = spreads.split_at(spreads.len() - 1);
+        let latest = latest[0];
+        let latest_z_score = match (returns::mean(earlier), returns::std_dev(earlier)) {
+            (Some(mean), Some(std_dev)) if std_dev > 0.0 => Some((latest - mean) / std_dev),
+            _ => None,
+        };
+        let fit = LinearFit::new(&spreads);
+        let fitted_change = fit.slope * (spreads.len() - 1) as f64;
+        let trend = if fitted_change.abs() < MIN_TREND_POINTS.max(fit.residual_std_dev) {
+            TrendDirection::Flat
+        } else if fitted_change > 0.0 {
+            TrendDirection::Up
+        } else {
+            TrendDirection::Down
+        };
+
+        Ok(Self {
+            region_id,
+            type_id,
+            type_label,
+            mean_spread_percent: returns::mean(&spreads).unwrap_or(0.0),
+            std_dev_spread_percent: returns::std_dev(&spreads),
+            latest_spread_percent: latest,
+            latest_z_score,
+            trend,
+            trend_points_per_week: fit.slope,
+            weeks: points,
+        })
+    }
+
+    /// Plain-language reading of whether the latest spread is durable
+    pub fn verdict(&self) -> &'static str {
+        match (self.latest_z_score, self.trend) {
+            (Some(z), _) if z > UNUSUAL_Z_SCORE => {
+                "The latest week is far wider than usual: likely a fluke or a short-lived dislocation"
+            }
+            (Some(z), _) if z < -UNUSUAL_Z_SCORE => "The latest week is far tighter than usual: competition has moved in",
+            (_, TrendDirection::Down) => "Spreads are in their usual range but narrowing week over week",
+            (_, TrendDirection::Up) => "Spreads are in their usual range and widening week over week",
+            (_, TrendDirection::Flat) => "Spreads are steady: the current margin looks durable",
+        }
+    }
+}
+
+impl MarketClient {
+    /// Rebuilds an item's weekly spread history from its daily market history
+    pub async fn spread_history(&self, region_id: i32, type_id: i32, weeks: usize) -> Result<SpreadHistory> {
+        let history = self.fetch_market_history(region_id, type_id).await?;
+        SpreadHistory::from_history(region_id, type_id, self.type_label(type_id).await, &history, weeks)
+    }
+
+    /// Generates a formatted spread history report
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// println!("{}", client.get_spread_history_summary(10000002, 34, 12).await?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_spread_history_summary(&self, region_id: i32, type_id: i32, weeks: usize) -> Result<String> {
+        Ok(format_spread_history(&self.spread_history(region_id, type_id, weeks).await?))
+    }
+}
+
+/// Formats weekly spreads newest first, with the trend and verdict
+fn format_spread_history(spreads: &SpreadHistory) -> String {
+    let mut report = format!(
+        "Spread History for {} in Region {} (approximate, from daily high and low trades):\n\n\
+         | Week of | Avg high | Avg low | Spread | Volume | Days |\n\
+         |---|---|---|---|---|---|\n",
+        spreads.type_label, spreads.region_id
+    );
+    for w in spreads.weeks.iter().rev() {
+        report.push_str(&format!(
+            "| {} | {:.2} | {:.2} | {:.2}% | {} | {} |\n",
+            w.period_start, w.avg_high, w.avg_low, w.spread_percent, w.volume, w.days
+        ));
+    }
+    let usual = match spreads.std_dev_spread_percent {
+        Some(std_dev) => format!("{:.2}% ± {std_dev:.2}", spreads.mean_spread_percent),
+        None => format!("{:.2}%", spreads.mean_spread_percent),
+    };
+    report.push_str(&format!(
+        "\nLatest week: {:.2}% | Usual: {usual}{}\nTrend: {} ({:+.2} points per week)\n{}",
+        spreads.latest_spread_percent,
+        spreads
+            .latest_z_score
+            .map(|z| format!(" | {z:+.1}σ from earlier weeks"))
+            .unwrap_or_default(),
+        spreads.trend,
+        spreads.trend_points_per_week,
+        spreads.verdict()
+    ));
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::history_day;
+    use chrono::Duration;
+
+    /// Daily history from Monday 2025-01-06 with each week's spread in percent
+    fn weekly(spreads: &[f64]) -> Vec<MarketHistory> {
+        let monday = NaiveDate::from_ymd_opt(2025, 1, 6).unwrap();
+        spreads
+            .iter()
+            .enumerate()
+            .flat_map(|(week, &spread)| {
+                (0..7).map(move |day| {
+                    history_day(&(monday + Duration::days(week as i64 * 7 + day)).to_string(), 100.0, 10)
+                        .with_range(100.0, 100.0 * (1.0 + spread / 100.0))
+                        .with_order_count(5)
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_flags_a_fluke_week() {
+        let history = weekly(&[5.0, 5.5, 5.0, 4.5, 5.0, 5.5, 5.0, 20.0]);
+        let spreads = SpreadHistory::from_history(10000002, 34, "Tritanium".to_string(), &history, 8).unwrap();
+
+        assert_eq!(spreads.weeks.len(), 8);
+        assert_eq!(spreads.weeks[0].period_start, "2025-01-06");
+        assert_eq!(spreads.weeks[0].days, 7);
+        assert_eq!(spreads.weeks[0].volume, 70);
+        assert!((spreads.latest_spread_percent - 20.0).abs() < 1e-9);
+        assert!(spreads.latest_z_score.unwrap() > 2.0);
+        assert!(spreads.verdict().contains("fluke"));
+
+        let text = format_spread_history(&spreads);
+        assert!(text.contains("| 2025-02-24 | 120.00 | 100.00 | 20.00% | 70 | 7 |"));
+    }
+
+    #[test]
+    fn test_trend_and_window() {
+        let narrowing = weekly(&[12.0, 11.0, 10.0, 9.0, 8.0, 7.0]);
+        let spreads = SpreadHistory::from_history(10000002, 34, "A".to_string(), &narrowing, 6).unwrap();
+        assert_eq!(spreads.trend, TrendDirection::Down);
+        assert!((spreads.trend_points_per_week + 1.0).abs() < 1e-9);
+
+        let steady = weekly(&[30.0, 30.0, 8.0, 8.2, 7.8, 8.0, 8.1]);
+        let spreads = SpreadHistory::from_history(10000002, 34, "A".to_string(), &steady, 4).unwrap();
+        assert_eq!(spreads.weeks[0].period_start, "2025-01-27");
+        assert_eq!(spreads.trend, TrendDirection::Flat);
+        assert!(spreads.verdict().contains("durable"));
+
+        assert!(SpreadHistory::from_history(10000002, 34, "A".to_string(), &steady, 3).is_err());
+        assert!(SpreadHistory::from_history(10000002, 34, "A".to_string(), &weekly(&[5.0; 3]), 12).is_err());
+    }
+}
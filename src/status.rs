@@ -0,0 +1,235 @@
+//! Tranquility status and downtime awareness for TraderGrader
+//!
+//! Tranquility goes down for maintenance every day at 11:00 UTC, and for
+//! about fifteen minutes ESI answers with 502s and 503s. Left alone those
+//! reach the model as opaque upstream errors. [`MarketClient::explain_outage`]
+//! turns them into "EVE is in daily downtime" during the window, and checks
+//! `/status/` outside it to tell a server outage from a failing endpoint.
+
+use crate::error::{Result, TraderGraderError};
+use crate::market::MarketClient;
+use crate::types::ServerStatus;
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+
+/// Daily downtime start, UTC
+pub const DOWNTIME_START: NaiveTime = match NaiveTime::from_hms_opt(11, 0, 0) {
+    Some(time) => time,
+    None => panic!("valid time"),
+};
+
+/// Usual length of daily downtime
+pub const DOWNTIME_MINUTES: i64 = 15;
+
+/// Start and end of the daily downtime `now` falls in, if any
+///
+/// # Examples
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use tradergrader::status::downtime_window;
+///
+/// let during = Utc.with_ymd_and_hms(2025, 6, 1, 11, 5, 0).unwrap();
+/// let (_, end) = downtime_window(during).unwrap();
+/// assert_eq!(end.format("%H:%M").to_string(), "11:15");
+/// assert!(downtime_window(Utc.with_ymd_and_hms(2025, 6, 1, 11, 15, 0).unwrap()).is_none());
+/// ```
+pub fn downtime_window(now: DateTime<Utc>) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let start = now.date_naive().and_time(DOWNTIME_START).and_utc();
+    let end = start + Duration::minutes(DOWNTIME_MINUTES);
+    (start <= now && now < end).then_some((start, end))
+}
+
+/// When the next daily downtime starts, counting one in progress
+pub fn next_downtime(now: DateTime<Utc>) -> DateTime<Utc> {
+    let today = now.date_naive().and_time(DOWNTIME_START).and_utc();
+    if now < today + Duration::minutes(DOWNTIME_MINUTES) {
+        today
+    } else {
+        today + Duration::days(1)
+    }
+}
+
+/// Whether an error means ESI or the game server couldn't be reached at all
+fn is_outage(error: &TraderGraderError) -> bool {
+    match error {
+        TraderGraderError::EsiUnavailable { .. } => true,
+        TraderGraderError::NetworkError(e) => e.is_connect() || e.is_timeout(),
+        _ => false,
+    }
+}
+
+impl MarketClient {
+    /// Fetches Tranquility's status: players online, server version and VIP mode
+    ///
+    /// Cached for 30 seconds, as ESI does.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// let status = client.fetch_server_status().await?;
+    /// println!("{} pilots online", status.players);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn fetch_server_status(&self) -> Result<ServerStatus> {
+        self.esi().server_status().await
+    }
+
+    /// Replaces an opaque ESI outage error with what is known about the outage
+    ///
+    /// During daily downtime the error says so and when it ends. Outside it,
+    /// `/status/` is checked: when it fails too, the game server is reported
+    /// offline. Other errors, and outages `/status/` doesn't confirm, are
+    /// returned unchanged.
+    pub async fn explain_outage(&self, error: TraderGraderError) -> TraderGraderError {
+        if !is_outage(&error) {
+            return error;
+        }
+        let now = Utc::now();
+        if let Some((start, end)) = downtime_window(now) {
+            return TraderGraderError::EsiUnavailable {
+                message: format!(
+                    "EVE is in daily downtime ({}–{} UTC), try again after it ends ({error})",
+                    start.format("%H:%M"),
+                    end.format("%H:%M")
+                ),
+                retry_after_secs: Some((end - now).num_seconds().max(0) as u64),
+            };
+        }
+        match self.fetch_server_status().await {
+            Err(status_error) if is_outage(&status_error) => TraderGraderError::EsiUnavailable {
+                message: format!("Tranquility or ESI is offline, /status/ is failing as well ({error})"),
+                retry_after_secs: None,
+            },
+            _ => error,
+        }
+    }
+
+    /// Checks the server status in the background and warns when EVE is down
+    ///
+    /// Also fills the status cache, so the first `get_eve_status` is instant.
+    /// Must be called from within a Tokio runtime.
+    pub fn spawn_status_check(self: &std::sync::Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let client = std::sync::Arc::clone(self);
+        tokio::spawn(async move {
+            if let Some((_, end)) = downtime_window(Utc::now()) {
+                tracing::warn!("Starting during EVE daily downtime; ESI requests will fail until {end}");
+            }
+            match client.fetch_server_status().await {
+                Ok(status) if status.vip == Some(true) => tracing::warn!("Tranquility is in VIP mode"),
+                Ok(status) => tracing::info!(players = status.players, "Tranquility is up"),
+                Err(e) => {
+                    let error = client.explain_outage(e).await;
+                    tracing::warn!(%error, "EVE status check failed");
+                }
+            }
+        })
+    }
+
+    /// Generates a formatted Tranquility status report
+    ///
+    /// When the server can't be reached, the report says so (and whether
+    /// daily downtime explains it) instead of failing.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// println!("{}", client.get_eve_status_summary().await?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_eve_status_summary(&self) -> Result<String> {
+        let now = Utc::now();
+        match self.fetch_server_status().await {
+            Ok(status) => Ok(format_server_status(&status, now)),
+            Err(e) if is_outage(&e) => Ok(format!(
+                "❌ Tranquility is unreachable: {}\nNext daily downtime: {}",
+                self.explain_outage(e).await,
+                format_next_downtime(now)
+            )),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Formats the server status with uptime and the next downtime
+fn format_server_status(status: &ServerStatus, now: DateTime<Utc>) -> String {
+    let uptime = DateTime::parse_from_rfc3339(&status.start_time)
+        .map(|start| {
+            let up = now - start.with_timezone(&Utc);
+            format!(" (up {}h {:02}m)", up.num_hours(), up.num_minutes() % 60)
+        })
+        .unwrap_or_default();
+    let mut report = format!(
+        "✅ Tranquility is online\nPlayers: {}\nServer version: {}\nStarted: {}{uptime}\nVIP mode: {}\n",
+        status.players,
+        status.server_version,
+        status.start_time,
+        if status.vip == Some(true) { "on (only privileged accounts can log in)" } else { "off" }
+    );
+    report.push_str(&format!("Next daily downtime: {}", format_next_downtime(now)));
+    report
+}
+
+fn format_next_downtime(now: DateTime<Utc>) -> String {
+    if let Some((start, end)) = downtime_window(now) {
+        return format!("in progress ({}–{} UTC)", start.format("%H:%M"), end.format("%H:%M"));
+    }
+    let next = next_downtime(now);
+    let wait = next - now;
+    format!(
+        "{} UTC (in {}h {:02}m)",
+        next.format("%Y-%m-%d %H:%M"),
+        wait.num_hours(),
+        wait.num_minutes() % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 6, 1, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_downtime_window() {
+        assert!(downtime_window(at(10, 59)).is_none());
+        assert_eq!(downtime_window(at(11, 0)).map(|(start, _)| start), Some(at(11, 0)));
+        assert_eq!(next_downtime(at(9, 0)), at(11, 0));
+        assert_eq!(next_downtime(at(11, 10)), at(11, 0));
+        assert_eq!(next_downtime(at(12, 0)), at(11, 0) + Duration::days(1));
+        assert_eq!(format_next_downtime(at(10, 30)), "2025-06-01 11:00 UTC (in 0h 30m)");
+        assert_eq!(format_next_downtime(at(11, 3)), "in progress (11:00–11:15 UTC)");
+    }
+
+    #[test]
+    fn test_format_server_status() {
+        let status = ServerStatus {
+            players: 23_456,
+            server_version: "2900000".to_string(),
+            start_time: "2025-06-01T11:05:00Z".to_string(),
+            vip: None,
+        };
+        let text = format_server_status(&status, at(13, 35));
+        assert!(text.contains("Players: 23456"));
+        assert!(text.contains("(up 2h 30m)"));
+        assert!(text.contains("VIP mode: off"));
+        assert!(text.contains("Next daily downtime: 2025-06-02 11:00 UTC (in 21h 25m)"));
+    }
+
+    #[tokio::test]
+    async fn test_explain_outage_leaves_other_errors() {
+        let client = MarketClient::without_cache();
+        let error = client.explain_outage(TraderGraderError::InvalidParams("bad".to_string())).await;
+        assert!(matches!(error, TraderGraderError::InvalidParams(_)));
+    }
+}
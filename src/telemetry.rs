@@ -0,0 +1,152 @@
+//! Tracing setup for TraderGrader
+//!
+//! ESI requests, cache lookups and tool calls are instrumented with `tracing`
+//! spans and events so a slow tool call can be broken down after the fact.
+//! The binary installs a subscriber from [`TracingConfig::from_env`]: an
+//! `EnvFilter` directive string from `TRADERGRADER_TRACING` (or `RUST_LOG`),
+//! written to stderr or, with `TRADERGRADER_TRACING_FILE`, appended to a file.
+//! Nothing is ever written to stdout, which carries the MCP protocol.
+
+use crate::error::{Result, TraderGraderError};
+use std::fs::OpenOptions;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::EnvFilter;
+
+/// Filter used when neither `TRADERGRADER_TRACING` nor `RUST_LOG` is set
+pub const DEFAULT_TRACING_FILTER: &str = "warn,tradergrader=info";
+
+/// Where trace output goes
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum TracingOutput {
+    /// Standard error, the usual place MCP clients collect server logs
+    #[default]
+    Stderr,
+    /// Appended to a file, created if missing
+    File(PathBuf),
+}
+
+/// Subscriber configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TracingConfig {
+    /// `EnvFilter` directives, e.g. `tradergrader=debug` or `tradergrader::market=trace`
+    pub filter: String,
+    pub output: TracingOutput,
+    /// Also report when spans close, with their busy and idle time
+    pub span_timings: bool,
+}
+
+impl Default for TracingConfig {
+    fn default() -> Self {
+        Self {
+            filter: DEFAULT_TRACING_FILTER.to_string(),
+            output: TracingOutput::Stderr,
+            span_timings: false,
+        }
+    }
+}
+
+impl TracingConfig {
+    /// Create the default configuration: info for TraderGrader, warnings for dependencies
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Everything TraderGrader does, with span timings, for diagnosing slow calls
+    pub fn verbose() -> Self {
+        Self {
+            filter: "warn,tradergrader=debug".to_string(),
+            span_timings: true,
+            ..Self::default()
+        }
+    }
+
+    /// Load the configuration from the environment
+    ///
+    /// Reads `TRADERGRADER_TRACING` (falling back to `RUST_LOG`) for the
+    /// filter, `TRADERGRADER_TRACING_FILE` for a log file and
+    /// `TRADERGRADER_TRACING_SPANS=1` to report span timings.
+    pub fn from_env() -> Self {
+        let filter = std::env::var("TRADERGRADER_TRACING")
+            .or_else(|_| std::env::var("RUST_LOG"))
+            .ok()
+            .filter(|filter| !filter.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_TRACING_FILTER.to_string());
+        let output = match std::env::var("TRADERGRADER_TRACING_FILE") {
+            Ok(path) if !path.trim().is_empty() => TracingOutput::File(PathBuf::from(path.trim())),
+            _ => TracingOutput::Stderr,
+        };
+        let span_timings = std::env::var("TRADERGRADER_TRACING_SPANS").is_ok_and(|v| v == "1" || v == "true");
+        Self {
+            filter,
+            output,
+            span_timings,
+        }
+    }
+
+    /// Parses the filter directives
+    pub fn env_filter(&self) -> Result<EnvFilter> {
+        EnvFilter::try_new(&self.filter).map_err(|e| {
+            TraderGraderError::InternalError(format!("Invalid tracing filter '{}': {e}", self.filter))
+        })
+    }
+}
+
+/// Installs the global tracing subscriber
+///
+/// Fails if the filter doesn't parse, the log file can't be opened, or a
+/// subscriber is already installed.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tradergrader::telemetry::{init_tracing, TracingConfig};
+///
+/// if let Err(e) = init_tracing(&TracingConfig::from_env()) {
+///     eprintln!("Tracing disabled: {e}");
+/// }
+/// ```
+pub fn init_tracing(config: &TracingConfig) -> Result<()> {
+    let span_events = if config.span_timings { FmtSpan::CLOSE } else { FmtSpan::NONE };
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(config.env_filter()?)
+        .with_span_events(span_events);
+
+    let installed = match &config.output {
+        TracingOutput::Stderr => builder.with_writer(std::io::stderr).try_init(),
+        TracingOutput::File(path) => {
+            let file = OpenOptions::new().create(true).append(true).open(path).map_err(|e| {
+                TraderGraderError::InternalError(format!("Can't open trace file {}: {e}", path.display()))
+            })?;
+            builder.with_writer(Mutex::new(file)).with_ansi(false).try_init()
+        }
+    };
+    installed.map_err(|e| TraderGraderError::InternalError(format!("Tracing already initialized: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filters() {
+        assert!(TracingConfig::new().env_filter().is_ok());
+        assert!(TracingConfig::verbose().span_timings);
+
+        let config = TracingConfig {
+            filter: "tradergrader=loud".to_string(),
+            ..TracingConfig::default()
+        };
+        assert!(config.env_filter().unwrap_err().to_string().contains("Invalid tracing filter"));
+    }
+
+    #[test]
+    fn test_missing_log_directory_is_reported() {
+        let config = TracingConfig {
+            output: TracingOutput::File(PathBuf::from("/nonexistent/tradergrader/trace.log")),
+            ..TracingConfig::default()
+        };
+        assert!(init_tracing(&config).unwrap_err().to_string().contains("Can't open trace file"));
+    }
+}
@@ -0,0 +1,91 @@
+//! Market data fixtures shared by the unit tests
+//!
+//! [`history_day`] and [`market_order`] build a plain day of history and a
+//! plain order; the `with_*` methods change only the fields a test is about.
+
+use crate::types::{MarketHistory, MarketOrder};
+use chrono::{Duration, NaiveDate};
+
+/// A day of history that traded at `average` all day across a single order
+pub(crate) fn history_day(date: &str, average: f64, volume: i64) -> MarketHistory {
+    MarketHistory {
+        average,
+        date: date.to_string(),
+        highest: average,
+        lowest: average,
+        order_count: 1,
+        volume,
+        extra: Default::default(),
+    }
+}
+
+/// Consecutive days of history from `start`, one for each `(average, volume)`
+pub(crate) fn daily_history(start: &str, days: impl IntoIterator<Item = (f64, i64)>) -> Vec<MarketHistory> {
+    let start = NaiveDate::parse_from_str(start, "%Y-%m-%d").expect("start should be a date");
+    days.into_iter()
+        .enumerate()
+        .map(|(offset, (average, volume))| {
+            history_day(&(start + Duration::days(offset as i64)).to_string(), average, volume)
+        })
+        .collect()
+}
+
+impl MarketHistory {
+    /// Sets the day's lowest and highest trade
+    pub(crate) fn with_range(self, lowest: f64, highest: f64) -> Self {
+        Self { lowest, highest, ..self }
+    }
+
+    /// Sets how many orders traded that day
+    pub(crate) fn with_order_count(self, order_count: i64) -> Self {
+        Self { order_count, ..self }
+    }
+}
+
+/// A region-range Tritanium order for 100 units at Jita IV - Moon 4
+pub(crate) fn market_order(order_id: i64, is_buy_order: bool, price: f64) -> MarketOrder {
+    MarketOrder {
+        duration: 90,
+        is_buy_order,
+        issued: "2025-06-22T10:00:00Z".to_string(),
+        location_id: 60003760,
+        min_volume: 1,
+        order_id,
+        price,
+        range: "region".to_string(),
+        system_id: 30000142,
+        type_id: 34,
+        volume_remain: 100,
+        volume_total: 100,
+        extra: Default::default(),
+    }
+}
+
+impl MarketOrder {
+    /// Moves the order to another station or structure
+    pub(crate) fn with_location(self, location_id: i64) -> Self {
+        Self { location_id, ..self }
+    }
+
+    /// Sets the units left, as if none had traded yet
+    pub(crate) fn with_volume(self, volume_remain: i32) -> Self {
+        Self {
+            volume_remain,
+            volume_total: volume_remain,
+            ..self
+        }
+    }
+
+    /// Sets when the order was issued or last updated
+    pub(crate) fn with_issued(self, issued: &str) -> Self {
+        Self {
+            issued: issued.to_string(),
+            ..self
+        }
+    }
+
+    /// Makes it an order for another item
+    pub(crate) fn with_type(self, type_id: i32) -> Self {
+        Self { type_id, ..self }
+    }
+}
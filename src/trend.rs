@@ -0,0 +1,501 @@
+//! Multi-timeframe trend agreement for TraderGrader
+//!
+//! A single trend label hides whether a move is a blip or part of something
+//! bigger. This fits a log-price trend line over the last 7, 30 and 90 days,
+//! scales each move by the noise expected from daily volatility, and combines
+//! the three into one conviction score and a plain-language reading such as
+//! "Strong uptrend across all timeframes" or "Short-term bounce in a downtrend".
+//!
+//! The single label price analysis reports comes from [`TrendConfig`], which
+//! compares volume-weighted prices week on week and only calls a move strong
+//! when traded volume rises with it.
+
+use crate::error::{Result, TraderGraderError};
+use crate::market::MarketClient;
+use crate::returns;
+use crate::types::{MarketHistory, PriceTrend, TimeframeTrend, TrendAgreement, TrendDirection, TrendEvidence};
+use chrono::{Duration, NaiveDate};
+
+/// Days covered by the short, medium and long timeframes
+pub const TREND_TIMEFRAMES: (usize, usize, usize) = (7, 30, 90);
+
+/// Weights of the short, medium and long timeframes in the conviction score
+const TIMEFRAME_WEIGHTS: (f64, f64, f64) = (0.2, 0.35, 0.45);
+
+/// Trend strength beyond which a timeframe counts as trending
+const TREND_STRENGTH_THRESHOLD: f64 = 1.0;
+
+/// Smallest fitted move, in percent, that counts as a trend however quiet the market
+const MIN_TREND_PERCENT: f64 = 0.5;
+
+/// Cap on trend strength, reached when prices barely fluctuate around the trend
+const MAX_TREND_STRENGTH: f64 = 10.0;
+
+/// Rules behind the trend label of a price analysis
+///
+/// The volume-weighted average price of the last `window_days` calendar days
+/// is compared with the window before it. A move of `change_percent` is a
+/// trend; a move of `strong_change_percent` is a strong one only when volume
+/// over the same windows rose by `volume_confirmation_percent`, whichever way
+/// the price went, since heavy selling confirms a slide as heavy buying
+/// confirms a rally.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrendConfig {
+    /// Calendar days in each of the two windows compared
+    pub window_days: u32,
+    /// Volume-weighted price move, in percent, that counts as a trend
+    pub change_percent: f64,
+    /// Volume-weighted price move, in percent, that counts as a strong trend
+    pub strong_change_percent: f64,
+    /// Rise in traded volume, in percent, that confirms a strong trend
+    pub volume_confirmation_percent: f64,
+    /// Whether strong trends need volume confirmation at all
+    pub require_volume_confirmation: bool,
+}
+
+impl Default for TrendConfig {
+    fn default() -> Self {
+        Self {
+            window_days: 7,
+            change_percent: 2.0,
+            strong_change_percent: 5.0,
+            volume_confirmation_percent: 10.0,
+            require_volume_confirmation: true,
+        }
+    }
+}
+
+impl TrendConfig {
+    /// Checks the window isn't empty and the thresholds are positive and ordered
+    ///
+    /// A zero `change_percent` would call a perfectly flat market a trend.
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |field: &str, reason: &str| {
+            Err(TraderGraderError::InvalidArgument {
+                field: field.to_string(),
+                reason: reason.to_string(),
+            })
+        };
+        if self.window_days == 0 {
+            return invalid("window_days", "must be at least 1");
+        }
+        if !(self.change_percent > 0.0 && self.change_percent.is_finite()) {
+            return invalid("change_percent", "must be a positive number");
+        }
+        if !(self.strong_change_percent >= self.change_percent && self.strong_change_percent.is_finite()) {
+            return invalid("strong_change_percent", "must be at least change_percent");
+        }
+        if !self.volume_confirmation_percent.is_finite() {
+            return invalid("volume_confirmation_percent", "must be a number");
+        }
+        Ok(())
+    }
+
+    /// Labels the move between the last two windows of history in any order
+    ///
+    /// History that doesn't reach into the earlier window is `Stable` with
+    /// empty evidence. Rows with an unparseable date are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::{MarketHistory, PriceTrend, TrendConfig};
+    ///
+    /// // Price up 10% in the second week, on the same volume and then on double
+    /// let weeks = |second_week_volume: i64| -> Vec<MarketHistory> {
+    ///     (1..=14)
+    ///         .map(|day| MarketHistory {
+    ///             average: if day > 7 { 110.0 } else { 100.0 },
+    ///             date: format!("2025-06-{day:02}"),
+    ///             highest: 0.0,
+    ///             lowest: 0.0,
+    ///             order_count: 10,
+    ///             volume: if day > 7 { second_week_volume } else { 1_000 },
+    ///             extra: Default::default(),
+    ///         })
+    ///         .collect()
+    /// };
+    ///
+    /// let config = TrendConfig::default();
+    /// let (trend, evidence) = config.classify(&weeks(1_000));
+    /// assert_eq!(trend, PriceTrend::Upward);
+    /// assert!(!evidence.volume_confirmed);
+    /// assert_eq!(config.classify(&weeks(2_000)).0, PriceTrend::StrongUpward);
+    /// ```
+    pub fn classify(&self, history: &[MarketHistory]) -> (PriceTrend, TrendEvidence) {
+        let days: Vec<(NaiveDate, &MarketHistory)> = history
+            .iter()
+            .filter_map(|h| NaiveDate::parse_from_str(&h.date, "%Y-%m-%d").ok().map(|date| (date, h)))
+            .collect();
+        let Some(newest) = days.iter().map(|(date, _)| *date).max() else {
+            return (PriceTrend::Stable, TrendEvidence::default());
+        };
+        let window = i64::from(self.window_days.max(1));
+        let split = newest - Duration::days(window);
+        let oldest = split - Duration::days(window);
+        let rows_after = |from: NaiveDate, to: NaiveDate| -> Vec<&MarketHistory> {
+            days.iter().filter(|(date, _)| *date > from && *date <= to).map(|(_, h)| *h).collect()
+        };
+        let (recent, earlier) = (rows_after(split, newest), rows_after(oldest, split));
+        let (Some(recent_price), Some(earlier_price)) = (weighted_average(&recent), weighted_average(&earlier)) else {
+            return (PriceTrend::Stable, TrendEvidence::default());
+        };
+
+        let price_change_percent = (recent_price / earlier_price - 1.0) * 100.0;
+        let volume = |rows: &[&MarketHistory]| rows.iter().map(|h| h.volume).sum::<i64>() as f64;
+        let earlier_volume = volume(&earlier);
+        let volume_change_percent =
+            (earlier_volume > 0.0).then(|| (volume(&recent) / earlier_volume - 1.0) * 100.0);
+        let volume_confirmed = volume_change_percent.is_some_and(|change| change >= self.volume_confirmation_percent);
+
+        let strong = price_change_percent.abs() >= self.strong_change_percent
+            && (volume_confirmed || !self.require_volume_confirmation);
+        let trend = match price_change_percent {
+            change if change >= self.change_percent && strong => PriceTrend::StrongUpward,
+            change if change >= self.change_percent => PriceTrend::Upward,
+            change if change <= -self.change_percent && strong => PriceTrend::StrongDownward,
+            change if change <= -self.change_percent => PriceTrend::Downward,
+            _ => PriceTrend::Stable,
+        };
+        let evidence = TrendEvidence {
+            price_change_percent,
+            volume_change_percent,
+            volume_confirmed,
+        };
+        (trend, evidence)
+    }
+}
+
+/// Volume-weighted average price of some days, or their plain mean when none traded
+fn weighted_average(rows: &[&MarketHistory]) -> Option<f64> {
+    if rows.is_empty() {
+        return None;
+    }
+    let volume: i64 = rows.iter().map(|h| h.volume).sum();
+    let average = if volume > 0 {
+        rows.iter().map(|h| h.average * h.volume as f64).sum::<f64>() / volume as f64
+    } else {
+        rows.iter().map(|h| h.average).sum::<f64>() / rows.len() as f64
+    };
+    (average > 0.0).then_some(average)
+}
+
+impl TrendAgreement {
+    /// Compares trends over the last 7, 30 and 90 days of history in any order
+    ///
+    /// Timeframes longer than the available history are left out of the
+    /// score. Fails when there aren't enough days for even the short one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::{MarketHistory, TrendAgreement, TrendDirection};
+    ///
+    /// // Steady 1% a day rise with a little noise
+    /// let history: Vec<MarketHistory> = (0..100)
+    ///     .map(|day| {
+    ///         let noise = if day % 2 == 0 { 1.002 } else { 0.998 };
+    ///         MarketHistory {
+    ///             average: 100.0 * 1.01f64.powi(day) * noise,
+    ///             date: format!("day-{day:03}"),
+    ///             highest: 0.0,
+    ///             lowest: 0.0,
+    ///             order_count: 10,
+    ///             volume: 1_000,
+    ///             extra: Default::default(),
+    ///         }
+    ///     })
+    ///     .collect();
+    ///
+    /// let agreement = TrendAgreement::from_history(&history)?;
+    /// assert_eq!(agreement.long.unwrap().direction, TrendDirection::Up);
+    /// assert!(agreement.conviction > 50.0);
+    /// # Ok::<(), tradergrader::TraderGraderError>(())
+    /// ```
+    pub fn from_history(history: &[MarketHistory]) -> Result<Self> {
+        let prices: Vec<f64> = returns::daily_prices(history)
+            .into_iter()
+            .filter(|p| *p > 0.0)
+            .collect();
+        let (short_days, medium_days, long_days) = TREND_TIMEFRAMES;
+        let short = timeframe_trend(&prices, short_days)
+            .ok_or_else(|| format!("Need at least {short_days} days of history to compare trends"))?;
+        let medium = timeframe_trend(&prices, medium_days);
+        let long = timeframe_trend(&prices, long_days);
+
+        let (short_weight, medium_weight, long_weight) = TIMEFRAME_WEIGHTS;
+        let weighted = [(Some(&short), short_weight), (medium.as_ref(), medium_weight), (long.as_ref(), long_weight)];
+        let (score, weights) = weighted
+            .iter()
+            .filter_map(|(trend, weight)| trend.map(|t| (t, *weight)))
+            .fold((0.0, 0.0), |(score, weights), (trend, weight)| {
+                let contribution = (trend.strength / (2.0 * TREND_STRENGTH_THRESHOLD)).clamp(-1.0, 1.0);
+                (score + contribution * weight, weights + weight)
+            });
+        let conviction = score / weights * 100.0;
+
+        let reading = reading(&short, medium.as_ref(), long.as_ref(), conviction);
+        Ok(Self {
+            short: Some(short),
+            medium,
+            long,
+            conviction,
+            reading,
+        })
+    }
+}
+
+impl MarketClient {
+    /// Compares an item's short, medium and long-term price trends
+    pub async fn trend_agreement(&self, region_id: i32, type_id: i32) -> Result<TrendAgreement> {
+        let history = self.fetch_market_history(region_id, type_id).await?;
+        TrendAgreement::from_history(&history)
+    }
+
+    /// Generates a formatted multi-timeframe trend report
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// println!("{}", client.get_trend_agreement_summary(10000002, 34).await?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_trend_agreement_summary(&self, region_id: i32, type_id: i32) -> Result<String> {
+        let agreement = self.trend_agreement(region_id, type_id).await?;
+        Ok(format_trend_agreement(
+            &format!("Trend Agreement for {} in Region {region_id}", self.type_label(type_id).await),
+            &agreement,
+        ))
+    }
+}
+
+/// Fits a trend line to the log of the last `days` prices (oldest first)
+fn timeframe_trend(prices: &[f64], days: usize) -> Option<TimeframeTrend> {
+    if days < 3 || prices.len() < days {
+        return None;
+    }
+    let window = &prices[prices.len() - days..];
+    let logs: Vec<f64> = window.iter().map(|p| p.ln()).collect();
+
+    let n = days as f64;
+    let x_mean = (n - 1.0) / 2.0;
+    let y_mean = returns::mean(&logs)?;
+    let (covariance, variance) = logs.iter().enumerate().fold((0.0, 0.0), |(cov, var), (x, y)| {
+        let dx = x as f64 - x_mean;
+        (cov + dx * (y - y_mean), var + dx * dx)
+    });
+    let fitted_log_change = covariance / variance * (n - 1.0);
+    let change_percent = (fitted_log_change.exp() - 1.0) * 100.0;
+
+    let expected_noise = returns::std_dev(&returns::log_returns(window)).unwrap_or(0.0) * (n - 1.0).sqrt();
+    let strength = if expected_noise > 0.0 {
+        (fitted_log_change / expected_noise).clamp(-MAX_TREND_STRENGTH, MAX_TREND_STRENGTH)
+    } else if fitted_log_change == 0.0 {
+        0.0
+    } else {
+        MAX_TREND_STRENGTH.copysign(fitted_log_change)
+    };
+
+    let direction = if change_percent.abs() < MIN_TREND_PERCENT {
+        TrendDirection::Flat
+    } else if strength > TREND_STRENGTH_THRESHOLD {
+        TrendDirection::Up
+    } else if strength < -TREND_STRENGTH_THRESHOLD {
+        TrendDirection::Down
+    } else {
+        TrendDirection::Flat
+    };
+
+    Some(TimeframeTrend {
+        days,
+        change_percent,
+        strength,
+        direction,
+    })
+}
+
+/// Plain-language reading of how the timeframes line up
+fn reading(
+    short: &TimeframeTrend,
+    medium: Option<&TimeframeTrend>,
+    long: Option<&TimeframeTrend>,
+    conviction: f64,
+) -> String {
+    let directions: Vec<TrendDirection> = [Some(short), medium, long].iter().flatten().map(|t| t.direction).collect();
+    let longest = long.or(medium).map(|t| t.direction);
+
+    let reading = if directions.iter().all(|d| *d == TrendDirection::Up) {
+        if directions.len() == 3 {
+            "Strong uptrend across all timeframes"
+        } else {
+            "Uptrend across the available timeframes"
+        }
+    } else if directions.iter().all(|d| *d == TrendDirection::Down) {
+        if directions.len() == 3 {
+            "Strong downtrend across all timeframes"
+        } else {
+            "Downtrend across the available timeframes"
+        }
+    } else if directions.iter().all(|d| *d == TrendDirection::Flat) {
+        "No clear trend on any timeframe"
+    } else if short.direction == TrendDirection::Up && longest == Some(TrendDirection::Down) {
+        "Short-term bounce in a downtrend"
+    } else if short.direction == TrendDirection::Down && longest == Some(TrendDirection::Up) {
+        "Short-term pullback in an uptrend"
+    } else if conviction >= 25.0 {
+        "Leaning up with mixed timeframes"
+    } else if conviction <= -25.0 {
+        "Leaning down with mixed timeframes"
+    } else {
+        "Mixed signals across timeframes"
+    };
+    reading.to_string()
+}
+
+/// Formats a trend agreement as a text report
+fn format_trend_agreement(title: &str, agreement: &TrendAgreement) -> String {
+    let mut report = format!(
+        "{title}:\n{}\nConviction: {:+.0} (-100 strong down to +100 strong up)\n",
+        agreement.reading, agreement.conviction
+    );
+    let frames = [("Short", &agreement.short), ("Medium", &agreement.medium), ("Long", &agreement.long)];
+    for (label, trend) in frames {
+        match trend {
+            Some(t) => report.push_str(&format!(
+                "{label} ({}d): {} {:+.2}% (strength {:+.2})\n",
+                t.days, t.direction, t.change_percent, t.strength
+            )),
+            None => report.push_str(&format!("{label}: not enough history\n")),
+        }
+    }
+    report.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::history_day;
+
+    /// History with the given daily growth rates, one per segment of days
+    fn history(segments: &[(usize, f64)]) -> Vec<MarketHistory> {
+        let mut price = 100.0;
+        let mut days = Vec::new();
+        for &(length, growth) in segments {
+            for _ in 0..length {
+                // Alternating noise so volatility isn't zero
+                let noise = if days.len() % 2 == 0 { 1.003 } else { 0.997 };
+                price *= growth;
+                days.push(history_day(&format!("2025-{:03}", days.len()), price * noise, 1));
+            }
+        }
+        days
+    }
+
+    #[test]
+    fn test_strong_uptrend() {
+        let agreement = TrendAgreement::from_history(&history(&[(120, 1.01)])).unwrap();
+        assert_eq!(agreement.reading, "Strong uptrend across all timeframes");
+        assert!(agreement.conviction > 90.0);
+        let long = agreement.long.unwrap();
+        assert_eq!(long.days, 90);
+        assert!((long.change_percent - (1.01f64.powi(89) - 1.0) * 100.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_bounce_in_downtrend() {
+        let agreement = TrendAgreement::from_history(&history(&[(110, 0.99), (7, 1.03)])).unwrap();
+        assert_eq!(agreement.short.as_ref().unwrap().direction, TrendDirection::Up);
+        assert_eq!(agreement.long.as_ref().unwrap().direction, TrendDirection::Down);
+        assert_eq!(agreement.reading, "Short-term bounce in a downtrend");
+        assert!(agreement.conviction < 0.0);
+    }
+
+    #[test]
+    fn test_flat_market_and_short_history() {
+        let flat = TrendAgreement::from_history(&history(&[(40, 1.0)])).unwrap();
+        assert_eq!(flat.reading, "No clear trend on any timeframe");
+        assert!(flat.long.is_none());
+        assert!(flat.conviction.abs() < 25.0);
+
+        assert!(TrendAgreement::from_history(&history(&[(5, 1.01)])).is_err());
+
+        let report = format_trend_agreement("Trend", &flat);
+        assert!(report.contains("Long: not enough history"));
+        assert!(report.contains("Short (7d): Flat"));
+    }
+
+    /// Two weeks of dated history: the first at 100 ISK, the second at `price`
+    fn two_weeks(price: f64, first_volume: i64, second_volume: i64) -> Vec<MarketHistory> {
+        (1..=14)
+            .map(|day| {
+                let (average, volume) = if day > 7 { (price, second_volume) } else { (100.0, first_volume) };
+                history_day(&format!("2025-06-{day:02}"), average, volume)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_trend_config_needs_volume_for_strong_moves() {
+        let config = TrendConfig::default();
+        // A slide on heavy selling is confirmed; the same slide on thin volume isn't
+        let (trend, evidence) = config.classify(&two_weeks(90.0, 1_000, 1_500));
+        assert_eq!(trend, PriceTrend::StrongDownward);
+        assert_eq!(evidence.volume_change_percent, Some(50.0));
+        assert_eq!(config.classify(&two_weeks(90.0, 1_000, 500)).0, PriceTrend::Downward);
+        assert_eq!(config.classify(&two_weeks(101.0, 1_000, 5_000)).0, PriceTrend::Stable);
+
+        let unconfirmed = TrendConfig {
+            require_volume_confirmation: false,
+            ..config
+        };
+        assert_eq!(unconfirmed.classify(&two_weeks(90.0, 1_000, 500)).0, PriceTrend::StrongDownward);
+
+        // One traded day at a spike moves the weighted price less than the plain mean
+        let mut spike = two_weeks(100.0, 1_000, 1_000);
+        spike[13].average = 200.0;
+        spike[13].volume = 10;
+        assert_eq!(config.classify(&spike).0, PriceTrend::Stable);
+
+        assert_eq!(config.classify(&two_weeks(120.0, 1_000, 1_000)[7..]), (PriceTrend::Stable, TrendEvidence::default()));
+    }
+
+    #[test]
+    fn test_trend_config_validation() {
+        assert!(TrendConfig::default().validate().is_ok());
+        let zero_window = TrendConfig {
+            window_days: 0,
+            ..TrendConfig::default()
+        };
+        assert!(zero_window.validate().is_err());
+        let inverted = TrendConfig {
+            change_percent: 6.0,
+            ..TrendConfig::default()
+        };
+        assert!(inverted.validate().is_err());
+        let zero = TrendConfig {
+            change_percent: 0.0,
+            strong_change_percent: 0.0,
+            ..TrendConfig::default()
+        };
+        assert!(zero.validate().is_err());
+    }
+
+    #[test]
+    fn test_flat_market_stays_stable_at_smallest_threshold() {
+        let config = TrendConfig {
+            change_percent: f64::MIN_POSITIVE,
+            strong_change_percent: f64::MIN_POSITIVE,
+            require_volume_confirmation: false,
+            ..TrendConfig::default()
+        };
+        assert!(config.validate().is_ok());
+        let (trend, evidence) = config.classify(&two_weeks(100.0, 1_000, 1_000));
+        assert_eq!(evidence.price_change_percent, 0.0);
+        assert_eq!(trend, PriceTrend::Stable);
+        assert_eq!(config.classify(&two_weeks(100.5, 1_000, 1_000)).0, PriceTrend::StrongUpward);
+    }
+}
@@ -1,4 +1,6 @@
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
 
 /// Represents a market order from the EVE ESI API
 /// 
@@ -6,18 +8,31 @@ use serde::{Deserialize, Serialize};
 /// including price, volume, location, and timing details.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct MarketOrder {
+    #[serde(default)]
     pub duration: i32,
     pub is_buy_order: bool,
+    #[serde(default)]
     pub issued: String,
     pub location_id: i64,
+    /// Smallest quantity one fill must take (absent means no minimum)
+    #[serde(default = "no_minimum_volume")]
     pub min_volume: i32,
     pub order_id: i64,
     pub price: f64,
     pub range: String,
+    /// Solar system of the order (absent for structure market orders)
+    #[serde(default)]
     pub system_id: i32,
     pub type_id: i32,
     pub volume_remain: i32,
     pub volume_total: i32,
+    /// Fields ESI sent that this type doesn't declare
+    #[serde(default, with = "extra_fields")]
+    pub extra: ExtraFields,
+}
+
+fn no_minimum_volume() -> i32 {
+    1
 }
 
 /// Represents an item type in EVE Online
@@ -41,15 +56,73 @@ pub struct MarketHistory {
     pub date: String,
     pub highest: f64,
     pub lowest: f64,
+    #[serde(default)]
     pub order_count: i64,
     pub volume: i64,
+    /// Fields ESI sent that this type doesn't declare
+    #[serde(default, with = "extra_fields")]
+    pub extra: ExtraFields,
+}
+
+/// Fields of an ESI object that its type doesn't declare, kept so schema changes lose nothing
+pub type ExtraFields = HashMap<String, Value>;
+
+/// An ESI object decoded into the fields `T` declares, with the rest in `extra`
+#[derive(Deserialize)]
+pub(crate) struct EsiObject<T> {
+    #[serde(flatten)]
+    pub known: T,
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+/// ESI types that keep the fields they don't declare
+pub(crate) trait KeepsExtraFields {
+    fn set_extra(&mut self, extra: ExtraFields);
+}
+
+impl KeepsExtraFields for MarketOrder {
+    fn set_extra(&mut self, extra: ExtraFields) {
+        self.extra = extra;
+    }
+}
+
+impl KeepsExtraFields for MarketHistory {
+    fn set_extra(&mut self, extra: ExtraFields) {
+        self.extra = extra;
+    }
+}
+
+/// (De)serializes [`ExtraFields`] as JSON text in binary formats
+///
+/// The bincode cache can't decode arbitrary JSON values, so they are stored
+/// as one JSON string there and as a plain object in JSON.
+mod extra_fields {
+    use super::ExtraFields;
+    use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(extra: &ExtraFields, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            extra.serialize(serializer)
+        } else {
+            serde_json::to_string(extra).map_err(ser::Error::custom)?.serialize(serializer)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<ExtraFields, D::Error> {
+        if deserializer.is_human_readable() {
+            ExtraFields::deserialize(deserializer)
+        } else {
+            serde_json::from_str(&String::deserialize(deserializer)?).map_err(de::Error::custom)
+        }
+    }
 }
 
 /// Comprehensive price analysis including trends and volatility
 /// 
 /// Contains calculated metrics for price movement analysis including
 /// short-term and long-term changes, volatility measures, and trend direction.
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct PriceAnalysis {
     pub current_price: f64,
     pub day_change: f64,
@@ -58,8 +131,2330 @@ pub struct PriceAnalysis {
     pub week_change_percent: f64,
     pub month_change: f64,
     pub month_change_percent: f64,
+    /// Standard deviation of the last 30 daily averages, in ISK
     pub volatility: f64,
-    pub trend: String,
+    /// `volatility` relative to the 30-day mean price, in percent
+    #[serde(default)]
+    pub volatility_percent: f64,
+    /// Annualized standard deviation of daily log returns, in percent
+    #[serde(default)]
+    pub annualized_volatility_percent: f64,
+    /// 14-day average true range, in ISK
+    #[serde(default)]
+    pub average_true_range: f64,
+    /// Volume-weighted move over the last week, confirmed by volume when strong
+    pub trend: PriceTrend,
+    /// The price and volume changes `trend` was read from
+    #[serde(default)]
+    pub trend_evidence: TrendEvidence,
+    /// Moving averages, momentum and band indicators from the same history
+    #[serde(default)]
+    pub indicators: TechnicalIndicators,
+    /// How recent and complete the history behind the analysis is
+    #[serde(default)]
+    pub data_freshness: DataFreshness,
+}
+
+/// How recent and complete a stretch of daily history is
+///
+/// ESI publishes a day's history a day late and leaves out days without
+/// trades, so the newest row is rarely today and thin items have gaps.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct DataFreshness {
+    /// Newest day of history, `YYYY-MM-DD`
+    pub last_data_date: String,
+    /// Calendar days checked, ending at `last_data_date`
+    pub window_days: u32,
+    /// Days in the window without a history row
+    pub days_missing: u32,
+    /// Longest run of consecutive missing days in the window
+    pub longest_gap_days: u32,
+}
+
+/// Technical indicators calculated from daily average prices
+/// 
+/// Each value is `None` when the history is too short to calculate it
+/// (e.g. `sma_30` needs 30 days, MACD needs 34).
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct TechnicalIndicators {
+    pub sma_7: Option<f64>,
+    pub sma_30: Option<f64>,
+    pub ema_7: Option<f64>,
+    pub ema_30: Option<f64>,
+    /// 14-day relative strength index (0-100, above 70 overbought, below 30 oversold)
+    pub rsi_14: Option<f64>,
+    /// MACD line (12-day EMA minus 26-day EMA)
+    pub macd: Option<f64>,
+    /// 9-day EMA of the MACD line
+    pub macd_signal: Option<f64>,
+    pub macd_histogram: Option<f64>,
+    /// 20-day Bollinger bands at two standard deviations
+    pub bollinger_upper: Option<f64>,
+    pub bollinger_middle: Option<f64>,
+    pub bollinger_lower: Option<f64>,
+    /// Band width relative to the middle band ((upper - lower) / middle)
+    pub bollinger_width: Option<f64>,
+}
+
+/// Represents one of the authenticated character's own market orders
+/// 
+/// Returned by ESI `/characters/{character_id}/orders/`. Unlike public orders it
+/// carries the region and escrow, and some fields are only present for buy orders.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CharacterOrder {
+    pub duration: i32,
+    #[serde(default)]
+    pub escrow: Option<f64>,
+    #[serde(default)]
+    pub is_buy_order: bool,
+    pub is_corporation: bool,
+    pub issued: String,
+    pub location_id: i64,
+    #[serde(default)]
+    pub min_volume: Option<i32>,
+    pub order_id: i64,
+    pub price: f64,
+    pub range: String,
+    pub region_id: i32,
+    pub type_id: i32,
+    pub volume_remain: i32,
+    pub volume_total: i32,
+}
+
+/// A character's market transaction from ESI `/characters/{character_id}/wallet/transactions/`
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct WalletTransaction {
+    pub client_id: i32,
+    /// When the transaction happened (RFC 3339)
+    pub date: String,
+    pub is_buy: bool,
+    pub is_personal: bool,
+    pub journal_ref_id: i64,
+    pub location_id: i64,
+    pub quantity: i32,
+    pub transaction_id: i64,
+    pub type_id: i32,
+    pub unit_price: f64,
+}
+
+/// Competitive status of a character order against the current order book
+/// 
+/// A sell order is undercut when someone sells cheaper at the same location;
+/// a buy order is outbid when someone buys for more at the same location.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct OrderUndercutStatus {
+    pub order: CharacterOrder,
+    /// Best competing price at the order's location, if any competitor exists
+    pub best_competitor_price: Option<f64>,
+    /// How far the competitor is ahead of this order (always positive when undercut)
+    pub undercut_by: Option<f64>,
+    /// Number of competing orders on the same side at the same location
+    pub competitor_count: usize,
+    pub is_undercut: bool,
+}
+
+/// Total volume resting at a single price on one side of the book
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct PriceLevel {
+    pub price: f64,
+    pub volume: i64,
+    pub order_count: usize,
+}
+
+/// Cumulative volume within a percentage band around the mid-price
+/// 
+/// Buy volume counts bids priced at or above `mid * (1 - percent/100)`;
+/// sell volume counts asks priced at or below `mid * (1 + percent/100)`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct DepthBand {
+    pub percent: f64,
+    pub buy_volume: i64,
+    pub buy_isk: f64,
+    pub sell_volume: i64,
+    pub sell_isk: f64,
+}
+
+/// A price level holding unusually large volume compared to the rest of its side
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct OrderWall {
+    pub is_buy_order: bool,
+    pub price: f64,
+    pub volume: i64,
+    /// Level volume divided by the median level volume on the same side
+    pub size_vs_median: f64,
+}
+
+/// Order book depth for one item, bucketed by price level
+/// 
+/// Buy levels are sorted from the best (highest) bid down and sell levels from
+/// the best (lowest) ask up. Prices are only comparable within a single market,
+/// so callers normally build this from orders of one type in one region.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct OrderBookDepth {
+    pub best_bid: Option<f64>,
+    pub best_ask: Option<f64>,
+    pub mid_price: Option<f64>,
+    pub buy_levels: Vec<PriceLevel>,
+    pub sell_levels: Vec<PriceLevel>,
+    /// Cumulative depth at ±1%, ±5% and ±10% of the mid-price
+    pub bands: Vec<DepthBand>,
+    pub walls: Vec<OrderWall>,
+    pub target_price: Option<f64>,
+    /// Volume that has to trade for the best price to reach `target_price`
+    pub volume_to_target: Option<i64>,
+    /// ISK value of the orders between the current price and `target_price`
+    pub isk_to_target: Option<f64>,
+}
+
+/// A public contract from ESI `/contracts/public/{region_id}/`
+/// 
+/// Which optional fields are present depends on `contract_type`: courier
+/// contracts carry reward, collateral, volume and the route, item exchanges a price.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PublicContract {
+    pub contract_id: i64,
+    #[serde(rename = "type")]
+    pub contract_type: String,
+    pub date_issued: String,
+    pub date_expired: String,
+    pub issuer_id: i64,
+    pub issuer_corporation_id: i64,
+    #[serde(default)]
+    pub for_corporation: bool,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub start_location_id: Option<i64>,
+    #[serde(default)]
+    pub end_location_id: Option<i64>,
+    #[serde(default)]
+    pub price: Option<f64>,
+    #[serde(default)]
+    pub buyout: Option<f64>,
+    #[serde(default)]
+    pub reward: Option<f64>,
+    #[serde(default)]
+    pub collateral: Option<f64>,
+    /// Volume in m³
+    #[serde(default)]
+    pub volume: Option<f64>,
+    #[serde(default)]
+    pub days_to_complete: Option<i32>,
+}
+
+/// An item in a public contract, from ESI `/contracts/public/items/{contract_id}/`
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ContractItem {
+    pub record_id: i64,
+    pub type_id: i32,
+    pub quantity: i64,
+    /// `true` if the issuer hands the item over, `false` if the acceptor must supply it
+    pub is_included: bool,
+    #[serde(default)]
+    pub is_blueprint_copy: bool,
+}
+
+/// Going rates for public courier contracts on one route
+/// 
+/// Medians are used throughout so a single outlier contract (e.g. a 1 m³
+/// contract with a huge reward) doesn't skew the norm.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct CourierRouteRate {
+    pub start_location_id: i64,
+    pub end_location_id: i64,
+    pub contract_count: usize,
+    pub median_isk_per_m3: f64,
+    pub min_isk_per_m3: f64,
+    pub max_isk_per_m3: f64,
+    pub median_reward: f64,
+    pub median_volume: f64,
+    pub median_collateral: f64,
+    /// Reward as a percentage of collateral
+    pub median_reward_to_collateral_percent: Option<f64>,
+    pub median_days_to_complete: Option<f64>,
+}
+
+/// A region from ESI `/universe/regions/{region_id}/`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RegionInfo {
+    pub region_id: i32,
+    pub name: String,
+    pub constellations: Vec<i32>,
+}
+
+/// A constellation from ESI `/universe/constellations/{constellation_id}/`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ConstellationInfo {
+    pub constellation_id: i32,
+    pub name: String,
+    pub region_id: i32,
+    pub systems: Vec<i32>,
+}
+
+/// Position of an object in space, in meters
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct Position {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+/// A solar system from ESI `/universe/systems/{system_id}/`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SystemInfo {
+    pub system_id: i32,
+    pub name: String,
+    pub constellation_id: i32,
+    pub security_status: f64,
+    pub position: Position,
+    #[serde(default)]
+    pub stations: Vec<i64>,
+}
+
+/// An NPC station from ESI `/universe/stations/{station_id}/`
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct StationInfo {
+    pub station_id: i64,
+    pub name: String,
+    pub system_id: i32,
+}
+
+/// A resolved ID from ESI `/universe/names/`
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct UniverseName {
+    pub id: i64,
+    pub name: String,
+    /// What the ID refers to (`inventory_type`, `station`, `solar_system`, ...)
+    pub category: String,
+}
+
+/// Tranquility server status, from ESI `/status/`
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ServerStatus {
+    pub players: i64,
+    pub server_version: String,
+    /// When the server last came up, e.g. after daily downtime
+    pub start_time: String,
+    /// Whether only privileged accounts can log in
+    #[serde(default)]
+    pub vip: Option<bool>,
+}
+
+/// A player-owned structure, from ESI `/universe/structures/{structure_id}/`
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct StructureInfo {
+    pub name: String,
+    pub solar_system_id: i32,
+    #[serde(default)]
+    pub owner_id: Option<i32>,
+    #[serde(default)]
+    pub type_id: Option<i32>,
+}
+
+/// Kills in a solar system over the last hour, from ESI `/universe/system_kills/`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SystemKills {
+    pub system_id: i32,
+    pub ship_kills: i64,
+    pub pod_kills: i64,
+    pub npc_kills: i64,
+}
+
+/// One activity's cost index in a solar system
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct IndustryCostIndex {
+    /// Activity name, e.g. "manufacturing"
+    pub activity: String,
+    pub cost_index: f64,
+}
+
+/// Industry cost indices of a solar system, from ESI `/industry/systems/`
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct IndustrySystem {
+    pub solar_system_id: i32,
+    pub cost_indices: Vec<IndustryCostIndex>,
+}
+
+/// CCP's reference prices for an item, from ESI `/markets/prices/`
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct MarketPrice {
+    pub type_id: i32,
+    /// Price industry job fees are based on
+    pub adjusted_price: Option<f64>,
+    pub average_price: Option<f64>,
+}
+
+/// One insurance level of a ship
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct InsuranceLevelPrice {
+    /// Level name, e.g. "Platinum"
+    pub name: String,
+    /// Premium paid to insure the ship
+    pub cost: f64,
+    /// Paid out when the ship is destroyed
+    pub payout: f64,
+}
+
+/// A ship's insurance levels, from ESI `/insurance/prices/`
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct InsurancePrice {
+    pub type_id: i32,
+    pub levels: Vec<InsuranceLevelPrice>,
+}
+
+/// Jumps into a solar system over the last hour, from ESI `/universe/system_jumps/`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SystemJumps {
+    pub system_id: i32,
+    pub ship_jumps: i64,
+}
+
+/// Hourly player activity in one solar system
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct SystemActivity {
+    pub system_id: i32,
+    pub ship_jumps: i64,
+    pub ship_kills: i64,
+    pub pod_kills: i64,
+    pub npc_kills: i64,
+    pub demand_index: f64,
+}
+
+/// Activity-weighted demand estimate for a region over the last hour
+/// 
+/// Jumps approximate traffic (and so player count), destroyed ships are
+/// replacement purchases and NPC kills indicate ratters consuming ammunition
+/// and modules. `relative_score` scales the demand index to 0-100 against the
+/// most active region in the same comparison.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RegionActivity {
+    pub region_id: i32,
+    pub region_name: String,
+    pub system_count: usize,
+    /// Systems with any jumps or kills in the last hour
+    pub active_systems: usize,
+    pub ship_jumps: i64,
+    pub ship_kills: i64,
+    pub pod_kills: i64,
+    pub npc_kills: i64,
+    pub demand_index: f64,
+    pub relative_score: f64,
+    /// Busiest systems by demand index, most active first
+    pub top_systems: Vec<SystemActivity>,
+}
+
+/// How easily an item can be traded at scale, from history and the current book
+/// 
+/// `avg_spread_percent` uses each day's highest and lowest trade as a proxy for
+/// the spread, since ESI history doesn't record the book itself.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct LiquidityScore {
+    /// Days of history the averages are based on
+    pub days: usize,
+    pub avg_daily_volume: f64,
+    pub avg_daily_order_count: f64,
+    /// Average ISK traded per day (volume × average price)
+    pub avg_daily_turnover: f64,
+    pub avg_spread_percent: f64,
+    /// Spread between the best buy and sell order right now
+    pub current_spread_percent: Option<f64>,
+    /// Share of days with any trades, in percent
+    pub active_days_percent: f64,
+    /// Combined score from 0 (illiquid) to 100 (trades at scale)
+    pub score: f64,
+    pub rating: String,
+}
+
+/// One jump of a jump freighter route
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct JumpLeg {
+    pub from_system_id: i32,
+    pub from_name: String,
+    pub to_system_id: i32,
+    pub to_name: String,
+    pub distance_ly: f64,
+    /// Isotopes burned for this jump
+    pub fuel_units: i64,
+}
+
+/// Profit of hauling one cargo along a jump freighter route after fuel
+/// 
+/// Revenue is shown both for selling into the destination's best buy order
+/// (instant) and for listing at the destination's lowest sell price.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct JumpFreighterProfit {
+    pub ship: String,
+    pub legs: Vec<JumpLeg>,
+    pub total_distance_ly: f64,
+    pub fuel_type_id: i32,
+    /// Report label for the fuel, e.g. "Nitrogen Isotopes (17888)"
+    #[serde(default)]
+    pub fuel_label: String,
+    pub fuel_units: i64,
+    pub fuel_unit_price: f64,
+    pub fuel_cost: f64,
+    pub type_id: i32,
+    /// Report label for the cargo item, e.g. "Tritanium (34)"
+    #[serde(default)]
+    pub type_label: String,
+    pub quantity: i64,
+    /// Cost of buying the cargo from the source region's lowest sell orders
+    pub purchase_cost: f64,
+    pub instant_sell_revenue: Option<f64>,
+    pub listed_sell_revenue: Option<f64>,
+    pub instant_profit_after_fuel: Option<f64>,
+    pub listed_profit_after_fuel: Option<f64>,
+}
+
+/// An item type from ESI `/universe/types/{type_id}/`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TypeInfo {
+    pub type_id: i32,
+    pub name: String,
+    pub group_id: i32,
+    #[serde(default)]
+    pub market_group_id: Option<i32>,
+    /// Assembled volume in m³
+    #[serde(default)]
+    pub volume: Option<f64>,
+    /// Volume in m³ when packaged (ships and some modules shrink when packaged)
+    #[serde(default)]
+    pub packaged_volume: Option<f64>,
+    #[serde(default)]
+    pub published: bool,
+    /// Meta level, when known from the SDE or dogma attributes
+    #[serde(default)]
+    pub meta_level: Option<i32>,
+    /// Tech level (1, 2 or 3), when known from the SDE or dogma attributes
+    #[serde(default)]
+    pub tech_level: Option<i32>,
+    /// Dogma attribute values as ESI lists them; empty for types from the SDE
+    #[serde(default)]
+    pub dogma_attributes: Vec<DogmaAttributeValue>,
+}
+
+/// One dogma attribute of an item type
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct DogmaAttributeValue {
+    pub attribute_id: i32,
+    pub value: f64,
+}
+
+impl TypeInfo {
+    /// Value of a dogma attribute ESI listed for this type
+    pub fn dogma_attribute(&self, attribute_id: i32) -> Option<f64> {
+        self.dogma_attributes
+            .iter()
+            .find(|attribute| attribute.attribute_id == attribute_id)
+            .map(|attribute| attribute.value)
+    }
+
+    /// Volume of one unit in a cargo hold in m³ (packaged where the item packages)
+    pub fn cargo_volume(&self) -> f64 {
+        self.packaged_volume.or(self.volume).unwrap_or(0.0)
+    }
+}
+
+/// Profit of hauling one item between two systems' markets
+/// 
+/// Units are bought from the origin system's sell orders and sold into the
+/// destination system's buy orders for as long as each unit stays profitable
+/// after sales tax and the cargo hold has room.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct HaulingOpportunity {
+    pub type_id: i32,
+    pub name: String,
+    /// Volume of one unit in m³ (packaged where applicable)
+    pub unit_volume: f64,
+    pub units: i64,
+    pub purchase_cost: f64,
+    /// Revenue before sales tax
+    pub sales_revenue: f64,
+    pub sales_tax: f64,
+    /// Revenue after sales tax minus the purchase cost
+    pub profit: f64,
+    /// Profit relative to the purchase cost
+    pub margin_percent: f64,
+    /// Profit per m³ of cargo used
+    pub isk_per_m3: f64,
+    /// Profit divided by the number of jumps on the route
+    pub profit_per_jump: f64,
+}
+
+/// Hauling profitability of a set of items along one gate route
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HaulingAnalysis {
+    pub origin_system_id: i32,
+    pub destination_system_id: i32,
+    pub source_region_id: i32,
+    pub destination_region_id: i32,
+    /// Stargate jumps between the two systems
+    pub jumps: usize,
+    /// Systems on the route, including the origin and destination
+    pub route: Vec<i32>,
+    pub cargo_capacity_m3: f64,
+    /// Low- and null-sec stretches of the route, in route order
+    #[serde(default)]
+    pub route_segments: Vec<RouteSegment>,
+    /// Danger of the route from the last hour of kills, when that could be fetched
+    #[serde(default)]
+    pub route_risk: Option<RouteRisk>,
+    /// Profitable items, highest profit per jump first
+    pub opportunities: Vec<HaulingOpportunity>,
+}
+
+/// Security band of a solar system, as shown in game
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecurityClass {
+    /// 0.5 to 1.0
+    HighSec,
+    /// 0.1 to 0.4
+    LowSec,
+    /// 0.0 and below, including wormhole space
+    NullSec,
+}
+
+impl SecurityClass {
+    /// Band of a system's true security status, rounded the way the game displays it
+    pub fn from_security_status(security_status: f64) -> Self {
+        if security_status >= 0.45 {
+            Self::HighSec
+        } else if security_status > 0.0 {
+            Self::LowSec
+        } else {
+            Self::NullSec
+        }
+    }
+}
+
+impl std::fmt::Display for SecurityClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::HighSec => "high-sec",
+            Self::LowSec => "low-sec",
+            Self::NullSec => "null-sec",
+        })
+    }
+}
+
+/// Who holds a solar system, from ESI `/sovereignty/map/`
+///
+/// Empire and NPC null-sec systems carry a faction, claimed null-sec systems
+/// an alliance and its corporation; unclaimed systems carry neither.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct SovereigntySystem {
+    pub system_id: i32,
+    #[serde(default)]
+    pub alliance_id: Option<i32>,
+    #[serde(default)]
+    pub corporation_id: Option<i32>,
+    #[serde(default)]
+    pub faction_id: Option<i32>,
+}
+
+/// One system on a planned gate route
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct RouteSystem {
+    pub system_id: i32,
+    pub name: String,
+    pub security_status: f64,
+    pub security_class: SecurityClass,
+}
+
+/// A run of consecutive low- or null-sec systems on a route
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct RouteSegment {
+    pub security_class: SecurityClass,
+    /// Names of the first and last system of the run (the same for a single system)
+    pub from_system: String,
+    pub to_system: String,
+    pub system_count: usize,
+    pub lowest_security_status: f64,
+}
+
+/// A gate route between two systems with the security of every system on it
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct RoutePlan {
+    pub origin_system_id: i32,
+    pub destination_system_id: i32,
+    /// Route preference it was planned with: shortest, secure or insecure
+    pub flag: String,
+    /// Stargate jumps between the two systems
+    pub jumps: usize,
+    /// Systems on the route, including the origin and destination
+    pub systems: Vec<RouteSystem>,
+    /// Low- and null-sec stretches of the route, in route order
+    pub segments: Vec<RouteSegment>,
+}
+
+/// Recent danger in one system on a route, from the last hour of kills and jumps
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct SystemRisk {
+    pub system_id: i32,
+    pub name: String,
+    pub security_status: f64,
+    pub security_class: SecurityClass,
+    pub ship_kills: i64,
+    pub pod_kills: i64,
+    pub ship_jumps: i64,
+    /// Pod kills and a high share of kills to traffic, the pattern of a gate camp
+    pub likely_camp: bool,
+    /// Danger from 0 to 100
+    pub risk_score: f64,
+}
+
+/// A planned route rated by the kills along it over the last hour
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct RouteRisk {
+    pub origin_system_id: i32,
+    pub destination_system_id: i32,
+    /// Route preference it was planned with: shortest, secure or insecure
+    pub flag: String,
+    pub jumps: usize,
+    /// Every system on the route, in route order
+    pub systems: Vec<SystemRisk>,
+    /// Combined danger of the route from 0 to 100
+    pub risk_score: f64,
+    /// "Low", "Moderate", "High" or "Extreme"
+    pub rating: String,
+}
+
+/// Suggested reward for a courier contract along one gate route
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct CourierQuote {
+    pub origin_system_id: i32,
+    pub origin_name: String,
+    pub destination_system_id: i32,
+    pub destination_name: String,
+    /// Stargate jumps between the two systems
+    pub jumps: usize,
+    /// Jumps into high-, low- and null-sec systems
+    pub high_sec_jumps: usize,
+    pub low_sec_jumps: usize,
+    pub null_sec_jumps: usize,
+    /// Lowest security status on the route and the system it belongs to
+    pub lowest_security_status: f64,
+    pub lowest_security_system: String,
+    /// Low- and null-sec stretches of the route, in route order
+    pub segments: Vec<RouteSegment>,
+    pub volume_m3: f64,
+    pub collateral: f64,
+    /// Reward parts: per jump, per m³ per jump, and the collateral premium
+    pub jump_reward: f64,
+    pub volume_reward: f64,
+    pub collateral_reward: f64,
+    /// Sum of the parts, raised to the minimum reward
+    pub reward: f64,
+}
+
+/// A market group from ESI `/markets/groups/{market_group_id}/`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MarketGroupInfo {
+    pub market_group_id: i32,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub parent_group_id: Option<i32>,
+    /// Item types directly in this group (empty for groups that only hold subgroups)
+    #[serde(default)]
+    pub types: Vec<i32>,
+}
+
+/// Ordering applied to market scan results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanSort {
+    /// Widest spread relative to the sell price first
+    SpreadPercent,
+    /// Highest average daily volume first
+    Volume,
+    /// Highest spread × average daily volume first
+    #[default]
+    ProfitPotential,
+}
+
+impl std::str::FromStr for ScanSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().replace('-', "_").as_str() {
+            "spread" | "spread_percent" => Ok(Self::SpreadPercent),
+            "volume" => Ok(Self::Volume),
+            "profit" | "profit_potential" => Ok(Self::ProfitPotential),
+            other => Err(format!("Unknown sort order: {other}")),
+        }
+    }
+}
+
+/// One item's row in a market scan
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ScanResult {
+    pub type_id: i32,
+    /// Report label for the item, e.g. "Tritanium (34)"
+    pub type_label: String,
+    pub best_buy: Option<f64>,
+    pub best_sell: Option<f64>,
+    pub spread: Option<f64>,
+    /// Spread relative to the best sell price
+    pub spread_percent: Option<f64>,
+    pub buy_orders: usize,
+    pub sell_orders: usize,
+    /// Average daily volume over the last 30 days of history
+    pub avg_daily_volume: f64,
+    /// Spread × average daily volume: an upper bound on daily station-trading margin
+    pub profit_potential: f64,
+}
+
+/// Results of scanning many items in one region
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MarketScan {
+    pub region_id: i32,
+    pub sort_by: ScanSort,
+    /// Ranked results for items that could be fetched
+    pub results: Vec<ScanResult>,
+    /// Items that failed, with the error message
+    pub failures: Vec<(i32, String)>,
+}
+
+/// Direction of a price trend over one timeframe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrendDirection {
+    Up,
+    Down,
+    Flat,
+}
+
+impl std::fmt::Display for TrendDirection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Up => "Up",
+            Self::Down => "Down",
+            Self::Flat => "Flat",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Size and direction of the last week's price move, as reported by price analysis
+///
+/// Also reads the display names analyses were serialized with when the trend was free text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceTrend {
+    /// Up more than 5%
+    #[serde(alias = "Strong Upward")]
+    StrongUpward,
+    /// Up 2–5%
+    #[serde(alias = "Upward")]
+    Upward,
+    /// Within 2% either way
+    #[serde(alias = "Stable")]
+    Stable,
+    /// Down 2–5%
+    #[serde(alias = "Downward")]
+    Downward,
+    /// Down more than 5%
+    #[serde(alias = "Strong Downward")]
+    StrongDownward,
+}
+
+impl PriceTrend {
+    /// The direction of the move, without its size
+    pub fn direction(&self) -> TrendDirection {
+        match self {
+            Self::StrongUpward | Self::Upward => TrendDirection::Up,
+            Self::Stable => TrendDirection::Flat,
+            Self::Downward | Self::StrongDownward => TrendDirection::Down,
+        }
+    }
+}
+
+/// What a price analysis trend label was read from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+pub struct TrendEvidence {
+    /// Volume-weighted average price of the last window against the one before, in percent
+    pub price_change_percent: f64,
+    /// Traded volume over the same windows, in percent; `None` when the earlier window had none
+    pub volume_change_percent: Option<f64>,
+    /// Whether volume rose enough to confirm a strong move
+    pub volume_confirmed: bool,
+}
+
+impl std::fmt::Display for PriceTrend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::StrongUpward => "Strong Upward",
+            Self::Upward => "Upward",
+            Self::Stable => "Stable",
+            Self::Downward => "Downward",
+            Self::StrongDownward => "Strong Downward",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Price trend fitted over the last `days` days
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct TimeframeTrend {
+    pub days: usize,
+    /// Price change over the window along the fitted trend line, in percent
+    pub change_percent: f64,
+    /// Fitted change divided by the noise expected over the window from daily
+    /// volatility; beyond ±1 the move stands out from random drift
+    pub strength: f64,
+    pub direction: TrendDirection,
+}
+
+/// Whether short, medium and long-term trends point the same way
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct TrendAgreement {
+    /// 7-day trend
+    pub short: Option<TimeframeTrend>,
+    /// 30-day trend
+    pub medium: Option<TimeframeTrend>,
+    /// 90-day trend
+    pub long: Option<TimeframeTrend>,
+    /// Composite conviction from -100 (strong downtrend on every timeframe)
+    /// to +100 (strong uptrend on every timeframe)
+    pub conviction: f64,
+    /// Plain-language reading, e.g. "Short-term bounce in a downtrend"
+    pub reading: String,
+}
+
+/// Statistical model behind a price forecast
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ForecastModel {
+    /// Least-squares straight line through the daily prices
+    LinearRegression,
+    /// Additive Holt-Winters smoothing with a weekly season
+    HoltWinters,
+}
+
+impl std::fmt::Display for ForecastModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::LinearRegression => "Linear regression",
+            Self::HoltWinters => "Holt-Winters (weekly seasonality)",
+        })
+    }
+}
+
+/// Forecast price for one future day, with confidence bands
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ForecastPoint {
+    /// Days after the last day of history
+    pub days_ahead: usize,
+    /// Forecast date (YYYY-MM-DD)
+    pub date: String,
+    pub expected: f64,
+    /// 80% band; bounds never go below zero
+    pub lower_80: f64,
+    pub upper_80: f64,
+    /// 95% band
+    pub lower_95: f64,
+    pub upper_95: f64,
+}
+
+/// One model's forecast
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ModelForecast {
+    pub model: ForecastModel,
+    /// One point per day ahead, nearest first
+    pub points: Vec<ForecastPoint>,
+    /// Standard deviation of the model's in-sample errors, in ISK
+    pub residual_std_dev: f64,
+}
+
+/// Price forecasts extrapolated from market history
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct PriceForecast {
+    /// Last day of history the models were fitted to (YYYY-MM-DD)
+    pub last_date: String,
+    /// Average price on that day
+    pub last_price: f64,
+    /// Days of history the models were fitted to
+    pub fitted_days: usize,
+    pub linear: ModelForecast,
+    /// Needs at least two weeks of history
+    pub holt_winters: Option<ModelForecast>,
+}
+
+/// Daily series an anomaly was found in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum AnomalyMetric {
+    /// Average traded price
+    Price,
+    /// Units traded
+    Volume,
+}
+
+impl std::fmt::Display for AnomalyMetric {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Price => "Price",
+            Self::Volume => "Volume",
+        })
+    }
+}
+
+/// A day whose price or volume broke far out of its recent range
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct MarketAnomaly {
+    /// Day of the anomaly (YYYY-MM-DD)
+    pub date: String,
+    pub metric: AnomalyMetric,
+    /// Average price or units traded that day
+    pub value: f64,
+    /// Mean over the baseline days before it
+    pub baseline_mean: f64,
+    /// Standard deviation over the baseline days
+    pub baseline_std_dev: f64,
+    /// Standard deviations from the baseline mean; negative for drops
+    pub z_score: f64,
+}
+
+/// Price and volume anomalies found in market history
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct AnomalyReport {
+    /// Days in the rolling baseline before each checked day
+    pub window_days: usize,
+    /// Standard deviations from the baseline that count as an anomaly
+    pub threshold: f64,
+    /// Days compared against a full baseline
+    pub days_checked: usize,
+    /// Anomalies, oldest first
+    pub anomalies: Vec<MarketAnomaly>,
+}
+
+/// Edge of a price channel a breakout crossed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum BreakoutDirection {
+    /// Above the channel high
+    Upside,
+    /// Below the channel low
+    Downside,
+}
+
+impl std::fmt::Display for BreakoutDirection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Upside => "Upside",
+            Self::Downside => "Downside",
+        })
+    }
+}
+
+/// A day whose average price closed outside the range of the days before it
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Breakout {
+    /// Day of the breakout (YYYY-MM-DD)
+    pub date: String,
+    pub direction: BreakoutDirection,
+    /// Average price that day
+    pub price: f64,
+    /// Highest and lowest daily average over the channel days before it
+    pub channel_high: f64,
+    pub channel_low: f64,
+    /// How far past the broken edge the price went, in percent
+    pub beyond_percent: f64,
+    /// Units traded that day against the channel days' average
+    pub volume_ratio: f64,
+}
+
+/// Donchian-style channel breakouts found in market history
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct BreakoutReport {
+    /// Days of data in the channel each day is compared against
+    pub window_days: usize,
+    /// Newest day's average price
+    pub current_price: f64,
+    /// Channel the newest day was compared against
+    pub channel_high: f64,
+    pub channel_low: f64,
+    /// The newest day's breakout, if it broke out
+    pub current: Option<Breakout>,
+    /// Every breakout in the history, oldest first
+    pub breakouts: Vec<Breakout>,
+}
+
+/// Why a backtested position was closed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum BacktestExit {
+    /// The price reached the profit target
+    TakeProfit,
+    /// The price fell to the stop loss
+    StopLoss,
+    /// The position was held for the maximum number of days
+    MaxHolding,
+    /// History ran out with the position open; valued at the last price
+    EndOfData,
+}
+
+impl std::fmt::Display for BacktestExit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::TakeProfit => "take profit",
+            Self::StopLoss => "stop loss",
+            Self::MaxHolding => "max holding",
+            Self::EndOfData => "still open",
+        })
+    }
+}
+
+/// One round trip of a backtested strategy
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct BacktestTrade {
+    /// Day bought (YYYY-MM-DD)
+    pub entry_date: String,
+    pub entry_price: f64,
+    /// Day sold (YYYY-MM-DD)
+    pub exit_date: String,
+    pub exit_price: f64,
+    pub exit: BacktestExit,
+    pub holding_days: i64,
+    /// Broker fees on both orders plus sales tax, in ISK
+    pub fees: f64,
+    /// Profit after fees, in ISK
+    pub profit: f64,
+    /// Profit after fees against the ISK committed, in percent
+    pub return_percent: f64,
+}
+
+/// Hypothetical outcome of replaying trading rules over market history
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct BacktestResult {
+    /// First and last day replayed (YYYY-MM-DD)
+    pub from_date: String,
+    pub to_date: String,
+    pub days_replayed: usize,
+    /// Trades in the order they were opened
+    pub trades: Vec<BacktestTrade>,
+    /// Profit after fees over every trade, in ISK
+    pub total_profit: f64,
+    pub total_fees: f64,
+    /// Share of trades closed at a profit, in percent
+    pub win_rate_percent: f64,
+    /// Largest fall from a peak in cumulative profit, open positions valued daily, in ISK
+    pub max_drawdown: f64,
+    /// `max_drawdown` against the ISK committed per trade, in percent
+    pub max_drawdown_percent: f64,
+    /// Price change over the replayed days, for comparison with simply holding
+    pub buy_and_hold_percent: f64,
+}
+
+/// How one item performed over the dates it shares with the others
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ItemPerformance {
+    pub type_id: i32,
+    /// Display label, e.g. "PLEX (44992)"
+    pub type_label: String,
+    /// Average price on the first shared day
+    pub first_price: f64,
+    /// Average price on the last shared day
+    pub last_price: f64,
+    /// Change from the first to the last shared day, in percent
+    pub return_percent: f64,
+    /// Standard deviation of daily log returns, in percent
+    pub daily_volatility_percent: f64,
+}
+
+/// Pearson correlation of two items' daily returns
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ItemCorrelation {
+    pub first_type_id: i32,
+    pub second_type_id: i32,
+    /// From -1 to 1; `None` when either item's price never moved
+    pub correlation: Option<f64>,
+    /// How much the first item gained on the second, in percent
+    pub relative_return_percent: f64,
+}
+
+/// Price relationships between items in one region
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ItemComparison {
+    pub region_id: i32,
+    /// First and last day every item traded on (YYYY-MM-DD)
+    pub start_date: String,
+    pub end_date: String,
+    /// Days every item traded on; returns are taken between consecutive ones
+    pub common_days: usize,
+    /// Items in the order they were requested
+    pub items: Vec<ItemPerformance>,
+    /// Every pair of items, in request order
+    pub correlations: Vec<ItemCorrelation>,
+}
+
+/// How one item's price and volume changed across an event
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct SpeculationReaction {
+    pub type_id: i32,
+    /// Display label, e.g. "PLEX (44992)"
+    pub type_label: String,
+    /// Volume-weighted average price over the window before and from the event
+    pub price_before: f64,
+    pub price_after: f64,
+    pub price_change_percent: f64,
+    /// Average units traded per calendar day in each window
+    pub volume_before: f64,
+    pub volume_after: f64,
+    /// `None` when nothing traded before the event
+    pub volume_change_percent: Option<f64>,
+    /// Price move in standard deviations of the noise expected before the event; negative for drops
+    pub significance: f64,
+}
+
+/// Items ranked by how strongly they reacted to an event
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct SpeculationScreen {
+    pub region_id: i32,
+    /// Event name, or its date when given as one
+    pub event: String,
+    /// Day of the event (YYYY-MM-DD)
+    pub event_date: String,
+    /// Calendar days compared on each side of the event
+    pub window_days: usize,
+    /// Items by descending absolute significance
+    pub reactions: Vec<SpeculationReaction>,
+    /// Items without history on both sides of the event, as `(type_id, reason)`
+    pub skipped: Vec<(i32, String)>,
+}
+
+/// Units of an item held, with what they cost
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct PortfolioPosition {
+    pub type_id: i32,
+    pub quantity: i64,
+    /// Total ISK paid for the units held
+    pub cost_basis: f64,
+}
+
+impl PortfolioPosition {
+    /// Average ISK paid per unit
+    pub fn average_cost(&self) -> f64 {
+        if self.quantity > 0 {
+            self.cost_basis / self.quantity as f64
+        } else {
+            0.0
+        }
+    }
+}
+
+/// A position priced against a region's current orders
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct PositionValuation {
+    pub position: PortfolioPosition,
+    /// Display label, e.g. "Tritanium (34)"
+    pub type_label: String,
+    pub best_buy: Option<f64>,
+    pub best_sell: Option<f64>,
+    /// Quantity × best sell: what the units are listed for
+    pub market_value: Option<f64>,
+    /// Quantity × best buy: what selling into buy orders right now would fetch
+    pub liquidation_value: Option<f64>,
+    /// Market value less cost basis
+    pub unrealized_pnl: Option<f64>,
+    /// Unrealized P&L relative to cost basis
+    pub unrealized_pnl_percent: Option<f64>,
+    /// Why the position couldn't be priced, if it couldn't
+    pub error: Option<String>,
+}
+
+/// Every position in the portfolio valued in one region
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct PortfolioValuation {
+    pub region_id: i32,
+    /// Positions in type ID order
+    pub positions: Vec<PositionValuation>,
+    pub total_cost_basis: f64,
+    /// Totals over the positions that have a price
+    pub total_market_value: f64,
+    pub total_liquidation_value: f64,
+    /// Market value less cost basis over the positions with a sell price
+    pub total_unrealized_pnl: f64,
+}
+
+/// Whether a trade bought or sold
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TradeSide {
+    Buy,
+    Sell,
+}
+
+impl std::fmt::Display for TradeSide {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Buy => "buy",
+            Self::Sell => "sell",
+        })
+    }
+}
+
+impl std::str::FromStr for TradeSide {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "buy" | "bought" => Ok(Self::Buy),
+            "sell" | "sold" => Ok(Self::Sell),
+            other => Err(format!("Unknown trade side: {other} (expected buy or sell)")),
+        }
+    }
+}
+
+/// An executed trade in the trade journal
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct JournalTrade {
+    /// When the trade executed (RFC 3339)
+    pub date: String,
+    pub type_id: i32,
+    pub side: TradeSide,
+    pub quantity: i64,
+    pub unit_price: f64,
+    /// Broker fees and sales tax paid on the trade, in ISK
+    pub fees: f64,
+    /// ESI transaction the trade was imported from, if it was
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction_id: Option<i64>,
+}
+
+/// Realized results of one item's trades
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ItemTradeStats {
+    pub type_id: i32,
+    pub buys: usize,
+    pub sells: usize,
+    pub units_bought: i64,
+    pub units_sold: i64,
+    /// Sale proceeds less the average cost of the units sold and all fees
+    pub realized_profit: f64,
+    pub fees_paid: f64,
+    /// Sells that made money after fees
+    pub winning_sells: usize,
+    /// Share of sells that made money, in percent; `None` before any sell
+    pub win_rate_percent: Option<f64>,
+    /// Realized profit per hour between the first and last trade
+    pub isk_per_hour: Option<f64>,
+    /// Units bought and not yet sold
+    pub open_units: i64,
+    /// Units sold with no recorded purchase to match, left out of the profit
+    pub unmatched_units_sold: i64,
+}
+
+/// Realized P&L across the trade journal
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct TradeReport {
+    /// Per-item results, most profitable first
+    pub items: Vec<ItemTradeStats>,
+    pub trades: usize,
+    pub total_realized_profit: f64,
+    pub total_fees_paid: f64,
+    /// Share of all sells that made money, in percent
+    pub win_rate_percent: Option<f64>,
+    /// Realized profit per hour between the first and last trade in the journal
+    pub isk_per_hour: Option<f64>,
+}
+
+/// Which side of the book an item is priced from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceBasis {
+    /// Highest buy order: buying by placing orders, or selling instantly
+    Buy,
+    /// Lowest sell order: buying instantly, or selling by listing
+    Sell,
+}
+
+impl std::fmt::Display for PriceBasis {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Buy => "buy",
+            Self::Sell => "sell",
+        })
+    }
+}
+
+impl std::str::FromStr for PriceBasis {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "buy" => Ok(Self::Buy),
+            "sell" => Ok(Self::Sell),
+            other => Err(format!("Unknown price basis: {other} (expected buy or sell)")),
+        }
+    }
+}
+
+/// One input of a manufacturing job
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ManufacturingMaterial {
+    pub type_id: i32,
+    /// Display label, e.g. "Tritanium (34)"
+    pub type_label: String,
+    /// Quantity per run at ME 0
+    pub base_quantity: i64,
+    /// Quantity for every run after material efficiency
+    pub quantity: i64,
+    /// Hub price per unit, or `None` when the hub has no order on that side
+    pub unit_price: Option<f64>,
+    /// CCP's adjusted price, used for the job fee
+    pub adjusted_price: Option<f64>,
+}
+
+impl ManufacturingMaterial {
+    /// Quantity × unit price, when the material has a price
+    pub fn total_cost(&self) -> Option<f64> {
+        self.unit_price.map(|price| price * self.quantity as f64)
+    }
+}
+
+/// Build-vs-buy economics of a manufacturing job at a trade hub
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ManufacturingProfit {
+    pub blueprint_type_id: i32,
+    pub product_type_id: i32,
+    /// Display label, e.g. "Rifter (587)"
+    pub product_label: String,
+    pub hub: String,
+    /// Solar system the job is installed in
+    pub system_id: i32,
+    pub runs: i64,
+    pub material_efficiency: i32,
+    pub time_efficiency: i32,
+    pub units_produced: i64,
+    /// Build time for every run after time efficiency, in seconds
+    pub build_time_secs: i64,
+    pub input_basis: PriceBasis,
+    pub product_basis: PriceBasis,
+    pub materials: Vec<ManufacturingMaterial>,
+    /// Cost of every material, or `None` when any material has no price
+    pub material_cost: Option<f64>,
+    /// Base materials at adjusted prices, what the job fee is charged on
+    pub estimated_item_value: f64,
+    /// Manufacturing cost index of the system, `None` when ESI lists none
+    pub system_cost_index: Option<f64>,
+    pub facility_tax_percent: f64,
+    /// Installation fee: cost index, facility tax and SCC surcharge on the estimated item value
+    pub job_cost: f64,
+    /// Materials plus job fee
+    pub build_cost: Option<f64>,
+    pub build_cost_per_unit: Option<f64>,
+    /// Product price at the hub on the product basis side
+    pub product_unit_price: Option<f64>,
+    /// Units produced × product price
+    pub sale_value: Option<f64>,
+    /// Sales tax on the sale, plus the broker fee when it is made with a sell order
+    pub selling_fees: Option<f64>,
+    /// Sale value less selling fees and build cost
+    pub profit: Option<f64>,
+    /// Profit relative to sale value
+    pub margin_percent: Option<f64>,
+    /// Units produced at the hub's lowest sell price: what buying them instead would cost
+    pub buy_cost: Option<f64>,
+    /// Buy cost less build cost; positive when building is cheaper
+    pub build_savings: Option<f64>,
+}
+
+/// One item's share of a region's recent trading
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ItemFlow {
+    pub type_id: i32,
+    /// Display label, e.g. "Tritanium (34)"
+    pub type_label: String,
+    /// Σ volume × average price over the window
+    pub isk_volume: f64,
+    pub units_traded: i64,
+    /// Days in the window with any trades
+    pub trading_days: usize,
+    /// ISK volume spread over every day of the window
+    pub avg_daily_isk_volume: f64,
+    /// Change between the first and last trading day's average price
+    pub price_change_percent: Option<f64>,
+    pub last_trade_date: Option<String>,
+    /// Share of the region's total ISK volume across the sampled items
+    pub share_percent: f64,
+}
+
+/// A region's recent market activity across a basket of items
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct RegionFlowReport {
+    pub region_id: i32,
+    pub days: usize,
+    pub start_date: String,
+    pub end_date: String,
+    pub total_isk_volume: f64,
+    pub avg_daily_isk_volume: f64,
+    /// Items by ISK volume, largest first
+    pub items: Vec<ItemFlow>,
+    /// Items with the largest price change either way, largest first
+    pub top_movers: Vec<i32>,
+    /// Items with no trades in the last few days of the window
+    pub dead_items: Vec<i32>,
+    /// Items whose history couldn't be fetched, with the error
+    pub failures: Vec<(i32, String)>,
+}
+
+/// How often one side of an item's book was beaten across samples
+#[derive(Debug, Clone, PartialEq, Default, Deserialize, Serialize)]
+pub struct UndercutSideStats {
+    /// Best price in the first sample
+    pub first_best: Option<f64>,
+    /// Best price in the last sample
+    pub last_best: Option<f64>,
+    /// Intervals between samples in which the best price changed
+    pub best_price_changes: usize,
+    /// Orders issued or modified between samples that beat the previous best price
+    pub undercuts: usize,
+    /// Undercuts over the time between the first and last sample, with at least two samples
+    pub undercuts_per_hour: Option<f64>,
+    /// Best orders in the last sample updated within the hour before it
+    pub recently_updated_top_orders: usize,
+}
+
+/// Estimated undercut frequency for an item, from repeated order book samples
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct UndercutEstimate {
+    pub region_id: i32,
+    pub type_id: i32,
+    /// Station the estimate is limited to, if any
+    pub station_id: Option<i64>,
+    pub samples: usize,
+    /// Time between the first and last sample
+    pub window_secs: i64,
+    pub sell: UndercutSideStats,
+    pub buy: UndercutSideStats,
+}
+
+/// An item's average price change over a period
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct PriceMover {
+    pub type_id: i32,
+    /// Display label, e.g. "Tritanium (34)"
+    pub type_label: String,
+    /// Trading day the change is measured from
+    pub from_date: String,
+    /// Most recent trading day
+    pub to_date: String,
+    pub from_price: f64,
+    pub to_price: f64,
+    pub change_percent: f64,
+    /// Units traded after the from date
+    pub volume: i64,
+}
+
+/// Biggest gainers and losers in a basket of items over one period
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct TopMovers {
+    pub region_id: i32,
+    pub period: Period,
+    /// Most recent date any item traded, which changes are measured up to
+    pub end_date: Option<String>,
+    /// Items that rose, biggest rise first
+    pub gainers: Vec<PriceMover>,
+    /// Items that fell, biggest fall first
+    pub losers: Vec<PriceMover>,
+    /// Items without a change to rank: no recent trades, too little volume or no earlier price
+    pub unranked: usize,
+    /// Items whose history couldn't be fetched, with the error
+    pub failures: Vec<(i32, String)>,
+}
+
+/// One week of spreads rebuilt from daily high and low trade prices
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct SpreadPoint {
+    /// Monday of the week
+    pub period_start: String,
+    /// Days of history in the week
+    pub days: usize,
+    /// Mean of the daily highest prices
+    pub avg_high: f64,
+    /// Mean of the daily lowest prices
+    pub avg_low: f64,
+    /// Mean daily spread, high over low, in percent of the low
+    pub spread_percent: f64,
+    pub volume: i64,
+}
+
+/// An item's approximate spread over recent weeks and how it's trending
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct SpreadHistory {
+    pub region_id: i32,
+    pub type_id: i32,
+    /// Display label, e.g. "Tritanium (34)"
+    pub type_label: String,
+    /// Weeks oldest first; the latest may be partial
+    pub weeks: Vec<SpreadPoint>,
+    pub mean_spread_percent: f64,
+    pub std_dev_spread_percent: Option<f64>,
+    pub latest_spread_percent: f64,
+    /// Latest week against the mean and spread of the weeks before it
+    pub latest_z_score: Option<f64>,
+    pub trend: TrendDirection,
+    /// Fitted change in spread, in percentage points per week
+    pub trend_points_per_week: f64,
+}
+
+/// One region's order book for an item, as compared across regions
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct RegionQuote {
+    pub region_id: i32,
+    /// Report label for the region, e.g. "The Forge"
+    pub region_label: String,
+    /// Highest buy order anywhere in the region
+    pub best_buy: Option<f64>,
+    /// Lowest sell order anywhere in the region
+    pub best_sell: Option<f64>,
+    /// Spread relative to the best sell price
+    pub spread_percent: Option<f64>,
+    pub buy_orders: usize,
+    pub sell_orders: usize,
+    /// Units wanted by buy orders across the region
+    pub buy_volume: i64,
+    /// Units listed for sale across the region
+    pub sell_volume: i64,
+}
+
+/// One item's order books compared across regions
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct MultiRegionSummary {
+    pub type_id: i32,
+    /// Report label for the item, e.g. "Tritanium (34)"
+    pub type_label: String,
+    /// Quotes in the order the regions were requested
+    pub quotes: Vec<RegionQuote>,
+    /// Region with the lowest sell price, when at least two regions have one
+    pub cheapest_sell_region_id: Option<i32>,
+    /// Region with the highest buy price, when at least two regions have one
+    pub highest_buy_region_id: Option<i32>,
+    /// Regions whose orders couldn't be fetched, with the error
+    pub failures: Vec<(i32, String)>,
+}
+
+/// Which side of the market to list orders from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderType {
+    Buy,
+    Sell,
+    #[default]
+    All,
+}
+
+impl std::str::FromStr for OrderType {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "buy" => Ok(Self::Buy),
+            "sell" => Ok(Self::Sell),
+            "all" | "both" => Ok(Self::All),
+            other => Err(format!("Unknown order type: {other} (expected buy, sell or all)")),
+        }
+    }
+}
+
+/// Ordering applied to listed market orders
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderSort {
+    /// Best prices first: cheapest sell orders, then highest buy orders
+    #[default]
+    Price,
+    /// Most units remaining first
+    Volume,
+    /// Most recently issued first
+    Issued,
+}
+
+impl std::str::FromStr for OrderSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "price" => Ok(Self::Price),
+            "volume" => Ok(Self::Volume),
+            "issued" | "newest" => Ok(Self::Issued),
+            other => Err(format!("Unknown sort order: {other} (expected price, volume or issued)")),
+        }
+    }
+}
+
+/// Which market orders to list, and in what order
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize, Serialize)]
+pub struct OrderFilter {
+    pub order_type: OrderType,
+    /// Smallest number of units still for sale or wanted (`volume_remain`)
+    pub min_volume: Option<i32>,
+    /// Highest unit price listed
+    pub max_price: Option<f64>,
+    pub sort_by: OrderSort,
+}
+
+/// A region's market orders after filtering and sorting
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct OrderListing {
+    pub region_id: i32,
+    pub type_id: Option<i32>,
+    pub filter: OrderFilter,
+    /// Orders in the region (for the item, if given) before filtering
+    pub total_orders: usize,
+    /// Orders passing the filter, sorted
+    pub orders: Vec<MarketOrder>,
+}
+
+/// How long an order of a given size and price should take to fill
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct FillEstimate {
+    pub region_id: i32,
+    pub type_id: i32,
+    /// Report label for the item, e.g. "Tritanium (34)"
+    pub type_label: String,
+    /// Which kind of order is being filled
+    pub side: TradeSide,
+    pub quantity: i64,
+    /// Price the order is placed at
+    pub price: f64,
+    /// Days of history the volume is averaged over
+    pub window_days: usize,
+    /// Average daily volume over the window, at any price
+    pub avg_daily_volume: f64,
+    /// Share of that volume, from 0 to 1, traded at prices the order would have filled at
+    pub volume_share: f64,
+    /// For buy orders, buy orders' share of the orders in the book, taken as the
+    /// share of volume sold into buy orders rather than bought from sell orders
+    pub buy_order_ratio: Option<f64>,
+    /// Units a day expected to trade into the order's side at prices it would have filled at
+    pub daily_volume_at_price: f64,
+    /// Units in competing orders at the same or a better price, which fill first
+    pub units_ahead: i64,
+    /// Days to work through the units ahead and fill the quantity, when anything traded at the price
+    pub estimated_days: Option<f64>,
+}
+
+/// A suggested price for a new order, read from the shape of the order book
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ListingAdvice {
+    pub region_id: i32,
+    pub type_id: i32,
+    /// Report label for the item, e.g. "Tritanium (34)"
+    pub type_label: String,
+    /// Station or structure the book was narrowed to, if any
+    pub station_id: Option<i64>,
+    /// Which kind of order is being listed
+    pub side: TradeSide,
+    /// Best price on the listing's side of the book
+    pub best_price: f64,
+    /// Best price on the other side, which the listing must not cross
+    pub opposite_price: Option<f64>,
+    /// Smallest price change allowed at the best price
+    pub tick_size: f64,
+    /// Median gap between the best price levels: how far competitors step past each other
+    pub typical_step: Option<f64>,
+    /// Orders within 1% of the best price
+    pub orders_near_best: usize,
+    /// First of the best price levels holding far more volume than the others
+    pub wall: Option<PriceLevel>,
+    pub suggested_price: f64,
+    /// Place in the queue to fill; 1 when the listing fills first
+    pub queue_position: usize,
+    /// Units in orders that fill before the listing
+    pub units_ahead: i64,
+    /// Why this price, in a sentence
+    pub rationale: String,
+}
+
+/// One trade hub's market for an item
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct HubQuote {
+    pub hub: String,
+    pub region_id: i32,
+    pub station_id: i64,
+    /// Highest buy order at the hub station
+    pub best_buy: Option<f64>,
+    /// Lowest sell order at the hub station
+    pub best_sell: Option<f64>,
+    pub spread: Option<f64>,
+    /// Spread relative to the best sell price
+    pub spread_percent: Option<f64>,
+    /// Units listed for sale at the hub station
+    pub sell_volume: i64,
+    /// Units wanted by buy orders at the hub station
+    pub buy_volume: i64,
+    /// Average daily volume traded across the hub's region over the last 30 days
+    pub avg_daily_volume: f64,
+}
+
+/// The same item compared across trade hubs
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HubComparison {
+    pub type_id: i32,
+    /// Report label for the item, e.g. "Tritanium (34)"
+    pub type_label: String,
+    /// Quotes in the order the hubs were requested
+    pub quotes: Vec<HubQuote>,
+    /// Hubs whose data couldn't be fetched, with the error
+    pub failures: Vec<(String, String)>,
+}
+
+/// One basket item in a market snapshot, quoted at every hub
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct SnapshotItem {
+    pub type_id: i32,
+    /// Report label for the item, e.g. "Tritanium (34)"
+    pub type_label: String,
+    /// Quotes in the order the hubs were configured
+    pub quotes: Vec<HubQuote>,
+    /// Hub with the lowest sell order
+    pub cheapest_hub: Option<String>,
+    /// Hub with the highest buy order
+    pub best_bid_hub: Option<String>,
+    /// How far the dearest hub's best sell is above the cheapest's, in percent
+    pub sell_gap_percent: Option<f64>,
+    /// Hubs whose data couldn't be fetched, with the error
+    pub failures: Vec<(String, String)>,
+}
+
+/// One year's window of a year-over-year comparison
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct YearWindow {
+    /// 0 for the latest window, 1 for the same days a year earlier, and so on
+    pub years_ago: u32,
+    pub start_date: String,
+    pub end_date: String,
+    /// Days in the window with any trades
+    pub days_traded: usize,
+    /// Mean of the daily average prices
+    pub average_price: f64,
+    /// Units traded per calendar day of the window
+    pub average_daily_volume: f64,
+    /// How far the latest window's price is above this one's, in percent
+    pub price_change_percent: Option<f64>,
+    /// How far the latest window's volume is above this one's, in percent
+    pub volume_change_percent: Option<f64>,
+}
+
+/// An item's recent market compared with the same days in earlier years
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct YearOverYear {
+    pub region_id: i32,
+    pub type_id: i32,
+    pub window_days: usize,
+    /// Oldest day of history available, from ESI or the archive
+    pub first_date: String,
+    /// Latest window first
+    pub years: Vec<YearWindow>,
+}
+
+/// An alliance or NPC faction holding systems in a region
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct SovereignHolder {
+    pub holder_id: i32,
+    pub name: Option<String>,
+    /// "alliance" or "faction"
+    pub kind: String,
+    pub systems: usize,
+}
+
+/// How easily a region's market can be reached, from its safest NPC station
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MarketAccess {
+    /// NPC stations in high-sec: open to anyone
+    HighSec,
+    /// NPC stations at best in low-sec: open, past gate camps
+    LowSec,
+    /// NPC stations in NPC-held null-sec: open, through null-sec
+    NpcNullSec,
+    /// NPC stations only in alliance-held null-sec: open, but deep in someone's space
+    SovereignNullSec,
+    /// No NPC stations: orders sit in player structures that may refuse docking
+    StructuresOnly,
+}
+
+impl std::fmt::Display for MarketAccess {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::HighSec => "high-sec NPC stations",
+            Self::LowSec => "low-sec NPC stations",
+            Self::NpcNullSec => "NPC null-sec stations",
+            Self::SovereignNullSec => "NPC stations in sovereign null-sec",
+            Self::StructuresOnly => "player structures only",
+        })
+    }
+}
+
+/// Security, sovereignty and NPC stations of a region, to judge whether its market is reachable
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct RegionContext {
+    pub region_id: i32,
+    pub name: String,
+    pub system_count: usize,
+    pub highsec_systems: usize,
+    pub lowsec_systems: usize,
+    pub nullsec_systems: usize,
+    /// Most systems first
+    pub holders: Vec<SovereignHolder>,
+    /// Systems no alliance or faction holds
+    pub unclaimed_systems: usize,
+    pub npc_stations: usize,
+    pub systems_with_stations: usize,
+    pub access: MarketAccess,
+}
+
+/// What one insurance level pays against the cost of a hull
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct InsuranceLevelValue {
+    pub name: String,
+    pub cost: f64,
+    pub payout: f64,
+    /// Payout less the premium
+    pub net_payout: f64,
+    /// Net payout less the hull's market price: what losing a bought hull gains
+    pub gain_vs_market: Option<f64>,
+    /// Net payout less the hull's build cost: what losing a built hull gains
+    pub gain_vs_build: Option<f64>,
+}
+
+/// A ship's insurance levels compared with its hull price and build cost at a trade hub
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct InsuranceAnalysis {
+    pub type_id: i32,
+    pub type_label: String,
+    pub hub: String,
+    /// Lowest sell order for the hull
+    pub market_price: Option<f64>,
+    /// Blueprint materials for one hull at the lowest sell orders; `None` without an SDE
+    pub build_cost: Option<f64>,
+    pub material_efficiency: i32,
+    /// Cheapest premium first
+    pub levels: Vec<InsuranceLevelValue>,
+    /// Whether Platinum's net payout beats buying or building the hull
+    pub platinum_exceeds_cost: bool,
+}
+
+/// A public item exchange or auction contract valued at a trade hub
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ContractValuation {
+    pub contract_id: i64,
+    pub contract_type: String,
+    pub title: Option<String>,
+    pub start_location_id: Option<i64>,
+    pub date_expired: String,
+    /// What accepting costs: the price, or an auction's buyout when it has one
+    pub asking_price: f64,
+    /// The included items, e.g. "3x Rifter (587), 1x Tritanium (34)"
+    pub item_summary: String,
+    pub item_count: usize,
+    /// Included items at the lowest sell order, less the items the acceptor supplies
+    pub market_value: Option<f64>,
+    /// As `market_value`, but included items at the highest buy order
+    pub liquidation_value: Option<f64>,
+    /// How far the asking price is under the market value
+    pub discount_percent: Option<f64>,
+    /// Liquidation value less the asking price
+    pub instant_profit: Option<f64>,
+    /// Items with no orders at the hub, and blueprint copies
+    pub unpriced_items: Vec<i32>,
+}
+
+/// Public contracts in a region valued against market prices at a trade hub
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ContractScan {
+    pub region_id: i32,
+    pub hub: String,
+    /// Outstanding contracts in the region
+    pub total_contracts: usize,
+    /// Contracts that passed the filters
+    pub matched: usize,
+    /// Matching contracts whose items were fetched and priced, newest first
+    pub valued: usize,
+    /// Biggest discount first; contracts that can't be valued last
+    pub contracts: Vec<ContractValuation>,
+}
+
+/// An item an LP store offer takes besides LP and ISK
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct LpRequiredItem {
+    pub type_id: i32,
+    pub quantity: i64,
+}
+
+/// One offer in a corporation's loyalty point store, from ESI `/loyalty/stores/{corporation_id}/offers/`
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct LpStoreOffer {
+    pub offer_id: i32,
+    pub type_id: i32,
+    /// Units the offer hands out
+    pub quantity: i64,
+    pub lp_cost: i64,
+    pub isk_cost: f64,
+    /// Analysis kredits, only asked by the Concord store
+    #[serde(default)]
+    pub ak_cost: Option<i64>,
+    #[serde(default)]
+    pub required_items: Vec<LpRequiredItem>,
+}
+
+/// An LP store offer priced at a trade hub
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct LpOfferValue {
+    pub offer_id: i32,
+    pub type_id: i32,
+    pub type_label: String,
+    pub quantity: i64,
+    pub lp_cost: i64,
+    pub isk_cost: f64,
+    /// Required items bought from sell orders; `None` if any has no price
+    pub required_items_cost: Option<f64>,
+    /// The offer's units at the chosen side of the book
+    pub output_value: Option<f64>,
+    /// Sales tax, plus the broker fee when listing
+    pub selling_fees: Option<f64>,
+    /// Output value after fees, ISK cost and required items
+    pub profit: Option<f64>,
+    pub isk_per_lp: Option<f64>,
+    /// Required items with no sell orders at the hub
+    pub unpriced_items: Vec<i32>,
+}
+
+/// A corporation's LP store ranked by ISK per LP at a trade hub
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct LpStoreValue {
+    pub corporation_id: i32,
+    pub corporation_name: Option<String>,
+    pub hub: String,
+    /// Side of the book the offers' output is sold to
+    pub output_basis: PriceBasis,
+    /// Offers in the store, before `limit`
+    pub total_offers: usize,
+    /// Best ISK per LP first; offers that can't be priced last
+    pub offers: Vec<LpOfferValue>,
+}
+
+/// Profit of one run of a fuel block blueprint at a trade hub
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct FuelBlockMargin {
+    pub block_type_id: i32,
+    pub block_label: String,
+    pub hub: String,
+    /// Inputs for one run bought from sell orders; `None` if any has no price
+    pub input_cost: Option<f64>,
+    /// The run's blocks at the lowest sell order
+    pub output_value: Option<f64>,
+    /// Sales tax and broker fee of listing the blocks
+    pub selling_fees: Option<f64>,
+    pub profit: Option<f64>,
+    /// Profit as a share of the blocks' value
+    pub margin_percent: Option<f64>,
+    /// Inputs with no sell orders at the hub
+    pub unpriced_inputs: Vec<i32>,
+}
+
+/// Compressed against raw price of one ice ore at a trade hub
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct CompressionQuote {
+    pub hub: String,
+    pub raw_price: Option<f64>,
+    pub compressed_price: Option<f64>,
+    /// How much more a compressed unit sells for than a raw one
+    pub premium_percent: Option<f64>,
+}
+
+/// One ice ore's compression premium at each hub
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct IceCompression {
+    pub ice_type_id: i32,
+    pub ice_label: String,
+    pub compressed_type_id: i32,
+    /// In the order the hubs were given
+    pub quotes: Vec<CompressionQuote>,
+}
+
+/// Fuel block production margins and ice compression premiums across trade hubs
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct FuelBlockAnalysis {
+    pub hubs: Vec<String>,
+    pub material_efficiency: i32,
+    /// Whether recipes came from the SDE rather than the bundled blueprint
+    pub recipe_from_sde: bool,
+    /// Most profitable first
+    pub margins: Vec<FuelBlockMargin>,
+    pub compression: Vec<IceCompression>,
+}
+
+/// One item in a price index basket
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct IndexComponent {
+    pub type_id: i32,
+    pub type_label: String,
+    /// Share of the basket's value on the base day
+    pub weight_percent: f64,
+    /// Average price on the base day
+    pub base_price: f64,
+    /// Average price on the index's last day
+    pub latest_price: f64,
+    pub change_percent: f64,
+}
+
+/// A price index's value on one day
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct IndexPoint {
+    pub date: String,
+    /// 100 on the base day
+    pub value: f64,
+    /// Change from the day before; `None` on the base day
+    pub change_percent: Option<f64>,
+}
+
+/// A weighted basket of items priced daily against a base day
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct MineralIndex {
+    pub region_id: i32,
+    pub base_date: String,
+    /// Oldest first
+    pub points: Vec<IndexPoint>,
+    pub components: Vec<IndexComponent>,
+}
+
+/// One item's market on the PLEX dashboard
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct PlexMarketItem {
+    pub type_id: i32,
+    pub type_label: String,
+    pub best_buy: Option<f64>,
+    pub best_sell: Option<f64>,
+    /// Volume-weighted average price over the dashboard's days
+    pub average_price: Option<f64>,
+    /// Change in the daily average from the first of the dashboard's days to the last
+    pub change_percent: Option<f64>,
+    pub avg_daily_volume: f64,
+}
+
+/// Profit of extracting skill points into a Large Skill Injector and selling it
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct SkillExtraction {
+    /// Skill Extractor at the lowest sell order
+    pub extractor_cost: Option<f64>,
+    /// Injector sold to the highest buy order, after sales tax
+    pub instant_profit: Option<f64>,
+    /// Injector listed at the lowest sell order, after sales tax and broker fee
+    pub listed_profit: Option<f64>,
+}
+
+/// What injected skill points cost a character with up to some total of skill points
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct InjectionBracket {
+    /// Bracket applies below this many skill points; `None` for the top bracket
+    pub below_sp: Option<i64>,
+    /// Skill points a Large Skill Injector gives in the bracket
+    pub large_injector_sp: i64,
+    pub large_isk_per_sp: Option<f64>,
+    pub small_isk_per_sp: Option<f64>,
+}
+
+/// PLEX, skill injector and extractor prices with extraction profit and injection costs
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct PlexDashboard {
+    pub region_id: i32,
+    /// Days of trading averaged
+    pub days: usize,
+    pub items: Vec<PlexMarketItem>,
+    /// Large Skill Injector price in PLEX, both at the lowest sell order
+    pub injector_in_plex: Option<f64>,
+    pub extraction: SkillExtraction,
+    /// Lowest skill point bracket first
+    pub injection: Vec<InjectionBracket>,
+}
+
+/// An item found by name search
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ItemMatch {
+    pub type_id: i32,
+    pub name: String,
+    /// 1 for an exact name, lower the further the name is from the query
+    pub score: f64,
+    pub market_group_id: Option<i32>,
+    pub market_group: Option<String>,
+}
+
+/// Items matching a name search, best first
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ItemSearch {
+    pub query: String,
+    pub matches: Vec<ItemMatch>,
+    /// Whether a local SDE was searched for near matches, rather than exact names only
+    pub fuzzy: bool,
+}
+
+/// Key stats for a basket of items across the trade hubs at one point in time
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct MarketSnapshot {
+    pub generated_at: chrono::DateTime<chrono::Utc>,
+    /// Hub names, in the order each item's quotes are listed
+    pub hubs: Vec<String>,
+    pub items: Vec<SnapshotItem>,
+}
+
+/// One item's best prices in one region of a price matrix
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct PriceMatrixCell {
+    pub region_id: i32,
+    /// Lowest sell order in the region
+    pub best_sell: Option<f64>,
+    /// Highest buy order in the region
+    pub best_buy: Option<f64>,
+    /// Why the region's orders couldn't be fetched, if they couldn't
+    pub error: Option<String>,
+}
+
+/// One item across every region of a price matrix, with its extremes marked
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct PriceMatrixRow {
+    pub type_id: i32,
+    /// Report label for the item, e.g. "Tritanium (34)"
+    pub type_label: String,
+    /// Cells in the order the regions were requested
+    pub cells: Vec<PriceMatrixCell>,
+    /// Region with the cheapest sell price, when at least two regions have one
+    pub min_sell_region_id: Option<i32>,
+    /// Region with the dearest sell price, when at least two regions have one
+    pub max_sell_region_id: Option<i32>,
+    /// Region with the lowest buy price, when at least two regions have one
+    pub min_buy_region_id: Option<i32>,
+    /// Region with the highest buy price, when at least two regions have one
+    pub max_buy_region_id: Option<i32>,
+    /// Dearest sell price above the cheapest, as a percentage of the cheapest
+    pub sell_spread_percent: Option<f64>,
+}
+
+/// Best prices for several items across several regions
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct PriceMatrix {
+    pub region_ids: Vec<i32>,
+    /// Column labels, e.g. "The Forge"
+    pub region_labels: Vec<String>,
+    /// Whether buy prices are part of the report
+    pub include_buy: bool,
+    /// Rows in the order the items were requested
+    pub rows: Vec<PriceMatrixRow>,
+}
+
+/// One weighted part of a trade grade
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct GradeComponent {
+    /// Component name, e.g. "margin"
+    pub name: String,
+    /// Score from 0 (worst) to 100 (best)
+    pub score: f64,
+    /// Share of the overall score, after normalizing the weights
+    pub weight: f64,
+    /// What the score is based on, e.g. "12.5% after fees"
+    pub detail: String,
+}
+
+/// Letter grade for a proposed buy-low, sell-high trade with its breakdown
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct TradeGrade {
+    pub region_id: i32,
+    pub type_id: i32,
+    /// Report label for the item, e.g. "Tritanium (34)"
+    pub type_label: String,
+    /// Price the buy order is placed at
+    pub buy_price: f64,
+    /// Price the sell order is placed at
+    pub sell_price: f64,
+    pub quantity: i64,
+    /// Profit per unit after broker fees on both orders and sales tax
+    pub profit_per_unit: f64,
+    /// Profit as a percentage of the cost including fees
+    pub net_margin_percent: f64,
+    /// Days to buy and then sell the quantity, when the item trades at all
+    pub estimated_days_to_fill: Option<f64>,
+    pub components: Vec<GradeComponent>,
+    /// Weighted score from 0 to 100
+    pub score: f64,
+    /// A to F; a trade that loses money after fees is always an F
+    pub grade: char,
+}
+
+/// A named list of items to keep an eye on, shareable as an import code
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Watchlist {
+    pub name: String,
+    /// Region the list was curated for, if any
+    pub region_id: Option<i32>,
+    /// Item type IDs in the curator's order
+    pub type_ids: Vec<i32>,
+}
+
+/// Length of the candles produced by history aggregation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Period {
+    #[default]
+    Daily,
+    /// ISO weeks, Monday to Sunday
+    Weekly,
+    /// Calendar months
+    Monthly,
+}
+
+impl std::str::FromStr for Period {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "daily" | "day" | "1d" => Ok(Self::Daily),
+            "weekly" | "week" | "1w" => Ok(Self::Weekly),
+            "monthly" | "month" | "1m" => Ok(Self::Monthly),
+            other => Err(format!("Unknown period: {other}")),
+        }
+    }
+}
+
+/// OHLC-style summary of market history over one period
+///
+/// ESI only reports daily averages, so `open` and `close` are the average
+/// prices of the first and last trading days in the period.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Candle {
+    /// First day of the period (Monday for weeks, the 1st for months)
+    pub period_start: String,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    /// Volume-weighted average price
+    pub average: f64,
+    pub volume: i64,
+    /// ISK traded: each day's volume times its average price, summed
+    pub isk_turnover: f64,
+    pub order_count: i64,
+    /// Days of history in the period
+    pub days: usize,
+}
+
+/// Recent ISK turnover and volume averages for an item
+///
+/// Windows are calendar days ending at `end_date`, so days without trades
+/// pull the averages down.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct HistoryStats {
+    /// Newest day of history the windows end at
+    pub end_date: String,
+    pub isk_turnover_7d: f64,
+    pub isk_turnover_30d: f64,
+    pub avg_daily_volume_7d: f64,
+    pub avg_daily_volume_30d: f64,
+    pub avg_daily_isk_turnover_7d: f64,
+    pub avg_daily_isk_turnover_30d: f64,
+    /// 7-day average daily volume against the 30-day one, in percent; `None` without 30-day volume
+    pub volume_change_percent: Option<f64>,
+    pub volume_trend: TrendDirection,
 }
 
 #[cfg(test)]
@@ -82,6 +2477,7 @@ mod tests {
             type_id: 34,
             volume_remain: 1000,
             volume_total: 1000,
+            extra: Default::default(),
         };
 
         // Test serialization
@@ -95,6 +2491,31 @@ mod tests {
         assert!(deserialized.is_buy_order);
     }
 
+    #[test]
+    fn test_market_order_tolerates_schema_drift() {
+        let json = serde_json::json!({
+            "is_buy_order": false,
+            "location_id": 60003760,
+            "order_id": 6817354821_i64,
+            "price": 5.25,
+            "range": "region",
+            "type_id": 34,
+            "volume_remain": 900,
+            "volume_total": 1000,
+            "escrow": 0.0
+        });
+        let EsiObject { known: mut order, extra } = serde_json::from_value::<EsiObject<MarketOrder>>(json).unwrap();
+        order.set_extra(extra);
+        assert_eq!(order.min_volume, 1);
+        assert_eq!(order.duration, 0);
+        assert_eq!(order.extra.get("escrow"), Some(&serde_json::json!(0.0)));
+
+        // Unknown fields survive the bincode cache
+        let cached: MarketOrder = bincode::deserialize(&bincode::serialize(&order).unwrap()).unwrap();
+        assert_eq!(cached.extra, order.extra);
+        assert_eq!(cached.order_id, 6817354821);
+    }
+
     #[test]
     fn test_market_history_serialization() {
         let history = MarketHistory {
@@ -104,6 +2525,7 @@ mod tests {
             lowest: 90.00,
             order_count: 150,
             volume: 50000,
+            extra: Default::default(),
         };
 
         let json = serde_json::to_string(&history).unwrap();
@@ -125,15 +2547,133 @@ mod tests {
             month_change: 15.0,
             month_change_percent: 17.65,
             volatility: 12.5,
-            trend: "bullish".to_string(),
+            volatility_percent: 12.5,
+            annualized_volatility_percent: 40.0,
+            average_true_range: 8.0,
+            trend: PriceTrend::Upward,
+            trend_evidence: TrendEvidence::default(),
+            indicators: TechnicalIndicators::default(),
+            data_freshness: DataFreshness::default(),
         };
 
         assert_eq!(analysis.current_price, 100.0);
-        assert_eq!(analysis.trend, "bullish");
+        assert_eq!(analysis.trend.direction(), TrendDirection::Up);
+
+        // Cached analyses round-trip through bincode
+        let cached: PriceAnalysis = bincode::deserialize(&bincode::serialize(&analysis).unwrap()).unwrap();
+        assert_eq!(cached, analysis);
+        assert_eq!(serde_json::to_value(analysis.trend).unwrap(), "upward");
         assert!(analysis.day_change > 0.0);
         assert!(analysis.week_change < 0.0);
     }
 
+    #[test]
+    fn test_price_analysis_backward_compatible() {
+        // Analyses serialized before relative volatility and indicators existed
+        let json = r#"{
+            "current_price": 100.0,
+            "day_change": 1.0,
+            "day_change_percent": 1.0,
+            "week_change": 2.0,
+            "week_change_percent": 2.0,
+            "month_change": 3.0,
+            "month_change_percent": 3.0,
+            "volatility": 4.0,
+            "trend": "Stable"
+        }"#;
+
+        let analysis: PriceAnalysis = serde_json::from_str(json).unwrap();
+        assert_eq!(analysis.volatility, 4.0);
+        assert_eq!(analysis.volatility_percent, 0.0);
+        assert_eq!(analysis.trend, PriceTrend::Stable);
+        assert_eq!(analysis.indicators, TechnicalIndicators::default());
+        assert_eq!(analysis.data_freshness, DataFreshness::default());
+    }
+
+    #[test]
+    fn test_type_info_cargo_volume() {
+        let json = r#"{
+            "type_id": 587,
+            "name": "Rifter",
+            "description": "The Rifter is a very powerful combat frigate.",
+            "group_id": 25,
+            "market_group_id": 64,
+            "volume": 27289.0,
+            "packaged_volume": 2500.0,
+            "published": true,
+            "dogma_attributes": [{"attribute_id": 422, "value": 1.0}]
+        }"#;
+        let rifter: TypeInfo = serde_json::from_str(json).unwrap();
+        assert_eq!(rifter.market_group_id, Some(64));
+        assert_eq!(rifter.dogma_attribute(422), Some(1.0));
+        assert_eq!(rifter.dogma_attribute(633), None);
+        assert_eq!(rifter.cargo_volume(), 2500.0);
+
+        let unpackaged = TypeInfo {
+            packaged_volume: None,
+            volume: Some(0.01),
+            ..rifter
+        };
+        assert_eq!(unpackaged.cargo_volume(), 0.01);
+    }
+
+    #[test]
+    fn test_scan_sort_parsing() {
+        assert_eq!("spread".parse::<ScanSort>(), Ok(ScanSort::SpreadPercent));
+        assert_eq!("Profit-Potential".parse::<ScanSort>(), Ok(ScanSort::ProfitPotential));
+        assert!("alphabetical".parse::<ScanSort>().is_err());
+        assert_eq!(ScanSort::default(), ScanSort::ProfitPotential);
+    }
+
+    #[test]
+    fn test_public_courier_contract_deserialization() {
+        let json = r#"{
+            "collateral": 500000000.0,
+            "contract_id": 190000001,
+            "date_expired": "2025-07-06T10:00:00Z",
+            "date_issued": "2025-06-22T10:00:00Z",
+            "days_to_complete": 3,
+            "end_location_id": 60008494,
+            "for_corporation": false,
+            "issuer_corporation_id": 98000001,
+            "issuer_id": 2112000001,
+            "reward": 25000000.0,
+            "start_location_id": 60003760,
+            "title": "",
+            "type": "courier",
+            "volume": 60000.0
+        }"#;
+
+        let contract: PublicContract = serde_json::from_str(json).unwrap();
+        assert_eq!(contract.contract_type, "courier");
+        assert_eq!(contract.volume, Some(60000.0));
+        assert!(contract.price.is_none());
+    }
+
+    #[test]
+    fn test_character_order_optional_fields() {
+        // Sell orders omit is_buy_order, escrow and min_volume
+        let json = r#"{
+            "duration": 90,
+            "is_corporation": false,
+            "issued": "2025-06-22T10:00:00Z",
+            "location_id": 60003760,
+            "order_id": 42,
+            "price": 5.5,
+            "range": "region",
+            "region_id": 10000002,
+            "type_id": 34,
+            "volume_remain": 100,
+            "volume_total": 200
+        }"#;
+
+        let order: CharacterOrder = serde_json::from_str(json).unwrap();
+        assert!(!order.is_buy_order);
+        assert!(order.escrow.is_none());
+        assert!(order.min_volume.is_none());
+        assert_eq!(order.region_id, 10000002);
+    }
+
     #[test]
     fn test_market_type_validation() {
         let market_type = MarketType {
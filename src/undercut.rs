@@ -0,0 +1,310 @@
+//! Undercut frequency estimation for TraderGrader
+//!
+//! Whether a 0.01 ISK war is worth fighting depends on how often competitors
+//! reprice. This samples an item's order book a few times, waiting for the
+//! cached copy to expire between samples so every snapshot is new data from
+//! ESI rather than an extra request for the same data, and counts the orders
+//! that beat the previous best price since the snapshot before. Each
+//! modified order carries a fresh `issued` time, so repricings between
+//! samples are counted, not just the ones still on top.
+
+use crate::cache::CacheKey;
+use crate::error::{Result, TraderGraderError};
+use crate::market::MarketClient;
+use crate::orderbook::{BookSide, MarketOrderBook};
+use crate::types::{MarketOrder, UndercutEstimate, UndercutSideStats};
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+
+/// Default order book samples per estimate
+pub const DEFAULT_UNDERCUT_SAMPLES: usize = 2;
+
+/// Most order book samples per estimate
+pub const MAX_UNDERCUT_SAMPLES: usize = 6;
+
+/// Wait between samples when the cache can't say when ESI will have new orders (ESI's order cache timer)
+const DEFAULT_SAMPLE_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Longest wait between samples, whatever the cached copy's TTL
+const MAX_SAMPLE_INTERVAL: Duration = Duration::from_secs(600);
+
+/// Best orders checked for recent updates in the latest snapshot
+const TOP_ORDERS: usize = 5;
+
+/// How recently a top order must have been updated to count as active
+const RECENT_UPDATE_SECS: i64 = 3600;
+
+fn issued_at(order: &MarketOrder) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(&order.issued).ok().map(|d| d.with_timezone(&Utc))
+}
+
+/// Whether `price` beats `best` on a side of the book
+fn beats(side: BookSide, price: f64, best: Option<f64>) -> bool {
+    match (side, best) {
+        (_, None) => true,
+        (BookSide::Ask, Some(best)) => price < best,
+        (BookSide::Bid, Some(best)) => price > best,
+    }
+}
+
+/// Undercut statistics for one side across timestamped snapshots, oldest first
+fn side_stats(snapshots: &[(DateTime<Utc>, MarketOrderBook)], side: BookSide) -> UndercutSideStats {
+    let best = |book: &MarketOrderBook| match side {
+        BookSide::Ask => book.best_ask(),
+        BookSide::Bid => book.best_bid(),
+    };
+
+    let mut best_price_changes = 0;
+    let mut undercuts = 0;
+    for pair in snapshots.windows(2) {
+        let ((previous_at, previous), (_, current)) = (&pair[0], &pair[1]);
+        let previous_best = best(previous);
+        if best(current) != previous_best {
+            best_price_changes += 1;
+        }
+        undercuts += current
+            .side(side)
+            .filter(|o| issued_at(o).is_some_and(|issued| issued > *previous_at))
+            .filter(|o| beats(side, o.price, previous_best))
+            .count();
+    }
+
+    let (first_at, last_at) = match (snapshots.first(), snapshots.last()) {
+        (Some((first, _)), Some((last, _))) => (*first, *last),
+        _ => return UndercutSideStats::default(),
+    };
+    let hours = (last_at - first_at).num_seconds() as f64 / 3600.0;
+    let latest = &snapshots[snapshots.len() - 1].1;
+    UndercutSideStats {
+        first_best: best(&snapshots[0].1),
+        last_best: best(latest),
+        best_price_changes,
+        undercuts,
+        undercuts_per_hour: (snapshots.len() > 1 && hours > 0.0).then(|| undercuts as f64 / hours),
+        recently_updated_top_orders: latest
+            .side(side)
+            .take(TOP_ORDERS)
+            .filter(|o| issued_at(o).is_some_and(|issued| (last_at - issued).num_seconds() <= RECENT_UPDATE_SECS))
+            .count(),
+    }
+}
+
+impl UndercutEstimate {
+    /// Estimates undercut rates from timestamped order snapshots, oldest first
+    ///
+    /// With `station_id` only orders at that station count, as 0.01 ISK wars
+    /// are fought per station; otherwise the whole region is one market.
+    pub fn from_snapshots(
+        region_id: i32,
+        type_id: i32,
+        station_id: Option<i64>,
+        snapshots: &[(DateTime<Utc>, Vec<MarketOrder>)],
+    ) -> Result<Self> {
+        if snapshots.is_empty() {
+            return Err(TraderGraderError::InsufficientData("No order book samples to estimate from".to_string()));
+        }
+        let books: Vec<(DateTime<Utc>, MarketOrderBook)> = snapshots
+            .iter()
+            .map(|(taken_at, orders)| {
+                let orders = orders
+                    .iter()
+                    .filter(|o| o.type_id == type_id && station_id.is_none_or(|id| o.location_id == id))
+                    .cloned()
+                    .collect();
+                (*taken_at, MarketOrderBook::new(orders))
+            })
+            .collect();
+
+        Ok(Self {
+            region_id,
+            type_id,
+            station_id,
+            samples: books.len(),
+            window_secs: (books[books.len() - 1].0 - books[0].0).num_seconds(),
+            sell: side_stats(&books, BookSide::Ask),
+            buy: side_stats(&books, BookSide::Bid),
+        })
+    }
+}
+
+impl MarketClient {
+    /// Samples an item's orders `samples` times and estimates how often the best price is beaten
+    ///
+    /// Samples go through the order cache. Between samples this waits until
+    /// the cached copy expires (ESI refreshes orders about every five minutes),
+    /// so a call with `n` samples takes roughly `n - 1` cache periods and
+    /// spends one ESI request per sample.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// // PLEX at Jita 4-4, two samples about five minutes apart
+    /// let estimate = client.estimate_undercut_rate(10000002, 44992, Some(60003760), 2).await?;
+    /// println!("{:?} sell undercuts per hour", estimate.sell.undercuts_per_hour);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn estimate_undercut_rate(
+        &self,
+        region_id: i32,
+        type_id: i32,
+        station_id: Option<i64>,
+        samples: usize,
+    ) -> Result<UndercutEstimate> {
+        if !(1..=MAX_UNDERCUT_SAMPLES).contains(&samples) {
+            return Err(TraderGraderError::InvalidArgument {
+                field: "samples".to_string(),
+                reason: format!("must be between 1 and {MAX_UNDERCUT_SAMPLES}"),
+            });
+        }
+        let cache_key = CacheKey::market_orders(region_id, Some(type_id));
+        let mut snapshots = Vec::with_capacity(samples);
+        for sample in 0..samples {
+            if sample > 0 {
+                let wait = self
+                    .esi()
+                    .cached_ttl::<Vec<MarketOrder>>(&cache_key)
+                    .await
+                    .map_or(DEFAULT_SAMPLE_INTERVAL, |ttl| ttl + Duration::from_secs(1));
+                tokio::time::sleep(wait.min(MAX_SAMPLE_INTERVAL)).await;
+            }
+            let orders = self.fetch_market_orders(region_id, Some(type_id)).await?;
+            snapshots.push((Utc::now(), orders));
+        }
+        UndercutEstimate::from_snapshots(region_id, type_id, station_id, &snapshots)
+    }
+
+    /// Generates a formatted undercut frequency estimate
+    pub async fn get_undercut_rate_summary(
+        &self,
+        region_id: i32,
+        type_id: i32,
+        station_id: Option<i64>,
+        samples: usize,
+    ) -> Result<String> {
+        let estimate = self.estimate_undercut_rate(region_id, type_id, station_id, samples).await?;
+        let market = match station_id {
+            Some(station_id) => format!("Station {station_id}"),
+            None => format!("Region {region_id}"),
+        };
+        Ok(format_undercut_estimate(
+            &format!("Undercut Rate for {} in {market}", self.type_label(type_id).await),
+            &estimate,
+        ))
+    }
+}
+
+/// Plain-language verdict on a side's undercut rate
+fn describe_rate(stats: &UndercutSideStats) -> &'static str {
+    match stats.undercuts_per_hour {
+        Some(rate) if rate >= 6.0 => "hot: a 0.01 ISK war here needs constant attention",
+        Some(rate) if rate >= 1.0 => "active: expect to update several times a session",
+        Some(_) => "calm: a 0.01 ISK undercut should hold for a while",
+        None if stats.recently_updated_top_orders >= 3 => "likely active: most top orders were updated in the last hour",
+        None => "too few samples to measure; top orders look settled",
+    }
+}
+
+/// Formats sell and buy side estimates with a verdict each
+fn format_undercut_estimate(title: &str, estimate: &UndercutEstimate) -> String {
+    let price = |p: Option<f64>| p.map_or_else(|| "-".to_string(), |p| format!("{p:.2}"));
+    let mut text = format!(
+        "{title}:\n{} samples over {} minutes\n",
+        estimate.samples,
+        estimate.window_secs / 60
+    );
+    for (name, stats) in [("Sell orders", &estimate.sell), ("Buy orders", &estimate.buy)] {
+        let rate = stats
+            .undercuts_per_hour
+            .map_or_else(|| "n/a".to_string(), |rate| format!("{rate:.1}/hour"));
+        text.push_str(&format!(
+            "\n{name}: best {} -> {}\n\
+             Beaten {} times ({rate}); best price changed in {} of {} intervals\n\
+             Top {TOP_ORDERS} orders updated in the last hour: {}\n\
+             Verdict: {}\n",
+            price(stats.first_best),
+            price(stats.last_best),
+            stats.undercuts,
+            stats.best_price_changes,
+            estimate.samples.saturating_sub(1),
+            stats.recently_updated_top_orders,
+            describe_rate(stats)
+        ));
+    }
+    text.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::market_order;
+
+    fn order(order_id: i64, is_buy_order: bool, price: f64, issued: &str, location_id: i64) -> MarketOrder {
+        market_order(order_id, is_buy_order, price)
+            .with_issued(issued)
+            .with_location(location_id)
+            .with_type(44992)
+            .with_volume(10)
+    }
+
+    fn at(time: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(time).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_counts_orders_beating_the_previous_best() {
+        let first = vec![
+            order(1, false, 100.0, "2025-06-01T11:00:00Z", 60003760),
+            order(2, true, 90.0, "2025-06-01T09:00:00Z", 60003760),
+        ];
+        // Two sellers undercut in turn, a seller relisted above the best and the bid never moved
+        let second = vec![
+            order(1, false, 100.0, "2025-06-01T11:00:00Z", 60003760),
+            order(3, false, 99.99, "2025-06-01T12:01:00Z", 60003760),
+            order(4, false, 99.98, "2025-06-01T12:03:00Z", 60003760),
+            order(5, false, 101.0, "2025-06-01T12:04:00Z", 60003760),
+            order(2, true, 90.0, "2025-06-01T09:00:00Z", 60003760),
+        ];
+        let snapshots = vec![(at("2025-06-01T12:00:00Z"), first), (at("2025-06-01T12:06:00Z"), second)];
+        let estimate = UndercutEstimate::from_snapshots(10000002, 44992, Some(60003760), &snapshots).unwrap();
+
+        assert_eq!(estimate.window_secs, 360);
+        assert_eq!(estimate.sell.undercuts, 2);
+        assert_eq!(estimate.sell.best_price_changes, 1);
+        assert_eq!(estimate.sell.undercuts_per_hour, Some(20.0));
+        assert_eq!(estimate.sell.last_best, Some(99.98));
+        assert_eq!(estimate.sell.recently_updated_top_orders, 3);
+        assert_eq!(estimate.buy.undercuts, 0);
+        assert_eq!(estimate.buy.undercuts_per_hour, Some(0.0));
+
+        let text = format_undercut_estimate("Undercut Rate", &estimate);
+        assert!(text.contains("Beaten 2 times (20.0/hour)"));
+        assert!(text.contains("Verdict: hot"));
+        assert!(text.contains("Verdict: calm"));
+    }
+
+    #[test]
+    fn test_station_filter_and_single_sample() {
+        let orders = vec![
+            order(1, false, 100.0, "2025-06-01T11:30:00Z", 60003760),
+            order(2, false, 50.0, "2025-06-01T11:59:00Z", 60008494),
+        ];
+        let snapshots = vec![(at("2025-06-01T12:00:00Z"), orders)];
+
+        let jita = UndercutEstimate::from_snapshots(10000002, 44992, Some(60003760), &snapshots).unwrap();
+        assert_eq!(jita.sell.first_best, Some(100.0));
+        assert_eq!(jita.sell.undercuts_per_hour, None);
+        assert_eq!(jita.sell.recently_updated_top_orders, 1);
+
+        let region = UndercutEstimate::from_snapshots(10000002, 44992, None, &snapshots).unwrap();
+        assert_eq!(region.sell.first_best, Some(50.0));
+        assert_eq!(region.sell.recently_updated_top_orders, 2);
+        // Missing samples are a data problem, not a malformed request
+        let empty = UndercutEstimate::from_snapshots(10000002, 44992, None, &[]).unwrap_err();
+        assert!(matches!(empty, TraderGraderError::InsufficientData(_)));
+        assert!(!empty.is_invalid_request());
+    }
+}
@@ -0,0 +1,518 @@
+//! Universe data for TraderGrader
+//!
+//! Regions, constellations, solar systems and stations from ESI's
+//! `/universe/` routes, bulk ID-to-name resolution, plus the hourly jump and
+//! kill statistics used to estimate where players actually are. Static data is
+//! cached for a day, station and structure names for a week; activity
+//! statistics follow ESI's hourly cache timer.
+
+use crate::cache::CacheKey;
+use crate::error::Result;
+use crate::market::MarketClient;
+use crate::orderbook::MarketOrderBook;
+use crate::scan::SCAN_CONCURRENCY;
+use crate::types::{
+    ConstellationInfo, RegionActivity, RegionInfo, StationInfo, StructureInfo, SystemActivity, SystemInfo,
+    SystemJumps, SystemKills, TypeInfo, UniverseName,
+};
+use futures::stream::{self, StreamExt};
+use std::collections::{BTreeSet, HashMap};
+
+/// Demand weight of one jump into a system (traffic, roughly player count)
+pub const JUMP_WEIGHT: f64 = 1.0;
+
+/// Demand weight of one destroyed ship (a replacement hull and fit)
+pub const SHIP_KILL_WEIGHT: f64 = 10.0;
+
+/// Demand weight of one destroyed capsule (implants and clones)
+pub const POD_KILL_WEIGHT: f64 = 2.0;
+
+/// Demand weight of one NPC kill (ratting consumes ammunition and modules)
+pub const NPC_KILL_WEIGHT: f64 = 0.25;
+
+/// Number of busiest systems reported per region
+const TOP_SYSTEMS: usize = 5;
+
+/// Region IDs in use: New Eden, wormhole space, Abyssal Deadspace and the hidden regions
+pub const REGION_ID_RANGE: std::ops::RangeInclusive<i64> = 10_000_001..=14_999_999;
+
+/// Solar system IDs in use, in the same order of space as regions
+pub const SYSTEM_ID_RANGE: std::ops::RangeInclusive<i64> = 30_000_001..=34_999_999;
+
+/// Maximum number of IDs ESI accepts in one `/universe/names/` request
+pub const NAMES_BATCH_SIZE: usize = 1000;
+
+/// NPC station IDs; market locations above this range are player structures
+pub const STATION_ID_RANGE: std::ops::RangeInclusive<i64> = 60_000_000..=64_999_999;
+
+impl MarketClient {
+    /// Fetches a region's name and constellations
+    pub async fn fetch_region(&self, region_id: i32) -> Result<RegionInfo> {
+        self.esi().get_cached(
+            &format!("/universe/regions/{region_id}/"),
+            &CacheKey::universe("region", region_id as i64),
+            "universe",
+        )
+        .await
+    }
+
+    /// Fetches a constellation's name, region and solar systems
+    pub async fn fetch_constellation(&self, constellation_id: i32) -> Result<ConstellationInfo> {
+        self.esi().get_cached(
+            &format!("/universe/constellations/{constellation_id}/"),
+            &CacheKey::universe("constellation", constellation_id as i64),
+            "universe",
+        )
+        .await
+    }
+
+    /// Fetches a solar system's name, security status and position
+    pub async fn fetch_system(&self, system_id: i32) -> Result<SystemInfo> {
+        if let Some(system) = self.static_data().and_then(|sde| sde.system_info(system_id)) {
+            return Ok(system);
+        }
+        self.esi().get_cached(
+            &format!("/universe/systems/{system_id}/"),
+            &CacheKey::universe("system", system_id as i64),
+            "universe",
+        )
+        .await
+    }
+
+    /// Fetches an NPC station's name and solar system
+    pub async fn fetch_station(&self, station_id: i64) -> Result<StationInfo> {
+        if let Some(station) = self.static_data().and_then(|sde| sde.get_station(station_id)) {
+            return Ok(StationInfo {
+                station_id,
+                name: station.name.clone(),
+                system_id: station.system_id,
+            });
+        }
+        self.esi().get_static(
+            &format!("/universe/stations/{station_id}/"),
+            &CacheKey::universe("station", station_id),
+            "locations",
+        )
+        .await
+    }
+
+    /// Fetches a player structure's name and solar system
+    ///
+    /// Needs an authenticated character holding the
+    /// `esi-universe.read_structures.v1` scope with docking access to the
+    /// structure. Cached for a week.
+    pub async fn fetch_structure(&self, structure_id: i64) -> Result<StructureInfo> {
+        self.esi().structure(structure_id).await
+    }
+
+    /// Names for the stations and structures orders are placed in
+    ///
+    /// Stations come from the SDE or ESI, structures from
+    /// [`fetch_structure`](Self::fetch_structure); lookups run concurrently.
+    /// Locations that can't be resolved, such as structures when no character
+    /// may read them, are left out so reports can fall back to the ID.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::MarketClient;
+    /// # async fn example() {
+    /// let client = MarketClient::new();
+    /// let names = client.resolve_location_names(&[60003760]).await;
+    /// assert_eq!(names[&60003760], "Jita IV - Moon 4 - Caldari Navy Assembly Plant");
+    /// # }
+    /// ```
+    pub async fn resolve_location_names(&self, location_ids: &[i64]) -> HashMap<i64, String> {
+        let ids: BTreeSet<i64> = location_ids.iter().copied().collect();
+        stream::iter(ids)
+            .map(|id| async move {
+                let name = if STATION_ID_RANGE.contains(&id) {
+                    self.fetch_station(id).await.map(|station| station.name)
+                } else {
+                    self.fetch_structure(id).await.map(|structure| structure.name)
+                };
+                (id, name)
+            })
+            .buffer_unordered(SCAN_CONCURRENCY)
+            .filter_map(|(id, name)| async move { name.ok().map(|name| (id, name)) })
+            .collect()
+            .await
+    }
+
+    /// Names for the locations of an order book's best buy and best sell orders
+    pub(crate) async fn best_order_locations(&self, book: &MarketOrderBook) -> HashMap<i64, String> {
+        let ids: Vec<i64> = book.best_buy().into_iter().chain(book.best_sell()).map(|o| o.location_id).collect();
+        self.resolve_location_names(&ids).await
+    }
+
+    /// Fetches an item type's name, group, market group and volumes
+    ///
+    /// Type data only changes with game patches, so it is cached for a week
+    /// regardless of ESI's cache headers. A loaded SDE answers without a request.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// let info = client.fetch_type_info(34).await?;
+    /// println!("{}: {} m³ per unit", info.name, info.cargo_volume());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn fetch_type_info(&self, type_id: i32) -> Result<TypeInfo> {
+        if let Some(info) = self.static_data().and_then(|sde| sde.type_info(type_id)) {
+            return Ok(info);
+        }
+        self.esi().type_info(type_id).await
+    }
+
+    /// Label for an item type in text reports, such as "Tritanium (34)"
+    ///
+    /// Falls back to "Type 34" when the type can't be looked up, so reports
+    /// never fail just because a name is unavailable.
+    pub async fn type_label(&self, type_id: i32) -> String {
+        match self.fetch_type_info(type_id).await {
+            Ok(info) => format!("{} ({type_id})", info.name),
+            Err(_) => format!("Type {type_id}"),
+        }
+    }
+
+    /// Fetches the region a solar system belongs to
+    pub async fn fetch_system_region(&self, system_id: i32) -> Result<i32> {
+        if let Some(system) = self.static_data().and_then(|sde| sde.get_system(system_id)) {
+            return Ok(system.region_id);
+        }
+        let system = self.fetch_system(system_id).await?;
+        Ok(self.fetch_constellation(system.constellation_id).await?.region_id)
+    }
+
+    /// Fetches the IDs of every solar system in a region
+    pub async fn fetch_region_systems(&self, region_id: i32) -> Result<Vec<i32>> {
+        let region = self.fetch_region(region_id).await?;
+        let mut systems = Vec::new();
+        for constellation_id in region.constellations {
+            systems.extend(self.fetch_constellation(constellation_id).await?.systems);
+        }
+        Ok(systems)
+    }
+
+    /// Resolves IDs (items, stations, systems, characters, ...) to names
+    ///
+    /// IDs are deduplicated and served from the local SDE or the per-ID cache
+    /// where possible; the rest are sent to ESI in batches of
+    /// [`NAMES_BATCH_SIZE`]. ESI rejects a whole batch when any ID in it is
+    /// unknown, so a rejected batch is split in half until the offending IDs are isolated and dropped. IDs outside the 32-bit
+    /// range (player structures) can't be resolved this way and are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// let orders = client.fetch_market_orders(10000002, None).await?;
+    /// let locations: Vec<i64> = orders.iter().map(|o| o.location_id).collect();
+    /// let names = client.resolve_names(&locations).await?;
+    /// println!("Resolved {} stations", names.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn resolve_names(&self, ids: &[i64]) -> Result<HashMap<i64, UniverseName>> {
+        let mut names = HashMap::new();
+        let mut missing = Vec::new();
+        for id in resolvable_ids(ids) {
+            if let Some(name) = self.static_data().and_then(|sde| sde.name(id)) {
+                names.insert(id, name);
+                continue;
+            }
+            match self.esi().cached::<UniverseName>(&CacheKey::universe("name", id)).await? {
+                Some(name) => {
+                    names.insert(id, name);
+                }
+                None => missing.push(id),
+            }
+        }
+
+        let mut batches: Vec<Vec<i64>> = missing.chunks(NAMES_BATCH_SIZE).map(<[i64]>::to_vec).collect();
+        while let Some(batch) = batches.pop() {
+            let response = self.esi().post_public("/universe/names/", &batch).await?;
+            if response.status() == reqwest::StatusCode::NOT_FOUND {
+                if batch.len() > 1 {
+                    let (left, right) = batch.split_at(batch.len() / 2);
+                    batches.push(left.to_vec());
+                    batches.push(right.to_vec());
+                }
+                continue;
+            }
+            if !response.status().is_success() {
+                return Err(self.rate_limiter().error_for_status(&response));
+            }
+
+            let resolved: Vec<UniverseName> = self.esi().read_json(response).await?;
+            for name in resolved {
+                self.esi().store_cached(&CacheKey::universe("name", name.id), name.clone(), "universe").await;
+                names.insert(name.id, name);
+            }
+        }
+        Ok(names)
+    }
+
+    /// Fetches ship, pod and NPC kills per system over the last hour
+    pub async fn fetch_system_kills(&self) -> Result<Vec<SystemKills>> {
+        self.esi().get_cached("/universe/system_kills/", &CacheKey::activity("system_kills"), "activity")
+            .await
+    }
+
+    /// Fetches jumps per system over the last hour
+    pub async fn fetch_system_jumps(&self) -> Result<Vec<SystemJumps>> {
+        self.esi().get_cached("/universe/system_jumps/", &CacheKey::activity("system_jumps"), "activity")
+            .await
+    }
+
+    /// Estimates activity-weighted demand for one or more regions
+    ///
+    /// Regions are returned most active first. The galaxy-wide jump and kill
+    /// statistics are fetched once regardless of how many regions are compared.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// // The Forge, Domain, Sinq Laison
+    /// let regions = client.get_region_activity(&[10000002, 10000043, 10000032]).await?;
+    /// println!("Most active: {}", regions[0].region_name);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_region_activity(&self, region_ids: &[i32]) -> Result<Vec<RegionActivity>> {
+        let kills = self.fetch_system_kills().await?;
+        let jumps = self.fetch_system_jumps().await?;
+        let activity = system_activity(&kills, &jumps);
+
+        let mut regions = Vec::with_capacity(region_ids.len());
+        for &region_id in region_ids {
+            let region = self.fetch_region(region_id).await?;
+            let systems = self.fetch_region_systems(region_id).await?;
+            regions.push(region_activity(region_id, region.name, &systems, &activity));
+        }
+
+        rank_regions(&mut regions);
+        Ok(regions)
+    }
+
+    /// Generates a formatted regional activity comparison
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tradergrader::{MarketClient, Result};
+    /// # async fn example() -> Result<()> {
+    /// let client = MarketClient::new();
+    /// let report = client.get_region_activity_summary(&[10000002, 10000043]).await?;
+    /// println!("{}", report);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_region_activity_summary(&self, region_ids: &[i32]) -> Result<String> {
+        let regions = self.get_region_activity(region_ids).await?;
+        Ok(format_region_activity(&regions))
+    }
+}
+
+/// Sorted, deduplicated IDs that `/universe/names/` can resolve
+fn resolvable_ids(ids: &[i64]) -> Vec<i64> {
+    ids.iter()
+        .copied()
+        .filter(|&id| id > 0 && id <= i32::MAX as i64)
+        .collect::<BTreeSet<i64>>()
+        .into_iter()
+        .collect()
+}
+
+/// Weighted demand index for a set of jump and kill counts
+pub fn demand_index(ship_jumps: i64, ship_kills: i64, pod_kills: i64, npc_kills: i64) -> f64 {
+    ship_jumps as f64 * JUMP_WEIGHT
+        + ship_kills as f64 * SHIP_KILL_WEIGHT
+        + pod_kills as f64 * POD_KILL_WEIGHT
+        + npc_kills as f64 * NPC_KILL_WEIGHT
+}
+
+/// Merges kill and jump statistics into per-system activity
+pub(crate) fn system_activity(kills: &[SystemKills], jumps: &[SystemJumps]) -> HashMap<i32, SystemActivity> {
+    let mut activity: HashMap<i32, SystemActivity> = HashMap::new();
+    for k in kills {
+        let entry = activity.entry(k.system_id).or_default();
+        entry.ship_kills += k.ship_kills;
+        entry.pod_kills += k.pod_kills;
+        entry.npc_kills += k.npc_kills;
+    }
+    for j in jumps {
+        activity.entry(j.system_id).or_default().ship_jumps += j.ship_jumps;
+    }
+    for (system_id, entry) in activity.iter_mut() {
+        entry.system_id = *system_id;
+        entry.demand_index = demand_index(entry.ship_jumps, entry.ship_kills, entry.pod_kills, entry.npc_kills);
+    }
+    activity
+}
+
+/// Totals activity over a region's systems
+fn region_activity(
+    region_id: i32,
+    region_name: String,
+    systems: &[i32],
+    activity: &HashMap<i32, SystemActivity>,
+) -> RegionActivity {
+    let mut active: Vec<SystemActivity> = systems
+        .iter()
+        .filter_map(|id| activity.get(id))
+        .filter(|a| a.demand_index > 0.0)
+        .cloned()
+        .collect();
+    active.sort_by(|a, b| b.demand_index.partial_cmp(&a.demand_index).unwrap_or(std::cmp::Ordering::Equal));
+
+    let ship_jumps = active.iter().map(|a| a.ship_jumps).sum();
+    let ship_kills = active.iter().map(|a| a.ship_kills).sum();
+    let pod_kills = active.iter().map(|a| a.pod_kills).sum();
+    let npc_kills = active.iter().map(|a| a.npc_kills).sum();
+
+    RegionActivity {
+        region_id,
+        region_name,
+        system_count: systems.len(),
+        active_systems: active.len(),
+        ship_jumps,
+        ship_kills,
+        pod_kills,
+        npc_kills,
+        demand_index: demand_index(ship_jumps, ship_kills, pod_kills, npc_kills),
+        relative_score: 0.0,
+        top_systems: active.into_iter().take(TOP_SYSTEMS).collect(),
+    }
+}
+
+/// Sorts regions by demand and scores them relative to the most active one
+fn rank_regions(regions: &mut [RegionActivity]) {
+    regions.sort_by(|a, b| b.demand_index.partial_cmp(&a.demand_index).unwrap_or(std::cmp::Ordering::Equal));
+    let top = regions.first().map(|r| r.demand_index).unwrap_or(0.0);
+    for region in regions.iter_mut() {
+        region.relative_score = if top > 0.0 { region.demand_index / top * 100.0 } else { 0.0 };
+    }
+}
+
+/// Formats a regional activity comparison as a text report
+fn format_region_activity(regions: &[RegionActivity]) -> String {
+    let mut report = "Region Activity (last hour):\n".to_string();
+    for (rank, region) in regions.iter().enumerate() {
+        report.push_str(&format!(
+            "\n{}. {} ({}) - score {:.0}/100\n\
+            Demand index: {:.0}\n\
+            Jumps: {} | Ship kills: {} | Pod kills: {} | NPC kills: {}\n\
+            Active systems: {}/{}\n",
+            rank + 1,
+            region.region_name,
+            region.region_id,
+            region.relative_score,
+            region.demand_index,
+            region.ship_jumps,
+            region.ship_kills,
+            region.pod_kills,
+            region.npc_kills,
+            region.active_systems,
+            region.system_count,
+        ));
+        if !region.top_systems.is_empty() {
+            let top: Vec<String> = region
+                .top_systems
+                .iter()
+                .map(|s| format!("{} ({:.0})", s.system_id, s.demand_index))
+                .collect();
+            report.push_str(&format!("Busiest systems: {}\n", top.join(", ")));
+        }
+    }
+    report.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::TraderGraderError;
+
+    fn kills(system_id: i32, ship_kills: i64, npc_kills: i64) -> SystemKills {
+        SystemKills {
+            system_id,
+            ship_kills,
+            pod_kills: 0,
+            npc_kills,
+        }
+    }
+
+    fn jumps(system_id: i32, ship_jumps: i64) -> SystemJumps {
+        SystemJumps { system_id, ship_jumps }
+    }
+
+    #[test]
+    fn test_resolvable_ids() {
+        let ids = [60003760, 34, 60003760, 1035466617946, 0, 34, 30000142];
+        assert_eq!(resolvable_ids(&ids), vec![34, 30000142, 60003760]);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_names_without_ids() {
+        let client = MarketClient::without_cache();
+        let names = client.resolve_names(&[1035466617946]).await.unwrap();
+        assert!(names.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_structures_need_a_character() {
+        let client = MarketClient::without_cache();
+        let error = client.fetch_structure(1035466617946).await.unwrap_err();
+        assert!(matches!(error, TraderGraderError::AuthenticationError(_)));
+        assert!(client.resolve_location_names(&[1035466617946]).await.is_empty());
+    }
+
+    #[test]
+    fn test_demand_index_weights() {
+        assert_eq!(demand_index(100, 0, 0, 0), 100.0);
+        assert_eq!(demand_index(0, 1, 1, 4), SHIP_KILL_WEIGHT + POD_KILL_WEIGHT + 1.0);
+    }
+
+    #[test]
+    fn test_region_activity_ranking() {
+        let activity = system_activity(
+            &[kills(1, 2, 0), kills(3, 0, 400)],
+            &[jumps(1, 500), jumps(2, 50), jumps(3, 10)],
+        );
+        assert_eq!(activity[&1].demand_index, 520.0);
+
+        let mut regions = vec![
+            region_activity(20, "Quiet".to_string(), &[3, 4], &activity),
+            region_activity(10, "Busy".to_string(), &[1, 2], &activity),
+        ];
+        assert_eq!(regions[0].active_systems, 1);
+        assert_eq!(regions[0].system_count, 2);
+
+        rank_regions(&mut regions);
+        assert_eq!(regions[0].region_name, "Busy");
+        assert_eq!(regions[0].relative_score, 100.0);
+        assert_eq!(regions[0].top_systems[0].system_id, 1);
+        assert_eq!(regions[1].demand_index, 110.0);
+        assert!((regions[1].relative_score - 110.0 / 570.0 * 100.0).abs() < 1e-9);
+
+        let report = format_region_activity(&regions);
+        assert!(report.contains("1. Busy (10) - score 100/100"));
+        assert!(report.contains("Busiest systems: 1 (520), 2 (50)"));
+    }
+
+    #[test]
+    fn test_rank_regions_without_activity() {
+        let mut regions = vec![region_activity(1, "Empty".to_string(), &[1], &HashMap::new())];
+        rank_regions(&mut regions);
+        assert_eq!(regions[0].relative_score, 0.0);
+    }
+}
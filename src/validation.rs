@@ -0,0 +1,212 @@
+//! Tool argument validation for TraderGrader
+//!
+//! Tool handlers used to read missing or mistyped arguments as 0 and send the
+//! request to ESI anyway, which came back as an unhelpful upstream error. Every
+//! tool call is now checked against the tool's JSON Schema first: required
+//! fields, value types, `enum` choices and `minimum`/`maximum` bounds (which
+//! is how region, system and type ID ranges are expressed). The first problem
+//! found is reported with the offending field's name, before any network call.
+
+use crate::error::{Result, TraderGraderError};
+use serde_json::Value;
+
+/// Checks tool call arguments against the tool's input schema
+///
+/// Missing arguments are treated as an empty object. Optional fields may be
+/// absent or `null`; properties the schema doesn't mention are ignored.
+/// String `enum` values match case-insensitively, as the tools parse them.
+///
+/// # Examples
+///
+/// ```
+/// use serde_json::json;
+/// use tradergrader::validation::validate_arguments;
+///
+/// let schema = json!({
+///     "type": "object",
+///     "properties": {"type_id": {"type": "integer", "minimum": 1}},
+///     "required": ["type_id"]
+/// });
+///
+/// assert!(validate_arguments(&schema, Some(&json!({"type_id": 34}))).is_ok());
+///
+/// let error = validate_arguments(&schema, Some(&json!({"type_id": 0}))).unwrap_err();
+/// assert_eq!(error.to_string(), "type_id must be at least 1, got 0");
+///
+/// let error = validate_arguments(&schema, None).unwrap_err();
+/// assert_eq!(error.to_string(), "type_id is required");
+/// ```
+pub fn validate_arguments(schema: &Value, arguments: Option<&Value>) -> Result<()> {
+    let empty = Value::Object(Default::default());
+    let arguments = match arguments {
+        None | Some(Value::Null) => &empty,
+        Some(arguments) => arguments,
+    };
+    let Some(fields) = arguments.as_object() else {
+        return Err(invalid("arguments", format!("must be an object, got {}", describe(arguments))));
+    };
+
+    let required = schema["required"].as_array().into_iter().flatten().filter_map(Value::as_str);
+    for name in required {
+        if fields.get(name).is_none_or(Value::is_null) {
+            return Err(invalid(name, "is required".to_string()));
+        }
+    }
+
+    if let Some(properties) = schema["properties"].as_object() {
+        for (name, property) in properties {
+            if let Some(value) = fields.get(name).filter(|v| !v.is_null()) {
+                validate_value(name, property, value)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks one value against its property schema
+fn validate_value(field: &str, schema: &Value, value: &Value) -> Result<()> {
+    // `type` is a name or a list of names the value may match any of
+    let expected: Vec<&str> = match &schema["type"] {
+        Value::String(name) => vec![name.as_str()],
+        Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    let checks: Vec<(bool, &str)> = expected
+        .iter()
+        .map(|name| match *name {
+            "integer" => (value.is_i64() || value.is_u64(), "an integer"),
+            "number" => (value.is_number(), "a number"),
+            "string" => (value.is_string(), "a string"),
+            "boolean" => (value.is_boolean(), "a boolean"),
+            "array" => (value.is_array(), "an array"),
+            "object" => (value.is_object(), "an object"),
+            _ => (true, ""),
+        })
+        .collect();
+    if !checks.is_empty() && !checks.iter().any(|(matches, _)| *matches) {
+        let labels: Vec<&str> = checks.iter().map(|(_, label)| *label).collect();
+        return Err(invalid(field, format!("must be {}, got {}", labels.join(" or "), describe(value))));
+    }
+
+    if let Some(number) = value.as_f64() {
+        if let Some(minimum) = schema["minimum"].as_f64().filter(|min| number < *min) {
+            return Err(invalid(field, format!("must be at least {}, got {value}", bound(minimum))));
+        }
+        if let Some(maximum) = schema["maximum"].as_f64().filter(|max| number > *max) {
+            return Err(invalid(field, format!("must be at most {}, got {value}", bound(maximum))));
+        }
+        if let Some(minimum) = schema["exclusiveMinimum"].as_f64().filter(|min| number <= *min) {
+            return Err(invalid(field, format!("must be greater than {}, got {value}", bound(minimum))));
+        }
+    }
+
+    if let (Some(choices), Some(text)) = (schema["enum"].as_array(), value.as_str()) {
+        let choices: Vec<&str> = choices.iter().filter_map(Value::as_str).collect();
+        if !choices.iter().any(|choice| choice.eq_ignore_ascii_case(text)) {
+            return Err(invalid(field, format!("must be one of {}, got {}", choices.join(", "), describe(value))));
+        }
+    }
+
+    if let Some(values) = value.as_array() {
+        for (index, item) in values.iter().enumerate() {
+            validate_value(&format!("{field}[{index}]"), &schema["items"], item)?;
+        }
+    }
+    Ok(())
+}
+
+fn invalid(field: &str, reason: String) -> TraderGraderError {
+    TraderGraderError::InvalidArgument {
+        field: field.to_string(),
+        reason,
+    }
+}
+
+/// Schema bounds are whole numbers in practice; show them without a trailing `.0`
+fn bound(value: f64) -> String {
+    if value.fract() == 0.0 {
+        format!("{value:.0}")
+    } else {
+        value.to_string()
+    }
+}
+
+/// Short description of a rejected value for error messages
+fn describe(value: &Value) -> String {
+    let text = value.to_string();
+    if text.chars().count() > 40 {
+        format!("{}…", text.chars().take(40).collect::<String>())
+    } else {
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "region_id": {"type": "integer", "minimum": 10000001, "maximum": 14999999},
+                "type_ids": {"type": "array", "items": {"type": "integer", "minimum": 1}},
+                "sort_by": {"type": "string", "enum": ["volume", "spread_percent"]},
+                "cargo_capacity_m3": {"type": "number", "exclusiveMinimum": 0}
+            },
+            "required": ["region_id"]
+        })
+    }
+
+    fn error(arguments: Value) -> String {
+        validate_arguments(&schema(), Some(&arguments)).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test_valid_arguments() {
+        let arguments = json!({
+            "region_id": 10000002,
+            "type_ids": [34, 35],
+            "sort_by": "Volume",
+            "cargo_capacity_m3": 62500.5,
+            "unknown": "ignored"
+        });
+        assert!(validate_arguments(&schema(), Some(&arguments)).is_ok());
+        assert!(validate_arguments(&schema(), Some(&json!({"region_id": 10000002, "sort_by": null}))).is_ok());
+    }
+
+    #[test]
+    fn test_required_and_types() {
+        assert_eq!(error(json!({})), "region_id is required");
+        assert_eq!(error(json!({"region_id": null})), "region_id is required");
+        assert_eq!(error(json!({"region_id": "10000002"})), "region_id must be an integer, got \"10000002\"");
+        assert_eq!(error(json!({"region_id": 10000002.5})), "region_id must be an integer, got 10000002.5");
+        assert_eq!(error(json!([1, 2])), "arguments must be an object, got [1,2]");
+
+        let either = json!({"type": ["integer", "string"]});
+        assert!(validate_value("region_id", &either, &json!("jita")).is_ok());
+        assert_eq!(
+            validate_value("region_id", &either, &json!(true)).unwrap_err().to_string(),
+            "region_id must be an integer or a string, got true"
+        );
+    }
+
+    #[test]
+    fn test_ranges_enums_and_items() {
+        assert_eq!(error(json!({"region_id": 0})), "region_id must be at least 10000001, got 0");
+        assert_eq!(error(json!({"region_id": 30000142})), "region_id must be at most 14999999, got 30000142");
+        assert_eq!(
+            error(json!({"region_id": 10000002, "type_ids": [34, -1]})),
+            "type_ids[1] must be at least 1, got -1"
+        );
+        assert_eq!(
+            error(json!({"region_id": 10000002, "sort_by": "price"})),
+            "sort_by must be one of volume, spread_percent, got \"price\""
+        );
+        assert_eq!(
+            error(json!({"region_id": 10000002, "cargo_capacity_m3": 0})),
+            "cargo_capacity_m3 must be greater than 0, got 0"
+        );
+    }
+}
@@ -0,0 +1,377 @@
+//! Watchlist sharing for TraderGrader
+//!
+//! Corpmates swap curated item lists through in-game chat and Discord, where
+//! only a short line of text survives. A watchlist is exported as a share code,
+//! `TGW1.` followed by URL-safe base64 of compact JSON, and every code is
+//! validated on import so a truncated or tampered paste fails with a clear
+//! message instead of producing a half-empty list.
+//!
+//! The server also keeps its own watchlist, a [`WatchlistStore`] of items per
+//! region saved as JSON, which MCP clients read as resources.
+
+use crate::error::{Result, TraderGraderError};
+use crate::market::MarketClient;
+use crate::types::Watchlist;
+use crate::universe::REGION_ID_RANGE;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Prefix of every share code; the digit is the format version
+pub const WATCHLIST_CODE_PREFIX: &str = "TGW1.";
+
+/// Most items a shared watchlist may hold
+pub const MAX_WATCHLIST_ITEMS: usize = 500;
+
+/// Longest accepted watchlist name, in characters
+pub const MAX_WATCHLIST_NAME_LENGTH: usize = 100;
+
+/// Longest share code accepted on import, a little over what a full list needs
+const MAX_CODE_LENGTH: usize = 8 * 1024;
+
+/// Share code payload with short keys to keep codes compact
+#[derive(Deserialize, Serialize)]
+struct SharedWatchlist {
+    n: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    r: Option<i32>,
+    t: Vec<i32>,
+}
+
+impl Watchlist {
+    /// Creates a validated watchlist, dropping repeated type IDs
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::Watchlist;
+    ///
+    /// let list = Watchlist::new("Minerals", Some(10000002), vec![34, 35, 34])?;
+    /// assert_eq!(list.type_ids, vec![34, 35]);
+    /// assert!(Watchlist::new("Bad", None, vec![-1]).is_err());
+    /// # Ok::<(), tradergrader::TraderGraderError>(())
+    /// ```
+    pub fn new(name: &str, region_id: Option<i32>, type_ids: Vec<i32>) -> Result<Self> {
+        let mut unique = Vec::with_capacity(type_ids.len());
+        for type_id in type_ids {
+            if !unique.contains(&type_id) {
+                unique.push(type_id);
+            }
+        }
+        let watchlist = Self {
+            name: name.trim().to_string(),
+            region_id,
+            type_ids: unique,
+        };
+        watchlist.validate()?;
+        Ok(watchlist)
+    }
+
+    /// Checks the name, region and items are usable
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |message: String| Err(TraderGraderError::InvalidParams(message));
+        if self.name.trim().is_empty() {
+            return invalid("Watchlist name can't be empty".to_string());
+        }
+        if self.name.chars().count() > MAX_WATCHLIST_NAME_LENGTH {
+            return invalid(format!("Watchlist name is longer than {MAX_WATCHLIST_NAME_LENGTH} characters"));
+        }
+        if self.type_ids.is_empty() {
+            return invalid("Watchlist has no items".to_string());
+        }
+        if self.type_ids.len() > MAX_WATCHLIST_ITEMS {
+            return invalid(format!(
+                "Watchlist has {} items, more than the {MAX_WATCHLIST_ITEMS} allowed",
+                self.type_ids.len()
+            ));
+        }
+        if let Some(region_id) = self.region_id.filter(|id| !REGION_ID_RANGE.contains(&(*id as i64))) {
+            return Err(TraderGraderError::InvalidRegionId { region_id });
+        }
+        if let Some(&type_id) = self.type_ids.iter().find(|id| **id <= 0) {
+            return Err(TraderGraderError::InvalidTypeId { type_id });
+        }
+        Ok(())
+    }
+
+    /// Encodes the watchlist as a share code
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::Watchlist;
+    ///
+    /// let list = Watchlist::new("Minerals", None, vec![34, 35, 36])?;
+    /// let code = list.export_code()?;
+    /// assert!(code.starts_with("TGW1."));
+    /// assert_eq!(Watchlist::from_code(&code)?, list);
+    /// # Ok::<(), tradergrader::TraderGraderError>(())
+    /// ```
+    pub fn export_code(&self) -> Result<String> {
+        self.validate()?;
+        let shared = SharedWatchlist {
+            n: self.name.clone(),
+            r: self.region_id,
+            t: self.type_ids.clone(),
+        };
+        let json = serde_json::to_vec(&shared)?;
+        Ok(format!("{WATCHLIST_CODE_PREFIX}{}", URL_SAFE_NO_PAD.encode(json)))
+    }
+
+    /// Decodes and validates a share code
+    ///
+    /// Whitespace around the code (and line breaks chat clients insert inside
+    /// it) is ignored.
+    pub fn from_code(code: &str) -> Result<Self> {
+        let code: String = code.split_whitespace().collect();
+        if code.len() > MAX_CODE_LENGTH {
+            return Err(TraderGraderError::InvalidParams("Watchlist code is too long".to_string()));
+        }
+        let payload = code.strip_prefix(WATCHLIST_CODE_PREFIX).ok_or_else(|| {
+            TraderGraderError::InvalidParams(format!(
+                "Not a watchlist code: expected it to start with {WATCHLIST_CODE_PREFIX}"
+            ))
+        })?;
+        let json = URL_SAFE_NO_PAD.decode(payload).map_err(|e| {
+            TraderGraderError::InvalidParams(format!("Watchlist code is damaged or incomplete: {e}"))
+        })?;
+        let shared: SharedWatchlist = serde_json::from_slice(&json).map_err(|e| {
+            TraderGraderError::InvalidParams(format!("Watchlist code is damaged or incomplete: {e}"))
+        })?;
+        Self::new(&shared.n, shared.r, shared.t)
+    }
+}
+
+/// The server's watchlist: item type IDs per region, optionally saved to a JSON file
+///
+/// Every change is written straight to the file, so the list survives restarts.
+#[derive(Debug, Default)]
+pub struct WatchlistStore {
+    path: Option<PathBuf>,
+    regions: Mutex<BTreeMap<i32, Vec<i32>>>,
+}
+
+/// File layout of a saved watchlist
+#[derive(Deserialize, Serialize)]
+struct SavedWatchlist {
+    regions: BTreeMap<i32, Vec<i32>>,
+}
+
+impl WatchlistStore {
+    /// A watchlist that lives only as long as the process
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Opens the watchlist saved at `path`, starting empty if the file doesn't exist yet
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let regions = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice::<SavedWatchlist>(&bytes)?.regions,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => {
+                return Err(TraderGraderError::InternalError(format!(
+                    "Failed to read watchlist {}: {e}",
+                    path.display()
+                )))
+            }
+        };
+        Ok(Self {
+            path: Some(path),
+            regions: Mutex::new(regions),
+        })
+    }
+
+    /// Where the watchlist is saved, if anywhere
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Item type IDs per region, in the order they were added
+    pub fn regions(&self) -> BTreeMap<i32, Vec<i32>> {
+        self.regions.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Every watched region and item pair
+    pub fn entries(&self) -> Vec<(i32, i32)> {
+        let regions = self.regions.lock().unwrap_or_else(|e| e.into_inner());
+        regions
+            .iter()
+            .flat_map(|(&region_id, type_ids)| type_ids.iter().map(move |&type_id| (region_id, type_id)))
+            .collect()
+    }
+
+    /// Adds items to a region's list, returning how many weren't already on it
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tradergrader::watchlist::WatchlistStore;
+    ///
+    /// let store = WatchlistStore::in_memory();
+    /// assert_eq!(store.add(10000002, &[34, 35])?, 2);
+    /// assert_eq!(store.add(10000002, &[35, 36])?, 1);
+    /// assert_eq!(store.remove(10000002, &[34])?, 1);
+    /// assert_eq!(store.entries(), vec![(10000002, 35), (10000002, 36)]);
+    /// # Ok::<(), tradergrader::TraderGraderError>(())
+    /// ```
+    pub fn add(&self, region_id: i32, type_ids: &[i32]) -> Result<usize> {
+        if !REGION_ID_RANGE.contains(&(region_id as i64)) {
+            return Err(TraderGraderError::InvalidRegionId { region_id });
+        }
+        if let Some(&type_id) = type_ids.iter().find(|id| **id <= 0) {
+            return Err(TraderGraderError::InvalidTypeId { type_id });
+        }
+        let mut regions = self.regions.lock().unwrap_or_else(|e| e.into_inner());
+        let mut updated = regions.clone();
+        let list = updated.entry(region_id).or_default();
+        let before = list.len();
+        for &type_id in type_ids {
+            if !list.contains(&type_id) {
+                list.push(type_id);
+            }
+        }
+        let added = list.len() - before;
+        let total: usize = updated.values().map(Vec::len).sum();
+        if total > MAX_WATCHLIST_ITEMS {
+            return Err(TraderGraderError::InvalidParams(format!(
+                "The watchlist can hold at most {MAX_WATCHLIST_ITEMS} items"
+            )));
+        }
+        self.save(&updated)?;
+        *regions = updated;
+        Ok(added)
+    }
+
+    /// Removes items from a region's list, returning how many were on it
+    pub fn remove(&self, region_id: i32, type_ids: &[i32]) -> Result<usize> {
+        let mut regions = self.regions.lock().unwrap_or_else(|e| e.into_inner());
+        let mut updated = regions.clone();
+        let Some(list) = updated.get_mut(&region_id) else {
+            return Ok(0);
+        };
+        let before = list.len();
+        list.retain(|type_id| !type_ids.contains(type_id));
+        let removed = before - list.len();
+        if list.is_empty() {
+            updated.remove(&region_id);
+        }
+        if removed > 0 {
+            self.save(&updated)?;
+            *regions = updated;
+        }
+        Ok(removed)
+    }
+
+    /// Writes the list to a temporary file and renames it over the old one
+    fn save(&self, regions: &BTreeMap<i32, Vec<i32>>) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let saved = SavedWatchlist {
+            regions: regions.clone(),
+        };
+        let write_error = |e: std::io::Error| {
+            TraderGraderError::InternalError(format!("Failed to save watchlist {}: {e}", path.display()))
+        };
+        let temp = path.with_extension("json.tmp");
+        std::fs::write(&temp, serde_json::to_vec_pretty(&saved)?).map_err(write_error)?;
+        std::fs::rename(&temp, path).map_err(write_error)
+    }
+}
+
+impl MarketClient {
+    /// Lists a watchlist's items by name, for showing an imported list
+    ///
+    /// Names come from one bulk lookup; items that can't be named are shown by ID.
+    pub async fn watchlist_summary(&self, watchlist: &Watchlist) -> String {
+        let mut ids: Vec<i64> = watchlist.type_ids.iter().map(|id| *id as i64).collect();
+        ids.extend(watchlist.region_id.map(i64::from));
+        let names = self.resolve_names(&ids).await.unwrap_or_default();
+        let name = |id: i64| names.get(&id).map(|n| n.name.clone());
+
+        let mut report = format!("Watchlist \"{}\" ({} items)", watchlist.name, watchlist.type_ids.len());
+        if let Some(region_id) = watchlist.region_id {
+            let region = name(region_id as i64).unwrap_or_else(|| "Unknown region".to_string());
+            report.push_str(&format!(" for {region} ({region_id})"));
+        }
+        report.push_str(":\n");
+        for &type_id in &watchlist.type_ids {
+            match name(type_id as i64) {
+                Some(item) => report.push_str(&format!("- {item} ({type_id})\n")),
+                None => report.push_str(&format!("- Type {type_id}\n")),
+            }
+        }
+        let ids: Vec<String> = watchlist.type_ids.iter().map(i32::to_string).collect();
+        report.push_str(&format!("Type IDs: {}", ids.join(", ")));
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let list = Watchlist::new("  Ice products  ", Some(10000002), vec![16272, 16273, 16272]).unwrap();
+        assert_eq!(list.name, "Ice products");
+        assert_eq!(list.type_ids, vec![16272, 16273]);
+
+        let code = list.export_code().unwrap();
+        assert!(!code.contains('='));
+        let chat_mangled = format!(" {}\n{} ", &code[..10], &code[10..]);
+        assert_eq!(Watchlist::from_code(&chat_mangled).unwrap(), list);
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(Watchlist::new("", None, vec![34]).is_err());
+        assert!(Watchlist::new("Empty", None, vec![]).is_err());
+        assert!(Watchlist::new(&"x".repeat(101), None, vec![34]).is_err());
+        assert!(Watchlist::new("Too many", None, (1..=501).collect()).is_err());
+        assert!(matches!(
+            Watchlist::new("Region", Some(42), vec![34]),
+            Err(TraderGraderError::InvalidRegionId { region_id: 42 })
+        ));
+        assert!(matches!(
+            Watchlist::new("Type", None, vec![34, 0]),
+            Err(TraderGraderError::InvalidTypeId { type_id: 0 })
+        ));
+    }
+
+    #[test]
+    fn test_store_persists() {
+        let path = std::env::temp_dir().join(format!("tradergrader-watchlist-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let store = WatchlistStore::open(&path).unwrap();
+        assert!(store.entries().is_empty());
+        assert_eq!(store.add(10000002, &[34, 35]).unwrap(), 2);
+        assert_eq!(store.add(10000043, &[34]).unwrap(), 1);
+        assert!(store.add(42, &[34]).is_err());
+        assert_eq!(store.remove(10000043, &[34]).unwrap(), 1);
+
+        let reopened = WatchlistStore::open(&path).unwrap();
+        assert_eq!(reopened.regions(), BTreeMap::from([(10000002, vec![34, 35])]));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_rejects_bad_codes() {
+        let code = Watchlist::new("Minerals", None, vec![34]).unwrap().export_code().unwrap();
+        assert!(Watchlist::from_code(&code[..code.len() - 4]).is_err());
+        assert!(Watchlist::from_code(&code.replace("TGW1.", "TGW9.")).is_err());
+        assert!(Watchlist::from_code("TGW1.!!!").is_err());
+
+        // Well-formed codes still have their contents validated
+        let forged = format!("{WATCHLIST_CODE_PREFIX}{}", URL_SAFE_NO_PAD.encode(r#"{"n":"x","t":[-5]}"#));
+        assert!(matches!(
+            Watchlist::from_code(&forged),
+            Err(TraderGraderError::InvalidTypeId { type_id: -5 })
+        ));
+    }
+}
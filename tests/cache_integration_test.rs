@@ -4,7 +4,7 @@
 //! TTL expiration, and ESI header respect.
 
 use std::time::Duration;
-use tradergrader::{CacheConfig, MarketClient, CacheBackend, CacheBackendExt};
+use tradergrader::{CacheConfig, MarketClient, CacheBackend};
 
 #[tokio::test]
 async fn test_cache_integration_workflow() {
@@ -67,7 +67,11 @@ async fn test_price_analysis() {
     
     let analysis = result.unwrap();
     assert!(analysis.current_price > 0.0, "Current price should be positive");
-    assert!(!analysis.trend.is_empty(), "Trend should not be empty");
+    assert_eq!(
+        analysis.trend.direction() == tradergrader::TrendDirection::Flat,
+        analysis.trend_evidence.price_change_percent.abs() < tradergrader::TrendConfig::default().change_percent,
+        "Trend should follow the volume-weighted change"
+    );
     
     // Test the summary format
     let summary_result = app.get_price_history_summary(10000002, 44992).await;